@@ -1,14 +1,634 @@
-use super::generated::cs_arm64;
+use super::generated::{arm64_op_mem, cs_arm64, cs_arm64_op};
 use core::marker::PhantomData;
 
 #[repr(transparent)]
 #[derive(Clone, Copy)]
 pub struct Details<'c> {
-    #[allow(dead_code)]
     inner: cs_arm64,
     _phantom: PhantomData<&'c ()>,
 }
 
+impl<'c> Details<'c> {
+    /// Returns the condition code of the instruction, or [`Cc::Invalid`] if it isn't
+    /// conditional.
+    pub fn cc(&self) -> Cc {
+        Cc::from_c(self.inner.cc).unwrap_or(Cc::Invalid)
+    }
+
+    /// Returns true if the instruction updates the condition flags (e.g. `adds`).
+    pub fn update_flags(&self) -> bool {
+        self.inner.update_flags
+    }
+
+    /// Returns true if the instruction writes the address back to the base register
+    /// (pre-indexed and post-indexed addressing).
+    pub fn writeback(&self) -> bool {
+        self.inner.writeback
+    }
+
+    /// Returns the number of operands in this instruction, or
+    /// zero when this instruction has no operands. This value will
+    /// be the same as the length of the slice returned by [`Details::operands`].
+    pub fn op_count(&self) -> usize {
+        self.inner.op_count as usize
+    }
+
+    /// Returns the operands contained in this instruction. The length
+    /// of the returned slice will be the same as the value returned
+    /// by [`Details::op_count`].
+    pub fn operands(&self) -> &[Op] {
+        unsafe {
+            &*(&self.inner.operands[..self.inner.op_count as usize] as *const [cs_arm64_op]
+                as *const [Op])
+        }
+    }
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy)]
+pub struct Op {
+    inner: cs_arm64_op,
+}
+
+impl Op {
+    /// Returns the type of this operand.
+    pub fn op_type(&self) -> OpType {
+        OpType::from_c(self.inner.type_).unwrap_or(OpType::Invalid)
+    }
+
+    /// Returns the value of this operand.
+    pub fn value(&self) -> OpValue {
+        let value = &self.inner.__bindgen_anon_1;
+        match self.op_type() {
+            OpType::Invalid => OpValue::Imm(0),
+            OpType::Reg => OpValue::Reg(Reg::from_c(unsafe { value.reg }).unwrap_or(Reg::Invalid)),
+            OpType::Imm => OpValue::Imm(unsafe { value.imm }),
+            OpType::Mem => OpValue::Mem(OpMem {
+                inner: unsafe { value.mem },
+            }),
+            OpType::Fp => OpValue::Fp(unsafe { value.fp }),
+            OpType::Cimm => OpValue::Cimm(unsafe { value.imm }),
+            OpType::RegMrs => OpValue::RegMrs(unsafe { value.reg }),
+            OpType::RegMsr => OpValue::RegMsr(unsafe { value.reg }),
+            OpType::Pstate => {
+                OpValue::Pstate(Pstate::from_c(unsafe { value.pstate }).unwrap_or(Pstate::Invalid))
+            }
+            OpType::Sys => OpValue::Sys(unsafe { value.sys }),
+            OpType::Prefetch => OpValue::Prefetch(
+                Prefetch::from_c(unsafe { value.prefetch }).unwrap_or(Prefetch::Invalid),
+            ),
+            OpType::Barrier => OpValue::Barrier(
+                Barrier::from_c(unsafe { value.barrier }).unwrap_or(Barrier::Invalid),
+            ),
+        }
+    }
+
+    /// Returns the index of the vector element that is accessed (e.g. `v1.s[2]`), if
+    /// any.
+    pub fn vector_index(&self) -> Option<u32> {
+        if self.inner.vector_index >= 0 {
+            Some(self.inner.vector_index as u32)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the arrangement of a vector register (e.g. `4s` in `v0.4s`), or
+    /// [`Vas::Invalid`] if this isn't a vector register.
+    pub fn vas(&self) -> Vas {
+        Vas::from_c(self.inner.vas).unwrap_or(Vas::Invalid)
+    }
+
+    /// Returns the size of the vector element that is accessed (e.g. `s` in `v1.s[2]`),
+    /// or [`Vess::Invalid`] if no element is accessed.
+    pub fn vess(&self) -> Vess {
+        Vess::from_c(self.inner.vess).unwrap_or(Vess::Invalid)
+    }
+
+    /// Returns the shift that is applied to this operand and its amount, if any.
+    pub fn shift(&self) -> Option<(Shifter, u32)> {
+        match Shifter::from_c(self.inner.shift.type_) {
+            Some(Shifter::Invalid) | None => None,
+            Some(shifter) => Some((shifter, self.inner.shift.value)),
+        }
+    }
+
+    /// Returns the extender that is applied to this operand, or [`Extender::Invalid`]
+    /// if there is none.
+    pub fn ext(&self) -> Extender {
+        Extender::from_c(self.inner.ext).unwrap_or(Extender::Invalid)
+    }
+
+    /// Returns how this operand was accessed.
+    pub fn access(&self) -> super::Access {
+        super::Access::from_bits_truncate(self.inner.access)
+    }
+}
+
+pub enum OpValue {
+    Reg(Reg),
+    Imm(i64),
+    Mem(OpMem),
+    Fp(f64),
+    /// An immediate for the `c<n>` operands of `sys` instructions.
+    Cimm(i64),
+    /// The system register that is read by `mrs`.
+    RegMrs(u32),
+    /// The system register that is written by `msr`.
+    RegMsr(u32),
+    Pstate(Pstate),
+    /// The operation of a `sys` instruction, e.g. `ic`, `dc`, or `tlbi`.
+    Sys(u32),
+    Prefetch(Prefetch),
+    Barrier(Barrier),
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct OpMem {
+    inner: arm64_op_mem,
+}
+
+impl OpMem {
+    /// Returns the base register.
+    pub fn base(&self) -> Reg {
+        Reg::from_c(self.inner.base).unwrap_or(Reg::Invalid)
+    }
+
+    /// Returns the index register.
+    pub fn index(&self) -> Reg {
+        Reg::from_c(self.inner.index).unwrap_or(Reg::Invalid)
+    }
+
+    /// Returns the displacement value.
+    pub fn disp(&self) -> i32 {
+        self.inner.disp
+    }
+}
+
+c_enum! {
+    /// Operand type for an ARM64 instruction's operands.
+    #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+    pub enum OpType: u8 {
+        /// Uninitialized.
+        Invalid = 0,
+        /// Register operand.
+        Reg,
+        /// Immediate operand.
+        Imm,
+        /// Memory operand.
+        Mem,
+        /// Floating point operand.
+        Fp,
+        /// C-Immediate operand.
+        Cimm = 64,
+        /// System register operand of `mrs`.
+        RegMrs,
+        /// System register operand of `msr`.
+        RegMsr,
+        /// PSTATE field operand.
+        Pstate,
+        /// Operation of a `sys` instruction.
+        Sys,
+        /// Prefetch operation operand.
+        Prefetch,
+        /// Memory barrier operand.
+        Barrier,
+    }
+}
+
+c_enum! {
+    /// Shifts of operands.
+    #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+    pub enum Shifter: u8 {
+        /// No shift.
+        Invalid = 0,
+        Lsl,
+        Msl,
+        Lsr,
+        Asr,
+        Ror,
+    }
+}
+
+c_enum! {
+    /// Extenders of register operands.
+    #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+    pub enum Extender: u8 {
+        /// No extender.
+        Invalid = 0,
+        Uxtb,
+        Uxth,
+        Uxtw,
+        Uxtx,
+        Sxtb,
+        Sxth,
+        Sxtw,
+        Sxtx,
+    }
+}
+
+c_enum! {
+    /// Condition codes.
+    #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+    pub enum Cc: u8 {
+        /// Not conditional.
+        Invalid = 0,
+        Eq,
+        Ne,
+        Hs,
+        Lo,
+        Mi,
+        Pl,
+        Vs,
+        Vc,
+        Hi,
+        Ls,
+        Ge,
+        Lt,
+        Gt,
+        Le,
+        Al,
+        Nv,
+    }
+}
+
+c_enum! {
+    /// Arrangements of vector registers.
+    #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+    pub enum Vas: u8 {
+        /// Not a vector register.
+        Invalid = 0,
+        _8b,
+        _16b,
+        _4h,
+        _8h,
+        _2s,
+        _4s,
+        _1d,
+        _2d,
+        _1q,
+    }
+}
+
+c_enum! {
+    /// Sizes of vector elements.
+    #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+    pub enum Vess: u8 {
+        /// No vector element.
+        Invalid = 0,
+        B,
+        H,
+        S,
+        D,
+    }
+}
+
+c_enum! {
+    /// Fields of PSTATE that can be written with `msr`.
+    #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+    pub enum Pstate: u8 {
+        /// Uninitialized.
+        Invalid = 0,
+        Spsel = 5,
+        Daifset = 30,
+        Daifclr = 31,
+    }
+}
+
+c_enum! {
+    /// Operations of `prfm`.
+    #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+    pub enum Prefetch: u8 {
+        /// Uninitialized.
+        Invalid = 0,
+        Pldl1keep = 1,
+        Pldl1strm = 2,
+        Pldl2keep = 3,
+        Pldl2strm = 4,
+        Pldl3keep = 5,
+        Pldl3strm = 6,
+        Plil1keep = 9,
+        Plil1strm = 10,
+        Plil2keep = 11,
+        Plil2strm = 12,
+        Plil3keep = 13,
+        Plil3strm = 14,
+        Pstl1keep = 17,
+        Pstl1strm = 18,
+        Pstl2keep = 19,
+        Pstl2strm = 20,
+        Pstl3keep = 21,
+        Pstl3strm = 22,
+    }
+}
+
+c_enum! {
+    /// Options of memory barriers (`dmb`, `dsb`).
+    #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+    pub enum Barrier: u8 {
+        /// Uninitialized.
+        Invalid = 0,
+        Oshld = 1,
+        Oshst = 2,
+        Osh = 3,
+        Nshld = 5,
+        Nshst = 6,
+        Nsh = 7,
+        Ishld = 9,
+        Ishst = 10,
+        Ish = 11,
+        Ld = 13,
+        St = 14,
+        Sy = 15,
+    }
+}
+
+c_enum_big! {
+    #[non_exhaustive]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    pub enum Reg: u16 {
+        @Start = Invalid,
+        @End   = Ending,
+
+        Invalid = 0,
+        X29,
+        X30,
+        Nzcv,
+        Sp,
+        Wsp,
+        Wzr,
+        Xzr,
+        B0,
+        B1,
+        B2,
+        B3,
+        B4,
+        B5,
+        B6,
+        B7,
+        B8,
+        B9,
+        B10,
+        B11,
+        B12,
+        B13,
+        B14,
+        B15,
+        B16,
+        B17,
+        B18,
+        B19,
+        B20,
+        B21,
+        B22,
+        B23,
+        B24,
+        B25,
+        B26,
+        B27,
+        B28,
+        B29,
+        B30,
+        B31,
+        D0,
+        D1,
+        D2,
+        D3,
+        D4,
+        D5,
+        D6,
+        D7,
+        D8,
+        D9,
+        D10,
+        D11,
+        D12,
+        D13,
+        D14,
+        D15,
+        D16,
+        D17,
+        D18,
+        D19,
+        D20,
+        D21,
+        D22,
+        D23,
+        D24,
+        D25,
+        D26,
+        D27,
+        D28,
+        D29,
+        D30,
+        D31,
+        H0,
+        H1,
+        H2,
+        H3,
+        H4,
+        H5,
+        H6,
+        H7,
+        H8,
+        H9,
+        H10,
+        H11,
+        H12,
+        H13,
+        H14,
+        H15,
+        H16,
+        H17,
+        H18,
+        H19,
+        H20,
+        H21,
+        H22,
+        H23,
+        H24,
+        H25,
+        H26,
+        H27,
+        H28,
+        H29,
+        H30,
+        H31,
+        Q0,
+        Q1,
+        Q2,
+        Q3,
+        Q4,
+        Q5,
+        Q6,
+        Q7,
+        Q8,
+        Q9,
+        Q10,
+        Q11,
+        Q12,
+        Q13,
+        Q14,
+        Q15,
+        Q16,
+        Q17,
+        Q18,
+        Q19,
+        Q20,
+        Q21,
+        Q22,
+        Q23,
+        Q24,
+        Q25,
+        Q26,
+        Q27,
+        Q28,
+        Q29,
+        Q30,
+        Q31,
+        S0,
+        S1,
+        S2,
+        S3,
+        S4,
+        S5,
+        S6,
+        S7,
+        S8,
+        S9,
+        S10,
+        S11,
+        S12,
+        S13,
+        S14,
+        S15,
+        S16,
+        S17,
+        S18,
+        S19,
+        S20,
+        S21,
+        S22,
+        S23,
+        S24,
+        S25,
+        S26,
+        S27,
+        S28,
+        S29,
+        S30,
+        S31,
+        W0,
+        W1,
+        W2,
+        W3,
+        W4,
+        W5,
+        W6,
+        W7,
+        W8,
+        W9,
+        W10,
+        W11,
+        W12,
+        W13,
+        W14,
+        W15,
+        W16,
+        W17,
+        W18,
+        W19,
+        W20,
+        W21,
+        W22,
+        W23,
+        W24,
+        W25,
+        W26,
+        W27,
+        W28,
+        W29,
+        W30,
+        X0,
+        X1,
+        X2,
+        X3,
+        X4,
+        X5,
+        X6,
+        X7,
+        X8,
+        X9,
+        X10,
+        X11,
+        X12,
+        X13,
+        X14,
+        X15,
+        X16,
+        X17,
+        X18,
+        X19,
+        X20,
+        X21,
+        X22,
+        X23,
+        X24,
+        X25,
+        X26,
+        X27,
+        X28,
+        V0,
+        V1,
+        V2,
+        V3,
+        V4,
+        V5,
+        V6,
+        V7,
+        V8,
+        V9,
+        V10,
+        V11,
+        V12,
+        V13,
+        V14,
+        V15,
+        V16,
+        V17,
+        V18,
+        V19,
+        V20,
+        V21,
+        V22,
+        V23,
+        V24,
+        V25,
+        V26,
+        V27,
+        V28,
+        V29,
+        V30,
+        V31,
+
+        #[doc(hidden)]
+        Ending,
+    }
+}
+
+#[allow(non_upper_case_globals)]
+impl Reg {
+    /// The first intra-procedure-call scratch register.
+    pub const Ip0: Reg = Reg::X16;
+    /// The second intra-procedure-call scratch register.
+    pub const Ip1: Reg = Reg::X17;
+    /// The frame pointer.
+    pub const Fp: Reg = Reg::X29;
+    /// The link register.
+    pub const Lr: Reg = Reg::X30;
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -26,4 +646,12 @@ mod test {
             sys::get_test_val("alignof(cs_arm64)")
         );
     }
+
+    #[test]
+    fn arm64_enum_size() {
+        assert_eq!(
+            Reg::Ending.to_c(),
+            sys::get_test_val("ARM64_REG_ENDING") as _
+        );
+    }
 }
@@ -1,14 +1,336 @@
-use super::generated::cs_mips;
+use super::generated::{cs_mips, cs_mips_op, mips_op_mem};
 use core::marker::PhantomData;
 
 #[repr(transparent)]
 #[derive(Clone, Copy)]
 pub struct Details<'c> {
-    #[allow(dead_code)]
     inner: cs_mips,
     _phantom: PhantomData<&'c ()>,
 }
 
+impl<'c> Details<'c> {
+    /// Returns the number of operands in this instruction, or
+    /// zero when this instruction has no operands. This value will
+    /// be the same as the length of the slice returned by [`Details::operands`].
+    pub fn op_count(&self) -> usize {
+        self.inner.op_count as usize
+    }
+
+    /// Returns the operands contained in this instruction. The length
+    /// of the returned slice will be the same as the value returned
+    /// by [`Details::op_count`].
+    pub fn operands(&self) -> &[Op] {
+        unsafe {
+            &*(&self.inner.operands[..self.inner.op_count as usize] as *const [cs_mips_op]
+                as *const [Op])
+        }
+    }
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy)]
+pub struct Op {
+    inner: cs_mips_op,
+}
+
+impl Op {
+    /// Returns the type of this operand.
+    pub fn op_type(&self) -> OpType {
+        OpType::from_c(self.inner.type_).unwrap_or(OpType::Invalid)
+    }
+
+    /// Returns the value of this operand.
+    pub fn value(&self) -> OpValue {
+        match self.op_type() {
+            OpType::Invalid => OpValue::Imm(0),
+            OpType::Reg => OpValue::Reg(
+                Reg::from_c(unsafe { self.inner.__bindgen_anon_1.reg }).unwrap_or(Reg::Invalid),
+            ),
+            OpType::Imm => OpValue::Imm(unsafe { self.inner.__bindgen_anon_1.imm }),
+            OpType::Mem => OpValue::Mem(unsafe {
+                OpMem {
+                    inner: self.inner.__bindgen_anon_1.mem,
+                }
+            }),
+        }
+    }
+}
+
+pub enum OpValue {
+    Reg(Reg),
+    Imm(i64),
+    Mem(OpMem),
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct OpMem {
+    inner: mips_op_mem,
+}
+
+impl OpMem {
+    /// Returns the base register.
+    pub fn base(&self) -> Reg {
+        Reg::from_c(self.inner.base).unwrap_or(Reg::Invalid)
+    }
+
+    /// Returns the displacement value.
+    pub fn disp(&self) -> i64 {
+        self.inner.disp
+    }
+}
+
+c_enum! {
+    /// Operand type for a MIPS instruction's operands.
+    #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+    pub enum OpType: u8 {
+        /// Uninitialized.
+        Invalid = 0,
+        /// Register operand.
+        Reg,
+        /// Immediate operand.
+        Imm,
+        /// Memory operand.
+        Mem,
+    }
+}
+
+c_enum_big! {
+    #[non_exhaustive]
+    #[derive(Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum Reg: u8 {
+        @Start = Invalid,
+        @End   = Ending,
+
+        Invalid = 0,
+        Pc,
+        R0,
+        R1,
+        R2,
+        R3,
+        R4,
+        R5,
+        R6,
+        R7,
+        R8,
+        R9,
+        R10,
+        R11,
+        R12,
+        R13,
+        R14,
+        R15,
+        R16,
+        R17,
+        R18,
+        R19,
+        R20,
+        R21,
+        R22,
+        R23,
+        R24,
+        R25,
+        R26,
+        R27,
+        R28,
+        R29,
+        R30,
+        R31,
+        Dspccond,
+        Dspcarry,
+        Dspefi,
+        Dspoutflag,
+        Dspoutflag16to19,
+        Dspoutflag20,
+        Dspoutflag21,
+        Dspoutflag22,
+        Dspoutflag23,
+        Dsppos,
+        Dspscount,
+        Ac0,
+        Ac1,
+        Ac2,
+        Ac3,
+        Cc0,
+        Cc1,
+        Cc2,
+        Cc3,
+        Cc4,
+        Cc5,
+        Cc6,
+        Cc7,
+        F0,
+        F1,
+        F2,
+        F3,
+        F4,
+        F5,
+        F6,
+        F7,
+        F8,
+        F9,
+        F10,
+        F11,
+        F12,
+        F13,
+        F14,
+        F15,
+        F16,
+        F17,
+        F18,
+        F19,
+        F20,
+        F21,
+        F22,
+        F23,
+        F24,
+        F25,
+        F26,
+        F27,
+        F28,
+        F29,
+        F30,
+        F31,
+        Fcc0,
+        Fcc1,
+        Fcc2,
+        Fcc3,
+        Fcc4,
+        Fcc5,
+        Fcc6,
+        Fcc7,
+        W0,
+        W1,
+        W2,
+        W3,
+        W4,
+        W5,
+        W6,
+        W7,
+        W8,
+        W9,
+        W10,
+        W11,
+        W12,
+        W13,
+        W14,
+        W15,
+        W16,
+        W17,
+        W18,
+        W19,
+        W20,
+        W21,
+        W22,
+        W23,
+        W24,
+        W25,
+        W26,
+        W27,
+        W28,
+        W29,
+        W30,
+        W31,
+        Hi,
+        Lo,
+        P0,
+        P1,
+        P2,
+        Mpl0,
+        Mpl1,
+        Mpl2,
+
+        #[doc(hidden)]
+        Ending,
+    }
+}
+
+#[allow(non_upper_case_globals)]
+impl Reg {
+    /// ABI name of `$0`.
+    pub const Zero: Reg = Reg::R0;
+    /// ABI name of `$1`.
+    pub const At: Reg = Reg::R1;
+    /// ABI name of `$2`.
+    pub const V0: Reg = Reg::R2;
+    /// ABI name of `$3`.
+    pub const V1: Reg = Reg::R3;
+    /// ABI name of `$4`.
+    pub const A0: Reg = Reg::R4;
+    /// ABI name of `$5`.
+    pub const A1: Reg = Reg::R5;
+    /// ABI name of `$6`.
+    pub const A2: Reg = Reg::R6;
+    /// ABI name of `$7`.
+    pub const A3: Reg = Reg::R7;
+    /// ABI name of `$8`.
+    pub const T0: Reg = Reg::R8;
+    /// ABI name of `$9`.
+    pub const T1: Reg = Reg::R9;
+    /// ABI name of `$10`.
+    pub const T2: Reg = Reg::R10;
+    /// ABI name of `$11`.
+    pub const T3: Reg = Reg::R11;
+    /// ABI name of `$12`.
+    pub const T4: Reg = Reg::R12;
+    /// ABI name of `$13`.
+    pub const T5: Reg = Reg::R13;
+    /// ABI name of `$14`.
+    pub const T6: Reg = Reg::R14;
+    /// ABI name of `$15`.
+    pub const T7: Reg = Reg::R15;
+    /// ABI name of `$16`.
+    pub const S0: Reg = Reg::R16;
+    /// ABI name of `$17`.
+    pub const S1: Reg = Reg::R17;
+    /// ABI name of `$18`.
+    pub const S2: Reg = Reg::R18;
+    /// ABI name of `$19`.
+    pub const S3: Reg = Reg::R19;
+    /// ABI name of `$20`.
+    pub const S4: Reg = Reg::R20;
+    /// ABI name of `$21`.
+    pub const S5: Reg = Reg::R21;
+    /// ABI name of `$22`.
+    pub const S6: Reg = Reg::R22;
+    /// ABI name of `$23`.
+    pub const S7: Reg = Reg::R23;
+    /// ABI name of `$24`.
+    pub const T8: Reg = Reg::R24;
+    /// ABI name of `$25`.
+    pub const T9: Reg = Reg::R25;
+    /// ABI name of `$26`.
+    pub const K0: Reg = Reg::R26;
+    /// ABI name of `$27`.
+    pub const K1: Reg = Reg::R27;
+    /// ABI name of `$28`.
+    pub const Gp: Reg = Reg::R28;
+    /// ABI name of `$29`.
+    pub const Sp: Reg = Reg::R29;
+    /// ABI name of `$30`.
+    pub const Fp: Reg = Reg::R30;
+    /// ABI name of `$30`.
+    pub const S8: Reg = Reg::R30;
+    /// ABI name of `$31`.
+    pub const Ra: Reg = Reg::R31;
+    /// `$hi0` of the DSP accumulator `ac0`.
+    pub const Hi0: Reg = Reg::Ac0;
+    /// `$hi1` of the DSP accumulator `ac1`.
+    pub const Hi1: Reg = Reg::Ac1;
+    /// `$hi2` of the DSP accumulator `ac2`.
+    pub const Hi2: Reg = Reg::Ac2;
+    /// `$hi3` of the DSP accumulator `ac3`.
+    pub const Hi3: Reg = Reg::Ac3;
+    /// `$lo0` of the DSP accumulator `ac0`.
+    pub const Lo0: Reg = Reg::Ac0;
+    /// `$lo1` of the DSP accumulator `ac1`.
+    pub const Lo1: Reg = Reg::Ac1;
+    /// `$lo2` of the DSP accumulator `ac2`.
+    pub const Lo2: Reg = Reg::Ac2;
+    /// `$lo3` of the DSP accumulator `ac3`.
+    pub const Lo3: Reg = Reg::Ac3;
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -26,4 +348,12 @@ mod test {
             sys::get_test_val("alignof(cs_mips)")
         );
     }
+
+    #[test]
+    fn mips_enum_size() {
+        assert_eq!(
+            Reg::Ending.to_c(),
+            sys::get_test_val("MIPS_REG_ENDING") as _
+        );
+    }
 }
@@ -59,11 +59,26 @@ impl core::cmp::PartialOrd for InsnId {
 /// This group may be equal to multiple groups from different architectures but
 /// not to multiple groups from the same architecture. This can also be converted
 /// into an architecture specific group for any architecture.
-#[derive(Copy, Clone, PartialEq, Eq, Default, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, Hash)]
 #[repr(transparent)]
 pub struct InsnGroup(u8);
 
 impl InsnGroup {
+    /// All jump instructions (conditional, direct, and indirect jumps).
+    pub const JUMP: InsnGroup = InsnGroup(1);
+    /// All call instructions.
+    pub const CALL: InsnGroup = InsnGroup(2);
+    /// All return instructions.
+    pub const RET: InsnGroup = InsnGroup(3);
+    /// All interrupt instructions (e.g. `int` and `syscall` on x86).
+    pub const INT: InsnGroup = InsnGroup(4);
+    /// All interrupt return instructions.
+    pub const IRET: InsnGroup = InsnGroup(5);
+    /// All privileged instructions.
+    pub const PRIVILEGE: InsnGroup = InsnGroup(6);
+    /// All relative branching instructions.
+    pub const BRANCH_RELATIVE: InsnGroup = InsnGroup(7);
+
     #[allow(dead_code)]
     pub(crate) fn to_primitive(self) -> u8 {
         self.0
@@ -84,6 +99,49 @@ impl Reg {
     }
 }
 
+/// Implements conversions between the registers of an architecture and generic registers.
+macro_rules! impl_arch_reg {
+    ($ArchModuleName:ident, $ArchFnName:ident) => {
+        impl PartialEq<$ArchModuleName::Reg> for Reg {
+            #[inline]
+            fn eq(&self, other: &$ArchModuleName::Reg) -> bool {
+                self.0 == other.to_primitive() as u16
+            }
+        }
+
+        impl PartialEq<Reg> for $ArchModuleName::Reg {
+            #[inline]
+            fn eq(&self, other: &Reg) -> bool {
+                self.to_primitive() as u16 == other.0
+            }
+        }
+
+        impl core::convert::From<$ArchModuleName::Reg> for Reg {
+            #[inline]
+            fn from(arch_reg: $ArchModuleName::Reg) -> Self {
+                Reg(arch_reg.to_primitive() as u16)
+            }
+        }
+
+        impl core::convert::From<Reg> for $ArchModuleName::Reg {
+            #[inline]
+            fn from(generic: Reg) -> $ArchModuleName::Reg {
+                $ArchModuleName::Reg::from_c(generic.0 as libc::c_int)
+                    .unwrap_or($ArchModuleName::Reg::Invalid)
+            }
+        }
+
+        impl Reg {
+            /// Convert a generic register to an architecture specific register.
+            #[inline]
+            pub fn $ArchFnName(self) -> $ArchModuleName::Reg {
+                $ArchModuleName::Reg::from_c(self.0 as libc::c_int)
+                    .unwrap_or($ArchModuleName::Reg::Invalid)
+            }
+        }
+    };
+}
+
 macro_rules! impl_arch {
     ($ArchModuleName:ident, $ArchTypeName:ident, $ArchFnName:ident) => {
         impl From<$ArchModuleName::InsnId> for InsnId {
@@ -138,44 +196,12 @@ macro_rules! impl_arch {
             }
         }
 
-        impl PartialEq<$ArchModuleName::Reg> for Reg {
-            #[inline]
-            fn eq(&self, other: &$ArchModuleName::Reg) -> bool {
-                self.0 == other.to_primitive() as u16
-            }
-        }
-
-        impl PartialEq<Reg> for $ArchModuleName::Reg {
-            #[inline]
-            fn eq(&self, other: &Reg) -> bool {
-                self.to_primitive() as u16 == other.0
-            }
-        }
-
-        impl core::convert::From<$ArchModuleName::Reg> for Reg {
-            #[inline]
-            fn from(arch_reg: $ArchModuleName::Reg) -> Self {
-                Reg(arch_reg.to_primitive() as u16)
-            }
-        }
-
-        impl core::convert::From<Reg> for $ArchModuleName::Reg {
-            #[inline]
-            fn from(generic: Reg) -> $ArchModuleName::Reg {
-                $ArchModuleName::Reg::from_c(generic.0 as libc::c_int)
-                    .unwrap_or($ArchModuleName::Reg::Invalid)
-            }
-        }
-
-        impl Reg {
-            /// Convert a generic register to an architecture specific register.
-            #[inline]
-            pub fn $ArchFnName(self) -> $ArchModuleName::Reg {
-                $ArchModuleName::Reg::from_c(self.0 as libc::c_int)
-                    .unwrap_or($ArchModuleName::Reg::Invalid)
-            }
-        }
+        impl_arch_reg!($ArchModuleName, $ArchFnName);
     };
 }
 
 impl_arch!(x86, X86, x86);
+impl_arch_reg!(arm64, arm64);
+impl_arch_reg!(mips, mips);
+impl_arch_reg!(ppc, ppc);
+impl_arch_reg!(sparc, sparc);
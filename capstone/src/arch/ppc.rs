@@ -1,14 +1,408 @@
-use super::generated::cs_ppc;
+use super::generated::{cs_ppc, cs_ppc_op, ppc_op_crx, ppc_op_mem};
 use core::marker::PhantomData;
 
 #[repr(transparent)]
 #[derive(Clone, Copy)]
 pub struct Details<'c> {
-    #[allow(dead_code)]
     inner: cs_ppc,
     _phantom: PhantomData<&'c ()>,
 }
 
+impl<'c> Details<'c> {
+    /// Returns the branch code of branch instructions, or [`Bc::Invalid`] for other
+    /// instructions.
+    pub fn bc(&self) -> Bc {
+        Bc::from_c(self.inner.bc).unwrap_or(Bc::Invalid)
+    }
+
+    /// Returns the branch hint of branch instructions, or [`Bh::Invalid`] if there
+    /// is no hint.
+    pub fn bh(&self) -> Bh {
+        Bh::from_c(self.inner.bh).unwrap_or(Bh::Invalid)
+    }
+
+    /// Returns true if this instruction updates CR0 (the `.` suffix).
+    pub fn update_cr0(&self) -> bool {
+        self.inner.update_cr0
+    }
+
+    /// Returns the number of operands in this instruction, or
+    /// zero when this instruction has no operands. This value will
+    /// be the same as the length of the slice returned by [`Details::operands`].
+    pub fn op_count(&self) -> usize {
+        self.inner.op_count as usize
+    }
+
+    /// Returns the operands contained in this instruction. The length
+    /// of the returned slice will be the same as the value returned
+    /// by [`Details::op_count`].
+    pub fn operands(&self) -> &[Op] {
+        unsafe {
+            &*(&self.inner.operands[..self.inner.op_count as usize] as *const [cs_ppc_op]
+                as *const [Op])
+        }
+    }
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy)]
+pub struct Op {
+    inner: cs_ppc_op,
+}
+
+impl Op {
+    /// Returns the type of this operand.
+    pub fn op_type(&self) -> OpType {
+        OpType::from_c(self.inner.type_).unwrap_or(OpType::Invalid)
+    }
+
+    /// Returns the value of this operand.
+    pub fn value(&self) -> OpValue {
+        match self.op_type() {
+            OpType::Invalid => OpValue::Imm(0),
+            OpType::Reg => OpValue::Reg(
+                Reg::from_c(unsafe { self.inner.__bindgen_anon_1.reg }).unwrap_or(Reg::Invalid),
+            ),
+            OpType::Imm => OpValue::Imm(unsafe { self.inner.__bindgen_anon_1.imm }),
+            OpType::Mem => OpValue::Mem(unsafe {
+                OpMem {
+                    inner: self.inner.__bindgen_anon_1.mem,
+                }
+            }),
+            OpType::Crx => OpValue::Crx(unsafe {
+                OpCrx {
+                    inner: self.inner.__bindgen_anon_1.crx,
+                }
+            }),
+        }
+    }
+}
+
+pub enum OpValue {
+    Reg(Reg),
+    Imm(i64),
+    Mem(OpMem),
+    Crx(OpCrx),
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct OpMem {
+    inner: ppc_op_mem,
+}
+
+impl OpMem {
+    /// Returns the base register.
+    pub fn base(&self) -> Reg {
+        Reg::from_c(self.inner.base).unwrap_or(Reg::Invalid)
+    }
+
+    /// Returns the displacement value.
+    pub fn disp(&self) -> i32 {
+        self.inner.disp
+    }
+}
+
+/// A condition register field operand.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct OpCrx {
+    inner: ppc_op_crx,
+}
+
+impl OpCrx {
+    /// Returns the scale of the condition register field.
+    pub fn scale(&self) -> u32 {
+        self.inner.scale
+    }
+
+    /// Returns the condition register.
+    pub fn reg(&self) -> Reg {
+        Reg::from_c(self.inner.reg).unwrap_or(Reg::Invalid)
+    }
+
+    /// Returns the condition that is tested.
+    pub fn cond(&self) -> Bc {
+        Bc::from_c(self.inner.cond).unwrap_or(Bc::Invalid)
+    }
+}
+
+c_enum! {
+    /// Operand type for a PowerPC instruction's operands.
+    #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+    pub enum OpType: u8 {
+        /// Uninitialized.
+        Invalid = 0,
+        /// Register operand.
+        Reg,
+        /// Immediate operand.
+        Imm,
+        /// Memory operand.
+        Mem,
+        /// Condition register operand.
+        Crx = 64,
+    }
+}
+
+c_enum! {
+    /// Branch codes of conditional branches.
+    #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+    pub enum Bc: u8 {
+        /// Uninitialized.
+        Invalid = 0,
+        Lt = 12,
+        Le = 36,
+        Eq = 76,
+        Ge = 4,
+        Gt = 44,
+        Ne = 68,
+        /// Unordered.
+        Un = 108,
+        /// Not unordered.
+        Nu = 100,
+        /// Summary overflow.
+        So = 140,
+        /// Not summary overflow.
+        Ns = 132,
+    }
+}
+
+c_enum! {
+    /// Branch hints.
+    #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+    pub enum Bh: u8 {
+        /// No hint.
+        Invalid = 0,
+        /// Likely to be taken (`+`).
+        Plus,
+        /// Unlikely to be taken (`-`).
+        Minus,
+    }
+}
+
+c_enum_big! {
+    #[non_exhaustive]
+    #[derive(Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum Reg: u8 {
+        @Start = Invalid,
+        @End   = Ending,
+
+        Invalid = 0,
+        Carry,
+        Cr0,
+        Cr1,
+        Cr2,
+        Cr3,
+        Cr4,
+        Cr5,
+        Cr6,
+        Cr7,
+        Ctr,
+        F0,
+        F1,
+        F2,
+        F3,
+        F4,
+        F5,
+        F6,
+        F7,
+        F8,
+        F9,
+        F10,
+        F11,
+        F12,
+        F13,
+        F14,
+        F15,
+        F16,
+        F17,
+        F18,
+        F19,
+        F20,
+        F21,
+        F22,
+        F23,
+        F24,
+        F25,
+        F26,
+        F27,
+        F28,
+        F29,
+        F30,
+        F31,
+        Lr,
+        R0,
+        R1,
+        R2,
+        R3,
+        R4,
+        R5,
+        R6,
+        R7,
+        R8,
+        R9,
+        R10,
+        R11,
+        R12,
+        R13,
+        R14,
+        R15,
+        R16,
+        R17,
+        R18,
+        R19,
+        R20,
+        R21,
+        R22,
+        R23,
+        R24,
+        R25,
+        R26,
+        R27,
+        R28,
+        R29,
+        R30,
+        R31,
+        V0,
+        V1,
+        V2,
+        V3,
+        V4,
+        V5,
+        V6,
+        V7,
+        V8,
+        V9,
+        V10,
+        V11,
+        V12,
+        V13,
+        V14,
+        V15,
+        V16,
+        V17,
+        V18,
+        V19,
+        V20,
+        V21,
+        V22,
+        V23,
+        V24,
+        V25,
+        V26,
+        V27,
+        V28,
+        V29,
+        V30,
+        V31,
+        Vrsave,
+        Vs0,
+        Vs1,
+        Vs2,
+        Vs3,
+        Vs4,
+        Vs5,
+        Vs6,
+        Vs7,
+        Vs8,
+        Vs9,
+        Vs10,
+        Vs11,
+        Vs12,
+        Vs13,
+        Vs14,
+        Vs15,
+        Vs16,
+        Vs17,
+        Vs18,
+        Vs19,
+        Vs20,
+        Vs21,
+        Vs22,
+        Vs23,
+        Vs24,
+        Vs25,
+        Vs26,
+        Vs27,
+        Vs28,
+        Vs29,
+        Vs30,
+        Vs31,
+        Vs32,
+        Vs33,
+        Vs34,
+        Vs35,
+        Vs36,
+        Vs37,
+        Vs38,
+        Vs39,
+        Vs40,
+        Vs41,
+        Vs42,
+        Vs43,
+        Vs44,
+        Vs45,
+        Vs46,
+        Vs47,
+        Vs48,
+        Vs49,
+        Vs50,
+        Vs51,
+        Vs52,
+        Vs53,
+        Vs54,
+        Vs55,
+        Vs56,
+        Vs57,
+        Vs58,
+        Vs59,
+        Vs60,
+        Vs61,
+        Vs62,
+        Vs63,
+        Q0,
+        Q1,
+        Q2,
+        Q3,
+        Q4,
+        Q5,
+        Q6,
+        Q7,
+        Q8,
+        Q9,
+        Q10,
+        Q11,
+        Q12,
+        Q13,
+        Q14,
+        Q15,
+        Q16,
+        Q17,
+        Q18,
+        Q19,
+        Q20,
+        Q21,
+        Q22,
+        Q23,
+        Q24,
+        Q25,
+        Q26,
+        Q27,
+        Q28,
+        Q29,
+        Q30,
+        Q31,
+        Rm,
+        Ctr8,
+        Lr8,
+        Cr1eq,
+        X2,
+
+        #[doc(hidden)]
+        Ending,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -26,4 +420,9 @@ mod test {
             sys::get_test_val("alignof(cs_ppc)")
         );
     }
+
+    #[test]
+    fn ppc_enum_size() {
+        assert_eq!(Reg::Ending.to_c(), sys::get_test_val("PPC_REG_ENDING") as _);
+    }
 }
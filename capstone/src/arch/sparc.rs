@@ -1,14 +1,280 @@
-use super::generated::cs_sparc;
+use super::generated::{cs_sparc, cs_sparc_op, sparc_op_mem};
 use core::marker::PhantomData;
 
 #[repr(transparent)]
 #[derive(Clone, Copy)]
 pub struct Details<'c> {
-    #[allow(dead_code)]
     inner: cs_sparc,
     _phantom: PhantomData<&'c ()>,
 }
 
+impl<'c> Details<'c> {
+    /// Returns the condition code of branch instructions, or [`Cc::Invalid`] for other
+    /// instructions.
+    pub fn cc(&self) -> Cc {
+        Cc::from_c(self.inner.cc).unwrap_or(Cc::Invalid)
+    }
+
+    /// Returns the branch hints of branch instructions.
+    pub fn hint(&self) -> Hint {
+        Hint::from_bits_truncate(self.inner.hint)
+    }
+
+    /// Returns the number of operands in this instruction, or
+    /// zero when this instruction has no operands. This value will
+    /// be the same as the length of the slice returned by [`Details::operands`].
+    pub fn op_count(&self) -> usize {
+        self.inner.op_count as usize
+    }
+
+    /// Returns the operands contained in this instruction. The length
+    /// of the returned slice will be the same as the value returned
+    /// by [`Details::op_count`].
+    pub fn operands(&self) -> &[Op] {
+        unsafe {
+            &*(&self.inner.operands[..self.inner.op_count as usize] as *const [cs_sparc_op]
+                as *const [Op])
+        }
+    }
+}
+
+#[repr(transparent)]
+#[derive(Clone, Copy)]
+pub struct Op {
+    inner: cs_sparc_op,
+}
+
+impl Op {
+    /// Returns the type of this operand.
+    pub fn op_type(&self) -> OpType {
+        OpType::from_c(self.inner.type_).unwrap_or(OpType::Invalid)
+    }
+
+    /// Returns the value of this operand.
+    pub fn value(&self) -> OpValue {
+        match self.op_type() {
+            OpType::Invalid => OpValue::Imm(0),
+            OpType::Reg => OpValue::Reg(
+                Reg::from_c(unsafe { self.inner.__bindgen_anon_1.reg }).unwrap_or(Reg::Invalid),
+            ),
+            OpType::Imm => OpValue::Imm(unsafe { self.inner.__bindgen_anon_1.imm }),
+            OpType::Mem => OpValue::Mem(unsafe {
+                OpMem {
+                    inner: self.inner.__bindgen_anon_1.mem,
+                }
+            }),
+        }
+    }
+}
+
+pub enum OpValue {
+    Reg(Reg),
+    Imm(i64),
+    Mem(OpMem),
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct OpMem {
+    inner: sparc_op_mem,
+}
+
+impl OpMem {
+    /// Returns the base register.
+    pub fn base(&self) -> Reg {
+        Reg::from_c(self.inner.base).unwrap_or(Reg::Invalid)
+    }
+
+    /// Returns the index register.
+    pub fn index(&self) -> Reg {
+        Reg::from_c(self.inner.index).unwrap_or(Reg::Invalid)
+    }
+
+    /// Returns the displacement value.
+    pub fn disp(&self) -> i32 {
+        self.inner.disp
+    }
+}
+
+c_enum! {
+    /// Operand type for a SPARC instruction's operands.
+    #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+    pub enum OpType: u8 {
+        /// Uninitialized.
+        Invalid = 0,
+        /// Register operand.
+        Reg,
+        /// Immediate operand.
+        Imm,
+        /// Memory operand.
+        Mem,
+    }
+}
+
+c_enum! {
+    /// Condition codes of branch instructions, on the integer (`Icc`) or floating point
+    /// (`Fcc`) condition codes.
+    #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+    pub enum Cc: u16 {
+        /// Uninitialized.
+        Invalid = 0,
+        IccA = 264,
+        IccN = 256,
+        IccNe = 265,
+        IccE = 257,
+        IccG = 266,
+        IccLe = 258,
+        IccGe = 267,
+        IccL = 259,
+        IccGu = 268,
+        IccLeu = 260,
+        IccCc = 269,
+        IccCs = 261,
+        IccPos = 270,
+        IccNeg = 262,
+        IccVc = 271,
+        IccVs = 263,
+        FccA = 280,
+        FccN = 272,
+        FccU = 279,
+        FccG = 278,
+        FccUg = 277,
+        FccL = 276,
+        FccUl = 275,
+        FccLg = 274,
+        FccNe = 273,
+        FccE = 281,
+        FccUe = 282,
+        FccGe = 283,
+        FccUge = 284,
+        FccLe = 285,
+        FccUle = 286,
+        FccO = 287,
+    }
+}
+
+bitflags::bitflags! {
+    /// Branch hints.
+    #[repr(transparent)]
+    pub struct Hint: u32 {
+        /// Annul the delay slot (`,a`).
+        const A = 1 << 0;
+        /// Predict taken (`,pt`).
+        const PT = 1 << 1;
+        /// Predict not taken (`,pn`).
+        const PN = 1 << 2;
+    }
+}
+
+c_enum_big! {
+    #[non_exhaustive]
+    #[derive(Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum Reg: u8 {
+        @Start = Invalid,
+        @End   = Ending,
+
+        Invalid = 0,
+        F0,
+        F1,
+        F2,
+        F3,
+        F4,
+        F5,
+        F6,
+        F7,
+        F8,
+        F9,
+        F10,
+        F11,
+        F12,
+        F13,
+        F14,
+        F15,
+        F16,
+        F17,
+        F18,
+        F19,
+        F20,
+        F21,
+        F22,
+        F23,
+        F24,
+        F25,
+        F26,
+        F27,
+        F28,
+        F29,
+        F30,
+        F31,
+        F32,
+        F34,
+        F36,
+        F38,
+        F40,
+        F42,
+        F44,
+        F46,
+        F48,
+        F50,
+        F52,
+        F54,
+        F56,
+        F58,
+        F60,
+        F62,
+        Fcc0,
+        Fcc1,
+        Fcc2,
+        Fcc3,
+        Fp,
+        G0,
+        G1,
+        G2,
+        G3,
+        G4,
+        G5,
+        G6,
+        G7,
+        I0,
+        I1,
+        I2,
+        I3,
+        I4,
+        I5,
+        I7,
+        Icc,
+        L0,
+        L1,
+        L2,
+        L3,
+        L4,
+        L5,
+        L6,
+        L7,
+        O0,
+        O1,
+        O2,
+        O3,
+        O4,
+        O5,
+        O7,
+        Sp,
+        Y,
+        Xcc,
+
+        #[doc(hidden)]
+        Ending,
+    }
+}
+
+#[allow(non_upper_case_globals)]
+impl Reg {
+    /// `%o6`, which is the same register as `%sp`.
+    pub const O6: Reg = Reg::Sp;
+    /// `%i6`, which is the same register as `%fp`.
+    pub const I6: Reg = Reg::Fp;
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -26,4 +292,12 @@ mod test {
             sys::get_test_val("alignof(cs_sparc)")
         );
     }
+
+    #[test]
+    fn sparc_enum_size() {
+        assert_eq!(
+            Reg::Ending.to_c(),
+            sys::get_test_val("SPARC_REG_ENDING") as _
+        );
+    }
 }
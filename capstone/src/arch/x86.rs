@@ -25,6 +25,16 @@ impl<'c> Details<'c> {
         self.inner.prefix[idx] == prefix.to_primitive()
     }
 
+    /// Returns the prefixes of the instruction, in the order lock/rep, segment override,
+    /// operand size override, and address size override.
+    pub fn prefixes(&self) -> impl Iterator<Item = Prefix> + '_ {
+        self.inner
+            .prefix
+            .iter()
+            .filter(|&&prefix| prefix != 0)
+            .filter_map(|&prefix| Prefix::from_primitive(prefix))
+    }
+
     /// Instruction opcode. This value can be from 1 to 4 bytes in size.
     /// This will contain the VEX opcode as well.
     pub fn opcode(&self) -> &[u8] {
@@ -148,6 +158,30 @@ impl Op {
         OpType::from_c(self.inner.type_).unwrap_or(OpType::Invalid)
     }
 
+    /// Returns the register if this is a register operand.
+    pub fn reg(&self) -> Option<Reg> {
+        match self.value() {
+            OpValue::Reg(reg) => Some(reg),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is an immediate operand.
+    pub fn imm(&self) -> Option<i64> {
+        match self.value() {
+            OpValue::Imm(imm) if self.op_type() == OpType::Imm => Some(imm),
+            _ => None,
+        }
+    }
+
+    /// Returns the memory operand if this is one.
+    pub fn mem(&self) -> Option<OpMem> {
+        match self.value() {
+            OpValue::Mem(mem) => Some(mem),
+            _ => None,
+        }
+    }
+
     /// Returns the value of this operand.
     pub fn value(&self) -> OpValue {
         match self.op_type() {
@@ -255,6 +289,30 @@ impl OpMem {
     pub fn disp(&self) -> i64 {
         self.inner.disp
     }
+
+    /// Returns the address that this memory operand refers to if it doesn't depend on the
+    /// values of any registers, which is the case for absolute addresses and addresses
+    /// relative to the instruction pointer. `next_insn_address` is the address of the
+    /// instruction after the one that this operand belongs to, which is what the
+    /// instruction pointer points to. Addresses with an FS or GS segment override are
+    /// relative to a thread local base that isn't known, so there's no address for them.
+    pub fn effective_address(&self, next_insn_address: u64) -> Option<u64> {
+        if matches!(self.segment(), Reg::Fs | Reg::Gs) {
+            return None;
+        }
+        if !matches!(self.index(), Reg::Invalid | Reg::Eiz | Reg::Riz) {
+            return None;
+        }
+
+        match self.base() {
+            Reg::Rip => Some(next_insn_address.wrapping_add(self.disp() as u64)),
+            Reg::Eip | Reg::Ip => {
+                Some(next_insn_address.wrapping_add(self.disp() as u64) & 0xffff_ffff)
+            }
+            Reg::Invalid => Some(self.disp() as u64),
+            _ => None,
+        }
+    }
 }
 
 c_enum! {
@@ -517,7 +575,7 @@ union X86EFlagsOrFpuFlags {
 
 c_enum_big! {
     #[non_exhaustive]
-    #[derive(Copy, Clone, PartialEq, Eq, Hash)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
     pub enum Reg: u8 {
         @Start = Invalid,
         @End   = Ending,
@@ -2387,6 +2445,40 @@ mod test {
         );
     }
 
+    #[test]
+    fn x86_effective_address() {
+        let mem = |base: Reg, index: Reg, disp: i64| OpMem {
+            inner: x86_op_mem {
+                segment: Reg::Invalid.to_c() as _,
+                base: base.to_c() as _,
+                index: index.to_c() as _,
+                scale: 1,
+                disp,
+            },
+        };
+
+        assert_eq!(
+            mem(Reg::Rip, Reg::Invalid, 0x20).effective_address(0x1000),
+            Some(0x1020)
+        );
+        assert_eq!(
+            mem(Reg::Rip, Reg::Invalid, -0x20).effective_address(0x1000),
+            Some(0xfe0)
+        );
+        assert_eq!(
+            mem(Reg::Invalid, Reg::Invalid, 0x4000).effective_address(0x1000),
+            Some(0x4000)
+        );
+        assert_eq!(
+            mem(Reg::Rax, Reg::Invalid, 0x20).effective_address(0x1000),
+            None
+        );
+        assert_eq!(
+            mem(Reg::Rip, Reg::Rcx, 0x20).effective_address(0x1000),
+            None
+        );
+    }
+
     #[test]
     fn x86_enum_size() {
         assert_eq!(Reg::Ending.to_c(), sys::get_test_val("X86_REG_ENDING") as _);
@@ -2,7 +2,7 @@ use crate::arch::{
     arm, arm64, evm, m680x, m68k, mips, mos65xx, ppc, sparc, sysz, tms320c64x, x86, xcore,
     InsnGroup, Reg,
 };
-use crate::{sys, util, Arch};
+use crate::{sys, util, Arch, Capstone, Error, RegsBuffer, RegsUsed};
 use core::marker::PhantomData;
 
 const MNEMONIC_SIZE: usize = 32;
@@ -83,6 +83,50 @@ impl<'a> Insn<'a> {
     pub fn operands(&self) -> &str {
         unsafe { util::cstr(self.op_str.as_ptr(), 160) }
     }
+
+    /// Returns true if this is data that was skipped over in SKIPDATA mode instead of an
+    /// instruction. See [`Capstone::set_skipdata_mode`].
+    #[inline]
+    pub fn is_skipdata(&self) -> bool {
+        self.id == 0
+    }
+
+    /// Returns the groups that this instruction belongs to, e.g. [`InsnGroup::JUMP`] or
+    /// [`InsnGroup::CALL`], which can be compared to generic groups and to the groups of
+    /// the instruction's architecture. This is empty if details are not enabled for the
+    /// Capstone instance that disassembled the instruction, and for data in Skipdata mode.
+    pub fn groups(&self) -> impl Iterator<Item = InsnGroup> + '_ {
+        // Capstone only allocates details while they're enabled, and they can't be
+        // disabled while an instruction that it disassembled is borrowed.
+        let details = if self.is_skipdata() {
+            None
+        } else {
+            unsafe { self.detail.as_ref() }
+        };
+        details
+            .map(|details| &details.groups[..details.groups_count as usize])
+            .unwrap_or(&[])
+            .iter()
+            .map(|&group| unsafe { core::mem::transmute::<u8, InsnGroup>(group) })
+    }
+
+    /// Returns all of the registers that this instruction reads from, either implicitly or
+    /// explicitly. `caps` must be the Capstone instance that disassembled this instruction,
+    /// with details enabled.
+    pub fn regs_read(&self, caps: &Capstone) -> Result<RegsBuffer, Error> {
+        let mut regs_used = RegsUsed::default();
+        caps.regs_used(self, &mut regs_used)?;
+        Ok(regs_used.read)
+    }
+
+    /// Returns all of the registers that this instruction writes to, either implicitly or
+    /// explicitly. `caps` must be the Capstone instance that disassembled this instruction,
+    /// with details enabled.
+    pub fn regs_written(&self, caps: &Capstone) -> Result<RegsBuffer, Error> {
+        let mut regs_used = RegsUsed::default();
+        caps.regs_used(self, &mut regs_used)?;
+        Ok(regs_used.write)
+    }
 }
 
 /// A buffer of disassembled instructions.
@@ -155,6 +199,14 @@ impl<'a> InsnIter<'a> {
         }
     }
 
+    /// Starts disassembling the given code instead, reusing the instruction buffer
+    /// of this iterator.
+    pub fn reset(&mut self, code: &'a [u8], address: u64) {
+        self.code = code.as_ptr();
+        self.size = code.len() as libc::size_t;
+        self.addr = address;
+    }
+
     /// Frees the `Insn`(`cs_insn`) if it is not currently null
     /// then clears the pointer.
     fn free(&mut self) {
@@ -268,6 +320,50 @@ impl<'i> Details<'i> {
             None
         }
     }
+
+    /// If these are details for an ARM64 instruction, this will return
+    /// ARM64 specific details. If these are not details for an ARM64 instruction
+    /// this will return [`Option::None`].
+    pub fn arm64(self) -> Option<&'i arm64::Details<'i>> {
+        if self.arch == Arch::Arm64 {
+            Some(unsafe { &self.inner.arch.arm64 })
+        } else {
+            None
+        }
+    }
+
+    /// If these are details for a MIPS instruction, this will return
+    /// MIPS specific details. If these are not details for a MIPS instruction
+    /// this will return [`Option::None`].
+    pub fn mips(self) -> Option<&'i mips::Details<'i>> {
+        if self.arch == Arch::Mips {
+            Some(unsafe { &self.inner.arch.mips })
+        } else {
+            None
+        }
+    }
+
+    /// If these are details for a PowerPC instruction, this will return
+    /// PowerPC specific details. If these are not details for a PowerPC instruction
+    /// this will return [`Option::None`].
+    pub fn ppc(self) -> Option<&'i ppc::Details<'i>> {
+        if self.arch == Arch::PowerPc {
+            Some(unsafe { &self.inner.arch.ppc })
+        } else {
+            None
+        }
+    }
+
+    /// If these are details for a SPARC instruction, this will return
+    /// SPARC specific details. If these are not details for a SPARC instruction
+    /// this will return [`Option::None`].
+    pub fn sparc(self) -> Option<&'i sparc::Details<'i>> {
+        if self.arch == Arch::Sparc {
+            Some(unsafe { &self.inner.arch.sparc })
+        } else {
+            None
+        }
+    }
 }
 
 /// Wrapper around cs_detail.
@@ -54,8 +54,10 @@ pub struct Capstone {
     handle: sys::Handle,
     packed: PackedCSInfo,
 
+    /// Custom mnemonics by the C instruction ID that they were set for. These are
+    /// kept here because Capstone only stores pointers to them.
     #[cfg(feature = "alloc")]
-    mnemonics: Map<InsnId, Cow<'static, str>>,
+    mnemonics: Map<libc::c_int, Cow<'static, str>>,
 
     #[cfg(feature = "alloc")]
     skipdata_callback: Option<Box<SkipdataCallback>>,
@@ -195,7 +197,12 @@ impl Capstone {
 
     /// Returns an iterator that will lazily disassemble the instructions
     /// in the given binary.
-    pub fn disasm_iter<'s>(&'s self, code: &[u8], address: u64) -> InsnIter<'s> {
+    ///
+    /// Every instruction is disassembled into the same buffer, so memory is only
+    /// allocated once no matter how much code there is, but an instruction is
+    /// overwritten by the next one that the iterator returns. The buffer can be
+    /// reused for more code with [`InsnIter::reset`].
+    pub fn disasm_iter<'s>(&'s self, code: &'s [u8], address: u64) -> InsnIter<'s> {
         let insn = unsafe { sys::cs_malloc(self.handle) };
         assert!(!insn.is_null(), "cs_malloc() returned a null insn");
 
@@ -278,7 +285,7 @@ impl Capstone {
         let insn = insn.into();
         let mnemonic = util::ensure_c_string(mnemonic.into());
         let mnemonic_ptr = mnemonic.as_ptr() as *const libc::c_char; // this is a stable pointer to string data.
-        self.mnemonics.insert(insn, mnemonic);
+        self.mnemonics.insert(insn.to_c(), mnemonic);
 
         self.set_mnemonic_inner(insn, mnemonic_ptr)
     }
@@ -289,7 +296,7 @@ impl Capstone {
     /// # Panics
     /// If `mnemonic` is not a valid C string.
     #[cfg(not(feature = "alloc"))]
-    pub fn set_mnemonic<I, M>(&mut self, insn: I, mnemonic: &'static str) -> Result<(), Error>
+    pub fn set_mnemonic<I>(&mut self, insn: I, mnemonic: &'static str) -> Result<(), Error>
     where
         I: Into<InsnId>,
     {
@@ -462,7 +469,7 @@ impl Capstone {
     /// # Panics
     /// If `mnemonic` is not a valid C string.
     #[cfg(not(feature = "alloc"))]
-    pub fn setup_skipdata<M, F>(
+    pub fn setup_skipdata(
         &mut self,
         mnemonic: Option<&'static str>,
         callback: Option<fn(&[u8], usize) -> usize>,
@@ -1043,7 +1050,7 @@ where
     unsafe { sys::cs_support(query_int) }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod test {
     use super::*;
 
@@ -1105,6 +1112,55 @@ mod test {
         }
     }
 
+    #[test]
+    fn disasm_iter_reset() {
+        let caps = Capstone::open(Arch::X86, Mode::Bits64).expect("failed to open capstone");
+
+        // nop; ret
+        let mut insns = caps.disasm_iter(&[0x90, 0xc3], 0x1000);
+        let first: Vec<(u64, String)> = (&mut insns)
+            .map(|insn| insn.expect("failed to disassemble"))
+            .map(|insn| (insn.address(), insn.mnemonic().to_string()))
+            .collect();
+        assert_eq!(
+            first,
+            [(0x1000, "nop".to_string()), (0x1001, "ret".to_string())]
+        );
+
+        // int3
+        insns.reset(&[0xcc], 0x2000);
+        let insn = insns
+            .next()
+            .expect("no instruction after reset")
+            .expect("failed to disassemble");
+        assert_eq!(insn.address(), 0x2000);
+        assert_eq!(insn.mnemonic(), "int3");
+        assert!(insns.next().is_none());
+    }
+
+    #[test]
+    fn insn_regs_accessed() {
+        let mut caps = Capstone::open(Arch::X86, Mode::Bits64).expect("failed to open capstone");
+        caps.set_details_enabled(true)
+            .expect("failed to enable capstone instruction details");
+
+        // add rax, rbx
+        let insns = caps
+            .disasm(&[0x48, 0x01, 0xd8], 0x1000)
+            .expect("failed to disassemble");
+        let insn = &insns[0];
+
+        let read = insn.regs_read(&caps).expect("failed to get registers read");
+        let written = insn
+            .regs_written(&caps)
+            .expect("failed to get registers written");
+        assert!(read.contains(&x86::Reg::Rax.into()));
+        assert!(read.contains(&x86::Reg::Rbx.into()));
+        assert!(written.contains(&x86::Reg::Rax.into()));
+        assert!(written.contains(&x86::Reg::Eflags.into()));
+        assert!(!written.contains(&x86::Reg::Rbx.into()));
+    }
+
     #[test]
     fn validate_packed_cs_info_states() {
         for arch in ALL_ARCHS.iter().copied() {
@@ -65,6 +65,11 @@ struct ep_helper__entry ep_helper__entries[] = {
     { "X86_REG_ENDING", (size_t)X86_REG_ENDING },
     { "X86_INS_ENDING", (size_t)X86_INS_ENDING },
     { "X86_GRP_ENDING", (size_t)X86_GRP_ENDING },
+
+    { "ARM64_REG_ENDING", (size_t)ARM64_REG_ENDING },
+    { "MIPS_REG_ENDING", (size_t)MIPS_REG_ENDING },
+    { "PPC_REG_ENDING", (size_t)PPC_REG_ENDING },
+    { "SPARC_REG_ENDING", (size_t)SPARC_REG_ENDING },
 };
 
 CAPSTONE_EXPORT
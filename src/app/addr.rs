@@ -0,0 +1,253 @@
+use super::cli::{AddrOpts, Opts, OutputFormat};
+use super::json;
+use super::printer;
+use super::theme::Theme;
+use crate::disasm::{
+    self,
+    binary::{Binary, BinaryData, SearchOptions},
+    context::{self, AddressContext},
+    symbol::Symbol,
+};
+use anyhow::Context as _;
+use std::path::{Path, PathBuf};
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+/// What was found at an address of the binary.
+pub struct AddressInfo<'b> {
+    pub address: u64,
+    /// The position of the address in the file of the binary, if it is in a symbol.
+    pub file_offset: Option<usize>,
+    /// The symbol that contains the address and the offset of the address in it.
+    pub symbol: Option<(&'b Symbol, u64)>,
+    /// The source lines that the instruction at the address was generated from.
+    pub locations: Vec<(&'b Path, u32)>,
+}
+
+/// Prints the symbol and source lines that contain each address.
+pub fn run(opts: &Opts, addr_opts: &AddrOpts, color_choice: ColorChoice) -> anyhow::Result<()> {
+    let (_, mut bin, lazy_dwarf) = open_binary(opts)?;
+
+    let addresses = if addr_opts.file_offsets {
+        addr_opts
+            .addresses
+            .iter()
+            .map(|&offset| {
+                bin.symbolicate_offset(offset as usize)
+                    .map(|(symbol, symbol_offset)| symbol.address() + symbol_offset)
+                    .with_context(|| format!("no symbol contains the file offset 0x{:x}", offset))
+            })
+            .collect::<anyhow::Result<Vec<u64>>>()?
+    } else {
+        addr_opts.addresses.clone()
+    };
+
+    if lazy_dwarf {
+        for &address in addresses.iter() {
+            bin.load_dwarf_symbols_at(address)?;
+        }
+    }
+
+    let mut infos = Vec::with_capacity(addresses.len());
+    for &address in addresses.iter() {
+        let symbol = bin.symbolicate(address);
+        let file_offset =
+            symbol.map(|(symbol, symbol_offset)| symbol.offset() + symbol_offset as usize);
+        let locations = bin
+            .addr2line(address)?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        infos.push(AddressInfo {
+            address,
+            file_offset,
+            symbol,
+            locations,
+        });
+    }
+
+    if addr_opts.context.is_some() && opts.format != OutputFormat::Text {
+        return Err(anyhow::anyhow!(
+            "the instructions around addresses can only be printed as text"
+        ));
+    }
+    let mut contexts = Vec::new();
+    if let (Some(count), OutputFormat::Text) =
+        (addr_opts.context.or(opts.config.context), opts.format)
+    {
+        let options = context_options(opts)?;
+        for &address in addresses.iter() {
+            match context::disasm_around(&bin, address, count, options) {
+                Ok(context) => contexts.push(Some(context)),
+                Err(err) => {
+                    log::warn!("failed to disassemble around 0x{:x}: {:#}", address, err);
+                    contexts.push(None);
+                }
+            }
+        }
+    }
+
+    let result = match opts.format {
+        OutputFormat::Text => print_addresses(
+            &mut StandardStream::stdout(color_choice),
+            &infos,
+            &contexts,
+            opts,
+            &bin,
+        ),
+        OutputFormat::Json => json::print_addresses(&mut std::io::stdout().lock(), &infos),
+        OutputFormat::Dot => {
+            return Err(anyhow::anyhow!(
+                "addresses can't be written as a Graphviz DOT graph"
+            ))
+        }
+        OutputFormat::Html => return Err(anyhow::anyhow!("addresses can't be written as HTML")),
+        OutputFormat::Markdown => {
+            return Err(anyhow::anyhow!("addresses can't be written as Markdown"))
+        }
+        OutputFormat::JsonLines => {
+            return Err(anyhow::anyhow!("addresses can't be written as JSON lines"))
+        }
+        OutputFormat::Asm => return Err(anyhow::anyhow!("addresses can't be written as assembly")),
+    };
+    result.context("error occured while printing addresses")
+}
+
+/// Loads the binary with only the DWARF symbols that are needed for looking up addresses,
+/// which `Binary::load_dwarf_symbols_at` loads if the returned flag is true. Returns the
+/// path of the binary, too.
+pub(super) fn open_binary(opts: &Opts) -> anyhow::Result<(PathBuf, Binary, bool)> {
+    let binary_path = super::find_binary_path(opts)?;
+    log::debug!("using binary {}", binary_path.display());
+    let data = BinaryData::from_path(&binary_path)
+        .with_context(|| format!("failed to load binary `{}`", binary_path.display()))?;
+    let sources = super::parse_symbol_sources(opts)?;
+
+    // Only the DWARF symbols at the addresses are needed.
+    let lazy_dwarf = sources.is_empty();
+    let mut bin = Binary::new(
+        data,
+        SearchOptions {
+            sources: &sources,
+            dwarf_path: opts.debug_file_path.as_deref(),
+            dsym_path: opts.dsym_path.as_deref(),
+            pdb_path: None,
+            archive_member: opts.archive_member.as_deref(),
+            cache_symbols: !opts.no_cache,
+            lazy_dwarf,
+            include_locals: opts.include_locals,
+            exclude_compiler_generated: opts.exclude_compiler_generated,
+            mangled_names: opts.no_demangle,
+            rust_hashes: opts.keep_hashes,
+        },
+    )?;
+    bin.load_line_information()?;
+    Ok((binary_path, bin, lazy_dwarf))
+}
+
+/// Returns the options for disassembling the instructions around addresses.
+pub(super) fn context_options(opts: &Opts) -> anyhow::Result<disasm::Options> {
+    Ok(disasm::Options {
+        syntax: opts.syntax()?,
+        no_skip_data: opts.no_skip_data,
+        engine: opts.engine,
+        explain: opts.explain,
+        ..Default::default()
+    })
+}
+
+/// Prints the instructions around an address with the instruction at `marker` marked.
+pub(super) fn print_context(
+    out: &mut dyn WriteColor,
+    context: &AddressContext,
+    marker: u64,
+    opts: &Opts,
+    bin: &Binary,
+    theme: &Theme,
+) -> anyhow::Result<()> {
+    printer::print_disassembly(
+        out,
+        &context.symbol,
+        &context.disassembly,
+        printer::DisasmOptions {
+            show_source: false,
+            show_inlined: false,
+            show_bytes: opts.show_bytes(),
+            bytes_format: opts.bytes_format(bin.endian())?,
+            show_header: false,
+            show_mangled: false,
+            labels: !opts.no_labels,
+            arrows: opts.arrow_style(),
+            theme,
+            archive_member: None,
+            marker: Some(marker),
+            aliases: &[],
+            frame: None,
+            unwind: &[],
+            call_sites: &[],
+            costs: None,
+            samples: None,
+            executed: None,
+            panic_paths: &[],
+            stats: None,
+            checks: None,
+        },
+    )?;
+    out.reset()?;
+    Ok(())
+}
+
+fn print_addresses(
+    out: &mut dyn WriteColor,
+    infos: &[AddressInfo],
+    contexts: &[Option<AddressContext>],
+    opts: &Opts,
+    bin: &Binary,
+) -> anyhow::Result<()> {
+    let theme = opts.theme()?;
+
+    let mut clr_addr = ColorSpec::new();
+    clr_addr.set_fg(Some(Color::Blue));
+
+    let mut clr_name = ColorSpec::new();
+    clr_name.set_fg(Some(Color::Cyan)).set_bold(true);
+
+    let mut clr_location = ColorSpec::new();
+    clr_location.set_fg(Some(Color::Magenta));
+
+    for (idx, info) in infos.iter().enumerate() {
+        out.set_color(&clr_addr)?;
+        write!(out, "0x{:x}", info.address)?;
+        out.reset()?;
+        write!(out, ": ")?;
+
+        match info.symbol {
+            Some((symbol, offset)) => {
+                out.set_color(&clr_name)?;
+                write!(out, "{}", symbol.name())?;
+                out.reset()?;
+                if offset > 0 {
+                    write!(out, "+0x{:x}", offset)?;
+                }
+            }
+            None => write!(out, "??")?,
+        }
+        if let Some(file_offset) = info.file_offset {
+            write!(out, " (file offset 0x{:x})", file_offset)?;
+        }
+        writeln!(out)?;
+
+        out.set_color(&clr_location)?;
+        for &(path, line) in info.locations.iter() {
+            writeln!(out, "    at {}:{}", path.display(), line)?;
+        }
+        out.reset()?;
+
+        if let Some(Some(context)) = contexts.get(idx) {
+            writeln!(out)?;
+            print_context(out, context, info.address, opts, bin, &theme)?;
+            writeln!(out)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,217 @@
+use crate::disasm::{Disassembly, Jump};
+use std::io;
+use termcolor::{Color, ColorSpec, WriteColor};
+
+/// The maximum number of columns that can be used for drawing arrows.
+/// Jumps that don't fit are just not drawn.
+const MAX_COLUMNS: usize = 8;
+
+const COLORS: &[Color] = &[
+    Color::Cyan,
+    Color::Green,
+    Color::Magenta,
+    Color::Yellow,
+    Color::Blue,
+    Color::Red,
+];
+
+/// The characters used for drawing jump arrows.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum ArrowStyle {
+    #[default]
+    Unicode,
+    Ascii,
+}
+
+impl std::str::FromStr for ArrowStyle {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("unicode") {
+            Ok(ArrowStyle::Unicode)
+        } else if s.eq_ignore_ascii_case("ascii") {
+            Ok(ArrowStyle::Ascii)
+        } else {
+            Err("invalid arrow style")
+        }
+    }
+}
+
+impl ArrowStyle {
+    fn vertical(self) -> char {
+        match self {
+            ArrowStyle::Unicode => '│',
+            ArrowStyle::Ascii => '|',
+        }
+    }
+
+    fn horizontal(self) -> char {
+        match self {
+            ArrowStyle::Unicode => '─',
+            ArrowStyle::Ascii => '-',
+        }
+    }
+
+    fn top_corner(self) -> char {
+        match self {
+            ArrowStyle::Unicode => '╭',
+            ArrowStyle::Ascii => '+',
+        }
+    }
+
+    fn bottom_corner(self) -> char {
+        match self {
+            ArrowStyle::Unicode => '╰',
+            ArrowStyle::Ascii => '+',
+        }
+    }
+
+    fn cross(self) -> char {
+        match self {
+            ArrowStyle::Unicode => '┼',
+            ArrowStyle::Ascii => '+',
+        }
+    }
+}
+
+struct Arrow {
+    /// Index of the line that jumps.
+    src: usize,
+    /// Index of the line that is jumped to.
+    dst: usize,
+    column: usize,
+}
+
+impl Arrow {
+    fn top(&self) -> usize {
+        self.src.min(self.dst)
+    }
+
+    fn bottom(&self) -> usize {
+        self.src.max(self.dst)
+    }
+}
+
+/// Arrows for the jumps inside of a function that are drawn in the gutter
+/// to the left of the listing.
+pub struct JumpArrows {
+    arrows: Vec<Arrow>,
+    columns: usize,
+    style: ArrowStyle,
+}
+
+impl JumpArrows {
+    pub fn new(dis: &Disassembly, style: ArrowStyle) -> JumpArrows {
+        let mut jumps = dis
+            .lines()
+            .iter()
+            .enumerate()
+            .filter_map(|(src, line)| match line.jump() {
+                Jump::Internal(dst) => Some((src, dst)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        // Shorter jumps are placed in the columns closest to the listing
+        // so that there are less crossing lines.
+        jumps.sort_by_key(|&(src, dst)| src.max(dst) - src.min(dst));
+
+        let mut arrows: Vec<Arrow> = Vec::with_capacity(jumps.len());
+        let mut columns = 0;
+        for (src, dst) in jumps {
+            let (top, bottom) = (src.min(dst), src.max(dst));
+            let column = (0..MAX_COLUMNS).find(|&column| {
+                !arrows
+                    .iter()
+                    .any(|a| a.column == column && a.top() <= bottom && top <= a.bottom())
+            });
+
+            if let Some(column) = column {
+                columns = columns.max(column + 1);
+                arrows.push(Arrow { src, dst, column });
+            }
+        }
+
+        // Outer arrows are drawn first so that the corners of inner arrows are drawn over
+        // their horizontal lines.
+        arrows.sort_by_key(|a| std::cmp::Reverse(a.column));
+
+        JumpArrows {
+            arrows,
+            columns,
+            style,
+        }
+    }
+
+    /// Writes the gutter for the row containing the instruction at the given line index.
+    pub fn write_line(&self, out: &mut dyn WriteColor, line: usize) -> io::Result<()> {
+        self.write_row(out, line, true)
+    }
+
+    /// Writes the gutter for a row that is printed before the instruction at the given
+    /// line index (e.g. source lines).
+    pub fn write_before(&self, out: &mut dyn WriteColor, line: usize) -> io::Result<()> {
+        self.write_row(out, line, false)
+    }
+
+    fn write_row(&self, out: &mut dyn WriteColor, line: usize, is_line: bool) -> io::Result<()> {
+        if self.columns == 0 {
+            return Ok(());
+        }
+
+        // Each column is two characters wide and the last character is for the arrow head.
+        let width = self.columns * 2 + 1;
+        let mut cells: Vec<(char, Option<usize>)> = vec![(' ', None); width];
+
+        for arrow in self.arrows.iter() {
+            let x = (self.columns - 1 - arrow.column) * 2;
+            let passes = if is_line {
+                arrow.top() < line && line < arrow.bottom()
+            } else {
+                arrow.top() < line && line <= arrow.bottom()
+            };
+
+            if passes {
+                cells[x] = (self.style.vertical(), Some(arrow.column));
+            }
+        }
+
+        if is_line {
+            for arrow in self.arrows.iter() {
+                if line != arrow.src && line != arrow.dst {
+                    continue;
+                }
+
+                let x = (self.columns - 1 - arrow.column) * 2;
+                let corner = if line == arrow.top() && arrow.top() != arrow.bottom() {
+                    self.style.top_corner()
+                } else {
+                    self.style.bottom_corner()
+                };
+                cells[x] = (corner, Some(arrow.column));
+
+                for cell in &mut cells[(x + 1)..(width - 1)] {
+                    if cell.0 == ' ' {
+                        *cell = (self.style.horizontal(), Some(arrow.column));
+                    } else if cell.0 == self.style.vertical() {
+                        cell.0 = self.style.cross();
+                    }
+                }
+
+                if line == arrow.dst {
+                    cells[width - 1] = ('>', Some(arrow.column));
+                } else if cells[width - 1].0 != '>' {
+                    cells[width - 1] = (self.style.horizontal(), Some(arrow.column));
+                }
+            }
+        }
+
+        let mut color = ColorSpec::new();
+        for (c, column) in cells {
+            color.set_fg(column.map(|column| COLORS[column % COLORS.len()]));
+            out.set_color(&color)?;
+            write!(out, "{}", c)?;
+        }
+        out.reset()
+    }
+}
@@ -0,0 +1,125 @@
+use super::printer::local_labels;
+use crate::disasm::{
+    binary::{Arch, Binary},
+    symbol::Symbol,
+    Disassembly, Jump, Syntax,
+};
+use std::fmt;
+use std::io::Write;
+
+/// Writes the directives that come before the functions in the assembly.
+pub fn print_header(out: &mut dyn Write, arch: Arch, syntax: Syntax) -> anyhow::Result<()> {
+    // The GNU assembler reads AT&T syntax by default.
+    if matches!(arch, Arch::X86 | Arch::X86_64) && syntax == Syntax::Intel {
+        writeln!(out, "\t.intel_syntax noprefix")?;
+    }
+    writeln!(out, "\t.text")?;
+    Ok(())
+}
+
+/// Writes the disassembly of a function as GNU assembler source in the style of
+/// `rustc --emit asm`, without addresses or bytes. Jumps inside of the function go to
+/// `.LBB` labels and calls and jumps to other functions go to their (mangled) names, so
+/// that the output can be assembled again. `index` numbers the labels of the function.
+pub fn print_disassembly(
+    out: &mut dyn Write,
+    bin: &Binary,
+    index: usize,
+    symbol: &Symbol,
+    disassembly: &Disassembly,
+) -> anyhow::Result<()> {
+    let comment = comment_prefix(bin.arch());
+    let name = AsmName(symbol.mangled_name());
+    writeln!(out)?;
+    writeln!(out, "\t.globl\t{}", name)?;
+    writeln!(out, "{}:", name)?;
+    if symbol.mangled_name() != symbol.name() {
+        writeln!(out, "{} {}", comment, symbol.name())?;
+    }
+
+    let lines = disassembly.lines();
+    let labels = local_labels(disassembly);
+    for (idx, line) in lines.iter().enumerate() {
+        if let Some(&label) = labels.get(&idx) {
+            writeln!(out, ".LBB{}_{}:", index, label)?;
+        }
+
+        let target = match line.jump() {
+            Jump::Internal(target) => labels
+                .get(&target)
+                .map(|&label| (lines[target].address(), format!(".LBB{}_{}", index, label))),
+            Jump::External(address) => bin.symbolicate(address).map(|(target, offset)| {
+                let name = AsmName(target.mangled_name()).to_string();
+                if offset == 0 {
+                    (address, name)
+                } else {
+                    (address, format!("{}+0x{:x}", name, offset))
+                }
+            }),
+            _ => None,
+        };
+        let operands = match target {
+            Some((address, ref target)) => replace_address(line.operands(), address, target),
+            None => line.operands().to_string(),
+        };
+
+        if operands.is_empty() {
+            write!(out, "\t{}", line.mnemonic())?;
+        } else {
+            write!(out, "\t{}\t{}", line.mnemonic(), operands)?;
+        }
+        // The target of a jump is already in its operands.
+        if target.is_none() && !line.comments().is_empty() {
+            write!(out, "\t{} {}", comment, line.comments())?;
+        }
+        writeln!(out)?;
+    }
+
+    writeln!(out, ".Lfunc_end{}:", index)?;
+    Ok(())
+}
+
+/// Replaces the address of a jump target in operands, including an `#` or `$` before it.
+pub(super) fn replace_address(operands: &str, address: u64, target: &str) -> String {
+    let hex = format!("0x{:x}", address);
+    match operands.find(&hex) {
+        Some(start) => {
+            let end = start + hex.len();
+            let start = start - operands[..start].ends_with(['#', '$']) as usize;
+            format!("{}{}{}", &operands[..start], target, &operands[end..])
+        }
+        None => operands.to_string(),
+    }
+}
+
+/// The characters that start a comment in GNU assembler source for an architecture.
+fn comment_prefix(arch: Arch) -> &'static str {
+    match arch {
+        Arch::AArch64 => "//",
+        Arch::Arm | Arch::Thumb => "@",
+        Arch::Sparc | Arch::Sparc64 => "!",
+        _ => "#",
+    }
+}
+
+/// A symbol name that is quoted if it has characters that the assembler doesn't allow in
+/// names.
+struct AsmName<'a>(&'a str);
+
+impl fmt::Display for AsmName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plain = self
+            .0
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '$');
+        if plain && !self.0.is_empty() {
+            f.write_str(self.0)
+        } else {
+            write!(
+                f,
+                "\"{}\"",
+                self.0.replace('\\', "\\\\").replace('"', "\\\"")
+            )
+        }
+    }
+}
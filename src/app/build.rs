@@ -0,0 +1,106 @@
+use super::cli::{Opts, TargetKind};
+use anyhow::Context as _;
+use cargo_metadata::Message;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Runs `cargo build` with the Cargo options that were passed in on the command line and
+/// returns the path of the binary, example, test, or benchmark executable that was built.
+/// If the package has no binaries then its `cdylib` or `staticlib` is used instead.
+pub fn build_binary(opts: &Opts) -> anyhow::Result<PathBuf> {
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let mut cmd = Command::new(cargo);
+    cmd.arg("build")
+        .arg("--message-format=json-render-diagnostics")
+        .stdout(Stdio::piped());
+
+    if let Some(ref manifest_path) = opts.manifest_path {
+        cmd.arg("--manifest-path").arg(manifest_path);
+    }
+    if let Some(ref package) = opts.package {
+        cmd.args(["--package", package]);
+    }
+    let (kind, name) = opts.target();
+    if let Some(name) = name {
+        cmd.arg(format!("--{}", kind.as_str())).arg(name);
+    }
+    if let Some(ref triple) = opts.target_triple {
+        cmd.args(["--target", triple]);
+    }
+    if opts.release {
+        cmd.arg("--release");
+    }
+    if let Some(ref profile) = opts.profile {
+        cmd.args(["--profile", profile]);
+    }
+    if let Some(ref features) = opts.features {
+        cmd.args(["--features", features]);
+    }
+    if opts.all_features {
+        cmd.arg("--all-features");
+    }
+    if opts.no_default_features {
+        cmd.arg("--no-default-features");
+    }
+
+    log::debug!("running {:?}", cmd);
+    let mut child = cmd.spawn().context("failed to run `cargo build`")?;
+
+    let mut binaries = Vec::new();
+    let mut libraries = Vec::new();
+    let stdout = child.stdout.take().expect("missing cargo stdout");
+    for message in Message::parse_stream(BufReader::new(stdout)) {
+        let message = message.context("failed to read the output of `cargo build`")?;
+        if let Message::CompilerArtifact(artifact) = message {
+            if artifact.target.kind.iter().any(|k| k == kind.as_str()) {
+                if let Some(executable) = artifact.executable {
+                    binaries.push((artifact.target.name, executable));
+                }
+            } else if name.is_none() && kind == TargetKind::Bin {
+                if let Some(library) = library_file(&artifact.filenames) {
+                    libraries.push((artifact.target.name, library));
+                }
+            }
+        }
+    }
+
+    let status = child.wait().context("failed to run `cargo build`")?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("`cargo build` failed ({})", status));
+    }
+
+    // Packages without binaries can still build libraries that can be disassembled.
+    if binaries.is_empty() {
+        binaries = libraries;
+    }
+
+    if binaries.len() > 1 {
+        let mut s = String::from("multiple binaries were built:");
+        for (name, path) in binaries {
+            s.push_str(&format!("\n    - `{}` ({})", name, path.display()));
+        }
+        s.push_str("\n\nuse `--bin` to choose one of them");
+        return Err(anyhow::anyhow!(s));
+    }
+
+    binaries
+        .pop()
+        .map(|(_, path)| path)
+        .context("`cargo build` did not build any binaries")
+}
+
+/// Returns the dynamic or static library in the files of a `cdylib` or `staticlib`
+/// artifact, preferring dynamic libraries.
+fn library_file(filenames: &[PathBuf]) -> Option<PathBuf> {
+    let has_extension = |extensions: &[&str]| {
+        filenames.iter().find(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| extensions.contains(&ext))
+        })
+    };
+    has_extension(&["so", "dylib", "dll", "wasm"])
+        .or_else(|| has_extension(&["a", "lib"]))
+        .cloned()
+}
@@ -0,0 +1,100 @@
+use crate::disasm::callgraph::CallGraph;
+use termcolor::{Color, ColorSpec, WriteColor};
+
+/// Prints the callees and callers of the root node as trees, or every function and its
+/// callees if there is no root.
+pub fn print_callgraph(
+    out: &mut dyn WriteColor,
+    graph: &CallGraph,
+    root: Option<usize>,
+    depth: usize,
+) -> anyhow::Result<()> {
+    let mut clr_name = ColorSpec::new();
+    clr_name.set_fg(Some(Color::Cyan)).set_bold(true);
+
+    let mut clr_heading = ColorSpec::new();
+    clr_heading.set_italic(true).set_fg(Some(Color::Yellow));
+
+    let root = if let Some(root) = root {
+        root
+    } else {
+        for (node, info) in graph.nodes().iter().enumerate() {
+            if graph.callees(node).is_empty() {
+                continue;
+            }
+
+            out.set_color(&clr_name)?;
+            writeln!(out, "{}:", info.name())?;
+            out.reset()?;
+            for &callee in graph.callees(node) {
+                writeln!(out, "  -> {}", graph.nodes()[callee].name())?;
+            }
+        }
+        return Ok(());
+    };
+
+    out.set_color(&clr_name)?;
+    writeln!(out, "{}:", graph.nodes()[root].name())?;
+
+    let mut path = vec![root];
+
+    out.set_color(&clr_heading)?;
+    writeln!(out, "  ; callees")?;
+    out.reset()?;
+    print_tree(out, graph, CallGraph::callees, &mut path, "  ", depth)?;
+
+    out.set_color(&clr_heading)?;
+    writeln!(out, "  ; callers")?;
+    out.reset()?;
+    print_tree(out, graph, CallGraph::callers, &mut path, "  ", depth)?;
+
+    Ok(())
+}
+
+fn print_tree<'g, 'b>(
+    out: &mut dyn WriteColor,
+    graph: &'g CallGraph<'b>,
+    adjacent: fn(&'g CallGraph<'b>, usize) -> &'g [usize],
+    path: &mut Vec<usize>,
+    prefix: &str,
+    depth: usize,
+) -> anyhow::Result<()> {
+    let node = *path.last().expect("path should contain the root");
+    let children = adjacent(graph, node);
+
+    if depth == 0 {
+        if !children.is_empty() {
+            writeln!(out, "{}└── ...", prefix)?;
+        }
+        return Ok(());
+    }
+
+    for (idx, &child) in children.iter().enumerate() {
+        let last = idx + 1 == children.len();
+        let (branch, indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+
+        let name = graph.nodes()[child].name();
+        if path.contains(&child) {
+            writeln!(out, "{}{}{} (recursive)", prefix, branch, name)?;
+            continue;
+        }
+
+        writeln!(out, "{}{}{}", prefix, branch, name)?;
+        path.push(child);
+        print_tree(
+            out,
+            graph,
+            adjacent,
+            path,
+            &format!("{}{}", prefix, indent),
+            depth - 1,
+        )?;
+        path.pop();
+    }
+
+    Ok(())
+}
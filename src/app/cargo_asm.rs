@@ -0,0 +1,90 @@
+use super::asm::replace_address;
+use super::printer::local_labels;
+use crate::disasm::{binary::Binary, symbol::Symbol, Disassembly, Jump};
+use std::path::Path;
+use termcolor::WriteColor;
+
+/// Writes the disassembly of a function like `cargo asm` does: the instructions without
+/// addresses or bytes, indented by a space, with `.LBB` labels for jumps inside of the
+/// function and the names of the functions that are called. With `rust` the source lines
+/// are interleaved with the instructions, but the source of the standard library is folded
+/// into a single comment with its file for each run of instructions that came from it.
+pub fn print_disassembly(
+    out: &mut dyn WriteColor,
+    bin: &Binary,
+    symbol: &Symbol,
+    disassembly: &Disassembly,
+    rust: bool,
+) -> anyhow::Result<()> {
+    writeln!(out, "{}:", symbol.name())?;
+
+    let lines = disassembly.lines();
+    let labels = local_labels(disassembly);
+    let mut last_location = None;
+    let mut last_std_file = None;
+    for (idx, line) in lines.iter().enumerate() {
+        if rust {
+            if let Some(location) = line.source_locations().last() {
+                match std_library_file(location.path()) {
+                    Some(file) if last_std_file != Some(file) => {
+                        writeln!(out, " // {}", file)?;
+                        last_std_file = Some(file);
+                    }
+                    Some(_) => {}
+                    None if last_location != Some(location) => {
+                        for source_line in line.source_lines() {
+                            writeln!(out, "{}", source_line)?;
+                        }
+                        last_std_file = None;
+                    }
+                    None => {}
+                }
+                last_location = Some(location);
+            }
+        }
+
+        if let Some(&label) = labels.get(&idx) {
+            writeln!(out, ".LBB0_{}:", label)?;
+        }
+
+        let target = match line.jump() {
+            Jump::Internal(target) => labels
+                .get(&target)
+                .map(|&label| (lines[target].address(), format!(".LBB0_{}", label))),
+            Jump::External(address) => {
+                bin.symbolicate(address)
+                    .map(|(target, offset)| match offset {
+                        0 => (address, target.name().to_string()),
+                        _ => (address, format!("{}+0x{:x}", target.name(), offset)),
+                    })
+            }
+            _ => None,
+        };
+        let operands = match target {
+            Some((address, ref target)) => replace_address(line.operands(), address, target),
+            None => line.operands().to_string(),
+        };
+        if operands.is_empty() {
+            writeln!(out, " {}", line.mnemonic())?;
+        } else {
+            writeln!(out, " {:<7} {}", line.mnemonic(), operands)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the path of a source file of the standard library relative to its `library`
+/// directory, e.g. `core/src/slice/iter.rs`, if it is one.
+fn std_library_file(path: &Path) -> Option<&str> {
+    let path = path.to_str()?;
+    let start = path
+        .find("/library/")
+        .map(|idx| idx + "/library/".len())
+        .or_else(|| path.find("/src/lib").map(|idx| idx + "/src/".len()))?;
+    // Only the files of the standard library that come with rustc.
+    if path.contains("/rustc/") || path.contains("/rustlib/") {
+        Some(&path[start..])
+    } else {
+        None
+    }
+}
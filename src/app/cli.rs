@@ -1,73 +1,698 @@
-use clap::Clap;
+use super::arrows::ArrowStyle;
+use super::config::Config;
+use super::map::MapStyle;
+use super::printer::{ByteOrder, BytesFormat};
+use super::theme::{Theme, THEME_NAMES};
+use crate::disasm::{
+    binary::{Arch, Endian},
+    Engine, Syntax,
+};
+use clap::{AppSettings, Clap};
 use std::path::PathBuf;
 use termcolor::ColorChoice;
 
 #[derive(Clap)]
-#[clap(name = "cargo-disasm", version = env!("CARGO_PKG_VERSION"), author = "Marc C.")]
+#[clap(
+    name = "cargo-disasm",
+    version = env!("CARGO_PKG_VERSION"),
+    author = "Marc C.",
+    setting = AppSettings::SubcommandsNegateReqs
+)]
 pub struct Opts {
     /// The name of the symbol to match and disassemble.
-    pub symbol: String,
+    #[clap(required_unless_present_any = &["all", "callgraph", "tui", "sizes", "raw"])]
+    pub symbol: Option<String>,
+
+    /// Treat the symbol name as a regular expression and disassemble
+    /// every symbol that matches it.
+    #[clap(long = "regex")]
+    pub regex: bool,
+
+    /// Disassemble every function symbol found in the binary. When this is
+    /// used the first positional argument is the path of the binary.
+    #[clap(long = "all", conflicts_with = "regex")]
+    pub all: bool,
+
+    /// List the symbols that are most similar to the symbol name and choose which one
+    /// to disassemble.
+    #[clap(long = "pick", conflicts_with_all = &["regex", "all"])]
+    pub pick: bool,
 
     /// Path of the binary to disassemble. This can be left unspecified if the
     /// Cargo options are going to be used instead or if the current directory
     /// contains a Cargo project with one binary target.
     pub binary_path: Option<PathBuf>,
 
+    /// Path of the binary, object file, or archive to disassemble. This is the same as
+    /// passing the path after the symbol.
+    #[clap(long = "file", global = true, conflicts_with = "binary-path")]
+    pub file: Option<PathBuf>,
+
     /// Comma separated list of sources that will be used for finding symbols.
     /// By default this is `auto`.
     ///
-    /// Possible values are: auto, dwarf, pdb, elf, pe, mach, archive,
-    /// obj (elf + pe + mach + archive), debug (dwarf + pdb),
+    /// Possible values are: auto, dwarf, pdb, elf, pe, mach, archive, wasm,
+    /// obj (elf + pe + mach + archive + wasm), debug (dwarf + pdb),
     /// all (use everything)
-    #[clap(
-        long = "symsrc",
-        multiple = true,
-        use_delimiter = true,
-        default_value = "auto"
-    )]
+    #[clap(long = "symsrc", global = true, multiple = true, use_delimiter = true)]
     pub symbol_sources: Vec<String>,
 
     /// Path to Cargo.toml
-    #[clap(long = "manifest-path")]
+    #[clap(long = "manifest-path", global = true)]
     pub manifest_path: Option<PathBuf>,
 
     /// When using a Cargo project, this option can be used to search
     /// a specific package for a binary.
-    #[clap(short = 'p', long = "package")]
+    #[clap(short = 'p', long = "package", global = true)]
     pub package: Option<String>,
 
     /// When using a Cargo project, this option can be used to search for
-    /// a target with a specific name.
-    #[clap(short = 't', long = "target")]
+    /// a binary target with a specific name.
+    #[clap(short = 't', long = "bin", global = true)]
     pub target_name: Option<String>,
 
+    /// When using a Cargo project, disassemble the example with this name.
+    #[clap(long = "example", global = true, conflicts_with_all = &["target-name", "test", "bench"])]
+    pub example: Option<String>,
+
+    /// When using a Cargo project, disassemble the executable of the integration test
+    /// with this name.
+    #[clap(long = "test", global = true, conflicts_with_all = &["target-name", "bench"])]
+    pub test: Option<String>,
+
+    /// When using a Cargo project, disassemble the executable of the benchmark with
+    /// this name.
+    #[clap(long = "bench", global = true, conflicts_with = "target-name")]
+    pub bench: Option<String>,
+
+    /// When using a Cargo project, disassemble the build artifacts for the given
+    /// target triple (e.g. wasm32-unknown-unknown).
+    #[clap(long = "target", global = true)]
+    pub target_triple: Option<String>,
+
+    /// Path to the dSYM bundle with the debug information of a Mach-O binary. By default
+    /// the bundle is searched for next to the binary and with Spotlight.
+    #[clap(long = "dsym", global = true)]
+    pub dsym_path: Option<PathBuf>,
+
+    /// Path to the separate debug file of a stripped ELF binary. By default the debug
+    /// file is found using the build ID or `.gnu_debuglink` of the binary.
+    #[clap(long = "debug-file", global = true)]
+    pub debug_file_path: Option<PathBuf>,
+
+    /// Disassemble a shared library that the binary depends on instead, e.g. `libssl.so.3`
+    /// or `libssl`. The library is found using the search paths of the binary, like the
+    /// dynamic linker does.
+    #[clap(long = "lib", global = true)]
+    pub library: Option<String>,
+
+    /// Disassemble a binary without a container format, such as a flat firmware image or an
+    /// Intel HEX or S-record file. Each contiguous part of the binary is disassembled as one
+    /// function and the first positional argument is the path of the binary.
+    #[clap(long = "raw", requires = "arch")]
+    pub raw: bool,
+
+    /// The architecture of a `--raw` binary: x86, x86_64, arm, thumb, or aarch64.
+    #[clap(long = "arch", requires = "raw", parse(try_from_str = parse_arch))]
+    pub arch: Option<Arch>,
+
+    /// The address that a flat `--raw` binary is loaded at, e.g. 0x08000000. Intel HEX and
+    /// S-record files contain their addresses.
+    #[clap(long = "base", requires = "raw", default_value = "0", parse(try_from_str = parse_address))]
+    pub base: u64,
+
+    /// When disassembling a static library or rlib, only search the object file
+    /// with this name.
+    #[clap(long = "member")]
+    pub archive_member: Option<String>,
+
     /// Disassemble the release mode build artifacts.
-    #[clap(long = "release")]
+    #[clap(long = "release", global = true)]
     pub release: bool,
 
+    /// Disassemble the build artifacts of a Cargo profile, e.g. `bench` or a custom
+    /// profile.
+    #[clap(long = "profile", global = true, conflicts_with = "release")]
+    pub profile: Option<String>,
+
+    /// Run `cargo build` with the Cargo options first and disassemble the binary that
+    /// it builds.
+    #[clap(long = "build", global = true, conflicts_with_all = &["binary-path", "file"])]
+    pub build: bool,
+
+    /// Keep running and rebuild with `--build` whenever a file of the workspace changes,
+    /// then print what changed in the disassembly of the symbol since the previous build.
+    #[clap(long = "watch", requires_all = &["build", "symbol"], conflicts_with_all = &["all", "regex", "pick", "tui"])]
+    pub watch: bool,
+
+    /// Space or comma separated list of features to activate with `--build`.
+    #[clap(long = "features", global = true, requires = "build")]
+    pub features: Option<String>,
+
+    /// Activate all of the features of the package with `--build`.
+    #[clap(long = "all-features", global = true, requires = "build")]
+    pub all_features: bool,
+
+    /// Don't activate the default features of the package with `--build`.
+    #[clap(long = "no-default-features", global = true, requires = "build")]
+    pub no_default_features: bool,
+
+    /// Don't use or update the cache of the symbols and line information that were read
+    /// from the debug information of binaries.
+    #[clap(long = "no-cache", global = true)]
+    pub no_cache: bool,
+
+    /// Keep the local labels and mapping symbols that are emitted inside of functions (e.g.
+    /// `.LBB0_1`, `ltmp0`, `$d`, and `$x`), which are hidden by default.
+    #[clap(long = "include-locals", global = true)]
+    pub include_locals: bool,
+
+    /// Hide the functions that were generated by the compiler without a source function,
+    /// such as outlined functions (`OUTLINED_FUNCTION_0`) and retpoline thunks.
+    #[clap(long = "exclude-compiler-generated", global = true)]
+    pub exclude_compiler_generated: bool,
+
+    /// Show and search for the mangled names of symbols instead of demangling them, e.g. to
+    /// compare them with the output of `nm` or a linker map.
+    #[clap(long = "no-demangle", global = true)]
+    pub no_demangle: bool,
+
+    /// Keep the hashes in demangled Rust names, which are the `::h0123456789abcdef` suffix
+    /// of legacy names and the crate disambiguators of v0 names.
+    #[clap(long = "keep-hashes", global = true, conflicts_with = "no-demangle")]
+    pub keep_hashes: bool,
+
+    /// Show the mangled name of a symbol next to its demangled name.
+    #[clap(long = "show-mangled", global = true, conflicts_with = "no-demangle")]
+    pub show_mangled: bool,
+
     /// Sets the log level: (default)=+error, 0=+warning, 1=+info, 2=+debug, 3=+trace
     /// The `quiet` flag can be used to turn off logging completely.
-    #[clap(short, long, parse(from_occurrences))]
+    #[clap(short, long, global = true, parse(from_occurrences))]
     pub verbose: u32,
 
     /// Disables logging.
-    #[clap(short, long)]
+    #[clap(short, long, global = true)]
     pub quiet: bool,
 
-    /// Coloring: auto, always, never, and always-ansi (only uses ansi color codes).
-    #[clap(long = "color", default_value = "auto", parse(try_from_str = parse_colorchoice))]
-    pub color_choice: ColorChoice,
+    /// Coloring: auto (default), always, never, and always-ansi (only uses ansi color codes).
+    #[clap(long = "color", global = true, parse(try_from_str = parse_colorchoice))]
+    pub color_choice: Option<ColorChoice>,
 
     /// If it is available, show source code alongside disassembly.
-    #[clap(short = 'S', long = "show-source")]
+    #[clap(short = 'S', long = "show-source", alias = "rust")]
     pub show_source: bool,
 
+    /// Print the disassembly like `cargo asm`, without addresses and with the names of
+    /// the functions that are called. With `--rust` (or `--show-source`) the source lines
+    /// of the function are interleaved, but the source of the standard library is folded.
+    #[clap(long = "cargo-asm")]
+    pub cargo_asm: bool,
+
+    /// If it is available, mark the instructions where inlined function calls begin.
+    #[clap(long = "show-inlined")]
+    pub show_inlined: bool,
+
+    /// Comment the instructions that spill local variables to the stack, load them back, or
+    /// start keeping them in a register with the names of the variables, from their
+    /// locations in the DWARF debug information.
+    #[clap(long = "vars")]
+    pub vars: bool,
+
+    /// Comment the instructions that follow a recognizable idiom with pseudocode, e.g.
+    /// `rax = rdi + rsi*4` for `lea rax, [rdi + rsi*4]`, and the conditional jumps after a
+    /// comparison with their conditions, including the bounds checks that panic.
+    #[clap(long = "explain")]
+    pub explain: bool,
+
     /// Show the bytes for each opcode alongside disassembly.
     #[clap(short = 'B', long = "show-bytes")]
     pub show_bytes: bool,
+
+    /// Hide the bytes for each opcode. This overrides `--show-bytes`.
+    #[clap(long = "hide-bytes", overrides_with = "show-bytes")]
+    pub hide_bytes: bool,
+
+    /// Show the share of the samples of a profile that were in each instruction, from a
+    /// `perf.data` file or the output of `perf script`.
+    #[clap(long = "perf", parse(from_os_str))]
+    pub perf: Option<PathBuf>,
+
+    /// Mark the basic blocks that were executed (`+`) or not (`-`) according to coverage
+    /// from `-C instrument-coverage`: a `.profraw` or `.profdata` file, which needs
+    /// `llvm-profdata` and `llvm-cov`, or an LCOV file.
+    #[clap(long = "coverage", parse(from_os_str))]
+    pub coverage: Option<PathBuf>,
+
+    /// Show the estimated latency and reciprocal throughput of each instruction in cycles,
+    /// and the totals for each basic block. Estimates are only available for x86, x86_64,
+    /// and AArch64.
+    #[clap(long = "cost")]
+    pub cost: bool,
+
+    /// Hide the size of the stack frame and the saved registers that are shown below the
+    /// name of each function.
+    #[clap(long = "no-frame")]
+    pub no_frame: bool,
+
+    /// Fold the paths that only lead to a panic, such as failed bounds checks and
+    /// `unwrap`s, into a single line with the function that panics.
+    #[clap(long = "fold-panics")]
+    pub fold_panics: bool,
+
+    /// Highlight the conditional jumps of the bounds checks and arithmetic overflow checks
+    /// that can panic, and list them below the name of each function, e.g. to see whether
+    /// `get_unchecked` or iterators removed them.
+    #[clap(long = "checks")]
+    pub checks: bool,
+
+    /// Count the instructions of each function by class (loads, stores, branches, calls,
+    /// and SIMD) and print the counts after its disassembly.
+    #[clap(long = "stats")]
+    pub stats: bool,
+
+    /// Instead of the disassembly, report whether the functions and their loops were
+    /// vectorized and with which SIMD extensions (SSE, AVX, AVX2, AVX-512, NEON, or SVE).
+    #[clap(long = "vectorized", conflicts_with_all = &["tui", "callgraph", "cfg"])]
+    pub vectorized: bool,
+
+    /// Show the unwind information of functions as the directives that describe it, from
+    /// `.eh_frame` or `.debug_frame` (`.cfi_*`) or from the `.pdata` and `.xdata` of x86_64
+    /// Windows binaries (`.seh_*`).
+    #[clap(long = "unwind")]
+    pub unwind: bool,
+
+    /// Show the addresses of jump targets inside of a function instead of labeling them,
+    /// e.g. `jne 0x401234` instead of `jne .LBB0_3`.
+    #[clap(long = "no-labels")]
+    pub no_labels: bool,
+
+    /// Mark the ranges of calls that unwind to landing pads and the landing pads that they
+    /// unwind to, which run drops (cleanup) or catch panics, e.g. in `catch_unwind`. These
+    /// are read from the exception tables in `.gcc_except_table`.
+    #[clap(long = "landing-pads")]
+    pub landing_pads: bool,
+
+    /// The number of bytes that are shown together without spaces between them, e.g. 4 to
+    /// show 32-bit instruction words. 0 shows all of the bytes of an opcode together.
+    /// The default is 1.
+    #[clap(long = "byte-group")]
+    pub byte_group: Option<usize>,
+
+    /// The order of the bytes in each group: memory (the order they are stored in) or value
+    /// (as a number using the byte order of the binary, like objdump). The default is memory.
+    #[clap(long = "byte-order", parse(try_from_str = parse_byte_order))]
+    pub byte_order: Option<ByteOrder>,
+
+    /// Stop disassembling a function at the first invalid instruction instead of showing
+    /// its bytes as `.byte` or `.word` data and continuing after it.
+    #[clap(long = "no-skip-data", global = true)]
+    pub no_skip_data: bool,
+
+    /// Assembly syntax for x86 and x86_64: intel or att.
+    /// The default can be set using the `CARGO_DISASM_SYNTAX` environment variable.
+    #[clap(long = "syntax", global = true, parse(try_from_str = parse_syntax))]
+    pub syntax: Option<Syntax>,
+
+    /// The disassembler to use: capstone (default) or iced. iced is only available for
+    /// x86 and x86_64 and if cargo-disasm was built with the `iced` feature.
+    #[clap(long = "engine", global = true, default_value = "capstone", parse(try_from_str = parse_engine))]
+    pub engine: Engine,
+
+    /// Colors used for disassembly: default, muted, or plain.
+    /// The default can be set using the `CARGO_DISASM_THEME` environment variable.
+    #[clap(long = "theme", global = true)]
+    pub theme: Option<String>,
+
+    /// Draw arrows for the jumps inside of a function in the left gutter: unicode (default)
+    /// or ascii.
+    #[clap(
+        long = "arrows",
+        require_equals = true,
+        min_values = 0,
+        default_missing_value = "unicode",
+        parse(try_from_str = parse_arrow_style)
+    )]
+    pub arrows: Option<ArrowStyle>,
+
+    /// Output the control flow graph of the function as a Graphviz DOT graph
+    /// instead of a listing. This is the same as `--format dot`.
+    #[clap(long = "cfg", conflicts_with = "all")]
+    pub cfg: bool,
+
+    /// Print the direct calls between functions instead of disassembly. If a symbol
+    /// is given then only the callers and callees of that symbol are printed.
+    #[clap(long = "callgraph", conflicts_with_all = &["all", "regex", "cfg"])]
+    pub callgraph: bool,
+
+    /// The number of levels of callers and callees that are printed by `--callgraph`.
+    #[clap(long = "depth", default_value = "3")]
+    pub depth: usize,
+
+    /// Browse the symbols and their disassembly in an interactive terminal UI. If a
+    /// symbol is given then its disassembly is opened first.
+    #[clap(long = "tui", conflicts_with_all = &["all", "regex", "pick", "cfg", "callgraph"])]
+    pub tui: bool,
+
+    /// List the functions in the binary from largest to smallest instead of disassembling
+    /// them. When this is used the first positional argument is the path of the binary.
+    #[clap(long = "sizes", conflicts_with_all = &["all", "regex", "pick", "cfg", "callgraph", "tui"])]
+    pub sizes: bool,
+
+    /// With `--sizes`, list the combined size of the functions of each crate instead.
+    #[clap(long = "crates", requires = "sizes")]
+    pub crates: bool,
+
+    /// Only disassemble or list the functions of a crate with `--all`, `--regex`, or
+    /// `--sizes`. Functions without a Rust path are attributed to the crate of their DWARF
+    /// compilation unit.
+    #[clap(long = "crate")]
+    pub crate_name: Option<String>,
+
+    /// The number of entries listed by `--sizes`, or 0 to list all of them.
+    #[clap(long = "limit", default_value = "20")]
+    pub limit: usize,
+
+    /// Output format: text, json, json-lines (disassembly only), dot (control flow graphs
+    /// and call graphs only), html (disassembly only), markdown (disassembly only), or asm
+    /// (disassembly only).
+    #[clap(long = "format", global = true, default_value = "text", parse(try_from_str = parse_output_format))]
+    pub format: OutputFormat,
+
+    /// Export the disassembly for another tool instead of printing it: mca (assembly for
+    /// `llvm-mca` with a code region for each function).
+    #[clap(long = "export", parse(try_from_str = parse_export), conflicts_with_all = &["cfg", "callgraph", "tui", "sizes"])]
+    pub export: Option<Export>,
+
+    /// Don't read the defaults for options from the configuration file
+    /// (`~/.config/cargo-disasm/config.toml` or `CARGO_DISASM_CONFIG`).
+    #[clap(long = "no-config", global = true)]
+    pub no_config: bool,
+
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+
+    /// The defaults that were read from the configuration file.
+    #[clap(skip)]
+    pub config: Config,
+}
+
+#[derive(Clap)]
+pub enum Command {
+    /// Compare the disassembly of a symbol in two builds of a binary.
+    Diff(DiffOpts),
+
+    /// Find the symbols and source lines that contain addresses, e.g. from a backtrace.
+    Addr(AddrOpts),
+
+    /// Find the functions of the frames of the crashing thread in a core dump or a Windows
+    /// minidump, and disassemble the instructions around them.
+    Core(CoreOpts),
+
+    /// Find the instructions of every function that match a regular expression, e.g.
+    /// `lock cmpxchg` or `rdtsc`, or the code that matches a byte pattern.
+    Grep(GrepOpts),
+
+    /// Print the format, architecture, linked libraries, build ID, debug information,
+    /// sections, and symbol counts of the binary.
+    Info,
+
+    /// Write the symbols of the binary as a symbol map for other tools, including the ones
+    /// that were found in debug information or by heuristics in a stripped binary.
+    Map(MapOpts),
+
+    /// List the sections of the binary with their addresses and sizes.
+    Sections,
+
+    /// Disassemble a whole section, including the code that isn't in any symbol.
+    Section(SectionOpts),
+}
+
+#[derive(Clap)]
+pub struct DiffOpts {
+    /// Path of the old build of the binary.
+    pub old_binary: PathBuf,
+
+    /// Path of the new build of the binary.
+    pub new_binary: PathBuf,
+
+    /// The name of the symbol to compare. If no symbol is given then every function
+    /// that changed between the two builds is listed instead.
+    pub symbol: Option<String>,
+}
+
+#[derive(Clap)]
+pub struct AddrOpts {
+    /// The addresses to look up, in hexadecimal with a `0x` prefix or in decimal.
+    #[clap(required = true, parse(try_from_str = parse_address))]
+    pub addresses: Vec<u64>,
+
+    /// Treat the addresses as positions in the file of the binary instead of virtual
+    /// addresses.
+    #[clap(long = "offset")]
+    pub file_offsets: bool,
+
+    /// Disassemble this many instructions before and after each address, even if it is in
+    /// the middle of a function or outside of any known symbol. Where the instructions
+    /// before an address start is found heuristically on x86 and x86_64.
+    #[clap(long = "context")]
+    pub context: Option<usize>,
+}
+
+#[derive(Clap)]
+pub struct CoreOpts {
+    /// Path of the core dump or minidump.
+    #[clap(parse(from_os_str))]
+    pub dump: PathBuf,
+
+    /// Disassemble this many instructions before and after the address of each frame, or
+    /// none with 0.
+    #[clap(long = "context", default_value = "8")]
+    pub context: usize,
+
+    /// The maximum number of frames to print. The frames after the crash are found by
+    /// scanning the stack for return addresses, so some of them may be stale.
+    #[clap(long = "max-frames", default_value = "32")]
+    pub max_frames: usize,
+}
+
+#[derive(Clap)]
+pub struct GrepOpts {
+    /// The regular expression that is matched against the mnemonic and operands of each
+    /// instruction, which are separated by a space. With `--bytes`, bytes in hexadecimal and
+    /// `??` wildcards, e.g. `48 8b ?? ??`.
+    pub pattern: String,
+
+    /// Match the pattern without regard to case.
+    #[clap(short = 'i', long = "ignore-case", conflicts_with = "bytes")]
+    pub ignore_case: bool,
+
+    /// Search the sections with code for a byte pattern instead of matching instructions.
+    /// Matches don't have to start at an instruction.
+    #[clap(long = "bytes")]
+    pub bytes: bool,
+}
+
+#[derive(Clap)]
+pub struct MapOpts {
+    /// The style of the lines of the map: nm (`address type name`, like `nm`) or perf
+    /// (`address size name`, like the `/tmp/perf-<pid>.map` files that `perf` reads).
+    /// `--format json` writes the symbols as JSON instead.
+    #[clap(long = "style", default_value = "nm", parse(try_from_str = parse_map_style))]
+    pub style: MapStyle,
+}
+
+#[derive(Clap)]
+pub struct SectionOpts {
+    /// The name of the section, e.g. `.text.unlikely`. Mach-O sections can be named with
+    /// or without their segment, e.g. `__TEXT,__text` or `__text`.
+    pub name: String,
+}
+
+/// The kinds of Cargo targets that can be disassembled.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum TargetKind {
+    Bin,
+    Example,
+    Test,
+    Bench,
+}
+
+impl TargetKind {
+    /// The name of the target kind that is used by Cargo.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TargetKind::Bin => "bin",
+            TargetKind::Example => "example",
+            TargetKind::Test => "test",
+            TargetKind::Bench => "bench",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human readable (and optionally colored) disassembly.
+    Text,
+    /// Structured JSON for consumption by other tools.
+    Json,
+    /// Newline-delimited JSON records that can be parsed as a stream.
+    JsonLines,
+    /// Graphviz DOT graphs.
+    Dot,
+    /// A standalone HTML page.
+    Html,
+    /// Fenced code blocks with a header for each symbol.
+    Markdown,
+    /// GNU assembler source without addresses, like `rustc --emit asm`.
+    Asm,
+}
+
+/// The formats that disassembly can be exported to for other tools.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Export {
+    /// Assembly with code regions for `llvm-mca`.
+    Mca,
 }
 
 impl Opts {
+    /// Returns the kind of Cargo target that should be disassembled and its name, which
+    /// is only optional for binaries.
+    pub fn target(&self) -> (TargetKind, Option<&str>) {
+        if let Some(ref name) = self.example {
+            (TargetKind::Example, Some(name))
+        } else if let Some(ref name) = self.test {
+            (TargetKind::Test, Some(name))
+        } else if let Some(ref name) = self.bench {
+            (TargetKind::Bench, Some(name))
+        } else {
+            (TargetKind::Bin, self.target_name.as_deref())
+        }
+    }
+
+    /// Returns the style of the jump arrows if they should be drawn.
+    pub fn arrow_style(&self) -> Option<ArrowStyle> {
+        self.arrows
+    }
+
+    /// Returns how the bytes of opcodes should be shown for a binary with the given
+    /// byte order.
+    pub fn bytes_format(&self, endian: Endian) -> anyhow::Result<BytesFormat> {
+        let byte_group = self.byte_group.or(self.config.byte_group).unwrap_or(1);
+        let byte_order = match self.byte_order {
+            Some(order) => order,
+            None => self
+                .config
+                .parse(
+                    "byte-order",
+                    self.config.byte_order.as_deref(),
+                    parse_byte_order,
+                )?
+                .unwrap_or_default(),
+        };
+
+        Ok(BytesFormat {
+            group_size: if byte_group == 0 {
+                usize::MAX
+            } else {
+                byte_group
+            },
+            reverse: byte_order == ByteOrder::Value && endian != Endian::Big,
+        })
+    }
+
+    /// Returns true if the bytes of opcodes should be shown, either because of
+    /// `--show-bytes` or because of the configuration file.
+    pub fn show_bytes(&self) -> bool {
+        (self.show_bytes || self.config.show_bytes == Some(true)) && !self.hide_bytes
+    }
+
+    /// Returns true if the instruction counts should be printed after each listing,
+    /// either because of `--stats` or because of the configuration file.
+    pub fn show_stats(&self) -> bool {
+        self.stats || self.config.stats == Some(true)
+    }
+
+    /// Returns true if every function is disassembled, either because of `--all` or
+    /// because a raw binary was passed without a symbol.
+    pub fn disassemble_all(&self) -> bool {
+        self.all || (self.raw && self.symbol.is_none())
+    }
+
+    /// Returns the coloring that was passed in on the command line
+    /// or the default from the configuration file if there was none.
+    pub fn color_choice(&self) -> anyhow::Result<ColorChoice> {
+        match self.color_choice {
+            Some(choice) => Ok(choice),
+            None => Ok(self
+                .config
+                .parse("color", self.config.color.as_deref(), parse_colorchoice)?
+                .unwrap_or(ColorChoice::Auto)),
+        }
+    }
+
+    /// Returns the symbol sources that were passed in on the command line
+    /// or the ones from the configuration file if there were none.
+    pub fn symbol_sources(&self) -> &[String] {
+        if self.symbol_sources.is_empty() {
+            &self.config.symsrc
+        } else {
+            &self.symbol_sources
+        }
+    }
+
+    /// Returns the color theme that was passed in on the command line
+    /// or the default theme from the environment or the configuration file
+    /// if there was none.
+    pub fn theme(&self) -> anyhow::Result<Theme> {
+        let (name, from_env) = match self.theme {
+            Some(ref name) => (name.clone(), false),
+            None => match std::env::var("CARGO_DISASM_THEME") {
+                Ok(name) => (name, true),
+                Err(_) => {
+                    let theme =
+                        self.config
+                            .parse("theme", self.config.theme.as_deref(), |name| {
+                                Theme::named(name).ok_or_else(|| invalid_theme(name))
+                            })?;
+                    return Ok(theme.unwrap_or_else(|| {
+                        Theme::named("default").expect("missing default theme")
+                    }));
+                }
+            },
+        };
+
+        Theme::named(&name).ok_or_else(|| {
+            anyhow::anyhow!(
+                "{}{}",
+                if from_env {
+                    "invalid CARGO_DISASM_THEME value: "
+                } else {
+                    ""
+                },
+                invalid_theme(&name)
+            )
+        })
+    }
+
+    /// Returns the assembly syntax that was passed in on the command line
+    /// or the default syntax from the environment or the configuration file
+    /// if there was none.
+    pub fn syntax(&self) -> anyhow::Result<Syntax> {
+        if let Some(syntax) = self.syntax {
+            return Ok(syntax);
+        }
+
+        match std::env::var("CARGO_DISASM_SYNTAX") {
+            Ok(s) => parse_syntax(&s)
+                .map_err(|err| anyhow::anyhow!("invalid CARGO_DISASM_SYNTAX value: {}", err)),
+            Err(_) => Ok(self
+                .config
+                .parse("syntax", self.config.syntax.as_deref(), parse_syntax)?
+                .unwrap_or_default()),
+        }
+    }
+
     pub fn log_level_filter(&self) -> log::LevelFilter {
         use log::LevelFilter;
 
@@ -84,6 +709,14 @@ impl Opts {
     }
 }
 
+fn invalid_theme(name: &str) -> String {
+    format!(
+        "{} is not a valid theme (available themes: {})",
+        name,
+        THEME_NAMES.join(", ")
+    )
+}
+
 pub fn parse_colorchoice(s: &str) -> Result<ColorChoice, String> {
     if s.eq_ignore_ascii_case("auto") {
         Ok(ColorChoice::Auto)
@@ -97,3 +730,73 @@ pub fn parse_colorchoice(s: &str) -> Result<ColorChoice, String> {
         Err(format!("{} is not a valid color value", s))
     }
 }
+
+pub fn parse_output_format(s: &str) -> Result<OutputFormat, String> {
+    if s.eq_ignore_ascii_case("text") {
+        Ok(OutputFormat::Text)
+    } else if s.eq_ignore_ascii_case("json") {
+        Ok(OutputFormat::Json)
+    } else if s.eq_ignore_ascii_case("json-lines")
+        || s.eq_ignore_ascii_case("jsonl")
+        || s.eq_ignore_ascii_case("ndjson")
+    {
+        Ok(OutputFormat::JsonLines)
+    } else if s.eq_ignore_ascii_case("dot") {
+        Ok(OutputFormat::Dot)
+    } else if s.eq_ignore_ascii_case("html") {
+        Ok(OutputFormat::Html)
+    } else if s.eq_ignore_ascii_case("markdown") || s.eq_ignore_ascii_case("md") {
+        Ok(OutputFormat::Markdown)
+    } else if s.eq_ignore_ascii_case("asm") || s.eq_ignore_ascii_case("s") {
+        Ok(OutputFormat::Asm)
+    } else {
+        Err(format!("{} is not a valid output format", s))
+    }
+}
+
+pub fn parse_address(s: &str) -> Result<u64, String> {
+    let result = if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16)
+    } else {
+        s.parse::<u64>()
+    };
+    result.map_err(|_| format!("{} is not a valid address", s))
+}
+
+pub fn parse_arch(s: &str) -> Result<Arch, String> {
+    s.parse::<Arch>()
+        .map_err(|_| format!("{} is not a supported architecture", s))
+}
+
+pub fn parse_arrow_style(s: &str) -> Result<ArrowStyle, String> {
+    s.parse::<ArrowStyle>()
+        .map_err(|_| format!("{} is not a valid arrow style", s))
+}
+
+pub fn parse_byte_order(s: &str) -> Result<ByteOrder, String> {
+    s.parse::<ByteOrder>()
+        .map_err(|_| format!("{} is not a valid byte order", s))
+}
+
+pub fn parse_map_style(s: &str) -> Result<MapStyle, String> {
+    s.parse::<MapStyle>()
+        .map_err(|_| format!("{} is not a valid symbol map style", s))
+}
+
+pub fn parse_export(s: &str) -> Result<Export, String> {
+    if s.eq_ignore_ascii_case("mca") || s.eq_ignore_ascii_case("llvm-mca") {
+        Ok(Export::Mca)
+    } else {
+        Err(format!("{} is not a valid export format", s))
+    }
+}
+
+pub fn parse_engine(s: &str) -> Result<Engine, String> {
+    s.parse::<Engine>()
+        .map_err(|_| format!("{} is not a valid disassembly engine", s))
+}
+
+pub fn parse_syntax(s: &str) -> Result<Syntax, String> {
+    s.parse::<Syntax>()
+        .map_err(|_| format!("{} is not a valid assembly syntax", s))
+}
@@ -0,0 +1,83 @@
+//! Defaults for the command line options that are read from a TOML file, e.g.
+//! `~/.config/cargo-disasm/config.toml`.
+
+use anyhow::Context as _;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// The settings in the configuration file. Options that are passed on the command line
+/// always take precedence over these.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// The file that the configuration was read from.
+    #[serde(skip)]
+    pub path: PathBuf,
+
+    pub syntax: Option<String>,
+    pub theme: Option<String>,
+    pub color: Option<String>,
+    /// Symbol sources in the same format as `--symsrc`.
+    pub symsrc: Vec<String>,
+    pub show_bytes: Option<bool>,
+    pub byte_group: Option<usize>,
+    pub byte_order: Option<String>,
+    /// The number of instructions that `addr` shows around each address, as in `--context`.
+    pub context: Option<usize>,
+    /// Print the instruction counts after each listing, as in `--stats`.
+    pub stats: Option<bool>,
+}
+
+impl Config {
+    /// Reads the configuration file from the path in `CARGO_DISASM_CONFIG`, or from
+    /// `cargo-disasm/config.toml` in the user's configuration directory. A missing
+    /// configuration file is the same as an empty one.
+    pub fn load() -> anyhow::Result<Config> {
+        let (path, explicit) = match std::env::var_os("CARGO_DISASM_CONFIG") {
+            Some(path) => (PathBuf::from(path), true),
+            None => match crate::util::user_config_dir() {
+                Some(dir) => (dir.join("cargo-disasm").join("config.toml"), false),
+                None => return Ok(Config::default()),
+            },
+        };
+
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if !explicit && err.kind() == std::io::ErrorKind::NotFound => {
+                log::trace!("no config file at {}", path.display());
+                return Ok(Config::default());
+            }
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("failed to read config file `{}`", path.display()))
+            }
+        };
+
+        let mut config = toml::from_str::<Config>(&contents)
+            .with_context(|| format!("invalid config file `{}`", path.display()))?;
+        log::debug!("using config file {}", path.display());
+        config.path = path;
+        Ok(config)
+    }
+
+    /// Parses a value from the configuration file and adds the file and the name of the
+    /// setting to errors.
+    pub fn parse<T>(
+        &self,
+        name: &str,
+        value: Option<&str>,
+        parse: fn(&str) -> Result<T, String>,
+    ) -> anyhow::Result<Option<T>> {
+        match value {
+            Some(value) => parse(value).map(Some).map_err(|err| {
+                anyhow::anyhow!(
+                    "invalid `{}` in config file `{}`: {}",
+                    name,
+                    self.path.display(),
+                    err
+                )
+            }),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Line coverage from `-C instrument-coverage`, which is used to mark the basic blocks that
+//! were executed.
+
+use crate::disasm::{cfg::ControlFlowGraph, source::SourceLocation, Disassembly};
+use anyhow::Context as _;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The execution counts of source lines.
+pub struct Coverage {
+    files: HashMap<PathBuf, HashMap<u32, u64>>,
+}
+
+impl Coverage {
+    /// Reads coverage from an LCOV file, or from a `.profraw` or `.profdata` profile of the
+    /// binary, which is exported to LCOV using `llvm-profdata` and `llvm-cov`.
+    pub fn load(path: &Path, binary_path: &Path) -> anyhow::Result<Coverage> {
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        let lcov = match extension {
+            Some("profraw") => {
+                let profdata = std::env::temp_dir()
+                    .join(format!("cargo-disasm-{}.profdata", std::process::id()));
+                run_tool(
+                    Command::new("llvm-profdata")
+                        .args(["merge", "-sparse"])
+                        .arg(path)
+                        .arg("-o")
+                        .arg(&profdata),
+                )?;
+                let lcov = export_lcov(&profdata, binary_path);
+                let _ = std::fs::remove_file(&profdata);
+                lcov?
+            }
+            Some("profdata") => export_lcov(path, binary_path)?,
+            _ => std::fs::read(path)
+                .with_context(|| format!("failed to read coverage {}", path.display()))?,
+        };
+
+        let coverage = Coverage::parse(&String::from_utf8_lossy(&lcov));
+        log::debug!(
+            "read coverage for {} files from {}",
+            coverage.files.len(),
+            path.display()
+        );
+        Ok(coverage)
+    }
+
+    /// Parses the `SF` (source file) and `DA` (line data) records of an LCOV file.
+    fn parse(lcov: &str) -> Coverage {
+        let mut files = HashMap::<PathBuf, HashMap<u32, u64>>::new();
+        let mut current_file = None;
+        for line in lcov.lines() {
+            let line = line.trim();
+            if let Some(path) = line.strip_prefix("SF:") {
+                current_file = Some(PathBuf::from(path));
+            } else if let Some(data) = line.strip_prefix("DA:") {
+                let mut fields = data.split(',');
+                let line_number = fields.next().and_then(|n| n.parse::<u32>().ok());
+                let count = fields.next().and_then(|n| n.parse::<u64>().ok());
+                if let (Some(file), Some(line_number), Some(count)) =
+                    (current_file.as_ref(), line_number, count)
+                {
+                    *files
+                        .entry(file.clone())
+                        .or_default()
+                        .entry(line_number)
+                        .or_default() += count;
+                }
+            } else if line == "end_of_record" {
+                current_file = None;
+            }
+        }
+        Coverage { files }
+    }
+
+    /// Returns whether each line of the disassembly was executed, based on the source lines
+    /// of its basic block. This is `None` for blocks without source lines in the coverage.
+    pub fn executed_lines(&self, disassembly: &Disassembly) -> Vec<Option<bool>> {
+        let lines = disassembly.lines();
+        let mut executed = vec![None; lines.len()];
+        for block in ControlFlowGraph::new(disassembly).blocks() {
+            let counts = lines[block.lines()]
+                .iter()
+                .flat_map(|line| line.source_locations())
+                .filter_map(|location| self.count(location));
+
+            let mut block_executed = None;
+            for count in counts {
+                block_executed = Some(block_executed.unwrap_or(false) || count > 0);
+            }
+            for line_executed in &mut executed[block.lines()] {
+                *line_executed = block_executed;
+            }
+        }
+        executed
+    }
+
+    fn count(&self, location: &SourceLocation) -> Option<u64> {
+        let lines = match self.files.get(location.path()) {
+            Some(lines) => lines,
+            // The paths can be relative to different directories.
+            None => self.files.iter().find_map(|(path, lines)| {
+                if path.ends_with(location.path()) || location.path().ends_with(path) {
+                    Some(lines)
+                } else {
+                    None
+                }
+            })?,
+        };
+        lines.get(&location.line()).copied()
+    }
+}
+
+fn export_lcov(profdata: &Path, binary_path: &Path) -> anyhow::Result<Vec<u8>> {
+    run_tool(
+        Command::new("llvm-cov")
+            .args(["export", "-format=lcov", "-instr-profile"])
+            .arg(profdata)
+            .arg(binary_path),
+    )
+}
+
+/// Runs one of the LLVM tools and returns its output.
+fn run_tool(command: &mut Command) -> anyhow::Result<Vec<u8>> {
+    log::debug!("running {:?}", command);
+    let output = command.output().with_context(|| {
+        format!(
+            "failed to run {:?}, make sure that LLVM's tools are in the PATH",
+            command
+        )
+    })?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "{:?} failed: {}",
+            command,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_lcov() {
+        let coverage = Coverage::parse(
+            "TN:
+SF:/path/to/src/main.rs
+FN:1,main
+FNDA:1,main
+DA:1,1
+DA:2,0
+DA:3,5,0123456789abcdef
+DA:4,invalid
+LF:3
+LH:2
+end_of_record
+DA:5,1
+SF:src/lib.rs
+DA:10,2
+end_of_record
+SF:src/lib.rs
+DA:10,3
+DA:11,0
+end_of_record
+",
+        );
+        assert_eq!(coverage.files.len(), 2);
+
+        let main = &coverage.files[Path::new("/path/to/src/main.rs")];
+        assert_eq!(main.len(), 3);
+        assert_eq!(main[&1], 1);
+        assert_eq!(main[&2], 0);
+        assert_eq!(main[&3], 5);
+
+        // The counts of records for the same file are added together.
+        let lib = &coverage.files[Path::new("src/lib.rs")];
+        assert_eq!(lib.len(), 2);
+        assert_eq!(lib[&10], 5);
+        assert_eq!(lib[&11], 0);
+    }
+
+    #[test]
+    fn parse_empty() {
+        assert!(Coverage::parse("").files.is_empty());
+        assert!(Coverage::parse("DA:1,1\nend_of_record\n").files.is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+use super::addr;
+use super::cli::{CoreOpts, Opts, OutputFormat};
+use crate::disasm::{binary::Binary, context, coredump::CrashDump};
+use anyhow::Context as _;
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+/// Prints the frames of the crashing thread of a core dump or minidump with the
+/// instructions around the address of each frame.
+pub fn run(opts: &Opts, core_opts: &CoreOpts, color_choice: ColorChoice) -> anyhow::Result<()> {
+    if opts.format != OutputFormat::Text {
+        return Err(anyhow::anyhow!("crash dumps can only be printed as text"));
+    }
+
+    let (binary_path, mut bin, lazy_dwarf) = addr::open_binary(opts)?;
+    let dump = CrashDump::open(&core_opts.dump)?;
+    if dump.arch() != bin.arch() {
+        return Err(anyhow::anyhow!(
+            "the crash dump is for {:?} but the binary is for {:?}",
+            dump.arch(),
+            bin.arch()
+        ));
+    }
+
+    let bias = match dump.load_bias(&binary_path, &bin) {
+        Some(bias) => bias,
+        None => {
+            log::warn!(
+                "`{}` isn't mapped in the crashed process, assuming that it wasn't moved",
+                binary_path.display()
+            );
+            0
+        }
+    };
+    log::debug!("load bias of the binary is 0x{:x}", bias);
+
+    let frames = dump.backtrace(&bin, bias, core_opts.max_frames);
+    if lazy_dwarf {
+        for &frame in frames.iter() {
+            bin.load_dwarf_symbols_at(frame.wrapping_sub(bias))?;
+        }
+    }
+
+    print_frames(
+        &mut StandardStream::stdout(color_choice),
+        &dump,
+        &frames,
+        bias,
+        opts,
+        core_opts,
+        &bin,
+    )
+    .context("error occured while printing crash dump")
+}
+
+fn print_frames(
+    out: &mut dyn WriteColor,
+    dump: &CrashDump,
+    frames: &[u64],
+    bias: u64,
+    opts: &Opts,
+    core_opts: &CoreOpts,
+    bin: &Binary,
+) -> anyhow::Result<()> {
+    let theme = opts.theme()?;
+    let options = addr::context_options(opts)?;
+
+    let mut clr_addr = ColorSpec::new();
+    clr_addr.set_fg(Some(Color::Blue));
+
+    let mut clr_name = ColorSpec::new();
+    clr_name.set_fg(Some(Color::Cyan)).set_bold(true);
+
+    let mut clr_location = ColorSpec::new();
+    clr_location.set_fg(Some(Color::Magenta));
+
+    let mut clr_reason = ColorSpec::new();
+    clr_reason.set_fg(Some(Color::Red)).set_bold(true);
+
+    out.set_color(&clr_reason)?;
+    match dump.reason() {
+        Some(reason) => writeln!(out, "crashed with {}", reason)?,
+        None => writeln!(out, "crashed")?,
+    }
+    out.reset()?;
+    writeln!(out)?;
+
+    for (idx, &frame) in frames.iter().enumerate() {
+        let address = frame.wrapping_sub(bias);
+        write!(out, "#{:<3} ", idx)?;
+        out.set_color(&clr_addr)?;
+        write!(out, "0x{:016x}", frame)?;
+        out.reset()?;
+        write!(out, " in ")?;
+
+        let symbol = bin.symbolicate(address);
+        match symbol {
+            Some((symbol, offset)) => {
+                out.set_color(&clr_name)?;
+                write!(out, "{}", symbol.name())?;
+                out.reset()?;
+                if offset > 0 {
+                    write!(out, "+0x{:x}", offset)?;
+                }
+            }
+            // Only the crash can be outside of the binary, e.g. in a shared library.
+            None => match dump
+                .mapped_files()
+                .iter()
+                .find(|file| file.range.contains(&frame))
+            {
+                Some(file) => write!(out, "?? ({})", file.path)?,
+                None => write!(out, "??")?,
+            },
+        }
+        writeln!(out)?;
+
+        // Return addresses are after the call, which can be on a different line or the
+        // last instruction of an inlined function.
+        let lookup = if idx == 0 {
+            address
+        } else {
+            address.saturating_sub(1)
+        };
+        out.set_color(&clr_location)?;
+        for (path, line) in bin.addr2line(lookup)?.into_iter().flatten() {
+            writeln!(out, "      at {}:{}", path.display(), line)?;
+        }
+        out.reset()?;
+
+        if core_opts.context == 0 || symbol.is_none() {
+            continue;
+        }
+        match context::disasm_around(bin, address, core_opts.context, options) {
+            Ok(context) => {
+                writeln!(out)?;
+                addr::print_context(out, &context, address, opts, bin, &theme)?;
+                writeln!(out)?;
+            }
+            Err(err) => log::warn!("failed to disassemble around 0x{:x}: {:#}", address, err),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,286 @@
+use super::cli::{DiffOpts, Opts};
+use crate::disasm::{
+    self,
+    binary::{Binary, BinaryData, SearchOptions},
+    diff::{DiffOp, DiffStats, DisasmDiff},
+    symbol::{Symbol, SymbolSource},
+    DisasmLine, Disassembly,
+};
+use anyhow::Context as _;
+use std::collections::HashMap;
+use std::io::Write as _;
+use std::path::Path;
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+/// Compares a symbol in two builds of a binary, or lists the functions that changed
+/// between them if no symbol was given.
+pub fn run(opts: &Opts, diff_opts: &DiffOpts, color_choice: ColorChoice) -> anyhow::Result<()> {
+    let sources = super::parse_symbol_sources(opts)?;
+    let symbol_name = diff_opts.symbol.as_deref();
+    let old_bin = load_binary(&diff_opts.old_binary, opts, &sources, symbol_name)?;
+    let new_bin = load_binary(&diff_opts.new_binary, opts, &sources, symbol_name)?;
+    let options = disasm::Options {
+        syntax: opts.syntax()?,
+        no_skip_data: opts.no_skip_data,
+        engine: opts.engine,
+        ..Default::default()
+    };
+
+    let mut stdout = StandardStream::stdout(color_choice);
+    writeln!(stdout, "--- {}", diff_opts.old_binary.display())?;
+    writeln!(stdout, "+++ {}", diff_opts.new_binary.display())?;
+
+    let name = if let Some(name) = symbol_name {
+        name
+    } else {
+        return print_changed_symbols(&mut stdout, &old_bin, &new_bin, options);
+    };
+
+    let old_symbol = old_bin
+        .fuzzy_find_symbol(name)
+        .with_context(|| format!("no symbol matching `{}` was found in the old binary", name))?;
+    let new_symbol = new_bin
+        .fuzzy_find_symbol(old_symbol.name())
+        .with_context(|| format!("no symbol matching `{}` was found in the new binary", name))?;
+
+    let old = disasm::disasm(&old_bin, old_symbol, options)?;
+    let new = disasm::disasm(&new_bin, new_symbol, options)?;
+    let diff = DisasmDiff::new(&old, &new);
+    print_diff(&mut stdout, new_symbol, &old, &new, &diff, None)
+        .context("error occured while printing diff")
+}
+
+pub(super) fn load_binary(
+    path: &Path,
+    opts: &Opts,
+    sources: &[SymbolSource],
+    symbol_name: Option<&str>,
+) -> anyhow::Result<Binary> {
+    let data = BinaryData::from_path(path)
+        .with_context(|| format!("failed to load binary `{}`", path.display()))?;
+
+    let lazy_dwarf = sources.is_empty() && symbol_name.is_some();
+    let mut bin = Binary::new(
+        data,
+        SearchOptions {
+            sources,
+            dwarf_path: None,
+            dsym_path: None,
+            pdb_path: None,
+            archive_member: None,
+            cache_symbols: !opts.no_cache,
+            lazy_dwarf,
+            include_locals: opts.include_locals,
+            exclude_compiler_generated: opts.exclude_compiler_generated,
+            mangled_names: opts.no_demangle,
+            rust_hashes: opts.keep_hashes,
+        },
+    )?;
+
+    if lazy_dwarf {
+        if let Some(name) = symbol_name {
+            match bin.fuzzy_find_symbol(name).map(Symbol::address) {
+                Some(address) => bin.load_dwarf_symbols_at(address)?,
+                // The symbol may only be named by DWARF.
+                None => bin.load_deferred_dwarf_symbols()?,
+            }
+        }
+    }
+    Ok(bin)
+}
+
+/// Lists the functions that were added, removed, or whose instructions changed.
+/// Functions are matched by name.
+fn print_changed_symbols(
+    out: &mut dyn WriteColor,
+    old_bin: &Binary,
+    new_bin: &Binary,
+    options: disasm::Options,
+) -> anyhow::Result<()> {
+    let old_symbols = old_bin.all_symbols();
+    let new_symbols = new_bin.all_symbols();
+
+    let mut old_by_name = HashMap::with_capacity(old_symbols.len());
+    for &symbol in old_symbols.iter() {
+        old_by_name.entry(symbol.name()).or_insert(symbol);
+    }
+    let mut new_by_name = HashMap::with_capacity(new_symbols.len());
+    for &symbol in new_symbols.iter() {
+        new_by_name.entry(symbol.name()).or_insert(symbol);
+    }
+
+    let mut clr_removed = ColorSpec::new();
+    clr_removed.set_fg(Some(Color::Red));
+    let mut clr_added = ColorSpec::new();
+    clr_added.set_fg(Some(Color::Green));
+    let mut clr_changed = ColorSpec::new();
+    clr_changed.set_fg(Some(Color::Yellow));
+
+    for &symbol in old_symbols.iter() {
+        if !new_by_name.contains_key(symbol.name()) {
+            out.set_color(&clr_removed)?;
+            writeln!(out, "- {} ({} bytes)", symbol.name(), symbol.size())?;
+        }
+    }
+
+    for &new_symbol in new_symbols.iter() {
+        let old_symbol = match old_by_name.get(new_symbol.name()) {
+            Some(&old_symbol) => old_symbol,
+            None => {
+                out.set_color(&clr_added)?;
+                writeln!(out, "+ {} ({} bytes)", new_symbol.name(), new_symbol.size())?;
+                continue;
+            }
+        };
+
+        let disassemblies = disasm::disasm(old_bin, old_symbol, options)
+            .and_then(|old| disasm::disasm(new_bin, new_symbol, options).map(|new| (old, new)));
+        let (old, new) = match disassemblies {
+            Ok(disassemblies) => disassemblies,
+            Err(err) => {
+                log::warn!("failed to disassemble `{}`: {:?}", new_symbol.name(), err);
+                continue;
+            }
+        };
+
+        let diff = DisasmDiff::new(&old, &new);
+        if diff.has_changes() {
+            out.set_color(&clr_changed)?;
+            write!(out, "~ {}", new_symbol.name())?;
+            out.reset()?;
+            writeln!(
+                out,
+                " ({} -> {} bytes, {})",
+                old_symbol.size(),
+                new_symbol.size(),
+                StatsDisplay(diff.stats())
+            )?;
+        }
+    }
+    out.reset()?;
+
+    Ok(())
+}
+
+/// Prints the instructions of the old and new disassembly of a symbol, marking the
+/// instructions that were removed (`-`), added (`+`), or changed (`~`). With `context`
+/// only the changed instructions and that many instructions around them are printed, in
+/// hunks that are separated by `@@` like a unified diff.
+pub(super) fn print_diff(
+    out: &mut dyn WriteColor,
+    symbol: &Symbol,
+    old: &Disassembly,
+    new: &Disassembly,
+    diff: &DisasmDiff,
+    context: Option<usize>,
+) -> anyhow::Result<()> {
+    let addr_width =
+        |dis: &Disassembly| disasm::display::measure(dis).max_address_width_hex().max(1);
+    let old_width = addr_width(old);
+    let new_width = addr_width(new);
+
+    let mut clr_name = ColorSpec::new();
+    clr_name.set_fg(Some(Color::Cyan)).set_bold(true);
+    let mut clr_removed = ColorSpec::new();
+    clr_removed.set_fg(Some(Color::Red));
+    let mut clr_added = ColorSpec::new();
+    clr_added.set_fg(Some(Color::Green));
+    let mut clr_changed = ColorSpec::new();
+    clr_changed.set_fg(Some(Color::Yellow));
+    let mut clr_comm = ColorSpec::new();
+    clr_comm.set_italic(true).set_fg(Some(Color::Yellow));
+    let mut clr_hunk = ColorSpec::new();
+    clr_hunk.set_fg(Some(Color::Cyan));
+
+    out.set_color(&clr_name)?;
+    write!(out, "{}:", symbol.name())?;
+    out.reset()?;
+    writeln!(out, " ({})", StatsDisplay(diff.stats()))?;
+
+    let ops = diff.ops();
+    let shown = |idx: usize| match context {
+        Some(context) => {
+            let start = idx.saturating_sub(context);
+            let end = (idx + context + 1).min(ops.len());
+            ops[start..end]
+                .iter()
+                .any(|op| !matches!(op, DiffOp::Same(..)))
+        }
+        None => true,
+    };
+
+    let mut last_shown = None;
+    for (idx, &op) in ops.iter().enumerate() {
+        if !shown(idx) {
+            continue;
+        }
+        if context.is_some() && last_shown.is_none_or(|last| last + 1 != idx) {
+            out.set_color(&clr_hunk)?;
+            writeln!(out, "@@")?;
+        }
+        last_shown = Some(idx);
+
+        let (marker, color, old_line, new_line) = match op {
+            DiffOp::Same(o, n) => (' ', None, Some(o), Some(n)),
+            DiffOp::Removed(o) => ('-', Some(&clr_removed), Some(o), None),
+            DiffOp::Added(n) => ('+', Some(&clr_added), None, Some(n)),
+            DiffOp::Changed(o, n) => ('~', Some(&clr_changed), Some(o), Some(n)),
+        };
+        let old_line = old_line.map(|idx| &old.lines()[idx]);
+        let new_line = new_line.map(|idx| &new.lines()[idx]);
+
+        match color {
+            Some(color) => out.set_color(color)?,
+            None => out.reset()?,
+        }
+        write!(out, "{} ", marker)?;
+        match old_line {
+            Some(line) => write!(out, "{:<1$x}", line.address(), old_width)?,
+            None => write!(out, "{:<1$}", "", old_width)?,
+        }
+        write!(out, "  ")?;
+        match new_line {
+            Some(line) => write!(out, "{:<1$x}", line.address(), new_width)?,
+            None => write!(out, "{:<1$}", "", new_width)?,
+        }
+        write!(out, "    ")?;
+
+        let line = new_line
+            .or(old_line)
+            .expect("diff op without an instruction");
+        write_instruction(out, line)?;
+
+        if let (DiffOp::Changed(..), Some(old_line)) = (op, old_line) {
+            out.set_color(&clr_comm)?;
+            write!(out, "  ; was: ")?;
+            write_instruction(out, old_line)?;
+        } else if !line.comments().is_empty() {
+            out.set_color(&clr_comm)?;
+            write!(out, "  ; {}", line.comments())?;
+        }
+        out.reset()?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+fn write_instruction(out: &mut dyn WriteColor, line: &DisasmLine) -> std::io::Result<()> {
+    if line.operands().is_empty() {
+        write!(out, "{}", line.mnemonic())
+    } else {
+        write!(out, "{} {}", line.mnemonic(), line.operands())
+    }
+}
+
+struct StatsDisplay(DiffStats);
+
+impl std::fmt::Display for StatsDisplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "+{} -{} ~{} instructions",
+            self.0.added, self.0.removed, self.0.changed
+        )
+    }
+}
@@ -0,0 +1,103 @@
+use crate::disasm::callgraph::CallGraph;
+use crate::disasm::cfg::{ControlFlowGraph, EdgeKind};
+use crate::disasm::{symbol::Symbol, Disassembly};
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// Writes the control flow graph of a disassembled symbol as a Graphviz DOT graph.
+pub fn print_cfg(
+    out: &mut dyn Write,
+    sym: &Symbol,
+    dis: &Disassembly,
+    cfg: &ControlFlowGraph,
+) -> anyhow::Result<()> {
+    writeln!(out, "digraph \"{}\" {{", Escaped(sym.name()))?;
+    writeln!(out, "    label=\"{}\";", Escaped(sym.name()))?;
+    writeln!(out, "    labelloc=t;")?;
+    writeln!(out, "    node [shape=box, fontname=\"monospace\"];")?;
+    writeln!(out, "    edge [fontname=\"monospace\"];")?;
+
+    let mut label = String::new();
+    for (idx, block) in cfg.blocks().iter().enumerate() {
+        label.clear();
+        for line in &dis.lines()[block.lines()] {
+            let mut text = format!(
+                "{:x}  {} {}",
+                line.address(),
+                line.mnemonic(),
+                line.operands()
+            );
+            if !line.comments().is_empty() {
+                write!(text, " ; {}", line.comments())?;
+            }
+            // `\l` left justifies the line.
+            write!(label, "{}\\l", Escaped(&text))?;
+        }
+
+        writeln!(out, "    b{} [label=\"{}\"];", idx, label)?;
+    }
+
+    for (idx, block) in cfg.blocks().iter().enumerate() {
+        for edge in block.edges() {
+            let color = match edge.kind() {
+                EdgeKind::Jump => "blue",
+                EdgeKind::Taken => "darkgreen",
+                EdgeKind::NotTaken => "red",
+                EdgeKind::Next => "black",
+            };
+            writeln!(out, "    b{} -> b{} [color={}];", idx, edge.target(), color)?;
+        }
+    }
+
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+/// Writes the calls between functions as a Graphviz DOT graph. If there is a root
+/// node then only the nodes within `depth` calls of it are written.
+pub fn print_callgraph(
+    out: &mut dyn Write,
+    graph: &CallGraph,
+    root: Option<usize>,
+    depth: usize,
+) -> anyhow::Result<()> {
+    let (nodes, edges) = graph.subgraph(root, depth);
+
+    writeln!(out, "digraph callgraph {{")?;
+    writeln!(out, "    node [shape=box, fontname=\"monospace\"];")?;
+    for node in nodes {
+        let style = if Some(node) == root {
+            ", style=bold"
+        } else {
+            ""
+        };
+        writeln!(
+            out,
+            "    n{} [label=\"{}\"{}];",
+            node,
+            Escaped(graph.nodes()[node].name()),
+            style
+        )?;
+    }
+    for (caller, callee) in edges {
+        writeln!(out, "    n{} -> n{};", caller, callee)?;
+    }
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+/// Escapes a string for use inside of a quoted DOT string.
+struct Escaped<'s>(&'s str);
+
+impl std::fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '"' | '\\' => write!(f, "\\{}", c)?,
+                '\n' => f.write_str("\\n")?,
+                _ => write!(f, "{}", c)?,
+            }
+        }
+        Ok(())
+    }
+}
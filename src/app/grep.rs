@@ -0,0 +1,203 @@
+use super::cli::{GrepOpts, Opts, OutputFormat};
+use super::json;
+use crate::disasm::{
+    self,
+    binary::{Binary, BinaryData, SearchOptions},
+    signature::BytePattern,
+    symbol::Symbol,
+};
+use anyhow::Context as _;
+use regex::RegexBuilder;
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+/// An instruction or bytes that match the pattern.
+pub struct GrepMatch<'b> {
+    pub address: u64,
+    /// The symbol that contains the match and the offset of the match in it.
+    pub symbol: Option<(&'b Symbol, u64)>,
+    /// The mnemonic and operands of the instruction that matched a regular expression.
+    pub instruction: Option<String>,
+    /// The bytes that matched a byte pattern, in hexadecimal.
+    pub bytes: Option<String>,
+}
+
+/// Prints the instructions that match a regular expression, e.g. to find every
+/// `lock cmpxchg` or `rdtsc`, or with `--bytes` the places in the code that match a byte
+/// pattern.
+pub fn run(opts: &Opts, grep_opts: &GrepOpts, color_choice: ColorChoice) -> anyhow::Result<()> {
+    let binary_path = super::find_binary_path(opts)?;
+    log::debug!("using binary {}", binary_path.display());
+    let data = BinaryData::from_path(&binary_path)
+        .with_context(|| format!("failed to load binary `{}`", binary_path.display()))?;
+    let sources = super::parse_symbol_sources(opts)?;
+    let mut bin = Binary::new(
+        data,
+        SearchOptions {
+            sources: &sources,
+            dwarf_path: opts.debug_file_path.as_deref(),
+            dsym_path: opts.dsym_path.as_deref(),
+            pdb_path: None,
+            archive_member: opts.archive_member.as_deref(),
+            cache_symbols: !opts.no_cache,
+            lazy_dwarf: false,
+            include_locals: opts.include_locals,
+            exclude_compiler_generated: opts.exclude_compiler_generated,
+            mangled_names: opts.no_demangle,
+            rust_hashes: opts.keep_hashes,
+        },
+    )?;
+    if opts.crate_name.is_some() {
+        bin.load_line_information()?;
+    }
+
+    let matches = if grep_opts.bytes {
+        let pattern = grep_opts.pattern.parse::<BytePattern>().map_err(|err| {
+            anyhow::anyhow!("invalid byte pattern `{}`: {}", grep_opts.pattern, err)
+        })?;
+        find_bytes(&bin, &pattern)
+    } else {
+        find_instructions(&bin, opts, grep_opts)?
+    };
+    log::debug!(
+        "found {} matches for `{}`",
+        matches.len(),
+        grep_opts.pattern
+    );
+
+    let result = match opts.format {
+        OutputFormat::Text => print_matches(&mut StandardStream::stdout(color_choice), &matches),
+        OutputFormat::Json => json::print_grep_matches(&mut std::io::stdout().lock(), &matches),
+        _ => {
+            return Err(anyhow::anyhow!(
+                "matches can only be printed as text or JSON"
+            ))
+        }
+    };
+    result.context("error occured while printing matches")
+}
+
+/// Disassembles every function (of a crate with `--crate`) and finds the instructions
+/// that match the regular expression.
+fn find_instructions<'b>(
+    bin: &'b Binary,
+    opts: &Opts,
+    grep_opts: &GrepOpts,
+) -> anyhow::Result<Vec<GrepMatch<'b>>> {
+    let regex = RegexBuilder::new(&grep_opts.pattern)
+        .case_insensitive(grep_opts.ignore_case)
+        .build()
+        .with_context(|| format!("`{}` is not a valid regular expression", grep_opts.pattern))?;
+    let options = disasm::Options {
+        syntax: opts.syntax()?,
+        no_skip_data: opts.no_skip_data,
+        engine: opts.engine,
+        ..Default::default()
+    };
+    let symbols = super::symbol_query(bin, opts).sized().functions().to_vec();
+
+    let mut matches = Vec::new();
+    for symbol in symbols {
+        let disassembly = match disasm::disasm(bin, symbol, options) {
+            Ok(disassembly) => disassembly,
+            Err(err) => {
+                log::warn!("failed to disassemble `{}`: {:?}", symbol.name(), err);
+                continue;
+            }
+        };
+        for line in disassembly.lines() {
+            // Data in between instructions isn't searched.
+            if line.mnemonic().starts_with('.') {
+                continue;
+            }
+            let instruction = if line.operands().is_empty() {
+                line.mnemonic().to_string()
+            } else {
+                format!("{} {}", line.mnemonic(), line.operands())
+            };
+            if regex.is_match(&instruction) {
+                matches.push(GrepMatch {
+                    address: line.address(),
+                    symbol: Some((symbol, line.address() - symbol.address())),
+                    instruction: Some(instruction),
+                    bytes: None,
+                });
+            }
+        }
+    }
+    Ok(matches)
+}
+
+/// Finds the places in the code of the binary that match a byte pattern, which don't have
+/// to be at the start of an instruction.
+fn find_bytes<'b>(bin: &'b Binary, pattern: &BytePattern) -> Vec<GrepMatch<'b>> {
+    pattern
+        .find_in_code(bin)
+        .into_iter()
+        .map(|address| {
+            let bytes = bin.code_section_at(address).and_then(|(range, offset)| {
+                let start = offset + (address - range.start) as usize;
+                bin.data().get(start..(start + pattern.len()))
+            });
+            GrepMatch {
+                address,
+                symbol: bin.symbolicate(address),
+                instruction: None,
+                bytes: bytes.map(|bytes| {
+                    bytes
+                        .iter()
+                        .map(|byte| format!("{:02x}", byte))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                }),
+            }
+        })
+        .collect()
+}
+
+fn print_matches(out: &mut dyn WriteColor, matches: &[GrepMatch]) -> anyhow::Result<()> {
+    let mut clr_addr = ColorSpec::new();
+    clr_addr.set_fg(Some(Color::Blue));
+    let mut clr_name = ColorSpec::new();
+    clr_name.set_fg(Some(Color::Cyan)).set_bold(true);
+
+    let addr_width = matches
+        .iter()
+        .map(|m| format!("{:x}", m.address).len())
+        .max()
+        .unwrap_or(1);
+    for m in matches {
+        out.set_color(&clr_addr)?;
+        write!(out, "0x{:<1$x}", m.address, addr_width)?;
+        out.reset()?;
+        write!(out, "  ")?;
+        match m.symbol {
+            Some((symbol, offset)) => {
+                out.set_color(&clr_name)?;
+                write!(out, "{}", symbol.name())?;
+                out.reset()?;
+                if offset > 0 {
+                    write!(out, "+0x{:x}", offset)?;
+                }
+            }
+            None => write!(out, "??")?,
+        }
+        if let Some(ref instruction) = m.instruction {
+            write!(out, "  {}", instruction)?;
+        }
+        if let Some(ref bytes) = m.bytes {
+            write!(out, "  {}", bytes)?;
+        }
+        writeln!(out)?;
+    }
+
+    let functions = {
+        let mut addresses = matches
+            .iter()
+            .filter_map(|m| m.symbol.map(|(symbol, _)| symbol.address()))
+            .collect::<Vec<_>>();
+        addresses.dedup();
+        addresses.len()
+    };
+    writeln!(out, "{} matches in {} functions", matches.len(), functions)?;
+    Ok(())
+}
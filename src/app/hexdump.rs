@@ -0,0 +1,146 @@
+use crate::disasm::binary::{Binary, Bits, Endian};
+use crate::disasm::symbol::Symbol;
+use anyhow::Context as _;
+use std::convert::TryInto as _;
+use termcolor::{Color, ColorSpec, WriteColor};
+
+const BYTES_PER_ROW: usize = 16;
+
+/// Prints the contents of a data symbol as hexadecimal and ASCII. Symbols with the size
+/// of an integer or a float are also printed as those types, and pointers to other symbols
+/// are symbolicated.
+pub fn print_hex_dump(out: &mut dyn WriteColor, bin: &Binary, sym: &Symbol) -> anyhow::Result<()> {
+    let bytes = bin
+        .data()
+        .get(sym.offset()..sym.end())
+        .with_context(|| format!("`{}` is outside of the binary", sym.name()))?;
+
+    let mut clr_addr = ColorSpec::new();
+    clr_addr.set_fg(Some(Color::Blue));
+
+    let mut clr_bytes = ColorSpec::new();
+    clr_bytes.set_fg(Some(Color::Yellow));
+
+    let mut clr_comm = ColorSpec::new();
+    clr_comm.set_italic(true).set_fg(Some(Color::Yellow));
+
+    out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)).set_bold(true))?;
+    writeln!(out, "{}:", sym.name())?;
+    out.set_color(&clr_comm)?;
+    writeln!(
+        out,
+        "  ; 0x{:x}..0x{:x} ({} bytes, {})",
+        sym.address(),
+        sym.end_address(),
+        sym.size(),
+        sym.symbol_type()
+    )?;
+
+    let addr_width = format!("{:x}", sym.end_address()).len();
+    for (row, chunk) in bytes.chunks(BYTES_PER_ROW).enumerate() {
+        out.set_color(&clr_addr)?;
+        write!(
+            out,
+            "  {:<1$x}",
+            sym.address() + (row * BYTES_PER_ROW) as u64,
+            addr_width
+        )?;
+        out.set_color(&clr_bytes)?;
+        write!(out, "  ")?;
+        for idx in 0..BYTES_PER_ROW {
+            if idx == BYTES_PER_ROW / 2 {
+                write!(out, " ")?;
+            }
+            match chunk.get(idx) {
+                Some(byte) => write!(out, " {:02x}", byte)?,
+                None => write!(out, "   ")?,
+            }
+        }
+        out.reset()?;
+
+        let ascii = chunk
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+        writeln!(out, "  |{}|", ascii)?;
+    }
+
+    out.set_color(&clr_comm)?;
+    for interpretation in interpret(bin, bytes) {
+        writeln!(out, "  ; {}", interpretation)?;
+    }
+    out.reset()?;
+
+    Ok(())
+}
+
+/// Reads the bytes of a symbol as the integer and float types of the same size.
+fn interpret(bin: &Binary, bytes: &[u8]) -> Vec<String> {
+    let big_endian = bin.endian() == Endian::Big;
+    let mut interpretations = Vec::new();
+
+    macro_rules! read {
+        ($ty:ty) => {{
+            let bytes = bytes.try_into().expect("unexpected number of bytes");
+            if big_endian {
+                <$ty>::from_be_bytes(bytes)
+            } else {
+                <$ty>::from_le_bytes(bytes)
+            }
+        }};
+    }
+
+    let pointer = match bytes.len() {
+        1 => {
+            interpretations.push(format!("u8 = {}, i8 = {}", bytes[0], bytes[0] as i8));
+            None
+        }
+        2 => {
+            let value = read!(u16);
+            interpretations.push(format!("u16 = {}, i16 = {}", value, value as i16));
+            None
+        }
+        4 => {
+            let value = read!(u32);
+            interpretations.push(format!(
+                "u32 = {}, i32 = {}, f32 = {}",
+                value,
+                value as i32,
+                f32::from_bits(value)
+            ));
+            Some(value as u64).filter(|_| bin.bits() == Bits::Bits32)
+        }
+        8 => {
+            let value = read!(u64);
+            interpretations.push(format!(
+                "u64 = {}, i64 = {}, f64 = {}",
+                value,
+                value as i64,
+                f64::from_bits(value)
+            ));
+            Some(value).filter(|_| bin.bits() == Bits::Bits64)
+        }
+        16 => {
+            let value = read!(u128);
+            interpretations.push(format!("u128 = {}, i128 = {}", value, value as i128));
+            None
+        }
+        _ => None,
+    };
+
+    if let Some((symbol, offset)) = pointer.and_then(|addr| bin.symbolicate(addr)) {
+        if offset == 0 {
+            interpretations.push(format!("-> {}", symbol.name()));
+        } else {
+            interpretations.push(format!("-> {}+0x{:x}", symbol.name(), offset));
+        }
+    }
+
+    interpretations
+}
@@ -0,0 +1,177 @@
+use crate::disasm::{binary::Binary, symbol::Symbol, Disassembly, Jump};
+use std::collections::HashSet;
+use std::fmt;
+use std::io::Write;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+pre, table { font-family: monospace; }
+table { border-collapse: collapse; }
+td { padding: 0 1em 0 0; white-space: pre; vertical-align: top; }
+tr:target { background: #ffec99; }
+a { color: #1864ab; text-decoration: none; }
+a:hover { text-decoration: underline; }
+.addr { color: #868e96; }
+.mnem { color: #2b8a3e; font-weight: bold; }
+.comm { color: #e67700; font-style: italic; }
+details.source summary { color: #862e9c; cursor: pointer; }
+details.source pre { margin: 0 0 0 2em; color: #862e9c; }
+";
+
+/// Writes the disassembly of symbols as a standalone HTML page, where the targets of
+/// jumps and calls to symbols on the page are links.
+pub fn print_disassemblies(
+    out: &mut dyn Write,
+    bin: &Binary,
+    disassemblies: &[(&Symbol, Disassembly)],
+) -> anyhow::Result<()> {
+    let title = match disassemblies {
+        [(symbol, _)] => symbol.name(),
+        _ => "cargo-disasm",
+    };
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html>")?;
+    writeln!(out, "<head>")?;
+    writeln!(out, "<meta charset=\"utf-8\">")?;
+    writeln!(out, "<title>{}</title>", Escaped(title))?;
+    writeln!(out, "<style>{}</style>", STYLE)?;
+    writeln!(out, "</head>")?;
+    writeln!(out, "<body>")?;
+
+    if disassemblies.len() > 1 {
+        writeln!(out, "<nav><ul>")?;
+        for (symbol, _) in disassemblies {
+            writeln!(
+                out,
+                "<li><a href=\"#{}\">{}</a></li>",
+                SymbolId(symbol.address()),
+                Escaped(symbol.name())
+            )?;
+        }
+        writeln!(out, "</ul></nav>")?;
+    }
+
+    let on_page = disassemblies
+        .iter()
+        .map(|(symbol, _)| symbol.address())
+        .collect::<HashSet<u64>>();
+    for (symbol, disassembly) in disassemblies {
+        print_disassembly(out, bin, symbol, disassembly, &on_page)?;
+    }
+
+    writeln!(out, "</body>")?;
+    writeln!(out, "</html>")?;
+    Ok(())
+}
+
+fn print_disassembly(
+    out: &mut dyn Write,
+    bin: &Binary,
+    symbol: &Symbol,
+    disassembly: &Disassembly,
+    on_page: &HashSet<u64>,
+) -> anyhow::Result<()> {
+    writeln!(out, "<section id=\"{}\">", SymbolId(symbol.address()))?;
+    writeln!(out, "<h2>{}</h2>", Escaped(symbol.name()))?;
+    writeln!(out, "<table>")?;
+
+    let lines = disassembly.lines();
+    let mut last_location = None;
+    for line in lines {
+        // Source lines are collapsed under their location.
+        for location in line.source_locations() {
+            if last_location == Some(location) {
+                continue;
+            }
+            last_location = Some(location);
+            write!(
+                out,
+                "<tr><td colspan=\"4\"><details class=\"source\"><summary>{}</summary><pre>",
+                Escaped(&location.to_string())
+            )?;
+            for source_line in line.source_lines() {
+                writeln!(out, "{}", Escaped(source_line))?;
+            }
+            writeln!(out, "</pre></details></td></tr>")?;
+        }
+
+        let target = match line.jump() {
+            Jump::Internal(idx) => Some(LineId(symbol.address(), lines[idx].address()).to_string()),
+            Jump::External(addr) => bin
+                .symbolicate(addr)
+                .filter(|(target, _)| on_page.contains(&target.address()))
+                .map(|(target, _)| SymbolId(target.address()).to_string()),
+            _ => None,
+        };
+
+        write!(
+            out,
+            "<tr id=\"{}\"><td class=\"addr\">{:x}</td><td class=\"mnem\">{}</td><td>",
+            LineId(symbol.address(), line.address()),
+            line.address(),
+            Escaped(line.mnemonic())
+        )?;
+        match target {
+            Some(ref target) => write!(
+                out,
+                "<a href=\"#{}\">{}</a>",
+                target,
+                Escaped(line.operands())
+            )?,
+            None => write!(out, "{}", Escaped(line.operands()))?,
+        }
+        write!(out, "</td><td class=\"comm\">")?;
+        if !line.comments().is_empty() {
+            match target {
+                Some(ref target) => write!(
+                    out,
+                    "; <a href=\"#{}\">{}</a>",
+                    target,
+                    Escaped(line.comments())
+                )?,
+                None => write!(out, "; {}", Escaped(line.comments()))?,
+            }
+        }
+        writeln!(out, "</td></tr>")?;
+    }
+
+    writeln!(out, "</table>")?;
+    writeln!(out, "</section>")?;
+    Ok(())
+}
+
+/// The ID of the section of a symbol.
+struct SymbolId(u64);
+
+impl fmt::Display for SymbolId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "s{:x}", self.0)
+    }
+}
+
+/// The ID of the row of an instruction, from the address of its symbol and its own.
+struct LineId(u64, u64);
+
+impl fmt::Display for LineId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "s{:x}-{:x}", self.0, self.1)
+    }
+}
+
+/// Escapes the characters of text that have a meaning in HTML.
+struct Escaped<'s>(&'s str);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '&' => f.write_str("&amp;")?,
+                '"' => f.write_str("&quot;")?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        Ok(())
+    }
+}
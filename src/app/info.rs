@@ -0,0 +1,142 @@
+use super::cli::{Opts, OutputFormat};
+use super::json;
+use super::sections;
+use crate::disasm::{
+    binary::{Binary, BinaryData, SearchOptions},
+    symbol::SymbolSource,
+};
+use anyhow::Context as _;
+use std::path::Path;
+use termcolor::{ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+/// Prints a summary of a binary, as a quick check of what it is and what can be found in
+/// it before disassembling it.
+pub fn run(opts: &Opts, color_choice: ColorChoice) -> anyhow::Result<()> {
+    let binary_path = super::find_binary_path(opts)?;
+    log::debug!("using binary {}", binary_path.display());
+    let data = BinaryData::from_path(&binary_path)
+        .with_context(|| format!("failed to load binary `{}`", binary_path.display()))?;
+
+    // `auto` stops looking for symbols once there are enough of them, so every source is
+    // loaded to count the symbols that each one has.
+    let mut sources = super::parse_symbol_sources(opts)?;
+    if sources.is_empty() {
+        sources.extend_from_slice(super::ALL_SYMBOL_SOURCES);
+    }
+    let bin = Binary::new(
+        data,
+        SearchOptions {
+            sources: &sources,
+            dwarf_path: opts.debug_file_path.as_deref(),
+            dsym_path: opts.dsym_path.as_deref(),
+            pdb_path: None,
+            archive_member: opts.archive_member.as_deref(),
+            cache_symbols: !opts.no_cache,
+            lazy_dwarf: false,
+            include_locals: opts.include_locals,
+            exclude_compiler_generated: opts.exclude_compiler_generated,
+            mangled_names: opts.no_demangle,
+            rust_hashes: opts.keep_hashes,
+        },
+    )?;
+
+    let result = match opts.format {
+        OutputFormat::Text => print_info(
+            &mut StandardStream::stdout(color_choice),
+            &bin,
+            &binary_path,
+        ),
+        OutputFormat::Json => json::print_info(&mut std::io::stdout().lock(), &bin, &binary_path),
+        _ => {
+            return Err(anyhow::anyhow!(
+                "binary information can only be printed as text or JSON"
+            ))
+        }
+    };
+    result.context("error occured while printing binary information")
+}
+
+/// Returns the names of the kinds of debug information that were found, e.g. `DWARF`.
+pub fn debug_info_kinds(bin: &Binary) -> Vec<&'static str> {
+    let mut kinds = Vec::new();
+    if bin.has_dwarf() {
+        kinds.push("DWARF");
+    }
+    if bin.has_pdb() {
+        kinds.push("PDB");
+    }
+    kinds
+}
+
+/// Counts the symbols that were loaded from each source, in the order in which the sources
+/// were first seen.
+pub fn symbol_counts(bin: &Binary) -> Vec<(SymbolSource, usize)> {
+    let mut counts: Vec<(SymbolSource, usize)> = Vec::new();
+    for symbol in bin.all_symbols() {
+        match counts
+            .iter_mut()
+            .find(|(source, _)| *source == symbol.source())
+        {
+            Some((_, count)) => *count += 1,
+            None => counts.push((symbol.source(), 1)),
+        }
+    }
+    counts
+}
+
+fn print_info(out: &mut dyn WriteColor, bin: &Binary, path: &Path) -> anyhow::Result<()> {
+    let mut clr_heading = ColorSpec::new();
+    clr_heading.set_bold(true);
+
+    writeln!(out, "file          {}", path.display())?;
+    writeln!(out, "format        {}", bin.format())?;
+    writeln!(
+        out,
+        "architecture  {} ({}, {})",
+        bin.arch(),
+        bin.bits(),
+        bin.endian()
+    )?;
+    writeln!(out, "os            {}", bin.os())?;
+    writeln!(out, "build id      {}", bin.build_id().unwrap_or("none"))?;
+
+    let kinds = debug_info_kinds(bin);
+    write!(out, "debug info    ")?;
+    if kinds.is_empty() {
+        write!(out, "none")?;
+    } else {
+        write!(out, "{}", kinds.join(", "))?;
+    }
+    if let Some(debug_file) = bin.debug_file() {
+        write!(out, " (from {})", debug_file.display())?;
+    }
+    writeln!(out)?;
+
+    writeln!(out)?;
+    out.set_color(&clr_heading)?;
+    writeln!(out, "libraries ({})", bin.libraries().len())?;
+    out.reset()?;
+    for library in bin.libraries() {
+        writeln!(out, "  {}", library)?;
+    }
+
+    writeln!(out)?;
+    out.set_color(&clr_heading)?;
+    writeln!(out, "sections ({})", bin.sections().len())?;
+    out.reset()?;
+    sections::print_sections(out, bin.sections(), "  ")?;
+
+    let counts = symbol_counts(bin);
+    writeln!(out)?;
+    out.set_color(&clr_heading)?;
+    writeln!(
+        out,
+        "symbols ({})",
+        counts.iter().map(|&(_, count)| count).sum::<usize>()
+    )?;
+    out.reset()?;
+    for (source, count) in counts {
+        writeln!(out, "  {:<8} {}", source.to_string(), count)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,492 @@
+use super::addr::AddressInfo;
+use super::grep::GrepMatch;
+use super::info;
+use super::printer::Hex;
+use super::sizes::SizeReport;
+use crate::disasm::binary::{Binary, Section};
+use crate::disasm::callgraph::CallGraph;
+use crate::disasm::{symbol::Symbol, DisasmLine, Disassembly, Jump};
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+
+/// The version of the records written by `--format json-lines`, which is increased
+/// whenever a field is removed or changes its meaning. New fields can be added without
+/// changing the version.
+pub const JSON_LINES_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct JsonDisassembly<'a> {
+    symbol: JsonSymbol<'a>,
+    lines: Vec<JsonLine<'a>>,
+}
+
+/// A record of `--format json-lines`. Every record is a JSON object on its own line with a
+/// `type` field:
+///
+/// - `header`: the first record, with the `version` of the schema.
+/// - `symbol`: a disassembled symbol, with the same fields as `symbol` in `--format json`.
+/// - `line`: a line of the disassembly of the last symbol, with the same fields as the
+///   entries of `lines` in `--format json` and the `symbol` address that it belongs to.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum JsonRecord<'a> {
+    Header {
+        version: u32,
+    },
+    Symbol(JsonSymbol<'a>),
+    Line {
+        /// The address of the symbol.
+        symbol: u64,
+        #[serde(flatten)]
+        line: JsonLine<'a>,
+    },
+}
+
+#[derive(Serialize)]
+struct JsonSymbol<'a> {
+    name: &'a str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mangled_name: Option<&'a str>,
+
+    /// The mangling scheme of Rust symbols, `legacy` or `v0`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mangling: Option<String>,
+
+    /// The segments of the path of Rust symbols, starting with the crate.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    path: Vec<&'a str>,
+
+    address: u64,
+    size: usize,
+    source: String,
+    lang: String,
+}
+
+#[derive(Serialize)]
+struct JsonLine<'a> {
+    address: u64,
+    bytes: String,
+    mnemonic: &'a str,
+    operands: &'a str,
+
+    #[serde(skip_serializing_if = "str::is_empty")]
+    comments: &'a str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_lines: Option<&'a [Box<str>]>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    source_locations: Vec<JsonSourceLocation>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    inlined_calls: Vec<JsonInlinedCall<'a>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    jump: Option<JsonJump>,
+}
+
+#[derive(Serialize)]
+struct JsonSourceLocation {
+    file: String,
+    line: u32,
+}
+
+#[derive(Serialize)]
+struct JsonInlinedCall<'a> {
+    name: &'a str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    call_location: Option<JsonSourceLocation>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum JsonJump {
+    /// Index of the line that is jumped to inside of the same listing.
+    Internal(usize),
+    /// Address of a jump target outside of the listing.
+    External(u64),
+    /// Address of the pointer that is jumped through.
+    Indirect(u64),
+}
+
+#[derive(Serialize)]
+struct JsonCallGraph<'a> {
+    nodes: Vec<JsonCallGraphNode<'a>>,
+    /// Pairs of indices into `nodes` for each caller and callee.
+    edges: Vec<(usize, usize)>,
+}
+
+#[derive(Serialize)]
+struct JsonCallGraphNode<'a> {
+    name: &'a str,
+    address: u64,
+}
+
+#[derive(Serialize)]
+struct JsonSizes<'a> {
+    /// The combined size of every function in the binary.
+    total: usize,
+    entries: Vec<JsonSizeEntry<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonSizeEntry<'a> {
+    name: &'a str,
+
+    #[serde(rename = "crate", skip_serializing_if = "Option::is_none")]
+    crate_name: Option<&'a str>,
+
+    size: usize,
+    /// The number of functions in a crate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    functions: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct JsonGrepMatch<'a> {
+    address: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symbol: Option<JsonSymbol<'a>>,
+
+    /// The offset of the address in the symbol.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symbol_offset: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instruction: Option<&'a str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bytes: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct JsonInfo<'a> {
+    file: String,
+    format: String,
+    arch: String,
+    bits: String,
+    endian: String,
+    os: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    build_id: Option<&'a str>,
+
+    /// The kinds of debug information that were found, e.g. `DWARF`.
+    debug_info: Vec<&'static str>,
+
+    /// The file that the debug information was loaded from if it isn't the binary.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    debug_file: Option<String>,
+
+    libraries: Vec<&'a str>,
+    sections: Vec<JsonSection<'a>>,
+    symbols: Vec<JsonSymbolCount>,
+}
+
+#[derive(Serialize)]
+struct JsonSection<'a> {
+    name: &'a str,
+    address: u64,
+    size: u64,
+
+    /// The file offset of the section, unless it takes up no space in the file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<usize>,
+
+    code: bool,
+}
+
+#[derive(Serialize)]
+struct JsonSymbolCount {
+    source: String,
+    count: usize,
+}
+
+#[derive(Serialize)]
+struct JsonAddress<'a> {
+    address: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_offset: Option<usize>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symbol: Option<JsonSymbol<'a>>,
+
+    /// The offset of the address in the symbol.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symbol_offset: Option<u64>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    source_locations: Vec<JsonSourceLocation>,
+}
+
+pub fn print_disassembly(
+    out: &mut dyn Write,
+    sym: &Symbol,
+    dis: &Disassembly,
+) -> anyhow::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, &json_disassembly(sym, dis))?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Writes multiple disassembled symbols as a single JSON array.
+pub fn print_disassemblies(
+    out: &mut dyn Write,
+    disassemblies: &[(&Symbol, Disassembly)],
+) -> anyhow::Result<()> {
+    let json = disassemblies
+        .iter()
+        .map(|&(sym, ref dis)| json_disassembly(sym, dis))
+        .collect::<Vec<_>>();
+    serde_json::to_writer_pretty(&mut *out, &json)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Writes the header record of `--format json-lines`, which comes before the records of
+/// the symbols.
+pub fn print_json_lines_header(out: &mut dyn Write) -> anyhow::Result<()> {
+    print_record(
+        out,
+        &JsonRecord::Header {
+            version: JSON_LINES_VERSION,
+        },
+    )
+}
+
+/// Writes a disassembled symbol as a `symbol` record followed by a `line` record for each
+/// line of its disassembly.
+pub fn print_disassembly_records(
+    out: &mut dyn Write,
+    sym: &Symbol,
+    dis: &Disassembly,
+) -> anyhow::Result<()> {
+    print_record(out, &JsonRecord::Symbol(json_symbol(sym)))?;
+    for line in dis.lines() {
+        print_record(
+            out,
+            &JsonRecord::Line {
+                symbol: sym.address(),
+                line: json_line(line),
+            },
+        )?;
+    }
+    Ok(())
+}
+
+fn print_record(out: &mut dyn Write, record: &JsonRecord) -> anyhow::Result<()> {
+    serde_json::to_writer(&mut *out, record)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn json_symbol(sym: &Symbol) -> JsonSymbol<'_> {
+    JsonSymbol {
+        name: sym.name(),
+        mangled_name: Some(sym.mangled_name()).filter(|&name| name != sym.name()),
+        mangling: sym.rust_mangling().map(|mangling| mangling.to_string()),
+        path: sym.rust_path(),
+        address: sym.address(),
+        size: sym.size(),
+        source: sym.source().to_string(),
+        lang: sym.lang().to_string(),
+    }
+}
+
+fn json_disassembly<'a>(sym: &'a Symbol, dis: &'a Disassembly) -> JsonDisassembly<'a> {
+    JsonDisassembly {
+        symbol: json_symbol(sym),
+        lines: dis.lines().iter().map(json_line).collect(),
+    }
+}
+
+fn json_line(line: &DisasmLine) -> JsonLine<'_> {
+    JsonLine {
+        address: line.address(),
+        bytes: Hex(line.bytes()).to_string(),
+        mnemonic: line.mnemonic(),
+        operands: line.operands(),
+        comments: line.comments(),
+        source_lines: Some(line.source_lines()).filter(|s| !s.is_empty()),
+        source_locations: line
+            .source_locations()
+            .iter()
+            .map(|loc| JsonSourceLocation {
+                file: loc.path().display().to_string(),
+                line: loc.line(),
+            })
+            .collect(),
+        inlined_calls: line
+            .inlined_calls()
+            .iter()
+            .map(|call| JsonInlinedCall {
+                name: call.name(),
+                call_location: call.call_location().map(|loc| JsonSourceLocation {
+                    file: loc.path().display().to_string(),
+                    line: loc.line(),
+                }),
+            })
+            .collect(),
+        jump: match line.jump() {
+            Jump::Internal(idx) => Some(JsonJump::Internal(idx)),
+            Jump::External(addr) => Some(JsonJump::External(addr)),
+            Jump::Indirect(addr) => Some(JsonJump::Indirect(addr)),
+            Jump::None => None,
+        },
+    }
+}
+
+/// Writes the calls between functions. If there is a root node then only the nodes
+/// within `depth` calls of it are written.
+pub fn print_callgraph(
+    out: &mut dyn Write,
+    graph: &CallGraph,
+    root: Option<usize>,
+    depth: usize,
+) -> anyhow::Result<()> {
+    let (nodes, edges) = graph.subgraph(root, depth);
+
+    let json = JsonCallGraph {
+        nodes: nodes
+            .iter()
+            .map(|&node| JsonCallGraphNode {
+                name: graph.nodes()[node].name(),
+                address: graph.nodes()[node].address(),
+            })
+            .collect(),
+        edges: edges
+            .iter()
+            .filter_map(|&(caller, callee)| {
+                Some((
+                    nodes.binary_search(&caller).ok()?,
+                    nodes.binary_search(&callee).ok()?,
+                ))
+            })
+            .collect(),
+    };
+    serde_json::to_writer_pretty(&mut *out, &json)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Writes the sizes of functions or crates.
+pub fn print_sizes(out: &mut dyn Write, report: &SizeReport, by_crate: bool) -> anyhow::Result<()> {
+    let json = JsonSizes {
+        total: report.total,
+        entries: report
+            .entries
+            .iter()
+            .map(|entry| JsonSizeEntry {
+                name: entry.name,
+                crate_name: entry.crate_name,
+                size: entry.size,
+                functions: Some(entry.count).filter(|_| by_crate),
+            })
+            .collect(),
+    };
+    serde_json::to_writer_pretty(&mut *out, &json)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Writes the symbols and source locations that were found for addresses.
+pub fn print_addresses(out: &mut dyn Write, infos: &[AddressInfo]) -> anyhow::Result<()> {
+    let json = infos
+        .iter()
+        .map(|info| JsonAddress {
+            address: info.address,
+            file_offset: info.file_offset,
+            symbol: info.symbol.map(|(sym, _)| json_symbol(sym)),
+            symbol_offset: info.symbol.map(|(_, offset)| offset),
+            source_locations: info
+                .locations
+                .iter()
+                .map(|&(path, line)| JsonSourceLocation {
+                    file: path.display().to_string(),
+                    line,
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_writer_pretty(&mut *out, &json)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Writes the instructions or bytes that matched the pattern of `grep`.
+pub fn print_grep_matches(out: &mut dyn Write, matches: &[GrepMatch]) -> anyhow::Result<()> {
+    let json = matches
+        .iter()
+        .map(|m| JsonGrepMatch {
+            address: m.address,
+            symbol: m.symbol.map(|(sym, _)| json_symbol(sym)),
+            symbol_offset: m.symbol.map(|(_, offset)| offset),
+            instruction: m.instruction.as_deref(),
+            bytes: m.bytes.as_deref(),
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_writer_pretty(&mut *out, &json)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Writes the symbols of a symbol map.
+pub fn print_symbols(out: &mut dyn Write, symbols: &[&Symbol]) -> anyhow::Result<()> {
+    let json = symbols
+        .iter()
+        .map(|&symbol| json_symbol(symbol))
+        .collect::<Vec<_>>();
+    serde_json::to_writer_pretty(&mut *out, &json)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Writes the sections of a binary.
+pub fn print_sections(out: &mut dyn Write, sections: &[Section]) -> anyhow::Result<()> {
+    let json = sections.iter().map(json_section).collect::<Vec<_>>();
+    serde_json::to_writer_pretty(&mut *out, &json)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn json_section(section: &Section) -> JsonSection<'_> {
+    JsonSection {
+        name: section.name(),
+        address: section.address(),
+        size: section.size(),
+        offset: section.offset(),
+        code: section.is_code(),
+    }
+}
+
+/// Writes the summary of a binary that is printed by `info`.
+pub fn print_info(out: &mut dyn Write, bin: &Binary, path: &Path) -> anyhow::Result<()> {
+    let json = JsonInfo {
+        file: path.display().to_string(),
+        format: bin.format().to_string(),
+        arch: bin.arch().to_string(),
+        bits: bin.bits().to_string(),
+        endian: bin.endian().to_string(),
+        os: bin.os().to_string(),
+        build_id: bin.build_id(),
+        debug_info: info::debug_info_kinds(bin),
+        debug_file: bin.debug_file().map(|path| path.display().to_string()),
+        libraries: bin.libraries().iter().map(|library| &**library).collect(),
+        sections: bin.sections().iter().map(json_section).collect(),
+        symbols: info::symbol_counts(bin)
+            .into_iter()
+            .map(|(source, count)| JsonSymbolCount {
+                source: source.to_string(),
+                count,
+            })
+            .collect(),
+    };
+    serde_json::to_writer_pretty(&mut *out, &json)?;
+    writeln!(out)?;
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use super::cli::{MapOpts, Opts, OutputFormat};
+use super::json;
+use crate::disasm::{
+    binary::{Binary, BinaryData, Bits, SearchOptions},
+    symbol::{Symbol, SymbolType},
+};
+use anyhow::Context as _;
+use std::io::Write;
+
+/// The styles of the lines of a text symbol map.
+#[derive(Copy, Clone, PartialEq, Eq, Default)]
+pub enum MapStyle {
+    /// `address type name`, like the output of `nm`.
+    #[default]
+    Nm,
+    /// `address size name` in hexadecimal, like the `/tmp/perf-<pid>.map` files that
+    /// `perf` reads the symbols of JIT compiled code from.
+    Perf,
+}
+
+impl std::str::FromStr for MapStyle {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("nm") {
+            Ok(MapStyle::Nm)
+        } else if s.eq_ignore_ascii_case("perf") {
+            Ok(MapStyle::Perf)
+        } else {
+            Err("invalid symbol map style")
+        }
+    }
+}
+
+/// Writes the symbols of a binary as a symbol map, including the ones that were found in
+/// the debug information or by heuristics in a stripped binary, so that other tools can
+/// use them.
+pub fn run(opts: &Opts, map_opts: &MapOpts) -> anyhow::Result<()> {
+    let binary_path = super::find_binary_path(opts)?;
+    log::debug!("using binary {}", binary_path.display());
+    let data = BinaryData::from_path(&binary_path)
+        .with_context(|| format!("failed to load binary `{}`", binary_path.display()))?;
+    let sources = super::parse_symbol_sources(opts)?;
+    let bin = Binary::new(
+        data,
+        SearchOptions {
+            sources: &sources,
+            dwarf_path: opts.debug_file_path.as_deref(),
+            dsym_path: opts.dsym_path.as_deref(),
+            pdb_path: None,
+            archive_member: opts.archive_member.as_deref(),
+            cache_symbols: !opts.no_cache,
+            lazy_dwarf: false,
+            include_locals: opts.include_locals,
+            exclude_compiler_generated: opts.exclude_compiler_generated,
+            mangled_names: opts.no_demangle,
+            rust_hashes: opts.keep_hashes,
+        },
+    )?;
+
+    let symbols = super::symbol_query(&bin, opts).sized().functions().to_vec();
+    log::debug!("writing {} symbols", symbols.len());
+
+    let stdout = std::io::stdout();
+    let result = match opts.format {
+        OutputFormat::Text => print_map(&mut stdout.lock(), &bin, &symbols, map_opts.style),
+        OutputFormat::Json => json::print_symbols(&mut stdout.lock(), &symbols),
+        _ => {
+            return Err(anyhow::anyhow!(
+                "symbol maps can only be written as text or JSON"
+            ))
+        }
+    };
+    result.context("error occured while writing symbol map")
+}
+
+fn print_map(
+    out: &mut dyn Write,
+    bin: &Binary,
+    symbols: &[&Symbol],
+    style: MapStyle,
+) -> anyhow::Result<()> {
+    let addr_width = if bin.bits() == Bits::Bits32 { 8 } else { 16 };
+    for symbol in symbols {
+        match style {
+            MapStyle::Nm => {
+                let kind = match symbol.symbol_type() {
+                    SymbolType::Function => 'T',
+                    SymbolType::Static => 'D',
+                };
+                writeln!(
+                    out,
+                    "{:01$x} {2} {3}",
+                    symbol.address(),
+                    addr_width,
+                    kind,
+                    symbol.name()
+                )?;
+            }
+            MapStyle::Perf => writeln!(
+                out,
+                "{:x} {:x} {}",
+                symbol.address(),
+                symbol.size(),
+                symbol.name()
+            )?,
+        }
+    }
+    Ok(())
+}
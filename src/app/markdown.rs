@@ -0,0 +1,88 @@
+//! Markdown output, which wraps the disassembly of each symbol in a fenced code block with
+//! a header that describes the build, for pasting into issues about codegen.
+
+use crate::disasm::{binary::Arch, symbol::Symbol};
+use std::io::Write;
+use std::path::Path;
+use termcolor::{NoColor, WriteColor};
+
+/// The build that the disassembled binary came from.
+pub struct BuildInfo<'a> {
+    pub binary: &'a Path,
+    pub arch: Arch,
+    /// The profile of the build, which is unknown for binaries that were passed in by path.
+    pub profile: Option<&'a str>,
+    pub build_id: Option<&'a str>,
+}
+
+/// Writes a header for a symbol followed by a fenced code block with the output of `print`,
+/// which is printed without colors.
+pub fn print_symbol(
+    out: &mut dyn Write,
+    symbol: &Symbol,
+    build: &BuildInfo,
+    print: impl FnOnce(&mut dyn WriteColor) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let mut text = NoColor::new(Vec::new());
+    print(&mut text)?;
+    let mut text = text.into_inner();
+    if !text.ends_with(b"\n") {
+        text.push(b'\n');
+    }
+
+    writeln!(out, "### {}", Code(symbol.name()))?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "`{:#x}..{:#x}` ({} bytes)",
+        symbol.address(),
+        symbol.end_address(),
+        symbol.size()
+    )?;
+    writeln!(out)?;
+
+    let file_name = build
+        .binary
+        .file_name()
+        .unwrap_or(build.binary.as_os_str())
+        .to_string_lossy();
+    write!(out, "{} · {}", Code(&file_name), build.arch)?;
+    if let Some(profile) = build.profile {
+        write!(out, " · {}", profile)?;
+    }
+    if let Some(build_id) = build.build_id {
+        write!(out, " · build ID `{}`", build_id)?;
+    }
+    writeln!(out, " · cargo-disasm {}", env!("CARGO_PKG_VERSION"))?;
+    writeln!(out)?;
+
+    // The fence has to be longer than any run of backticks in the code block.
+    let fence = "`".repeat(longest_backtick_run(&text).max(2) + 1);
+    let language = if symbol.is_function() { "asm" } else { "text" };
+    writeln!(out, "{}{}", fence, language)?;
+    out.write_all(&text)?;
+    writeln!(out, "{}", fence)?;
+    Ok(())
+}
+
+fn longest_backtick_run(text: &[u8]) -> usize {
+    text.split(|&b| b != b'`')
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Formats text as inline code, using as many backticks as it takes to include the
+/// backticks in the text (e.g. in the names of some closures).
+struct Code<'s>(&'s str);
+
+impl std::fmt::Display for Code<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ticks = "`".repeat(longest_backtick_run(self.0.as_bytes()) + 1);
+        if self.0.starts_with('`') || self.0.ends_with('`') {
+            write!(f, "{} {} {}", ticks, self.0, ticks)
+        } else {
+            write!(f, "{}{}{}", ticks, self.0, ticks)
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use crate::disasm::{binary::Arch, symbol::Symbol, Disassembly, Syntax};
+use std::io::Write;
+
+/// Writes the disassembly of a symbol as assembly that can be analyzed by `llvm-mca`,
+/// inside of a code region that is named after the symbol.
+pub fn print_disassembly(
+    out: &mut dyn Write,
+    arch: Arch,
+    syntax: Syntax,
+    symbol: &Symbol,
+    disassembly: &Disassembly,
+) -> anyhow::Result<()> {
+    // llvm-mca reads AT&T syntax by default.
+    if matches!(arch, Arch::X86 | Arch::X86_64) && syntax == Syntax::Intel {
+        writeln!(out, ".intel_syntax noprefix")?;
+    }
+
+    writeln!(out, "# LLVM-MCA-BEGIN {}", symbol.name())?;
+    for line in disassembly.lines() {
+        // Data in between instructions, such as `.word`s, is only kept as a comment so
+        // that it isn't treated as an instruction.
+        if line.mnemonic().starts_with('.') {
+            writeln!(out, "# {} {}", line.mnemonic(), line.operands())?;
+        } else if line.operands().is_empty() {
+            writeln!(out, "{}", line.mnemonic())?;
+        } else {
+            writeln!(out, "{} {}", line.mnemonic(), line.operands())?;
+        }
+    }
+    writeln!(out, "# LLVM-MCA-END")?;
+    Ok(())
+}
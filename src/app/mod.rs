@@ -1,21 +1,73 @@
+mod addr;
+mod arrows;
+mod asm;
+mod build;
+mod callgraph;
+mod cargo_asm;
 pub mod cli;
+mod config;
+mod coverage;
+mod crash;
+mod diff;
+mod dot;
+mod grep;
+mod hexdump;
+mod html;
+mod info;
+mod json;
 pub mod logging;
+mod map;
+mod markdown;
+mod mca;
+mod perf;
 mod printer;
+mod sections;
+mod sizes;
+mod theme;
+#[cfg(feature = "tui")]
+mod tui;
+mod vectorized;
+mod watch;
 
 use crate::disasm::{
     self,
-    binary::{Binary, BinaryData, SearchOptions},
-    symbol::SymbolSource,
+    binary::{self, Binary, BinaryData, SearchOptions},
+    callgraph::CallGraph,
+    cfg::ControlFlowGraph,
+    cost, frame, panics,
+    stats::InstructionStats,
+    symbol::{Symbol, SymbolSource},
+    symbol_table::SymbolQuery,
+    unwind, vector,
 };
 use anyhow::Context as _;
 use clap::Clap as _;
-use cli::Opts;
+use cli::{Export, Opts, OutputFormat, TargetKind};
 use logging::AppLogger;
-use std::path::PathBuf;
+use regex::Regex;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
 use termcolor::ColorChoice;
 use termcolor::StandardStream;
+use termcolor::WriteColor;
 
 fn parse_options() -> Opts {
+    let mut opts = parse_options_from_args();
+
+    if let Some(file) = opts.file.take() {
+        opts.binary_path = Some(file);
+    }
+
+    // There is no symbol name when disassembling or listing everything so the
+    // only positional argument is the binary.
+    if (opts.all || opts.sizes || opts.raw) && opts.binary_path.is_none() && !opts.build {
+        opts.binary_path = opts.symbol.take().map(PathBuf::from);
+    }
+
+    opts
+}
+
+fn parse_options_from_args() -> Opts {
     if std::env::var("CARGO").is_ok() {
         let mut args = std::env::args_os().collect::<Vec<_>>();
         if args.len() > 2 && args[1] == "disasm" {
@@ -31,10 +83,14 @@ fn parse_options() -> Opts {
 }
 
 pub fn run() -> anyhow::Result<()> {
-    let opts = parse_options();
+    let mut opts = parse_options();
 
     unsafe { AppLogger::instance().set_level(opts.log_level_filter()) };
-    let color_choice = match opts.color_choice {
+    if !opts.no_config {
+        opts.config = config::Config::load()?;
+    }
+
+    let color_choice = match opts.color_choice()? {
         ColorChoice::Auto => unsafe {
             let out_choice = if atty::is(atty::Stream::Stdout) {
                 ColorChoice::Always
@@ -59,23 +115,368 @@ pub fn run() -> anyhow::Result<()> {
         },
     };
 
+    match opts.command {
+        Some(cli::Command::Diff(ref diff_opts)) => {
+            return diff::run(&opts, diff_opts, color_choice)
+        }
+        Some(cli::Command::Addr(ref addr_opts)) => {
+            return addr::run(&opts, addr_opts, color_choice)
+        }
+        Some(cli::Command::Core(ref core_opts)) => {
+            return crash::run(&opts, core_opts, color_choice)
+        }
+        Some(cli::Command::Grep(ref grep_opts)) => {
+            return grep::run(&opts, grep_opts, color_choice)
+        }
+        Some(cli::Command::Info) => return info::run(&opts, color_choice),
+        Some(cli::Command::Map(ref map_opts)) => return map::run(&opts, map_opts),
+        Some(cli::Command::Sections) => return sections::run_list(&opts, color_choice),
+        Some(cli::Command::Section(ref section_opts)) => {
+            return sections::run(&opts, section_opts, color_choice)
+        }
+        None => {}
+    }
+
+    if opts.watch {
+        return watch::run(&opts, color_choice);
+    }
+
     let binary_path = find_binary_path(&opts)?;
     log::debug!("using binary {}", binary_path.display());
     let data = BinaryData::from_path(&binary_path)
         .with_context(|| format!("failed to load binary `{}`", binary_path.display()))?;
-    let mut sources = Vec::new();
-    for s in opts.symbol_sources.iter() {
-        if s.eq_ignore_ascii_case("all") {
-            // object file formats
-            sources.push(SymbolSource::Elf);
-            sources.push(SymbolSource::Mach);
-            sources.push(SymbolSource::Pe);
-            sources.push(SymbolSource::Archive);
+    let sources = parse_symbol_sources(&opts)?;
 
-            // debug formats
-            sources.push(SymbolSource::Dwarf);
-            sources.push(SymbolSource::Pdb);
+    // Only the DWARF symbols near the symbol that is found with the other sources are
+    // needed to disassemble a single symbol.
+    let lazy_dwarf = sources.is_empty()
+        && !opts.all
+        && !opts.regex
+        && !opts.callgraph
+        && !opts.tui
+        && !opts.sizes;
+
+    let search_options = SearchOptions {
+        sources: &sources,
+        dwarf_path: opts.debug_file_path.as_deref(),
+        dsym_path: opts.dsym_path.as_deref(),
+        pdb_path: None,
+        archive_member: opts.archive_member.as_deref(),
+        cache_symbols: !opts.no_cache,
+        lazy_dwarf,
+        include_locals: opts.include_locals,
+        exclude_compiler_generated: opts.exclude_compiler_generated,
+        mangled_names: opts.no_demangle,
+        rust_hashes: opts.keep_hashes,
+    };
+    let mut bin = match opts.arch {
+        Some(arch) if opts.raw => Binary::new_raw(data, arch, opts.base)?,
+        _ => Binary::new(data, search_options)?,
+    };
+
+    if lazy_dwarf {
+        if let Some(name) = opts.symbol.as_deref() {
+            match bin.fuzzy_find_symbol(name).map(Symbol::address) {
+                Some(address) => bin.load_dwarf_symbols_at(address)?,
+                // The symbol may only be named by DWARF.
+                None => bin.load_deferred_dwarf_symbols()?,
+            }
+        }
+    }
+
+    // The DWARF compilation units are also used to find the crates of functions without
+    // a Rust path.
+    if opts.show_source
+        || opts.show_inlined
+        || opts.coverage.is_some()
+        || opts.crates
+        || opts.crate_name.is_some()
+        || opts.vars
+    {
+        bin.load_line_information()?;
+    }
+
+    if opts.callgraph {
+        return print_callgraph(&bin, &opts, color_choice);
+    }
+
+    if opts.tui {
+        return run_tui(&bin, &opts);
+    }
+
+    if opts.sizes {
+        return print_sizes(&bin, &opts, color_choice);
+    }
+
+    let symbols = find_symbols(&bin, &opts)?;
+
+    if opts.vectorized {
+        if opts.format != OutputFormat::Text {
+            return Err(anyhow::anyhow!(
+                "vectorization reports can only be printed as text"
+            ));
+        }
+        let mut reports = Vec::with_capacity(symbols.len());
+        for &symbol in symbols.iter().filter(|symbol| symbol.is_function()) {
+            if let Some(d) = disasm_symbol(&bin, symbol, &opts)? {
+                reports.push((symbol, vector::analyze(bin.arch(), &d)));
+            }
+        }
+        return vectorized::print_reports(&mut StandardStream::stdout(color_choice), &reports)
+            .context("error occured while printing vectorization reports");
+    }
 
+    if let Some(Export::Mca) = opts.export {
+        let syntax = opts.syntax()?;
+        let stdout = std::io::stdout();
+        let mut stdout = stdout.lock();
+        for &symbol in symbols.iter().filter(|symbol| symbol.is_function()) {
+            if let Some(disassembly) = disasm_symbol(&bin, symbol, &opts)? {
+                mca::print_disassembly(&mut stdout, bin.arch(), syntax, symbol, &disassembly)
+                    .context("error occured while writing llvm-mca assembly")?;
+            }
+        }
+        return Ok(());
+    }
+
+    if opts.cfg || opts.format == OutputFormat::Dot {
+        let stdout = std::io::stdout();
+        let mut stdout = stdout.lock();
+        for &symbol in symbols.iter() {
+            if let Some(disassembly) = disasm_symbol(&bin, symbol, &opts)? {
+                let cfg = ControlFlowGraph::new(&disassembly);
+                dot::print_cfg(&mut stdout, symbol, &disassembly, &cfg)
+                    .context("error occured while writing control flow graph")?;
+            }
+        }
+        return Ok(());
+    }
+
+    match opts.format {
+        OutputFormat::Text | OutputFormat::Markdown => {
+            let theme = opts.theme()?;
+            let profile = opts.perf.as_deref().map(perf::Profile::load).transpose()?;
+            let coverage = opts
+                .coverage
+                .as_deref()
+                .map(|path| coverage::Coverage::load(path, &binary_path))
+                .transpose()?;
+            let build = markdown::BuildInfo {
+                binary: &binary_path,
+                arch: bin.arch(),
+                profile: build_profile(&opts),
+                build_id: bin.build_id(),
+            };
+            let mut stdout = StandardStream::stdout(color_choice);
+            for (idx, &symbol) in symbols.iter().enumerate() {
+                if idx > 0 {
+                    writeln!(stdout)?;
+                }
+
+                if !symbol.is_function() {
+                    let print =
+                        |out: &mut dyn WriteColor| hexdump::print_hex_dump(out, &bin, symbol);
+                    let result = if opts.format == OutputFormat::Markdown {
+                        markdown::print_symbol(&mut stdout, symbol, &build, print)
+                    } else {
+                        print(&mut stdout)
+                    };
+                    result.context("error occured while printing hex dump")?;
+                    continue;
+                }
+
+                let disassembly = if let Some(d) = disasm_symbol(&bin, symbol, &opts)? {
+                    d
+                } else {
+                    continue;
+                };
+
+                let archive_member = bin.archive_member_at(symbol.offset());
+                let aliases = bin.symbol_table().aliases(symbol);
+                let samples = profile
+                    .as_ref()
+                    .map(|profile| profile.samples_for(symbol, &disassembly));
+                let executed = coverage
+                    .as_ref()
+                    .map(|coverage| coverage.executed_lines(&disassembly));
+                let frame = if opts.no_frame {
+                    None
+                } else {
+                    frame::analyze(&bin, symbol, &disassembly).with_context(|| {
+                        format!("failed to analyze the stack frame of `{}`", symbol.name())
+                    })?
+                };
+                let unwind = if opts.unwind {
+                    unwind::directives_for(&bin, symbol).with_context(|| {
+                        format!(
+                            "failed to read the unwind information of `{}`",
+                            symbol.name()
+                        )
+                    })?
+                } else {
+                    Vec::new()
+                };
+                let call_sites = if opts.landing_pads {
+                    unwind::call_sites_for(&bin, symbol).with_context(|| {
+                        format!("failed to read the exception table of `{}`", symbol.name())
+                    })?
+                } else {
+                    Vec::new()
+                };
+                let panic_paths = if opts.fold_panics {
+                    panics::find_panic_paths(&disassembly)
+                } else {
+                    Vec::new()
+                };
+                let checks = if opts.checks {
+                    Some(panics::find_checks(&disassembly))
+                } else {
+                    None
+                };
+                let stats = if opts.show_stats() {
+                    Some(InstructionStats::collect(bin.arch(), &disassembly))
+                } else {
+                    None
+                };
+                let costs = if opts.cost {
+                    Some(cost::estimate_all(bin.arch(), &disassembly))
+                } else {
+                    None
+                };
+                let print = |out: &mut dyn WriteColor| {
+                    if opts.cargo_asm {
+                        return cargo_asm::print_disassembly(
+                            out,
+                            &bin,
+                            symbol,
+                            &disassembly,
+                            opts.show_source,
+                        );
+                    }
+                    printer::print_disassembly(
+                        out,
+                        symbol,
+                        &disassembly,
+                        printer::DisasmOptions {
+                            show_source: opts.show_source,
+                            show_inlined: opts.show_inlined,
+                            show_bytes: opts.show_bytes(),
+                            bytes_format: opts.bytes_format(bin.endian())?,
+                            show_header: opts.disassemble_all() || archive_member.is_some(),
+                            show_mangled: opts.show_mangled,
+                            labels: !opts.no_labels,
+                            arrows: opts.arrow_style(),
+                            theme: &theme,
+                            archive_member,
+                            marker: None,
+                            aliases: &aliases,
+                            frame: frame.as_ref(),
+                            unwind: &unwind,
+                            call_sites: &call_sites,
+                            costs: costs.as_deref(),
+                            samples: samples.as_deref(),
+                            executed: executed.as_deref(),
+                            panic_paths: &panic_paths,
+                            stats: stats.as_ref(),
+                            checks: checks.as_deref(),
+                        },
+                    )
+                };
+                let result = if opts.format == OutputFormat::Markdown {
+                    markdown::print_symbol(&mut stdout, symbol, &build, print)
+                } else {
+                    print(&mut stdout)
+                };
+                result.context("error occured while printing disassembly")?;
+            }
+        }
+
+        // Handled above.
+        OutputFormat::Dot => unreachable!(),
+
+        OutputFormat::Json => {
+            let mut disassemblies = Vec::with_capacity(symbols.len());
+            for &symbol in symbols.iter() {
+                if let Some(d) = disasm_symbol(&bin, symbol, &opts)? {
+                    disassemblies.push((symbol, d));
+                }
+            }
+
+            let stdout = std::io::stdout();
+            let result = if opts.regex || opts.disassemble_all() {
+                json::print_disassemblies(&mut stdout.lock(), &disassemblies)
+            } else {
+                let (symbol, ref disassembly) = disassemblies[0];
+                json::print_disassembly(&mut stdout.lock(), symbol, disassembly)
+            };
+            result.context("error occured while writing JSON disassembly")?;
+        }
+
+        OutputFormat::JsonLines => {
+            let stdout = std::io::stdout();
+            let mut stdout = stdout.lock();
+            json::print_json_lines_header(&mut stdout)
+                .context("error occured while writing JSON lines")?;
+            // Symbols are written as soon as they're disassembled so that long dumps can
+            // be processed while they're written.
+            for &symbol in symbols.iter() {
+                if let Some(d) = disasm_symbol(&bin, symbol, &opts)? {
+                    json::print_disassembly_records(&mut stdout, symbol, &d)
+                        .context("error occured while writing JSON lines")?;
+                }
+            }
+        }
+
+        OutputFormat::Html => {
+            let mut disassemblies = Vec::with_capacity(symbols.len());
+            for &symbol in symbols.iter().filter(|symbol| symbol.is_function()) {
+                if let Some(d) = disasm_symbol(&bin, symbol, &opts)? {
+                    disassemblies.push((symbol, d));
+                }
+            }
+
+            html::print_disassemblies(&mut std::io::stdout().lock(), &bin, &disassemblies)
+                .context("error occured while writing HTML disassembly")?;
+        }
+
+        OutputFormat::Asm => {
+            let stdout = std::io::stdout();
+            let mut stdout = stdout.lock();
+            asm::print_header(&mut stdout, bin.arch(), opts.syntax()?)
+                .context("error occured while writing assembly")?;
+            let functions = symbols.iter().filter(|symbol| symbol.is_function());
+            for (idx, &symbol) in functions.enumerate() {
+                if let Some(d) = disasm_symbol(&bin, symbol, &opts)? {
+                    asm::print_disassembly(&mut stdout, &bin, idx, symbol, &d)
+                        .context("error occured while writing assembly")?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The symbol sources that `--symsrc all` loads.
+const ALL_SYMBOL_SOURCES: &[SymbolSource] = &[
+    // object file formats
+    SymbolSource::Elf,
+    SymbolSource::Mach,
+    SymbolSource::Pe,
+    SymbolSource::Archive,
+    SymbolSource::Wasm,
+    // debug formats
+    SymbolSource::Dwarf,
+    SymbolSource::Pdb,
+];
+
+/// Returns the symbol sources that were passed in on the command line or set in the
+/// configuration file. An empty list means that the sources are chosen automatically.
+fn parse_symbol_sources(opts: &Opts) -> anyhow::Result<Vec<SymbolSource>> {
+    let mut sources = Vec::new();
+    for s in opts.symbol_sources() {
+        if s.eq_ignore_ascii_case("all") {
+            sources.extend_from_slice(ALL_SYMBOL_SOURCES);
             break;
         } else if s.eq_ignore_ascii_case("auto") {
             sources.clear();
@@ -88,11 +489,14 @@ pub fn run() -> anyhow::Result<()> {
             sources.push(SymbolSource::Pe);
         } else if s.eq_ignore_ascii_case("archive") {
             sources.push(SymbolSource::Archive);
+        } else if s.eq_ignore_ascii_case("wasm") {
+            sources.push(SymbolSource::Wasm);
         } else if s.eq_ignore_ascii_case("obj") {
             sources.push(SymbolSource::Elf);
             sources.push(SymbolSource::Mach);
             sources.push(SymbolSource::Pe);
             sources.push(SymbolSource::Archive);
+            sources.push(SymbolSource::Wasm);
         } else if s.eq_ignore_ascii_case("dwarf") {
             sources.push(SymbolSource::Dwarf);
         } else if s.eq_ignore_ascii_case("pdb") {
@@ -106,49 +510,303 @@ pub fn run() -> anyhow::Result<()> {
     }
     sources.sort_unstable();
     sources.dedup();
+    Ok(sources)
+}
 
-    let search_options = SearchOptions {
-        sources: &sources,
-        dwarf_path: None,
-        dsym_path: None,
-        pdb_path: None,
+/// Prints the sizes of the functions or crates in the binary.
+fn print_sizes(bin: &Binary, opts: &Opts, color_choice: ColorChoice) -> anyhow::Result<()> {
+    let mut report = sizes::SizeReport::new(bin, symbol_query(bin, opts), opts.crates);
+    report.truncate(opts.limit);
+
+    let result = match opts.format {
+        OutputFormat::Text => {
+            let mut stdout = StandardStream::stdout(color_choice);
+            sizes::print_sizes(&mut stdout, &report, opts.crates)
+        }
+        OutputFormat::Json => {
+            json::print_sizes(&mut std::io::stdout().lock(), &report, opts.crates)
+        }
+        OutputFormat::Dot => {
+            return Err(anyhow::anyhow!(
+                "sizes can't be written as a Graphviz DOT graph"
+            ))
+        }
+        OutputFormat::Html => return Err(anyhow::anyhow!("sizes can't be written as HTML")),
+        OutputFormat::Markdown => {
+            return Err(anyhow::anyhow!("sizes can't be written as Markdown"))
+        }
+        OutputFormat::JsonLines => {
+            return Err(anyhow::anyhow!("sizes can't be written as JSON lines"))
+        }
+        OutputFormat::Asm => return Err(anyhow::anyhow!("sizes can't be written as assembly")),
     };
-    let mut bin = Binary::new(data, search_options)?;
+    result.context("error occured while printing sizes")
+}
 
-    if opts.show_source {
-        bin.load_line_information()?;
-    }
+/// Prints the call graph of the entire binary or the callers and
+/// callees of a single symbol.
+fn print_callgraph(bin: &Binary, opts: &Opts, color_choice: ColorChoice) -> anyhow::Result<()> {
+    let graph = CallGraph::new(bin).context("error while building call graph")?;
 
-    // FIXME temporary test code
-    if let Some(symbol) = bin.fuzzy_find_symbol(&opts.symbol) {
-        let disassembly = disasm::disasm(&bin, symbol, opts.show_source)?;
-        let mut stdout = StandardStream::stdout(color_choice);
-        printer::print_disassembly(
-            &mut stdout,
-            symbol,
-            &disassembly,
-            printer::DisasmOptions {
-                show_source: opts.show_source,
-                show_bytes: opts.show_bytes,
-            },
+    let root = if let Some(ref name) = opts.symbol {
+        let symbol = bin
+            .fuzzy_find_symbol(name)
+            .with_context(|| format!("no symbol matching `{}` was found", name))?;
+        Some(
+            graph
+                .node_at(symbol.address())
+                .with_context(|| format!("`{}` is not in the call graph", symbol.name()))?,
         )
-        .context("error occured while printing disassembly")?;
     } else {
+        None
+    };
+
+    let result = match opts.format {
+        OutputFormat::Text => {
+            let mut stdout = StandardStream::stdout(color_choice);
+            callgraph::print_callgraph(&mut stdout, &graph, root, opts.depth)
+        }
+        OutputFormat::Json => {
+            json::print_callgraph(&mut std::io::stdout().lock(), &graph, root, opts.depth)
+        }
+        OutputFormat::Dot => {
+            dot::print_callgraph(&mut std::io::stdout().lock(), &graph, root, opts.depth)
+        }
+        OutputFormat::Html => return Err(anyhow::anyhow!("call graphs can't be written as HTML")),
+        OutputFormat::Markdown => {
+            return Err(anyhow::anyhow!("call graphs can't be written as Markdown"))
+        }
+        OutputFormat::JsonLines => {
+            return Err(anyhow::anyhow!(
+                "call graphs can't be written as JSON lines"
+            ))
+        }
+        OutputFormat::Asm => {
+            return Err(anyhow::anyhow!("call graphs can't be written as assembly"))
+        }
+    };
+    result.context("error occured while printing call graph")
+}
+
+/// Disassembles a single symbol. When disassembling every symbol in the binary
+/// errors are only logged so that a single bad symbol does not stop everything
+/// else from being printed. Data symbols are not disassembled.
+fn disasm_symbol(
+    bin: &Binary,
+    symbol: &Symbol,
+    opts: &Opts,
+) -> anyhow::Result<Option<disasm::Disassembly>> {
+    if !symbol.is_function() {
+        let message = format!(
+            "`{}` is a {} and can only be printed as a text hex dump",
+            symbol.name(),
+            symbol.symbol_type()
+        );
+        if opts.disassemble_all() || opts.regex {
+            log::warn!("{}", message);
+            return Ok(None);
+        }
+        return Err(anyhow::anyhow!(message));
+    }
+
+    let options = disasm::Options {
+        // Coverage is matched to instructions using their source lines.
+        load_source: opts.show_source || opts.coverage.is_some(),
+        load_inlined_calls: opts.show_inlined,
+        load_variables: opts.vars,
+        syntax: opts.syntax()?,
+        no_skip_data: opts.no_skip_data,
+        engine: opts.engine,
+        explain: opts.explain,
+    };
+
+    match disasm::disasm(bin, symbol, options) {
+        Ok(disassembly) => Ok(Some(disassembly)),
+        Err(err) if opts.disassemble_all() => {
+            log::warn!("failed to disassemble `{}`: {:?}", symbol.name(), err);
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(feature = "tui")]
+fn run_tui(bin: &Binary, opts: &Opts) -> anyhow::Result<()> {
+    let initial = match opts.symbol.as_deref() {
+        Some(_) => Some(find_symbols(bin, opts)?[0]),
+        None => None,
+    };
+    let options = disasm::Options {
+        load_source: opts.show_source,
+        load_inlined_calls: opts.show_inlined,
+        load_variables: opts.vars,
+        syntax: opts.syntax()?,
+        no_skip_data: opts.no_skip_data,
+        engine: opts.engine,
+        explain: opts.explain,
+    };
+    tui::run(bin, initial, options)
+}
+
+#[cfg(not(feature = "tui"))]
+fn run_tui(_bin: &Binary, _opts: &Opts) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!(
+        "cargo-disasm was built without the `tui` feature"
+    ))
+}
+
+/// Finds the symbols that should be disassembled using the symbol name
+/// or pattern that was passed in on the command line.
+fn find_symbols<'b>(bin: &'b Binary, opts: &Opts) -> anyhow::Result<Vec<&'b Symbol>> {
+    if opts.disassemble_all() {
+        let symbols = symbol_query(bin, opts).sized().functions().to_vec();
+        if symbols.is_empty() {
+            if let Some(ref crate_name) = opts.crate_name {
+                return Err(anyhow::anyhow!(
+                    "no functions from the crate `{}` were found",
+                    crate_name
+                ));
+            }
+        }
+        log::debug!("disassembling {} symbols", symbols.len());
+        return Ok(symbols);
+    }
+
+    let name = opts
+        .symbol
+        .as_deref()
+        .context("a symbol name is required")?;
+
+    if opts.regex {
+        let regex = Regex::new(name)
+            .with_context(|| format!("`{}` is not a valid regular expression", name))?;
+        let symbols = symbol_query(bin, opts).name_regex(&regex).to_vec();
+        if symbols.is_empty() {
+            return Err(anyhow::anyhow!("no symbols matching `{}` were found", name));
+        }
+        log::debug!("found {} symbols matching `{}`", symbols.len(), name);
+        Ok(symbols)
+    } else if opts.pick {
+        pick_symbol(bin, name).map(|symbol| vec![symbol])
+    } else if let Some(symbol) = bin.fuzzy_find_symbol(name) {
+        Ok(vec![symbol])
+    } else {
+        let mut message = format!("no symbol matching `{}` was found", name);
+        let suggestions = bin.suggest_symbols(name, SUGGESTION_COUNT);
+        if !suggestions.is_empty() {
+            message.push_str("\n\nthe most similar symbols are:");
+            for symbol in suggestions {
+                message.push_str("\n    ");
+                message.push_str(symbol.name());
+            }
+        }
+        Err(anyhow::anyhow!(message))
+    }
+}
+
+/// Returns a query for the symbols of the binary that only lists the symbols of the crate
+/// that was passed with `--crate`, if any.
+fn symbol_query<'b>(bin: &'b Binary, opts: &Opts) -> SymbolQuery<'b> {
+    let query = bin.symbol_table().iter();
+    match opts.crate_name {
+        Some(ref crate_name) => query.crate_name(crate_name),
+        None => query,
+    }
+}
+
+/// The number of similar symbols that are listed when a symbol isn't found.
+const SUGGESTION_COUNT: usize = 10;
+
+/// Lists the symbols that are most similar to `name` and asks which one to disassemble.
+fn pick_symbol<'b>(bin: &'b Binary, name: &str) -> anyhow::Result<&'b Symbol> {
+    use std::io::BufRead as _;
+
+    if !atty::is(atty::Stream::Stdin) {
+        return Err(anyhow::anyhow!("`--pick` requires an interactive terminal"));
+    }
+
+    let mut candidates = Vec::with_capacity(SUGGESTION_COUNT);
+    candidates.extend(bin.fuzzy_find_symbol(name));
+    for symbol in bin.suggest_symbols(name, SUGGESTION_COUNT) {
+        if candidates.len() < SUGGESTION_COUNT
+            && !candidates
+                .iter()
+                .any(|c: &&Symbol| c.name() == symbol.name())
+        {
+            candidates.push(symbol);
+        }
+    }
+    if candidates.is_empty() {
         return Err(anyhow::anyhow!(
-            "no symbol matching `{}` was found",
-            opts.symbol
+            "no symbols similar to `{}` were found",
+            name
         ));
     }
 
-    Ok(())
+    let stderr = std::io::stderr();
+    let mut stderr = stderr.lock();
+    for (idx, symbol) in candidates.iter().enumerate() {
+        writeln!(
+            stderr,
+            "{:>3}) {} (0x{:x})",
+            idx + 1,
+            symbol.name(),
+            symbol.address()
+        )?;
+    }
+    write!(stderr, "pick a symbol [1-{}]: ", candidates.len())?;
+    stderr.flush()?;
+
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line)?;
+    line.trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|choice| candidates.get(choice.checked_sub(1)?))
+        .copied()
+        .with_context(|| format!("`{}` is not one of the listed symbols", line.trim()))
+}
+
+/// Use options to find the binary to search for the symbol in, or the shared library that
+/// it depends on with `--lib`.
+/// Returns the name of the profile that the binary was built with, or `None` if the binary
+/// was passed in by path.
+fn build_profile(opts: &Opts) -> Option<&str> {
+    if opts.binary_path.is_some() {
+        None
+    } else if let Some(ref profile) = opts.profile {
+        Some(profile)
+    } else if opts.release {
+        Some("release")
+    } else {
+        Some("dev")
+    }
 }
 
-/// Use options to find the binary to search for the symbol in.
 fn find_binary_path(opts: &Opts) -> anyhow::Result<PathBuf> {
+    let path = find_artifact_path(opts)?;
+    let name = match opts.library {
+        Some(ref name) => name,
+        None => return Ok(path),
+    };
+
+    let data = BinaryData::from_path(&path)
+        .with_context(|| format!("failed to load binary `{}`", path.display()))?;
+    let library = binary::find_library(&data, name)
+        .with_context(|| format!("failed to find library `{}` of `{}`", name, path.display()))?;
+    log::debug!("using library {} of {}", library.display(), path.display());
+    Ok(library)
+}
+
+/// Use options to find the binary of the Cargo project if no path was passed in.
+fn find_artifact_path(opts: &Opts) -> anyhow::Result<PathBuf> {
     use cargo_metadata::{MetadataCommand, Package, Target};
     if let Some(ref b) = opts.binary_path {
         return Ok(b.clone());
     }
+    if opts.build {
+        return build::build_binary(opts);
+    }
 
     log::trace!("running cargo_metadata");
     let mut cmd = MetadataCommand::new();
@@ -173,23 +831,40 @@ fn find_binary_path(opts: &Opts) -> anyhow::Result<PathBuf> {
         }
     };
 
-    let match_target = |target: &Target| {
-        if let Some(ref t) = opts.target_name {
-            if !target.name.eq_ignore_ascii_case(t) {
-                return false;
-            }
+    // `--target` used to be the long form of `-t` before it took a target triple.
+    if let Some(triple) = opts.target_triple.as_deref() {
+        let is_target_name = metadata
+            .packages
+            .iter()
+            .filter(|p| match_package(p))
+            .flat_map(|p| &p.targets)
+            .any(|t| t.name.eq_ignore_ascii_case(triple));
+        if is_target_name {
+            log::warn!(
+                "`--target {0}` picks a target triple, use `--bin {0}` (or `-t {0}`) to choose the target named `{0}`",
+                triple
+            );
         }
-        // FIXME support the other target types at some point (test, example, bench, lib)
-        target.kind.iter().any(|k| k == "bin")
+    }
+
+    let (kind, name) = opts.target();
+    let match_name =
+        |target: &Target| name.is_none_or(|name| target.name.eq_ignore_ascii_case(name));
+    let find_targets = |match_target: &dyn Fn(&Target) -> bool| {
+        metadata
+            .packages
+            .iter()
+            .filter(|p| match_package(p))
+            .flat_map(|p| p.targets.iter().map(move |t| (p, t)))
+            .filter(|(_, t)| match_name(t) && match_target(t))
+            .collect::<Vec<(&Package, &Target)>>()
     };
 
-    let found_targets = metadata
-        .packages
-        .iter()
-        .filter(|p| match_package(p))
-        .flat_map(|p| p.targets.iter().map(move |t| (p, t)))
-        .filter(|(_, t)| match_target(t))
-        .collect::<Vec<(&Package, &Target)>>();
+    let mut found_targets = find_targets(&|target| target.kind.iter().any(|k| k == kind.as_str()));
+    // Packages without binaries can still build libraries that can be disassembled.
+    if found_targets.is_empty() && kind == TargetKind::Bin {
+        found_targets = find_targets(&is_native_library);
+    }
 
     if found_targets.is_empty() {
         return Err(anyhow::anyhow!("no matching targets were found"));
@@ -209,10 +884,35 @@ fn find_binary_path(opts: &Opts) -> anyhow::Result<PathBuf> {
 
     let (_package, target) = found_targets.into_iter().next().unwrap();
     let mut path = metadata.target_directory.clone();
-    if opts.release {
-        path.push("release");
-    } else {
-        path.push("debug");
+    if let Some(ref triple) = opts.target_triple {
+        path.push(triple);
+    }
+    // The `dev` and `test` profiles use the `debug` directory and the `bench` profile
+    // uses the `release` directory.
+    match opts.profile.as_deref() {
+        Some("dev") | Some("test") => path.push("debug"),
+        Some("bench") => path.push("release"),
+        Some(profile) => path.push(profile),
+        None if opts.release => path.push("release"),
+        None => path.push("debug"),
+    }
+
+    let is_wasm = opts
+        .target_triple
+        .as_deref()
+        .is_some_and(|triple| triple.starts_with("wasm"));
+
+    if is_native_library(target) {
+        return find_library_artifact(&path, target, is_wasm);
+    }
+
+    match kind {
+        TargetKind::Bin => {}
+        TargetKind::Example => path.push("examples"),
+        TargetKind::Test | TargetKind::Bench => {
+            path.push("deps");
+            return find_hashed_executable(&path, &target.name);
+        }
     }
     path.push(&target.name);
 
@@ -222,5 +922,125 @@ fn find_binary_path(opts: &Opts) -> anyhow::Result<PathBuf> {
         path.push(format!("{}.exe", target.name));
     }
 
+    if is_wasm && !path.is_file() {
+        path.pop();
+        path.push(format!("{}.wasm", target.name));
+    }
+
     Ok(path)
 }
+
+/// Returns true for library targets that are built as a `cdylib` or `staticlib`, which
+/// contain the code of the library unlike rlibs.
+fn is_native_library(target: &cargo_metadata::Target) -> bool {
+    target
+        .kind
+        .iter()
+        .any(|k| k == "cdylib" || k == "staticlib")
+}
+
+/// Finds the dynamic or static library that was built for a library target, preferring
+/// dynamic libraries. Versioned dynamic libraries such as `libfoo.so.1` are also found
+/// if there is no unversioned one.
+fn find_library_artifact(
+    dir: &Path,
+    target: &cargo_metadata::Target,
+    is_wasm: bool,
+) -> anyhow::Result<PathBuf> {
+    let name = target.name.replace('-', "_");
+    let is_cdylib = target.kind.iter().any(|k| k == "cdylib");
+    let is_staticlib = target.kind.iter().any(|k| k == "staticlib");
+
+    let mut file_names = Vec::new();
+    if is_cdylib {
+        if is_wasm {
+            file_names.push(format!("{}.wasm", name));
+        } else if cfg!(target_os = "windows") {
+            file_names.push(format!("{}.dll", name));
+        } else if cfg!(target_os = "macos") {
+            file_names.push(format!("lib{}.dylib", name));
+        } else {
+            file_names.push(format!("lib{}.so", name));
+        }
+    }
+    if is_staticlib {
+        if cfg!(target_os = "windows") {
+            file_names.push(format!("{}.lib", name));
+        }
+        file_names.push(format!("lib{}.a", name));
+    }
+
+    for file_name in file_names.iter() {
+        let path = dir.join(file_name);
+        if path.is_file() {
+            return Ok(path);
+        }
+    }
+
+    if is_cdylib && !is_wasm {
+        let so_prefix = format!("lib{}.so.", name);
+        let dylib_prefix = format!("lib{}.", name);
+        let entries = std::fs::read_dir(dir)
+            .with_context(|| format!("failed to read directory `{}`", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            let is_versioned = path
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .is_some_and(|file_name| {
+                    file_name.starts_with(&so_prefix)
+                        || (file_name.starts_with(&dylib_prefix) && file_name.ends_with(".dylib"))
+                });
+            if is_versioned && path.is_file() {
+                return Ok(path);
+            }
+        }
+    }
+
+    Err(anyhow::anyhow!(
+        "no library for `{}` was found in `{}` (expected {})",
+        target.name,
+        dir.display(),
+        file_names.join(" or ")
+    ))
+}
+
+/// Finds the most recently built executable of a test or benchmark target in the `deps`
+/// directory. Their file names are the name of the target followed by a hash, e.g.
+/// `integration_tests-4f2e5c8b1a0d9e7f`.
+fn find_hashed_executable(deps_dir: &Path, target_name: &str) -> anyhow::Result<PathBuf> {
+    let prefix = format!("{}-", target_name.replace('-', "_"));
+    let entries = std::fs::read_dir(deps_dir)
+        .with_context(|| format!("failed to read directory `{}`", deps_dir.display()))?;
+
+    let mut newest: Option<(std::time::SystemTime, PathBuf)> = None;
+    for entry in entries {
+        let path = entry?.path();
+        let is_executable = matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            None | Some("exe") | Some("wasm")
+        );
+        let hash = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.strip_prefix(&prefix));
+        let is_match = hash
+            .is_some_and(|hash| hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit()));
+        if !is_executable || !is_match {
+            continue;
+        }
+
+        let modified = path.metadata()?.modified()?;
+        if newest.as_ref().is_none_or(|(time, _)| modified > *time) {
+            newest = Some((modified, path));
+        }
+    }
+
+    newest.map(|(_, path)| path).with_context(|| {
+        format!(
+            "no executable for `{}` was found in `{}`, build it with `cargo test --no-run` or `cargo bench --no-run` first",
+            target_name,
+            deps_dir.display()
+        )
+    })
+}
@@ -0,0 +1,203 @@
+//! Sample counts from `perf record` profiles, which are shown next to the disassembly.
+
+use crate::disasm::{demangle, symbol::Symbol, Disassembly};
+use anyhow::Context as _;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+/// The number of times that each instruction was sampled.
+pub struct Profile {
+    /// Samples by the address of the sampled instruction.
+    addresses: HashMap<u64, u64>,
+    /// Samples by the name of the sampled symbol and the offset of the instruction in it,
+    /// which also work for position independent binaries that were loaded at a different
+    /// address than the one in the file.
+    symbols: HashMap<String, HashMap<u64, u64>>,
+}
+
+impl Profile {
+    /// Reads a profile from a `perf.data` file, using `perf script` to decode it, or from
+    /// the output of `perf script`.
+    pub fn load(path: &Path) -> anyhow::Result<Profile> {
+        let data = std::fs::read(path)
+            .with_context(|| format!("failed to read profile {}", path.display()))?;
+
+        let script = if data.starts_with(b"PERFILE2") {
+            log::debug!("running `perf script` for {}", path.display());
+            let output = Command::new("perf")
+                .args(["script", "--hide-call-graph", "-F", "ip,sym,symoff", "-i"])
+                .arg(path)
+                .output()
+                .context("failed to run `perf script`, is perf installed?")?;
+            if !output.status.success() {
+                return Err(anyhow::anyhow!(
+                    "`perf script` failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            output.stdout
+        } else {
+            data
+        };
+
+        let profile = Profile::parse(&String::from_utf8_lossy(&script));
+        log::debug!(
+            "read {} samples from {}",
+            profile.addresses.values().sum::<u64>(),
+            path.display()
+        );
+        Ok(profile)
+    }
+
+    /// Parses the output of `perf script`. Only the first frame of each sample is counted
+    /// if the samples have call chains.
+    fn parse(script: &str) -> Profile {
+        let mut profile = Profile {
+            addresses: HashMap::new(),
+            symbols: HashMap::new(),
+        };
+
+        // Set when the line with the sample's event didn't have an address, which means
+        // that it's on the first line of the call chain.
+        let mut waiting_for_frame = false;
+        for line in script.lines() {
+            // Call chain frames are indented with tabs.
+            let is_frame = line.starts_with('\t');
+            if is_frame && !waiting_for_frame {
+                continue;
+            }
+            if line.trim().is_empty() {
+                waiting_for_frame = false;
+                continue;
+            }
+
+            match parse_frame(line) {
+                Some((address, symbol)) => {
+                    *profile.addresses.entry(address).or_default() += 1;
+                    if let Some((name, offset)) = symbol {
+                        *profile
+                            .symbols
+                            .entry(symbol_key(name))
+                            .or_default()
+                            .entry(offset)
+                            .or_default() += 1;
+                    }
+                    waiting_for_frame = false;
+                }
+                None => waiting_for_frame = !is_frame,
+            }
+        }
+        profile
+    }
+
+    /// Returns the number of samples for each line of the disassembly of a symbol.
+    pub fn samples_for(&self, symbol: &Symbol, disassembly: &Disassembly) -> Vec<u64> {
+        let lines = disassembly.lines();
+        match self.symbols.get(&symbol_key(symbol.mangled_name())) {
+            Some(offsets) => lines
+                .iter()
+                .map(|line| {
+                    let offset = line.address() - symbol.address();
+                    offsets.get(&offset).copied().unwrap_or(0)
+                })
+                .collect(),
+            None => lines
+                .iter()
+                .map(|line| self.addresses.get(&line.address()).copied().unwrap_or(0))
+                .collect(),
+        }
+    }
+}
+
+/// Parses the address and the symbol and offset of a frame, e.g. the end of
+/// `cargo-disasm 1234 567.89: 250000 cycles:u: 55d5c4a3b1c4 main+0x14 (/path/to/binary)`
+/// or a call chain frame like `\t55d5c4a3b1c4 main+0x14 (/path/to/binary)`.
+fn parse_frame(line: &str) -> Option<(u64, Option<(&str, u64)>)> {
+    // The address comes after the name of the event, if it isn't on the next line.
+    if line.trim_end().ends_with(':') {
+        return None;
+    }
+    let frame = match line.rfind(": ") {
+        Some(idx) => &line[(idx + 2)..],
+        None => line,
+    };
+    let frame = frame.trim();
+    let frame = match frame.rfind(" (") {
+        Some(idx) if frame.ends_with(')') => frame[..idx].trim_end(),
+        _ => frame,
+    };
+
+    let (address, symbol) = match frame.find(char::is_whitespace) {
+        Some(idx) => (&frame[..idx], frame[idx..].trim()),
+        None => (frame, ""),
+    };
+    let address = u64::from_str_radix(address, 16).ok()?;
+    let symbol = symbol.rfind("+0x").and_then(|idx| {
+        let offset = u64::from_str_radix(&symbol[(idx + 3)..], 16).ok()?;
+        Some((&symbol[..idx], offset))
+    });
+    Some((address, symbol))
+}
+
+/// The name that samples of a symbol are counted under. perf shows names mangled,
+/// demangled, or demangled with the hashes of Rust names, so they are demangled the same
+/// way as the names of symbols in binaries, without the hashes.
+fn symbol_key(name: &str) -> String {
+    let (name, _) = demangle::demangle(name.into());
+    demangle::strip_rust_hash(&name).to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn frames() {
+        assert_eq!(
+            parse_frame(
+                "cargo-disasm 1234 567.89: 250000 cycles:u: 55d5c4a3b1c4 main+0x14 (/path/to/binary)"
+            ),
+            Some((0x55d5c4a3b1c4, Some(("main", 0x14))))
+        );
+        assert_eq!(
+            parse_frame("\t55d5c4a3b1c4 main+0x14 (/path/to/binary)"),
+            Some((0x55d5c4a3b1c4, Some(("main", 0x14))))
+        );
+        assert_eq!(
+            parse_frame("\t55d5c4a3b1c4 [unknown] (/path/to/binary)"),
+            Some((0x55d5c4a3b1c4, None))
+        );
+        assert_eq!(
+            parse_frame("cargo-disasm 1234 567.89: 250000 cycles:u:"),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_samples() {
+        let profile = Profile::parse(
+            "cargo-disasm 1234 567.89: 250000 cycles:u: 55d5c4a3b1c4 main+0x14 (/path/to/binary)
+cargo-disasm 1234 567.90: 250000 cycles:u: 55d5c4a3b1c4 main+0x14 (/path/to/binary)
+cargo-disasm 1234 567.91: 250000 cycles:u:
+\t55d5c4a3b1c8 core::fmt::write::h0123456789abcdef+0x18 (/path/to/binary)
+\t55d5c4a3b1c4 main+0x14 (/path/to/binary)
+
+cargo-disasm 1234 567.92: 250000 cycles:u: 55d5c4a3b1b0 _ZN4core3fmt5write17h0123456789abcdefE+0x0 (/path/to/binary)
+",
+        );
+        assert_eq!(profile.addresses.len(), 3);
+        assert_eq!(profile.addresses[&0x55d5c4a3b1c4], 2);
+        assert_eq!(profile.addresses[&0x55d5c4a3b1c8], 1);
+        assert_eq!(profile.addresses[&0x55d5c4a3b1b0], 1);
+
+        assert_eq!(
+            profile.symbols["main"],
+            [(0x14, 2)].iter().copied().collect()
+        );
+        assert_eq!(
+            profile.symbols["core::fmt::write"],
+            [(0x18, 1), (0, 1)].iter().copied().collect()
+        );
+    }
+}
@@ -1,14 +1,30 @@
+use super::arrows::{ArrowStyle, JumpArrows};
+use super::theme::{classify_operands, Theme};
+use crate::disasm::cfg::ControlFlowGraph;
+use crate::disasm::cost::Cost;
+use crate::disasm::frame::FrameLayout;
+use crate::disasm::panics::{Check, CheckKind, PanicPath};
+use crate::disasm::stats::InstructionStats;
 use crate::disasm::strmatch::Tokenizer;
-use crate::disasm::{self, symbol::Symbol, Disassembly};
-use termcolor::{Color, ColorSpec, WriteColor};
+use crate::disasm::unwind::{CallSite, UnwindDirective};
+use crate::disasm::{self, symbol::Symbol, Disassembly, Jump};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use termcolor::{ColorSpec, WriteColor};
 
 const MAX_OPERAND_LEN: usize = 72;
 
+/// The width of the latency and throughput column, e.g. ` 26  6.00`.
+const COST_WIDTH: usize = 9;
+
+/// The width of the column with the share of samples, e.g. ` 12.34%`.
+const HEAT_WIDTH: usize = 7;
+
 pub fn print_disassembly(
     out: &mut dyn WriteColor,
     sym: &Symbol,
     dis: &Disassembly,
-    opt: DisasmOptions,
+    opt: DisasmOptions<'_>,
 ) -> anyhow::Result<()> {
     let measure = disasm::display::measure(dis);
 
@@ -19,10 +35,28 @@ pub fn print_disassembly(
     let max_mnem = measure.max_mnemonic_len(); // mnemonic length
     let mut max_oprn = measure.max_operands_len(); // operand length
     let max_comm = measure.max_comments_len(); // comment length
-    let max_bytes = measure.max_bytes_width_hex(1); // bytes length
+    let max_bytes = measure.max_bytes_width_grouped(opt.bytes_format.group_size); // bytes length
 
-    let addr_indent = space_sm;
-    let bytes_indent = addr_indent + max_addr + space_lg;
+    let heat_indent = space_sm;
+    let coverage_indent = heat_indent
+        + if opt.samples.is_some() {
+            Spacing(HEAT_WIDTH) + space_sm
+        } else {
+            Spacing(0)
+        };
+    let addr_indent = coverage_indent
+        + if opt.executed.is_some() {
+            Spacing(1) + space_sm
+        } else {
+            Spacing(0)
+        };
+    let cost_indent = addr_indent + max_addr + space_lg;
+    let bytes_indent = cost_indent
+        + if opt.costs.is_some() {
+            Spacing(COST_WIDTH) + space_lg
+        } else {
+            Spacing(0)
+        };
     let mnem_indent = bytes_indent
         + if opt.show_bytes {
             space_sm + max_bytes // spacing comes after
@@ -36,81 +70,397 @@ pub fn print_disassembly(
         max_oprn = MAX_OPERAND_LEN;
     }
 
+    let theme = opt.theme;
     let clr_norm = ColorSpec::new(); // normal color
+    let clr_addr = &theme.address;
+    let clr_bytes = &theme.bytes;
+    let clr_source = &theme.source;
+    let clr_location = &theme.location;
+    let clr_mnem = &theme.mnemonic;
+    let mut clr_oprn_sym = theme.jump_target.clone(); // operands color (for jumps to symbols)
+    let clr_comm = &theme.comment;
+
+    out.set_color(&theme.symbol)?;
+    if opt.show_mangled && sym.mangled_name() != sym.name() {
+        write!(out, "{} ", sym.name())?;
+        out.set_color(clr_comm)?;
+        write!(out, "[{}]", sym.mangled_name())?;
+        out.set_color(&theme.symbol)?;
+        writeln!(out, ":")?;
+    } else {
+        writeln!(out, "{}:", sym.name())?;
+    }
+    if opt.show_header {
+        out.set_color(clr_comm)?;
+        write!(
+            out,
+            "{}; 0x{:x}..0x{:x} ({} bytes, {}",
+            space_sm,
+            sym.address(),
+            sym.end_address(),
+            sym.size(),
+            sym.source()
+        )?;
+        if let Some(member) = opt.archive_member {
+            write!(out, ", in {}", member)?;
+        }
+        writeln!(out, ")")?;
+    }
+    if !opt.aliases.is_empty() {
+        out.set_color(clr_comm)?;
+        write!(out, "{}; also known as: ", space_sm)?;
+        for (idx, alias) in opt.aliases.iter().enumerate() {
+            if idx > 0 {
+                write!(out, ", ")?;
+            }
+            write!(out, "{}", alias.name())?;
+        }
+        writeln!(out)?;
+    }
+    if let Some(frame) = opt.frame {
+        out.set_color(clr_comm)?;
+        writeln!(out, "{}; {}", space_sm, frame)?;
+    }
+    if let Some(checks) = opt.checks {
+        out.set_color(clr_comm)?;
+        if checks.is_empty() {
+            writeln!(out, "{}; no bounds or overflow checks", space_sm)?;
+        }
+        for &kind in &[CheckKind::Bounds, CheckKind::Overflow] {
+            let addresses = checks
+                .iter()
+                .filter(|check| check.kind == kind)
+                .map(|check| dis.lines()[check.line].address())
+                .collect::<Vec<_>>();
+            if !addresses.is_empty() {
+                writeln!(out, "{}; {}", space_sm, CheckList(kind, &addresses))?;
+            }
+        }
+    }
+    let total_samples = opt.samples.map_or(0, |samples| samples.iter().sum::<u64>());
+    if opt.samples.is_some() {
+        out.set_color(clr_comm)?;
+        writeln!(out, "{}; {} samples", space_sm, total_samples)?;
+    }
+    out.set_color(&clr_norm)?;
 
-    let mut clr_addr = ColorSpec::new(); // address color
-    clr_addr.set_fg(Some(Color::Blue));
-
-    let mut clr_bytes = ColorSpec::new();
-    clr_bytes.set_fg(Some(Color::Yellow));
-
-    let mut clr_source = ColorSpec::new(); // mnemonic color
-    clr_source.set_fg(Some(Color::Magenta));
-    clr_source.set_bold(true);
-
-    let mut clr_mnem = ColorSpec::new(); // mnemonic color
-    clr_mnem.set_fg(Some(Color::Green));
-    clr_mnem.set_bold(true);
+    let arrows = opt.arrows.map(|style| JumpArrows::new(dis, style));
+    let gutter = |out: &mut dyn WriteColor, idx: usize, is_line: bool| match arrows {
+        Some(ref arrows) if is_line => arrows.write_line(out, idx),
+        Some(ref arrows) => arrows.write_before(out, idx),
+        None => Ok(()),
+    };
+
+    // The total cost of each basic block, by the index of its last line.
+    let mut block_costs = vec![None; dis.lines().len()];
+    if let Some(costs) = opt.costs {
+        for block in ControlFlowGraph::new(dis).blocks() {
+            let lines = block.lines();
+            if lines.is_empty() {
+                continue;
+            }
+            let last = lines.end - 1;
+            block_costs[last] = Some(BlockCost::new(&costs[lines]));
+        }
+    }
 
-    let clr_oprn = ColorSpec::new(); // operands color
-    let mut clr_oprn_sym = clr_oprn.clone(); // operands color (for jumps to symbols)
-    clr_oprn_sym.set_fg(Some(Color::Cyan));
+    let labels = if opt.labels {
+        local_labels(dis)
+    } else {
+        HashMap::new()
+    };
+
+    // The basic blocks are separated by an empty line when arrows are drawn, so that the
+    // jumps can be followed from block to block.
+    let block_starts = if opt.arrows.is_some() {
+        ControlFlowGraph::new(dis)
+            .blocks()
+            .iter()
+            .map(|block| block.lines().start)
+            .filter(|&start| start > 0)
+            .collect::<HashSet<_>>()
+    } else {
+        HashSet::new()
+    };
+
+    let mut last_location = None;
+    let mut unwind_idx = 0;
+    for (idx, line) in dis.lines().iter().enumerate() {
+        // Paths that only lead to a panic are folded into a single line where they start,
+        // which keeps the labels of the jump targets in them.
+        if let Some(path) = opt
+            .panic_paths
+            .iter()
+            .find(|path| path.lines.contains(&idx))
+        {
+            if idx == path.lines.start {
+                for label_idx in path.lines.clone() {
+                    if let Some(&label) = labels.get(&label_idx) {
+                        gutter(out, idx, false)?;
+                        out.set_color(&theme.jump_target)?;
+                        writeln!(out, "{}{}:", space_sm, LocalLabel(label))?;
+                    }
+                }
+                gutter(out, idx, true)?;
+                out.set_color(clr_addr)?;
+                write!(out, "{}{:<2$x}", addr_indent, line.address(), max_addr)?;
+                out.set_color(clr_comm)?;
+                writeln!(
+                    out,
+                    "{}; {} instructions that panic in {}",
+                    space_lg,
+                    path.lines.len(),
+                    path.panic
+                )?;
+            }
+            continue;
+        }
 
-    let mut clr_comm = ColorSpec::new(); // comment color
-    clr_comm.set_italic(true);
-    clr_comm.set_fg(Some(Color::Yellow));
+        if block_starts.contains(&idx) {
+            gutter(out, idx, false)?;
+            writeln!(out)?;
+        }
 
-    out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)).set_bold(true))?;
-    writeln!(out, "{}:", sym.name())?;
-    out.set_color(&clr_norm)?;
+        if opt.show_inlined {
+            for call in line.inlined_calls() {
+                gutter(out, idx, false)?;
+                out.set_color(clr_comm)?;
+                writeln!(out, "{}; {}", source_indent, call)?;
+            }
+        }
 
-    for line in dis.lines() {
         if opt.show_source {
+            for location in line.source_locations() {
+                if last_location == Some(location) {
+                    continue;
+                }
+                last_location = Some(location);
+                gutter(out, idx, false)?;
+                out.set_color(clr_location)?;
+                writeln!(out, "{}{}", source_indent, location)?;
+            }
+
             for source_line in line.source_lines() {
-                out.set_color(&clr_source)?;
+                gutter(out, idx, false)?;
+                out.set_color(clr_source)?;
                 writeln!(out, "{}{}", source_indent, source_line)?;
             }
         }
 
+        while let Some(directive) = opt
+            .unwind
+            .get(unwind_idx)
+            .filter(|directive| directive.address <= line.address())
+        {
+            unwind_idx += 1;
+            gutter(out, idx, false)?;
+            out.set_color(clr_comm)?;
+            writeln!(out, "{}; {}", source_indent, directive.text)?;
+        }
+
+        for table in dis.jump_tables() {
+            for (cases, _) in table
+                .cases()
+                .into_iter()
+                .filter(|&(_, target)| target == line.address())
+            {
+                gutter(out, idx, false)?;
+                out.set_color(clr_comm)?;
+                writeln!(
+                    out,
+                    "{}; {} of the jump at 0x{:x}",
+                    source_indent,
+                    CaseList(&cases),
+                    dis.lines()[table.jump_line()].address()
+                )?;
+            }
+        }
+
+        for call_site in opt.call_sites {
+            if call_site.range.end == line.address() {
+                gutter(out, idx, false)?;
+                out.set_color(clr_comm)?;
+                writeln!(out, "{}; end of try range", source_indent)?;
+            }
+        }
+        for call_site in opt.call_sites {
+            if call_site.range.start == line.address() {
+                gutter(out, idx, false)?;
+                out.set_color(clr_comm)?;
+                writeln!(
+                    out,
+                    "{}; try range 0x{:x} - 0x{:x}, unwinds to 0x{:x}",
+                    source_indent,
+                    call_site.range.start,
+                    call_site.range.end,
+                    call_site.landing_pad
+                )?;
+            }
+        }
+        if opt
+            .call_sites
+            .iter()
+            .any(|call_site| call_site.landing_pad == line.address())
+        {
+            gutter(out, idx, false)?;
+            out.set_color(clr_comm)?;
+            write!(out, "{}; landing pad for calls at ", source_indent)?;
+            let mut cleanup = true;
+            let call_sites = opt
+                .call_sites
+                .iter()
+                .filter(|call_site| call_site.landing_pad == line.address());
+            for (call_idx, call_site) in call_sites.enumerate() {
+                if call_idx > 0 {
+                    write!(out, ", ")?;
+                }
+                write!(
+                    out,
+                    "0x{:x} - 0x{:x}",
+                    call_site.range.start, call_site.range.end
+                )?;
+                cleanup &= call_site.is_cleanup();
+            }
+            writeln!(out, " ({})", if cleanup { "cleanup" } else { "catch" })?;
+        }
+
+        if let Some(&label) = labels.get(&idx) {
+            gutter(out, idx, false)?;
+            out.set_color(&theme.jump_target)?;
+            writeln!(out, "{}{}:", space_sm, LocalLabel(label))?;
+        }
+
+        gutter(out, idx, true)?;
         out.set_color(&clr_norm)?;
-        write!(out, "{}", space_sm)?;
+        if opt.marker == Some(line.address()) {
+            write!(out, "=>")?;
+        } else {
+            write!(out, "{}", space_sm)?;
+        }
+
+        if let Some(samples) = opt.samples {
+            let share = if total_samples > 0 {
+                samples[idx] as f64 / total_samples as f64
+            } else {
+                0.0
+            };
+            if samples[idx] > 0 {
+                out.set_color(match share {
+                    share if share >= 0.1 => &theme.heat[2],
+                    share if share >= 0.02 => &theme.heat[1],
+                    _ => &theme.heat[0],
+                })?;
+                write!(out, "{:>6.2}%", share * 100.0)?;
+            } else {
+                write!(out, "{}", Spacing(HEAT_WIDTH))?;
+            }
+
+            out.set_color(&clr_norm)?;
+            write!(out, "{}", space_sm)?;
+        }
 
-        out.set_color(&clr_addr)?;
+        // `+` marks the blocks that were executed and `-` the ones that weren't.
+        if let Some(executed) = opt.executed {
+            match executed[idx] {
+                Some(true) => {
+                    out.set_color(clr_comm)?;
+                    write!(out, "+")?;
+                }
+                Some(false) => {
+                    out.set_color(&theme.uncovered)?;
+                    write!(out, "-")?;
+                }
+                None => write!(out, " ")?,
+            }
+
+            out.set_color(&clr_norm)?;
+            write!(out, "{}", space_sm)?;
+        }
+
+        out.set_color(clr_addr)?;
         write!(out, "{:<1$x}", line.address(), max_addr)?;
 
         out.set_color(&clr_norm)?;
         write!(out, "{}", space_lg)?;
 
+        if let Some(costs) = opt.costs {
+            out.set_color(clr_comm)?;
+            match costs[idx] {
+                Some(cost) => write!(out, "{:>3} {:>5.2}", cost.latency, cost.throughput)?,
+                None => write!(out, "{}", Spacing(COST_WIDTH))?,
+            }
+
+            out.set_color(&clr_norm)?;
+            write!(out, "{}", space_lg)?;
+        }
+
         if opt.show_bytes {
-            out.set_color(&clr_bytes)?;
-            write!(out, "{:>1$}", Hex(line.bytes()), max_bytes)?;
+            out.set_color(clr_bytes)?;
+            write!(
+                out,
+                "{:>1$}",
+                GroupedHex(line.bytes(), opt.bytes_format),
+                max_bytes
+            )?;
 
             out.set_color(&clr_norm)?;
             write!(out, "{}", space_sm)?;
         }
 
-        out.set_color(&clr_mnem)?;
+        let is_check = opt
+            .checks
+            .is_some_and(|checks| checks.iter().any(|check| check.line == idx));
+        out.set_color(if is_check { &theme.check } else { clr_mnem })?;
         write!(out, "{:<1$}", line.mnemonic(), max_mnem)?;
 
         out.set_color(&clr_norm)?;
         write!(out, "{}", space_sm)?;
 
-        let oprn_color = if line.is_symbolicated_jump() {
+        // Internal jumps use the label of their target instead of its address, which makes
+        // the comment with the target redundant.
+        let mut line_operands = Cow::Borrowed(line.operands());
+        let mut comments = line.comments();
+        if let Jump::Internal(target) = line.jump() {
+            if let Some(&label) = labels.get(&target) {
+                let address = format!("0x{:x}", dis.lines()[target].address());
+                if let Some(start) = line.operands().find(&address) {
+                    let end = start + address.len();
+                    let start = start - line.operands()[..start].ends_with('#') as usize;
+                    line_operands = Cow::Owned(format!(
+                        "{}{}{}",
+                        &line.operands()[..start],
+                        LocalLabel(label),
+                        &line.operands()[end..]
+                    ));
+                    comments = "";
+                }
+            }
+        }
+
+        // Jumps to symbols have a single color, the other operands are highlighted.
+        let operand_kinds = if line.is_symbolicated_jump() {
             clr_oprn_sym
                 .set_italic(line.jump().is_external())
                 .set_bold(line.jump().is_internal());
-            &clr_oprn_sym
+            None
         } else {
-            &clr_oprn
+            Some(classify_operands(&line_operands))
+        };
+        let oprn_color = |token: &str| match operand_kinds {
+            Some(ref kinds) => theme.operand_color(
+                kinds,
+                token.as_ptr() as usize - line_operands.as_ptr() as usize,
+            ),
+            None => &clr_oprn_sym,
         };
-        out.set_color(oprn_color)?;
 
-        let mut operands = WordWrapped::new(line.operands(), max_oprn);
+        let mut operands = WordWrapped::new(&line_operands, max_oprn);
         let mut has_more_operands = false;
         let mut operand_chars_printed = 0;
         while let Some(operand) = operands.next() {
             if let WrappedStr::Str(token) = operand {
                 operand_chars_printed += token.len();
+                out.set_color(oprn_color(token))?;
                 write!(out, "{}", token)?;
             } else {
                 has_more_operands = true;
@@ -119,39 +469,34 @@ pub fn print_disassembly(
         }
 
         // Write the comment after the first line of the operands:
-        if !line.comments().is_empty() {
+        if !comments.is_empty() {
             out.set_color(&clr_norm)?;
             write!(
                 out,
                 "{}",
-                Spacing(space_lg.0 + (max_oprn - operand_chars_printed))
+                Spacing(space_lg.0 + max_oprn.saturating_sub(operand_chars_printed))
             )?;
-            out.set_color(&clr_comm)?;
-            write!(out, "; {:<1$}", line.comments(), max_comm)?;
+            out.set_color(clr_comm)?;
+            write!(out, "; {:<1$}", comments, max_comm)?;
         }
 
         // Write the remaining lines of the operands if there are any:
         if has_more_operands {
             out.set_color(&clr_norm)?;
             writeln!(out)?;
+            gutter(out, idx + 1, false)?;
             write!(out, "{}", oprn_indent)?;
-            let mut in_oprn_color = false;
             for w in operands {
                 match w {
                     WrappedStr::Str(s) => {
-                        if !in_oprn_color {
-                            out.set_color(oprn_color)?;
-                            in_oprn_color = true;
-                        }
+                        out.set_color(oprn_color(s))?;
                         write!(out, "{}", s)?;
                     }
 
                     WrappedStr::Break => {
-                        if in_oprn_color {
-                            out.set_color(&clr_norm)?;
-                            in_oprn_color = false;
-                        }
+                        out.set_color(&clr_norm)?;
                         writeln!(out)?;
+                        gutter(out, idx + 1, false)?;
                         write!(out, "{}", oprn_indent)?;
                     }
                 }
@@ -160,12 +505,193 @@ pub fn print_disassembly(
             out.set_color(&clr_norm)?;
         }
         writeln!(out)?;
+
+        for table in dis.jump_tables() {
+            if table.jump_line() != idx {
+                continue;
+            }
+            gutter(out, idx + 1, false)?;
+            out.set_color(clr_comm)?;
+            writeln!(
+                out,
+                "{}; jump table at 0x{:x} with {} entries:",
+                mnem_indent,
+                table.address(),
+                table.targets().len()
+            )?;
+            for (cases, target) in table.cases() {
+                gutter(out, idx + 1, false)?;
+                let label = dis
+                    .lines()
+                    .iter()
+                    .position(|line| line.address() == target)
+                    .and_then(|target_idx| labels.get(&target_idx));
+                match label {
+                    Some(&label) => writeln!(
+                        out,
+                        "{};   {} => {}",
+                        mnem_indent,
+                        CaseList(&cases),
+                        LocalLabel(label)
+                    )?,
+                    None => writeln!(
+                        out,
+                        "{};   {} => {}+0x{:x}",
+                        mnem_indent,
+                        CaseList(&cases),
+                        sym.name(),
+                        target - sym.address()
+                    )?,
+                }
+            }
+            out.set_color(&clr_norm)?;
+        }
+
+        if let Some(ref block_cost) = block_costs[idx] {
+            gutter(out, idx + 1, false)?;
+            out.set_color(clr_comm)?;
+            write!(
+                out,
+                "{}; block: {} instructions, {} cycles of latency, {:.2} cycles of throughput",
+                cost_indent, block_cost.instructions, block_cost.latency, block_cost.throughput
+            )?;
+            if block_cost.unknown > 0 {
+                write!(out, " ({} without estimates)", block_cost.unknown)?;
+            }
+            out.set_color(&clr_norm)?;
+            writeln!(out)?;
+        }
+    }
+
+    // Directives at the end of the symbol, e.g. `.cfi_endproc`.
+    for directive in &opt.unwind[unwind_idx..] {
+        out.set_color(clr_comm)?;
+        writeln!(out, "{}; {}", source_indent, directive.text)?;
+    }
+    if let Some(stats) = opt.stats {
+        out.set_color(clr_comm)?;
+        writeln!(out, "{}; {}", space_sm, stats)?;
     }
+    out.set_color(&clr_norm)?;
 
     Ok(())
 }
 
-pub struct Hex<'b>(&'b [u8]);
+/// Numbers the lines that are the targets of jumps inside of the function or of jump
+/// tables in address order, by their indices.
+pub(super) fn local_labels(dis: &Disassembly) -> HashMap<usize, usize> {
+    let lines = dis.lines();
+    let mut targets = lines
+        .iter()
+        .filter_map(|line| match line.jump() {
+            Jump::Internal(target) => Some(target),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    for table in dis.jump_tables() {
+        targets.extend(
+            table
+                .targets()
+                .iter()
+                .filter_map(|&target| lines.iter().position(|line| line.address() == target)),
+        );
+    }
+    targets.sort_unstable();
+    targets.dedup();
+    targets
+        .into_iter()
+        .enumerate()
+        .map(|(label, target)| (target, label))
+        .collect()
+}
+
+/// Displays a local label in the style of LLVM, e.g. `.LBB0_3`.
+struct LocalLabel(usize);
+
+impl std::fmt::Display for LocalLabel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, ".LBB0_{}", self.0)
+    }
+}
+
+/// Displays the checks of one kind in a function, e.g. `3 bounds checks at 0x10, 0x24,
+/// 0x3c`.
+struct CheckList<'a>(CheckKind, &'a [u64]);
+
+impl std::fmt::Display for CheckList<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = if self.1.len() == 1 { "" } else { "s" };
+        write!(f, "{} {}{} at ", self.1.len(), self.0, plural)?;
+        for (idx, address) in self.1.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "0x{:x}", address)?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays the cases of a jump table that go to the same target, e.g. `case 0, 2..=4`.
+struct CaseList<'a>(&'a [usize]);
+
+impl std::fmt::Display for CaseList<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "case ")?;
+        let mut idx = 0;
+        while idx < self.0.len() {
+            let start = self.0[idx];
+            let mut end = start;
+            while idx + 1 < self.0.len() && self.0[idx + 1] == end + 1 {
+                idx += 1;
+                end += 1;
+            }
+            if start != self.0[0] {
+                write!(f, ", ")?;
+            }
+            if end > start {
+                write!(f, "{}..={}", start, end)?;
+            } else {
+                write!(f, "{}", start)?;
+            }
+            idx += 1;
+        }
+        Ok(())
+    }
+}
+
+/// The total estimated cost of the instructions in a basic block. The latency is the sum
+/// of the latencies, which assumes that every instruction depends on the one before it.
+#[derive(Clone)]
+struct BlockCost {
+    instructions: usize,
+    unknown: usize,
+    latency: u32,
+    throughput: f32,
+}
+
+impl BlockCost {
+    fn new(costs: &[Option<Cost>]) -> BlockCost {
+        let mut block_cost = BlockCost {
+            instructions: costs.len(),
+            unknown: 0,
+            latency: 0,
+            throughput: 0.0,
+        };
+        for cost in costs {
+            match cost {
+                Some(cost) => {
+                    block_cost.latency += cost.latency;
+                    block_cost.throughput += cost.throughput;
+                }
+                None => block_cost.unknown += 1,
+            }
+        }
+        block_cost
+    }
+}
+
+pub struct Hex<'b>(pub &'b [u8]);
 
 impl std::fmt::Display for Hex<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -203,6 +729,28 @@ impl std::fmt::Display for Hex<'_> {
     }
 }
 
+/// Hexadecimal bytes printed in groups that are separated by spaces.
+pub struct GroupedHex<'b>(pub &'b [u8], pub BytesFormat);
+
+impl std::fmt::Display for GroupedHex<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut buffer = String::with_capacity(self.0.len() * 3);
+        for group in self.0.chunks(self.1.group_size.max(1)) {
+            if !buffer.is_empty() {
+                buffer.push(' ');
+            }
+
+            let mut push = |byte: &u8| buffer.push_str(&format!("{:02x}", byte));
+            if self.1.reverse {
+                group.iter().rev().for_each(&mut push);
+            } else {
+                group.iter().for_each(&mut push);
+            }
+        }
+        f.pad(&buffer)
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct Spacing(usize);
 
@@ -332,8 +880,103 @@ pub enum WrappedStr<'s> {
     Break,
 }
 
+/// The order that the bytes of each group are printed in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum ByteOrder {
+    /// The order that the bytes are stored in.
+    #[default]
+    Memory,
+    /// Each group is printed as a number using the byte order of the binary, like
+    /// `objdump` does for architectures with fixed size instructions.
+    Value,
+}
+
+impl std::str::FromStr for ByteOrder {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("memory") {
+            Ok(ByteOrder::Memory)
+        } else if s.eq_ignore_ascii_case("value") {
+            Ok(ByteOrder::Value)
+        } else {
+            Err("invalid byte order")
+        }
+    }
+}
+
+/// How the bytes of each instruction are printed.
+#[derive(Copy, Clone)]
+pub struct BytesFormat {
+    /// The number of bytes that are printed without spaces between them.
+    pub group_size: usize,
+    /// Print the bytes of each group in reverse.
+    pub reverse: bool,
+}
+
 #[derive(Copy, Clone)]
-pub struct DisasmOptions {
+pub struct DisasmOptions<'a> {
     pub show_bytes: bool,
+    pub bytes_format: BytesFormat,
     pub show_source: bool,
+    pub show_inlined: bool,
+
+    /// Show the address range, size, and source of the symbol below its name.
+    pub show_header: bool,
+
+    /// Show the mangled name of the symbol next to its name.
+    pub show_mangled: bool,
+
+    /// Label the targets of jumps inside of the function (e.g. `.LBB0_3:`) and use the
+    /// labels as the operands of the jumps instead of addresses.
+    pub labels: bool,
+
+    /// Draw arrows for jumps inside of the function in the left gutter.
+    pub arrows: Option<ArrowStyle>,
+
+    pub theme: &'a Theme,
+
+    /// The archive member that the symbol is from, which is shown in the header.
+    pub archive_member: Option<&'a str>,
+
+    /// The address of a line that is marked with `=>`, e.g. the address of a crash.
+    pub marker: Option<u64>,
+
+    /// The other symbols with the same code, which are listed below the name.
+    pub aliases: &'a [&'a Symbol],
+
+    /// The size of the stack frame and the saved registers, which are shown below the
+    /// name.
+    pub frame: Option<&'a FrameLayout>,
+
+    /// The unwind directives of the symbol sorted by address, which are shown above the
+    /// instructions that they apply to.
+    pub unwind: &'a [UnwindDirective],
+
+    /// The calls that unwind to landing pads, which are marked where their ranges begin
+    /// and end and at their landing pads.
+    pub call_sites: &'a [CallSite],
+
+    /// The estimated cost of each line, which is shown in a column after the address
+    /// along with the total for each basic block.
+    pub costs: Option<&'a [Option<Cost>]>,
+
+    /// The number of profiling samples for each line, which are shown as a share of the
+    /// samples of the symbol in the first column.
+    pub samples: Option<&'a [u64]>,
+
+    /// Whether the basic block of each line was executed according to coverage data, if
+    /// that is known.
+    pub executed: Option<&'a [Option<bool>]>,
+
+    /// The paths that only lead to a panic, which are folded into a single line each.
+    pub panic_paths: &'a [PanicPath],
+
+    /// The counts of the instructions by their class, which are shown after the
+    /// instructions.
+    pub stats: Option<&'a InstructionStats>,
+
+    /// The bounds and overflow checks, which are listed below the name and highlighted,
+    /// if they should be shown.
+    pub checks: Option<&'a [Check]>,
 }
@@ -0,0 +1,220 @@
+use super::cli::{Opts, OutputFormat, SectionOpts};
+use super::{addr, hexdump, json, printer};
+use crate::disasm::{
+    self,
+    binary::{Binary, BinaryData, SearchOptions, Section},
+    section::split_section,
+};
+use anyhow::Context as _;
+use std::io::Write as _;
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+/// Lists the sections of the binary.
+pub fn run_list(opts: &Opts, color_choice: ColorChoice) -> anyhow::Result<()> {
+    let bin = open_binary(opts)?;
+    let result = match opts.format {
+        OutputFormat::Text => print_sections(
+            &mut StandardStream::stdout(color_choice),
+            bin.sections(),
+            "",
+        ),
+        OutputFormat::Json => json::print_sections(&mut std::io::stdout().lock(), bin.sections()),
+        _ => {
+            return Err(anyhow::anyhow!(
+                "sections can only be printed as text or JSON"
+            ))
+        }
+    };
+    result.context("error occured while printing sections")
+}
+
+/// Disassembles every symbol in a section and the code in between them that isn't in any
+/// symbol.
+pub fn run(
+    opts: &Opts,
+    section_opts: &SectionOpts,
+    color_choice: ColorChoice,
+) -> anyhow::Result<()> {
+    let bin = open_binary(opts)?;
+    let section = find_section(&bin, &section_opts.name)?;
+    if section.offset().is_none() {
+        return Err(anyhow::anyhow!(
+            "`{}` takes up no space in the file and has nothing to disassemble",
+            section.name()
+        ));
+    }
+    if !section.is_code() {
+        log::warn!("`{}` is not a section with code", section.name());
+    }
+
+    let parts = split_section(&bin, section);
+    let options = addr::context_options(opts)?;
+    let mut disassemblies = Vec::with_capacity(parts.len());
+    for part in parts.iter() {
+        let symbol = part.symbol();
+        if !symbol.is_function() {
+            disassemblies.push((symbol, None));
+            continue;
+        }
+        match disasm::disasm(&bin, symbol, options) {
+            Ok(disassembly) => disassemblies.push((symbol, Some(disassembly))),
+            Err(err) => log::warn!("failed to disassemble `{}`: {:?}", symbol.name(), err),
+        }
+    }
+
+    match opts.format {
+        OutputFormat::Text => {
+            let theme = opts.theme()?;
+            let mut stdout = StandardStream::stdout(color_choice);
+            for (idx, &(symbol, ref disassembly)) in disassemblies.iter().enumerate() {
+                if idx > 0 {
+                    writeln!(stdout)?;
+                }
+                let disassembly = match disassembly {
+                    Some(disassembly) => disassembly,
+                    None => {
+                        hexdump::print_hex_dump(&mut stdout, &bin, symbol)
+                            .context("error occured while printing hex dump")?;
+                        continue;
+                    }
+                };
+                printer::print_disassembly(
+                    &mut stdout,
+                    symbol,
+                    disassembly,
+                    printer::DisasmOptions {
+                        show_source: false,
+                        show_inlined: false,
+                        show_bytes: opts.show_bytes(),
+                        bytes_format: opts.bytes_format(bin.endian())?,
+                        show_header: true,
+                        show_mangled: opts.show_mangled,
+                        labels: !opts.no_labels,
+                        arrows: opts.arrow_style(),
+                        theme: &theme,
+                        archive_member: bin.archive_member_at(symbol.offset()),
+                        marker: None,
+                        aliases: &[],
+                        frame: None,
+                        unwind: &[],
+                        call_sites: &[],
+                        costs: None,
+                        samples: None,
+                        executed: None,
+                        panic_paths: &[],
+                        stats: None,
+                        checks: None,
+                    },
+                )
+                .context("error occured while printing disassembly")?;
+            }
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let disassemblies = disassemblies
+                .into_iter()
+                .filter_map(|(symbol, disassembly)| Some((symbol, disassembly?)))
+                .collect::<Vec<_>>();
+            json::print_disassemblies(&mut std::io::stdout().lock(), &disassemblies)
+                .context("error occured while writing JSON disassembly")
+        }
+        _ => Err(anyhow::anyhow!(
+            "sections can only be disassembled as text or JSON"
+        )),
+    }
+}
+
+fn open_binary(opts: &Opts) -> anyhow::Result<Binary> {
+    let binary_path = super::find_binary_path(opts)?;
+    log::debug!("using binary {}", binary_path.display());
+    let data = BinaryData::from_path(&binary_path)
+        .with_context(|| format!("failed to load binary `{}`", binary_path.display()))?;
+    let sources = super::parse_symbol_sources(opts)?;
+    Binary::new(
+        data,
+        SearchOptions {
+            sources: &sources,
+            dwarf_path: opts.debug_file_path.as_deref(),
+            dsym_path: opts.dsym_path.as_deref(),
+            pdb_path: None,
+            archive_member: opts.archive_member.as_deref(),
+            cache_symbols: !opts.no_cache,
+            lazy_dwarf: false,
+            include_locals: opts.include_locals,
+            exclude_compiler_generated: opts.exclude_compiler_generated,
+            mangled_names: opts.no_demangle,
+            rust_hashes: opts.keep_hashes,
+        },
+    )
+}
+
+/// Finds a section by its name. Mach-O sections also match without their segment, e.g.
+/// `__text` for `__TEXT,__text`.
+fn find_section<'b>(bin: &'b Binary, name: &str) -> anyhow::Result<&'b Section> {
+    let sections = bin.sections();
+    sections
+        .iter()
+        .find(|section| section.name() == name)
+        .or_else(|| {
+            sections
+                .iter()
+                .find(|section| section.name().rsplit(',').next() == Some(name))
+        })
+        .ok_or_else(|| {
+            let mut message = format!("the binary has no section named `{}`", name);
+            let code_sections = sections
+                .iter()
+                .filter(|section| section.is_code())
+                .collect::<Vec<_>>();
+            if !code_sections.is_empty() {
+                message.push_str("\n\nthe sections with code are:");
+                for section in code_sections {
+                    message.push_str("\n    ");
+                    message.push_str(section.name());
+                }
+            }
+            anyhow::anyhow!(message)
+        })
+}
+
+/// Prints a table of sections with their addresses and sizes, and whether they have code
+/// or take up no space in the file. Each row starts with `indent`.
+pub fn print_sections(
+    out: &mut dyn WriteColor,
+    sections: &[Section],
+    indent: &str,
+) -> anyhow::Result<()> {
+    let mut clr_name = ColorSpec::new();
+    clr_name.set_fg(Some(Color::Cyan));
+    let mut clr_addr = ColorSpec::new();
+    clr_addr.set_fg(Some(Color::Blue));
+
+    let name_width = sections
+        .iter()
+        .map(|section| section.name().len())
+        .max()
+        .unwrap_or(0);
+    let addr_width = sections
+        .iter()
+        .map(|section| format!("{:x}", section.address()).len())
+        .max()
+        .unwrap_or(1);
+    for section in sections {
+        write!(out, "{}", indent)?;
+        out.set_color(&clr_name)?;
+        write!(out, "{:<1$}", section.name(), name_width)?;
+        out.reset()?;
+        write!(out, "  ")?;
+        out.set_color(&clr_addr)?;
+        write!(out, "0x{:01$x}", section.address(), addr_width)?;
+        out.reset()?;
+        write!(out, "  {:>10} bytes", section.size())?;
+        if section.is_code() {
+            write!(out, "  code")?;
+        } else if section.offset().is_none() {
+            write!(out, "  not in file")?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
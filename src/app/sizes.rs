@@ -0,0 +1,190 @@
+use crate::disasm::binary::Binary;
+use crate::disasm::symbol_table::SymbolQuery;
+use std::collections::HashMap;
+use std::fmt;
+use termcolor::{Color, ColorSpec, WriteColor};
+
+/// The crate that functions without a Rust path are listed under.
+const UNKNOWN_CRATE: &str = "[unknown]";
+
+/// The size of a function, or of all of the functions in a crate.
+pub struct SizeEntry<'b> {
+    pub name: &'b str,
+    /// The crate of a function. This is `None` for crates.
+    pub crate_name: Option<&'b str>,
+    pub size: usize,
+    /// The number of functions that the size is made up of.
+    pub count: usize,
+}
+
+/// All of the function sizes in a binary, largest first.
+pub struct SizeReport<'b> {
+    /// The combined size of every function in the binary.
+    pub total: usize,
+    pub entries: Vec<SizeEntry<'b>>,
+}
+
+impl<'b> SizeReport<'b> {
+    /// Lists the size of every function that `query` lists, or the combined size of the
+    /// functions of each crate if `by_crate` is true.
+    pub fn new(bin: &'b Binary, query: SymbolQuery<'b>, by_crate: bool) -> SizeReport<'b> {
+        let symbols = query.sized().functions().to_vec();
+        let total = symbols.iter().map(|sym| sym.size()).sum();
+
+        let mut entries = if by_crate {
+            let mut crates = HashMap::<&str, SizeEntry>::new();
+            for sym in symbols {
+                let name = bin.crate_of(sym).unwrap_or(UNKNOWN_CRATE);
+                let entry = crates.entry(name).or_insert(SizeEntry {
+                    name,
+                    crate_name: None,
+                    size: 0,
+                    count: 0,
+                });
+                entry.size += sym.size();
+                entry.count += 1;
+            }
+            crates.into_values().collect::<Vec<_>>()
+        } else {
+            symbols
+                .into_iter()
+                .map(|sym| SizeEntry {
+                    name: sym.name(),
+                    crate_name: Some(bin.crate_of(sym).unwrap_or(UNKNOWN_CRATE)),
+                    size: sym.size(),
+                    count: 1,
+                })
+                .collect::<Vec<_>>()
+        };
+        entries.sort_unstable_by(|lhs, rhs| rhs.size.cmp(&lhs.size).then(lhs.name.cmp(rhs.name)));
+
+        SizeReport { total, entries }
+    }
+
+    /// Removes all but the `limit` largest entries. A limit of 0 keeps every entry.
+    pub fn truncate(&mut self, limit: usize) {
+        if limit > 0 {
+            self.entries.truncate(limit);
+        }
+    }
+}
+
+/// Prints a table of sizes with the percentage of the total size of the functions in
+/// the binary that each entry makes up.
+pub fn print_sizes(
+    out: &mut dyn WriteColor,
+    report: &SizeReport,
+    by_crate: bool,
+) -> anyhow::Result<()> {
+    let mut clr_heading = ColorSpec::new();
+    clr_heading.set_bold(true);
+
+    let mut clr_size = ColorSpec::new();
+    clr_size.set_fg(Some(Color::Yellow));
+
+    let mut clr_crate = ColorSpec::new();
+    clr_crate.set_fg(Some(Color::Cyan));
+
+    let crate_width = report
+        .entries
+        .iter()
+        .filter_map(|entry| entry.crate_name)
+        .map(str::len)
+        .max()
+        .unwrap_or(0)
+        .max("Crate".len());
+
+    out.set_color(&clr_heading)?;
+    if by_crate {
+        writeln!(
+            out,
+            "{:>6}  {:>9}  {:>9}  Crate",
+            "Code%", "Size", "Functions"
+        )?;
+    } else {
+        writeln!(
+            out,
+            "{:>6}  {:>9}  {:<2$}  Name",
+            "Code%", "Size", crate_width
+        )?;
+    }
+    out.reset()?;
+
+    let mut listed = 0;
+    for entry in report.entries.iter() {
+        listed += entry.size;
+
+        write!(out, "{:>6}  ", Percent(entry.size, report.total))?;
+        out.set_color(&clr_size)?;
+        write!(out, "{:>9}", ByteSize(entry.size))?;
+        out.reset()?;
+
+        if by_crate {
+            write!(out, "  {:>9}  ", entry.count)?;
+            out.set_color(&clr_crate)?;
+            write!(out, "{}", entry.name)?;
+            out.reset()?;
+        } else {
+            out.set_color(&clr_crate)?;
+            write!(out, "  {:<1$}", entry.crate_name.unwrap_or(""), crate_width)?;
+            out.reset()?;
+            write!(out, "  {}", entry.name)?;
+        }
+        writeln!(out)?;
+    }
+
+    if listed < report.total {
+        let rest = report.total - listed;
+        writeln!(
+            out,
+            "{:>6}  {:>9}  (smaller entries, use `--limit 0` to list everything)",
+            Percent(rest, report.total),
+            ByteSize(rest),
+        )?;
+    }
+    out.set_color(&clr_heading)?;
+    writeln!(
+        out,
+        "{:>6}  {:>9}  total",
+        Percent(report.total, report.total),
+        ByteSize(report.total),
+    )?;
+    out.reset()?;
+
+    Ok(())
+}
+
+struct Percent(usize, usize);
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = if self.1 == 0 {
+            0.0
+        } else {
+            self.0 as f64 * 100.0 / self.1 as f64
+        };
+        f.pad(&format!("{:.1}%", percent))
+    }
+}
+
+/// Formats a number of bytes with binary units, e.g. `12.3KiB`.
+struct ByteSize(usize);
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+        let mut size = self.0 as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit + 1 < UNITS.len() {
+            size /= 1024.0;
+            unit += 1;
+        }
+
+        if unit == 0 {
+            f.pad(&format!("{}B", self.0))
+        } else {
+            f.pad(&format!("{:.1}{}", size, UNITS[unit]))
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Colors for the parts of a disassembly listing.
+
+use termcolor::{Color, ColorSpec};
+
+/// The names of the built-in themes.
+pub const THEME_NAMES: &[&str] = &["default", "muted", "plain"];
+
+pub struct Theme {
+    /// The name of the symbol above its disassembly.
+    pub symbol: ColorSpec,
+    pub address: ColorSpec,
+    pub bytes: ColorSpec,
+    pub mnemonic: ColorSpec,
+    pub register: ColorSpec,
+    pub immediate: ColorSpec,
+    /// Other parts of operands, such as punctuation and size keywords.
+    pub operand: ColorSpec,
+    /// The operands of jumps and calls with a known target.
+    pub jump_target: ColorSpec,
+    pub comment: ColorSpec,
+    /// Source lines shown with `--show-source`.
+    pub source: ColorSpec,
+    /// The file and line of source lines.
+    pub location: ColorSpec,
+    /// The share of samples from `--perf`, from instructions with few samples to the
+    /// hottest ones.
+    pub heat: [ColorSpec; 3],
+    /// The marker of basic blocks that were not executed according to `--coverage`.
+    pub uncovered: ColorSpec,
+    /// The conditional jumps of bounds and overflow checks with `--checks`.
+    pub check: ColorSpec,
+}
+
+impl Theme {
+    /// Returns the built-in theme with the given name.
+    pub fn named(name: &str) -> Option<Theme> {
+        if name.eq_ignore_ascii_case("default") {
+            Some(Theme::dark())
+        } else if name.eq_ignore_ascii_case("muted") {
+            Some(Theme::muted())
+        } else if name.eq_ignore_ascii_case("plain") {
+            Some(Theme::plain())
+        } else {
+            None
+        }
+    }
+
+    /// Colors for terminals with a dark background.
+    fn dark() -> Theme {
+        Theme {
+            symbol: color(Color::Cyan).set_bold(true).clone(),
+            address: color(Color::Blue),
+            bytes: color(Color::Yellow),
+            mnemonic: color(Color::Green).set_bold(true).clone(),
+            register: color(Color::Red),
+            immediate: color(Color::Magenta),
+            operand: ColorSpec::new(),
+            jump_target: color(Color::Cyan),
+            comment: color(Color::Yellow).set_italic(true).clone(),
+            source: color(Color::Magenta).set_bold(true).clone(),
+            location: color(Color::Magenta),
+            heat: [
+                color(Color::Green),
+                color(Color::Yellow),
+                color(Color::Red).set_bold(true).clone(),
+            ],
+            uncovered: color(Color::Red).set_bold(true).clone(),
+            check: color(Color::Red).set_bold(true).clone(),
+        }
+    }
+
+    /// Only highlights mnemonics, jump targets, and comments, which also works well on
+    /// light backgrounds.
+    fn muted() -> Theme {
+        Theme {
+            symbol: ColorSpec::new().set_bold(true).clone(),
+            address: ColorSpec::new().set_dimmed(true).clone(),
+            bytes: ColorSpec::new().set_dimmed(true).clone(),
+            mnemonic: color(Color::Blue).set_bold(true).clone(),
+            register: ColorSpec::new(),
+            immediate: ColorSpec::new(),
+            operand: ColorSpec::new(),
+            jump_target: color(Color::Blue),
+            comment: ColorSpec::new().set_italic(true).set_dimmed(true).clone(),
+            source: ColorSpec::new().set_bold(true).clone(),
+            location: ColorSpec::new().set_dimmed(true).clone(),
+            heat: [
+                ColorSpec::new().set_dimmed(true).clone(),
+                ColorSpec::new(),
+                color(Color::Red).set_bold(true).clone(),
+            ],
+            uncovered: color(Color::Red).set_bold(true).clone(),
+            check: color(Color::Red).set_bold(true).clone(),
+        }
+    }
+
+    /// No colors, only bold and italic text.
+    fn plain() -> Theme {
+        Theme {
+            symbol: ColorSpec::new().set_bold(true).clone(),
+            address: ColorSpec::new(),
+            bytes: ColorSpec::new(),
+            mnemonic: ColorSpec::new().set_bold(true).clone(),
+            register: ColorSpec::new(),
+            immediate: ColorSpec::new(),
+            operand: ColorSpec::new(),
+            jump_target: ColorSpec::new().set_bold(true).clone(),
+            comment: ColorSpec::new().set_italic(true).clone(),
+            source: ColorSpec::new().set_bold(true).clone(),
+            location: ColorSpec::new(),
+            heat: [
+                ColorSpec::new(),
+                ColorSpec::new(),
+                ColorSpec::new().set_bold(true).clone(),
+            ],
+            uncovered: ColorSpec::new().set_bold(true).clone(),
+            check: ColorSpec::new().set_bold(true).set_underline(true).clone(),
+        }
+    }
+
+    /// Returns the color of the operand part that starts at the given byte offset.
+    pub fn operand_color(&self, kinds: &[OperandKind], offset: usize) -> &ColorSpec {
+        match kinds.get(offset) {
+            Some(OperandKind::Register) => &self.register,
+            Some(OperandKind::Immediate) => &self.immediate,
+            _ => &self.operand,
+        }
+    }
+}
+
+fn color(fg: Color) -> ColorSpec {
+    let mut spec = ColorSpec::new();
+    spec.set_fg(Some(fg));
+    spec
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OperandKind {
+    Register,
+    Immediate,
+    Other,
+}
+
+/// Words in operands that are not registers.
+const OPERAND_KEYWORDS: &[&str] = &[
+    "byte", "word", "dword", "qword", "tbyte", "xword", "xmmword", "ymmword", "zmmword", "ptr",
+    "lsl", "lsr", "asr", "ror", "rrx", "uxtb", "uxth", "uxtw", "uxtx", "sxtb", "sxth", "sxtw",
+    "sxtx", "mul", "vl",
+];
+
+/// Returns the kind of operand that each byte of the operands belongs to. This is only
+/// based on how the operands look, so that it works for every architecture.
+pub fn classify_operands(operands: &str) -> Vec<OperandKind> {
+    let bytes = operands.as_bytes();
+    let mut kinds = vec![OperandKind::Other; bytes.len()];
+
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let start = idx;
+        let byte = bytes[idx];
+
+        // Immediates can have a prefix (`#` for ARM, `$` for AT&T) and a sign.
+        let mut number_start = idx;
+        if matches!(byte, b'#' | b'$') {
+            number_start += 1;
+        }
+        if bytes.get(number_start) == Some(&b'-') {
+            number_start += 1;
+        }
+        if bytes.get(number_start).is_some_and(u8::is_ascii_digit) {
+            idx = number_start;
+            if bytes[idx..].starts_with(b"0x") {
+                idx += 2;
+            }
+            while idx < bytes.len() && (bytes[idx].is_ascii_hexdigit() || bytes[idx] == b'.') {
+                idx += 1;
+            }
+            kinds[start..idx].fill(OperandKind::Immediate);
+            continue;
+        }
+
+        if byte == b'%' || byte.is_ascii_alphabetic() || byte == b'_' {
+            idx += 1;
+            while idx < bytes.len() && (bytes[idx].is_ascii_alphanumeric() || bytes[idx] == b'_') {
+                idx += 1;
+            }
+            let word = operands[start..idx].trim_start_matches('%');
+            if !OPERAND_KEYWORDS
+                .iter()
+                .any(|keyword| keyword.eq_ignore_ascii_case(word))
+            {
+                kinds[start..idx].fill(OperandKind::Register);
+            }
+            continue;
+        }
+
+        idx += 1;
+    }
+
+    kinds
+}
@@ -0,0 +1,380 @@
+//! An interactive terminal browser with a searchable list of the symbols in a binary and
+//! the disassembly of the selected symbol. Calls and jumps can be followed and then
+//! returned from like links.
+
+use crate::disasm::{self, binary::Binary, strmatch, symbol::Symbol, Disassembly, Jump};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Span, Spans};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{Frame, Terminal};
+
+const HELP: &str =
+    "enter: open/follow  backspace: back  tab: switch pane  arrows/pgup/pgdn: move  esc: quit";
+
+/// Opens the browser, starting with the disassembly of `initial` if there is one.
+pub fn run(bin: &Binary, initial: Option<&Symbol>, options: disasm::Options) -> anyhow::Result<()> {
+    let mut app = App::new(bin, options);
+    if let Some(symbol) = initial {
+        app.open(symbol, symbol.address());
+    }
+
+    enable_raw_mode()?;
+    let mut stdout = std::io::stdout();
+    crossterm::execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let result = app.run(&mut terminal);
+
+    // The terminal is restored even if the browser failed so that the error is readable.
+    disable_raw_mode()?;
+    crossterm::execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Focus {
+    Symbols,
+    Disassembly,
+}
+
+/// The disassembly of a symbol that is being viewed.
+struct View<'b> {
+    symbol: &'b Symbol,
+    disassembly: Disassembly,
+    cursor: usize,
+    scroll: usize,
+}
+
+struct App<'b> {
+    bin: &'b Binary,
+    options: disasm::Options,
+
+    /// Every symbol in the binary, sorted by address.
+    symbols: Vec<&'b Symbol>,
+    query: String,
+    /// Indices of the symbols matching the query, best matches first.
+    matches: Vec<usize>,
+    selected: usize,
+    scroll: usize,
+
+    view: Option<View<'b>>,
+    /// The views that were left by following jumps, most recent last.
+    history: Vec<View<'b>>,
+    focus: Focus,
+    status: String,
+}
+
+impl<'b> App<'b> {
+    fn new(bin: &'b Binary, options: disasm::Options) -> App<'b> {
+        let symbols = bin.all_symbols();
+        let matches = (0..symbols.len()).collect();
+        App {
+            bin,
+            options,
+            symbols,
+            query: String::new(),
+            matches,
+            selected: 0,
+            scroll: 0,
+            view: None,
+            history: Vec::new(),
+            focus: Focus::Symbols,
+            status: HELP.into(),
+        }
+    }
+
+    fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> anyhow::Result<()> {
+        loop {
+            terminal.draw(|f| self.draw(f))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Returns false if the browser should be closed.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Esc => return false,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.focus = match self.focus {
+                    Focus::Symbols if self.view.is_some() => Focus::Disassembly,
+                    _ => Focus::Symbols,
+                };
+                return true;
+            }
+            _ => {}
+        }
+
+        match self.focus {
+            Focus::Symbols => self.handle_symbols_key(key),
+            Focus::Disassembly => return self.handle_disassembly_key(key),
+        }
+        true
+    }
+
+    fn handle_symbols_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.filter();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.filter();
+            }
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected += 1,
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(PAGE),
+            KeyCode::PageDown => self.selected += PAGE,
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.selected = self.matches.len(),
+            KeyCode::Enter => {
+                if let Some(&idx) = self.matches.get(self.selected) {
+                    let symbol = self.symbols[idx];
+                    self.history.clear();
+                    self.open(symbol, symbol.address());
+                }
+            }
+            _ => {}
+        }
+        self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+    }
+
+    fn handle_disassembly_key(&mut self, key: KeyEvent) -> bool {
+        let view = match self.view {
+            Some(ref mut view) => view,
+            None => return true,
+        };
+        let last_line = view.disassembly.lines().len().saturating_sub(1);
+
+        match key.code {
+            KeyCode::Char('q') => return false,
+            KeyCode::Char('/') => self.focus = Focus::Symbols,
+            KeyCode::Up | KeyCode::Char('k') => view.cursor = view.cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => view.cursor = (view.cursor + 1).min(last_line),
+            KeyCode::PageUp => view.cursor = view.cursor.saturating_sub(PAGE),
+            KeyCode::PageDown => view.cursor = (view.cursor + PAGE).min(last_line),
+            KeyCode::Home => view.cursor = 0,
+            KeyCode::End => view.cursor = last_line,
+            KeyCode::Enter | KeyCode::Right => self.follow(),
+            KeyCode::Backspace | KeyCode::Left => self.back(),
+            _ => {}
+        }
+        true
+    }
+
+    fn filter(&mut self) {
+        let tokens = strmatch::Tokenizer::new(&self.query).collect::<Vec<&str>>();
+        let mut matches = self
+            .symbols
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, sym)| {
+                let dist = strmatch::distance(
+                    tokens.iter().copied(),
+                    strmatch::Tokenizer::new(sym.name()),
+                    u32::MAX,
+                )?;
+                Some((dist, sym.name().len(), idx))
+            })
+            .collect::<Vec<_>>();
+        if !tokens.is_empty() {
+            matches.sort_unstable();
+        }
+        self.matches = matches.into_iter().map(|(_, _, idx)| idx).collect();
+        self.selected = 0;
+        self.scroll = 0;
+    }
+
+    /// Disassembles a symbol and shows it with the cursor on the line containing `addr`.
+    fn open(&mut self, symbol: &'b Symbol, addr: u64) {
+        match disasm::disasm(self.bin, symbol, self.options) {
+            Ok(disassembly) => {
+                let cursor = disassembly
+                    .lines()
+                    .iter()
+                    .position(|line| line.contains_addr(addr))
+                    .unwrap_or(0);
+                if let Some(view) = self.view.take() {
+                    self.history.push(view);
+                }
+                self.view = Some(View {
+                    symbol,
+                    disassembly,
+                    cursor,
+                    scroll: 0,
+                });
+                self.focus = Focus::Disassembly;
+                self.status = HELP.into();
+            }
+            Err(err) => self.status = format!("failed to disassemble `{}`: {}", symbol.name(), err),
+        }
+    }
+
+    /// Follows the jump or call of the instruction under the cursor.
+    fn follow(&mut self) {
+        let view = match self.view {
+            Some(ref mut view) => view,
+            None => return,
+        };
+        match view.disassembly.lines()[view.cursor].jump() {
+            Jump::Internal(idx) => view.cursor = idx,
+            Jump::External(addr) => match self.bin.symbolicate(addr) {
+                Some((symbol, _)) => self.open(symbol, addr),
+                None => self.status = format!("no symbol contains 0x{:x}", addr),
+            },
+            Jump::Indirect(_) | Jump::None => {
+                self.status = "this instruction has no target that can be followed".into()
+            }
+        }
+    }
+
+    fn back(&mut self) {
+        if let Some(view) = self.history.pop() {
+            self.view = Some(view);
+        }
+    }
+
+    fn draw<B: Backend>(&mut self, f: &mut Frame<B>) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)].as_ref())
+            .split(f.size());
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(35), Constraint::Percentage(65)].as_ref())
+            .split(rows[0]);
+
+        self.draw_symbols(f, panes[0]);
+        self.draw_disassembly(f, panes[1]);
+        f.render_widget(
+            Paragraph::new(self.status.as_str()).style(Style::default().fg(Color::DarkGray)),
+            rows[1],
+        );
+    }
+
+    fn draw_symbols<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+            .split(area);
+
+        let search = Paragraph::new(self.query.as_str())
+            .block(pane_block("search", self.focus == Focus::Symbols));
+        f.render_widget(search, parts[0]);
+
+        // Only the visible symbols are turned into list items because there may be a
+        // very large number of them.
+        let height = parts[1].height.saturating_sub(2) as usize;
+        self.scroll = scroll_to(self.selected, self.scroll, height);
+        let items = self
+            .matches
+            .iter()
+            .skip(self.scroll)
+            .take(height)
+            .map(|&idx| ListItem::new(self.symbols[idx].name()))
+            .collect::<Vec<_>>();
+        let title = format!("symbols ({})", self.matches.len());
+        let list = List::new(items)
+            .block(pane_block(&title, self.focus == Focus::Symbols))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let mut state = ListState::default();
+        if !self.matches.is_empty() {
+            state.select(Some(self.selected - self.scroll));
+        }
+        f.render_stateful_widget(list, parts[1], &mut state);
+    }
+
+    fn draw_disassembly<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let focused = self.focus == Focus::Disassembly;
+        let view = match self.view {
+            Some(ref mut view) => view,
+            None => {
+                f.render_widget(Block::default().borders(Borders::ALL), area);
+                return;
+            }
+        };
+
+        let height = area.height.saturating_sub(2) as usize;
+        view.scroll = scroll_to(view.cursor, view.scroll, height);
+
+        let lines = view
+            .disassembly
+            .lines()
+            .iter()
+            .enumerate()
+            .skip(view.scroll)
+            .take(height)
+            .map(|(idx, line)| {
+                let mut spans = vec![
+                    Span::styled(
+                        format!("{:>8x}  ", line.address()),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(
+                        format!("{:<7} ", line.mnemonic()),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(line.operands().to_owned()),
+                ];
+                if !line.comments().is_empty() {
+                    spans.push(Span::styled(
+                        format!("  ; {}", line.comments()),
+                        Style::default().fg(Color::Yellow),
+                    ));
+                }
+
+                let mut spans = Spans::from(spans);
+                if idx == view.cursor && focused {
+                    for span in spans.0.iter_mut() {
+                        span.style = span.style.add_modifier(Modifier::REVERSED);
+                    }
+                }
+                spans
+            })
+            .collect::<Vec<_>>();
+
+        f.render_widget(
+            Paragraph::new(lines).block(pane_block(view.symbol.name(), focused)),
+            area,
+        );
+    }
+}
+
+/// The number of lines that page up and page down move by.
+const PAGE: usize = 20;
+
+fn pane_block(title: &str, focused: bool) -> Block<'_> {
+    let style = if focused {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default()
+    };
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(style)
+        .title(title)
+}
+
+/// Returns the new scroll offset that keeps the selected line within the visible lines.
+fn scroll_to(selected: usize, scroll: usize, height: usize) -> usize {
+    if selected < scroll {
+        selected
+    } else if height > 0 && selected >= scroll + height {
+        selected + 1 - height
+    } else {
+        scroll
+    }
+}
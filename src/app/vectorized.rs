@@ -0,0 +1,51 @@
+use crate::disasm::{symbol::Symbol, vector::VectorizationReport};
+use termcolor::{Color, ColorSpec, WriteColor};
+
+/// Prints whether each function and each of its loops were vectorized, and with which
+/// SIMD extension.
+pub fn print_reports(
+    out: &mut dyn WriteColor,
+    reports: &[(&Symbol, VectorizationReport)],
+) -> anyhow::Result<()> {
+    let mut clr_name = ColorSpec::new();
+    clr_name.set_fg(Some(Color::Cyan)).set_bold(true);
+    let mut clr_vectorized = ColorSpec::new();
+    clr_vectorized.set_fg(Some(Color::Green));
+    let mut clr_scalar = ColorSpec::new();
+    clr_scalar.set_fg(Some(Color::Yellow));
+    let mut clr_addr = ColorSpec::new();
+    clr_addr.set_fg(Some(Color::Blue));
+
+    for (symbol, report) in reports {
+        out.set_color(&clr_name)?;
+        write!(out, "{}", symbol.name())?;
+        out.reset()?;
+        write!(out, ": ")?;
+        out.set_color(if report.function.is_vectorized() {
+            &clr_vectorized
+        } else {
+            &clr_scalar
+        })?;
+        writeln!(out, "{}", report.function)?;
+
+        for loop_usage in report.loops.iter() {
+            out.set_color(&clr_addr)?;
+            write!(
+                out,
+                "    loop 0x{:x}..0x{:x}",
+                loop_usage.range.start, loop_usage.range.end
+            )?;
+            out.reset()?;
+            write!(out, ": ")?;
+            out.set_color(if loop_usage.usage.is_vectorized() {
+                &clr_vectorized
+            } else {
+                &clr_scalar
+            })?;
+            writeln!(out, "{}", loop_usage.usage)?;
+        }
+        out.reset()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use super::cli::{Opts, OutputFormat};
+use super::diff;
+use super::printer;
+use crate::disasm::{diff::DisasmDiff, Disassembly};
+use anyhow::Context as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+/// How often the files of the workspace are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How many unchanged instructions are printed around the changed ones.
+const DIFF_CONTEXT: usize = 3;
+
+/// Rebuilds the binary whenever a file of the workspace changes and prints the changes to
+/// the disassembly of the symbol. The first build prints the whole disassembly. Failed
+/// builds are reported and the previous disassembly is kept to compare the next one with.
+pub fn run(opts: &Opts, color_choice: ColorChoice) -> anyhow::Result<()> {
+    if opts.format != OutputFormat::Text {
+        return Err(anyhow::anyhow!("`--watch` can only print text"));
+    }
+    let root = workspace_root(opts)?;
+    log::debug!("watching {}", root.display());
+
+    let mut stdout = StandardStream::stdout(color_choice);
+    let mut previous: Option<Disassembly> = None;
+    loop {
+        let stamp = latest_modification(&root);
+        match rebuild(&mut stdout, opts, previous.as_ref()) {
+            Ok(disassembly) => previous = Some(disassembly),
+            Err(err) => log::error!("{:?}", err),
+        }
+        writeln!(stdout)?;
+        log::info!("waiting for changes in {}", root.display());
+
+        while latest_modification(&root) == stamp {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Builds and disassembles the symbol, and prints it or its changes since `previous`.
+fn rebuild(
+    out: &mut dyn WriteColor,
+    opts: &Opts,
+    previous: Option<&Disassembly>,
+) -> anyhow::Result<Disassembly> {
+    let binary_path = super::find_binary_path(opts)?;
+    let sources = super::parse_symbol_sources(opts)?;
+    let name = opts.symbol.as_deref().expect("`--watch` without a symbol");
+    let bin = diff::load_binary(&binary_path, opts, &sources, Some(name))?;
+    let symbol = bin
+        .fuzzy_find_symbol(name)
+        .with_context(|| format!("no symbol matching `{}` was found", name))?;
+    let disassembly = super::disasm_symbol(&bin, symbol, opts)?
+        .with_context(|| format!("`{}` can't be disassembled", symbol.name()))?;
+
+    let previous = match previous {
+        Some(previous) => previous,
+        None => {
+            let theme = opts.theme()?;
+            printer::print_disassembly(
+                out,
+                symbol,
+                &disassembly,
+                printer::DisasmOptions {
+                    show_source: false,
+                    show_inlined: false,
+                    show_bytes: opts.show_bytes(),
+                    bytes_format: opts.bytes_format(bin.endian())?,
+                    show_header: false,
+                    show_mangled: opts.show_mangled,
+                    labels: !opts.no_labels,
+                    arrows: opts.arrow_style(),
+                    theme: &theme,
+                    archive_member: None,
+                    marker: None,
+                    aliases: &[],
+                    frame: None,
+                    unwind: &[],
+                    call_sites: &[],
+                    costs: None,
+                    samples: None,
+                    executed: None,
+                    panic_paths: &[],
+                    stats: None,
+                    checks: None,
+                },
+            )
+            .context("error occured while printing disassembly")?;
+            out.reset()?;
+            return Ok(disassembly);
+        }
+    };
+
+    let changes = DisasmDiff::new(previous, &disassembly);
+    if changes.has_changes() {
+        diff::print_diff(
+            out,
+            symbol,
+            previous,
+            &disassembly,
+            &changes,
+            Some(DIFF_CONTEXT),
+        )
+        .context("error occured while printing diff")?;
+    } else {
+        let mut clr_name = ColorSpec::new();
+        clr_name.set_fg(Some(Color::Cyan)).set_bold(true);
+        out.set_color(&clr_name)?;
+        write!(out, "{}", symbol.name())?;
+        out.reset()?;
+        writeln!(out, ": no changes")?;
+    }
+    Ok(disassembly)
+}
+
+/// Finds the root directory of the Cargo workspace, which is watched for changes.
+fn workspace_root(opts: &Opts) -> anyhow::Result<PathBuf> {
+    let mut cmd = cargo_metadata::MetadataCommand::new();
+    cmd.no_deps();
+    if let Some(ref m) = opts.manifest_path {
+        cmd.manifest_path(m);
+    }
+    let metadata = cmd
+        .exec()
+        .context("error occurred while running cargo_metadata")?;
+    Ok(metadata.workspace_root)
+}
+
+/// Returns the latest modification time of the files in a directory and its
+/// subdirectories, skipping `target` and hidden directories such as `.git`.
+fn latest_modification(dir: &Path) -> Option<SystemTime> {
+    let mut latest = None;
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            log::trace!("failed to read {}: {}", dir.display(), err);
+            return None;
+        }
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        if file_name.starts_with('.') {
+            continue;
+        }
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(_) => continue,
+        };
+        let modified = if file_type.is_dir() {
+            if file_name == "target" {
+                continue;
+            }
+            latest_modification(&entry.path())
+        } else {
+            entry.metadata().and_then(|m| m.modified()).ok()
+        };
+        latest = latest.max(modified);
+    }
+    latest
+}
@@ -0,0 +1,96 @@
+use super::{Flow, Jump};
+use capstone::{arm64, Capstone, Insn};
+
+pub fn identify_jump_target(insn: &Insn, caps: &Capstone) -> Jump {
+    let has_immediate_target = matches!(
+        insn.mnemonic(),
+        "b" | "bl" | "cbz" | "cbnz" | "tbz" | "tbnz"
+    ) || insn.mnemonic().starts_with("b.");
+    if !has_immediate_target {
+        return Jump::None;
+    }
+
+    let details = caps.details(insn);
+    let details = match details.arm64() {
+        Some(details) => details,
+        None => {
+            log::error!("instruction did not have ARM64 details");
+            return Jump::None;
+        }
+    };
+
+    // The target is always the last operand, e.g. `cbz x0, #0x4005d0`.
+    match details.operands().last().map(|op| op.value()) {
+        Some(arm64::OpValue::Imm(addr)) => Jump::External(addr as u64),
+        _ => Jump::None,
+    }
+}
+
+/// Returns the address that is loaded from by literal loads (e.g. `ldr x0, #0x4006f0`) or
+/// computed by `adr`.
+pub fn identify_memory_reference(insn: &Insn, caps: &Capstone) -> Option<u64> {
+    let mnemonic = insn.mnemonic();
+    if mnemonic != "adr" && !mnemonic.starts_with("ldr") && mnemonic != "prfm" {
+        return None;
+    }
+
+    let details = caps.details(insn);
+    match details.arm64()?.operands().last()?.value() {
+        arm64::OpValue::Imm(addr) => Some(addr as u64),
+        _ => None,
+    }
+}
+
+pub fn identify_flow(insn: &Insn) -> Flow {
+    // Branches with pointer authentication (e.g. `retaa` or `blraaz`) have the same flow
+    // as the branches that they are based on.
+    match insn.mnemonic() {
+        "ret" | "retaa" | "retab" | "eret" | "eretaa" | "eretab" => Flow::Return,
+        "bl" | "blr" | "blraa" | "blraaz" | "blrab" | "blrabz" => Flow::Call,
+        "b" | "br" | "braa" | "braaz" | "brab" | "brabz" => Flow::Jump,
+        "cbz" | "cbnz" | "tbz" | "tbnz" => Flow::ConditionalJump,
+        "brk" | "hlt" | "udf" => Flow::Halt,
+        mnemonic if mnemonic.starts_with("b.") => Flow::ConditionalJump,
+        _ => Flow::Next,
+    }
+}
+
+/// Returns the mnemonic and operands of pointer authentication and branch target
+/// identification instructions that are disassembled as the `hint` instructions that they
+/// are encoded as, e.g. `hint #0x19` is `paciasp`.
+pub fn instruction_alias(insn: &Insn) -> Option<(&'static str, &'static str)> {
+    if insn.mnemonic() != "hint" {
+        return None;
+    }
+
+    let alias = match parse_immediate(insn.operands())? {
+        7 => ("xpaclri", ""),
+        8 => ("pacia1716", ""),
+        10 => ("pacib1716", ""),
+        12 => ("autia1716", ""),
+        14 => ("autib1716", ""),
+        24 => ("paciaz", ""),
+        25 => ("paciasp", ""),
+        26 => ("pacibz", ""),
+        27 => ("pacibsp", ""),
+        28 => ("autiaz", ""),
+        29 => ("autiasp", ""),
+        30 => ("autibz", ""),
+        31 => ("autibsp", ""),
+        32 => ("bti", ""),
+        34 => ("bti", "c"),
+        36 => ("bti", "j"),
+        38 => ("bti", "jc"),
+        _ => return None,
+    };
+    Some(alias)
+}
+
+fn parse_immediate(operand: &str) -> Option<u64> {
+    let operand = operand.trim().strip_prefix('#')?;
+    if let Some(hex) = operand.strip_prefix("0x") {
+        u64::from_str_radix(hex, 16).ok()
+    } else {
+        operand.parse().ok()
+    }
+}
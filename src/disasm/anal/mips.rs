@@ -0,0 +1,61 @@
+use super::{Flow, Jump};
+use capstone::{mips, Capstone, Insn};
+
+pub fn identify_jump_target(insn: &Insn, caps: &Capstone) -> Jump {
+    let flow = identify_flow(insn);
+    if !matches!(flow, Flow::Call | Flow::Jump | Flow::ConditionalJump) {
+        return Jump::None;
+    }
+
+    let details = caps.details(insn);
+    let details = match details.mips() {
+        Some(details) => details,
+        None => {
+            log::error!("instruction did not have MIPS details");
+            return Jump::None;
+        }
+    };
+
+    // The target is the last operand, e.g. `beq $a0, $zero, 0x400120`.
+    match details.operands().last().map(|op| op.value()) {
+        Some(mips::OpValue::Imm(addr)) => Jump::External(addr as u64),
+        _ => Jump::None,
+    }
+}
+
+pub fn identify_memory_reference(insn: &Insn, caps: &Capstone) -> Option<u64> {
+    let details = caps.details(insn);
+    let details = details.mips()?;
+
+    // Only accesses relative to `$zero` have an address that doesn't depend on registers.
+    details.operands().iter().find_map(|op| match op.value() {
+        mips::OpValue::Mem(mem) if mem.base() == mips::Reg::Zero => Some(mem.disp() as u64),
+        _ => None,
+    })
+}
+
+pub fn identify_flow(insn: &Insn) -> Flow {
+    match insn.mnemonic() {
+        "jr" if insn.operands() == "$ra" => Flow::Return,
+        "jr.hb" if insn.operands() == "$ra" => Flow::Return,
+        "eret" | "deret" => Flow::Return,
+        "jal" | "jalr" | "jalr.hb" | "jalx" | "bal" | "jalrc" | "jialc" | "balc" => Flow::Call,
+        "bgezal" | "bltzal" | "bgezall" | "bltzall" => Flow::Call,
+        "j" | "jr" | "jr.hb" | "b" | "jrc" | "jic" | "bc" => Flow::Jump,
+        "break" | "sdbbp" => Flow::Halt,
+        mnemonic if mnemonic.starts_with('b') && is_branch_mnemonic(mnemonic) => {
+            Flow::ConditionalJump
+        }
+        _ => Flow::Next,
+    }
+}
+
+/// Returns true for the conditional branches, which all start with `b` (e.g. `beq`,
+/// `bnez`, `bc1t`), unlike other instructions that start with `b` (e.g. `bitswap`).
+fn is_branch_mnemonic(mnemonic: &str) -> bool {
+    const CONDITIONS: &[&str] = &[
+        "beq", "bne", "bgez", "bgtz", "blez", "bltz", "bgec", "bltc", "bgeuc", "bltuc", "bovc",
+        "bnvc", "bc1", "bc2", "bz.", "bnz.", "bbit", "bposge",
+    ];
+    CONDITIONS.iter().any(|prefix| mnemonic.starts_with(prefix))
+}
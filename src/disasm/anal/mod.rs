@@ -1,20 +1,89 @@
+mod arm64;
+mod mips;
+mod ppc;
+mod sparc;
 mod x86;
 
-use capstone::{Arch, Capstone, Insn};
+use super::vector::SimdClass;
+use capstone::{Arch, Capstone, Insn, InsnGroup};
 
 pub fn identify_jump_target(insn: &Insn, caps: &Capstone) -> Jump {
     match caps.arch() {
         Arch::X86 => x86::identify_jump_target(insn, caps),
+        Arch::Arm64 => arm64::identify_jump_target(insn, caps),
+        Arch::Mips => mips::identify_jump_target(insn, caps),
+        Arch::PowerPc => ppc::identify_jump_target(insn, caps),
+        Arch::Sparc => sparc::identify_jump_target(insn, caps),
         _ => Jump::None,
     }
 }
 
+pub fn identify_flow(insn: &Insn, caps: &Capstone) -> Flow {
+    match caps.arch() {
+        Arch::X86 => x86::identify_flow(insn),
+        Arch::Arm64 => arm64::identify_flow(insn),
+        Arch::Mips => mips::identify_flow(insn),
+        Arch::PowerPc => ppc::identify_flow(insn, caps),
+        Arch::Sparc => sparc::identify_flow(insn, caps),
+        _ => identify_flow_from_groups(insn),
+    }
+}
+
+/// Identifies the flow of an instruction using only the generic groups that Capstone puts
+/// it in, for architectures that don't have their own analysis.
+fn identify_flow_from_groups(insn: &Insn) -> Flow {
+    let mut flow = Flow::Next;
+    for group in insn.groups() {
+        match group {
+            InsnGroup::RET | InsnGroup::IRET => return Flow::Return,
+            InsnGroup::CALL => flow = Flow::Call,
+            // Generic groups don't say whether a jump is conditional, so this assumes that
+            // execution can continue on to the next instruction.
+            InsnGroup::JUMP if flow == Flow::Next => flow = Flow::ConditionalJump,
+            _ => {}
+        }
+    }
+    flow
+}
+
+/// Returns the mnemonic and operands that an instruction should be shown with instead of
+/// the ones from Capstone, e.g. for instructions that Capstone only knows by their encoding.
+pub fn instruction_alias(insn: &Insn, caps: &Capstone) -> Option<(&'static str, &'static str)> {
+    match caps.arch() {
+        Arch::Arm64 => arm64::instruction_alias(insn),
+        _ => None,
+    }
+}
+
+/// Returns the address of the memory that an instruction references, if it can be
+/// determined without knowing the values of any registers.
+pub fn identify_memory_reference(insn: &Insn, caps: &Capstone) -> Option<u64> {
+    match caps.arch() {
+        Arch::X86 => x86::identify_memory_reference(insn, caps),
+        Arch::Arm64 => arm64::identify_memory_reference(insn, caps),
+        Arch::Mips => mips::identify_memory_reference(insn, caps),
+        _ => None,
+    }
+}
+
+/// Returns the SIMD extension of an instruction from the groups that Capstone puts it in.
+/// Capstone only has these groups for x86.
+pub fn identify_simd_class(insn: &Insn, caps: &Capstone) -> Option<SimdClass> {
+    match caps.arch() {
+        Arch::X86 => x86::identify_simd_class(insn),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum Jump {
     /// This is a jump an internal instruction inside of the symbol's function.
     Internal(usize),
     /// This is a jump to some external address that should be symbolicated.
     External(u64),
+    /// This is a jump through a pointer that is stored at the given address
+    /// (e.g. a call through the Import Address Table of a PE binary).
+    Indirect(u64),
     /// There is no jump.
     None,
 }
@@ -30,3 +99,34 @@ impl Jump {
         matches!(self, &Jump::External(..))
     }
 }
+
+/// How an instruction affects the flow of execution inside of a function.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Flow {
+    /// Execution always continues on to the next instruction.
+    Next,
+    /// Calls a function and then continues on to the next instruction.
+    Call,
+    /// Either jumps or continues on to the next instruction.
+    ConditionalJump,
+    /// Always jumps somewhere else.
+    Jump,
+    /// Returns from the function.
+    Return,
+    /// Execution never continues past this instruction (e.g. `ud2` or `hlt`).
+    Halt,
+}
+
+impl Flow {
+    /// Returns true if this instruction ends a basic block.
+    #[inline]
+    pub fn is_terminator(&self) -> bool {
+        !matches!(self, &Flow::Next | &Flow::Call)
+    }
+
+    /// Returns true if execution can continue on to the next instruction.
+    #[inline]
+    pub fn falls_through(&self) -> bool {
+        matches!(self, &Flow::Next | &Flow::Call | &Flow::ConditionalJump)
+    }
+}
@@ -0,0 +1,59 @@
+use super::{Flow, Jump};
+use capstone::{ppc, Capstone, Insn};
+
+pub fn identify_jump_target(insn: &Insn, caps: &Capstone) -> Jump {
+    let flow = identify_flow(insn, caps);
+    if !matches!(flow, Flow::Call | Flow::Jump | Flow::ConditionalJump) {
+        return Jump::None;
+    }
+
+    let details = caps.details(insn);
+    let details = match details.ppc() {
+        Some(details) => details,
+        None => {
+            log::error!("instruction did not have PowerPC details");
+            return Jump::None;
+        }
+    };
+
+    // The target is the last operand, e.g. `bne cr7, 0x10000420`. Branches to the link
+    // register and count register don't have one.
+    match details.operands().last().map(|op| op.value()) {
+        Some(ppc::OpValue::Imm(addr)) => Jump::External(addr as u64),
+        _ => Jump::None,
+    }
+}
+
+pub fn identify_flow(insn: &Insn, caps: &Capstone) -> Flow {
+    let mnemonic = insn.mnemonic().trim_end_matches(['+', '-']);
+    if !mnemonic.starts_with('b') || NOT_BRANCHES.iter().any(|p| mnemonic.starts_with(p)) {
+        return match mnemonic {
+            "trap" => Flow::Halt,
+            "rfi" | "rfid" => Flow::Return,
+            _ => Flow::Next,
+        };
+    }
+
+    // Branches that test a condition have a branch code, and the ones that decrement the
+    // count register (`bdnz`) are conditional as well.
+    let is_conditional = caps
+        .details(insn)
+        .ppc()
+        .is_some_and(|details| details.bc() != ppc::Bc::Invalid)
+        || mnemonic.starts_with("bd")
+        || (mnemonic.starts_with("bc") && !mnemonic.starts_with("bctr"));
+    let links = mnemonic.ends_with('l') || mnemonic.ends_with("la");
+
+    if links && !is_conditional {
+        Flow::Call
+    } else if mnemonic.ends_with("lr") && !is_conditional {
+        Flow::Return
+    } else if is_conditional {
+        Flow::ConditionalJump
+    } else {
+        Flow::Jump
+    }
+}
+
+/// Instructions that start with `b` but aren't branches.
+const NOT_BRANCHES: &[&str] = &["bcd", "bpermd", "brd", "brh", "brinc", "brw"];
@@ -0,0 +1,57 @@
+use super::{Flow, Jump};
+use capstone::{sparc, Capstone, Insn};
+
+pub fn identify_jump_target(insn: &Insn, caps: &Capstone) -> Jump {
+    let flow = identify_flow(insn, caps);
+    if !matches!(flow, Flow::Call | Flow::Jump | Flow::ConditionalJump) {
+        return Jump::None;
+    }
+
+    let details = caps.details(insn);
+    let details = match details.sparc() {
+        Some(details) => details,
+        None => {
+            log::error!("instruction did not have SPARC details");
+            return Jump::None;
+        }
+    };
+
+    // The target is the last operand, e.g. `bne %icc, 0x10490`.
+    match details.operands().last().map(|op| op.value()) {
+        Some(sparc::OpValue::Imm(addr)) => Jump::External(addr as u64),
+        _ => Jump::None,
+    }
+}
+
+pub fn identify_flow(insn: &Insn, caps: &Capstone) -> Flow {
+    let mnemonic = insn.mnemonic();
+    match mnemonic {
+        "ret" | "retl" | "retry" | "done" | "rett" => return Flow::Return,
+        "call" => return Flow::Call,
+        "jmp" => return Flow::Jump,
+        "unimp" | "illtrap" => return Flow::Halt,
+        _ => {}
+    }
+    // `jmpl` with a destination register links, e.g. `jmpl %g1, %o7` is an indirect call.
+    if mnemonic == "jmpl" {
+        return if insn.operands().ends_with("%g0") {
+            Flow::Jump
+        } else {
+            Flow::Call
+        };
+    }
+
+    let is_branch = mnemonic.starts_with('b') || mnemonic.starts_with("fb");
+    if !is_branch || mnemonic.starts_with("bmask") || mnemonic.starts_with("bshuffle") {
+        return Flow::Next;
+    }
+
+    // The condition of `ba` (branch always) and `bn` (branch never) doesn't depend on
+    // anything, so only `ba` is treated as an unconditional jump.
+    match caps.details(insn).sparc().map(|details| details.cc()) {
+        Some(sparc::Cc::IccA) | Some(sparc::Cc::FccA) => Flow::Jump,
+        Some(sparc::Cc::IccN) | Some(sparc::Cc::FccN) => Flow::Next,
+        _ if mnemonic == "ba" || mnemonic == "b" => Flow::Jump,
+        _ => Flow::ConditionalJump,
+    }
+}
@@ -1,31 +1,98 @@
-use super::Jump;
-use capstone::{x86, Capstone, Insn};
+use super::{Flow, Jump, SimdClass};
+use capstone::{x86, Capstone, Insn, InsnGroup};
 
 pub fn identify_jump_target(insn: &Insn, caps: &Capstone) -> Jump {
-    let generic_details = caps.details(insn);
-
-    let is_jump = generic_details.groups().iter().any(|&g| {
-        g == x86::InsnGroup::Call
-            || g == x86::InsnGroup::Jump
-            || g == x86::InsnGroup::BranchRelative
-    });
+    let is_jump = insn
+        .groups()
+        .any(|g| g == InsnGroup::CALL || g == InsnGroup::JUMP || g == InsnGroup::BRANCH_RELATIVE);
 
     if !is_jump {
         return Jump::None;
     }
 
+    let generic_details = caps.details(insn);
+
     if let Some(details) = generic_details.x86() {
         // Do these even exist?
         if details.operands().len() != 1 {
             return Jump::None;
         }
 
-        match details.operands()[0].value() {
-            x86::OpValue::Imm(addr) => Jump::External(addr as u64),
-            _ => Jump::None,
+        // call qword ptr [rip + disp] or call dword ptr [disp]
+        let next_insn = insn.address() + insn.size() as u64;
+        let operand = &details.operands()[0];
+        if let Some(addr) = operand.imm() {
+            Jump::External(addr as u64)
+        } else if let Some(slot) = operand
+            .mem()
+            .and_then(|mem| mem.effective_address(next_insn))
+        {
+            Jump::Indirect(slot)
+        } else {
+            Jump::None
         }
     } else {
         log::error!("instruction did not have x86 details");
         Jump::None
     }
 }
+
+pub fn identify_memory_reference(insn: &Insn, caps: &Capstone) -> Option<u64> {
+    let details = caps.details(insn);
+    let details = details.x86()?;
+
+    // [rip + disp] or [disp]
+    let next_insn = insn.address() + insn.size() as u64;
+    details
+        .operands()
+        .iter()
+        .find_map(|op| op.mem()?.effective_address(next_insn))
+}
+
+pub fn identify_simd_class(insn: &Insn) -> Option<SimdClass> {
+    insn.groups()
+        .filter_map(|group| {
+            let class = match group.x86() {
+                x86::InsnGroup::Mmx | x86::InsnGroup::_3dnow => SimdClass::Mmx,
+                x86::InsnGroup::Sse1
+                | x86::InsnGroup::Sse2
+                | x86::InsnGroup::Sse3
+                | x86::InsnGroup::Ssse3
+                | x86::InsnGroup::Sse41
+                | x86::InsnGroup::Sse42
+                | x86::InsnGroup::Sse4a => SimdClass::Sse,
+                x86::InsnGroup::Avx
+                | x86::InsnGroup::Fma
+                | x86::InsnGroup::Fma4
+                | x86::InsnGroup::F16c => SimdClass::Avx,
+                x86::InsnGroup::Avx2 => SimdClass::Avx2,
+                x86::InsnGroup::Avx512 => SimdClass::Avx512,
+                _ => return None,
+            };
+            Some(class)
+        })
+        .max()
+}
+
+pub fn identify_flow(insn: &Insn) -> Flow {
+    let has_group = |group| insn.groups().any(|g| g == group);
+
+    if has_group(InsnGroup::RET) || has_group(InsnGroup::IRET) {
+        Flow::Return
+    } else if has_group(InsnGroup::CALL) {
+        Flow::Call
+    } else if has_group(InsnGroup::JUMP) {
+        // The mnemonic is used here because it's the same for both Intel and AT&T syntax
+        // (except for the suffixes).
+        let mnemonic = insn.mnemonic();
+        if mnemonic.starts_with("jmp") || mnemonic.starts_with("ljmp") {
+            Flow::Jump
+        } else {
+            Flow::ConditionalJump
+        }
+    } else if matches!(insn.mnemonic(), "ud2" | "ud2b" | "hlt") {
+        Flow::Halt
+    } else {
+        Flow::Next
+    }
+}
@@ -0,0 +1,178 @@
+//! Comments the atomic instructions and fences with the operation and the memory ordering
+//! that they implement, e.g. `atomic fetch_add, AcqRel` for `ldaddal` on AArch64.
+//!
+//! The ordering can only be inferred as far as the instructions tell them apart. Locked
+//! instructions on x86 are full barriers whatever the ordering in the source was, and
+//! AArch64 uses `ldar` for both `Acquire` and `SeqCst` loads, so those are described by what
+//! the hardware guarantees.
+
+use super::binary::{Arch, Binary};
+use super::Disassembly;
+
+/// The operations of the AArch64 LSE atomics by the base of their mnemonic, which is
+/// followed by the ordering (`a`, `l`, or `al`) and the size (`b` or `h`). The pair
+/// variants come before the single ones so that they're matched first.
+const LSE_OPERATIONS: &[(&str, &str)] = &[
+    ("casp", "compare_exchange (pair)"),
+    ("cas", "compare_exchange"),
+    ("swp", "swap"),
+    ("ldadd", "fetch_add"),
+    ("ldclr", "fetch_and (clear)"),
+    ("ldeor", "fetch_xor"),
+    ("ldset", "fetch_or"),
+    ("ldsmax", "fetch_max"),
+    ("ldsmin", "fetch_min"),
+    ("ldumax", "fetch_max (unsigned)"),
+    ("ldumin", "fetch_min (unsigned)"),
+    ("stadd", "fetch_add"),
+    ("stclr", "fetch_and (clear)"),
+    ("steor", "fetch_xor"),
+    ("stset", "fetch_or"),
+    ("stsmax", "fetch_max"),
+    ("stsmin", "fetch_min"),
+    ("stumax", "fetch_max (unsigned)"),
+    ("stumin", "fetch_min (unsigned)"),
+];
+
+/// Comments the atomic instructions and fences of x86, x86_64, ARM, and AArch64. The
+/// comments of instructions that already have one, such as the name of the static that
+/// is accessed, are kept after the description.
+pub fn annotate(binary: &Binary, disassembly: &mut Disassembly) {
+    let arch = binary.arch();
+    for line in disassembly.lines.iter_mut() {
+        let description = match arch {
+            Arch::X86 | Arch::X86_64 => describe_x86(line.mnemonic(), line.operands()),
+            Arch::AArch64 => describe_arm64(line.mnemonic(), line.operands()),
+            Arch::Arm | Arch::Thumb => describe_arm(line.mnemonic(), line.operands()),
+            _ => None,
+        };
+        let description = match description {
+            Some(description) => description,
+            None => continue,
+        };
+        line.comments = Some(match line.comments.take() {
+            Some(comments) => format!("{}, {}", description, comments).into(),
+            None => description.into(),
+        });
+    }
+}
+
+/// Describes the locked read-modify-write instructions of x86 (and `xchg` with memory,
+/// which is locked implicitly) and the fences.
+fn describe_x86(mnemonic: &str, operands: &str) -> Option<String> {
+    let (locked, mnemonic) = match mnemonic.strip_prefix("lock ") {
+        Some(mnemonic) => (true, mnemonic.trim()),
+        None => (false, mnemonic),
+    };
+    let is_memory = operands.contains('[') || operands.contains('(');
+    let description = x86_description(mnemonic, locked, is_memory).or_else(|| {
+        // AT&T syntax adds the operand size to the mnemonic, e.g. `xaddq`.
+        let base = mnemonic.strip_suffix(|c| matches!(c, 'b' | 'w' | 'l' | 'q'))?;
+        if operands.contains('%') {
+            x86_description(base, locked, is_memory)
+        } else {
+            None
+        }
+    })?;
+    Some(description)
+}
+
+fn x86_description(mnemonic: &str, locked: bool, is_memory: bool) -> Option<String> {
+    let operation = match mnemonic {
+        "mfence" => return Some("fence SeqCst".to_string()),
+        "lfence" => return Some("load fence".to_string()),
+        "sfence" => return Some("store fence".to_string()),
+        "xchg" if is_memory => {
+            return Some("atomic swap or SeqCst store (full barrier)".to_string())
+        }
+        _ if !locked => return None,
+        "cmpxchg" => "compare_exchange",
+        "cmpxchg8b" | "cmpxchg16b" => "compare_exchange (double width)",
+        "xadd" => "fetch_add",
+        "add" | "inc" => "add",
+        "sub" | "dec" => "sub",
+        "and" | "or" | "xor" | "not" | "neg" => mnemonic,
+        "bts" => "bit set",
+        "btr" => "bit reset",
+        "btc" => "bit complement",
+        _ => return None,
+    };
+    Some(format!("atomic {} (full barrier)", operation))
+}
+
+/// Describes the load-acquire and store-release instructions, the exclusive loads and
+/// stores of LL/SC loops, the LSE atomics, and the barriers of AArch64.
+fn describe_arm64(mnemonic: &str, operands: &str) -> Option<String> {
+    if mnemonic == "dmb" {
+        return describe_barrier(operands);
+    }
+    // The size suffix of loads and stores, e.g. `ldarb`.
+    let sized = mnemonic
+        .strip_suffix(|c| c == 'b' || c == 'h')
+        .unwrap_or(mnemonic);
+    let description = match sized {
+        "ldar" => "atomic load, Acquire or SeqCst",
+        "ldapr" | "ldapur" => "atomic load, Acquire",
+        "stlr" | "stlur" => "atomic store, Release or SeqCst",
+        "ldxr" | "ldxp" => "load exclusive, Relaxed",
+        "ldaxr" | "ldaxp" => "load exclusive, Acquire",
+        "stxr" | "stxp" => "store exclusive, Relaxed",
+        "stlxr" | "stlxp" => "store exclusive, Release",
+        _ => return describe_lse(mnemonic),
+    };
+    Some(description.to_string())
+}
+
+/// Describes an LSE atomic, e.g. `ldaddal` or `casb`.
+fn describe_lse(mnemonic: &str) -> Option<String> {
+    let (base, operation) = LSE_OPERATIONS
+        .iter()
+        .find(|(base, _)| mnemonic.starts_with(base))?;
+    let suffix = &mnemonic[base.len()..];
+    let ordering_suffix = suffix
+        .strip_suffix(|c| c == 'b' || c == 'h')
+        .unwrap_or(suffix);
+    let ordering = match ordering_suffix {
+        "" => "Relaxed",
+        "a" => "Acquire",
+        "l" => "Release",
+        "al" => "AcqRel",
+        _ => return None,
+    };
+    Some(format!("atomic {}, {}", operation, ordering))
+}
+
+/// Describes a `dmb` barrier, which Rust emits for fences and for the orderings that the
+/// other instructions can't give, e.g. `dmb ishld` for `fence(Acquire)`.
+fn describe_barrier(operands: &str) -> Option<String> {
+    let option = operands.trim().trim_start_matches('#').to_lowercase();
+    let description = match option.as_str() {
+        "ish" | "sy" | "osh" | "nsh" | "0xb" | "0xf" => "fence Release, AcqRel, or SeqCst",
+        "ishld" | "ld" | "oshld" | "nshld" | "0x9" | "0xd" => "fence Acquire",
+        "ishst" | "st" | "oshst" | "nshst" | "0xa" | "0xe" => "fence for stores",
+        _ => return None,
+    };
+    Some(description.to_string())
+}
+
+/// Describes the load-acquire and store-release instructions, the exclusive loads and
+/// stores, and the barriers of ARM, which has `dmb` before or after plain loads and stores
+/// for the orderings before ARMv8.
+fn describe_arm(mnemonic: &str, operands: &str) -> Option<String> {
+    if mnemonic == "dmb" {
+        return describe_barrier(operands);
+    }
+    let sized = mnemonic
+        .strip_suffix(|c| c == 'b' || c == 'h' || c == 'd')
+        .unwrap_or(mnemonic);
+    let description = match sized {
+        "lda" => "atomic load, Acquire or SeqCst",
+        "stl" => "atomic store, Release or SeqCst",
+        "ldrex" => "load exclusive",
+        "strex" => "store exclusive",
+        "ldaex" => "load exclusive, Acquire",
+        "stlex" => "store exclusive, Release",
+        _ => return None,
+    };
+    Some(description.to_string())
+}
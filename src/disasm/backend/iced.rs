@@ -0,0 +1,176 @@
+use super::{Backend, DecodedInsn};
+use crate::disasm::binary::{Arch, Binary};
+use crate::disasm::vector::SimdClass;
+use crate::disasm::{Flow, Jump, Options, Syntax};
+use iced_x86::{
+    CpuidFeature, Decoder, DecoderOptions, FlowControl, Formatter, GasFormatter, Instruction,
+    IntelFormatter, Mnemonic, OpKind, Register,
+};
+
+pub struct IcedBackend {
+    bitness: u32,
+    formatter: Box<dyn Formatter>,
+    skip_data: bool,
+    mnemonic: String,
+    operands: String,
+}
+
+impl IcedBackend {
+    pub fn new(binary: &Binary, options: &Options) -> anyhow::Result<IcedBackend> {
+        let bitness = match binary.arch() {
+            Arch::X86 => 32,
+            Arch::X86_64 => 64,
+            arch => {
+                return Err(anyhow::anyhow!(
+                    "the iced engine doesn't support {}, only x86 and x86_64",
+                    arch
+                ))
+            }
+        };
+        let formatter: Box<dyn Formatter> = match options.syntax {
+            Syntax::Intel => Box::new(IntelFormatter::new()),
+            Syntax::Att => Box::new(GasFormatter::new()),
+        };
+
+        Ok(IcedBackend {
+            bitness,
+            formatter,
+            skip_data: !options.no_skip_data,
+            mnemonic: String::new(),
+            operands: String::new(),
+        })
+    }
+}
+
+impl Backend for IcedBackend {
+    fn decode(
+        &mut self,
+        code: &[u8],
+        address: u64,
+        f: &mut dyn FnMut(DecodedInsn) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let mut decoder = Decoder::with_ip(self.bitness, code, address, DecoderOptions::NONE);
+        let mut insn = Instruction::default();
+        while decoder.can_decode() {
+            let offset = decoder.position();
+            decoder.decode_out(&mut insn);
+
+            if insn.is_invalid() {
+                if !self.skip_data {
+                    break;
+                }
+                // Like Capstone's SKIPDATA mode, this skips over one byte at a time until
+                // there is a valid instruction.
+                decoder.set_position(offset + 1)?;
+                decoder.set_ip(insn.ip() + 1);
+                f(DecodedInsn::data(insn.ip(), &code[offset..(offset + 1)]))?;
+                continue;
+            }
+
+            self.mnemonic.clear();
+            self.operands.clear();
+            self.formatter.format_mnemonic(&insn, &mut self.mnemonic);
+            self.formatter
+                .format_all_operands(&insn, &mut self.operands);
+
+            let jump = identify_jump_target(&insn);
+            let memory_reference = match jump {
+                Jump::None => identify_memory_reference(&insn),
+                _ => None,
+            };
+            f(DecodedInsn {
+                address: insn.ip(),
+                bytes: &code[offset..(offset + insn.len())],
+                mnemonic: &self.mnemonic,
+                operands: &self.operands,
+                jump,
+                flow: identify_flow(&insn),
+                memory_reference,
+                simd: identify_simd_class(&insn),
+                is_data: false,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+fn identify_jump_target(insn: &Instruction) -> Jump {
+    if !matches!(
+        insn.flow_control(),
+        FlowControl::Call
+            | FlowControl::IndirectCall
+            | FlowControl::UnconditionalBranch
+            | FlowControl::IndirectBranch
+            | FlowControl::ConditionalBranch
+    ) {
+        return Jump::None;
+    }
+
+    match insn.op0_kind() {
+        OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64 => {
+            Jump::External(insn.near_branch_target())
+        }
+        // call qword ptr [rip + disp] or call dword ptr [disp]
+        OpKind::Memory => match identify_memory_reference(insn) {
+            Some(slot) => Jump::Indirect(slot),
+            None => Jump::None,
+        },
+        _ => Jump::None,
+    }
+}
+
+fn identify_memory_reference(insn: &Instruction) -> Option<u64> {
+    if insn.is_ip_rel_memory_operand() {
+        return Some(insn.ip_rel_memory_address());
+    }
+
+    // Addresses in the FS or GS segments are thread local.
+    let has_absolute_address = (0..insn.op_count()).any(|op| insn.op_kind(op) == OpKind::Memory)
+        && insn.memory_base() == Register::None
+        && insn.memory_index() == Register::None
+        && !matches!(insn.segment_prefix(), Register::FS | Register::GS);
+    if has_absolute_address {
+        Some(insn.memory_displacement64())
+    } else {
+        None
+    }
+}
+
+fn identify_simd_class(insn: &Instruction) -> Option<SimdClass> {
+    insn.cpuid_features()
+        .iter()
+        .filter_map(|feature| {
+            let class = match feature {
+                CpuidFeature::MMX | CpuidFeature::D3NOW => SimdClass::Mmx,
+                CpuidFeature::SSE
+                | CpuidFeature::SSE2
+                | CpuidFeature::SSE3
+                | CpuidFeature::SSSE3
+                | CpuidFeature::SSE4_1
+                | CpuidFeature::SSE4_2
+                | CpuidFeature::SSE4A => SimdClass::Sse,
+                CpuidFeature::AVX | CpuidFeature::FMA | CpuidFeature::F16C => SimdClass::Avx,
+                CpuidFeature::AVX2 => SimdClass::Avx2,
+                CpuidFeature::AVX512F
+                | CpuidFeature::AVX512BW
+                | CpuidFeature::AVX512CD
+                | CpuidFeature::AVX512DQ
+                | CpuidFeature::AVX512VL => SimdClass::Avx512,
+                _ => return None,
+            };
+            Some(class)
+        })
+        .max()
+}
+
+fn identify_flow(insn: &Instruction) -> Flow {
+    match insn.flow_control() {
+        FlowControl::Return => Flow::Return,
+        FlowControl::Call | FlowControl::IndirectCall => Flow::Call,
+        FlowControl::UnconditionalBranch | FlowControl::IndirectBranch => Flow::Jump,
+        FlowControl::ConditionalBranch => Flow::ConditionalJump,
+        FlowControl::Exception => Flow::Halt,
+        _ if insn.mnemonic() == Mnemonic::Hlt => Flow::Halt,
+        _ => Flow::Next,
+    }
+}
@@ -0,0 +1,174 @@
+//! The disassemblers that decode the instructions of symbols. Everything that doesn't
+//! depend on the disassembler, such as symbolication and source lines, is done with the
+//! decoded instructions in [`super::disasm`].
+
+#[cfg(feature = "iced")]
+mod iced;
+
+use super::anal::{self, Flow, Jump};
+use super::binary::{Binary, CodeMode};
+use super::vector::SimdClass;
+use super::{capstone_for_binary, capstone_mode, Options};
+use anyhow::Context as _;
+use capstone::Capstone;
+
+/// The disassembler used for instructions.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Engine {
+    #[default]
+    Capstone,
+    /// iced-x86, which only supports x86 and x86_64.
+    Iced,
+}
+
+impl std::str::FromStr for Engine {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("capstone") {
+            Ok(Engine::Capstone)
+        } else if s.eq_ignore_ascii_case("iced") || s.eq_ignore_ascii_case("iced-x86") {
+            Ok(Engine::Iced)
+        } else {
+            Err("invalid disassembly engine")
+        }
+    }
+}
+
+/// An instruction, or data that couldn't be disassembled, that was decoded by a backend.
+pub struct DecodedInsn<'a> {
+    pub address: u64,
+    pub bytes: &'a [u8],
+    pub mnemonic: &'a str,
+    pub operands: &'a str,
+    pub jump: Jump,
+    pub flow: Flow,
+    /// The address of the memory that the instruction references, if it isn't a jump.
+    pub memory_reference: Option<u64>,
+    /// The SIMD extension of the instruction, if the backend knows it.
+    pub simd: Option<SimdClass>,
+    /// These bytes are not a valid instruction and should be shown as data.
+    pub is_data: bool,
+}
+
+impl<'a> DecodedInsn<'a> {
+    fn data(address: u64, bytes: &'a [u8]) -> DecodedInsn<'a> {
+        DecodedInsn {
+            address,
+            bytes,
+            mnemonic: "",
+            operands: "",
+            jump: Jump::None,
+            flow: Flow::Next,
+            memory_reference: None,
+            simd: None,
+            is_data: true,
+        }
+    }
+}
+
+/// A disassembler that decodes machine code into instructions.
+pub trait Backend {
+    /// Decodes the instructions in `code`, which starts at `address`, and passes each of
+    /// them to `f` in order.
+    fn decode(
+        &mut self,
+        code: &[u8],
+        address: u64,
+        f: &mut dyn FnMut(DecodedInsn) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()>;
+
+    /// Switches between ARM and Thumb code. This is only called for ARM binaries.
+    fn set_code_mode(&mut self, _mode: CodeMode) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Creates the backend for the engine that was selected in the options.
+pub fn for_binary(binary: &Binary, options: &Options) -> anyhow::Result<Box<dyn Backend>> {
+    match options.engine {
+        Engine::Capstone => Ok(Box::new(CapstoneBackend::new(binary, options)?)),
+
+        #[cfg(feature = "iced")]
+        Engine::Iced => Ok(Box::new(iced::IcedBackend::new(binary, options)?)),
+
+        #[cfg(not(feature = "iced"))]
+        Engine::Iced => Err(anyhow::anyhow!(
+            "cargo-disasm was built without the iced engine (the `iced` feature)"
+        )),
+    }
+}
+
+pub struct CapstoneBackend {
+    caps: Capstone,
+    mode: capstone::Mode,
+}
+
+impl CapstoneBackend {
+    pub fn new(binary: &Binary, options: &Options) -> anyhow::Result<CapstoneBackend> {
+        let mut caps = capstone_for_binary(binary, options.syntax)?;
+        if !options.no_skip_data {
+            caps.set_skipdata_mode(true)
+                .context("failed to enable Capstone's SKIPDATA mode")?;
+        }
+        Ok(CapstoneBackend {
+            caps,
+            mode: capstone_mode(binary),
+        })
+    }
+}
+
+impl Backend for CapstoneBackend {
+    fn decode(
+        &mut self,
+        code: &[u8],
+        address: u64,
+        f: &mut dyn FnMut(DecodedInsn) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let caps = &self.caps;
+        for insn in caps.disasm_iter(code, address) {
+            let insn = insn.context("failed to disassemble instruction")?;
+
+            // Data inside of functions, such as jump tables, can't be disassembled and is
+            // skipped over by Capstone in SKIPDATA mode.
+            if insn.is_skipdata() {
+                f(DecodedInsn::data(insn.address(), insn.bytes()))?;
+                continue;
+            }
+
+            let jump = anal::identify_jump_target(insn, caps);
+            let memory_reference = match jump {
+                Jump::None => anal::identify_memory_reference(insn, caps),
+                _ => None,
+            };
+            let (mnemonic, operands) =
+                anal::instruction_alias(insn, caps).unwrap_or((insn.mnemonic(), insn.operands()));
+            f(DecodedInsn {
+                address: insn.address(),
+                bytes: insn.bytes(),
+                mnemonic,
+                operands,
+                jump,
+                flow: anal::identify_flow(insn, caps),
+                memory_reference,
+                simd: anal::identify_simd_class(insn, caps),
+                is_data: false,
+            })?;
+        }
+        Ok(())
+    }
+
+    fn set_code_mode(&mut self, mode: CodeMode) -> anyhow::Result<()> {
+        match mode {
+            CodeMode::Thumb => self
+                .caps
+                .set_mode(self.mode | capstone::Mode::Thumb)
+                .context("failed to switch Capstone to Thumb mode"),
+            CodeMode::Arm => self
+                .caps
+                .set_mode(self.mode - capstone::Mode::Thumb)
+                .context("failed to switch Capstone to ARM mode"),
+            CodeMode::Data => Ok(()),
+        }
+    }
+}
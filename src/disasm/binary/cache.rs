@@ -0,0 +1,157 @@
+//! A persistent cache of the symbols and line information that were read from the debug
+//! information of a binary, so that it doesn't have to be parsed again on every run.
+//! Cache entries are only used if the binary and its separate debug file have the same
+//! size and modification time as when they were written.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Identifies the version of a binary that cache entries were written for.
+#[derive(Serialize, Deserialize, PartialEq, Eq)]
+struct CacheKey {
+    version: Box<str>,
+    binary: FileStamp,
+
+    /// The separate debug file, dSYM or PDB that the symbols were read from.
+    debug_file: Option<FileStamp>,
+
+    /// The dSYM bundle that was passed with `--dsym`, if any.
+    dsym_path: Option<PathBuf>,
+}
+
+/// Identifies the version of a file by its path, size and modification time.
+#[derive(Serialize, Deserialize, PartialEq, Eq)]
+struct FileStamp {
+    path: PathBuf,
+    len: u64,
+    modified: Duration,
+}
+
+impl FileStamp {
+    fn new(path: &Path) -> Option<FileStamp> {
+        let path = path.canonicalize().ok()?;
+        let metadata = std::fs::metadata(&path).ok()?;
+        let modified = metadata
+            .modified()
+            .ok()?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()?;
+        Some(FileStamp {
+            path,
+            len: metadata.len(),
+            modified,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct CacheEntryRef<'v, T: ?Sized> {
+    key: &'v CacheKey,
+    value: &'v T,
+}
+
+#[derive(Deserialize)]
+struct CacheEntry<T> {
+    key: CacheKey,
+    value: T,
+}
+
+pub struct Cache {
+    dir: PathBuf,
+    key: CacheKey,
+}
+
+impl Cache {
+    /// Returns the cache for the binary at the given path and the dSYM bundle that was
+    /// passed for it, or `None` if there is no cache directory or if the binary's metadata
+    /// can't be read.
+    pub fn new(binary_path: &Path, dsym_path: Option<&Path>) -> Option<Cache> {
+        let dir = crate::util::user_cache_dir()?
+            .join("cargo-disasm")
+            .join("symbols");
+        Some(Cache {
+            dir,
+            key: CacheKey {
+                version: env!("CARGO_PKG_VERSION").into(),
+                binary: FileStamp::new(binary_path)?,
+                debug_file: None,
+                dsym_path: dsym_path.map(Path::to_path_buf),
+            },
+        })
+    }
+
+    /// Adds the separate file that the debug information of the binary is read from to
+    /// the key of the cache entries, so that they aren't used with another debug file or
+    /// after it was rebuilt. Returns `false` if the file's metadata can't be read, in which
+    /// case the cache can't be used.
+    pub fn set_debug_file(&mut self, path: &Path) -> bool {
+        self.key.debug_file = FileStamp::new(path);
+        self.key.debug_file.is_some()
+    }
+
+    /// Returns the cached value with the given name if there is one for this version of
+    /// the binary.
+    pub fn load<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        let path = self.entry_path(name);
+        let file = std::fs::File::open(&path).ok()?;
+        match serde_json::from_reader::<_, CacheEntry<T>>(std::io::BufReader::new(file)) {
+            Ok(entry) if entry.key == self.key => {
+                log::debug!("using cached {} from `{}`", name, path.display());
+                Some(entry.value)
+            }
+            Ok(_) => {
+                log::debug!("ignoring stale cache entry `{}`", path.display());
+                None
+            }
+            Err(err) => {
+                log::debug!("failed to read cache entry `{}`: {}", path.display(), err);
+                None
+            }
+        }
+    }
+
+    /// Caches a value with the given name for this version of the binary. Failing to
+    /// write the cache isn't an error because it will just be rebuilt on the next run.
+    pub fn store<T: Serialize + ?Sized>(&self, name: &str, value: &T) {
+        let path = self.entry_path(name);
+        let store = || -> anyhow::Result<()> {
+            std::fs::create_dir_all(&self.dir)?;
+
+            // Write to a temporary file first so that other runs never see a partially
+            // written entry.
+            let mut partial_path = path.clone().into_os_string();
+            partial_path.push(".partial");
+            let file = std::fs::File::create(&partial_path)?;
+            let entry = CacheEntryRef {
+                key: &self.key,
+                value,
+            };
+            let mut writer = std::io::BufWriter::new(file);
+            serde_json::to_writer(&mut writer, &entry)?;
+            writer.flush()?;
+            std::fs::rename(&partial_path, &path)?;
+            Ok(())
+        };
+
+        if let Err(err) = store() {
+            log::warn!(
+                "failed to write cache entry `{}`: {:?}",
+                path.display(),
+                err
+            );
+        }
+    }
+
+    /// Entries are named with a hash of the binary's path so that rebuilding a binary
+    /// replaces its entries instead of adding new ones.
+    fn entry_path(&self, name: &str) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        self.key.binary.path.hash(&mut hasher);
+        self.dir
+            .join(format!("{:016x}-{}.json", hasher.finish(), name))
+    }
+}
@@ -1,14 +1,24 @@
-use super::{Arch, Binary, BinaryData, Bits, Endian, DWARF_SECTIONS};
-use crate::disasm::dwarf::DwarfInfo;
-use crate::disasm::symbol::{Symbol, SymbolSource};
+use super::{
+    stubs, Arch, Binary, BinaryData, Bits, CodeMode, Endian, Format, Os, Relocation, Section,
+    DWARF_SECTIONS,
+};
+use crate::disasm::debuginfod;
+use crate::disasm::demangle;
+use crate::disasm::dwarf::{BinaryDataReader, DwarfInfo, DwarfPackage};
+use crate::disasm::symbol::{Symbol, SymbolSource, SymbolType};
+use crate::disasm::tls::ThreadLocals;
+use crate::disasm::unwind::{UnwindSection, UnwindSections};
 use crate::util;
 use anyhow::Context as _;
-use goblin::elf::Elf;
+use goblin::elf::{section_header, Elf};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 pub fn load_arch_info(binary: &mut Binary, elf: &Elf) -> anyhow::Result<()> {
     use goblin::elf::header;
 
     log::debug!("object type   = ELF");
+    binary.format = Format::Elf;
 
     binary.bits = Bits::from_elf_class(elf.header.e_ident[header::EI_CLASS]);
     binary.endian = Endian::from(
@@ -16,25 +26,76 @@ pub fn load_arch_info(binary: &mut Binary, elf: &Elf) -> anyhow::Result<()> {
             .endianness()
             .context("failed to identify ELF endianness")?,
     );
-    binary.arch = Arch::from_elf_machine(elf.header.e_machine);
+    binary.arch = Arch::from_elf_machine(elf.header.e_machine, binary.bits);
+    if matches!(binary.arch, Arch::RiscV32 | Arch::RiscV64) {
+        binary.compressed_instructions = elf.header.e_flags & EF_RISCV_RVC != 0;
+    }
+    // Most Linux binaries have the System V ABI rather than the GNU one.
+    binary.os = match elf.header.e_ident[header::EI_OSABI] {
+        ELFOSABI_NONE | ELFOSABI_GNU => Os::Linux,
+        _ => Os::Unknown,
+    };
 
     log::debug!("object bits   = {}", binary.bits);
     log::debug!("object endian = {}", binary.endian);
     log::debug!("object arch   = {}", binary.arch);
+    log::debug!("object os     = {}", binary.os);
+    if binary.compressed_instructions {
+        log::debug!("object uses compressed RISC-V instructions");
+    }
+    if is_relocatable(elf) {
+        log::debug!("object is relocatable, using file offsets as addresses");
+    } else if elf.program_headers.is_empty() {
+        log::debug!("object has no program headers, using section addresses");
+    }
 
     Ok(())
 }
 
 pub fn load_symbols(elf: &Elf, symbols: &mut Vec<Symbol>) -> anyhow::Result<()> {
-    for sym in elf.syms.iter().filter(|sym| sym.is_function()) {
+    load_symbol_table(elf, elf, symbols)
+}
+
+/// Loads the symbol table of a separate debug file. The debug file has the same section
+/// headers as the stripped binary but its code sections have no contents, so the file
+/// offsets of the symbols come from the binary's section headers.
+pub fn load_debug_file_symbols(
+    debug_elf: &Elf,
+    elf: &Elf,
+    symbols: &mut Vec<Symbol>,
+) -> anyhow::Result<()> {
+    load_symbol_table(debug_elf, elf, symbols)
+}
+
+fn load_symbol_table(sym_elf: &Elf, elf: &Elf, symbols: &mut Vec<Symbol>) -> anyhow::Result<()> {
+    use goblin::elf::sym::STT_OBJECT;
+
+    let is_arm = elf.header.e_machine == goblin::elf::header::EM_ARM;
+    for sym in sym_elf.syms.iter() {
+        let symbol_type = if sym.is_function() {
+            SymbolType::Function
+        } else if sym.st_type() == STT_OBJECT {
+            SymbolType::Static
+        } else {
+            continue;
+        };
+
         // FIXME handle symbols with a size of 0 (usually external symbols).
         if sym.st_size == 0 {
             continue;
         }
 
+        // Absolute and common symbols (e.g. in kernel modules and linker scripts) aren't in
+        // a section that they could be read from.
+        if sym.st_shndx == section_header::SHN_UNDEF as usize
+            || sym.st_shndx >= section_header::SHN_LORESERVE as usize
+        {
+            continue;
+        }
+
         // FIXME maybe the error here should just be a warning instead. I'm pretty sure it's
         // recoverable :|
-        let sym_name = if let Some(name) = elf
+        let sym_name = if let Some(name) = sym_elf
             .strtab
             .get(sym.st_name)
             .transpose()
@@ -53,23 +114,45 @@ pub fn load_symbols(elf: &Elf, symbols: &mut Vec<Symbol>) -> anyhow::Result<()>
                     sym.st_shndx
                 )
             })?;
+            // Zero initialized data has no contents in the file.
+            if sym_section.sh_type == section_header::SHT_NOBITS {
+                continue;
+            }
             (sym_section.sh_offset, sym_section.sh_addr)
         };
 
+        // The lowest bit of the addresses of ARM functions is set for Thumb functions.
+        let st_value = if is_arm && sym.is_function() {
+            sym.st_value & !1
+        } else {
+            sym.st_value
+        };
+
         // FIXME clamp values to section bounds.
-        // FIXME This works for executable and shared objects that use st_value as a virtual
-        // address to the symbol, but I also want to handle relocatable files, in which case
-        // st_value would hold a section offset for the symbol.
-        let sym_addr = sym.st_value;
-        let sym_offset = (sym_addr - section_addr) + section_offset;
+        // In relocatable objects st_value is an offset into the symbol's section and
+        // every section starts at address 0, so file offsets are used as addresses instead.
+        let (sym_addr, sym_offset) = if is_relocatable(elf) {
+            let sym_offset = section_offset + st_value;
+            (sym_offset, sym_offset)
+        } else if let Some(section_relative) = st_value.checked_sub(section_addr) {
+            (st_value, section_relative + section_offset)
+        } else {
+            log::debug!(
+                "ignoring `{}` because it is outside of its section",
+                sym_name
+            );
+            continue;
+        };
 
-        symbols.push(Symbol::new(
+        let mut symbol = Symbol::new(
             sym_name,
             sym_addr,
             sym_offset as usize,
             sym.st_size as usize,
             SymbolSource::Elf,
-        ));
+        );
+        symbol.set_symbol_type(symbol_type);
+        symbols.push(symbol);
     }
 
     Ok(())
@@ -95,34 +178,371 @@ pub fn load_dwarf_symbols(
     elf: &Elf,
     symbols: &mut Vec<Symbol>,
 ) -> anyhow::Result<()> {
+    let sections = sections_by_address(elf);
+    dwarf.load_symbols(symbols, |addr| addr_to_offset(&sections, addr))?;
+
+    Ok(())
+}
+
+/// Loads symbols from the `.dwo` files of a binary that was built with `-gsplit-dwarf`, or
+/// from the `.dwp` package next to the binary if there is one.
+pub fn load_split_dwarf_symbols(
+    dwarf: &DwarfInfo,
+    elf: &Elf,
+    binary_path: &Path,
+    endian: Endian,
+    symbols: &mut Vec<Symbol>,
+) -> anyhow::Result<()> {
+    use gimli::{EndianReader, RunTimeEndian};
+
+    let skeletons = dwarf.skeleton_units()?;
+    if skeletons.is_empty() {
+        return Ok(());
+    }
+    log::debug!("found {} split DWARF compilation units", skeletons.len());
+
+    let endian = RunTimeEndian::from(endian);
+    let sections = sections_by_address(elf);
+    let binary_dir = binary_path.parent();
+
+    let mut package_path = binary_path.as_os_str().to_owned();
+    package_path.push(".dwp");
+    let package_path = PathBuf::from(package_path);
+    let package_data = if package_path.is_file() {
+        log::debug!("found DWARF package at `{}`", package_path.display());
+        Some(BinaryData::from_path(&package_path)?)
+    } else {
+        None
+    };
+    let package = match package_data {
+        Some(ref data) => {
+            let package_elf = Elf::parse(data).context("failed to parse DWARF package")?;
+            let index = section_by_name(&package_elf, ".debug_cu_index", data)?;
+            let index = DwarfPackage::parse(EndianReader::new(index, endian))
+                .context("failed to parse DWARF package index")?;
+            Some((data, package_elf, index))
+        }
+        None => None,
+    };
+
+    for skeleton in skeletons.iter() {
+        let symbols_count_before = symbols.len();
+
+        let result = match (&package, skeleton.dwo_id()) {
+            (Some((data, package_elf, index)), Some(dwo_id)) => {
+                let loader = |section: gimli::SectionId| -> anyhow::Result<BinaryDataReader> {
+                    let name = format!("{}.dwo", section.name());
+                    let section_data = section_by_name(package_elf, &name, data)?;
+                    let range = index
+                        .section_range(dwo_id, section, section_data.len())
+                        .unwrap_or(0..0);
+                    Ok(EndianReader::new(section_data.slice(range), endian))
+                };
+                dwarf.load_split_symbols(skeleton, loader, symbols, |addr| {
+                    addr_to_offset(&sections, addr)
+                })
+            }
+
+            _ => {
+                let dwo_path = if let Some(dwo_path) = skeleton.locate_dwo(binary_dir) {
+                    dwo_path
+                } else {
+                    log::warn!("failed to find split DWARF object `{}`", skeleton);
+                    continue;
+                };
+                let data = BinaryData::from_path(&dwo_path)?;
+                let dwo_elf = Elf::parse(&data)
+                    .with_context(|| format!("failed to parse `{}`", dwo_path.display()))?;
+                let loader = |section: gimli::SectionId| {
+                    let name = format!("{}.dwo", section.name());
+                    section_by_name(&dwo_elf, &name, &data).map(|d| EndianReader::new(d, endian))
+                };
+                dwarf.load_split_symbols(skeleton, loader, symbols, |addr| {
+                    addr_to_offset(&sections, addr)
+                })
+            }
+        };
+
+        // A single bad split object shouldn't stop the rest of them from being loaded.
+        if let Err(err) = result {
+            log::warn!(
+                "failed to load split DWARF object `{}`: {:?}",
+                skeleton,
+                err
+            );
+        } else {
+            log::trace!(
+                "found {} symbols in split DWARF object `{}`",
+                symbols.len() - symbols_count_before,
+                skeleton
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The directories that the dynamic linker searches after the directories from the
+/// binary and `LD_LIBRARY_PATH`.
+const DEFAULT_LIBRARY_DIRECTORIES: &[&str] =
+    &["/lib", "/usr/lib", "/lib64", "/usr/lib64", "/usr/local/lib"];
+
+/// Finds a library in `DT_NEEDED` using the `DT_RPATH`, `LD_LIBRARY_PATH`, `DT_RUNPATH`,
+/// and default library directories, in the same order as the dynamic linker.
+pub fn find_library(elf: &Elf, data: &BinaryData, name: &str) -> anyhow::Result<PathBuf> {
+    let library = super::match_library(&elf.libraries, name)?;
+    if library.contains('/') {
+        return Ok(PathBuf::from(library));
+    }
+
+    let origin = data
+        .path()
+        .parent()
+        .and_then(|dir| dir.canonicalize().ok())
+        .unwrap_or_default();
+    let origin = origin.to_string_lossy();
+    let expand = |paths: &[&str]| {
+        paths
+            .iter()
+            .flat_map(|paths| paths.split(':'))
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| {
+                PathBuf::from(
+                    dir.replace("${ORIGIN}", &origin)
+                        .replace("$ORIGIN", &origin),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let dynamic_strings = |tag: u64| {
+        elf.dynamic
+            .iter()
+            .flat_map(|dynamic| dynamic.dyns.iter())
+            .filter(|entry| entry.d_tag == tag)
+            .filter_map(|entry| elf.dynstrtab.get(entry.d_val as usize)?.ok())
+            .collect::<Vec<_>>()
+    };
+    let rpaths = dynamic_strings(goblin::elf::dynamic::DT_RPATH);
+    let runpaths = dynamic_strings(goblin::elf::dynamic::DT_RUNPATH);
+
+    // `DT_RPATH` is ignored when there is a `DT_RUNPATH`.
+    let mut directories = Vec::new();
+    if runpaths.is_empty() {
+        directories.extend(expand(&rpaths));
+    }
+    if let Some(paths) = std::env::var_os("LD_LIBRARY_PATH") {
+        directories.extend(std::env::split_paths(&paths));
+    }
+    directories.extend(expand(&runpaths));
+    if let Some(triple) = multiarch_triple(elf.header.e_machine) {
+        directories.push(Path::new("/lib").join(triple));
+        directories.push(Path::new("/usr/lib").join(triple));
+    }
+    directories.extend(DEFAULT_LIBRARY_DIRECTORIES.iter().map(PathBuf::from));
+
+    log::debug!("searching for `{}` in {:?}", library, directories);
+    super::find_in_directories(library, &directories)
+}
+
+/// Returns the name of the Debian style multiarch library directories for a machine.
+fn multiarch_triple(machine: u16) -> Option<&'static str> {
+    use goblin::elf::header;
+
+    match machine {
+        header::EM_X86_64 => Some("x86_64-linux-gnu"),
+        header::EM_386 => Some("i386-linux-gnu"),
+        header::EM_AARCH64 => Some("aarch64-linux-gnu"),
+        header::EM_ARM => Some("arm-linux-gnueabihf"),
+        header::EM_PPC64 => Some("powerpc64le-linux-gnu"),
+        header::EM_RISCV => Some("riscv64-linux-gnu"),
+        _ => None,
+    }
+}
+
+/// The directory that distributions install separate debug files into.
+const DEBUG_FILE_DIRECTORY: &str = "/usr/lib/debug";
+
+const NT_GNU_BUILD_ID: u32 = 3;
+
+/// Set in the ELF header flags of RISC-V binaries that use the C extension.
+const EF_RISCV_RVC: u32 = 0x1;
+
+/// The OS ABIs in the ELF identification that are used by Linux binaries.
+const ELFOSABI_NONE: u8 = 0;
+const ELFOSABI_GNU: u8 = 3;
+
+/// Finds the separate debug file of a stripped binary using its build ID or its
+/// `.gnu_debuglink` section. The same places are searched as GDB does, as well as the
+/// debuginfod client cache. If the debug file isn't found locally it is downloaded from
+/// a debuginfod server when the `debuginfod` feature is enabled.
+pub fn find_debug_file(
+    elf: &Elf,
+    data: &BinaryData,
+    build_id: Option<&str>,
+    endian: Endian,
+) -> Option<PathBuf> {
+    if let Some(build_id) = build_id.filter(|build_id| build_id.len() > 2) {
+        let path = Path::new(DEBUG_FILE_DIRECTORY)
+            .join(".build-id")
+            .join(&build_id[..2])
+            .join(format!("{}.debug", &build_id[2..]));
+        if path.is_file() {
+            return Some(path);
+        }
+    }
+
+    if let Some(path) = find_debuglink_file(elf, data, endian) {
+        return Some(path);
+    }
+
+    build_id.and_then(debuginfod::fetch_debuginfo)
+}
+
+fn find_debuglink_file(elf: &Elf, data: &BinaryData, endian: Endian) -> Option<PathBuf> {
+    let (name, crc) = debuglink(elf, data, endian)?;
+    log::debug!("binary debug link = {}", name);
+
+    let binary_dir = data.path().parent().unwrap_or_else(|| Path::new(""));
+    let mut candidates = vec![binary_dir.join(name), binary_dir.join(".debug").join(name)];
+    if let Ok(binary_dir) = binary_dir.canonicalize() {
+        let relative_dir = binary_dir.strip_prefix("/").unwrap_or(&binary_dir);
+        candidates.push(
+            Path::new(DEBUG_FILE_DIRECTORY)
+                .join(relative_dir)
+                .join(name),
+        );
+    }
+
+    candidates.into_iter().find(|path| {
+        // The link may name the binary itself if it was never stripped.
+        if !path.is_file() || path.as_path() == data.path() {
+            return false;
+        }
+        match BinaryData::from_path(path) {
+            Ok(contents) if crc32(&contents) == crc => true,
+            Ok(_) => {
+                log::warn!(
+                    "ignoring `{}` because its CRC does not match",
+                    path.display()
+                );
+                false
+            }
+            Err(err) => {
+                log::warn!("failed to read `{}`: {:?}", path.display(), err);
+                false
+            }
+        }
+    })
+}
+
+/// Returns the build ID of the binary from its `NT_GNU_BUILD_ID` note as a hex string.
+pub fn build_id(elf: &Elf, data: &[u8], endian: Endian) -> Option<String> {
+    build_id_note(elf, data, endian).map(|build_id| {
+        build_id
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    })
+}
+
+/// Returns the contents of the `NT_GNU_BUILD_ID` note of the binary.
+fn build_id_note<'d>(elf: &Elf, data: &'d [u8], endian: Endian) -> Option<&'d [u8]> {
+    let notes = section_bytes(elf, ".note.gnu.build-id", data)?;
+
+    let align = |len: usize| (len + 3) & !3;
+    let mut offset = 0;
+    while offset + 12 <= notes.len() {
+        let name_len = read_u32(notes, offset, endian)? as usize;
+        let desc_len = read_u32(notes, offset + 4, endian)? as usize;
+        let note_type = read_u32(notes, offset + 8, endian)?;
+        let name = notes.get((offset + 12)..(offset + 12 + name_len))?;
+        let desc_offset = offset + 12 + align(name_len);
+        let desc = notes.get(desc_offset..(desc_offset + desc_len))?;
+
+        if note_type == NT_GNU_BUILD_ID && name == b"GNU\0" {
+            return Some(desc);
+        }
+        offset = desc_offset + align(desc_len);
+    }
+    None
+}
+
+/// Returns the file name and CRC32 checksum of the debug file from the `.gnu_debuglink`
+/// section of the binary.
+fn debuglink<'d>(elf: &Elf, data: &'d [u8], endian: Endian) -> Option<(&'d str, u32)> {
+    let link = section_bytes(elf, ".gnu_debuglink", data)?;
+    let name_len = link.iter().position(|&b| b == 0)?;
+    let name = std::str::from_utf8(&link[..name_len]).ok()?;
+    // The checksum follows the name and is aligned to 4 bytes.
+    let crc = read_u32(link, (name_len + 4) & !3, endian)?;
+    Some((name, crc))
+}
+
+/// The CRC32 checksum used by `.gnu_debuglink`.
+fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = if c & 1 != 0 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+        }
+        *entry = c;
+    }
+
+    !data.iter().fold(!0u32, |crc, &byte| {
+        table[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn section_bytes<'d>(elf: &Elf, name: &str, data: &'d [u8]) -> Option<&'d [u8]> {
+    let section = elf
+        .section_headers
+        .iter()
+        .find(|section| elf.shdr_strtab.get(section.sh_name).and_then(Result::ok) == Some(name))?;
+    if section.sh_type == section_header::SHT_NOBITS {
+        return None;
+    }
+    let start = section.sh_offset as usize;
+    data.get(start..(start + section.sh_size as usize))
+}
+
+/// Returns the address ranges of the sections that are loaded into memory and their
+/// file offsets, sorted by address. The sections of relocatable objects use their file
+/// offsets as addresses, like their symbols.
+pub fn sections_by_address(elf: &Elf) -> Vec<(std::ops::Range<u64>, usize)> {
+    let relocatable = is_relocatable(elf);
     let mut sections: Vec<(std::ops::Range<u64>, usize)> = elf
         .section_headers
         .iter()
-        .filter(|header| header.sh_addr != 0) // does not appear in the process memory
+        .filter(|header| is_loaded(header))
         .map(|header| {
-            (
-                header.sh_addr..(header.sh_addr + header.sh_size),
-                header.sh_offset as usize,
-            )
+            let addr = if relocatable {
+                header.sh_offset
+            } else {
+                header.sh_addr
+            };
+            (addr..(addr + header.sh_size), header.sh_offset as usize)
         })
         .collect();
     sections.sort_unstable_by(|(lhs, _), (rhs, _)| {
         lhs.start.cmp(&rhs.start).then(lhs.end.cmp(&rhs.end))
     });
+    sections
+}
 
-    let addr_to_offset = |addr| {
-        sections
-            .binary_search_by(|(probe, _)| util::cmp_range_to_idx(probe, addr))
-            .ok()
-            .map(|idx| {
-                let &(ref range, off) = &sections[idx];
-                (addr - range.start) as usize + off
-            })
-    };
-
-    dwarf.load_symbols(symbols, addr_to_offset)?;
-
-    Ok(())
+fn addr_to_offset(sections: &[(std::ops::Range<u64>, usize)], addr: u64) -> Option<usize> {
+    sections
+        .binary_search_by(|(probe, _)| util::cmp_range_to_idx(probe, addr))
+        .ok()
+        .map(|idx| {
+            let &(ref range, off) = &sections[idx];
+            (addr - range.start) as usize + off
+        })
 }
 
 pub fn contains_dwarf(elf: &Elf) -> bool {
@@ -154,3 +574,580 @@ fn section_by_name(elf: &Elf, name: &str, data: &BinaryData) -> anyhow::Result<B
     }
     Ok(data.slice(0..0))
 }
+
+/// Returns the addresses where the instruction set of an ARM binary changes, using its
+/// mapping symbols (`$a`, `$t`, and `$d` for data), or the lowest bit of the addresses of
+/// its functions, which is set for Thumb functions, if there are no mapping symbols.
+pub fn load_arm_code_modes(elf: &Elf) -> Vec<(u64, CodeMode)> {
+    let relocatable = is_relocatable(elf);
+    let mut mapping_modes = Vec::new();
+    let mut function_modes = Vec::new();
+
+    for sym in elf.syms.iter() {
+        if sym.st_shndx == section_header::SHN_UNDEF as usize
+            || sym.st_shndx >= section_header::SHN_LORESERVE as usize
+        {
+            continue;
+        }
+        let value = match elf.section_headers.get(sym.st_shndx) {
+            Some(section) if relocatable => section.sh_offset + sym.st_value,
+            Some(_) => sym.st_value,
+            None => continue,
+        };
+
+        if sym.is_function() {
+            let mode = if value & 1 != 0 {
+                CodeMode::Thumb
+            } else {
+                CodeMode::Arm
+            };
+            function_modes.push((value & !1, mode));
+            continue;
+        }
+
+        // Mapping symbols can have a suffix, e.g. `$d.1`.
+        let mode = match elf.strtab.get(sym.st_name).and_then(Result::ok) {
+            Some(name) if name == "$a" || name.starts_with("$a.") => CodeMode::Arm,
+            Some(name) if name == "$t" || name.starts_with("$t.") => CodeMode::Thumb,
+            Some(name) if name == "$d" || name.starts_with("$d.") => CodeMode::Data,
+            _ => continue,
+        };
+        mapping_modes.push((value, mode));
+    }
+
+    let mut modes = if mapping_modes.is_empty() {
+        function_modes
+    } else {
+        mapping_modes
+    };
+    modes.sort_unstable_by_key(|&(addr, _)| addr);
+    modes.dedup_by_key(|&mut (addr, _)| addr);
+    log::debug!("found {} ARM instruction set changes", modes.len());
+    modes
+}
+
+/// Returns true if the section is loaded into memory and has contents in the file. Sections
+/// can be loaded at address 0, e.g. the vector table of firmware images.
+fn is_loaded(header: &section_header::SectionHeader) -> bool {
+    header.sh_flags & u64::from(section_header::SHF_ALLOC) != 0
+        && header.sh_type != section_header::SHT_NOBITS
+}
+
+/// Returns true if this is a relocatable object (e.g. a `.o` file) that hasn't been linked.
+pub fn is_relocatable(elf: &Elf) -> bool {
+    elf.header.e_type == goblin::elf::header::ET_REL
+}
+
+/// Lists the sections in the section header table, without the null section at its
+/// start.
+pub fn load_section_list(elf: &Elf) -> anyhow::Result<Vec<Section>> {
+    let relocatable = is_relocatable(elf);
+    let mut sections = Vec::new();
+    for header in elf.section_headers.iter() {
+        if header.sh_type == section_header::SHT_NULL {
+            continue;
+        }
+        let name = elf
+            .shdr_strtab
+            .get(header.sh_name)
+            .transpose()
+            .context("failed to retrieve ELF section name")?
+            .unwrap_or("");
+        let addr = if relocatable {
+            header.sh_offset
+        } else {
+            header.sh_addr
+        };
+        let offset = if header.sh_type == section_header::SHT_NOBITS {
+            None
+        } else {
+            Some(header.sh_offset as usize)
+        };
+        sections.push(Section::new(
+            name,
+            addr,
+            header.sh_size,
+            offset,
+            header.sh_flags & u64::from(section_header::SHF_EXECINSTR) != 0,
+        ));
+    }
+    Ok(sections)
+}
+
+/// Finds the address ranges and file offsets of the sections with code.
+pub fn load_code_sections(elf: &Elf) -> Vec<(std::ops::Range<u64>, usize)> {
+    let relocatable = is_relocatable(elf);
+    let mut sections: Vec<(std::ops::Range<u64>, usize)> = elf
+        .section_headers
+        .iter()
+        .filter(|header| header.sh_flags & u64::from(section_header::SHF_EXECINSTR) != 0)
+        .filter(|header| relocatable || is_loaded(header))
+        .map(|header| {
+            let addr = if relocatable {
+                header.sh_offset
+            } else {
+                header.sh_addr
+            };
+            (addr..(addr + header.sh_size), header.sh_offset as usize)
+        })
+        .collect();
+    sections.sort_unstable_by_key(|(range, _)| range.start);
+    sections
+}
+
+/// Finds the read-only data sections that string literals are stored in.
+/// `base` is the file offset of the object if it is in an archive.
+pub fn load_string_sections(
+    elf: &Elf,
+    base: u64,
+    sections: &mut Vec<(std::ops::Range<u64>, usize)>,
+) -> anyhow::Result<()> {
+    let relocatable = is_relocatable(elf);
+    for header in elf.section_headers.iter() {
+        let name = elf
+            .shdr_strtab
+            .get(header.sh_name)
+            .transpose()
+            .context("failed to retrieve ELF section name")?;
+
+        // Also matches sections like `.rodata.str1.1` in relocatable objects.
+        if !name.map(|n| n.starts_with(".rodata")).unwrap_or(false) {
+            continue;
+        }
+
+        let addr = if relocatable {
+            base + header.sh_offset
+        } else if is_loaded(header) {
+            header.sh_addr
+        } else {
+            continue;
+        };
+        sections.push((
+            addr..(addr + header.sh_size),
+            (base + header.sh_offset) as usize,
+        ));
+    }
+    Ok(())
+}
+
+/// Finds the sections with call frame information. The sections of relocatable objects
+/// are skipped because the addresses in them are only filled in by relocations.
+pub fn load_unwind_sections(elf: &Elf) -> anyhow::Result<UnwindSections> {
+    if is_relocatable(elf) {
+        return Ok(UnwindSections::default());
+    }
+
+    let mut sections = UnwindSections {
+        sections: sections_by_address(elf),
+        ..UnwindSections::default()
+    };
+
+    for header in elf.section_headers.iter() {
+        if header.sh_type == section_header::SHT_NOBITS {
+            continue;
+        }
+        let name = elf
+            .shdr_strtab
+            .get(header.sh_name)
+            .transpose()
+            .context("failed to retrieve ELF section name")?;
+        let section = UnwindSection {
+            address: header.sh_addr,
+            offset: header.sh_offset as usize,
+            size: header.sh_size as usize,
+        };
+        match name {
+            Some(".eh_frame") => sections.eh_frame = Some(section),
+            Some(".debug_frame") => sections.debug_frame = Some(section),
+            _ => {}
+        }
+    }
+    Ok(sections)
+}
+
+/// Loads the dynamic relocations of a linked binary, which are applied to the data that
+/// position independent code loads pointers from, such as GOT entries. Relative relocations
+/// have no symbol, so their target is the address in their addend.
+pub fn load_dynamic_relocations(
+    elf: &Elf,
+    relocations: &mut Vec<(u64, Relocation)>,
+) -> anyhow::Result<()> {
+    if is_relocatable(elf) {
+        return Ok(());
+    }
+
+    let relocs = elf
+        .dynrelas
+        .iter()
+        .chain(elf.dynrels.iter())
+        .chain(elf.pltrelocs.iter());
+    for reloc in relocs {
+        let addend = reloc.r_addend.unwrap_or(0);
+        let sym = match elf.dynsyms.get(reloc.r_sym) {
+            Some(sym) if reloc.r_sym != 0 => sym,
+            _ => {
+                relocations.push((
+                    reloc.r_offset,
+                    Relocation::new("", Some(addend as u64), 0, false),
+                ));
+                continue;
+            }
+        };
+        let name = elf
+            .dynstrtab
+            .get(sym.st_name)
+            .transpose()
+            .context("failed to get ELF dynamic relocation symbol name")?;
+        let name = match name {
+            Some(name) if !name.is_empty() => name,
+            _ => continue,
+        };
+        let relocation = if is_thread_local_relocation(elf.header.e_machine, reloc.r_type) {
+            Relocation::new_thread_local(name, addend)
+        } else {
+            Relocation::new(name, None, addend, false)
+        };
+        relocations.push((reloc.r_offset, relocation));
+    }
+    Ok(())
+}
+
+/// Returns true if a dynamic relocation fills in the module ID or offset of a thread-local
+/// variable, or a TLS descriptor.
+fn is_thread_local_relocation(machine: u16, r_type: u32) -> bool {
+    use goblin::elf::{header, reloc};
+
+    match machine {
+        header::EM_X86_64 => matches!(
+            r_type,
+            reloc::R_X86_64_DTPMOD64
+                | reloc::R_X86_64_DTPOFF64
+                | reloc::R_X86_64_TPOFF64
+                | reloc::R_X86_64_TLSDESC
+        ),
+        header::EM_386 => matches!(
+            r_type,
+            reloc::R_386_TLS_TPOFF
+                | reloc::R_386_TLS_DTPMOD32
+                | reloc::R_386_TLS_DTPOFF32
+                | reloc::R_386_TLS_TPOFF32
+                | reloc::R_386_TLS_DESC
+        ),
+        header::EM_AARCH64 => matches!(
+            r_type,
+            reloc::R_AARCH64_TLS_DTPMOD
+                | reloc::R_AARCH64_TLS_DTPREL
+                | reloc::R_AARCH64_TLS_TPREL
+                | reloc::R_AARCH64_TLSDESC
+        ),
+        _ => false,
+    }
+}
+
+/// Finds the layout of the TLS block from the `PT_TLS` program header and the offsets of
+/// the thread-local variables in it from the symbol table.
+pub fn load_thread_locals(elf: &Elf) -> anyhow::Result<ThreadLocals> {
+    use goblin::elf::{program_header::PT_TLS, sym::STT_TLS};
+
+    let segment = match elf.program_headers.iter().find(|ph| ph.p_type == PT_TLS) {
+        Some(segment) => segment,
+        None => return Ok(ThreadLocals::default()),
+    };
+    let block_align = segment.p_align.max(1);
+    let mut thread_locals = ThreadLocals {
+        block_size: segment.p_memsz.div_ceil(block_align) * block_align,
+        block_align,
+        ..ThreadLocals::default()
+    };
+
+    for sym in elf.syms.iter().filter(|sym| sym.st_type() == STT_TLS) {
+        let name = match elf
+            .strtab
+            .get(sym.st_name)
+            .transpose()
+            .context("failed to get ELF symbol name")?
+        {
+            Some(name) if !name.is_empty() => name,
+            _ => continue,
+        };
+        let (name, _) = demangle::demangle(name.into());
+        thread_locals.variables.push((
+            sym.st_value..(sym.st_value + sym.st_size),
+            name.into_owned().into_boxed_str(),
+        ));
+    }
+    thread_locals
+        .variables
+        .sort_unstable_by_key(|(range, _)| range.start);
+    log::trace!(
+        "found {} thread-local variables",
+        thread_locals.variables.len()
+    );
+    Ok(thread_locals)
+}
+
+/// Loads the relocations that are applied to the code of a relocatable object. Addresses in
+/// relocatable objects are file offsets, and `base` is the file offset of the object if it is
+/// in an archive.
+pub fn load_relocations(
+    elf: &Elf,
+    data: &[u8],
+    base: u64,
+    relocations: &mut Vec<(u64, Relocation)>,
+) -> anyhow::Result<()> {
+    use goblin::elf::{header, reloc, sym};
+
+    if !is_relocatable(elf) {
+        return Ok(());
+    }
+
+    for (reloc_section_idx, relocs) in elf.shdr_relocs.iter() {
+        let target_section = match elf
+            .section_headers
+            .get(*reloc_section_idx)
+            .and_then(|header| elf.section_headers.get(header.sh_info as usize))
+        {
+            Some(header) if header.sh_flags & u64::from(section_header::SHF_EXECINSTR) != 0 => {
+                header
+            }
+            _ => continue,
+        };
+
+        for reloc in relocs.iter() {
+            let sym = match elf.syms.get(reloc.r_sym) {
+                Some(sym) => sym,
+                None => continue,
+            };
+            let sym_section = elf.section_headers.get(sym.st_shndx);
+
+            let name = if sym.st_type() == sym::STT_SECTION {
+                sym_section.and_then(|header| elf.shdr_strtab.get(header.sh_name))
+            } else {
+                elf.strtab.get(sym.st_name)
+            }
+            .transpose()
+            .context("failed to get ELF relocation symbol name")?;
+            let name = match name {
+                Some(name) if !name.is_empty() => name,
+                _ => continue,
+            };
+
+            let target_address = if sym.st_shndx == section_header::SHN_UNDEF as usize {
+                None
+            } else {
+                sym_section.map(|header| base + header.sh_offset + sym.st_value)
+            };
+
+            let offset = target_section.sh_offset + reloc.r_offset;
+            let (pc_relative, implicit_addend) = match elf.header.e_machine {
+                header::EM_X86_64 => (
+                    matches!(
+                        reloc.r_type,
+                        reloc::R_X86_64_PC32
+                            | reloc::R_X86_64_PLT32
+                            | reloc::R_X86_64_GOTPCREL
+                            | reloc::R_X86_64_GOTPCRELX
+                            | reloc::R_X86_64_REX_GOTPCRELX
+                    ),
+                    None,
+                ),
+                // i386 uses REL relocations so the addend is stored in the code.
+                header::EM_386 => (
+                    matches!(reloc.r_type, reloc::R_386_PC32 | reloc::R_386_PLT32),
+                    read_u32(data, offset as usize, Endian::Little).map(|a| i64::from(a as i32)),
+                ),
+                _ => (false, None),
+            };
+            let addend = reloc.r_addend.or(implicit_addend).unwrap_or(0);
+
+            relocations.push((
+                base + offset,
+                Relocation::new(name, target_address, addend, pc_relative),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the PLT entries of the ELF object and maps their addresses to the names
+/// of the functions that they jump to (e.g. `malloc@libc`).
+pub fn load_plt_imports(
+    elf: &Elf,
+    data: &[u8],
+    arch: Arch,
+    endian: Endian,
+    imports: &mut HashMap<u64, Box<str>>,
+) -> anyhow::Result<()> {
+    let versions = load_version_libraries(elf, data, endian);
+    let versym_offset = elf
+        .section_headers
+        .iter()
+        .find(|header| header.sh_type == section_header::SHT_GNU_VERSYM)
+        .map(|header| header.sh_offset as usize);
+
+    let reloc_import = |reloc: goblin::elf::reloc::Reloc| -> Option<Box<str>> {
+        let sym = elf.dynsyms.get(reloc.r_sym)?;
+        let name = elf.dynstrtab.get(sym.st_name)?.ok()?;
+        if name.is_empty() {
+            return None;
+        }
+
+        let library = versym_offset
+            .and_then(|offset| read_u16(data, offset + reloc.r_sym * 2, endian))
+            .and_then(|idx| versions.get(&(idx & 0x7fff)));
+        Some(match library {
+            Some(library) => format!("{}@{}", name, library).into(),
+            None => name.into(),
+        })
+    };
+
+    // Maps the address of GOT slots to the name of the symbol that they are relocated to.
+    let mut got_slots = HashMap::new();
+    for reloc in elf
+        .pltrelocs
+        .iter()
+        .chain(elf.dynrelas.iter())
+        .chain(elf.dynrels.iter())
+    {
+        let r_offset = reloc.r_offset;
+        if let Some(import) = reloc_import(reloc) {
+            got_slots.insert(r_offset, import);
+        }
+    }
+
+    let got_plt_addr = elf
+        .section_headers
+        .iter()
+        .find(|header| matches!(elf.shdr_strtab.get(header.sh_name), Some(Ok(".got.plt"))))
+        .map(|header| header.sh_addr);
+
+    for header in elf.section_headers.iter() {
+        let name = elf
+            .shdr_strtab
+            .get(header.sh_name)
+            .transpose()
+            .context("failed to retrieve ELF section name")?;
+
+        if !matches!(name, Some(".plt") | Some(".plt.sec") | Some(".plt.got")) {
+            continue;
+        }
+
+        let start = header.sh_offset as usize;
+        let end = start + header.sh_size as usize;
+        let section_data = if let Some(section_data) = data.get(start..end) {
+            section_data
+        } else {
+            continue;
+        };
+
+        match arch {
+            Arch::X86 | Arch::X86_64 => {
+                let entry_size = if header.sh_entsize != 0 {
+                    header.sh_entsize as usize
+                } else {
+                    16
+                };
+
+                for (idx, entry) in section_data.chunks(entry_size).enumerate() {
+                    let entry_addr = header.sh_addr + (idx * entry_size) as u64;
+                    let slot = stubs::x86_stub_slot(entry, entry_addr, arch, got_plt_addr);
+                    if let Some(import) = slot.and_then(|slot| got_slots.get(&slot)) {
+                        imports.insert(entry_addr, import.clone());
+                    }
+                }
+            }
+
+            Arch::AArch64 => {
+                for (idx, entry) in section_data.chunks(16).enumerate() {
+                    let entry_addr = header.sh_addr + (idx * 16) as u64;
+                    let slot = stubs::aarch64_stub_slot(entry, entry_addr);
+                    if let Some(import) = slot.and_then(|slot| got_slots.get(&slot)) {
+                        imports.insert(entry_addr, import.clone());
+                    }
+                }
+            }
+
+            // FIXME support ARM PLT entries.
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Maps the version indices used by `.gnu.version` to the (short) names of the libraries
+/// that they are required from using `.gnu.version_r`.
+fn load_version_libraries(elf: &Elf, data: &[u8], endian: Endian) -> HashMap<u16, String> {
+    let mut libraries = HashMap::new();
+    let header = if let Some(header) = elf
+        .section_headers
+        .iter()
+        .find(|header| header.sh_type == section_header::SHT_GNU_VERNEED)
+    {
+        header
+    } else {
+        return libraries;
+    };
+
+    // The number of entries is stored in `sh_info` for this section type.
+    let mut offset = header.sh_offset as usize;
+    for _ in 0..header.sh_info {
+        let (aux_count, file, aux_offset, next) = match (
+            read_u16(data, offset + 2, endian),
+            read_u32(data, offset + 4, endian),
+            read_u32(data, offset + 8, endian),
+            read_u32(data, offset + 12, endian),
+        ) {
+            (Some(cnt), Some(file), Some(aux), Some(next)) => (cnt, file, aux, next),
+            _ => break,
+        };
+
+        if let Some(Ok(file)) = elf.dynstrtab.get(file as usize) {
+            // libc.so.6 -> libc
+            let library = file.split(".so").next().unwrap_or(file);
+
+            let mut aux = offset + aux_offset as usize;
+            for _ in 0..aux_count {
+                match (
+                    read_u16(data, aux + 6, endian),
+                    read_u32(data, aux + 12, endian),
+                ) {
+                    (Some(version_index), Some(aux_next)) => {
+                        libraries.insert(version_index & 0x7fff, library.to_string());
+                        if aux_next == 0 {
+                            break;
+                        }
+                        aux += aux_next as usize;
+                    }
+                    _ => break,
+                }
+            }
+        }
+
+        if next == 0 {
+            break;
+        }
+        offset += next as usize;
+    }
+
+    libraries
+}
+
+fn read_u16(data: &[u8], offset: usize, endian: Endian) -> Option<u16> {
+    let mut bytes = [0u8; 2];
+    bytes.copy_from_slice(data.get(offset..(offset + 2))?);
+    Some(match endian {
+        Endian::Big => u16::from_be_bytes(bytes),
+        _ => u16::from_le_bytes(bytes),
+    })
+}
+
+fn read_u32(data: &[u8], offset: usize, endian: Endian) -> Option<u32> {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(data.get(offset..(offset + 4))?);
+    Some(match endian {
+        Endian::Big => u32::from_be_bytes(bytes),
+        _ => u32::from_le_bytes(bytes),
+    })
+}
@@ -0,0 +1,300 @@
+//! Finds the functions of binaries without symbols, such as stripped release builds, so
+//! that they can still be navigated.
+//!
+//! Function starts are found in three ways: the address ranges of the call frame
+//! information (or the function table of PE files), the targets of direct calls, and
+//! instructions that only start functions, such as `endbr64` or `push rbp; mov rbp, rsp`.
+//! Each function is named `sub_<address>` and ends where the next one starts, unless its
+//! call frame information says where it ends.
+
+use super::{Arch, Binary};
+use crate::disasm::symbol::{Symbol, SymbolSource};
+use crate::disasm::{unwind, Flow, Jump, Options};
+use std::ops::Range;
+
+/// The instruction sequences that start functions on x86_64: `endbr64` and
+/// `push rbp; mov rbp, rsp` (in both encodings of `mov`).
+const X86_64_PROLOGUES: &[&[u8]] = &[
+    &[0xf3, 0x0f, 0x1e, 0xfa],
+    &[0x55, 0x48, 0x89, 0xe5],
+    &[0x55, 0x48, 0x8b, 0xec],
+];
+
+/// The instruction sequences that start functions on x86: `endbr32` and
+/// `push ebp; mov ebp, esp` (in both encodings of `mov`).
+const X86_PROLOGUES: &[&[u8]] = &[
+    &[0xf3, 0x0f, 0x1e, 0xfb],
+    &[0x55, 0x89, 0xe5],
+    &[0x55, 0x8b, 0xec],
+];
+
+/// The bytes that end functions or pad the space in between them on x86: `ret`, `int3`,
+/// and `nop`.
+const X86_FUNCTION_ENDS: &[u8] = &[0xc3, 0xcc, 0x90];
+
+/// `paciasp`, which signs the return address at the start of AArch64 functions.
+const ARM64_PACIASP: u32 = 0xd503_233f;
+
+/// `bti c`, the landing pad of AArch64 functions that can be called indirectly.
+const ARM64_BTI_C: u32 = 0xd503_245f;
+
+/// `stp x29, x30, [sp, #-N]!`, which saves the frame pointer and link register, with the
+/// bits of the offset masked out.
+const ARM64_STP_FRAME: (u32, u32) = (0xffc0_7fff, 0xa980_7bfd);
+
+/// Finds the functions in the sections with code of a binary without symbols. Functions
+/// that are imported through a stub, such as a PLT entry, are named after the import.
+pub fn find_functions(binary: &Binary) -> Vec<Symbol> {
+    let find_timer = std::time::Instant::now();
+    let code_sections = binary.code_sections();
+    let in_code = |addr: u64| code_sections.iter().any(|(range, _)| range.contains(&addr));
+
+    let mut unwind_ranges = match unwind::function_ranges(binary) {
+        Ok(ranges) => ranges,
+        Err(err) => {
+            log::warn!("failed to read the unwind information: {:?}", err);
+            Vec::new()
+        }
+    };
+    unwind_ranges.retain(|range| in_code(range.start));
+    unwind_ranges.sort_unstable_by_key(|range| range.start);
+    unwind_ranges.dedup_by_key(|range| range.start);
+
+    let mut call_targets = Vec::new();
+    let mut prologues = Vec::new();
+    for (range, offset) in code_sections {
+        let code = match binary
+            .data()
+            .get(*offset..(*offset + (range.end - range.start) as usize))
+        {
+            Some(code) => code,
+            None => continue,
+        };
+        find_call_targets(binary, range.clone(), *offset, &mut call_targets);
+        find_prologues(binary.arch(), code, range.start, &mut prologues);
+    }
+    log::debug!(
+        "found {} functions with unwind information, {} call targets, and {} prologues",
+        unwind_ranges.len(),
+        call_targets.len(),
+        prologues.len()
+    );
+
+    let starts = merge_starts(
+        &unwind_ranges,
+        call_targets
+            .into_iter()
+            .chain(prologues)
+            .filter(|&addr| in_code(addr)),
+    );
+
+    let mut functions = Vec::with_capacity(starts.len());
+    for (idx, &start) in starts.iter().enumerate() {
+        let (section, offset) = match binary.code_section_at(start) {
+            Some(section) => section,
+            None => continue,
+        };
+        let next = starts
+            .get(idx + 1)
+            .copied()
+            .unwrap_or(section.end)
+            .min(section.end);
+        let end = match unwind_ranges.binary_search_by_key(&start, |range| range.start) {
+            Ok(range_idx) => unwind_ranges[range_idx].end.min(next),
+            Err(_) => next,
+        };
+        let name = match binary.import_at(start) {
+            Some(import) => import.to_string(),
+            None => format!("sub_{:x}", start),
+        };
+        functions.push(Symbol::new_unmangled(
+            name,
+            start,
+            offset + (start - section.start) as usize,
+            (end - start) as usize,
+            SymbolSource::Heuristic,
+        ));
+    }
+    log::debug!(
+        "found {} functions with heuristics in {}",
+        functions.len(),
+        crate::util::DurationDisplay(find_timer.elapsed())
+    );
+    functions
+}
+
+/// Merges the starts of the functions with unwind information, which have to be sorted by
+/// their starts, with the other starts that were found. The starts that are inside of a
+/// function with unwind information are most likely not functions, e.g. calls that only
+/// push the address of the next instruction.
+fn merge_starts(unwind_ranges: &[Range<u64>], starts: impl Iterator<Item = u64>) -> Vec<u64> {
+    let inside_unwind_range = |addr: u64| {
+        let idx = unwind_ranges.partition_point(|range| range.start <= addr);
+        idx > 0 && addr > unwind_ranges[idx - 1].start && addr < unwind_ranges[idx - 1].end
+    };
+    let mut starts = starts
+        .filter(|&addr| !inside_unwind_range(addr))
+        .chain(unwind_ranges.iter().map(|range| range.start))
+        .collect::<Vec<_>>();
+    starts.sort_unstable();
+    starts.dedup();
+    starts
+}
+
+/// Disassembles a section with code from its start and adds the targets of the direct
+/// calls in it.
+fn find_call_targets(binary: &Binary, range: Range<u64>, offset: usize, targets: &mut Vec<u64>) {
+    let symbol = Symbol::new_unmangled(
+        format!("0x{:x}", range.start),
+        range.start,
+        offset,
+        (range.end - range.start) as usize,
+        SymbolSource::Heuristic,
+    );
+    let disassembly = match crate::disasm::disasm(binary, &symbol, Options::default()) {
+        Ok(disassembly) => disassembly,
+        Err(err) => {
+            log::warn!(
+                "failed to disassemble the code at 0x{:x}: {:?}",
+                range.start,
+                err
+            );
+            return;
+        }
+    };
+    let lines = disassembly.lines();
+    for line in lines {
+        if line.flow() != Flow::Call {
+            continue;
+        }
+        match line.jump() {
+            Jump::Internal(idx) => targets.push(lines[idx].address()),
+            Jump::External(addr) => targets.push(addr),
+            Jump::Indirect(_) | Jump::None => {}
+        }
+    }
+}
+
+/// Adds the addresses of the instructions that only start functions. On x86 they also have
+/// to be aligned to 16 bytes or follow the end of another function or padding, because a
+/// byte pattern can also be found in the middle of other instructions.
+fn find_prologues(arch: Arch, code: &[u8], address: u64, starts: &mut Vec<u64>) {
+    match arch {
+        Arch::X86 | Arch::X86_64 => {
+            let prologues = if arch == Arch::X86_64 {
+                X86_64_PROLOGUES
+            } else {
+                X86_PROLOGUES
+            };
+            for idx in 0..code.len() {
+                let addr = address + idx as u64;
+                let follows_end = idx == 0 || X86_FUNCTION_ENDS.contains(&code[idx - 1]);
+                if (addr.is_multiple_of(16) || follows_end)
+                    && prologues
+                        .iter()
+                        .any(|prologue| code[idx..].starts_with(prologue))
+                {
+                    starts.push(addr);
+                }
+            }
+        }
+        Arch::AArch64 => {
+            let is_prologue = |insn: u32| {
+                insn == ARM64_PACIASP
+                    || insn == ARM64_BTI_C
+                    || insn & ARM64_STP_FRAME.0 == ARM64_STP_FRAME.1
+            };
+            let mut previous = None;
+            for (idx, insn) in code.chunks_exact(4).enumerate() {
+                let insn = u32::from_le_bytes([insn[0], insn[1], insn[2], insn[3]]);
+                // Only the first instruction of a prologue starts the function, e.g.
+                // `bti c` and not the `paciasp` after it.
+                if is_prologue(insn) && !previous.is_some_and(is_prologue) {
+                    starts.push(address + (idx * 4) as u64);
+                }
+                previous = Some(insn);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn prologues(arch: Arch, code: &[u8], address: u64) -> Vec<u64> {
+        let mut starts = Vec::new();
+        find_prologues(arch, code, address, &mut starts);
+        starts
+    }
+
+    #[test]
+    fn x86_64_prologues() {
+        let mut code = vec![0xcc; 0x40];
+        // endbr64 at an aligned address.
+        code[0x00..0x04].copy_from_slice(&[0xf3, 0x0f, 0x1e, 0xfa]);
+        // push rbp; mov rbp, rsp after a ret.
+        code[0x10] = 0xc3;
+        code[0x11..0x15].copy_from_slice(&[0x55, 0x48, 0x89, 0xe5]);
+        // push rbp; mov rbp, rsp in the middle of another instruction.
+        code[0x20..0x23].copy_from_slice(&[0x48, 0xb8, 0x01]);
+        code[0x23..0x27].copy_from_slice(&[0x55, 0x48, 0x8b, 0xec]);
+        // push rbp; mov rbp, rsp at an aligned address.
+        code[0x30..0x34].copy_from_slice(&[0x55, 0x48, 0x8b, 0xec]);
+
+        assert_eq!(
+            prologues(Arch::X86_64, &code, 0x1000),
+            vec![0x1000, 0x1011, 0x1030]
+        );
+    }
+
+    #[test]
+    fn x86_prologues() {
+        // push ebp; mov ebp, esp after padding, and the x86_64 prologue isn't one on x86.
+        let code = [0x90, 0x55, 0x89, 0xe5, 0xc3, 0x90, 0x55, 0x48, 0x89, 0xe5];
+        assert_eq!(prologues(Arch::X86, &code, 0x2000), vec![0x2001]);
+    }
+
+    #[test]
+    fn aarch64_prologues() {
+        let insns: &[u32] = &[
+            // bti c; paciasp; stp x29, x30, [sp, #-0x20]!
+            0xd503_245f,
+            0xd503_233f,
+            0xa9be_7bfd,
+            // mov x29, sp; ret
+            0x9100_03fd,
+            0xd65f_03c0,
+            // stp x29, x30, [sp, #-0x10]!
+            0xa9bf_7bfd,
+            // ret
+            0xd65f_03c0,
+            // stp x29, x30, [sp, #0x10] doesn't allocate a frame.
+            0xa901_7bfd,
+        ];
+        let code = insns
+            .iter()
+            .flat_map(|insn| insn.to_le_bytes().to_vec())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            prologues(Arch::AArch64, &code, 0x4000),
+            vec![0x4000, 0x4014]
+        );
+    }
+
+    #[test]
+    fn other_architectures_have_no_prologues() {
+        assert!(prologues(Arch::Mips, &[0x55, 0x48, 0x89, 0xe5], 0).is_empty());
+    }
+
+    #[test]
+    fn starts_inside_unwind_ranges() {
+        let unwind_ranges = [0x1000..0x1040, 0x1080..0x10c0];
+        let starts = merge_starts(
+            &unwind_ranges,
+            vec![0x1000, 0x1010, 0x1040, 0x1060, 0x10bf, 0x10c0].into_iter(),
+        );
+        assert_eq!(starts, vec![0x1000, 0x1040, 0x1060, 0x1080, 0x10c0]);
+    }
+}
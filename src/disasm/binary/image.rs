@@ -0,0 +1,290 @@
+//! Text formats for firmware images that are made up of records with addresses, which are
+//! merged into the segments of memory that they write to.
+
+use anyhow::Context as _;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImageFormat {
+    IntelHex,
+    SRecord,
+}
+
+/// A contiguous range of memory that is written by the records of an image.
+pub struct Segment {
+    pub address: u64,
+    pub data: Vec<u8>,
+}
+
+/// Returns the format of the image if the data looks like Intel HEX or Motorola S-records.
+pub fn detect(data: &[u8]) -> Option<ImageFormat> {
+    let first_line = data.split(|&b| b == b'\n').next()?;
+    let first_line = trim_line(first_line);
+    if first_line.len() < 10 || !first_line[1..].iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    match first_line[0] {
+        b':' => Some(ImageFormat::IntelHex),
+        b'S' => Some(ImageFormat::SRecord),
+        _ => None,
+    }
+}
+
+/// Reads the records of an image and merges them into segments, sorted by address.
+pub fn parse(data: &[u8], format: ImageFormat) -> anyhow::Result<Vec<Segment>> {
+    let mut records = Vec::<(u64, Vec<u8>)>::new();
+    let mut add_record = |address: u64, data: &[u8]| {
+        if !data.is_empty() {
+            records.push((address, data.to_vec()));
+        }
+    };
+
+    match format {
+        ImageFormat::IntelHex => parse_intel_hex(data, &mut add_record)?,
+        ImageFormat::SRecord => parse_srecords(data, &mut add_record)?,
+    }
+
+    // Records that follow each other or overlap are merged into the same segment.
+    let mut ranges = records
+        .iter()
+        .map(|(address, data)| *address..(*address + data.len() as u64))
+        .collect::<Vec<_>>();
+    ranges.sort_unstable_by_key(|range| range.start);
+    let mut segments: Vec<Segment> = Vec::new();
+    for range in ranges {
+        match segments.last_mut() {
+            Some(last) if range.start <= last.address + last.data.len() as u64 => {
+                let end = range.end.max(last.address + last.data.len() as u64);
+                last.data.resize((end - last.address) as usize, 0);
+            }
+            _ => segments.push(Segment {
+                address: range.start,
+                data: vec![0; (range.end - range.start) as usize],
+            }),
+        }
+    }
+
+    // The records are written in the order of the image, so later records overwrite the
+    // bytes of earlier records that overlap them.
+    for (address, data) in records {
+        let idx = segments.partition_point(|segment| segment.address <= address) - 1;
+        let segment = &mut segments[idx];
+        let start = (address - segment.address) as usize;
+        segment.data[start..(start + data.len())].copy_from_slice(&data);
+    }
+    Ok(segments)
+}
+
+fn parse_intel_hex(data: &[u8], add_record: &mut dyn FnMut(u64, &[u8])) -> anyhow::Result<()> {
+    // Set by the extended segment address and extended linear address records.
+    let mut base = 0u64;
+
+    for (idx, line) in lines(data) {
+        let record = line
+            .strip_prefix(b":")
+            .and_then(decode_hex)
+            .with_context(|| format!("invalid Intel HEX record on line {}", idx + 1))?;
+        if record.len() < 5 || record.len() != record[0] as usize + 5 {
+            return Err(anyhow::anyhow!(
+                "invalid Intel HEX record length on line {}",
+                idx + 1
+            ));
+        }
+        if record.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) != 0 {
+            return Err(anyhow::anyhow!(
+                "invalid Intel HEX checksum on line {}",
+                idx + 1
+            ));
+        }
+
+        let offset = u64::from(u16::from_be_bytes([record[1], record[2]]));
+        let payload = &record[4..(record.len() - 1)];
+        match record[3] {
+            0x00 => add_record(base + offset, payload),
+            0x01 => break,
+            0x02 if payload.len() == 2 => {
+                base = u64::from(u16::from_be_bytes([payload[0], payload[1]])) << 4
+            }
+            0x04 if payload.len() == 2 => {
+                base = u64::from(u16::from_be_bytes([payload[0], payload[1]])) << 16
+            }
+            // Start addresses don't write to memory.
+            0x03 | 0x05 => {}
+            ty => {
+                return Err(anyhow::anyhow!(
+                    "unknown Intel HEX record type {:02x} on line {}",
+                    ty,
+                    idx + 1
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_srecords(data: &[u8], add_record: &mut dyn FnMut(u64, &[u8])) -> anyhow::Result<()> {
+    for (idx, line) in lines(data) {
+        let (ty, record) = match line {
+            [b'S', ty, rest @ ..] => (*ty, decode_hex(rest)),
+            _ => (0, None),
+        };
+        let record = record.with_context(|| format!("invalid S-record on line {}", idx + 1))?;
+        if record.is_empty() || record.len() != record[0] as usize + 1 {
+            return Err(anyhow::anyhow!(
+                "invalid S-record length on line {}",
+                idx + 1
+            ));
+        }
+        if record.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) != 0xff {
+            return Err(anyhow::anyhow!(
+                "invalid S-record checksum on line {}",
+                idx + 1
+            ));
+        }
+
+        let address_len = match ty {
+            b'1' => 2,
+            b'2' => 3,
+            b'3' => 4,
+            // Headers, record counts, and start addresses don't write to memory.
+            b'0' | b'5' | b'6' | b'7' | b'8' | b'9' => continue,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "unknown S-record type S{} on line {}",
+                    ty as char,
+                    idx + 1
+                ))
+            }
+        };
+        if record.len() < address_len + 2 {
+            return Err(anyhow::anyhow!(
+                "invalid S-record length on line {}",
+                idx + 1
+            ));
+        }
+
+        let address = record[1..=address_len]
+            .iter()
+            .fold(0u64, |address, &b| (address << 8) | u64::from(b));
+        add_record(address, &record[(address_len + 1)..(record.len() - 1)]);
+    }
+    Ok(())
+}
+
+/// Returns the non-empty lines of the image and their indices.
+fn lines(data: &[u8]) -> impl Iterator<Item = (usize, &[u8])> {
+    data.split(|&b| b == b'\n')
+        .map(trim_line)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+}
+
+fn trim_line(line: &[u8]) -> &[u8] {
+    let end = line
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(0, |idx| idx + 1);
+    &line[..end]
+}
+
+fn decode_hex(hex: &[u8]) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    hex.chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn segments(image: &str, format: ImageFormat) -> Vec<(u64, Vec<u8>)> {
+        parse(image.as_bytes(), format)
+            .unwrap()
+            .into_iter()
+            .map(|segment| (segment.address, segment.data))
+            .collect()
+    }
+
+    #[test]
+    fn detect_formats() {
+        assert_eq!(
+            detect(b":0401000001020304F1\r\n"),
+            Some(ImageFormat::IntelHex)
+        );
+        assert_eq!(detect(b"S10510000102E7\n"), Some(ImageFormat::SRecord));
+        assert_eq!(detect(b"\x7fELF\x02\x01\x01\x00\x00\x00"), None);
+    }
+
+    #[test]
+    fn intel_hex_data_and_end_of_file() {
+        let image = "\
+            :0401000001020304F1\n\
+            :020104000506EE\n\
+            :0102000007F6\n\
+            :00000001FF\n\
+            :0100000009F6\n";
+        assert_eq!(
+            segments(image, ImageFormat::IntelHex),
+            vec![(0x100, vec![1, 2, 3, 4, 5, 6]), (0x200, vec![7])]
+        );
+    }
+
+    #[test]
+    fn intel_hex_extended_addresses() {
+        let image = "\
+            :020000021000EC\r\n\
+            :02001000AABB89\r\n\
+            :020000040800F2\r\n\
+            :01002000CC13\r\n\
+            :0400000508000020CF\r\n\
+            :00000001FF\r\n";
+        assert_eq!(
+            segments(image, ImageFormat::IntelHex),
+            vec![(0x10010, vec![0xaa, 0xbb]), (0x0800_0020, vec![0xcc])]
+        );
+    }
+
+    #[test]
+    fn srecords() {
+        let image = "\
+            S0060000686472BB\n\
+            S10510000102E7\n\
+            S2060200000304F0\n\
+            S307080000000506E5\n\
+            S5030002FA\n\
+            S9031000EC\n";
+        assert_eq!(
+            segments(image, ImageFormat::SRecord),
+            vec![
+                (0x1000, vec![1, 2]),
+                (0x20000, vec![3, 4]),
+                (0x0800_0000, vec![5, 6]),
+            ]
+        );
+    }
+
+    #[test]
+    fn bad_checksums() {
+        assert!(parse(b":0401000001020304F2\n", ImageFormat::IntelHex).is_err());
+        assert!(parse(b"S10510000102E8\n", ImageFormat::SRecord).is_err());
+    }
+
+    #[test]
+    fn overlapping_records() {
+        // A later record at a higher address overwrites the end of an earlier one.
+        let image = ":04010000AABBCCDDED\n:0301020011223394\n";
+        assert_eq!(
+            segments(image, ImageFormat::IntelHex),
+            vec![(0x100, vec![0xaa, 0xbb, 0x11, 0x22, 0x33])]
+        );
+
+        // A later record at a lower address overwrites the start of an earlier one.
+        let image = ":020102001122C8\n:03010000AABBCCCB\n";
+        assert_eq!(
+            segments(image, ImageFormat::IntelHex),
+            vec![(0x100, vec![0xaa, 0xbb, 0xcc, 0x22])]
+        );
+    }
+}
@@ -1,14 +1,20 @@
-use super::{Arch, Binary, BinaryData, Bits, Endian, DWARF_SECTIONS};
+use super::{stubs, Arch, Binary, BinaryData, Bits, Endian, Format, Os, DWARF_SECTIONS};
+use crate::disasm::demangle;
 use crate::disasm::dwarf::DwarfInfo;
 use crate::disasm::symbol::{Symbol, SymbolSource};
+use crate::disasm::tls::ThreadLocals;
+use crate::disasm::unwind::{UnwindSection, UnwindSections};
 use crate::util;
 use anyhow::Context as _;
 use goblin::mach::segment::Section;
 use goblin::mach::{Mach, MachO};
+use std::collections::HashMap;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
 pub fn load_arch_info(binary: &mut Binary, mach: &MachO) -> anyhow::Result<()> {
     log::debug!("object type   = Mach-O");
+    binary.format = Format::MachO;
 
     binary.bits = if mach.is_64 {
         Bits::Bits64
@@ -24,7 +30,10 @@ pub fn load_arch_info(binary: &mut Binary, mach: &MachO) -> anyhow::Result<()> {
 
     log::debug!("object bits   = {}", binary.bits);
     log::debug!("object endian = {}", binary.endian);
+    binary.os = Os::MacOs;
+
     log::debug!("object arch   = {}", binary.arch);
+    log::debug!("object os     = {}", binary.os);
 
     Ok(())
 }
@@ -88,13 +97,209 @@ pub fn load_symbols(
     Ok(())
 }
 
+/// Finds the read-only data sections that string literals are stored in.
+pub fn load_string_sections(sections: &[Section], string_sections: &mut Vec<(Range<u64>, usize)>) {
+    for section in sections {
+        let is_string_section = matches!(
+            (section.segname(), section.name()),
+            (Ok("__TEXT"), Ok("__cstring")) | (Ok("__TEXT"), Ok("__const"))
+        );
+
+        if is_string_section {
+            string_sections.push((
+                section.addr..(section.addr + section.size),
+                section.offset as usize,
+            ));
+        }
+    }
+}
+
+/// Lists the sections of every segment, which are named after both, e.g. `__TEXT,__text`.
+pub fn load_section_list(sections: &[Section]) -> Vec<super::Section> {
+    use goblin::mach::constants::{
+        SECTION_TYPE, S_ATTR_PURE_INSTRUCTIONS, S_ATTR_SOME_INSTRUCTIONS, S_GB_ZEROFILL,
+        S_THREAD_LOCAL_ZEROFILL, S_ZEROFILL,
+    };
+
+    sections
+        .iter()
+        .map(|section| {
+            let name = format!(
+                "{},{}",
+                section.segname().unwrap_or("?"),
+                section.name().unwrap_or("?")
+            );
+            let zerofill = matches!(
+                section.flags & SECTION_TYPE,
+                S_ZEROFILL | S_GB_ZEROFILL | S_THREAD_LOCAL_ZEROFILL
+            );
+            let offset = if zerofill {
+                None
+            } else {
+                Some(section.offset as usize)
+            };
+            super::Section::new(
+                &name,
+                section.addr,
+                section.size,
+                offset,
+                section.flags & (S_ATTR_PURE_INSTRUCTIONS | S_ATTR_SOME_INSTRUCTIONS) != 0,
+            )
+        })
+        .collect()
+}
+
+/// Finds the address ranges and file offsets of the sections with code, which have
+/// instructions in them according to their attributes.
+pub fn load_code_sections(sections: &[Section]) -> Vec<(Range<u64>, usize)> {
+    use goblin::mach::constants::{S_ATTR_PURE_INSTRUCTIONS, S_ATTR_SOME_INSTRUCTIONS};
+
+    let mut code_sections: Vec<(Range<u64>, usize)> = sections
+        .iter()
+        .filter(|section| {
+            section.flags & (S_ATTR_PURE_INSTRUCTIONS | S_ATTR_SOME_INSTRUCTIONS) != 0
+        })
+        .map(|section| {
+            (
+                section.addr..(section.addr + section.size),
+                section.offset as usize,
+            )
+        })
+        .collect();
+    code_sections.sort_unstable_by_key(|(range, _)| range.start);
+    code_sections
+}
+
+/// Finds the sections with call frame information. Functions that are described by the
+/// compact unwind information in `__unwind_info` are also in `__eh_frame` if they can't be
+/// described by compact unwind encodings.
+pub fn load_unwind_sections(sections: &[Section]) -> UnwindSections {
+    let mut unwind_sections = UnwindSections {
+        sections: sections_by_address(sections),
+        ..UnwindSections::default()
+    };
+    for section in sections {
+        let unwind_section = UnwindSection {
+            address: section.addr,
+            offset: section.offset as usize,
+            size: section.size as usize,
+        };
+        match (section.segname(), section.name()) {
+            (Ok("__TEXT"), Ok("__eh_frame")) => unwind_sections.eh_frame = Some(unwind_section),
+            (Ok("__DWARF"), Ok("__debug_frame")) => {
+                unwind_sections.debug_frame = Some(unwind_section)
+            }
+            _ => {}
+        }
+    }
+    unwind_sections
+}
+
+/// Finds the `__thread_vars` section, which has the descriptors that code passes to the
+/// functions that return the addresses of thread-local variables.
+pub fn load_thread_locals(sections: &[Section]) -> ThreadLocals {
+    let descriptors = sections
+        .iter()
+        .find(|section| {
+            section.segname().ok() == Some("__DATA") && section.name().ok() == Some("__thread_vars")
+        })
+        .map(|section| section.addr..(section.addr + section.size));
+    ThreadLocals {
+        descriptors,
+        ..ThreadLocals::default()
+    }
+}
+
+/// Maps the addresses of the entries in `__stubs` to the names of the functions that they
+/// jump to. The names of the functions are found by matching the pointer that each stub
+/// jumps through with the pointers bound by dyld.
+pub fn load_stub_imports(
+    mach: &MachO,
+    sections: &[Section],
+    data: &[u8],
+    arch: Arch,
+    imports: &mut HashMap<u64, Box<str>>,
+) -> anyhow::Result<()> {
+    let stub_size = match arch {
+        Arch::X86 | Arch::X86_64 => 6,
+        Arch::AArch64 => 12,
+        // FIXME support ARM stubs.
+        _ => return Ok(()),
+    };
+
+    let stubs_section = if let Some(section) = sections
+        .iter()
+        .find(|section| section.name().ok() == Some("__stubs"))
+    {
+        section
+    } else {
+        return Ok(());
+    };
+
+    let mach_imports = match mach.imports() {
+        Ok(mach_imports) => mach_imports,
+        Err(err) => {
+            log::debug!(
+                "soft error while reading Mach-O dyld binding information: {}",
+                err
+            );
+            return Ok(());
+        }
+    };
+
+    let pointers = mach_imports
+        .into_iter()
+        .map(|import| {
+            // Mach-O symbol names start with an extra underscore.
+            let name = import.name.strip_prefix('_').unwrap_or(import.name);
+            let (name, _) = demangle::demangle(name.into());
+            // /usr/lib/libSystem.B.dylib -> libSystem
+            let library = Path::new(import.dylib)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.split('.').next())
+                .filter(|library| !library.is_empty());
+            let import_name: Box<str> = match library {
+                Some(library) => format!("{}@{}", name, library).into(),
+                None => name.into(),
+            };
+            (import.address, import_name)
+        })
+        .collect::<HashMap<u64, Box<str>>>();
+
+    let start = stubs_section.offset as usize;
+    let end = start + stubs_section.size as usize;
+    let stubs_data = if let Some(stubs_data) = data.get(start..end) {
+        stubs_data
+    } else {
+        return Ok(());
+    };
+
+    for (idx, stub) in stubs_data.chunks(stub_size).enumerate() {
+        let stub_addr = stubs_section.addr + (idx * stub_size) as u64;
+        let slot = match arch {
+            Arch::AArch64 => stubs::aarch64_stub_slot(stub, stub_addr),
+            _ => stubs::x86_stub_slot(stub, stub_addr, arch, None),
+        };
+        if let Some(import) = slot.and_then(|slot| pointers.get(&slot)) {
+            imports.insert(stub_addr, import.clone());
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads the DWARF debug information from the dSYM of the binary, or from the binary
+/// itself if it has no dSYM. The path of the dSYM object is returned with it.
 pub fn load_dwarf(
+    mach: &MachO,
     sections: &[Section],
     endian: Endian,
     data: &BinaryData,
-) -> anyhow::Result<Option<Box<DwarfInfo>>> {
-    if let dwarf @ Some(_) = load_dsym_dwarf(data)? {
-        return Ok(dwarf);
+    dsym_path: Option<&Path>,
+) -> anyhow::Result<Option<(Box<DwarfInfo>, Option<PathBuf>)>> {
+    if let Some((dwarf, object_path)) = load_dsym_dwarf(mach, data, dsym_path)? {
+        return Ok(Some((dwarf, Some(object_path))));
     }
 
     if !contains_dwarf(sections) {
@@ -108,50 +313,44 @@ pub fn load_dwarf(
     };
     let sup_loader =
         |_section: gimli::SectionId| Ok(gimli::EndianReader::new(data.slice(0..0), endian));
-    Ok(Some(Box::new(DwarfInfo::new(loader, sup_loader)?)))
+    Ok(Some((Box::new(DwarfInfo::new(loader, sup_loader)?), None)))
 }
 
-fn load_dsym_dwarf(data: &BinaryData) -> anyhow::Result<Option<Box<DwarfInfo>>> {
-    let dsym_directory = if let Some(d) = find_dsym_directory(data.path()) {
-        d
+fn load_dsym_dwarf(
+    mach: &MachO,
+    data: &BinaryData,
+    dsym_path: Option<&Path>,
+) -> anyhow::Result<Option<(Box<DwarfInfo>, PathBuf)>> {
+    let uuid = object_uuid(mach);
+    let object_path = if let Some(path) = find_dsym_object(data.path(), uuid, dsym_path) {
+        path
     } else {
+        log::trace!("did not find a dSYM for `{}`", data.path().display());
         return Ok(None);
     };
-
-    log::trace!("found dSYM directory: {}", dsym_directory.display());
-    let object_path = {
-        let mut o_path = dsym_directory;
-        o_path.push("Contents");
-        o_path.push("Resources");
-        o_path.push("DWARF");
-        if let Some(fname) = data.path().file_name() {
-            o_path.push(fname);
-        } else {
-            return Ok(None);
-        }
-        o_path
-    };
-
-    if !object_path.is_file() {
-        log::trace!(
-            "did not find dSYM DWARF object file at expected path: {}",
-            object_path.display()
-        );
-        return Ok(None);
-    } else {
-        log::trace!(
-            "located dSYM DWARF object file at {}",
-            object_path.display()
-        );
-    }
+    log::debug!(
+        "located dSYM DWARF object file at {}",
+        object_path.display()
+    );
 
     let data = BinaryData::from_path(&object_path).context("failed to load Mach-O DWARF binary")?;
     let mach = Mach::parse(&data)
         .with_context(|| format!("failed to parse Mach-O binary {}", object_path.display()))?;
     let mach = match mach {
-        goblin::mach::Mach::Fat(multi) => multi
-            .get(0)
-            .context("failed to get first object from fat Mach binary")?,
+        goblin::mach::Mach::Fat(multi) => {
+            // Use the architecture that the executable was built for.
+            let mut objects = multi.into_iter().filter_map(Result::ok);
+            let first = objects
+                .next()
+                .context("failed to get first object from fat Mach binary")?;
+            if object_uuid(&first) == uuid {
+                first
+            } else {
+                objects
+                    .find(|obj| object_uuid(obj) == uuid)
+                    .unwrap_or(first)
+            }
+        }
         goblin::mach::Mach::Binary(obj) => obj,
     };
 
@@ -170,7 +369,7 @@ fn load_dsym_dwarf(data: &BinaryData) -> anyhow::Result<Option<Box<DwarfInfo>>>
         |_section: gimli::SectionId| Ok(gimli::EndianReader::new(data.slice(0..0), endian));
     let dwarf = Box::new(DwarfInfo::new(loader, sup_loader)?);
 
-    Ok(Some(dwarf))
+    Ok(Some((dwarf, object_path)))
 }
 
 pub fn load_dwarf_symbols(
@@ -190,6 +389,23 @@ pub fn load_dwarf_symbols(
     Ok(())
 }
 
+/// Returns the address ranges of the sections and their file offsets, sorted by address.
+pub fn sections_by_address(sections: &[Section]) -> Vec<(Range<u64>, usize)> {
+    let mut sections: Vec<(Range<u64>, usize)> = sections
+        .iter()
+        .map(|section| {
+            (
+                section.addr..(section.addr + section.size),
+                section.offset as usize,
+            )
+        })
+        .collect();
+    sections.sort_unstable_by(|(lhs, _), (rhs, _)| {
+        lhs.start.cmp(&rhs.start).then(lhs.end.cmp(&rhs.end))
+    });
+    sections
+}
+
 pub fn load_sections(mach: &MachO) -> anyhow::Result<Vec<Section>> {
     let mut sections: Vec<Section> = Vec::new();
     for segment in mach.segments.iter() {
@@ -201,22 +417,154 @@ pub fn load_sections(mach: &MachO) -> anyhow::Result<Vec<Section>> {
     Ok(sections)
 }
 
-/// Find the dSYM directory relative to an executable.
-fn find_dsym_directory(executable_path: &Path) -> Option<PathBuf> {
-    let executable_dir = executable_path.parent()?;
-    let entries = executable_dir.read_dir().ok().or_else(|| {
-        log::warn!("failed to open `{}` as directory", executable_dir.display());
-        None
-    })?;
-
-    entries
-        .filter_map(|entry| entry.map(|e| e.path()).ok())
-        .filter(|path| {
-            path.file_name()
-                .filter(|n| n.to_string_lossy().ends_with(".dSYM"))
-                .is_some()
+/// Returns the UUID that links a Mach-O object to its dSYM.
+fn object_uuid(mach: &MachO) -> Option<[u8; 16]> {
+    use goblin::mach::load_command::CommandVariant;
+
+    mach.load_commands
+        .iter()
+        .find_map(|load_command| match load_command.command {
+            CommandVariant::Uuid(ref uuid) => Some(uuid.uuid),
+            _ => None,
+        })
+}
+
+/// Returns the paths of the `LC_RPATH` load commands of an object.
+fn rpaths<'a>(mach: &MachO, bytes: &'a [u8]) -> Vec<&'a str> {
+    use goblin::mach::load_command::CommandVariant;
+
+    mach.load_commands
+        .iter()
+        .filter_map(|load_command| match load_command.command {
+            CommandVariant::Rpath(ref rpath) => {
+                let path = bytes.get(load_command.offset + rpath.path as usize..)?;
+                let len = path.iter().position(|&b| b == 0)?;
+                std::str::from_utf8(&path[..len]).ok()
+            }
+            _ => None,
         })
-        .find(|path| path.is_dir())
+        .collect()
+}
+
+/// Returns the UUIDs of every architecture in a (possibly fat) Mach-O file.
+fn file_uuids(path: &Path) -> Vec<[u8; 16]> {
+    let data = match BinaryData::from_path(path) {
+        Ok(data) => data,
+        Err(_) => return Vec::new(),
+    };
+
+    match Mach::parse(&data) {
+        Ok(Mach::Binary(obj)) => object_uuid(&obj).into_iter().collect(),
+        Ok(Mach::Fat(multi)) => multi
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter_map(|obj| object_uuid(&obj))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Searches for the DWARF object file inside of the dSYM bundle for an executable.
+/// Bundles are checked in this order:
+///
+/// 1. The bundle that was passed in with `--dsym`.
+/// 2. `foo.dSYM` next to the executable `foo`.
+/// 3. `deps/foo-<hash>.dSYM` next to the executable, which is where Cargo puts them.
+/// 4. Any other bundle next to the executable.
+/// 5. Bundles that Spotlight has indexed with the executable's UUID.
+///
+/// If the executable has a UUID, the object in the bundle must have the same one.
+fn find_dsym_object(
+    executable_path: &Path,
+    uuid: Option<[u8; 16]>,
+    dsym_path: Option<&Path>,
+) -> Option<PathBuf> {
+    let file_name = executable_path.file_name()?.to_string_lossy();
+    let executable_dir = executable_path.parent()?;
+
+    let mut bundles: Vec<PathBuf> = Vec::new();
+    bundles.extend(dsym_path.map(Path::to_path_buf));
+    bundles.push(executable_dir.join(format!("{}.dSYM", file_name)));
+
+    let dsyms_in = |dir: &Path, prefix: &str| -> Vec<PathBuf> {
+        let entries = match dir.read_dir() {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+        entries
+            .filter_map(|entry| entry.map(|e| e.path()).ok())
+            .filter(|path| {
+                path.file_name()
+                    .map(|n| n.to_string_lossy())
+                    .filter(|n| n.starts_with(prefix) && n.ends_with(".dSYM"))
+                    .is_some()
+            })
+            .filter(|path| path.is_dir())
+            .collect()
+    };
+    bundles.extend(dsyms_in(
+        &executable_dir.join("deps"),
+        &format!("{}-", file_name.replace('-', "_")),
+    ));
+    bundles.extend(dsyms_in(executable_dir, ""));
+
+    let uuid_matches = |object: &Path| match uuid {
+        Some(uuid) => file_uuids(object).contains(&uuid),
+        None => true,
+    };
+
+    let find_in_bundle = |bundle: &Path| -> Option<PathBuf> {
+        let dwarf_dir = bundle.join("Contents").join("Resources").join("DWARF");
+        log::trace!("searching dSYM bundle {}", bundle.display());
+
+        // The object is named after the file that the bundle was made for, which
+        // isn't always the name of the executable (e.g. Cargo's hashed copies in deps/).
+        let named = dwarf_dir.join(&*file_name);
+        if named.is_file() && uuid_matches(&named) {
+            return Some(named);
+        }
+        dwarf_dir
+            .read_dir()
+            .ok()?
+            .filter_map(|entry| entry.map(|e| e.path()).ok())
+            .find(|path| path.is_file() && uuid_matches(path))
+    };
+
+    if let Some(object) = bundles.iter().find_map(|bundle| find_in_bundle(bundle)) {
+        return Some(object);
+    }
+
+    let uuid = uuid?;
+    spotlight_dsyms(uuid)
+        .iter()
+        .find_map(|bundle| find_in_bundle(bundle))
+}
+
+/// Asks Spotlight for the dSYM bundles that contain the given UUID.
+#[cfg(target_os = "macos")]
+fn spotlight_dsyms(uuid: [u8; 16]) -> Vec<PathBuf> {
+    let uuid = format!(
+        "{:02X}{:02X}{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+        uuid[0], uuid[1], uuid[2], uuid[3], uuid[4], uuid[5], uuid[6], uuid[7],
+        uuid[8], uuid[9], uuid[10], uuid[11], uuid[12], uuid[13], uuid[14], uuid[15],
+    );
+    log::trace!("searching Spotlight for dSYM with UUID {}", uuid);
+
+    let output = std::process::Command::new("mdfind")
+        .arg(format!("com_apple_xcode_dsym_uuids == {}", uuid))
+        .output();
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(PathBuf::from)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn spotlight_dsyms(_uuid: [u8; 16]) -> Vec<PathBuf> {
+    Vec::new()
 }
 
 fn section_by_name(
@@ -269,3 +617,45 @@ const MACH_DWARF_SECTIONS: &[&str] = &[
     "__debug_ranges",
     "__debug_rnglists",
 ];
+
+/// Finds a library that the binary links to, replacing `@executable_path` and
+/// `@loader_path` with the directory of the binary and trying every `LC_RPATH` for
+/// `@rpath`. System libraries are only in the dyld shared cache since macOS 11, so they
+/// can't be found. `bytes` are the bytes of the object, which are only a part of `data` in
+/// a fat binary.
+pub fn find_library(
+    mach: &MachO,
+    bytes: &[u8],
+    data: &BinaryData,
+    name: &str,
+) -> anyhow::Result<PathBuf> {
+    // The first library is always the binary itself.
+    let libraries = mach.libs.iter().skip(1).copied().collect::<Vec<_>>();
+    let library = super::match_library(&libraries, name)?;
+
+    let binary_dir = data.path().parent().unwrap_or_else(|| Path::new(""));
+    let expand = |path: &str| {
+        if let Some(rest) = path
+            .strip_prefix("@executable_path/")
+            .or_else(|| path.strip_prefix("@loader_path/"))
+        {
+            binary_dir.join(rest)
+        } else {
+            PathBuf::from(path)
+        }
+    };
+
+    let candidates = match library.strip_prefix("@rpath/") {
+        Some(rest) => rpaths(mach, bytes)
+            .into_iter()
+            .map(|rpath| expand(rpath).join(rest))
+            .collect::<Vec<_>>(),
+        None => vec![expand(library)],
+    };
+
+    log::debug!("searching for `{}` in {:?}", library, candidates);
+    candidates
+        .into_iter()
+        .find(|path| path.is_file())
+        .with_context(|| format!("`{}` was not found", library))
+}
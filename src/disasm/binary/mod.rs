@@ -1,20 +1,37 @@
+mod cache;
 mod elf;
+mod heuristics;
+mod image;
 mod mach;
 mod pe;
+mod raw;
+mod stubs;
+mod wasm;
+
+pub use self::wasm::WasmFunction;
 
 use super::dwarf::DwarfInfo;
 use super::pdb::PDBInfo;
-use super::strmatch::{distance, Tokenizer};
+use super::source::InlinedCall;
+use super::strmatch::{distance, Tokenizer, TrigramMatcher};
 use super::symbol::{Symbol, SymbolSource};
+use super::symbol_table::{unique_symbols, SymbolIndex, SymbolTable};
+use super::tls::ThreadLocals;
+use super::unwind::UnwindSections;
+use super::vars::VariableLocation;
+use super::{Disassembly, Options};
 use crate::util;
 use anyhow::Context as _;
 
 use goblin::{archive::Archive, elf::Elf, mach::MachO, pe::PE, Object};
 use memmap::{Mmap, MmapOptions};
+use regex::Regex;
+use std::collections::HashMap;
 use std::convert::TryFrom as _;
 use std::fmt;
 use std::fs::File;
 use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -23,6 +40,9 @@ use std::sync::Arc;
 /// and symsrc is `auto`, the binary may keep loading more sources.
 const AUTO_SOURCES_THRESHOLD: usize = 128 * 1024;
 
+/// The source files and lines of an address.
+type SourceLines<'a> = Box<dyn 'a + Iterator<Item = (&'a Path, u32)>>;
+
 pub struct Binary {
     /// Shared binary data. This must be pinned because it is referred to
     data: BinaryData,
@@ -33,63 +53,571 @@ pub struct Binary {
     /// PDB debugging information that was found.
     pdb: Option<Box<PDBInfo>>,
 
+    /// The container format of the binary.
+    format: Format,
+
     arch: Arch,
     endian: Endian,
     bits: Bits,
 
+    /// The operating system that the binary was built for, which is guessed from its
+    /// object format.
+    os: Os,
+
+    /// The sections of the binary in the order of the section table.
+    sections: Vec<Section>,
+
+    /// The shared libraries that the binary depends on, as they are named in the binary.
+    libraries: Vec<Box<str>>,
+
+    /// The file that the debug information was loaded from if it isn't in the binary, e.g.
+    /// a separate debug file, a dSYM, or a PDB.
+    debug_file: Option<PathBuf>,
+
     /// A vector of symbols that are sorted by their address in ascending order.
     symbols: Vec<Symbol>,
+
+    /// Finds the highest priority symbol at an address or file offset (regardless of which
+    /// source it was loaded from).
+    symbol_index: SymbolIndex,
+
+    /// Maps the addresses that imported functions are reached through to their names. These
+    /// are either the addresses of stubs (e.g. PLT entries or Mach-O stubs) or the addresses
+    /// of the pointers that are called through (e.g. Import Address Table entries).
+    imports: HashMap<u64, Box<str>>,
+
+    /// The address ranges and file offsets of read-only data sections that may contain
+    /// string literals. Sorted by address in ascending order.
+    string_sections: Vec<(Range<u64>, usize)>,
+
+    /// The address ranges and file offsets of the sections with code, sorted by address in
+    /// ascending order.
+    code_sections: Vec<(Range<u64>, usize)>,
+
+    /// The sections with the unwind information of functions.
+    unwind_sections: UnwindSections,
+
+    /// The thread-local variables and the layout of the TLS block.
+    thread_locals: ThreadLocals,
+
+    /// The function index space if this is a WebAssembly module.
+    wasm_functions: wasm::WasmFunctions,
+
+    /// The relocations in the code of relocatable objects, sorted by address in
+    /// ascending order.
+    relocations: Vec<(u64, Relocation)>,
+
+    /// The dynamic relocations of the data of linked binaries, e.g. of GOT entries, sorted
+    /// by address in ascending order.
+    data_relocations: Vec<(u64, Relocation)>,
+
+    /// The addresses where the instruction set changes in ARM binaries, sorted by address
+    /// in ascending order.
+    code_modes: Vec<(u64, CodeMode)>,
+
+    /// True if a RISC-V binary uses the C extension, which mixes 2 byte compressed
+    /// instructions in with 4 byte instructions.
+    compressed_instructions: bool,
+
+    /// The file offset ranges and names of the object files that symbols were loaded from
+    /// if this is an archive. Sorted by offset in ascending order.
+    archive_members: Vec<(Range<usize>, Box<str>)>,
+
+    /// The GNU build ID of an ELF binary as a hex string.
+    build_id: Option<Box<str>>,
+
+    /// The cache of the symbols and line information that were read from the binary's
+    /// debug information, if caching is enabled.
+    cache: Option<cache::Cache>,
+
+    /// The address ranges of the sections and their file offsets, sorted by address.
+    /// Used to find the offsets of DWARF symbols that are loaded on demand.
+    dwarf_sections: Vec<(Range<u64>, usize)>,
+
+    /// True if DWARF symbols are only loaded on demand by `load_dwarf_symbols_at`.
+    dwarf_symbols_deferred: bool,
+
+    /// Keep local labels and mapping symbols, see `SearchOptions::include_locals`.
+    include_locals: bool,
+
+    /// Remove compiler generated functions, see
+    /// `SearchOptions::exclude_compiler_generated`.
+    exclude_compiler_generated: bool,
+
+    /// Name symbols by their mangled names, see `SearchOptions::mangled_names`.
+    mangled_names: bool,
+
+    /// Keep the hashes in Rust names, see `SearchOptions::rust_hashes`.
+    rust_hashes: bool,
 }
 
 impl Binary {
     pub fn new(data: BinaryData, options: SearchOptions) -> anyhow::Result<Binary> {
-        let mut binary = Binary {
+        let cache = if options.cache_symbols {
+            cache::Cache::new(data.path(), options.dsym_path)
+        } else {
+            None
+        };
+
+        let mut binary = Binary::empty(data, cache);
+        binary.include_locals = options.include_locals;
+        binary.exclude_compiler_generated = options.exclude_compiler_generated;
+        binary.mangled_names = options.mangled_names;
+        binary.rust_hashes = options.rust_hashes;
+        binary.parse_object(options)?;
+        binary.sort_symbols();
+
+        // Deferring DWARF symbols only works if there are other symbols to search.
+        if binary.symbols.is_empty() {
+            binary.load_deferred_dwarf_symbols()?;
+        }
+
+        binary
+            .string_sections
+            .sort_unstable_by_key(|(range, _)| range.start);
+        binary.relocations.sort_unstable_by_key(|&(addr, _)| addr);
+        binary
+            .data_relocations
+            .sort_unstable_by_key(|&(addr, _)| addr);
+
+        // Stripped binaries can still be navigated with the functions that are found by
+        // heuristics.
+        if !binary.symbols.iter().any(Symbol::is_function) && !binary.code_sections.is_empty() {
+            log::info!("no functions were found in the symbols, searching for them instead");
+            let functions = heuristics::find_functions(&binary);
+            binary.symbols.extend(functions);
+            binary.sort_symbols();
+        }
+
+        Ok(binary)
+    }
+
+    /// Loads the binary at `path` and the symbols from all of the sources that are found in
+    /// it or next to it, such as DWARF debug information in a dSYM directory or a PDB file.
+    pub fn open<P: AsRef<Path>>(path: P) -> anyhow::Result<Binary> {
+        let path = path.as_ref();
+        let data = BinaryData::from_path(path)
+            .with_context(|| format!("failed to load binary `{}`", path.display()))?;
+        Binary::new(
+            data,
+            SearchOptions {
+                sources: &[],
+                dwarf_path: None,
+                dsym_path: None,
+                pdb_path: None,
+                archive_member: None,
+                cache_symbols: false,
+                lazy_dwarf: false,
+                include_locals: false,
+                exclude_compiler_generated: false,
+                mangled_names: false,
+                rust_hashes: false,
+            },
+        )
+    }
+
+    /// Loads a binary that only contains code and data without a container format, such as
+    /// a flat firmware image, which is loaded at the `base` address. Intel HEX and S-record
+    /// images are loaded at the addresses of their records instead. Each contiguous part of
+    /// the binary is a single function symbol because there is nothing else to find symbols
+    /// in.
+    pub fn new_raw(data: BinaryData, arch: Arch, base: u64) -> anyhow::Result<Binary> {
+        let (data, segments) = raw::load_image(data, base)?;
+        let mut binary = Binary::empty(data, None);
+        raw::load_arch_info(&mut binary, arch);
+        raw::load_symbols(&mut binary, &segments);
+        binary.sort_symbols();
+        Ok(binary)
+    }
+
+    fn empty(data: BinaryData, cache: Option<cache::Cache>) -> Binary {
+        Binary {
             data,
             dwarf: None,
             pdb: None,
 
+            format: Format::Unknown,
             arch: Arch::Unknown,
             endian: Endian::Unknown,
             bits: Bits::Unknown,
+            os: Os::Unknown,
 
+            sections: Vec::new(),
+            libraries: Vec::new(),
+            debug_file: None,
             symbols: Vec::new(),
-        };
+            symbol_index: SymbolIndex::default(),
+            imports: HashMap::new(),
+            string_sections: Vec::new(),
+            code_sections: Vec::new(),
+            unwind_sections: UnwindSections::default(),
+            thread_locals: ThreadLocals::default(),
+            wasm_functions: wasm::WasmFunctions::default(),
+            relocations: Vec::new(),
+            data_relocations: Vec::new(),
+            code_modes: Vec::new(),
+            compressed_instructions: false,
+            archive_members: Vec::new(),
+            build_id: None,
+            cache,
+            dwarf_sections: Vec::new(),
+            dwarf_symbols_deferred: false,
+            include_locals: false,
+            exclude_compiler_generated: false,
+            mangled_names: false,
+            rust_hashes: false,
+        }
+    }
 
-        binary.parse_object(options).map(|_| {
-            let symbol_sort_timer = std::time::Instant::now();
-            binary.symbols.sort_unstable_by(|lhs, rhs| {
-                lhs.address()
-                    .cmp(&rhs.address())
-                    .then(lhs.end_address().cmp(&rhs.end_address()))
+    fn sort_symbols(&mut self) {
+        let include_locals = self.include_locals;
+        let exclude_compiler_generated = self.exclude_compiler_generated;
+        if !include_locals || exclude_compiler_generated {
+            let symbols_count_before = self.symbols.len();
+            self.symbols.retain(|sym| {
+                (include_locals || !sym.is_local_label())
+                    && !(exclude_compiler_generated && sym.is_compiler_generated())
             });
+            if self.symbols.len() < symbols_count_before {
+                log::trace!(
+                    "removed {} local labels and compiler generated symbols",
+                    symbols_count_before - self.symbols.len()
+                );
+            }
+        }
+
+        // Other binaries can have an `@` in their names too, e.g. the versions of ELF
+        // symbols, so only the names of 32-bit Windows binaries are undecorated.
+        if self.format == Format::Pe && self.bits == Bits::Bits32 {
+            self.symbols
+                .iter_mut()
+                .for_each(Symbol::strip_c_decorations);
+        }
+
+        if self.mangled_names {
+            self.symbols.iter_mut().for_each(Symbol::use_mangled_name);
+        } else if self.rust_hashes {
+            self.symbols
+                .iter_mut()
+                .for_each(Symbol::use_name_with_hashes);
+        }
+
+        let symbol_sort_timer = std::time::Instant::now();
+        self.symbols.sort_unstable_by(|lhs, rhs| {
+            lhs.address()
+                .cmp(&rhs.address())
+                .then(lhs.end_address().cmp(&rhs.end_address()))
+        });
+        log::trace!(
+            "sorted {} symbols in {}",
+            self.symbols.len(),
+            util::DurationDisplay(symbol_sort_timer.elapsed())
+        );
+
+        self.symbol_index = SymbolIndex::new(&self.symbols);
+    }
+
+    /// Loads the DWARF symbols of the compilation unit that contains the given address if
+    /// DWARF symbols were deferred by `SearchOptions::lazy_dwarf`. References to symbols
+    /// must be looked up again afterwards because the new symbols are sorted in.
+    pub fn load_dwarf_symbols_at(&mut self, addr: u64) -> anyhow::Result<()> {
+        if !self.dwarf_symbols_deferred {
+            return Ok(());
+        }
+        let dwarf = match self.dwarf {
+            Some(ref mut dwarf) => dwarf,
+            None => return Ok(()),
+        };
+
+        let load_symbols_timer = std::time::Instant::now();
+        let symbols_count_before = self.symbols.len();
+        let sections = &self.dwarf_sections;
+        dwarf
+            .load_symbols_at(addr, &mut self.symbols, |addr| {
+                section_offset(sections, addr)
+            })
+            .context("error while gathering DWARF symbols")?;
+
+        if self.symbols.len() > symbols_count_before {
             log::trace!(
-                "sorted {} symbols in {}",
-                binary.symbols.len(),
-                util::DurationDisplay(symbol_sort_timer.elapsed())
+                "found {} symbols in DWARF compilation unit at 0x{:x} in {}",
+                self.symbols.len() - symbols_count_before,
+                addr,
+                util::DurationDisplay(load_symbols_timer.elapsed())
             );
+            self.sort_symbols();
+        }
+        Ok(())
+    }
 
-            binary
-        })
+    /// Only indexes the address ranges of the DWARF compilation units instead of loading
+    /// all of their symbols, unless the symbols are already cached.
+    fn defer_dwarf_symbols(&mut self, sections: Vec<(Range<u64>, usize)>) -> anyhow::Result<()> {
+        if self.dwarf.is_none() {
+            return Ok(());
+        }
+
+        let cache = self.cache.as_ref();
+        if let Some(symbols) = cache.and_then(|cache| cache.load::<Vec<Symbol>>("dwarf-symbols")) {
+            self.symbols.extend(symbols);
+            return Ok(());
+        }
+
+        log::info!("deferring DWARF symbols until they are needed");
+        self.dwarf_sections = sections;
+        self.dwarf_symbols_deferred = true;
+        self.load_compilation_unit_index()
+    }
+
+    /// Loads all of the DWARF symbols if they were deferred by `SearchOptions::lazy_dwarf`,
+    /// e.g. because a symbol that is only named by DWARF can't be found without them.
+    /// References to symbols must be looked up again afterwards.
+    pub fn load_deferred_dwarf_symbols(&mut self) -> anyhow::Result<()> {
+        if !self.dwarf_symbols_deferred {
+            return Ok(());
+        }
+
+        log::info!("retrieving symbols from DWARF debug information");
+        let load_symbols_timer = std::time::Instant::now();
+        let symbols_count_before = self.symbols.len();
+
+        self.dwarf_symbols_deferred = false;
+        let sections = std::mem::take(&mut self.dwarf_sections);
+        let dwarf = self
+            .dwarf
+            .take()
+            .expect("DWARF symbols should only be deferred if there is DWARF");
+        let result = self.load_cached_symbols("dwarf-symbols", |symbols| {
+            dwarf
+                .load_symbols(symbols, |addr| section_offset(&sections, addr))
+                .context("error while gather DWARF symbols")
+        });
+        self.dwarf = Some(dwarf);
+        result?;
+
+        log::trace!(
+            "found {} symbols in DWARF debug information in {}",
+            self.symbols.len() - symbols_count_before,
+            util::DurationDisplay(load_symbols_timer.elapsed())
+        );
+        self.sort_symbols();
+        Ok(())
     }
 
     /// Returns a symbol (and offset) for an address.
     pub fn symbolicate(&self, addr: u64) -> Option<(&Symbol, u64)> {
-        let mut idx = self
-            .symbols
-            .binary_search_by(|probe| util::cmp_range_to_idx(&probe.address_range(), addr))
-            .ok()?;
+        // Signed pointers, e.g. return addresses in backtraces, have a pointer
+        // authentication code in their upper bits.
+        let addr = if self.arch == Arch::AArch64 {
+            strip_pointer_authentication(addr)
+        } else {
+            addr
+        };
+
+        self.symbol_table().at_address(addr)
+    }
+
+    /// Returns a symbol (and offset) for a position in the file of the binary.
+    pub fn symbolicate_offset(&self, offset: usize) -> Option<(&Symbol, u64)> {
+        self.symbol_table().at_offset(offset)
+    }
+
+    /// Returns the crate that a symbol belongs to, which is the first segment of the path
+    /// of Rust symbols or otherwise the crate of the DWARF compilation unit that contains
+    /// it. Compilation units are only used after `load_line_information`.
+    pub fn crate_of<'s>(&'s self, symbol: &'s Symbol) -> Option<&'s str> {
+        self.symbol_table().crate_of(symbol)
+    }
+
+    /// Returns the name of the imported function that is reached through the given address.
+    pub fn import_at(&self, addr: u64) -> Option<&str> {
+        self.imports.get(&addr).map(|name| &**name)
+    }
+
+    /// Returns the GNU build ID of the binary as a hex string if it has one.
+    pub fn build_id(&self) -> Option<&str> {
+        self.build_id.as_deref()
+    }
+
+    /// Returns the function with the given index if this is a WebAssembly module.
+    pub fn wasm_function(&self, index: u32) -> Option<WasmFunction<'_>> {
+        self.wasm_functions.get(index)
+    }
 
-        // We might have duplicates of a symbol (e.g. from DWARF and ELF), so we want
-        // to scan backwards until we find the one with the highest priority.
-        while idx > 0 {
-            if self.symbols[idx - 1].address_range().contains(&addr) {
-                idx -= 1;
-            } else {
-                break;
+    /// Splits a symbol into the parts that are in different instruction sets or that are
+    /// data, e.g. literal pools in ARM code. `None` is the default instruction set of the
+    /// binary, and the only one for most architectures.
+    pub fn code_regions(&self, symbol: &Symbol) -> Vec<(Range<u64>, Option<CodeMode>)> {
+        let range = symbol.address_range();
+        let first = self
+            .code_modes
+            .partition_point(|&(addr, _)| addr <= range.start);
+
+        let mut regions = Vec::new();
+        let mut start = range.start;
+        let mut mode = first.checked_sub(1).map(|idx| self.code_modes[idx].1);
+        for &(addr, next_mode) in self.code_modes[first..]
+            .iter()
+            .take_while(|&&(addr, _)| addr < range.end)
+        {
+            if mode == Some(next_mode) {
+                continue;
+            }
+            if addr > start {
+                regions.push((start..addr, mode));
+                start = addr;
             }
+            mode = Some(next_mode);
+        }
+        regions.push((start..range.end, mode));
+        regions
+    }
+
+    /// Returns the address ranges and file offsets of the sections with code.
+    pub fn code_sections(&self) -> &[(Range<u64>, usize)] {
+        &self.code_sections
+    }
+
+    /// Returns all of the sections of the binary, including the ones without code and the
+    /// ones that aren't loaded into memory.
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    /// Returns the names of the shared libraries that the binary depends on.
+    pub fn libraries(&self) -> &[Box<str>] {
+        &self.libraries
+    }
+
+    /// Returns true if DWARF debug information was found in the binary or next to it.
+    pub fn has_dwarf(&self) -> bool {
+        self.dwarf.is_some()
+    }
+
+    /// Returns true if a PDB was found for the binary.
+    pub fn has_pdb(&self) -> bool {
+        self.pdb.is_some()
+    }
+
+    /// Returns the path of the file that the debug information was loaded from if it isn't
+    /// the binary itself.
+    pub fn debug_file(&self) -> Option<&Path> {
+        self.debug_file.as_deref()
+    }
+
+    /// Returns the address that a PE image prefers to be loaded at, which the addresses of
+    /// its symbols include, or 0 for other formats.
+    pub fn image_base(&self) -> u64 {
+        self.unwind_sections.image_base
+    }
+
+    /// Returns the address range and file offset of the code that contains an address. That
+    /// is a section with code, or the symbol that contains the address in binaries without
+    /// sections.
+    pub fn code_section_at(&self, addr: u64) -> Option<(Range<u64>, usize)> {
+        if let Ok(idx) = self
+            .code_sections
+            .binary_search_by(|(probe, _)| util::cmp_range_to_idx(probe, addr))
+        {
+            return Some(self.code_sections[idx].clone());
         }
+        let (symbol, _) = self.symbolicate(addr)?;
+        Some((symbol.address_range(), symbol.offset()))
+    }
 
-        self.symbols.get(idx).map(|sym| (sym, addr - sym.address()))
+    /// Returns the first relocation (and its address) that is applied inside of the
+    /// given address range.
+    pub fn relocation_in(&self, range: Range<u64>) -> Option<(u64, &Relocation)> {
+        let idx = match self
+            .relocations
+            .binary_search_by(|&(addr, _)| addr.cmp(&range.start))
+        {
+            Ok(idx) | Err(idx) => idx,
+        };
+        self.relocations
+            .get(idx)
+            .filter(|&&(addr, _)| addr < range.end)
+            .map(|&(addr, ref reloc)| (addr, reloc))
+    }
+
+    /// Returns the dynamic relocation that is applied at an address, e.g. the relocation
+    /// that fills in a GOT entry.
+    pub fn data_relocation_at(&self, addr: u64) -> Option<&Relocation> {
+        let idx = self
+            .data_relocations
+            .binary_search_by(|&(probe, _)| probe.cmp(&addr))
+            .ok()?;
+        Some(&self.data_relocations[idx].1)
+    }
+
+    /// Returns the name of the archive member that contains the given file offset.
+    pub fn archive_member_at(&self, offset: usize) -> Option<&str> {
+        let idx = self
+            .archive_members
+            .binary_search_by(|(probe, _)| util::cmp_range_to_idx(probe, offset))
+            .ok()?;
+        Some(&self.archive_members[idx].1)
+    }
+
+    /// Returns the bytes at an address if they are inside of a read-only data section or a
+    /// symbol, e.g. to read the entries of jump tables.
+    pub fn read_only_data_at(&self, addr: u64, len: usize) -> Option<&[u8]> {
+        let offset = match self
+            .string_sections
+            .binary_search_by(|(probe, _)| util::cmp_range_to_idx(probe, addr))
+        {
+            Ok(section_idx) => {
+                let &(ref range, offset) = &self.string_sections[section_idx];
+                if addr + len as u64 > range.end {
+                    return None;
+                }
+                offset + (addr - range.start) as usize
+            }
+            Err(_) => {
+                let (symbol, symbol_offset) = self.symbolicate(addr)?;
+                if symbol_offset + len as u64 > symbol.size() as u64 {
+                    return None;
+                }
+                symbol.offset() + symbol_offset as usize
+            }
+        };
+        self.data.get(offset..(offset + len))
+    }
+
+    /// Attempts to read a string literal at the given address if it is inside of a
+    /// read-only data section. Strings that are too short or that contain control
+    /// characters are ignored because they are more likely to be some other kind of data.
+    pub fn string_at(&self, addr: u64) -> Option<&str> {
+        const MIN_STRING_LEN: usize = 4;
+        const MAX_STRING_LEN: usize = 64;
+
+        let section_idx = self
+            .string_sections
+            .binary_search_by(|(probe, _)| util::cmp_range_to_idx(probe, addr))
+            .ok()?;
+        let &(ref range, offset) = &self.string_sections[section_idx];
+
+        let start = offset + (addr - range.start) as usize;
+        let end = offset + (range.end - range.start) as usize;
+        let bytes = self.data.get(start..end.min(start + MAX_STRING_LEN))?;
+
+        // Rust string literals are not NUL terminated so we stop at the first character
+        // that is unlikely to be part of the string instead.
+        let string = match std::str::from_utf8(bytes) {
+            Ok(string) => string,
+            Err(err) => std::str::from_utf8(&bytes[..err.valid_up_to()]).ok()?,
+        };
+        let len = string
+            .char_indices()
+            .find(|&(_, c)| c.is_control() && c != '\n' && c != '\t')
+            .map(|(idx, _)| idx)
+            .unwrap_or_else(|| string.len());
+
+        if string[..len].chars().count() >= MIN_STRING_LEN {
+            Some(&string[..len])
+        } else {
+            None
+        }
     }
 
     // /// Returns an iterator of symbols matching the given `name` string
@@ -111,6 +639,60 @@ impl Binary {
     //     })
     // }
 
+    /// Returns all of the symbols with names matching the given regular expression
+    /// in ascending order of their addresses. Symbols that were found in more than
+    /// one source are only returned once.
+    pub fn find_regex<'s>(&'s self, regex: &Regex) -> Vec<&'s Symbol> {
+        let symbol_search_timer = std::time::Instant::now();
+        let matches = self.symbol_table().find_regex(regex);
+        log::trace!(
+            "matched `{}` in {}",
+            regex,
+            util::DurationDisplay(symbol_search_timer.elapsed())
+        );
+        matches
+    }
+
+    /// Returns every function symbol with a non-zero size in ascending order of their
+    /// addresses. Symbols that were found in more than one source are only returned once.
+    pub fn all_symbols(&self) -> Vec<&Symbol> {
+        self.symbol_table().iter().sized().functions().to_vec()
+    }
+
+    /// Returns every function and data symbol with a non-zero size in ascending order of
+    /// their addresses. Symbols that were found in more than one source are only returned
+    /// once.
+    pub fn symbols(&self) -> Vec<&Symbol> {
+        self.symbol_table().iter().sized().to_vec()
+    }
+
+    /// Returns the table of all of the symbols that were found in the binary, which can be
+    /// queried with filters.
+    pub fn symbol_table(&self) -> SymbolTable<'_> {
+        SymbolTable::new(&self.symbols, &self.symbol_index, self.dwarf.as_deref())
+    }
+
+    /// Disassembles a function symbol of this binary with the default options.
+    pub fn disassemble(&self, symbol: &Symbol) -> anyhow::Result<Disassembly> {
+        self.disassemble_with(symbol, Options::default())
+    }
+
+    /// Disassembles a function symbol of this binary.
+    pub fn disassemble_with(
+        &self,
+        symbol: &Symbol,
+        options: Options,
+    ) -> anyhow::Result<Disassembly> {
+        if !symbol.is_function() {
+            return Err(anyhow::anyhow!(
+                "`{}` is a {} and can't be disassembled",
+                symbol.name(),
+                symbol.symbol_type()
+            ));
+        }
+        super::disasm(self, symbol, options)
+    }
+
     pub fn fuzzy_find_symbol<'s>(&'s self, name: &str) -> Option<&'s Symbol> {
         let tokens = Tokenizer::new(name).collect::<Vec<&str>>();
         let symbol_search_timer = std::time::Instant::now();
@@ -150,10 +732,43 @@ impl Binary {
         symbol
     }
 
+    /// Returns up to `count` symbols with names that are similar to `name`, the most
+    /// similar first. This is used for suggestions when `fuzzy_find_symbol` finds nothing.
+    pub fn suggest_symbols<'s>(&'s self, name: &str, count: usize) -> Vec<&'s Symbol> {
+        let matcher = TrigramMatcher::new(name);
+        let symbol_search_timer = std::time::Instant::now();
+
+        let mut suggestions = unique_symbols(self.symbols.iter())
+            .into_iter()
+            .map(|sym| (matcher.score(sym.name()), sym))
+            .filter(|&(score, _)| score > 0.0)
+            .collect::<Vec<_>>();
+        suggestions.sort_by(|lhs, rhs| {
+            rhs.0
+                .partial_cmp(&lhs.0)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| lhs.1.name().len().cmp(&rhs.1.name().len()))
+                .then_with(|| lhs.1.name().cmp(rhs.1.name()))
+        });
+        suggestions.dedup_by(|lhs, rhs| lhs.1.name() == rhs.1.name());
+        suggestions.truncate(count);
+
+        log::trace!(
+            "found suggestions for `{}` in {}",
+            name,
+            util::DurationDisplay(symbol_search_timer.elapsed())
+        );
+        suggestions.into_iter().map(|(_, sym)| sym).collect()
+    }
+
     pub fn data(&self) -> &[u8] {
         &*self.data
     }
 
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
     pub fn arch(&self) -> Arch {
         self.arch
     }
@@ -162,8 +777,30 @@ impl Binary {
         self.endian
     }
 
+    pub fn bits(&self) -> Bits {
+        self.bits
+    }
+
+    pub fn os(&self) -> Os {
+        self.os
+    }
+
+    /// Returns the sections with the unwind information of functions.
+    pub fn unwind_sections(&self) -> &UnwindSections {
+        &self.unwind_sections
+    }
+
+    /// Returns the thread-local variables and the layout of the TLS block.
+    pub fn thread_locals(&self) -> &ThreadLocals {
+        &self.thread_locals
+    }
+
     fn parse_object(&mut self, options: SearchOptions) -> anyhow::Result<()> {
         let data = self.data.clone();
+        if data.starts_with(wasm::WASM_MAGIC) {
+            return self.parse_wasm_object(options);
+        }
+
         match Object::parse(&data).context("failed to parse object")? {
             Object::Elf(elf) => self.parse_elf_object(&elf, options),
             Object::PE(pe) => self.parse_pe_object(&pe, options),
@@ -176,7 +813,7 @@ impl Binary {
                 ),
                 goblin::mach::Mach::Binary(obj) => self.parse_mach_object(&obj, options),
             },
-            Object::Archive(archive) => self.parse_archive_object(&archive),
+            Object::Archive(archive) => self.parse_archive_object(&archive, options),
             Object::Unknown(magic) => Err(anyhow::anyhow!(
                 "failed to parse object with magic value 0x{:X}",
                 magic
@@ -186,6 +823,9 @@ impl Binary {
 
     fn parse_elf_object(&mut self, elf: &Elf, options: SearchOptions) -> anyhow::Result<()> {
         elf::load_arch_info(self, elf)?;
+        if self.arch == Arch::Arm {
+            self.code_modes = elf::load_arm_code_modes(elf);
+        }
 
         let load_all_symbols_timer = std::time::Instant::now();
         let mut load_elf_symbols = false;
@@ -196,40 +836,122 @@ impl Binary {
             _ => {}
         });
 
-        if elf::contains_dwarf(elf) {
-            let dwarf = elf::load_dwarf(elf, self.endian, &self.data)?;
-            if load_dwarf_symbols {
-                log::info!("retrieving symbols from DWARF debug information");
-                let symbols_count_before = self.symbols.len();
-                let load_symbols_timer = std::time::Instant::now();
+        self.build_id = elf::build_id(elf, &self.data, self.endian).map(String::into_boxed_str);
+        if let Some(ref build_id) = self.build_id {
+            log::debug!("object build ID = {}", build_id);
+        }
 
-                elf::load_dwarf_symbols(&dwarf, elf, &mut self.symbols)
-                    .context("error while gather DWARF symbols")?;
+        // Stripped binaries may have their debug information and symbol table in a
+        // separate debug file.
+        let debug_data = if elf::is_relocatable(elf) || elf::contains_dwarf(elf) {
+            None
+        } else if let Some(path) = options.dwarf_path {
+            Some(BinaryData::from_path(path)?)
+        } else if let Some(path) =
+            elf::find_debug_file(elf, &self.data, self.build_id.as_deref(), self.endian)
+        {
+            log::info!("using separate debug file `{}`", path.display());
+            Some(BinaryData::from_path(&path)?)
+        } else {
+            None
+        };
+        if let Some(ref data) = debug_data {
+            self.set_debug_file(data.path().to_path_buf());
+        }
+        let debug_elf = match debug_data {
+            Some(ref data) => {
+                Some(Elf::parse(data).context("failed to parse separate debug file")?)
+            }
+            None => None,
+        };
+        let (dwarf_elf, dwarf_data) = match (&debug_elf, &debug_data) {
+            (Some(debug_elf), Some(debug_data)) => (debug_elf, debug_data.clone()),
+            _ => (elf, self.data.clone()),
+        };
 
+        let load_object_symbols = load_elf_symbols || options.sources.is_empty();
+        let elf_symbols = self.load_symbols_in_parallel(
+            load_object_symbols,
+            |symbols| {
+                log::info!("retrieving symbols from ELF object");
+                let load_symbols_timer = std::time::Instant::now();
+                elf::load_symbols(elf, symbols).context("error while gathering ELF symbols")?;
+                if let Some(ref debug_elf) = debug_elf {
+                    elf::load_debug_file_symbols(debug_elf, elf, symbols)
+                        .context("error while gathering ELF symbols from separate debug file")?;
+                }
                 log::trace!(
-                    "found {} symbols in DWARF debug information in {}",
-                    self.symbols.len() - symbols_count_before,
+                    "found {} symbols in ELF object in {}",
+                    symbols.len(),
                     util::DurationDisplay(load_symbols_timer.elapsed())
                 );
-            }
-            self.dwarf = Some(dwarf);
-        }
+                Ok(())
+            },
+            |binary| {
+                // FIXME the debug sections of relocatable objects need relocations applied.
+                if elf::is_relocatable(elf) {
+                    log::debug!("ignoring DWARF debug information in relocatable object");
+                    return Ok(());
+                } else if !elf::contains_dwarf(dwarf_elf) {
+                    return Ok(());
+                }
+
+                let dwarf = elf::load_dwarf(dwarf_elf, binary.endian, &dwarf_data)?;
+                if options.lazy_dwarf && load_dwarf_symbols {
+                    binary.dwarf = Some(dwarf);
+                    return binary.defer_dwarf_symbols(elf::sections_by_address(elf));
+                } else if load_dwarf_symbols {
+                    log::info!("retrieving symbols from DWARF debug information");
+                    let symbols_count_before = binary.symbols.len();
+                    let load_symbols_timer = std::time::Instant::now();
+
+                    let data = binary.data.clone();
+                    let endian = binary.endian;
+                    binary.load_cached_symbols("dwarf-symbols", |symbols| {
+                        elf::load_dwarf_symbols(&dwarf, elf, symbols)
+                            .context("error while gather DWARF symbols")?;
+                        elf::load_split_dwarf_symbols(&dwarf, elf, data.path(), endian, symbols)
+                            .context("error while gathering split DWARF symbols")
+                    })?;
+
+                    log::trace!(
+                        "found {} symbols in DWARF debug information in {}",
+                        binary.symbols.len() - symbols_count_before,
+                        util::DurationDisplay(load_symbols_timer.elapsed())
+                    );
+                }
+                binary.dwarf = Some(dwarf);
+                Ok(())
+            },
+        )?;
+
+        self.sections = elf::load_section_list(elf)?;
+        self.libraries = elf
+            .libraries
+            .iter()
+            .map(|&library| library.into())
+            .collect();
+        elf::load_string_sections(elf, 0, &mut self.string_sections)?;
+        self.code_sections = elf::load_code_sections(elf);
+        self.unwind_sections = elf::load_unwind_sections(elf)?;
+        self.thread_locals = elf::load_thread_locals(elf)?;
+
+        let data = self.data.clone();
+        elf::load_relocations(elf, &data, 0, &mut self.relocations)
+            .context("error while loading ELF relocations")?;
+        elf::load_dynamic_relocations(elf, &mut self.data_relocations)
+            .context("error while loading ELF dynamic relocations")?;
+
+        elf::load_plt_imports(elf, &data, self.arch, self.endian, &mut self.imports)
+            .context("error while resolving ELF PLT entries")?;
+        log::trace!("found {} PLT entries", self.imports.len());
 
         // If we're using `auto` for the symbol source and no symbols are found.
         load_elf_symbols |=
             options.sources.is_empty() && self.symbols.len() < AUTO_SOURCES_THRESHOLD;
 
         if load_elf_symbols {
-            log::info!("retrieving symbols from ELF object");
-            let symbols_count_before = self.symbols.len();
-            let load_symbols_timer = std::time::Instant::now();
-            elf::load_symbols(elf, &mut self.symbols)
-                .context("error while gathering ELF symbols")?;
-            log::trace!(
-                "found {} symbols in ELF object in {}",
-                self.symbols.len() - symbols_count_before,
-                util::DurationDisplay(load_symbols_timer.elapsed())
-            );
+            self.symbols.extend(elf_symbols?);
         }
 
         log::debug!(
@@ -255,37 +977,84 @@ impl Binary {
 
         let sections = mach::load_sections(mach)?;
 
-        if let Some(dwarf) = mach::load_dwarf(&sections, self.endian, &self.data)? {
-            if load_dwarf_symbols {
-                log::info!("retrieving symbols from DWARF debug information");
-                let symbols_count_before = self.symbols.len();
+        let load_object_symbols = load_mach_symbols || options.sources.is_empty();
+        let mach_symbols = self.load_symbols_in_parallel(
+            load_object_symbols,
+            |symbols| {
+                log::info!("retrieving symbols from Mach-O object");
                 let load_symbols_timer = std::time::Instant::now();
-                mach::load_dwarf_symbols(&dwarf, &sections, &mut self.symbols)
-                    .context("error while gathering DWARF symbols")?;
+                mach::load_symbols(mach, &sections, symbols)
+                    .context("error while gathering Mach symbols")?;
                 log::trace!(
-                    "found {} symbols in DWARF debug information in {}",
-                    self.symbols.len() - symbols_count_before,
+                    "found {} symbols in Mach-O object in {}",
+                    symbols.len(),
                     util::DurationDisplay(load_symbols_timer.elapsed())
                 );
-            }
-            self.dwarf = Some(dwarf);
-        }
+                Ok(())
+            },
+            |binary| {
+                let dwarf = match mach::load_dwarf(
+                    mach,
+                    &sections,
+                    binary.endian,
+                    &binary.data,
+                    options.dsym_path,
+                )? {
+                    Some((dwarf, dsym_path)) => {
+                        if let Some(dsym_path) = dsym_path {
+                            binary.set_debug_file(dsym_path);
+                        }
+                        dwarf
+                    }
+                    None => return Ok(()),
+                };
+
+                if options.lazy_dwarf && load_dwarf_symbols {
+                    binary.dwarf = Some(dwarf);
+                    return binary.defer_dwarf_symbols(mach::sections_by_address(&sections));
+                } else if load_dwarf_symbols {
+                    log::info!("retrieving symbols from DWARF debug information");
+                    let symbols_count_before = binary.symbols.len();
+                    let load_symbols_timer = std::time::Instant::now();
+                    binary.load_cached_symbols("dwarf-symbols", |symbols| {
+                        mach::load_dwarf_symbols(&dwarf, &sections, symbols)
+                            .context("error while gathering DWARF symbols")
+                    })?;
+                    log::trace!(
+                        "found {} symbols in DWARF debug information in {}",
+                        binary.symbols.len() - symbols_count_before,
+                        util::DurationDisplay(load_symbols_timer.elapsed())
+                    );
+                }
+                binary.dwarf = Some(dwarf);
+                Ok(())
+            },
+        )?;
+
+        self.sections = mach::load_section_list(&sections);
+        // The first library is always the binary itself.
+        self.libraries = mach
+            .libs
+            .iter()
+            .skip(1)
+            .map(|&library| library.into())
+            .collect();
+        mach::load_string_sections(&sections, &mut self.string_sections);
+        self.code_sections = mach::load_code_sections(&sections);
+        self.unwind_sections = mach::load_unwind_sections(&sections);
+        self.thread_locals = mach::load_thread_locals(&sections);
+
+        let data = self.data.clone();
+        mach::load_stub_imports(mach, &sections, &data, self.arch, &mut self.imports)
+            .context("error while resolving Mach-O symbol stubs")?;
+        log::trace!("found {} symbol stubs", self.imports.len());
 
         // If we're using `auto` for the symbol source and no symbols are found.
         load_mach_symbols |=
             options.sources.is_empty() && self.symbols.len() < AUTO_SOURCES_THRESHOLD;
 
         if load_mach_symbols {
-            log::info!("retrieving symbols from Mach-O object");
-            let symbols_count_before = self.symbols.len();
-            let load_symbols_timer = std::time::Instant::now();
-            mach::load_symbols(mach, &sections, &mut self.symbols)
-                .context("error while gathering Mach symbols")?;
-            log::trace!(
-                "found {} symbols in Mach-O object in {}",
-                self.symbols.len() - symbols_count_before,
-                util::DurationDisplay(load_symbols_timer.elapsed())
-            );
+            self.symbols.extend(mach_symbols?);
         }
 
         log::debug!(
@@ -311,67 +1080,211 @@ impl Binary {
             _ => {}
         });
 
-        if let Some(pdb_path) =
-            pe::find_pdb_path(pe, self.data.path()).context("error while searching for PDB")?
-        {
-            log::debug!("found PDB at `{}`", pdb_path.display());
-            let pdb_data =
-                BinaryData::from_path(pdb_path).context("error while loading PDB data")?;
-            let mut pdb = pe::load_pdb(pdb_data)?;
-            if load_pdb_symbols {
-                log::info!("retrieving symbols from PDB debug information");
-                let symbols_count_before = self.symbols.len();
+        self.sections = pe::load_section_list(pe)?;
+        self.libraries = pe.libraries.iter().map(|&library| library.into()).collect();
+        pe::load_iat_imports(pe, &mut self.imports);
+        pe::load_string_sections(pe, &mut self.string_sections)?;
+        self.code_sections = pe::load_code_sections(pe);
+        self.unwind_sections = pe::load_unwind_sections(pe)?;
+        self.thread_locals = pe::load_thread_locals(pe)?;
+        log::trace!("found {} imports", self.imports.len());
+
+        let load_object_symbols = load_pe_symbols || options.sources.is_empty();
+        let data = self.data.clone();
+        let pe_symbols = self.load_symbols_in_parallel(
+            load_object_symbols,
+            |symbols| {
+                log::info!("retrieving symbols from PE/COFF object");
                 let load_symbols_timer = std::time::Instant::now();
-                pe::load_pdb_symbols(pe, &mut pdb, &mut self.symbols)
-                    .context("error while gather PDB symbols")?;
+                pe::load_symbols(pe, &data, symbols).context("error while gathering PE symbols")?;
                 log::trace!(
-                    "found {} symbols in PDB debug information in {}",
-                    self.symbols.len() - symbols_count_before,
+                    "found {} symbols in PE/COFF object in {}",
+                    symbols.len(),
                     util::DurationDisplay(load_symbols_timer.elapsed())
                 );
-            }
-            self.pdb = Some(pdb);
+                Ok(())
+            },
+            |binary| {
+                if let Some(pdb_path) = pe::find_pdb_path(pe, binary.data.path())
+                    .context("error while searching for PDB")?
+                {
+                    log::debug!("found PDB at `{}`", pdb_path.display());
+                    let pdb_data =
+                        BinaryData::from_path(&pdb_path).context("error while loading PDB data")?;
+                    binary.set_debug_file(pdb_path);
+                    let mut pdb = pe::load_pdb(pe, pdb_data)?;
+                    if load_pdb_symbols {
+                        log::info!("retrieving symbols from PDB debug information");
+                        let symbols_count_before = binary.symbols.len();
+                        let load_symbols_timer = std::time::Instant::now();
+                        binary.load_cached_symbols("pdb-symbols", |symbols| {
+                            pe::load_pdb_symbols(&mut pdb, symbols)
+                                .context("error while gather PDB symbols")
+                        })?;
+                        log::trace!(
+                            "found {} symbols in PDB debug information in {}",
+                            binary.symbols.len() - symbols_count_before,
+                            util::DurationDisplay(load_symbols_timer.elapsed())
+                        );
+                    }
+                    binary.pdb = Some(pdb);
+                }
+
+                if pe::contains_dwarf(pe) {
+                    let dwarf = pe::load_dwarf(pe, binary.endian, &binary.data)?;
+                    // If we're using `auto` for the symbol source and no symbols are found.
+                    load_dwarf_symbols |=
+                        options.sources.is_empty() && binary.symbols.len() < AUTO_SOURCES_THRESHOLD;
+
+                    if options.lazy_dwarf && load_dwarf_symbols {
+                        binary.dwarf = Some(dwarf);
+                        return binary.defer_dwarf_symbols(pe::sections_by_address(pe));
+                    } else if load_dwarf_symbols {
+                        let symbols_count_before = binary.symbols.len();
+                        let load_symbols_timer = std::time::Instant::now();
+                        log::info!("retrieving symbols from DWARF debug information");
+
+                        binary.load_cached_symbols("dwarf-symbols", |symbols| {
+                            pe::load_dwarf_symbols(&dwarf, pe, symbols)
+                                .context("error while gather DWARF symbols")
+                        })?;
+
+                        log::trace!(
+                            "found {} symbols in DWARF debug information in {}",
+                            binary.symbols.len() - symbols_count_before,
+                            util::DurationDisplay(load_symbols_timer.elapsed())
+                        );
+                    }
+                    binary.dwarf = Some(dwarf);
+                }
+                Ok(())
+            },
+        )?;
+
+        // If we're using `auto` for the symbol source and no symbols are found.
+        load_pe_symbols |=
+            options.sources.is_empty() && self.symbols.len() < AUTO_SOURCES_THRESHOLD;
+
+        if load_pe_symbols {
+            self.symbols.extend(pe_symbols?);
         }
 
-        if pe::contains_dwarf(pe) {
-            let dwarf = pe::load_dwarf(pe, self.endian, &self.data)?;
-            // If we're using `auto` for the symbol source and no symbols are found.
-            load_dwarf_symbols |=
-                options.sources.is_empty() && self.symbols.len() < AUTO_SOURCES_THRESHOLD;
+        log::debug!(
+            "found {} total symbols in {}",
+            self.symbols.len(),
+            util::DurationDisplay(load_all_symbols_timer.elapsed())
+        );
 
-            if load_dwarf_symbols {
-                let symbols_count_before = self.symbols.len();
-                let load_symbols_timer = std::time::Instant::now();
-                log::info!("retrieving symbols from DWARF debug information");
+        Ok(())
+    }
 
-                pe::load_dwarf_symbols(&dwarf, pe, &mut self.symbols)
-                    .context("error while gather DWARF symbols")?;
+    fn parse_wasm_object(&mut self, options: SearchOptions) -> anyhow::Result<()> {
+        wasm::load_arch_info(self);
 
-                log::trace!(
-                    "found {} symbols in DWARF debug information in {}",
-                    self.symbols.len() - symbols_count_before,
-                    util::DurationDisplay(load_symbols_timer.elapsed())
-                );
+        if !options.sources.is_empty() && !options.sources.contains(&SymbolSource::Wasm) {
+            return Ok(());
+        }
+
+        log::info!("retrieving symbols from WebAssembly module");
+        let load_symbols_timer = std::time::Instant::now();
+        let data = self.data.clone();
+        wasm::load_module(&data, &mut self.wasm_functions, &mut self.symbols)
+            .context("error while parsing WebAssembly module")?;
+        log::debug!(
+            "found {} total symbols in {}",
+            self.symbols.len(),
+            util::DurationDisplay(load_symbols_timer.elapsed())
+        );
+
+        Ok(())
+    }
+
+    fn parse_archive_object(
+        &mut self,
+        archive: &Archive,
+        options: SearchOptions,
+    ) -> anyhow::Result<()> {
+        let members = archive.members();
+        log::debug!("object type   = archive ({} members)", members.len());
+
+        if let Some(name) = options.archive_member {
+            if !members.contains(&name) {
+                return Err(anyhow::anyhow!(
+                    "archive has no member named `{}`, the members are:\n    {}",
+                    name,
+                    members.join("\n    ")
+                ));
             }
-            self.dwarf = Some(dwarf);
         }
 
-        // If we're using `auto` for the symbol source and no symbols are found.
-        load_pe_symbols |=
-            options.sources.is_empty() && self.symbols.len() < AUTO_SOURCES_THRESHOLD;
+        if !options.sources.is_empty() && !options.sources.contains(&SymbolSource::Archive) {
+            return Ok(());
+        }
 
-        if load_pe_symbols {
-            log::info!("retrieving symbols from PE/COFF object");
+        let load_all_symbols_timer = std::time::Instant::now();
+        let data = self.data.clone();
+        for name in members {
+            if options.archive_member.is_some_and(|m| m != name) {
+                continue;
+            }
+
+            let member = archive
+                .get(name)
+                .with_context(|| format!("failed to find archive member `{}`", name))?;
+            let member_data = archive
+                .extract(name, &data)
+                .with_context(|| format!("failed to extract archive member `{}`", name))?;
+            let member_offset = member.offset as usize;
             let symbols_count_before = self.symbols.len();
-            let load_symbols_timer = std::time::Instant::now();
-            pe::load_symbols(pe, &self.data, &mut self.symbols)
-                .context("error while gathering PE symbols")?;
+            let result = match Object::parse(member_data) {
+                Ok(Object::Elf(elf)) => {
+                    if self.arch == Arch::Unknown {
+                        elf::load_arch_info(self, &elf)?;
+                    }
+                    let base = member_offset as u64;
+                    elf::load_string_sections(&elf, base, &mut self.string_sections)
+                        .and_then(|_| {
+                            elf::load_relocations(&elf, member_data, base, &mut self.relocations)
+                        })
+                        .and_then(|_| elf::load_symbols(&elf, &mut self.symbols))
+                }
+                Ok(Object::Mach(goblin::mach::Mach::Binary(mach))) => {
+                    if self.arch == Arch::Unknown {
+                        mach::load_arch_info(self, &mach)?;
+                    }
+                    mach::load_sections(&mach).and_then(|sections| {
+                        mach::load_symbols(&mach, &sections, &mut self.symbols)
+                    })
+                }
+                // rlibs also contain metadata and other files that aren't objects.
+                _ => {
+                    log::debug!("skipping archive member `{}`", name);
+                    continue;
+                }
+            };
+            result.with_context(|| format!("error while gathering symbols from `{}`", name))?;
+
+            // Objects in an archive have not been assigned addresses yet so their file offsets
+            // are used instead, which also keeps symbols from different members apart.
+            for symbol in &mut self.symbols[symbols_count_before..] {
+                let offset = member_offset + symbol.offset();
+                symbol.set_offset(offset);
+                symbol.set_address(offset as u64);
+            }
+
             log::trace!(
-                "found {} symbols in PE/COFF object in {}",
+                "found {} symbols in archive member `{}`",
                 self.symbols.len() - symbols_count_before,
-                util::DurationDisplay(load_symbols_timer.elapsed())
+                name
             );
+            self.archive_members.push((
+                member_offset..member_offset + member_data.len(),
+                name.into(),
+            ));
         }
+        self.archive_members
+            .sort_unstable_by_key(|(range, _)| range.start);
+        self.format = Format::Archive;
 
         log::debug!(
             "found {} total symbols in {}",
@@ -382,40 +1295,247 @@ impl Binary {
         Ok(())
     }
 
-    fn parse_archive_object(&mut self, _archive: &Archive) -> anyhow::Result<()> {
-        Err(anyhow::anyhow!(
-            "archive objects are not currently supported"
-        ))
+    /// Loads the symbols from the debug information with `load_debug_symbols` on this thread
+    /// while the symbols from the object's own symbol table are loaded on another thread
+    /// with `load_object_symbols`. The result of loading the object symbols is returned so
+    /// that the caller can decide whether to keep them once it knows how many debug symbols
+    /// were found.
+    fn load_symbols_in_parallel<O, D>(
+        &mut self,
+        load_object: bool,
+        load_object_symbols: O,
+        load_debug_symbols: D,
+    ) -> anyhow::Result<anyhow::Result<Vec<Symbol>>>
+    where
+        O: Send + FnOnce(&mut Vec<Symbol>) -> anyhow::Result<()>,
+        D: FnOnce(&mut Binary) -> anyhow::Result<()>,
+    {
+        let mut object_symbols = Ok(Vec::new());
+        rayon::in_place_scope(|scope| {
+            if load_object {
+                let object_symbols = &mut object_symbols;
+                scope.spawn(move |_| {
+                    let mut symbols = Vec::new();
+                    *object_symbols = load_object_symbols(&mut symbols).map(|_| symbols);
+                });
+            }
+            load_debug_symbols(self)
+        })?;
+        Ok(object_symbols)
+    }
+
+    /// Sets the separate file that the debug information is read from, which cached
+    /// symbols are only used with.
+    fn set_debug_file(&mut self, path: PathBuf) {
+        if let Some(ref mut cache) = self.cache {
+            if !cache.set_debug_file(&path) {
+                log::debug!("not caching symbols, `{}` can't be read", path.display());
+                self.cache = None;
+            }
+        }
+        self.debug_file = Some(path);
+    }
+
+    /// Loads symbols using `load`, or from the cache if they were cached by an earlier run.
+    fn load_cached_symbols<F>(&mut self, name: &str, load: F) -> anyhow::Result<()>
+    where
+        F: FnOnce(&mut Vec<Symbol>) -> anyhow::Result<()>,
+    {
+        let cache = self.cache.as_ref();
+        if let Some(symbols) = cache.and_then(|cache| cache.load::<Vec<Symbol>>(name)) {
+            self.symbols.extend(symbols);
+            return Ok(());
+        }
+
+        let mut symbols = Vec::new();
+        load(&mut symbols)?;
+        if let Some(cache) = cache {
+            cache.store(name, &symbols);
+        }
+        self.symbols.extend(symbols);
+        Ok(())
     }
 
     pub fn load_line_information(&mut self) -> anyhow::Result<()> {
-        if let Some(ref mut dwarf) = self.dwarf {
-            dwarf.ensure_compilation_units()?;
+        self.load_compilation_unit_index()?;
+
+        if let Some(ref mut pdb) = self.pdb {
+            pdb.ensure_line_information()?;
         }
 
         Ok(())
     }
 
-    pub fn addr2line(
+    /// Loads the address ranges of the DWARF compilation units, or uses the ranges that
+    /// were cached by an earlier run.
+    fn load_compilation_unit_index(&mut self) -> anyhow::Result<()> {
+        if let Some(ref mut dwarf) = self.dwarf {
+            if !dwarf.compilation_units_loaded() {
+                let cache = self.cache.as_ref();
+                match cache.and_then(|cache| cache.load("dwarf-lines")) {
+                    Some(ranges) => dwarf.load_compilation_units_with_ranges(ranges)?,
+                    None => {
+                        dwarf.ensure_compilation_units()?;
+                        if let Some(cache) = cache {
+                            cache.store("dwarf-lines", dwarf.compilation_unit_ranges());
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn addr2line(&self, addr: u64) -> anyhow::Result<Option<SourceLines<'_>>> {
+        if let Some(ref dwarf) = self.dwarf {
+            if let Some(lines) = dwarf.addr2line(addr)? {
+                return Ok(Some(Box::new(lines)));
+            }
+        }
+
+        if let Some(ref pdb) = self.pdb {
+            if let Some(lines) = pdb.addr2line(addr) {
+                return Ok(Some(Box::new(lines)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the locations of the local variables and parameters of a function. This
+    /// information is only available from DWARF debug information, after
+    /// `load_line_information`.
+    pub fn variable_locations(&self, symbol: &Symbol) -> anyhow::Result<Vec<VariableLocation>> {
+        if let Some(ref dwarf) = self.dwarf {
+            dwarf.variable_locations(symbol.address())
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Returns the calls that were inlined starting at the given address, ordered from
+    /// the outermost call to the innermost one. This information is only available
+    /// from DWARF debug information.
+    pub fn inlined_calls_at(
         &self,
         addr: u64,
-    ) -> anyhow::Result<Option<impl '_ + Iterator<Item = (&Path, u32)>>> {
+    ) -> anyhow::Result<Option<impl '_ + Iterator<Item = &InlinedCall>>> {
         if let Some(ref dwarf) = self.dwarf {
-            return dwarf.addr2line(addr);
+            dwarf.inlined_calls_at(addr)
+        } else {
+            Ok(None)
         }
+    }
+}
 
-        Ok(None)
+/// Maps a virtual address to a file offset using the address ranges of the sections
+/// and their file offsets, sorted by address.
+fn section_offset(sections: &[(Range<u64>, usize)], addr: u64) -> Option<usize> {
+    sections
+        .binary_search_by(|(probe, _)| util::cmp_range_to_idx(probe, addr))
+        .ok()
+        .map(|idx| {
+            let &(ref range, off) = &sections[idx];
+            (addr - range.start) as usize + off
+        })
+}
+
+/// Finds the file of a shared library that a binary depends on, the same way that the
+/// dynamic linker would. The name can be the name of the dependency (e.g. `libssl.so.3`)
+/// or leave out its version and extension (e.g. `libssl`).
+pub fn find_library(data: &BinaryData, name: &str) -> anyhow::Result<PathBuf> {
+    match Object::parse(data).context("failed to parse object")? {
+        Object::Elf(elf) => elf::find_library(&elf, data, name),
+        Object::PE(pe) => pe::find_library(&pe, data, name),
+        Object::Mach(goblin::mach::Mach::Fat(multi)) => {
+            let arch = multi
+                .iter_arches()
+                .next()
+                .context("fat Mach binary has no objects")?
+                .context("failed to get first object from fat Mach binary")?;
+            let bytes = data
+                .get(arch.offset as usize..(arch.offset + arch.size) as usize)
+                .context("first object of fat Mach binary is out of bounds")?;
+            let obj = goblin::mach::MachO::parse(bytes, 0)
+                .context("failed to parse first object from fat Mach binary")?;
+            mach::find_library(&obj, bytes, data, name)
+        }
+        Object::Mach(goblin::mach::Mach::Binary(obj)) => mach::find_library(&obj, data, data, name),
+        _ => Err(anyhow::anyhow!(
+            "only ELF, PE, and Mach-O binaries depend on shared libraries"
+        )),
     }
 }
 
+/// Returns the dependency of a binary that matches the name that was passed to
+/// `find_library`.
+fn match_library<'a>(libraries: &[&'a str], name: &str) -> anyhow::Result<&'a str> {
+    let file_name = |library: &'a str| library.rsplit('/').next().unwrap_or(library);
+    let version_prefix = format!("{}.", name);
+
+    libraries
+        .iter()
+        .copied()
+        .find(|&library| library.eq_ignore_ascii_case(name) || file_name(library) == name)
+        .or_else(|| {
+            libraries
+                .iter()
+                .copied()
+                .find(|&library| file_name(library).starts_with(&version_prefix))
+        })
+        .ok_or_else(|| {
+            let mut message = format!("the binary does not depend on a library named `{}`", name);
+            if !libraries.is_empty() {
+                message.push_str("\n\nthe binary depends on:");
+                for library in libraries {
+                    message.push_str("\n    ");
+                    message.push_str(library);
+                }
+            }
+            anyhow::anyhow!(message)
+        })
+}
+
+/// Returns the first directory that contains a library.
+fn find_in_directories(library: &str, directories: &[PathBuf]) -> anyhow::Result<PathBuf> {
+    directories
+        .iter()
+        .map(|dir| dir.join(library))
+        .find(|path| path.is_file())
+        .with_context(|| {
+            format!(
+                "`{}` was not found in the library search paths of the binary",
+                library
+            )
+        })
+}
+
 struct BinaryDataInner {
-    /// The mapped memory for this binary data.
-    mmap: Mmap,
+    /// The contents of the file that this binary data was loaded from.
+    contents: FileContents,
 
     /// The original path that was used to load this binary data.
     path: PathBuf,
 }
 
+/// The contents of a file, which are read into a buffer instead if the file can't be
+/// mapped into memory (e.g. pipes and some special or network file systems).
+enum FileContents {
+    Mapped(Mmap),
+    Buffered(Vec<u8>),
+}
+
+impl std::ops::Deref for FileContents {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            FileContents::Mapped(mmap) => mmap,
+            FileContents::Buffered(buffer) => buffer,
+        }
+    }
+}
+
 /// Reference counted and memory mapped binary data.
 #[derive(Clone)]
 pub struct BinaryData {
@@ -438,15 +1558,39 @@ impl BinaryData {
             .with_context(|| format!("failed to open file at path `{}`", path.display()))?;
         let path = PathBuf::from(path);
 
-        unsafe {
-            MmapOptions::new()
-                .map(&file)
-                .map(|mmap| BinaryData {
-                    range: 0..mmap.len(),
-                    offset: 0,
-                    inner: Arc::new(BinaryDataInner { mmap, path }),
-                })
-                .map_err(|err| err.into())
+        // Mapping the file means that only the pages that are actually used are read.
+        let contents = match unsafe { MmapOptions::new().map(&file) } {
+            Ok(mmap) => FileContents::Mapped(mmap),
+            Err(err) => {
+                log::debug!(
+                    "failed to map `{}` into memory, reading it instead: {}",
+                    path.display(),
+                    err
+                );
+                let mut buffer = Vec::new();
+                io::BufReader::new(file)
+                    .read_to_end(&mut buffer)
+                    .with_context(|| format!("failed to read file at path `{}`", path.display()))?;
+                FileContents::Buffered(buffer)
+            }
+        };
+
+        Ok(BinaryData {
+            range: 0..contents.len(),
+            offset: 0,
+            inner: Arc::new(BinaryDataInner { contents, path }),
+        })
+    }
+
+    /// Creates binary data from bytes that were generated from the file at `path`.
+    fn from_bytes(path: &Path, bytes: Vec<u8>) -> BinaryData {
+        BinaryData {
+            range: 0..bytes.len(),
+            offset: 0,
+            inner: Arc::new(BinaryDataInner {
+                contents: FileContents::Buffered(bytes),
+                path: path.to_path_buf(),
+            }),
         }
     }
 
@@ -488,7 +1632,7 @@ impl BinaryData {
 impl std::fmt::Debug for BinaryData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("BinaryData")
-            .field("len", &self.inner.mmap.len())
+            .field("len", &self.inner.contents.len())
             .finish()
     }
 }
@@ -497,13 +1641,13 @@ impl std::ops::Deref for BinaryData {
     type Target = [u8];
 
     fn deref(&self) -> &Self::Target {
-        &self.inner.mmap[self.range.clone()]
+        &self.inner.contents[self.range.clone()]
     }
 }
 
 impl Read for BinaryData {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        let mut slice: &[u8] = &*self.inner.mmap;
+        let mut slice: &[u8] = &self.inner.contents;
 
         let len = std::cmp::min(buf.len(), slice.len() - self.offset);
         if len == 0 {
@@ -543,11 +1687,23 @@ pub enum Arch {
     X86,
     X86_64,
     Arm,
+    /// ARM processors that only execute Thumb instructions, e.g. Cortex-M.
+    Thumb,
     AArch64,
+    Mips,
+    Mips64,
+    PowerPc,
+    PowerPc64,
+    RiscV32,
+    RiscV64,
+    Sparc,
+    /// SPARC V9.
+    Sparc64,
+    Wasm,
 }
 
 impl Arch {
-    fn from_elf_machine(machine: u16) -> Arch {
+    fn from_elf_machine(machine: u16, bits: Bits) -> Arch {
         use goblin::elf::header;
 
         match machine {
@@ -555,6 +1711,15 @@ impl Arch {
             header::EM_X86_64 => Arch::X86_64,
             header::EM_ARM => Arch::Arm,
             header::EM_AARCH64 => Arch::AArch64,
+            header::EM_MIPS if bits == Bits::Bits64 => Arch::Mips64,
+            header::EM_MIPS => Arch::Mips,
+            header::EM_PPC => Arch::PowerPc,
+            header::EM_PPC64 => Arch::PowerPc64,
+            header::EM_SPARC | header::EM_SPARC32PLUS => Arch::Sparc,
+            header::EM_SPARCV9 => Arch::Sparc64,
+            // RV32 and RV64 share a machine type and are told apart by the ELF class.
+            header::EM_RISCV if bits == Bits::Bits64 => Arch::RiscV64,
+            header::EM_RISCV => Arch::RiscV32,
             _ => Arch::Unknown,
         }
     }
@@ -568,6 +1733,8 @@ impl Arch {
             cputype::CPU_TYPE_ARM64_32 => Arch::AArch64,
             cputype::CPU_TYPE_X86 => Arch::X86,
             cputype::CPU_TYPE_X86_64 => Arch::X86_64,
+            cputype::CPU_TYPE_POWERPC => Arch::PowerPc,
+            cputype::CPU_TYPE_POWERPC64 => Arch::PowerPc64,
             _ => Arch::Unknown,
         }
     }
@@ -579,12 +1746,53 @@ impl Arch {
             header::COFF_MACHINE_X86 => Arch::X86,
             header::COFF_MACHINE_X86_64 => Arch::X86_64,
             header::COFF_MACHINE_ARM => Arch::Arm,
+            // Windows on 32-bit ARM only uses Thumb-2.
+            header::COFF_MACHINE_ARMNT => Arch::Thumb,
             header::COFF_MACHINE_ARM64 => Arch::AArch64,
             _ => Arch::Unknown,
         }
     }
 }
 
+impl std::str::FromStr for Arch {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("x86") || s.eq_ignore_ascii_case("i386") {
+            Ok(Arch::X86)
+        } else if s.eq_ignore_ascii_case("x86_64")
+            || s.eq_ignore_ascii_case("x86-64")
+            || s.eq_ignore_ascii_case("amd64")
+        {
+            Ok(Arch::X86_64)
+        } else if s.eq_ignore_ascii_case("arm") {
+            Ok(Arch::Arm)
+        } else if s.eq_ignore_ascii_case("thumb") {
+            Ok(Arch::Thumb)
+        } else if s.eq_ignore_ascii_case("aarch64") || s.eq_ignore_ascii_case("arm64") {
+            Ok(Arch::AArch64)
+        } else if s.eq_ignore_ascii_case("mips") {
+            Ok(Arch::Mips)
+        } else if s.eq_ignore_ascii_case("mips64") {
+            Ok(Arch::Mips64)
+        } else if s.eq_ignore_ascii_case("powerpc") || s.eq_ignore_ascii_case("ppc") {
+            Ok(Arch::PowerPc)
+        } else if s.eq_ignore_ascii_case("powerpc64") || s.eq_ignore_ascii_case("ppc64") {
+            Ok(Arch::PowerPc64)
+        } else if s.eq_ignore_ascii_case("sparc") {
+            Ok(Arch::Sparc)
+        } else if s.eq_ignore_ascii_case("sparc64") || s.eq_ignore_ascii_case("sparcv9") {
+            Ok(Arch::Sparc64)
+        } else if s.eq_ignore_ascii_case("riscv32") || s.eq_ignore_ascii_case("rv32") {
+            Ok(Arch::RiscV32)
+        } else if s.eq_ignore_ascii_case("riscv64") || s.eq_ignore_ascii_case("rv64") {
+            Ok(Arch::RiscV64)
+        } else {
+            Err("invalid architecture")
+        }
+    }
+}
+
 impl fmt::Display for Arch {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let t = match self {
@@ -592,7 +1800,143 @@ impl fmt::Display for Arch {
             Arch::X86 => "x86",
             Arch::X86_64 => "x86_64",
             Arch::Arm => "arm",
+            Arch::Thumb => "thumb",
             Arch::AArch64 => "arm64",
+            Arch::Mips => "mips",
+            Arch::Mips64 => "mips64",
+            Arch::PowerPc => "powerpc",
+            Arch::PowerPc64 => "powerpc64",
+            Arch::RiscV32 => "riscv32",
+            Arch::RiscV64 => "riscv64",
+            Arch::Sparc => "sparc",
+            Arch::Sparc64 => "sparc64",
+            Arch::Wasm => "wasm",
+        };
+        write!(f, "{}", t)
+    }
+}
+
+/// Clears the pointer authentication code in the upper bits of a signed AArch64 pointer.
+/// User space addresses (bit 55 clear) have their upper bits cleared and kernel addresses
+/// have them set, assuming 48 bit virtual addresses.
+fn strip_pointer_authentication(addr: u64) -> u64 {
+    const ADDRESS_MASK: u64 = (1 << 48) - 1;
+
+    if addr & (1 << 55) == 0 {
+        addr & ADDRESS_MASK
+    } else {
+        addr | !ADDRESS_MASK
+    }
+}
+
+/// The instruction sets of ARM processors, or data that is mixed in with code.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CodeMode {
+    Arm,
+    Thumb,
+    Data,
+}
+
+/// The container format of a binary.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    Unknown,
+    Elf,
+    Pe,
+    MachO,
+    Archive,
+    Wasm,
+    /// Code and data without a container format, see `Binary::new_raw`.
+    Raw,
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let t = match self {
+            Format::Unknown => "unknown",
+            Format::Elf => "ELF",
+            Format::Pe => "PE/COFF",
+            Format::MachO => "Mach-O",
+            Format::Archive => "archive",
+            Format::Wasm => "WebAssembly",
+            Format::Raw => "raw",
+        };
+        write!(f, "{}", t)
+    }
+}
+
+/// A section of a binary.
+#[derive(Clone, Debug)]
+pub struct Section {
+    /// The name of the section. The names of Mach-O sections include their segment, e.g.
+    /// `__TEXT,__text`.
+    name: Box<str>,
+
+    /// The address of the section, or its file offset in relocatable objects.
+    address: u64,
+
+    size: u64,
+
+    /// The file offset of the contents of the section, which is `None` for sections that
+    /// take up no space in the file, such as `.bss`.
+    offset: Option<usize>,
+
+    /// True if the section contains instructions.
+    code: bool,
+}
+
+impl Section {
+    pub fn new(name: &str, address: u64, size: u64, offset: Option<usize>, code: bool) -> Self {
+        Section {
+            name: name.into(),
+            address,
+            size,
+            offset,
+            code,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn address_range(&self) -> Range<u64> {
+        self.address..(self.address + self.size)
+    }
+
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    pub fn is_code(&self) -> bool {
+        self.code
+    }
+}
+
+/// The operating system that a binary was built for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Os {
+    Unknown,
+    Linux,
+    Windows,
+    MacOs,
+}
+
+impl fmt::Display for Os {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let t = match self {
+            Os::Unknown => "unknown",
+            Os::Linux => "linux",
+            Os::Windows => "windows",
+            Os::MacOs => "macos",
         };
         write!(f, "{}", t)
     }
@@ -686,11 +2030,87 @@ const DWARF_SECTIONS: &[&str] = &[
     ".debug_rnglists",
 ];
 
+/// A relocation in the code of an object that hasn't been linked yet.
+pub struct Relocation {
+    /// The name of the symbol (or section) that the relocation refers to.
+    target: Box<str>,
+
+    /// The address of the target if it is defined in the same object.
+    target_address: Option<u64>,
+
+    addend: i64,
+
+    /// x86 PC-relative relocations are relative to the relocated field instead of the end
+    /// of the instruction that contains it.
+    pc_relative: bool,
+
+    /// The relocation fills in the module or offset of a thread-local variable.
+    thread_local: bool,
+}
+
+impl Relocation {
+    pub fn new(target: &str, target_address: Option<u64>, addend: i64, pc_relative: bool) -> Self {
+        Relocation {
+            target: target.into(),
+            target_address,
+            addend,
+            pc_relative,
+            thread_local: false,
+        }
+    }
+
+    /// Creates a dynamic relocation that fills in the module or offset of a thread-local
+    /// variable for the TLS models that don't know the offset when the binary is linked.
+    pub fn new_thread_local(target: &str, addend: i64) -> Self {
+        Relocation {
+            thread_local: true,
+            ..Relocation::new(target, None, addend, false)
+        }
+    }
+
+    /// The name of the symbol (or section) that the relocation refers to, which is empty
+    /// for relative relocations.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// The address of the target if it is defined in the same object or if this is a
+    /// relative relocation.
+    pub fn target_address(&self) -> Option<u64> {
+        self.target_address
+    }
+
+    pub fn addend(&self) -> i64 {
+        self.addend
+    }
+
+    pub fn is_thread_local(&self) -> bool {
+        self.thread_local
+    }
+
+    /// Returns the offset from the target that is referred to by an instruction that
+    /// ends at `insn_end` when the relocation is applied at `addr`.
+    pub fn offset(&self, addr: u64, insn_end: u64) -> i64 {
+        if self.pc_relative {
+            self.addend + (insn_end - addr) as i64
+        } else {
+            self.addend
+        }
+    }
+
+    /// Returns the address that is referred to by an instruction that ends at `insn_end`
+    /// when the relocation is applied at `addr`, if the target is in the same object.
+    pub fn resolve(&self, addr: u64, insn_end: u64) -> Option<u64> {
+        self.target_address
+            .map(|target| (target as i64 + self.offset(addr, insn_end)) as u64)
+    }
+}
+
 pub struct SearchOptions<'a> {
     pub sources: &'a [SymbolSource],
 
-    /// Path to an object file containing DWARF debug information.
-    /// Used for ELF and Mach-O object files.
+    /// Path to a separate debug file with the DWARF debug information and symbols of a
+    /// stripped binary. Used for ELF object files.
     pub dwarf_path: Option<&'a Path>,
 
     /// The path to the dSYM directory.
@@ -699,4 +2119,34 @@ pub struct SearchOptions<'a> {
 
     /// Path to a PDB file used for PE object files.
     pub pdb_path: Option<&'a Path>,
+
+    /// Only load symbols from the archive member with this name.
+    /// Used for static libraries and rlibs.
+    pub archive_member: Option<&'a str>,
+
+    /// Cache the symbols and line information that are read from debug information
+    /// between runs.
+    pub cache_symbols: bool,
+
+    /// Only index the address ranges of the DWARF compilation units instead of loading
+    /// all of their symbols up front. The symbols of a unit are loaded on demand with
+    /// `Binary::load_dwarf_symbols_at`. This makes looking up a single symbol much faster
+    /// in large programs.
+    pub lazy_dwarf: bool,
+
+    /// Keep the local labels and mapping symbols that are emitted inside of functions, such
+    /// as `.LBB0_1`, `ltmp0`, or ARM's `$d` and `$x`, instead of removing them.
+    pub include_locals: bool,
+
+    /// Remove the functions that were generated by the compiler without a source function,
+    /// such as outlined functions and retpoline thunks.
+    pub exclude_compiler_generated: bool,
+
+    /// Name symbols by their mangled names instead of demangling them, which is also how
+    /// they're searched for.
+    pub mangled_names: bool,
+
+    /// Keep the hashes at the end of legacy Rust names (`::h0123456789abcdef`) and the
+    /// crate disambiguators of v0 names, which are left out of demangled names by default.
+    pub rust_hashes: bool,
 }
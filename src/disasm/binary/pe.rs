@@ -1,14 +1,18 @@
-use super::{Arch, Binary, BinaryData, Bits, Endian, DWARF_SECTIONS};
+use super::{Arch, Binary, BinaryData, Bits, Endian, Format, Os, Section, DWARF_SECTIONS};
 use crate::disasm::dwarf::DwarfInfo;
 use crate::disasm::pdb::PDBInfo;
 use crate::disasm::symbol::{Symbol, SymbolSource};
+use crate::disasm::tls::ThreadLocals;
+use crate::disasm::unwind::{UnwindSection, UnwindSections};
 use crate::util;
 use anyhow::Context as _;
 use goblin::pe::PE;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 pub fn load_arch_info(binary: &mut Binary, pe: &PE) -> anyhow::Result<()> {
     log::debug!("object type   = PE/COFF");
+    binary.format = Format::Pe;
 
     binary.bits = if pe.is_64 { Bits::Bits64 } else { Bits::Bits32 };
     binary.endian = Endian::Little;
@@ -16,7 +20,10 @@ pub fn load_arch_info(binary: &mut Binary, pe: &PE) -> anyhow::Result<()> {
 
     log::debug!("object bits   = {}", binary.bits);
     log::debug!("object endian = {}", binary.endian);
+    binary.os = Os::Windows;
+
     log::debug!("object arch   = {}", binary.arch);
+    log::debug!("object os     = {}", binary.os);
 
     Ok(())
 }
@@ -114,16 +121,139 @@ pub fn load_symbols(pe: &PE, data: &BinaryData, symbols: &mut Vec<Symbol>) -> an
     Ok(())
 }
 
-pub fn load_pdb(pdb_data: BinaryData) -> anyhow::Result<Box<PDBInfo>> {
-    PDBInfo::new(pdb_data).map(Box::new)
+/// Maps the addresses of the Import Address Table entries to the names of the functions
+/// that they point to (e.g. `KERNEL32!CreateFileW`).
+pub fn load_iat_imports(pe: &PE, imports: &mut HashMap<u64, Box<str>>) {
+    for import in pe.imports.iter() {
+        // KERNEL32.dll -> KERNEL32
+        let dll = Path::new(import.dll)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(import.dll);
+        imports.insert(
+            pe.image_base as u64 + import.rva as u64,
+            format!("{}!{}", dll, import.name).into(),
+        );
+    }
 }
 
-pub fn load_pdb_symbols(
+/// Finds the read-only data sections that string literals are stored in.
+pub fn load_string_sections(
     pe: &PE,
-    pdb: &mut PDBInfo,
-    symbols: &mut Vec<Symbol>,
+    sections: &mut Vec<(std::ops::Range<u64>, usize)>,
 ) -> anyhow::Result<()> {
-    pdb.load_symbols(pe.image_base as u64, symbols)
+    for section in pe.sections.iter() {
+        let name = section
+            .name()
+            .context("failed to retrieve PE section name")?;
+
+        if name == ".rdata" {
+            let addr = pe.image_base as u64 + section.virtual_address as u64;
+            let size = section.virtual_size.min(section.size_of_raw_data) as u64;
+            sections.push((addr..(addr + size), section.pointer_to_raw_data as usize));
+        }
+    }
+    Ok(())
+}
+
+/// Lists the sections in the section table.
+pub fn load_section_list(pe: &PE) -> anyhow::Result<Vec<Section>> {
+    use goblin::pe::section_table::{IMAGE_SCN_CNT_CODE, IMAGE_SCN_MEM_EXECUTE};
+
+    let mut sections = Vec::new();
+    for section in pe.sections.iter() {
+        let name = section
+            .name()
+            .context("failed to retrieve PE section name")?;
+        // Object files don't have a virtual size.
+        let size = if section.virtual_size == 0 {
+            section.size_of_raw_data
+        } else {
+            section.virtual_size
+        };
+        let offset = if section.pointer_to_raw_data == 0 {
+            None
+        } else {
+            Some(section.pointer_to_raw_data as usize)
+        };
+        sections.push(Section::new(
+            name,
+            pe.image_base as u64 + section.virtual_address as u64,
+            size as u64,
+            offset,
+            section.characteristics & (IMAGE_SCN_CNT_CODE | IMAGE_SCN_MEM_EXECUTE) != 0,
+        ));
+    }
+    Ok(sections)
+}
+
+/// Finds the address ranges and file offsets of the sections with code.
+pub fn load_code_sections(pe: &PE) -> Vec<(std::ops::Range<u64>, usize)> {
+    use goblin::pe::section_table::{IMAGE_SCN_CNT_CODE, IMAGE_SCN_MEM_EXECUTE};
+
+    let mut sections: Vec<(std::ops::Range<u64>, usize)> = pe
+        .sections
+        .iter()
+        .filter(|section| {
+            section.characteristics & (IMAGE_SCN_CNT_CODE | IMAGE_SCN_MEM_EXECUTE) != 0
+        })
+        .map(|section| {
+            let addr = pe.image_base as u64 + section.virtual_address as u64;
+            let size = section.virtual_size.min(section.size_of_raw_data) as u64;
+            (addr..(addr + size), section.pointer_to_raw_data as usize)
+        })
+        .collect();
+    sections.sort_unstable_by_key(|(range, _)| range.start);
+    sections
+}
+
+/// Finds the function table of the binary in `.pdata` and the sections that the unwind
+/// information it points to can be in.
+pub fn load_unwind_sections(pe: &PE) -> anyhow::Result<UnwindSections> {
+    let mut unwind_sections = UnwindSections {
+        image_base: pe.image_base as u64,
+        sections: sections_by_address(pe),
+        ..UnwindSections::default()
+    };
+    for section in pe.sections.iter() {
+        let name = section
+            .name()
+            .context("failed to retrieve PE section name")?;
+
+        if name == ".pdata" {
+            unwind_sections.pdata = Some(UnwindSection {
+                address: pe.image_base as u64 + section.virtual_address as u64,
+                offset: section.pointer_to_raw_data as usize,
+                size: section.virtual_size.min(section.size_of_raw_data) as usize,
+            });
+        }
+    }
+    Ok(unwind_sections)
+}
+
+/// Finds the `.tls` section, which has the initial values of the thread-local variables at
+/// the offsets that they have in the TLS block of each thread.
+pub fn load_thread_locals(pe: &PE) -> anyhow::Result<ThreadLocals> {
+    for section in pe.sections.iter() {
+        let name = section
+            .name()
+            .context("failed to retrieve PE section name")?;
+        if name == ".tls" {
+            return Ok(ThreadLocals {
+                section_address: Some(pe.image_base as u64 + section.virtual_address as u64),
+                ..ThreadLocals::default()
+            });
+        }
+    }
+    Ok(ThreadLocals::default())
+}
+
+pub fn load_pdb(pe: &PE, pdb_data: BinaryData) -> anyhow::Result<Box<PDBInfo>> {
+    PDBInfo::new(pdb_data, pe.image_base as u64).map(Box::new)
+}
+
+pub fn load_pdb_symbols(pdb: &mut PDBInfo, symbols: &mut Vec<Symbol>) -> anyhow::Result<()> {
+    pdb.load_symbols(symbols)
 }
 
 pub fn load_dwarf(pe: &PE, endian: Endian, data: &BinaryData) -> anyhow::Result<Box<DwarfInfo>> {
@@ -136,11 +266,9 @@ pub fn load_dwarf(pe: &PE, endian: Endian, data: &BinaryData) -> anyhow::Result<
     Ok(Box::new(DwarfInfo::new(loader, sup_loader)?))
 }
 
-pub fn load_dwarf_symbols(
-    dwarf: &DwarfInfo,
-    pe: &PE,
-    symbols: &mut Vec<Symbol>,
-) -> anyhow::Result<()> {
+/// Returns the virtual address ranges of the sections and their file offsets, sorted by
+/// address.
+pub fn sections_by_address(pe: &PE) -> Vec<(std::ops::Range<u64>, usize)> {
     let mut sections: Vec<(std::ops::Range<u64>, usize)> = pe
         .sections
         .iter()
@@ -154,7 +282,15 @@ pub fn load_dwarf_symbols(
     sections.sort_unstable_by(|(lhs, _), (rhs, _)| {
         lhs.start.cmp(&rhs.start).then(lhs.end.cmp(&rhs.end))
     });
+    sections
+}
 
+pub fn load_dwarf_symbols(
+    dwarf: &DwarfInfo,
+    pe: &PE,
+    symbols: &mut Vec<Symbol>,
+) -> anyhow::Result<()> {
+    let sections = sections_by_address(pe);
     let addr_to_offset = |addr| {
         sections
             .binary_search_by(|(probe, _)| util::cmp_range_to_idx(probe, addr))
@@ -237,3 +373,23 @@ fn section_by_name(pe: &PE, data: &BinaryData, name: &str) -> anyhow::Result<Bin
     }
     Ok(data.slice(0..0))
 }
+
+/// Finds an imported DLL in the directory of the binary, the directories in `PATH`, or the
+/// Windows system directory.
+pub fn find_library(pe: &PE, data: &BinaryData, name: &str) -> anyhow::Result<PathBuf> {
+    let library = super::match_library(&pe.libraries, name)?;
+
+    let mut directories = Vec::new();
+    if let Some(dir) = data.path().parent() {
+        directories.push(dir.to_path_buf());
+    }
+    if let Some(paths) = std::env::var_os("PATH") {
+        directories.extend(std::env::split_paths(&paths));
+    }
+    if let Some(root) = std::env::var_os("SystemRoot") {
+        directories.push(Path::new(&root).join("System32"));
+    }
+
+    log::debug!("searching for `{}` in {:?}", library, directories);
+    super::find_in_directories(library, &directories)
+}
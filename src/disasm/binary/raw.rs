@@ -0,0 +1,102 @@
+use super::image;
+use super::{Arch, Binary, BinaryData, Bits, Endian, Format};
+use crate::disasm::symbol::{Symbol, SymbolSource};
+use anyhow::Context as _;
+use std::ops::Range;
+
+/// The address that each range of the contents of a raw binary is loaded at.
+type LoadRanges = Vec<(u64, Range<usize>)>;
+
+/// Returns the contents of a raw binary and the address that each range of its contents
+/// is loaded at. Intel HEX and S-record images are converted into the bytes of their
+/// segments, and other binaries are loaded at `base` as they are.
+pub fn load_image(data: BinaryData, base: u64) -> anyhow::Result<(BinaryData, LoadRanges)> {
+    let format = match image::detect(&data) {
+        Some(format) => format,
+        None => {
+            let len = data.len();
+            return Ok((data, vec![(base, 0..len)]));
+        }
+    };
+    log::debug!("object format = {:?}", format);
+
+    let segments = image::parse(&data, format).context("failed to parse firmware image")?;
+    let mut contents = Vec::with_capacity(segments.iter().map(|s| s.data.len()).sum());
+    let mut ranges = Vec::with_capacity(segments.len());
+    for segment in segments {
+        ranges.push((
+            segment.address,
+            contents.len()..(contents.len() + segment.data.len()),
+        ));
+        contents.extend_from_slice(&segment.data);
+    }
+    log::debug!("found {} segments in firmware image", ranges.len());
+
+    Ok((BinaryData::from_bytes(data.path(), contents), ranges))
+}
+
+pub fn load_arch_info(binary: &mut Binary, arch: Arch) {
+    log::debug!("object type   = raw");
+    binary.format = Format::Raw;
+
+    binary.arch = arch;
+    binary.bits = match arch {
+        Arch::X86
+        | Arch::Arm
+        | Arch::Thumb
+        | Arch::Mips
+        | Arch::PowerPc
+        | Arch::RiscV32
+        | Arch::Sparc
+        | Arch::Wasm => Bits::Bits32,
+        Arch::X86_64
+        | Arch::AArch64
+        | Arch::Mips64
+        | Arch::PowerPc64
+        | Arch::RiscV64
+        | Arch::Sparc64 => Bits::Bits64,
+        Arch::Unknown => Bits::Unknown,
+    };
+    // Flat binaries for these are almost always big endian.
+    binary.endian = match arch {
+        Arch::Mips
+        | Arch::Mips64
+        | Arch::PowerPc
+        | Arch::PowerPc64
+        | Arch::Sparc
+        | Arch::Sparc64 => Endian::Big,
+        _ => Endian::Little,
+    };
+    // Flat RISC-V binaries don't say which extensions they use and almost always use C.
+    binary.compressed_instructions = matches!(arch, Arch::RiscV32 | Arch::RiscV64);
+
+    log::debug!("object bits   = {}", binary.bits);
+    log::debug!("object endian = {}", binary.endian);
+    log::debug!("object arch   = {}", binary.arch);
+}
+
+/// Adds a symbol for each segment of the binary that is named after the file, and after
+/// the address of the segment if there are multiple segments.
+pub fn load_symbols(binary: &mut Binary, segments: &[(u64, Range<usize>)]) {
+    let file_name = binary
+        .data
+        .path()
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| String::from("raw"));
+
+    for (address, range) in segments {
+        let name = if segments.len() == 1 {
+            file_name.clone()
+        } else {
+            format!("{}@0x{:x}", file_name, address)
+        };
+        binary.symbols.push(Symbol::new_unmangled(
+            name,
+            *address,
+            range.start,
+            range.len(),
+            SymbolSource::Raw,
+        ));
+    }
+}
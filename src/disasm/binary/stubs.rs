@@ -0,0 +1,63 @@
+//! Decoding for the small stubs that binaries use to call imported functions.
+
+use super::Arch;
+
+/// Returns the address of the pointer that an x86 or x86_64 stub (e.g. a PLT entry) jumps
+/// through. `base_addr` is used for position independent 32-bit stubs, which jump relative
+/// to the address stored in `ebx`.
+pub fn x86_stub_slot(
+    entry: &[u8],
+    entry_addr: u64,
+    arch: Arch,
+    base_addr: Option<u64>,
+) -> Option<u64> {
+    for idx in 0..entry.len().saturating_sub(5) {
+        if entry[idx] != 0xff {
+            continue;
+        }
+        let mut disp = [0u8; 4];
+        disp.copy_from_slice(&entry[(idx + 2)..(idx + 6)]);
+        let disp = i32::from_le_bytes(disp);
+
+        match (arch, entry[idx + 1]) {
+            // jmp qword ptr [rip + disp32]
+            (Arch::X86_64, 0x25) => {
+                let next_insn = entry_addr + idx as u64 + 6;
+                return Some(next_insn.wrapping_add(disp as i64 as u64));
+            }
+            // jmp dword ptr [disp32]
+            (Arch::X86, 0x25) => return Some(disp as u32 as u64),
+            // jmp dword ptr [ebx + disp32] (position independent)
+            (Arch::X86, 0xa3) => {
+                return base_addr.map(|base| base.wrapping_add(disp as i64 as u64))
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns the address of the pointer that an AArch64 stub jumps through. These stubs
+/// load the pointer with an `adrp` + `ldr` pair before branching to it.
+pub fn aarch64_stub_slot(entry: &[u8], entry_addr: u64) -> Option<u64> {
+    let mut page = None;
+    for (idx, insn) in entry.chunks_exact(4).enumerate() {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(insn);
+        let insn = u32::from_le_bytes(bytes);
+        let pc = entry_addr + (idx * 4) as u64;
+
+        if insn & 0x9f00_0000 == 0x9000_0000 {
+            // adrp xN, page
+            let immlo = ((insn >> 29) & 0x3) as u64;
+            let immhi = ((insn >> 5) & 0x7_ffff) as u64;
+            let imm = (((immhi << 2) | immlo) << 43) as i64 >> 31; // sign extended, * 4096
+            page = Some((pc & !0xfff).wrapping_add(imm as u64));
+        } else if insn & 0xffc0_0000 == 0xf940_0000 {
+            // ldr xN, [xN, #imm]
+            let offset = ((insn >> 10) & 0xfff) as u64 * 8;
+            return page.map(|page| page + offset);
+        }
+    }
+    None
+}
@@ -0,0 +1,213 @@
+use super::{Arch, Binary, Bits, Endian, Format};
+use crate::disasm::symbol::{Symbol, SymbolSource};
+use crate::disasm::wasm::{skip_locals, Reader};
+use anyhow::Context as _;
+use std::collections::HashMap;
+
+pub const WASM_MAGIC: &[u8] = b"\0asm";
+
+/// A function in the function index space of a WebAssembly module.
+#[derive(Copy, Clone)]
+pub enum WasmFunction<'b> {
+    /// A function in the code section that starts at the given address.
+    Defined(u64),
+    /// An imported function with the given name.
+    Imported(&'b str),
+}
+
+/// The function index space of a WebAssembly module, which starts with the imported
+/// functions and is followed by the functions in the code section.
+#[derive(Default)]
+pub struct WasmFunctions {
+    imports: Vec<Box<str>>,
+    addresses: Vec<u64>,
+}
+
+impl WasmFunctions {
+    pub fn get(&self, index: u32) -> Option<WasmFunction<'_>> {
+        let index = index as usize;
+        if let Some(name) = self.imports.get(index) {
+            return Some(WasmFunction::Imported(name));
+        }
+        self.addresses
+            .get(index - self.imports.len())
+            .copied()
+            .map(WasmFunction::Defined)
+    }
+}
+
+pub fn load_arch_info(binary: &mut Binary) {
+    log::debug!("object type   = WebAssembly");
+    binary.format = Format::Wasm;
+
+    binary.bits = Bits::Bits32;
+    binary.endian = Endian::Little;
+    binary.arch = Arch::Wasm;
+
+    log::debug!("object bits   = {}", binary.bits);
+    log::debug!("object endian = {}", binary.endian);
+    log::debug!("object arch   = {}", binary.arch);
+}
+
+/// Finds the functions in a WebAssembly module. WebAssembly code isn't mapped into an
+/// address space so the addresses of the functions are their offsets in the file.
+pub fn load_module(
+    data: &[u8],
+    functions: &mut WasmFunctions,
+    symbols: &mut Vec<Symbol>,
+) -> anyhow::Result<()> {
+    let mut reader = Reader::new(data);
+    let header = reader
+        .bytes(8)
+        .context("WebAssembly module header is truncated")?;
+    if &header[..4] != WASM_MAGIC {
+        return Err(anyhow::anyhow!("not a WebAssembly module"));
+    }
+    let version = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    if version != 1 {
+        return Err(anyhow::anyhow!(
+            "unsupported WebAssembly module version {}",
+            version
+        ));
+    }
+
+    // The file offsets and lengths of the instructions in each function body.
+    let mut bodies = Vec::new();
+    let mut names = HashMap::new();
+    let mut export_names = HashMap::new();
+
+    while !reader.is_empty() {
+        let id = reader.u8().context("section header is truncated")?;
+        let size = reader.uleb().context("section header is truncated")? as usize;
+        let offset = reader.pos();
+        let mut section = Reader::new(
+            reader
+                .bytes(size)
+                .with_context(|| format!("section {} is truncated", id))?,
+        );
+
+        match id {
+            0 if section.name() == Some("name")
+                && load_function_names(&mut section, &mut names).is_none() =>
+            {
+                log::warn!("failed to parse WebAssembly name section");
+            }
+            2 => load_imports(&mut section, &mut functions.imports)
+                .context("failed to parse import section")?,
+            7 => load_exports(&mut section, &mut export_names)
+                .context("failed to parse export section")?,
+            10 => load_code(&mut section, offset, &mut bodies)
+                .context("failed to parse code section")?,
+            _ => {}
+        }
+    }
+
+    for (idx, &(offset, len)) in bodies.iter().enumerate() {
+        let index = (functions.imports.len() + idx) as u32;
+        functions.addresses.push(offset as u64);
+
+        let symbol = match names.get(&index).or_else(|| export_names.get(&index)) {
+            Some(&name) => Symbol::new(name, offset as u64, offset, len, SymbolSource::Wasm),
+            None => Symbol::new_unmangled(
+                format!("func[{}]", index),
+                offset as u64,
+                offset,
+                len,
+                SymbolSource::Wasm,
+            ),
+        };
+        symbols.push(symbol);
+    }
+
+    Ok(())
+}
+
+fn load_imports(section: &mut Reader, imports: &mut Vec<Box<str>>) -> Option<()> {
+    for _ in 0..section.uleb()? {
+        let module = section.name()?;
+        let field = section.name()?;
+        match section.u8()? {
+            // function
+            0x00 => {
+                section.uleb()?;
+                imports.push(format!("{}@{}", field, module).into());
+            }
+            // table
+            0x01 => {
+                section.u8()?;
+                skip_limits(section)?;
+            }
+            // memory
+            0x02 => skip_limits(section)?,
+            // global
+            0x03 => {
+                section.u8()?;
+                section.u8()?;
+            }
+            // tag
+            0x04 => {
+                section.u8()?;
+                section.uleb()?;
+            }
+            _ => return None,
+        }
+    }
+    Some(())
+}
+
+fn skip_limits(section: &mut Reader) -> Option<()> {
+    let flags = section.u8()?;
+    section.uleb()?;
+    if flags & 1 != 0 {
+        section.uleb()?;
+    }
+    Some(())
+}
+
+fn load_exports<'d>(section: &mut Reader<'d>, names: &mut HashMap<u32, &'d str>) -> Option<()> {
+    for _ in 0..section.uleb()? {
+        let name = section.name()?;
+        let kind = section.u8()?;
+        let index = section.uleb32()?;
+        if kind == 0x00 {
+            names.entry(index).or_insert(name);
+        }
+    }
+    Some(())
+}
+
+fn load_code(
+    section: &mut Reader,
+    section_offset: usize,
+    bodies: &mut Vec<(usize, usize)>,
+) -> Option<()> {
+    for _ in 0..section.uleb()? {
+        let size = section.uleb()? as usize;
+        let body_offset = section_offset + section.pos();
+        let mut body = Reader::new(section.bytes(size)?);
+        skip_locals(&mut body)?;
+        bodies.push((body_offset + body.pos(), size - body.pos()));
+    }
+    Some(())
+}
+
+/// Reads the function names subsection of the custom "name" section.
+fn load_function_names<'d>(
+    section: &mut Reader<'d>,
+    names: &mut HashMap<u32, &'d str>,
+) -> Option<()> {
+    while !section.is_empty() {
+        let id = section.u8()?;
+        let size = section.uleb()? as usize;
+        let mut subsection = Reader::new(section.bytes(size)?);
+        if id != 1 {
+            continue;
+        }
+
+        for _ in 0..subsection.uleb()? {
+            let index = subsection.uleb32()?;
+            names.insert(index, subsection.name()?);
+        }
+    }
+    Some(())
+}
@@ -0,0 +1,209 @@
+use super::binary::Binary;
+use super::{anal, capstone_for_binary, Flow, Jump, Syntax};
+use std::collections::HashMap;
+
+/// A function (or imported function) in a call graph.
+#[derive(Copy, Clone)]
+pub struct Node<'b> {
+    address: u64,
+    name: &'b str,
+}
+
+impl<'b> Node<'b> {
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    pub fn name(&self) -> &'b str {
+        self.name
+    }
+}
+
+/// The direct calls between all of the functions in a binary.
+/// Calls through registers or memory are not included.
+pub struct CallGraph<'b> {
+    nodes: Vec<Node<'b>>,
+    node_indices: HashMap<u64, usize>,
+
+    /// Sorted and deduplicated callees for each node.
+    callees: Vec<Vec<usize>>,
+    /// Sorted and deduplicated callers for each node.
+    callers: Vec<Vec<usize>>,
+}
+
+impl<'b> CallGraph<'b> {
+    /// Builds a call graph by disassembling every function in the binary.
+    pub fn new(binary: &'b Binary) -> anyhow::Result<CallGraph<'b>> {
+        let callgraph_timer = std::time::Instant::now();
+        let caps = capstone_for_binary(binary, Syntax::default())?;
+
+        let mut graph = CallGraph {
+            nodes: Vec::new(),
+            node_indices: HashMap::new(),
+            callees: Vec::new(),
+            callers: Vec::new(),
+        };
+
+        // The same instruction buffer is used for every function.
+        let mut insns = caps.disasm_iter(&[], 0);
+        let symbols = binary.all_symbols();
+        for symbol in symbols.iter() {
+            let caller = graph.add_node(symbol.address(), symbol.name());
+
+            insns.reset(
+                &binary.data()[symbol.offset()..symbol.end()],
+                symbol.address(),
+            );
+            for insn in &mut insns {
+                // The rest of a function that can't be disassembled is skipped, so that one
+                // function doesn't stop the call graph from being built.
+                let insn = match insn {
+                    Ok(insn) => insn,
+                    Err(err) => {
+                        log::warn!("failed to disassemble `{}`: {:?}", symbol.name(), err);
+                        break;
+                    }
+                };
+
+                let flow = anal::identify_flow(insn, &caps);
+                if flow != Flow::Call && flow != Flow::Jump {
+                    continue;
+                }
+
+                let insn_end = insn.address() + insn.bytes().len() as u64;
+                let target = if let Some((reloc_addr, reloc)) =
+                    binary.relocation_in(insn.address()..insn_end)
+                {
+                    match reloc.resolve(reloc_addr, insn_end) {
+                        Some(addr) => addr,
+                        None => continue,
+                    }
+                } else {
+                    match anal::identify_jump_target(insn, &caps) {
+                        Jump::External(addr) => addr,
+                        _ => continue,
+                    }
+                };
+
+                // Jumps inside of the same function are not calls.
+                if symbol.address_range().contains(&target) {
+                    continue;
+                }
+
+                let callee = if let Some(import) = binary.import_at(target) {
+                    graph.add_node(target, import)
+                } else if let Some((callee, 0)) = binary.symbolicate(target) {
+                    graph.add_node(callee.address(), callee.name())
+                } else {
+                    continue;
+                };
+
+                graph.callees[caller].push(callee);
+                graph.callers[callee].push(caller);
+            }
+        }
+
+        for edges in graph.callees.iter_mut().chain(graph.callers.iter_mut()) {
+            edges.sort_unstable();
+            edges.dedup();
+        }
+
+        log::trace!(
+            "built call graph with {} nodes from {} symbols in {}",
+            graph.nodes.len(),
+            symbols.len(),
+            crate::util::DurationDisplay(callgraph_timer.elapsed())
+        );
+
+        Ok(graph)
+    }
+
+    fn add_node(&mut self, address: u64, name: &'b str) -> usize {
+        if let Some(&idx) = self.node_indices.get(&address) {
+            return idx;
+        }
+
+        let idx = self.nodes.len();
+        self.nodes.push(Node { address, name });
+        self.callees.push(Vec::new());
+        self.callers.push(Vec::new());
+        self.node_indices.insert(address, idx);
+        idx
+    }
+
+    pub fn nodes(&self) -> &[Node<'b>] {
+        &self.nodes
+    }
+
+    /// Returns the index of the node for the function at the given address.
+    pub fn node_at(&self, address: u64) -> Option<usize> {
+        self.node_indices.get(&address).copied()
+    }
+
+    /// Indices of the nodes that are called by the given node.
+    pub fn callees(&self, node: usize) -> &[usize] {
+        &self.callees[node]
+    }
+
+    /// Indices of the nodes that call the given node.
+    pub fn callers(&self, node: usize) -> &[usize] {
+        &self.callers[node]
+    }
+
+    /// Returns the nodes and (caller, callee) pairs that are within `depth` calls of the
+    /// root node in either direction, or the entire graph if there is no root.
+    /// The nodes are sorted by their index.
+    pub fn subgraph(&self, root: Option<usize>, depth: usize) -> (Vec<usize>, Vec<(usize, usize)>) {
+        let mut edges = Vec::new();
+        if let Some(root) = root {
+            self.walk(root, depth, &self.callees, &mut |from, to| {
+                edges.push((from, to))
+            });
+            self.walk(root, depth, &self.callers, &mut |from, to| {
+                edges.push((to, from))
+            });
+            edges.sort_unstable();
+            edges.dedup();
+        } else {
+            for (caller, callees) in self.callees.iter().enumerate() {
+                edges.extend(callees.iter().map(|&callee| (caller, callee)));
+            }
+        }
+
+        let mut nodes = Vec::with_capacity(edges.len() * 2 + 1);
+        nodes.extend(root);
+        for &(caller, callee) in edges.iter() {
+            nodes.push(caller);
+            nodes.push(callee);
+        }
+        nodes.sort_unstable();
+        nodes.dedup();
+
+        (nodes, edges)
+    }
+
+    /// Breadth first walk from the root node, calling `visit` for every edge that is
+    /// followed.
+    fn walk<F>(&self, root: usize, depth: usize, adjacent: &[Vec<usize>], visit: &mut F)
+    where
+        F: FnMut(usize, usize),
+    {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut current = vec![root];
+        seen[root] = true;
+
+        for _ in 0..depth {
+            let mut next = Vec::new();
+            for &from in current.iter() {
+                for &to in adjacent[from].iter() {
+                    visit(from, to);
+                    if !seen[to] {
+                        seen[to] = true;
+                        next.push(to);
+                    }
+                }
+            }
+            current = next;
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use super::{Disassembly, Flow, Jump};
+use std::ops::Range;
+
+/// The basic blocks of a disassembled function and the edges between them.
+pub struct ControlFlowGraph {
+    blocks: Vec<BasicBlock>,
+}
+
+impl ControlFlowGraph {
+    /// Splits the disassembly into basic blocks at every branch and branch target.
+    pub fn new(disassembly: &Disassembly) -> ControlFlowGraph {
+        let lines = disassembly.lines();
+        if lines.is_empty() {
+            return ControlFlowGraph { blocks: Vec::new() };
+        }
+
+        // Indices of the lines that start a new basic block.
+        let mut leaders = vec![0];
+        let case_lines = disassembly
+            .jump_tables()
+            .iter()
+            .map(|table| {
+                let mut targets = table
+                    .targets()
+                    .iter()
+                    .filter_map(|&target| lines.iter().position(|l| l.contains_addr(target)))
+                    .collect::<Vec<_>>();
+                targets.sort_unstable();
+                targets.dedup();
+                (table.jump_line(), targets)
+            })
+            .collect::<Vec<_>>();
+        for (_, targets) in case_lines.iter() {
+            leaders.extend(targets.iter().copied());
+        }
+        for (idx, line) in lines.iter().enumerate() {
+            if let Jump::Internal(target) = line.jump() {
+                leaders.push(target);
+            }
+
+            if line.flow().is_terminator() && idx + 1 < lines.len() {
+                leaders.push(idx + 1);
+            }
+        }
+        leaders.sort_unstable();
+        leaders.dedup();
+
+        let mut blocks = leaders
+            .iter()
+            .enumerate()
+            .map(|(idx, &start)| BasicBlock {
+                lines: start..leaders.get(idx + 1).copied().unwrap_or(lines.len()),
+                edges: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        let block_of_line = |line: usize| match leaders.binary_search(&line) {
+            Ok(idx) => idx,
+            Err(idx) => idx - 1,
+        };
+
+        for (idx, block) in blocks.iter_mut().enumerate() {
+            let last = &lines[block.lines.end - 1];
+            let has_next = idx + 1 < leaders.len();
+
+            // Indirect jumps through a jump table can go to any of its cases.
+            if let Some((_, targets)) = case_lines
+                .iter()
+                .find(|&&(jump, _)| jump == block.lines.end - 1)
+            {
+                for &target in targets.iter() {
+                    block
+                        .edges
+                        .push(Edge::new(block_of_line(target), EdgeKind::Jump));
+                }
+                continue;
+            }
+
+            match (last.flow(), last.jump()) {
+                (Flow::Jump, Jump::Internal(target)) => {
+                    block
+                        .edges
+                        .push(Edge::new(block_of_line(target), EdgeKind::Jump));
+                }
+
+                (Flow::ConditionalJump, jump) => {
+                    if let Jump::Internal(target) = jump {
+                        block
+                            .edges
+                            .push(Edge::new(block_of_line(target), EdgeKind::Taken));
+                    }
+                    if has_next {
+                        block.edges.push(Edge::new(idx + 1, EdgeKind::NotTaken));
+                    }
+                }
+
+                (flow, _) if flow.falls_through() && has_next => {
+                    block.edges.push(Edge::new(idx + 1, EdgeKind::Next));
+                }
+
+                _ => {}
+            }
+        }
+
+        ControlFlowGraph { blocks }
+    }
+
+    pub fn blocks(&self) -> &[BasicBlock] {
+        &self.blocks
+    }
+}
+
+pub struct BasicBlock {
+    lines: Range<usize>,
+    edges: Vec<Edge>,
+}
+
+impl BasicBlock {
+    /// The range of indices of the disassembly lines that are in this block.
+    pub fn lines(&self) -> Range<usize> {
+        self.lines.clone()
+    }
+
+    /// Edges to the blocks that can be executed after this one.
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct Edge {
+    target: usize,
+    kind: EdgeKind,
+}
+
+impl Edge {
+    fn new(target: usize, kind: EdgeKind) -> Edge {
+        Edge { target, kind }
+    }
+
+    /// The index of the block that this edge goes to.
+    pub fn target(&self) -> usize {
+        self.target
+    }
+
+    pub fn kind(&self) -> EdgeKind {
+        self.kind
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// An unconditional jump.
+    Jump,
+    /// A conditional jump that was taken.
+    Taken,
+    /// A conditional jump that was not taken.
+    NotTaken,
+    /// Execution continues on to the next block without jumping.
+    Next,
+}
@@ -0,0 +1,134 @@
+//! Disassembles the instructions around an address that doesn't have to be in a known
+//! symbol or at the start of a function, e.g. the address of a crash.
+
+use super::binary::{Arch, Binary};
+use super::symbol::{Symbol, SymbolSource};
+use super::{disasm, Disassembly, Options};
+use anyhow::Context as _;
+
+/// The length of the longest instruction of any supported architecture, which is 15 bytes
+/// on x86.
+const MAX_INSTRUCTION_LEN: u64 = 15;
+
+/// The instructions around an address and the symbol that they are shown as.
+pub struct AddressContext {
+    /// A symbol for the disassembled range, which is named after the symbol that contains
+    /// its start, e.g. `main+0x20`, or after its address.
+    pub symbol: Symbol,
+
+    pub disassembly: Disassembly,
+}
+
+/// Disassembles up to `count` instructions before and after an address. The instructions
+/// before the address are found by decoding from different starting points and using the
+/// earliest one that decodes to an instruction at the address without going through
+/// invalid instructions, unless the start of the symbol that contains the address is close
+/// enough to decode from there.
+pub fn disasm_around(
+    binary: &Binary,
+    address: u64,
+    count: usize,
+    options: Options,
+) -> anyhow::Result<AddressContext> {
+    if binary.arch() == Arch::Wasm {
+        return Err(anyhow::anyhow!(
+            "instructions around an address can't be disassembled in WebAssembly modules"
+        ));
+    }
+    let (section, offset) = binary
+        .code_section_at(address)
+        .with_context(|| format!("0x{:x} isn't in a section with code", address))?;
+    let section_end = section
+        .end
+        .min(section.start + binary.data().len().saturating_sub(offset) as u64);
+
+    let (step, max_len) = match binary.arch() {
+        Arch::X86 | Arch::X86_64 => (1, MAX_INSTRUCTION_LEN),
+        Arch::Arm | Arch::Thumb | Arch::RiscV32 | Arch::RiscV64 => (2, 4),
+        _ => (4, 4),
+    };
+    let window = count as u64 * max_len;
+    let end = address
+        .saturating_add((count as u64 + 1) * max_len)
+        .min(section_end);
+
+    let earliest = address.saturating_sub(window).max(section.start);
+    let mut starts = Vec::new();
+    if let Some((symbol, symbol_offset)) = binary.symbolicate(address) {
+        if symbol_offset <= window * 4 && symbol.address() >= section.start {
+            starts.push(symbol.address());
+        }
+    }
+    let mut start = earliest + (address - earliest) % step;
+    while start <= address {
+        starts.push(start);
+        start += step;
+    }
+
+    for start in starts {
+        let symbol = range_symbol(binary, &section, offset, start..end);
+        let disassembly = match disasm(binary, &symbol, options) {
+            Ok(disassembly) => disassembly,
+            Err(err) => {
+                log::trace!("failed to disassemble from 0x{:x}: {:?}", start, err);
+                continue;
+            }
+        };
+        let lines = disassembly.lines();
+        let idx = match lines.iter().position(|line| line.address() == address) {
+            Some(idx) => idx,
+            None => continue,
+        };
+        // Data lines are bytes that aren't valid instructions.
+        let first = idx.saturating_sub(count);
+        if lines[first..idx]
+            .iter()
+            .any(|line| line.mnemonic().starts_with('.'))
+        {
+            continue;
+        }
+        log::trace!(
+            "aligned the instructions before 0x{:x} at 0x{:x}",
+            address,
+            start
+        );
+
+        // Disassemble again with only the instructions that are shown, so that jumps to
+        // the other ones aren't internal.
+        let range_start = lines[first].address();
+        let range_end = lines
+            .get(idx + count + 1)
+            .map_or(end, |line| line.address());
+        let symbol = range_symbol(binary, &section, offset, range_start..range_end);
+        let disassembly = disasm(binary, &symbol, options)?;
+        return Ok(AddressContext {
+            symbol,
+            disassembly,
+        });
+    }
+    Err(anyhow::anyhow!(
+        "no instruction starts at 0x{:x} when decoding from before it",
+        address
+    ))
+}
+
+/// Creates a symbol for an address range of a section.
+fn range_symbol(
+    binary: &Binary,
+    section: &std::ops::Range<u64>,
+    offset: usize,
+    range: std::ops::Range<u64>,
+) -> Symbol {
+    let name = match binary.symbolicate(range.start) {
+        Some((symbol, 0)) => symbol.name().to_string(),
+        Some((symbol, symbol_offset)) => format!("{}+0x{:x}", symbol.name(), symbol_offset),
+        None => format!("0x{:x}", range.start),
+    };
+    Symbol::new_unmangled(
+        name,
+        range.start,
+        offset + (range.start - section.start) as usize,
+        (range.end - range.start) as usize,
+        SymbolSource::Raw,
+    )
+}
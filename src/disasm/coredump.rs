@@ -0,0 +1,504 @@
+//! Reads the state of a crashed process from a Linux core dump or a Windows minidump, so
+//! that the frames of the crash can be found in the binary that crashed.
+//!
+//! Only the crashing thread of x86_64 and AArch64 processes is read. Its backtrace is
+//! recovered by scanning the stack for return addresses into the binary, because the
+//! stack is all that is known about the callers without unwinding through every module.
+
+use super::binary::{Arch, Binary, BinaryData};
+use anyhow::Context as _;
+use goblin::elf::{program_header, Elf};
+use std::ops::Range;
+use std::path::Path;
+
+/// How many bytes above the stack pointer are searched for return addresses.
+const STACK_SCAN_SIZE: u64 = 64 * 1024;
+
+const NT_PRSTATUS: u32 = 1;
+const NT_SIGINFO: u32 = 0x5349_4749;
+const NT_FILE: u32 = 0x4649_4c45;
+
+/// The offset of `pr_reg` in `struct elf_prstatus` on 64-bit Linux.
+const PRSTATUS_REGS_OFFSET: usize = 112;
+
+const MINIDUMP_SIGNATURE: &[u8] = b"MDMP";
+const MODULE_LIST_STREAM: u32 = 4;
+const MEMORY_LIST_STREAM: u32 = 5;
+const EXCEPTION_STREAM: u32 = 6;
+const SYSTEM_INFO_STREAM: u32 = 7;
+const MEMORY64_LIST_STREAM: u32 = 9;
+
+/// A file that was mapped into the crashed process.
+#[derive(Clone, Debug)]
+pub struct MappedFile {
+    /// The addresses that (a part of) the file was mapped at.
+    pub range: Range<u64>,
+
+    /// The position in the file that was mapped at the start of the range. Minidumps only
+    /// have the range of the whole image of each module, which doesn't have one.
+    pub file_offset: Option<u64>,
+
+    /// The path of the file in the crashed process.
+    pub path: String,
+}
+
+/// The crashing thread and memory of a crashed process.
+pub struct CrashDump {
+    data: BinaryData,
+    arch: Arch,
+    pc: u64,
+    sp: u64,
+    reason: Option<String>,
+    mapped_files: Vec<MappedFile>,
+
+    /// The address ranges of the memory in the dump and their offsets in the dump.
+    memory: Vec<(Range<u64>, usize)>,
+}
+
+impl CrashDump {
+    /// Reads a core dump or a minidump.
+    pub fn open<P: AsRef<Path>>(path: P) -> anyhow::Result<CrashDump> {
+        let path = path.as_ref();
+        let data = BinaryData::from_path(path)
+            .with_context(|| format!("failed to read `{}`", path.display()))?;
+        let dump = if data.starts_with(MINIDUMP_SIGNATURE) {
+            parse_minidump(data).context("failed to parse minidump")?
+        } else {
+            parse_elf_core(data).context("failed to parse core dump")?
+        };
+        log::debug!(
+            "read a {:?} crash dump with {} mapped files and {} memory ranges",
+            dump.arch,
+            dump.mapped_files.len(),
+            dump.memory.len()
+        );
+        Ok(dump)
+    }
+
+    pub fn arch(&self) -> Arch {
+        self.arch
+    }
+
+    /// Why the process crashed, e.g. `SIGSEGV at 0x0`, if that is known.
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+
+    pub fn mapped_files(&self) -> &[MappedFile] {
+        &self.mapped_files
+    }
+
+    /// Returns the memory of the process at an address, if it is in the dump.
+    pub fn read_memory(&self, addr: u64, len: usize) -> Option<&[u8]> {
+        let idx = self
+            .memory
+            .iter()
+            .position(|(range, _)| range.contains(&addr))?;
+        let (ref range, offset) = self.memory[idx];
+        if addr + len as u64 > range.end {
+            return None;
+        }
+        let start = offset + (addr - range.start) as usize;
+        self.data.get(start..(start + len))
+    }
+
+    /// Finds how far the binary was moved from its addresses when it was loaded into the
+    /// crashed process, by the mapped file with the same name as the binary. The address
+    /// of an instruction in the process minus the bias is its address in the binary.
+    pub fn load_bias(&self, binary_path: &Path, binary: &Binary) -> Option<u64> {
+        let name = binary_path.file_name()?.to_str()?;
+        let files = self
+            .mapped_files
+            .iter()
+            .filter(|file| file_name(&file.path).eq_ignore_ascii_case(name));
+        for file in files {
+            let file_offset = match file.file_offset {
+                Some(file_offset) => file_offset,
+                None => return Some(file.range.start.wrapping_sub(binary.image_base())),
+            };
+            let mapped = file_offset..(file_offset + (file.range.end - file.range.start));
+            for (range, offset) in binary.code_sections() {
+                let offset = *offset as u64;
+                if mapped.contains(&offset) {
+                    let address = file.range.start + (offset - file_offset);
+                    return Some(address.wrapping_sub(range.start));
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the address of the crash followed by the return addresses into the binary
+    /// that are found on the stack, as addresses in the crashed process.
+    pub fn backtrace(&self, binary: &Binary, bias: u64, max_frames: usize) -> Vec<u64> {
+        let mut frames = vec![self.pc];
+        let mut addr = self.sp & !7;
+        let end = self.sp.saturating_add(STACK_SCAN_SIZE);
+        while addr < end && frames.len() < max_frames {
+            let word = match self.read_memory(addr, 8) {
+                Some(bytes) => {
+                    let mut word = [0; 8];
+                    word.copy_from_slice(bytes);
+                    u64::from_le_bytes(word)
+                }
+                None => break,
+            };
+            addr += 8;
+
+            let return_address = word.wrapping_sub(bias);
+            if frames.last() != Some(&word) && follows_call(binary, return_address) {
+                frames.push(word);
+            }
+        }
+        frames
+    }
+}
+
+/// Returns the name of a file from a path in the crashed process, which uses the path
+/// separators of the system that it ran on.
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// Checks if the instruction before an address in the binary is a call, so that the address
+/// can be a return address.
+fn follows_call(binary: &Binary, addr: u64) -> bool {
+    let (range, offset) = match binary.code_section_at(addr) {
+        Some(section) => section,
+        None => return false,
+    };
+    let position = (addr - range.start) as usize;
+    let before = |len: usize| {
+        if position < len {
+            None
+        } else {
+            binary
+                .data()
+                .get((offset + position - len)..(offset + position))
+        }
+    };
+
+    match binary.arch() {
+        Arch::X86 | Arch::X86_64 => {
+            let bytes = match before(7).or_else(|| before(5)) {
+                Some(bytes) => bytes,
+                None => return false,
+            };
+            let len = bytes.len();
+            // `call rel32`, or `call r/m` (`ff /2`) with a ModRM byte and up to 4 more bytes.
+            bytes[len - 5] == 0xe8
+                || (2..=len.min(7))
+                    .any(|back| bytes[len - back] == 0xff && (bytes[len - back + 1] >> 3) & 7 == 2)
+        }
+        Arch::AArch64 => match before(4) {
+            Some(bytes) => {
+                let mut word = [0; 4];
+                word.copy_from_slice(bytes);
+                let insn = u32::from_le_bytes(word);
+                // `bl` or `blr`.
+                insn & 0xfc00_0000 == 0x9400_0000 || insn & 0xffff_fc1f == 0xd63f_0000
+            }
+            None => false,
+        },
+        _ => true,
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let mut bytes = [0; 2];
+    bytes.copy_from_slice(data.get(offset..(offset + 2))?);
+    Some(u16::from_le_bytes(bytes))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(data.get(offset..(offset + 4))?);
+    Some(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(data.get(offset..(offset + 8))?);
+    Some(u64::from_le_bytes(bytes))
+}
+
+/// Reads a Linux core dump. The registers of the crashing thread are in the first
+/// `NT_PRSTATUS` note, and the mapped files are in the `NT_FILE` note.
+fn parse_elf_core(data: BinaryData) -> anyhow::Result<CrashDump> {
+    use goblin::elf::header;
+
+    let elf = Elf::parse(&data).context("not a core dump or minidump")?;
+    if elf.header.e_type != header::ET_CORE {
+        return Err(anyhow::anyhow!("the ELF file is not a core dump"));
+    }
+    if !elf.little_endian || !elf.is_64 {
+        return Err(anyhow::anyhow!(
+            "only core dumps of 64-bit little endian processes are supported"
+        ));
+    }
+    let (arch, pc_idx, sp_idx) = match elf.header.e_machine {
+        header::EM_X86_64 => (Arch::X86_64, 16, 19),
+        header::EM_AARCH64 => (Arch::AArch64, 32, 31),
+        machine => {
+            return Err(anyhow::anyhow!(
+                "core dumps for the ELF machine {} are not supported",
+                machine
+            ))
+        }
+    };
+
+    let mut memory = Vec::new();
+    let mut registers = None;
+    let mut signal = None;
+    let mut fault_address = None;
+    let mut mapped_files = Vec::new();
+    for segment in elf.program_headers.iter() {
+        let start = segment.p_offset as usize;
+        let end = start + segment.p_filesz as usize;
+        match segment.p_type {
+            program_header::PT_LOAD if segment.p_filesz > 0 => {
+                memory.push((segment.p_vaddr..(segment.p_vaddr + segment.p_filesz), start))
+            }
+            program_header::PT_NOTE => {
+                let notes = data.get(start..end).context("note segment out of bounds")?;
+                for (note_type, desc) in elf_notes(notes) {
+                    match note_type {
+                        NT_PRSTATUS if registers.is_none() => {
+                            signal = read_u16(desc, 12).map(u32::from);
+                            let reg = |idx: usize| read_u64(desc, PRSTATUS_REGS_OFFSET + idx * 8);
+                            registers = match (reg(pc_idx), reg(sp_idx)) {
+                                (Some(pc), Some(sp)) => Some((pc, sp)),
+                                _ => None,
+                            };
+                        }
+                        NT_SIGINFO => fault_address = read_u64(desc, 16),
+                        NT_FILE => mapped_files = read_file_note(desc),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let (pc, sp) = registers.context("the core dump doesn't have an NT_PRSTATUS note")?;
+    let reason = signal.filter(|&signal| signal != 0).map(|signal| {
+        let name = match signal {
+            4 => "SIGILL".to_string(),
+            5 => "SIGTRAP".to_string(),
+            6 => "SIGABRT".to_string(),
+            7 => "SIGBUS".to_string(),
+            8 => "SIGFPE".to_string(),
+            11 => "SIGSEGV".to_string(),
+            signal => format!("signal {}", signal),
+        };
+        match fault_address {
+            Some(addr) if signal == 7 || signal == 11 => format!("{} at 0x{:x}", name, addr),
+            _ => name,
+        }
+    });
+    Ok(CrashDump {
+        data: data.clone(),
+        arch,
+        pc,
+        sp,
+        reason,
+        mapped_files,
+        memory,
+    })
+}
+
+/// Returns the type and contents of each note in a note segment.
+fn elf_notes(notes: &[u8]) -> Vec<(u32, &[u8])> {
+    let align = |len: usize| (len + 3) & !3;
+    let mut result = Vec::new();
+    let mut offset = 0;
+    while offset + 12 <= notes.len() {
+        let (name_len, desc_len, note_type) = match (
+            read_u32(notes, offset),
+            read_u32(notes, offset + 4),
+            read_u32(notes, offset + 8),
+        ) {
+            (Some(name_len), Some(desc_len), Some(note_type)) => {
+                (name_len as usize, desc_len as usize, note_type)
+            }
+            _ => break,
+        };
+        let desc_offset = offset + 12 + align(name_len);
+        match notes.get(desc_offset..(desc_offset + desc_len)) {
+            Some(desc) => result.push((note_type, desc)),
+            None => break,
+        }
+        offset = desc_offset + align(desc_len);
+    }
+    result
+}
+
+/// Reads the `NT_FILE` note, which has the number of mappings and the page size, then the
+/// start, end, and file offset (in pages) of each mapping, and then their paths.
+fn read_file_note(desc: &[u8]) -> Vec<MappedFile> {
+    let (count, page_size) = match (read_u64(desc, 0), read_u64(desc, 8)) {
+        (Some(count), Some(page_size)) => (count as usize, page_size),
+        _ => return Vec::new(),
+    };
+    let names_offset = 16 + count.saturating_mul(24);
+    let mut names = match desc.get(names_offset..) {
+        Some(names) => names.split(|&b| b == 0),
+        None => return Vec::new(),
+    };
+
+    let mut files = Vec::with_capacity(count);
+    for idx in 0..count {
+        let entry = 16 + idx * 24;
+        let (start, end, page) = match (
+            read_u64(desc, entry),
+            read_u64(desc, entry + 8),
+            read_u64(desc, entry + 16),
+        ) {
+            (Some(start), Some(end), Some(page)) => (start, end, page),
+            _ => break,
+        };
+        let path = match names.next() {
+            Some(name) => String::from_utf8_lossy(name).into_owned(),
+            None => break,
+        };
+        files.push(MappedFile {
+            range: start..end,
+            file_offset: Some(page * page_size),
+            path,
+        });
+    }
+    files
+}
+
+/// Reads a Windows minidump. The registers of the crashing thread are in the context of
+/// the exception stream.
+fn parse_minidump(data: BinaryData) -> anyhow::Result<CrashDump> {
+    let stream_count = read_u32(&data, 8).context("truncated header")? as usize;
+    let directory = read_u32(&data, 12).context("truncated header")? as usize;
+    let mut streams = std::collections::HashMap::new();
+    for idx in 0..stream_count {
+        let entry = directory + idx * 12;
+        let (stream_type, size, rva) = match (
+            read_u32(&data, entry),
+            read_u32(&data, entry + 4),
+            read_u32(&data, entry + 8),
+        ) {
+            (Some(stream_type), Some(size), Some(rva)) => (stream_type, size, rva),
+            _ => return Err(anyhow::anyhow!("truncated stream directory")),
+        };
+        if let Some(stream) = data.get((rva as usize)..(rva as usize + size as usize)) {
+            streams.insert(stream_type, stream);
+        }
+    }
+
+    let processor = streams
+        .get(&SYSTEM_INFO_STREAM)
+        .and_then(|info| read_u16(info, 0))
+        .context("the minidump doesn't have a system info stream")?;
+    let (arch, pc_offset, sp_offset) = match processor {
+        9 => (Arch::X86_64, 0xf8, 0x98),
+        12 => (Arch::AArch64, 0x108, 0x100),
+        processor => {
+            return Err(anyhow::anyhow!(
+                "minidumps for the processor architecture {} are not supported",
+                processor
+            ))
+        }
+    };
+
+    let exception = streams
+        .get(&EXCEPTION_STREAM)
+        .context("the minidump doesn't have an exception stream")?;
+    let code = read_u32(exception, 8).context("truncated exception stream")?;
+    let exception_address = read_u64(exception, 24).context("truncated exception stream")?;
+    let context_size = read_u32(exception, 160).context("truncated exception stream")? as usize;
+    let context_rva = read_u32(exception, 164).context("truncated exception stream")? as usize;
+    let context = data
+        .get(context_rva..(context_rva + context_size))
+        .context("thread context out of bounds")?;
+    let pc = read_u64(context, pc_offset).context("truncated thread context")?;
+    let sp = read_u64(context, sp_offset).context("truncated thread context")?;
+
+    let name = match code {
+        0xc000_0005 => "access violation".to_string(),
+        0xc000_001d => "illegal instruction".to_string(),
+        0xc000_0094 => "integer division by zero".to_string(),
+        0xc000_00fd => "stack overflow".to_string(),
+        0xc000_0409 => "fast fail".to_string(),
+        0x8000_0003 => "breakpoint".to_string(),
+        code => format!("exception 0x{:08x}", code),
+    };
+    let reason = Some(format!("{} at 0x{:x}", name, exception_address));
+
+    let mut mapped_files = Vec::new();
+    if let Some(modules) = streams.get(&MODULE_LIST_STREAM) {
+        let count = read_u32(modules, 0).unwrap_or(0) as usize;
+        for idx in 0..count {
+            let module = 4 + idx * 108;
+            let (base, size, name_rva) = match (
+                read_u64(modules, module),
+                read_u32(modules, module + 8),
+                read_u32(modules, module + 20),
+            ) {
+                (Some(base), Some(size), Some(name_rva)) => (base, size, name_rva as usize),
+                _ => break,
+            };
+            mapped_files.push(MappedFile {
+                range: base..(base + u64::from(size)),
+                file_offset: None,
+                path: read_minidump_string(&data, name_rva).unwrap_or_default(),
+            });
+        }
+    }
+
+    let mut memory = Vec::new();
+    if let Some(list) = streams.get(&MEMORY_LIST_STREAM) {
+        let count = read_u32(list, 0).unwrap_or(0) as usize;
+        for idx in 0..count {
+            let descriptor = 4 + idx * 16;
+            if let (Some(start), Some(size), Some(rva)) = (
+                read_u64(list, descriptor),
+                read_u32(list, descriptor + 8),
+                read_u32(list, descriptor + 12),
+            ) {
+                memory.push((start..(start + u64::from(size)), rva as usize));
+            }
+        }
+    }
+    // Full memory dumps list their memory consecutively after a base offset instead.
+    if let Some(list) = streams.get(&MEMORY64_LIST_STREAM) {
+        let count = read_u64(list, 0).unwrap_or(0) as usize;
+        let mut rva = read_u64(list, 8).unwrap_or(0) as usize;
+        for idx in 0..count {
+            let descriptor = 16 + idx * 16;
+            if let (Some(start), Some(size)) =
+                (read_u64(list, descriptor), read_u64(list, descriptor + 8))
+            {
+                memory.push((start..(start + size), rva));
+                rva += size as usize;
+            }
+        }
+    }
+
+    Ok(CrashDump {
+        data: data.clone(),
+        arch,
+        pc,
+        sp,
+        reason,
+        mapped_files,
+        memory,
+    })
+}
+
+/// Reads a `MINIDUMP_STRING`, which is the length in bytes followed by UTF-16.
+fn read_minidump_string(data: &[u8], rva: usize) -> Option<String> {
+    let len = read_u32(data, rva)? as usize;
+    let bytes = data.get((rva + 4)..(rva + 4 + len))?;
+    let units = bytes
+        .chunks_exact(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .collect::<Vec<_>>();
+    Some(String::from_utf16_lossy(&units))
+}
@@ -0,0 +1,158 @@
+//! Rough estimates of the latency and throughput of instructions. These are approximate
+//! values for recent cores (Skylake on x86, Cortex-A72 on AArch64) that are based on the
+//! measurements from uops.info and LLVM's scheduling models. They are only meant to point
+//! out expensive instructions and can be off by a lot for a specific CPU.
+
+use super::binary::Arch;
+use super::Disassembly;
+
+/// The load-to-use latency of a load that hits the L1 cache, which is added to the latency
+/// of instructions that have memory operands on x86.
+const LOAD_LATENCY: u32 = 5;
+
+/// The estimated cost of an instruction.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Cost {
+    /// The number of cycles until the result of the instruction can be used.
+    pub latency: u32,
+    /// The average number of cycles between instructions of the same kind when they're
+    /// independent (the reciprocal throughput).
+    pub throughput: f32,
+}
+
+impl Cost {
+    fn new(latency: u32, throughput: f32) -> Cost {
+        Cost {
+            latency,
+            throughput,
+        }
+    }
+}
+
+/// Returns the estimated cost of each line of the disassembly, or `None` for the lines
+/// that don't have an estimate.
+pub fn estimate_all(arch: Arch, disassembly: &Disassembly) -> Vec<Option<Cost>> {
+    disassembly
+        .lines()
+        .iter()
+        .map(|line| estimate(arch, line.mnemonic(), line.operands()))
+        .collect()
+}
+
+/// Returns the estimated cost of an instruction, if there is one for the architecture.
+pub fn estimate(arch: Arch, mnemonic: &str, operands: &str) -> Option<Cost> {
+    match arch {
+        Arch::X86 | Arch::X86_64 => estimate_x86(mnemonic, operands),
+        Arch::AArch64 => estimate_arm64(mnemonic),
+        _ => None,
+    }
+}
+
+fn estimate_x86(mnemonic: &str, operands: &str) -> Option<Cost> {
+    let mut cost = x86_cost(mnemonic)
+        // AT&T syntax has a suffix for the operand size (e.g. `addq`).
+        .or_else(|| x86_cost(mnemonic.get(..mnemonic.len().checked_sub(1)?)?))
+        // Most AVX instructions cost the same as the SSE instructions they're based on.
+        .or_else(|| x86_cost(mnemonic.strip_prefix('v')?))?;
+
+    let has_memory_operand = operands.contains('[') || operands.contains('(');
+    if has_memory_operand && !matches!(mnemonic, "lea" | "nop" | "leal" | "leaq" | "nopl" | "nopw")
+    {
+        cost.latency += LOAD_LATENCY;
+    }
+    Some(cost)
+}
+
+fn x86_cost(mnemonic: &str) -> Option<Cost> {
+    let cost = match mnemonic {
+        "nop" => Cost::new(0, 0.25),
+        "mov" | "movzx" | "movsx" | "movsxd" | "movabs" | "xchg" => Cost::new(1, 0.25),
+        "add" | "sub" | "and" | "or" | "xor" | "cmp" | "test" | "inc" | "dec" | "neg" | "not" => {
+            Cost::new(1, 0.25)
+        }
+        "lea" => Cost::new(1, 0.5),
+        "adc" | "sbb" | "shl" | "shr" | "sar" | "sal" | "rol" | "ror" | "bswap" => {
+            Cost::new(1, 0.5)
+        }
+        "shld" | "shrd" | "bt" | "bts" | "btr" | "btc" => Cost::new(3, 1.0),
+        "popcnt" | "lzcnt" | "tzcnt" | "bsf" | "bsr" => Cost::new(3, 1.0),
+        "imul" | "mul" => Cost::new(3, 1.0),
+        "div" | "idiv" => Cost::new(26, 6.0),
+        "push" => Cost::new(1, 1.0),
+        "pop" => Cost::new(2, 0.5),
+        "call" => Cost::new(2, 1.0),
+        "ret" => Cost::new(1, 1.0),
+        "jmp" => Cost::new(1, 1.0),
+        "cdq" | "cqo" | "cdqe" | "cwde" => Cost::new(1, 0.5),
+
+        // SSE and AVX
+        "movaps" | "movups" | "movapd" | "movupd" | "movdqa" | "movdqu" | "movdqa32"
+        | "movdqa64" | "movdqu8" | "movdqu32" | "movdqu64" | "movss" | "movsd" | "movd"
+        | "movq" => Cost::new(1, 0.33),
+        "xorps" | "xorpd" | "andps" | "andpd" | "andnps" | "andnpd" | "orps" | "orpd" | "pxor"
+        | "pand" | "pandn" | "por" => Cost::new(1, 0.33),
+        "paddb" | "paddw" | "paddd" | "paddq" | "psubb" | "psubw" | "psubd" | "psubq"
+        | "pcmpeqb" | "pcmpeqw" | "pcmpeqd" | "pcmpgtb" | "pcmpgtw" | "pcmpgtd" | "pminub"
+        | "pmaxub" => Cost::new(1, 0.33),
+        "pcmpeqq" | "pcmpgtq" => Cost::new(3, 1.0),
+        "pmullw" | "pmuludq" | "pmaddwd" => Cost::new(5, 0.5),
+        "pmulld" => Cost::new(10, 1.0),
+        "addps" | "addpd" | "addss" | "addsd" | "subps" | "subpd" | "subss" | "subsd" | "mulps"
+        | "mulpd" | "mulss" | "mulsd" | "minps" | "maxps" | "minss" | "maxss" => Cost::new(4, 0.5),
+        "divss" | "divps" => Cost::new(11, 3.0),
+        "divsd" | "divpd" => Cost::new(14, 4.0),
+        "sqrtss" | "sqrtps" => Cost::new(12, 3.0),
+        "sqrtsd" | "sqrtpd" => Cost::new(18, 6.0),
+        "ucomiss" | "ucomisd" | "comiss" | "comisd" => Cost::new(2, 1.0),
+        "shufps" | "shufpd" | "pshufd" | "pshufb" | "unpcklps" | "unpckhps" | "unpcklpd"
+        | "unpckhpd" | "punpcklbw" | "punpckldq" | "punpcklqdq" | "punpckhqdq" => Cost::new(1, 1.0),
+        "cvtsi2ss" | "cvtsi2sd" | "cvtss2sd" | "cvtsd2ss" | "cvttss2si" | "cvttsd2si"
+        | "cvtdq2ps" | "cvtps2dq" | "cvttps2dq" => Cost::new(4, 1.0),
+
+        // Conditional jumps, moves, and sets.
+        _ if mnemonic.starts_with("cmov") || mnemonic.starts_with("set") => Cost::new(1, 0.5),
+        _ if mnemonic.starts_with('j') => Cost::new(1, 0.5),
+        // Fused multiply-adds.
+        _ if mnemonic.starts_with("fmadd")
+            || mnemonic.starts_with("fmsub")
+            || mnemonic.starts_with("fnmadd")
+            || mnemonic.starts_with("fnmsub") =>
+        {
+            Cost::new(4, 0.5)
+        }
+        _ => return None,
+    };
+    Some(cost)
+}
+
+fn estimate_arm64(mnemonic: &str) -> Option<Cost> {
+    let cost = match mnemonic {
+        "nop" => Cost::new(0, 0.33),
+        "mov" | "movz" | "movn" | "movk" | "add" | "adds" | "sub" | "subs" | "and" | "ands"
+        | "orr" | "eor" | "bic" | "orn" | "mvn" | "neg" | "cmp" | "cmn" | "tst" | "adr"
+        | "adrp" | "csel" | "csinc" | "csinv" | "csneg" | "cset" | "csetm" | "cinc" => {
+            Cost::new(1, 0.5)
+        }
+        "lsl" | "lsr" | "asr" | "ror" | "ubfx" | "sbfx" | "ubfiz" | "sbfiz" | "bfi" | "bfxil"
+        | "uxtb" | "uxth" | "sxtb" | "sxth" | "sxtw" | "rev" | "clz" | "rbit" => Cost::new(1, 0.5),
+        "mul" | "madd" | "msub" | "mneg" | "smull" | "umull" | "smaddl" | "umaddl" => {
+            Cost::new(3, 1.0)
+        }
+        "smulh" | "umulh" => Cost::new(6, 4.0),
+        "sdiv" | "udiv" => Cost::new(12, 12.0),
+        "ldr" | "ldrb" | "ldrh" | "ldrsb" | "ldrsh" | "ldrsw" | "ldur" | "ldurb" | "ldurh"
+        | "ldp" | "ldpsw" | "ldar" | "ldaxr" | "ldxr" => Cost::new(4, 0.5),
+        "str" | "strb" | "strh" | "stur" | "sturb" | "sturh" | "stp" | "stlr" | "stlxr"
+        | "stxr" => Cost::new(1, 1.0),
+        "b" | "bl" | "br" | "blr" | "ret" | "cbz" | "cbnz" | "tbz" | "tbnz" => Cost::new(1, 1.0),
+        "fmov" => Cost::new(3, 0.5),
+        "fadd" | "fsub" | "fmul" | "fneg" | "fabs" | "fcmp" => Cost::new(4, 0.5),
+        "fmadd" | "fmsub" | "fnmadd" | "fnmsub" | "fmla" | "fmls" => Cost::new(7, 0.5),
+        "fdiv" => Cost::new(12, 8.0),
+        "fsqrt" => Cost::new(17, 14.0),
+        "scvtf" | "ucvtf" | "fcvtzs" | "fcvtzu" | "fcvt" => Cost::new(8, 1.0),
+        _ if mnemonic.starts_with("b.") => Cost::new(1, 1.0),
+        _ => return None,
+    };
+    Some(cost)
+}
@@ -0,0 +1,103 @@
+//! Downloads debug files and source files from the debuginfod servers in `DEBUGINFOD_URLS`
+//! using the build ID of a binary. Downloads are only made when the `debuginfod` feature
+//! is enabled, but the cache of the debuginfod client is always searched.
+
+use std::path::{Path, PathBuf};
+
+/// The directory that the debuginfod client caches downloaded files in.
+pub fn cache_dir() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("DEBUGINFOD_CACHE_PATH") {
+        return Some(PathBuf::from(path));
+    }
+    crate::util::user_cache_dir().map(|cache_dir| cache_dir.join("debuginfod_client"))
+}
+
+/// Returns the path of the cached debug file for a build ID, downloading it first if
+/// it is not in the cache.
+pub fn fetch_debuginfo(build_id: &str) -> Option<PathBuf> {
+    fetch(build_id, "debuginfo", "debuginfo")
+}
+
+/// Returns the path of a cached source file of the binary with the given build ID,
+/// downloading it first if it is not in the cache. `path` is the absolute path of the
+/// source file on the machine that the binary was built on.
+pub fn fetch_source(build_id: &str, path: &Path) -> Option<PathBuf> {
+    let path = path.to_str()?;
+    if !path.starts_with('/') {
+        return None;
+    }
+    // The client cache uses `#` in place of the separators in the source path.
+    let cache_name = format!("source{}", path.replace('/', "#"));
+    fetch(build_id, &cache_name, &format!("source{}", path))
+}
+
+#[cfg(feature = "debuginfod")]
+fn fetch(build_id: &str, cache_name: &str, artifact: &str) -> Option<PathBuf> {
+    use std::io::Write as _;
+
+    let cache_path = cache_dir()?.join(build_id).join(cache_name);
+    if cache_path.is_file() {
+        return Some(cache_path);
+    }
+
+    let urls = std::env::var("DEBUGINFOD_URLS").ok()?;
+    let timeout = std::env::var("DEBUGINFOD_TIMEOUT")
+        .ok()
+        .and_then(|timeout| timeout.parse().ok())
+        .unwrap_or(90);
+    let agent = ureq::AgentBuilder::new()
+        .timeout(std::time::Duration::from_secs(timeout))
+        .build();
+
+    for server in urls.split_whitespace() {
+        let url = format!(
+            "{}/buildid/{}/{}",
+            server.trim_end_matches('/'),
+            build_id,
+            artifact
+        );
+        log::info!("downloading `{}`", url);
+
+        let response = match agent.get(&url).call() {
+            Ok(response) => response,
+            Err(err) => {
+                log::debug!("failed to download `{}`: {}", url, err);
+                continue;
+            }
+        };
+
+        let download = || -> anyhow::Result<()> {
+            let cache_dir = cache_path
+                .parent()
+                .expect("cache path should have a parent");
+            std::fs::create_dir_all(cache_dir)?;
+
+            // Download to a temporary file first so that an interrupted download never
+            // ends up in the cache.
+            let mut partial_path = cache_path.clone().into_os_string();
+            partial_path.push(".partial");
+            let mut file = std::fs::File::create(&partial_path)?;
+            std::io::copy(&mut response.into_reader(), &mut file)?;
+            file.flush()?;
+            std::fs::rename(&partial_path, &cache_path)?;
+            Ok(())
+        };
+
+        match download() {
+            Ok(()) => return Some(cache_path),
+            Err(err) => log::warn!("failed to download `{}`: {:?}", url, err),
+        }
+    }
+
+    None
+}
+
+#[cfg(not(feature = "debuginfod"))]
+fn fetch(build_id: &str, cache_name: &str, _artifact: &str) -> Option<PathBuf> {
+    let cache_path = cache_dir()?.join(build_id).join(cache_name);
+    if cache_path.is_file() {
+        Some(cache_path)
+    } else {
+        None
+    }
+}
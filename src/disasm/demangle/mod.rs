@@ -0,0 +1,121 @@
+mod swift;
+
+use super::symbol::SymbolLang;
+use std::borrow::Cow;
+
+/// Demangles a symbol name, returning the demangled name and the language
+/// that the name was mangled for. If the name could not be demangled it is
+/// returned as is along with [`SymbolLang::Unknown`].
+pub fn demangle(name: Cow<'_, str>) -> (Cow<'_, str>, SymbolLang) {
+    use cpp_demangle::Symbol as CppSymbol;
+    use rustc_demangle::try_demangle;
+
+    if let Ok(demangled) = try_demangle(&name) {
+        return (format!("{:#}", demangled).into(), SymbolLang::Rust);
+    }
+
+    if let Some(demangled) = swift::demangle(&name) {
+        return (demangled.into(), SymbolLang::Swift);
+    }
+
+    // Microsoft mangled C++ names always start with a question mark.
+    if name.starts_with('?') {
+        use msvc_demangler::DemangleFlags;
+
+        if let Ok(demangled) = msvc_demangler::demangle(&name, DemangleFlags::llvm()) {
+            return (demangled.into(), SymbolLang::Cpp);
+        }
+    }
+
+    if let Ok(demangled) = CppSymbol::new(name.as_bytes()) {
+        return (demangled.to_string().into(), SymbolLang::Cpp);
+    }
+
+    (name, SymbolLang::Unknown)
+}
+
+/// Demangles a Rust symbol name with the hashes that `demangle` leaves out, which are the
+/// `::h0123456789abcdef` suffix of legacy names and the crate disambiguators of v0 names.
+pub fn demangle_rust_with_hashes(name: &str) -> Option<String> {
+    rustc_demangle::try_demangle(name)
+        .ok()
+        .map(|demangled| demangled.to_string())
+}
+
+/// Removes the hash from the end of a demangled legacy Rust name, e.g.
+/// `::h0123456789abcdef`, which `demangle` leaves out but `demangle_rust_with_hashes` and
+/// some other demanglers keep.
+pub fn strip_rust_hash(name: &str) -> &str {
+    match name.rfind("::h") {
+        Some(idx)
+            if name.len() - idx == 19
+                && name[(idx + 3)..].bytes().all(|b| b.is_ascii_hexdigit()) =>
+        {
+            &name[..idx]
+        }
+        _ => name,
+    }
+}
+
+/// Strips the decorations added to C names on 32-bit Windows by the
+/// stdcall (`_name@N`), fastcall (`@name@N`), and vectorcall (`name@@N`)
+/// calling conventions. `N` is the number of bytes used by the arguments. Names are only
+/// decorated like this on 32-bit Windows, so this isn't done by `demangle`.
+pub fn strip_c_decorations(name: &str) -> Option<&str> {
+    let (decorated, arg_bytes) = name.rsplit_once('@')?;
+    if arg_bytes.is_empty() || !arg_bytes.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    if let Some(fastcall) = decorated.strip_prefix('@') {
+        Some(fastcall)
+    } else if let Some(vectorcall) = decorated.strip_suffix('@') {
+        Some(vectorcall)
+    } else {
+        decorated.strip_prefix('_')
+    }
+    .filter(|undecorated| !undecorated.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn c_decorations() {
+        assert_eq!(strip_c_decorations("_WinMain@16"), Some("WinMain"));
+        assert_eq!(strip_c_decorations("@fastcall_fn@8"), Some("fastcall_fn"));
+        assert_eq!(
+            strip_c_decorations("vectorcall_fn@@24"),
+            Some("vectorcall_fn")
+        );
+        assert_eq!(strip_c_decorations("_cdecl_fn"), None);
+        assert_eq!(strip_c_decorations("memcpy@GLIBC_2.14"), None);
+        assert_eq!(strip_c_decorations("_@4"), None);
+    }
+
+    #[test]
+    fn rust_hashes() {
+        let mangled = "_ZN4core3fmt5write17h0123456789abcdefE";
+        let with_hash = demangle_rust_with_hashes(mangled).unwrap();
+        assert_eq!(with_hash, "core::fmt::write::h0123456789abcdef");
+        assert_eq!(strip_rust_hash(&with_hash), demangle(mangled.into()).0);
+        assert_eq!(strip_rust_hash("core::fmt::write"), "core::fmt::write");
+        assert_eq!(
+            strip_rust_hash("core::fmt::hello_world"),
+            "core::fmt::hello_world"
+        );
+        assert_eq!(
+            strip_rust_hash("main::h0123456789abcdeg"),
+            "main::h0123456789abcdeg"
+        );
+    }
+
+    #[test]
+    fn demangle_leaves_decorations() {
+        assert_eq!(
+            demangle("_WinMain@16".into()),
+            ("_WinMain@16".into(), SymbolLang::Unknown)
+        );
+    }
+}
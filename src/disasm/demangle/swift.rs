@@ -0,0 +1,129 @@
+//! A small demangler for Swift symbol names.
+//!
+//! This only recovers the context path of functions without parameters or a result
+//! (e.g. `main.Foo.bar()`) and of the type metadata of nominal types. Other symbols, such
+//! as functions with parameters, accessors, or anything with substitutions, are left
+//! mangled because their names would otherwise collide.
+
+/// Prefixes used for mangled Swift names. The leading underscore is added
+/// to all symbols on Mach-O.
+const PREFIXES: &[&str] = &["_$s", "$s", "_$S", "$S", "_$e", "$e", "_T0"];
+
+/// The suffixes of the symbols of nominal types and the prefixes of their demangled
+/// names.
+const TYPE_SYMBOLS: &[(&str, &str)] = &[
+    ("Ma", "type metadata accessor for "),
+    ("Mn", "nominal type descriptor for "),
+    ("N", "type metadata for "),
+];
+
+fn strip_prefix(name: &str) -> Option<&str> {
+    PREFIXES.iter().find_map(|prefix| name.strip_prefix(prefix))
+}
+
+pub fn demangle(name: &str) -> Option<String> {
+    let mut rest = strip_prefix(name)?.as_bytes();
+    let mut path: Vec<&str> = Vec::new();
+    let mut is_type = false;
+
+    loop {
+        match rest.first() {
+            // Identifiers are encoded as <length><characters>. A leading 0 is used
+            // for word substitutions and punycode which are not handled here.
+            Some(b'1'..=b'9') => {
+                let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+                let len: usize = std::str::from_utf8(&rest[..digits]).ok()?.parse().ok()?;
+                let ident = rest.get(digits..(digits + len))?;
+                path.push(std::str::from_utf8(ident).ok()?);
+                rest = &rest[(digits + len)..];
+                is_type = false;
+            }
+
+            // Nominal types: class, struct, enum, and protocol.
+            Some(b'C') | Some(b'V') | Some(b'O') | Some(b'P') if path.len() > 1 && !is_type => {
+                rest = &rest[1..];
+                is_type = true;
+            }
+
+            _ => break,
+        }
+    }
+
+    // The module is the first identifier, so there has to be at least one more.
+    if path.len() < 2 {
+        return None;
+    }
+
+    let path = path.join(".");
+    if is_type {
+        let (_, prefix) = TYPE_SYMBOLS
+            .iter()
+            .find(|(suffix, _)| rest == suffix.as_bytes())?;
+        Some(format!("{}{}", prefix, path))
+    } else if rest == b"yyF" {
+        // A function entity with the type `() -> ()`.
+        Some(format!("{}()", path))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn functions() {
+        assert_eq!(
+            demangle("$s4main5helloyyF").as_deref(),
+            Some("main.hello()")
+        );
+        assert_eq!(
+            demangle("_$s4main3FooV3baryyF").as_deref(),
+            Some("main.Foo.bar()")
+        );
+        assert_eq!(
+            demangle("$s4main3FooC5InnerO5resetyyF").as_deref(),
+            Some("main.Foo.Inner.reset()")
+        );
+    }
+
+    #[test]
+    fn type_metadata() {
+        assert_eq!(
+            demangle("$s4main3FooVMa").as_deref(),
+            Some("type metadata accessor for main.Foo")
+        );
+        assert_eq!(
+            demangle("_$s4main3FooCMn").as_deref(),
+            Some("nominal type descriptor for main.Foo")
+        );
+        assert_eq!(
+            demangle("$s4main3FooON").as_deref(),
+            Some("type metadata for main.Foo")
+        );
+    }
+
+    #[test]
+    fn unsupported_suffixes() {
+        // A function with an `Int` parameter, which would be an overload of `bar()`.
+        assert_eq!(demangle("$s4main3FooV3baryySiF"), None);
+        // A function that returns an `Int`.
+        assert_eq!(demangle("$s4main3FooV3barSiyF"), None);
+        // The getter of a property.
+        assert_eq!(demangle("$s4main3FooC4sizeSivg"), None);
+        // A static function.
+        assert_eq!(demangle("$s4main3FooV3baryyFZ"), None);
+        // The value witness table of a type.
+        assert_eq!(demangle("$s4main3FooVWV"), None);
+        // A word substitution.
+        assert_eq!(demangle("$s4main12FooContainerV03barB0yyF"), None);
+    }
+
+    #[test]
+    fn not_swift() {
+        assert_eq!(demangle("main"), None);
+        assert_eq!(demangle("$s4main"), None);
+        assert_eq!(demangle("_ZN4main3foo17h0123456789abcdefE"), None);
+    }
+}
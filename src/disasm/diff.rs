@@ -0,0 +1,295 @@
+use super::tls::destination;
+use super::{DisasmLine, Disassembly};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+
+/// Diffs larger than this (in instructions of the old symbol times instructions of the
+/// new symbol, after removing the common prefix and suffix) are not aligned and are
+/// reported as entirely removed and added instead.
+const MAX_ALIGNMENT_CELLS: usize = 1 << 24;
+
+/// How many instructions after an AArch64 `adrp` are searched for the instruction that adds
+/// the offset into the page.
+const PAGE_OFFSET_WINDOW: usize = 4;
+
+/// Symbolic names that only change because the layout of the binary changed: the offsets
+/// from the start of a section (`.rodata+0x1234`, `.plt.sec+0x20`, and the addends of
+/// relocations against sections) and the hashes and counters of anonymous constants
+/// (`anon.8f1c2e6b7d0a9e1f5c3b4a2d6e8f0c1a.12`).
+static LAYOUT_NAMES: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?P<anon>(?:\.L|l_)?anon\.)[0-9a-f]+\.[0-9]+|(?P<section>\.[A-Za-z_][\w.]*)[+-]0x[0-9a-fA-F]+",
+    )
+    .unwrap()
+});
+
+/// How an instruction of the old or new disassembly was changed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DiffOp {
+    /// The instruction at this index in the old disassembly is the same as the one at
+    /// this index in the new disassembly.
+    Same(usize, usize),
+    /// The instruction at this index in the old disassembly was removed.
+    Removed(usize),
+    /// The instruction at this index in the new disassembly was added.
+    Added(usize),
+    /// The instruction at this index in the old disassembly was replaced with an instruction
+    /// with the same mnemonic but different operands.
+    Changed(usize, usize),
+}
+
+/// The number of instructions that differ between two disassemblies.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct DiffStats {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+}
+
+/// The alignment of the instructions of the same symbol in two builds of a binary.
+///
+/// Instructions are compared without the addresses that are expected to move between
+/// builds: jumps inside of the symbol are compared by their mnemonic only, calls and jumps
+/// to other symbols are compared by the name of the target (with calls through PLT slots
+/// compared by the function they call), and large hexadecimal operands, PC relative
+/// displacements, the offsets into pages that follow an AArch64 `adrp`, and the names and
+/// addends that depend on the layout of sections are ignored.
+pub struct DisasmDiff {
+    ops: Vec<DiffOp>,
+}
+
+impl DisasmDiff {
+    pub fn new(old: &Disassembly, new: &Disassembly) -> DisasmDiff {
+        let diff_timer = std::time::Instant::now();
+        let old_keys = normalize_lines(old);
+        let new_keys = normalize_lines(new);
+
+        let prefix = old_keys
+            .iter()
+            .zip(new_keys.iter())
+            .take_while(|(o, n)| o == n)
+            .count();
+        let suffix = old_keys[prefix..]
+            .iter()
+            .rev()
+            .zip(new_keys[prefix..].iter().rev())
+            .take_while(|(o, n)| o == n)
+            .count();
+
+        let mut ops = Vec::with_capacity(old_keys.len().max(new_keys.len()));
+        ops.extend((0..prefix).map(|idx| DiffOp::Same(idx, idx)));
+        align(
+            &old_keys[prefix..old_keys.len() - suffix],
+            &new_keys[prefix..new_keys.len() - suffix],
+            prefix,
+            prefix,
+            &mut ops,
+        );
+        let old_suffix = old_keys.len() - suffix;
+        let new_suffix = new_keys.len() - suffix;
+        ops.extend((0..suffix).map(|idx| DiffOp::Same(old_suffix + idx, new_suffix + idx)));
+
+        let ops = pair_changes(ops, old, new);
+        log::trace!(
+            "diffed {} and {} instructions in {}",
+            old_keys.len(),
+            new_keys.len(),
+            crate::util::DurationDisplay(diff_timer.elapsed())
+        );
+        DisasmDiff { ops }
+    }
+
+    /// The instructions of both disassemblies in order.
+    pub fn ops(&self) -> &[DiffOp] {
+        &self.ops
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.ops.iter().any(|op| !matches!(op, DiffOp::Same(..)))
+    }
+
+    pub fn stats(&self) -> DiffStats {
+        let mut stats = DiffStats::default();
+        for op in self.ops.iter() {
+            match op {
+                DiffOp::Same(..) => {}
+                DiffOp::Removed(_) => stats.removed += 1,
+                DiffOp::Added(_) => stats.added += 1,
+                DiffOp::Changed(..) => stats.changed += 1,
+            }
+        }
+        stats
+    }
+}
+
+/// Finds the longest common subsequence of the old and new instructions and pushes the
+/// operations that turn one into the other.
+fn align(old: &[String], new: &[String], old_base: usize, new_base: usize, ops: &mut Vec<DiffOp>) {
+    let (n, m) = (old.len(), new.len());
+    if n.saturating_mul(m) > MAX_ALIGNMENT_CELLS {
+        log::warn!(
+            "not aligning {} and {} instructions because the difference is too large",
+            n,
+            m
+        );
+        ops.extend((0..n).map(|idx| DiffOp::Removed(old_base + idx)));
+        ops.extend((0..m).map(|idx| DiffOp::Added(new_base + idx)));
+        return;
+    }
+
+    // lengths[i * (m + 1) + j] is the length of the longest common subsequence of
+    // old[i..] and new[j..].
+    let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i * (m + 1) + j] = if old[i] == new[j] {
+                lengths[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+                lengths[(i + 1) * (m + 1) + j].max(lengths[i * (m + 1) + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            ops.push(DiffOp::Same(old_base + i, new_base + j));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * (m + 1) + j] >= lengths[i * (m + 1) + j + 1] {
+            ops.push(DiffOp::Removed(old_base + i));
+            i += 1;
+        } else {
+            ops.push(DiffOp::Added(new_base + j));
+            j += 1;
+        }
+    }
+    ops.extend((i..n).map(|idx| DiffOp::Removed(old_base + idx)));
+    ops.extend((j..m).map(|idx| DiffOp::Added(new_base + idx)));
+}
+
+/// Replaces removed and added instructions with the same mnemonic at the same position in
+/// a run of differences with a single changed instruction.
+fn pair_changes(ops: Vec<DiffOp>, old: &Disassembly, new: &Disassembly) -> Vec<DiffOp> {
+    let mut paired = Vec::with_capacity(ops.len());
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+
+    let flush = |removed: &mut Vec<usize>, added: &mut Vec<usize>, paired: &mut Vec<DiffOp>| {
+        for idx in 0..removed.len().max(added.len()) {
+            match (removed.get(idx), added.get(idx)) {
+                (Some(&o), Some(&n)) if old.lines()[o].mnemonic() == new.lines()[n].mnemonic() => {
+                    paired.push(DiffOp::Changed(o, n))
+                }
+                (o, n) => {
+                    paired.extend(o.map(|&o| DiffOp::Removed(o)));
+                    paired.extend(n.map(|&n| DiffOp::Added(n)));
+                }
+            }
+        }
+        removed.clear();
+        added.clear();
+    };
+
+    for op in ops {
+        match op {
+            DiffOp::Removed(o) => removed.push(o),
+            DiffOp::Added(n) => added.push(n),
+            op => {
+                flush(&mut removed, &mut added, &mut paired);
+                paired.push(op);
+            }
+        }
+    }
+    flush(&mut removed, &mut added, &mut paired);
+    paired
+}
+
+/// Returns the text of each instruction without the addresses that change between builds.
+fn normalize_lines(disassembly: &Disassembly) -> Vec<String> {
+    let lines = disassembly.lines();
+    lines
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            let is_page_offset = lines[idx.saturating_sub(PAGE_OFFSET_WINDOW)..idx]
+                .iter()
+                .filter(|prev| prev.mnemonic() == "adrp")
+                .any(|prev| uses_register(line.operands(), destination(prev.operands())));
+            normalize(line, is_page_offset)
+        })
+        .collect()
+}
+
+/// Returns true if a register is a source or the base of a memory operand, e.g. `x8` in
+/// `add x0, x8, #0x10` or `ldr x0, [x8, #0x10]`.
+fn uses_register(operands: &str, register: &str) -> bool {
+    !register.is_empty()
+        && operands
+            .split([',', '['])
+            .skip(1)
+            .any(|operand| operand.trim() == register)
+}
+
+/// Returns the text of an instruction without the addresses that change between builds.
+/// The immediates of instructions that add the offset into a page to its address are
+/// ignored with `is_page_offset`.
+fn normalize(line: &DisasmLine, is_page_offset: bool) -> String {
+    if line.jump().is_internal() {
+        return line.mnemonic().to_owned();
+    }
+    if line.is_symbolicated_jump() {
+        return format!(
+            "{} <{}>",
+            line.mnemonic(),
+            normalize_names(line.comments()).trim_end_matches("@plt")
+        );
+    }
+
+    let operands = line.operands();
+    let is_pc_relative = operands.contains("rip") || operands.contains("pc") || is_page_offset;
+    let mut normalized = String::with_capacity(line.mnemonic().len() + operands.len() + 1);
+    normalized.push_str(line.mnemonic());
+    normalized.push(' ');
+
+    let mut rest = operands;
+    while let Some(start) = rest.find("0x") {
+        let digits = rest[start + 2..]
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .map(|len| start + 2 + len)
+            .unwrap_or(rest.len());
+        let is_address = u64::from_str_radix(&rest[start + 2..digits], 16)
+            .map(|value| value >= 0x10000)
+            .unwrap_or(true);
+
+        normalized.push_str(&rest[..start]);
+        if is_pc_relative || is_address {
+            normalized.push_str("0x?");
+        } else {
+            normalized.push_str(&rest[start..digits]);
+        }
+        rest = &rest[digits..];
+    }
+    normalized.push_str(rest);
+
+    // String literals and relocation targets are compared by what they refer to.
+    if !line.comments().is_empty() {
+        normalized.push_str(" ; ");
+        normalized.push_str(line.comments());
+    }
+    normalize_names(&normalized)
+}
+
+/// Replaces the parts of symbolic names that depend on the layout of the binary, see
+/// `LAYOUT_NAMES`.
+fn normalize_names(text: &str) -> String {
+    LAYOUT_NAMES
+        .replace_all(text, |caps: &Captures| {
+            if let Some(anon) = caps.name("anon") {
+                format!("{}?", anon.as_str())
+            } else {
+                format!("{}+0x?", &caps["section"])
+            }
+        })
+        .into_owned()
+}
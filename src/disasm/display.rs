@@ -41,15 +41,18 @@ impl DisasmDisplayMeasure {
         ((64 - self.max_address.leading_zeros()) as f64 / 4.0).ceil() as usize
     }
 
-    /// Returns the maximum width of of bytes that need to be displayed
-    /// in hexidecimal characters. An optional `spacing` can be passed
-    /// to indicate the number of space characters that will be between each byte.
+    /// Returns the maximum width of the bytes that need to be displayed in hexadecimal
+    /// characters when they are displayed in groups of `group_size` bytes with a space
+    /// between each group.
     #[inline]
-    pub fn max_bytes_width_hex(&self, spacing: usize) -> usize {
+    pub fn max_bytes_width_grouped(&self, group_size: usize) -> usize {
         if self.max_bytes_count == 0 {
             return 0;
         }
-        (self.max_bytes_count as usize * 2) + (self.max_bytes_count as usize - 1) * spacing
+        let group_size = group_size.max(1);
+        let count = self.max_bytes_count as usize;
+        let groups = (count - 1) / group_size + 1;
+        (count * 2) + (groups - 1)
     }
 
     #[inline]
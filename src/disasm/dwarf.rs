@@ -1,9 +1,14 @@
 use crate::disasm::binary::BinaryData;
+use crate::disasm::demangle;
+use crate::disasm::dwarf_expr::{self, Location, Value};
+use crate::disasm::source::{InlinedCall, SourceLocation};
 use crate::disasm::symbol::{Symbol, SymbolSource};
+use crate::disasm::vars::{VariableLocation, VariableSlot};
 use crate::util;
 use anyhow::Context as _;
-use gimli::{read::EndianReader, Dwarf, RunTimeEndian};
+use gimli::{read::EndianReader, Dwarf, Reader as _, RunTimeEndian};
 use once_cell::unsync::OnceCell;
+use std::collections::HashMap;
 use std::ops::Range;
 use std::path::{Path, PathBuf};
 
@@ -270,6 +275,62 @@ impl DwarfInfo {
         Ok(())
     }
 
+    pub fn compilation_units_loaded(&self) -> bool {
+        self.compilation_units_initialized
+    }
+
+    /// The address ranges of the compilation units and their indices, sorted by address.
+    pub fn compilation_unit_ranges(&self) -> &[(Range<u64>, usize)] {
+        &self.compilation_unit_ranges
+    }
+
+    /// Loads the compilation units using address ranges that were found by an earlier
+    /// call to `ensure_compilation_units` (e.g. from a cache) instead of reading them from
+    /// the debug information again.
+    pub fn load_compilation_units_with_ranges(
+        &mut self,
+        ranges: Vec<(Range<u64>, usize)>,
+    ) -> anyhow::Result<()> {
+        self.compilation_units_initialized = true;
+
+        let mut unit_headers = self.dwarf.units();
+        while let Some(unit_header) = unit_headers
+            .next()
+            .context("error while reading DWARF compilation units")?
+        {
+            let unit = if let Ok(unit) = self.dwarf.unit(unit_header) {
+                unit
+            } else {
+                continue;
+            };
+
+            // The units are skipped the same way as in `add_compilation_unit` so that the
+            // indices in the ranges refer to the same units.
+            let tag = unit
+                .entries_raw(None)?
+                .read_abbreviation()?
+                .map(|abbrev| abbrev.tag());
+            if tag == Some(gimli::DW_TAG_compile_unit) || tag == Some(gimli::DW_TAG_skeleton_unit) {
+                // The language isn't used for anything so it isn't cached.
+                self.compilation_units
+                    .push(LazyCompilationUnit::new(unit, None));
+            }
+        }
+
+        if ranges
+            .iter()
+            .all(|&(_, unit_idx)| unit_idx < self.compilation_units.len())
+        {
+            self.compilation_unit_ranges = ranges;
+            return Ok(());
+        }
+
+        log::debug!("cached DWARF compilation unit ranges are invalid");
+        self.compilation_units.clear();
+        self.compilation_units_initialized = false;
+        self.ensure_compilation_units()
+    }
+
     #[cold]
     fn find_compilation_units(
         dwarf: &Dwarf<BinaryDataReader>,
@@ -314,7 +375,12 @@ impl DwarfInfo {
         let mut entries = unit.entries_raw(None)?;
 
         let abbrev = match entries.read_abbreviation()? {
-            Some(abbrev) if abbrev.tag() == gimli::DW_TAG_compile_unit => abbrev,
+            Some(abbrev)
+                if abbrev.tag() == gimli::DW_TAG_compile_unit
+                    || abbrev.tag() == gimli::DW_TAG_skeleton_unit =>
+            {
+                abbrev
+            }
             _ => return Ok(()),
         };
 
@@ -328,14 +394,13 @@ impl DwarfInfo {
             let attr = entries.read_attribute(*spec)?;
 
             match attr.name() {
+                // Skeleton units may use indices into .debug_addr for their addresses.
                 gimli::DW_AT_low_pc => {
-                    if let gimli::AttributeValue::Addr(val) = attr.value() {
-                        start_addr = Some(val);
-                    }
+                    start_addr = dwarf.attr_address(&unit, attr.value())?;
                 }
 
                 gimli::DW_AT_high_pc => {
-                    if let gimli::AttributeValue::Addr(val) = attr.value() {
+                    if let Some(val) = dwarf.attr_address(&unit, attr.value())? {
                         end_addr = Some(val);
                     } else if let Some(val) = attr.udata_value() {
                         size = Some(val);
@@ -372,23 +437,346 @@ impl DwarfInfo {
         Ok(())
     }
 
+    /// Finds the compilation units whose debugging information was split out into
+    /// `.dwo` files.
+    pub fn skeleton_units(&self) -> anyhow::Result<Vec<SkeletonUnit>> {
+        let mut skeletons = Vec::new();
+        let mut unit_headers = self.dwarf.units();
+
+        while let Some(unit_header) = unit_headers
+            .next()
+            .context("error while reading DWARF compilation units")?
+        {
+            let unit = if let Ok(unit) = self.dwarf.unit(unit_header) {
+                unit
+            } else {
+                continue;
+            };
+
+            if let Some(skeleton) = Self::skeleton_unit(&self.dwarf, &unit)
+                .context("error while reading skeleton compilation unit")?
+            {
+                skeletons.push(skeleton);
+            }
+        }
+
+        Ok(skeletons)
+    }
+
+    fn skeleton_unit(
+        dwarf: &Dwarf<BinaryDataReader>,
+        unit: &gimli::Unit<BinaryDataReader>,
+    ) -> Result<Option<SkeletonUnit>, gimli::Error> {
+        let mut entries = unit.entries_raw(None)?;
+        let abbrev = match entries.read_abbreviation()? {
+            Some(abbrev) => abbrev,
+            None => return Ok(None),
+        };
+
+        let mut dwo_name = None;
+        let mut comp_dir = None;
+        let mut dwo_id = None;
+        let mut addr_base = unit.addr_base;
+
+        for spec in abbrev.attributes() {
+            let attr = entries.read_attribute(*spec)?;
+            match attr.name() {
+                gimli::DW_AT_dwo_name | gimli::DW_AT_GNU_dwo_name => {
+                    let name = dwarf.attr_string(unit, attr.value())?;
+                    dwo_name = Some(PathBuf::from(&*name.to_string_lossy()?));
+                }
+                gimli::DW_AT_comp_dir => {
+                    let dir = dwarf.attr_string(unit, attr.value())?;
+                    comp_dir = Some(PathBuf::from(&*dir.to_string_lossy()?));
+                }
+                gimli::DW_AT_GNU_dwo_id => dwo_id = attr.udata_value(),
+                gimli::DW_AT_addr_base | gimli::DW_AT_GNU_addr_base => match attr.value() {
+                    gimli::AttributeValue::DebugAddrBase(base) => addr_base = base,
+                    gimli::AttributeValue::SecOffset(offset) => {
+                        addr_base = gimli::DebugAddrBase(offset)
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+
+        Ok(dwo_name.map(|dwo_name| SkeletonUnit {
+            dwo_name,
+            comp_dir,
+            dwo_id,
+            addr_base,
+        }))
+    }
+
+    /// Loads the symbols from the split DWARF object of a skeleton unit. `loader` provides
+    /// the split object's sections, which are the ones with names ending in `.dwo`.
+    pub fn load_split_symbols<L, F>(
+        &self,
+        skeleton: &SkeletonUnit,
+        loader: L,
+        symbols: &mut Vec<Symbol>,
+        addr_to_offset: F,
+    ) -> anyhow::Result<()>
+    where
+        L: Fn(gimli::SectionId) -> anyhow::Result<BinaryDataReader>,
+        F: Fn(u64) -> Option<usize>,
+    {
+        let mut dwo = gimli::Dwarf::load(&loader, &loader)?;
+        // Addresses in split units are indices into the skeleton's address table.
+        dwo.debug_addr = self.dwarf.debug_addr.clone();
+
+        let mut unit_headers = dwo.units();
+        let mut name_chain = NameChain::new();
+        while let Some(unit_header) = unit_headers
+            .next()
+            .context("error while reading split DWARF compilation units")?
+        {
+            let mut unit = dwo
+                .unit(unit_header)
+                .context("error while reading split DWARF compilation unit")?;
+            unit.addr_base = skeleton.addr_base;
+            // DWARF 5 string offsets tables start with a header.
+            if unit.header.version() >= 5 && unit.str_offsets_base.0 == 0 {
+                unit.str_offsets_base = gimli::DebugStrOffsetsBase(8);
+            }
+
+            Self::load_symbols_from_unit(&dwo, &unit, symbols, &addr_to_offset, &mut name_chain)
+                .context("failed to load symbols from split compilation unit")?;
+        }
+
+        Ok(())
+    }
+
     pub fn addr2line(
         &self,
         addr: u64,
     ) -> anyhow::Result<Option<impl '_ + Iterator<Item = (&Path, u32)>>> {
-        let range_idx = if let Ok(idx) = self
-            .compilation_unit_ranges
-            .binary_search_by(|&(ref probe, _)| util::cmp_range_to_idx(probe, addr))
-        {
-            idx
+        let unit = if let Some(unit) = self.unit_for_addr(addr) {
+            unit
         } else {
             return Ok(None);
         };
-        let unit_idx = self.compilation_unit_ranges[range_idx].1 as usize;
-        let unit = &self.compilation_units[unit_idx];
         let lines = unit.lines(&self.dwarf)?;
         Ok(lines.lines_for_addr(addr))
     }
+
+    /// Returns the inlined calls with an address range that starts at the given address,
+    /// ordered from the outermost call to the innermost one.
+    pub fn inlined_calls_at(
+        &self,
+        addr: u64,
+    ) -> anyhow::Result<Option<impl '_ + Iterator<Item = &InlinedCall>>> {
+        let unit = if let Some(unit) = self.unit_for_addr(addr) {
+            unit
+        } else {
+            return Ok(None);
+        };
+        let inlines = unit.inlines(&self.dwarf)?;
+        Ok(Some(inlines.calls_starting_at(addr)))
+    }
+
+    /// Returns the locations of the local variables and parameters of the function that
+    /// starts at the given address. The compilation units must be loaded first.
+    pub fn variable_locations(&self, addr: u64) -> anyhow::Result<Vec<VariableLocation>> {
+        let unit = if let Some(unit) = self.unit_for_addr(addr) {
+            unit
+        } else {
+            return Ok(Vec::new());
+        };
+        unit.variable_locations(&self.dwarf, addr)
+            .context("failed to read the locations of variables")
+    }
+
+    /// Loads the symbols of the compilation unit that contains the given address unless
+    /// they were already loaded by an earlier call. The compilation units must be loaded
+    /// first.
+    pub fn load_symbols_at<F>(
+        &mut self,
+        addr: u64,
+        symbols: &mut Vec<Symbol>,
+        addr_to_offset: F,
+    ) -> anyhow::Result<()>
+    where
+        F: Fn(u64) -> Option<usize>,
+    {
+        let unit = match self.unit_index_for_addr(addr) {
+            Some(unit_idx) => &mut self.compilation_units[unit_idx],
+            None => return Ok(()),
+        };
+        if unit.symbols_loaded {
+            return Ok(());
+        }
+        unit.symbols_loaded = true;
+
+        Self::load_symbols_from_unit(
+            &self.dwarf,
+            &unit.unit,
+            symbols,
+            &addr_to_offset,
+            &mut NameChain::new(),
+        )
+        .context("failed to load symbols from compilation unit")
+    }
+
+    /// Returns the crate of the Rust compilation unit that contains an address. This is
+    /// only known after the compilation units were loaded.
+    pub fn crate_name_at(&self, addr: u64) -> Option<&str> {
+        self.unit_for_addr(addr)?.crate_name.as_deref()
+    }
+
+    fn unit_for_addr(&self, addr: u64) -> Option<&LazyCompilationUnit> {
+        let unit_idx = self.unit_index_for_addr(addr)?;
+        Some(&self.compilation_units[unit_idx])
+    }
+
+    fn unit_index_for_addr(&self, addr: u64) -> Option<usize> {
+        let range_idx = self
+            .compilation_unit_ranges
+            .binary_search_by(|(probe, _)| util::cmp_range_to_idx(probe, addr))
+            .ok()?;
+        Some(self.compilation_unit_ranges[range_idx].1)
+    }
+}
+
+/// Returns the crate of a Rust compilation unit from its name, which ends with the name of
+/// the codegen unit, e.g. `my_crate` for `src/lib.rs/@/my_crate.1a2b3c4d-cgu.0`.
+fn unit_crate_name(name: &str) -> Option<&str> {
+    let (_, codegen_unit) = name.rsplit_once("/@/")?;
+    let crate_name = codegen_unit.split('.').next()?;
+    if crate_name.is_empty() {
+        None
+    } else {
+        Some(crate_name)
+    }
+}
+
+/// A compilation unit that was compiled with `-gsplit-dwarf`. Only a skeleton of the unit
+/// is left in the binary and the rest of its debugging information is in a `.dwo` file, or
+/// in a `.dwp` package that combines all of the `.dwo` files.
+pub struct SkeletonUnit {
+    dwo_name: PathBuf,
+    comp_dir: Option<PathBuf>,
+    dwo_id: Option<u64>,
+    addr_base: gimli::DebugAddrBase<usize>,
+}
+
+impl SkeletonUnit {
+    /// The ID that is used to find the unit in a DWARF package.
+    pub fn dwo_id(&self) -> Option<u64> {
+        self.dwo_id
+    }
+
+    /// Searches for the `.dwo` file of this unit. The path in the skeleton is relative
+    /// to the directory that the unit was compiled in, but the build directory may have
+    /// been moved since then so the binary's directory is also searched.
+    pub fn locate_dwo(&self, binary_dir: Option<&Path>) -> Option<PathBuf> {
+        let mut candidates = Vec::with_capacity(4);
+        match self.comp_dir {
+            Some(ref comp_dir) => candidates.push(comp_dir.join(&self.dwo_name)),
+            None => candidates.push(self.dwo_name.clone()),
+        }
+
+        if let (Some(dir), Some(file_name)) = (binary_dir, self.dwo_name.file_name()) {
+            candidates.push(dir.join(&self.dwo_name));
+            candidates.push(dir.join(file_name));
+            candidates.push(dir.join("deps").join(file_name));
+        }
+
+        candidates.into_iter().find(|path| path.is_file())
+    }
+}
+
+impl std::fmt::Display for SkeletonUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.dwo_name.display())
+    }
+}
+
+/// The index of a DWARF package (`.dwp`), which says where the contributions of each split
+/// compilation unit are in the package's sections.
+pub struct DwarfPackage {
+    /// The section IDs of the columns in the index.
+    columns: Vec<u32>,
+    /// Maps a DWO ID to the offset and size of the unit's contribution for each column.
+    units: HashMap<u64, Vec<Range<usize>>>,
+}
+
+impl DwarfPackage {
+    /// Parses the contents of the package's `.debug_cu_index` section.
+    pub fn parse(mut index: BinaryDataReader) -> Result<DwarfPackage, gimli::Error> {
+        use gimli::Reader as _;
+
+        // Version 2 is the GNU extension and version 5 is the standard format, which has a
+        // 2 byte version followed by 2 bytes of padding.
+        let version = index.read_u32()?;
+        if version != 2 && version != 5 && version >> 16 != 5 {
+            return Err(gimli::Error::UnknownVersion(u64::from(version)));
+        }
+        let column_count = index.read_u32()? as usize;
+        let unit_count = index.read_u32()? as usize;
+        let slot_count = index.read_u32()? as usize;
+
+        let mut signatures = Vec::with_capacity(slot_count);
+        for _ in 0..slot_count {
+            signatures.push(index.read_u64()?);
+        }
+        let mut rows = Vec::with_capacity(slot_count);
+        for _ in 0..slot_count {
+            rows.push(index.read_u32()? as usize);
+        }
+
+        let mut columns = Vec::with_capacity(column_count);
+        for _ in 0..column_count {
+            columns.push(index.read_u32()?);
+        }
+        let mut offsets = Vec::with_capacity(unit_count * column_count);
+        for _ in 0..(unit_count * column_count) {
+            offsets.push(index.read_u32()? as usize);
+        }
+        let mut sizes = Vec::with_capacity(unit_count * column_count);
+        for _ in 0..(unit_count * column_count) {
+            sizes.push(index.read_u32()? as usize);
+        }
+
+        let mut units = HashMap::with_capacity(unit_count);
+        for (&signature, &row) in signatures.iter().zip(rows.iter()) {
+            // Rows are 1-based and 0 marks an empty slot.
+            if row == 0 || row > unit_count {
+                continue;
+            }
+            let start = (row - 1) * column_count;
+            let ranges = (start..(start + column_count))
+                .map(|idx| offsets[idx]..(offsets[idx] + sizes[idx]))
+                .collect();
+            units.insert(signature, ranges);
+        }
+
+        Ok(DwarfPackage { columns, units })
+    }
+
+    /// Returns the range of a section in the package that belongs to the unit with the given
+    /// DWO ID. Sections that are shared by every unit (e.g. `.debug_str.dwo`) are returned
+    /// whole, and `section_len` is the length of the section.
+    pub fn section_range(
+        &self,
+        dwo_id: u64,
+        section: gimli::SectionId,
+        section_len: usize,
+    ) -> Option<Range<usize>> {
+        let column_id = match section {
+            gimli::SectionId::DebugInfo => 1,
+            gimli::SectionId::DebugAbbrev => 3,
+            gimli::SectionId::DebugLine => 4,
+            gimli::SectionId::DebugStrOffsets => 6,
+            _ => return Some(0..section_len),
+        };
+
+        match self.columns.iter().position(|&id| id == column_id) {
+            Some(column) => self.units.get(&dwo_id).map(|ranges| ranges[column].clone()),
+            None => Some(0..0),
+        }
+    }
 }
 
 pub struct LazyCompilationUnit {
@@ -399,6 +787,13 @@ pub struct LazyCompilationUnit {
     lang: Option<gimli::DwLang>,
 
     lines: OnceCell<Lines>,
+    inlines: OnceCell<Inlines>,
+
+    /// The crate of a Rust compilation unit, from the name of the unit.
+    crate_name: Option<Box<str>>,
+
+    /// True if the symbols of this unit were loaded by `DwarfInfo::load_symbols_at`.
+    symbols_loaded: bool,
 }
 
 impl LazyCompilationUnit {
@@ -406,10 +801,18 @@ impl LazyCompilationUnit {
         unit: gimli::Unit<BinaryDataReader>,
         lang: Option<gimli::DwLang>,
     ) -> LazyCompilationUnit {
+        let crate_name = unit
+            .name
+            .as_ref()
+            .and_then(|name| name.to_string_lossy().ok())
+            .and_then(|name| unit_crate_name(&name).map(Box::from));
         LazyCompilationUnit {
             unit,
             lang,
             lines: OnceCell::new(),
+            inlines: OnceCell::new(),
+            crate_name,
+            symbols_loaded: false,
         }
     }
 
@@ -514,6 +917,287 @@ impl LazyCompilationUnit {
             files: files.into_boxed_slice(),
         })
     }
+
+    fn inlines(&self, dwarf: &Dwarf<BinaryDataReader>) -> Result<&Inlines, gimli::Error> {
+        self.inlines.get_or_try_init(|| {
+            let load_inlines_timer = std::time::Instant::now();
+            let inlines = self.load_inlines(dwarf);
+            if let Ok(ref inlines) = inlines {
+                log::trace!(
+                    "loaded {} inlined ranges from DWARF debug information in {}",
+                    inlines.ranges.len(),
+                    util::DurationDisplay(load_inlines_timer.elapsed())
+                );
+            }
+            inlines
+        })
+    }
+
+    fn load_inlines(&self, dwarf: &Dwarf<BinaryDataReader>) -> Result<Inlines, gimli::Error> {
+        // The files referenced by DW_AT_call_file are the ones from the line program.
+        let lines = self.lines(dwarf)?;
+
+        let mut ranges = Vec::new();
+        let mut entries = self.unit.entries();
+        let mut depth = 0;
+
+        while let Some((delta_depth, entry)) = entries.next_dfs()? {
+            depth += delta_depth;
+
+            if entry.tag() != gimli::DW_TAG_inlined_subroutine {
+                continue;
+            }
+
+            // FIXME abstract origins can also be in other compilation units
+            //       (DW_FORM_ref_addr), which happens a lot with LTO. Those are just skipped
+            //       for now.
+            let name = match entry.attr_value(gimli::DW_AT_abstract_origin)? {
+                Some(gimli::AttributeValue::UnitRef(offset)) => self.entry_name(dwarf, offset)?,
+                _ => None,
+            };
+            let name = if let Some(name) = name {
+                name
+            } else {
+                continue;
+            };
+
+            let call_file = entry
+                .attr(gimli::DW_AT_call_file)?
+                .and_then(|attr| match attr.value() {
+                    gimli::AttributeValue::FileIndex(idx) => Some(idx),
+                    _ => attr.udata_value(),
+                })
+                .and_then(|idx| lines.files.get(idx as usize));
+            let call_line = entry
+                .attr(gimli::DW_AT_call_line)?
+                .and_then(|attr| attr.udata_value());
+            let call_location = match (call_file, call_line) {
+                (Some(path), Some(line)) => Some(SourceLocation::new(path, line as u32)),
+                _ => None,
+            };
+            let call = InlinedCall::new(name.into_boxed_str(), call_location);
+
+            let mut die_ranges = dwarf.die_ranges(&self.unit, entry)?;
+            while let Some(range) = die_ranges.next()? {
+                if range.begin < range.end {
+                    ranges.push(InlineRange {
+                        range: range.begin..range.end,
+                        depth,
+                        call: call.clone(),
+                    });
+                }
+            }
+        }
+
+        ranges.sort_by_key(|r| (r.range.start, r.depth));
+
+        Ok(Inlines {
+            ranges: ranges.into_boxed_slice(),
+        })
+    }
+
+    fn variable_locations(
+        &self,
+        dwarf: &Dwarf<BinaryDataReader>,
+        addr: u64,
+    ) -> Result<Vec<VariableLocation>, gimli::Error> {
+        let encoding = self.unit.encoding();
+        let mut locations = Vec::new();
+        let mut entries = self.unit.entries();
+        let mut depth = 0;
+
+        // The depth, address range, and frame base of the function while its children are
+        // visited.
+        let mut function: Option<(isize, Range<u64>, Option<Value>)> = None;
+
+        while let Some((delta_depth, entry)) = entries.next_dfs()? {
+            depth += delta_depth;
+
+            let (range, frame_base) = match function {
+                Some((function_depth, _, _)) if depth <= function_depth => break,
+                Some((_, ref range, frame_base)) => (range.clone(), frame_base),
+                None => {
+                    if entry.tag() != gimli::DW_TAG_subprogram {
+                        continue;
+                    }
+                    let mut die_ranges = dwarf.die_ranges(&self.unit, entry)?;
+                    let mut function_range = None;
+                    while let Some(range) = die_ranges.next()? {
+                        if range.begin == addr {
+                            function_range = Some(range.begin..range.end);
+                        }
+                    }
+                    let function_range = match function_range {
+                        Some(range) => range,
+                        None => continue,
+                    };
+
+                    // Variables on the stack are usually at an offset from the frame base.
+                    let frame_base = match entry.attr_value(gimli::DW_AT_frame_base)? {
+                        Some(gimli::AttributeValue::Exprloc(expr)) => {
+                            dwarf_expr::frame_base(expr, encoding).ok()
+                        }
+                        _ => None,
+                    };
+                    function = Some((depth, function_range, frame_base));
+                    continue;
+                }
+            };
+
+            if entry.tag() != gimli::DW_TAG_variable
+                && entry.tag() != gimli::DW_TAG_formal_parameter
+            {
+                continue;
+            }
+            let name = match self.variable_name(dwarf, entry)? {
+                Some(name) => name.into_boxed_str(),
+                None => continue,
+            };
+
+            let mut push = |range: Range<u64>, expr| {
+                if let Some(slot) = variable_slot(expr, encoding, frame_base) {
+                    if range.start < range.end {
+                        locations.push(VariableLocation {
+                            name: name.clone(),
+                            range,
+                            slot,
+                        });
+                    }
+                }
+            };
+            match entry.attr_value(gimli::DW_AT_location)? {
+                Some(gimli::AttributeValue::Exprloc(expr)) => push(range, expr),
+                Some(value) => {
+                    if let Some(mut list) = dwarf.attr_locations(&self.unit, value)? {
+                        while let Some(location) = list.next()? {
+                            push(location.range.begin..location.range.end, location.data);
+                        }
+                    }
+                }
+                None => {}
+            }
+        }
+
+        locations.sort_by_key(|location| location.range.start);
+        Ok(locations)
+    }
+
+    /// Finds the name of a variable, which the concrete instances of variables in inlined
+    /// functions only have through their abstract origin.
+    fn variable_name(
+        &self,
+        dwarf: &Dwarf<BinaryDataReader>,
+        entry: &gimli::DebuggingInformationEntry<'_, '_, BinaryDataReader>,
+    ) -> Result<Option<String>, gimli::Error> {
+        if let Some(value) = entry.attr_value(gimli::DW_AT_name)? {
+            let name = dwarf.attr_string(&self.unit, value)?;
+            return Ok(std::str::from_utf8(name.bytes()).ok().map(String::from));
+        }
+        match entry.attr_value(gimli::DW_AT_abstract_origin)? {
+            Some(gimli::AttributeValue::UnitRef(origin)) if origin != entry.offset() => {
+                self.variable_name(dwarf, &self.unit.entry(origin)?)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Finds the name of the subprogram at the given offset, preferring the
+    /// (demangled) linkage name because it is fully qualified.
+    fn entry_name(
+        &self,
+        dwarf: &Dwarf<BinaryDataReader>,
+        offset: gimli::UnitOffset,
+    ) -> Result<Option<String>, gimli::Error> {
+        let entry = self.unit.entry(offset)?;
+
+        if let Some(value) = entry.attr_value(gimli::DW_AT_linkage_name)? {
+            let name = dwarf.attr_string(&self.unit, value)?;
+            if let Ok(name) = std::str::from_utf8(name.bytes()) {
+                return Ok(Some(demangle::demangle(name.into()).0.into_owned()));
+            }
+        }
+
+        if let Some(value) = entry.attr_value(gimli::DW_AT_name)? {
+            let name = dwarf.attr_string(&self.unit, value)?;
+            if let Ok(name) = std::str::from_utf8(name.bytes()) {
+                return Ok(Some(name.to_string()));
+            }
+        }
+
+        // Concrete out-of-line instances and definitions point back to the entry
+        // that actually has the name.
+        for attr in &[gimli::DW_AT_abstract_origin, gimli::DW_AT_specification] {
+            if let Some(gimli::AttributeValue::UnitRef(origin)) = entry.attr_value(*attr)? {
+                if origin != offset {
+                    return self.entry_name(dwarf, origin);
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Evaluates a location expression to the register that a variable is in or the memory at
+/// an offset from a register. Only the first piece of variables that are split up into
+/// pieces is used.
+fn variable_slot(
+    expr: gimli::Expression<BinaryDataReader>,
+    encoding: gimli::Encoding,
+    frame_base: Option<Value>,
+) -> Option<VariableSlot> {
+    let context = dwarf_expr::Context {
+        frame_base,
+        ..Default::default()
+    };
+    let pieces = match dwarf_expr::evaluate(expr, encoding, &context) {
+        Ok(pieces) => pieces,
+        Err(err) => {
+            log::trace!("skipping variable location: {}", err);
+            return None;
+        }
+    };
+    match pieces.first()?.location {
+        Location::Register(register) => Some(VariableSlot::Register(register)),
+        Location::Memory(Value::Register { register, offset }) => Some(VariableSlot::Memory {
+            base: register,
+            offset,
+        }),
+        _ => None,
+    }
+}
+
+/// An index of the address ranges in a compilation unit that came from inlined calls.
+struct Inlines {
+    /// Sorted by start address and then by depth so that outer calls come first.
+    ranges: Box<[InlineRange]>,
+}
+
+impl Inlines {
+    fn calls_starting_at(&self, addr: u64) -> impl '_ + Iterator<Item = &InlinedCall> {
+        // The comparison never returns `Equal` so this always finds the index
+        // of the first range that starts at or after `addr`.
+        let start = match self.ranges.binary_search_by(|probe| {
+            probe
+                .range
+                .start
+                .cmp(&addr)
+                .then(std::cmp::Ordering::Greater)
+        }) {
+            Ok(idx) | Err(idx) => idx,
+        };
+
+        self.ranges[start..]
+            .iter()
+            .take_while(move |r| r.range.start == addr)
+            .map(|r| &r.call)
+    }
+}
+
+struct InlineRange {
+    range: Range<u64>,
+    depth: isize,
+    call: InlinedCall,
 }
 
 struct Lines {
@@ -0,0 +1,500 @@
+//! Evaluates DWARF expressions, such as the locations of variables and the frame bases of
+//! functions, without a running process.
+//!
+//! The values of registers aren't known when a binary is only disassembled, so values are
+//! tracked symbolically as a register or the canonical frame address (CFA) plus an offset.
+//! That covers the expressions that compilers emit for variables on the stack and in
+//! registers. Values that depend on memory or on arithmetic other than offsets evaluate to
+//! `Value::Unknown`, and only control flow stops the evaluation.
+
+use gimli::{Encoding, Expression, Operation, Reader, Register};
+
+/// The maximum number of operations that are evaluated, so that malformed expressions
+/// can't loop forever.
+const MAX_OPERATIONS: usize = 256;
+
+/// A value on the stack of the evaluator.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    /// A known constant.
+    Constant(u64),
+
+    /// The value of a register plus an offset.
+    Register { register: Register, offset: i64 },
+
+    /// The canonical frame address plus an offset.
+    Cfa { offset: i64 },
+
+    /// A value that depends on memory or on something else that isn't known statically.
+    Unknown,
+}
+
+impl Value {
+    /// Adds a constant to the value.
+    pub fn offset_by(self, value: i64) -> Value {
+        match self {
+            Value::Constant(constant) => Value::Constant(constant.wrapping_add(value as u64)),
+            Value::Register { register, offset } => Value::Register {
+                register,
+                offset: offset.wrapping_add(value),
+            },
+            Value::Cfa { offset } => Value::Cfa {
+                offset: offset.wrapping_add(value),
+            },
+            Value::Unknown => Value::Unknown,
+        }
+    }
+
+    fn add(self, other: Value) -> Value {
+        match (self, other) {
+            (value, Value::Constant(constant)) | (Value::Constant(constant), value) => {
+                value.offset_by(constant as i64)
+            }
+            _ => Value::Unknown,
+        }
+    }
+
+    fn sub(self, other: Value) -> Value {
+        match (self, other) {
+            (value, Value::Constant(constant)) => value.offset_by((constant as i64).wrapping_neg()),
+            _ => Value::Unknown,
+        }
+    }
+}
+
+/// Where (a piece of) an object is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Location {
+    /// The object doesn't exist here, e.g. because it was optimized out.
+    Empty,
+
+    /// The object is in a register.
+    Register(Register),
+
+    /// The object is in memory at an address.
+    Memory(Value),
+
+    /// The object doesn't exist in memory or a register, but its value is known.
+    Value(Value),
+
+    /// The object doesn't exist in memory or a register, but its bytes are known.
+    Bytes(Box<[u8]>),
+}
+
+/// A piece of an object and where it is. Objects that aren't split up into pieces have
+/// a single piece without a size.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Piece {
+    /// The size of the piece in bits, if the object is split up into pieces.
+    pub size_in_bits: Option<u64>,
+
+    /// Where the piece is.
+    pub location: Location,
+}
+
+/// What an expression is evaluated relative to.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Context {
+    /// The frame base of the function, which `DW_OP_fbreg` is relative to. See
+    /// `frame_base`.
+    pub frame_base: Option<Value>,
+
+    /// The value pushed by `DW_OP_push_object_address`, if there is one.
+    pub object_address: Option<Value>,
+}
+
+/// An error that stops the evaluation of an expression.
+#[derive(Debug)]
+pub enum EvalError {
+    /// The expression couldn't be parsed.
+    Parse(gimli::Error),
+
+    /// The expression uses an operation that can't be evaluated statically, e.g. a branch.
+    Unsupported(&'static str),
+
+    /// The expression pops more values than it pushed.
+    StackUnderflow,
+
+    /// The expression ran for longer than `MAX_OPERATIONS`.
+    TooLong,
+}
+
+impl std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvalError::Parse(err) => write!(f, "invalid DWARF expression: {}", err),
+            EvalError::Unsupported(op) => write!(f, "unsupported DWARF operation {}", op),
+            EvalError::StackUnderflow => f.write_str("DWARF expression stack underflow"),
+            EvalError::TooLong => f.write_str("DWARF expression is too long"),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+impl From<gimli::Error> for EvalError {
+    fn from(err: gimli::Error) -> Self {
+        EvalError::Parse(err)
+    }
+}
+
+/// Evaluates a location expression to the pieces of the object that it describes. An
+/// empty expression means that the object was optimized out.
+pub fn evaluate<R: Reader>(
+    expr: Expression<R>,
+    encoding: Encoding,
+    context: &Context,
+) -> Result<Vec<Piece>, EvalError> {
+    let mut operations = expr.operations(encoding);
+    let mut stack: Vec<Value> = Vec::new();
+    let mut pieces = Vec::new();
+    // The location that the operations since the last piece describe, unless it is memory
+    // at the address on the top of the stack.
+    let mut location: Option<Location> = None;
+    let mut count = 0;
+
+    while let Some(operation) = operations.next()? {
+        count += 1;
+        if count > MAX_OPERATIONS {
+            return Err(EvalError::TooLong);
+        }
+
+        match operation {
+            Operation::Nop => {}
+
+            // Literals and constants.
+            Operation::UnsignedConstant { value } => stack.push(Value::Constant(value)),
+            Operation::SignedConstant { value } => stack.push(Value::Constant(value as u64)),
+            Operation::Address { address } => stack.push(Value::Constant(address)),
+            Operation::TypedLiteral { .. }
+            | Operation::AddressIndex { .. }
+            | Operation::ConstantIndex { .. } => stack.push(Value::Unknown),
+
+            // Registers and frames.
+            Operation::Register { register } => location = Some(Location::Register(register)),
+            Operation::RegisterOffset {
+                register, offset, ..
+            } => stack.push(Value::Register { register, offset }),
+            Operation::FrameOffset { offset } => stack.push(
+                context
+                    .frame_base
+                    .map_or(Value::Unknown, |base| base.offset_by(offset)),
+            ),
+            Operation::CallFrameCFA => stack.push(Value::Cfa { offset: 0 }),
+            Operation::PushObjectAddress => {
+                stack.push(context.object_address.unwrap_or(Value::Unknown))
+            }
+            Operation::EntryValue { .. } | Operation::ParameterRef { .. } => {
+                stack.push(Value::Unknown)
+            }
+            Operation::TLS => {
+                pop(&mut stack)?;
+                stack.push(Value::Unknown)
+            }
+
+            // Stack manipulation.
+            Operation::Drop => {
+                pop(&mut stack)?;
+            }
+            Operation::Pick { index } => {
+                let index = usize::from(index);
+                if index >= stack.len() {
+                    return Err(EvalError::StackUnderflow);
+                }
+                stack.push(stack[stack.len() - 1 - index]);
+            }
+            Operation::Swap => {
+                let len = stack.len();
+                if len < 2 {
+                    return Err(EvalError::StackUnderflow);
+                }
+                stack.swap(len - 1, len - 2);
+            }
+            Operation::Rot => {
+                let len = stack.len();
+                if len < 3 {
+                    return Err(EvalError::StackUnderflow);
+                }
+                stack[(len - 3)..].rotate_right(1);
+            }
+            Operation::Deref { .. } => {
+                pop(&mut stack)?;
+                stack.push(Value::Unknown);
+            }
+
+            // Arithmetic, which is only tracked for offsets.
+            Operation::Plus => {
+                let (a, b) = pop2(&mut stack)?;
+                stack.push(a.add(b));
+            }
+            Operation::PlusConstant { value } => {
+                let a = pop(&mut stack)?;
+                stack.push(a.offset_by(value as i64));
+            }
+            Operation::Minus => {
+                let (a, b) = pop2(&mut stack)?;
+                stack.push(a.sub(b));
+            }
+            Operation::Neg => {
+                let value = match pop(&mut stack)? {
+                    Value::Constant(value) => Value::Constant(value.wrapping_neg()),
+                    _ => Value::Unknown,
+                };
+                stack.push(value);
+            }
+            Operation::Abs
+            | Operation::Not
+            | Operation::Convert { .. }
+            | Operation::Reinterpret { .. } => {
+                pop(&mut stack)?;
+                stack.push(Value::Unknown);
+            }
+            Operation::And
+            | Operation::Div
+            | Operation::Mod
+            | Operation::Mul
+            | Operation::Or
+            | Operation::Shl
+            | Operation::Shr
+            | Operation::Shra
+            | Operation::Xor
+            | Operation::Eq
+            | Operation::Ge
+            | Operation::Gt
+            | Operation::Le
+            | Operation::Lt
+            | Operation::Ne => {
+                pop2(&mut stack)?;
+                stack.push(Value::Unknown);
+            }
+
+            // The end of a location.
+            Operation::StackValue => location = Some(Location::Value(pop(&mut stack)?)),
+            Operation::ImplicitValue { data } => {
+                location = Some(Location::Bytes(data.to_slice()?.into_owned().into()))
+            }
+            Operation::ImplicitPointer { .. } => location = Some(Location::Value(Value::Unknown)),
+            Operation::Piece { size_in_bits, .. } => {
+                let piece_location = match location.take() {
+                    Some(location) => location,
+                    None => match stack.pop() {
+                        Some(address) => Location::Memory(address),
+                        None => Location::Empty,
+                    },
+                };
+                pieces.push(Piece {
+                    size_in_bits: Some(size_in_bits),
+                    location: piece_location,
+                });
+            }
+
+            Operation::Bra { .. } | Operation::Skip { .. } => {
+                return Err(EvalError::Unsupported("DW_OP_bra/DW_OP_skip"))
+            }
+            Operation::Call { .. } => return Err(EvalError::Unsupported("DW_OP_call")),
+        }
+    }
+
+    // Anything after the last piece (or the whole expression if there are no pieces).
+    let location = match location {
+        Some(location) => Some(location),
+        None => stack.pop().map(Location::Memory),
+    };
+    match location {
+        Some(location) => pieces.push(Piece {
+            size_in_bits: None,
+            location,
+        }),
+        None if pieces.is_empty() => pieces.push(Piece {
+            size_in_bits: None,
+            location: Location::Empty,
+        }),
+        None => {}
+    }
+    Ok(pieces)
+}
+
+/// Evaluates the `DW_AT_frame_base` of a function. Frame bases that are a register, such
+/// as `DW_OP_reg7` for `rsp`, mean the value of the register rather than memory.
+pub fn frame_base<R: Reader>(expr: Expression<R>, encoding: Encoding) -> Result<Value, EvalError> {
+    let pieces = evaluate(expr, encoding, &Context::default())?;
+    Ok(match pieces.first().map(|piece| &piece.location) {
+        Some(&Location::Register(register)) => Value::Register {
+            register,
+            offset: 0,
+        },
+        Some(&Location::Memory(value)) | Some(&Location::Value(value)) => value,
+        _ => Value::Unknown,
+    })
+}
+
+fn pop(stack: &mut Vec<Value>) -> Result<Value, EvalError> {
+    stack.pop().ok_or(EvalError::StackUnderflow)
+}
+
+fn pop2(stack: &mut Vec<Value>) -> Result<(Value, Value), EvalError> {
+    let b = pop(stack)?;
+    let a = pop(stack)?;
+    Ok((a, b))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gimli::{EndianSlice, Format, LittleEndian};
+
+    const ENCODING: Encoding = Encoding {
+        format: Format::Dwarf32,
+        version: 4,
+        address_size: 8,
+    };
+
+    fn eval(bytes: &[u8], context: &Context) -> Result<Vec<Piece>, EvalError> {
+        evaluate(
+            Expression(EndianSlice::new(bytes, LittleEndian)),
+            ENCODING,
+            context,
+        )
+    }
+
+    fn location(bytes: &[u8], context: &Context) -> Location {
+        let pieces = eval(bytes, context).unwrap();
+        assert_eq!(pieces.len(), 1);
+        assert_eq!(pieces[0].size_in_bits, None);
+        pieces[0].location.clone()
+    }
+
+    #[test]
+    fn register() {
+        // DW_OP_reg6
+        assert_eq!(
+            location(&[0x56], &Context::default()),
+            Location::Register(Register(6))
+        );
+        // DW_OP_regx 33
+        assert_eq!(
+            location(&[0x90, 33], &Context::default()),
+            Location::Register(Register(33))
+        );
+    }
+
+    #[test]
+    fn register_offset() {
+        // DW_OP_breg7 -8
+        assert_eq!(
+            location(&[0x77, 0x78], &Context::default()),
+            Location::Memory(Value::Register {
+                register: Register(7),
+                offset: -8,
+            })
+        );
+    }
+
+    #[test]
+    fn fbreg() {
+        let context = Context {
+            frame_base: Some(Value::Cfa { offset: 0 }),
+            ..Context::default()
+        };
+        // DW_OP_fbreg -16
+        assert_eq!(
+            location(&[0x91, 0x70], &context),
+            Location::Memory(Value::Cfa { offset: -16 })
+        );
+        // Without a frame base the address isn't known.
+        assert_eq!(
+            location(&[0x91, 0x70], &Context::default()),
+            Location::Memory(Value::Unknown)
+        );
+    }
+
+    #[test]
+    fn frame_base_register() {
+        // DW_OP_reg7
+        let expr = Expression(EndianSlice::new(&[0x57], LittleEndian));
+        assert_eq!(
+            frame_base(expr, ENCODING).unwrap(),
+            Value::Register {
+                register: Register(7),
+                offset: 0,
+            }
+        );
+        // DW_OP_call_frame_cfa
+        let expr = Expression(EndianSlice::new(&[0x9c], LittleEndian));
+        assert_eq!(
+            frame_base(expr, ENCODING).unwrap(),
+            Value::Cfa { offset: 0 }
+        );
+    }
+
+    #[test]
+    fn pieces() {
+        // DW_OP_reg0, DW_OP_piece 8, DW_OP_breg7 16, DW_OP_piece 4, DW_OP_piece 4
+        let pieces = eval(
+            &[0x50, 0x93, 8, 0x77, 16, 0x93, 4, 0x93, 4],
+            &Context::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            pieces,
+            vec![
+                Piece {
+                    size_in_bits: Some(64),
+                    location: Location::Register(Register(0)),
+                },
+                Piece {
+                    size_in_bits: Some(32),
+                    location: Location::Memory(Value::Register {
+                        register: Register(7),
+                        offset: 16,
+                    }),
+                },
+                Piece {
+                    size_in_bits: Some(32),
+                    location: Location::Empty,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn stack_value() {
+        // DW_OP_lit5, DW_OP_stack_value
+        assert_eq!(
+            location(&[0x35, 0x9f], &Context::default()),
+            Location::Value(Value::Constant(5))
+        );
+        // DW_OP_breg6 0, DW_OP_plus_uconst 24, DW_OP_stack_value
+        assert_eq!(
+            location(&[0x76, 0, 0x23, 24, 0x9f], &Context::default()),
+            Location::Value(Value::Register {
+                register: Register(6),
+                offset: 24,
+            })
+        );
+        // DW_OP_breg6 0, DW_OP_deref, DW_OP_stack_value
+        assert_eq!(
+            location(&[0x76, 0, 0x06, 0x9f], &Context::default()),
+            Location::Value(Value::Unknown)
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(location(&[], &Context::default()), Location::Empty);
+    }
+
+    #[test]
+    fn errors() {
+        // DW_OP_bra 0
+        assert!(matches!(
+            eval(&[0x28, 0, 0], &Context::default()),
+            Err(EvalError::Unsupported(_))
+        ));
+        // DW_OP_drop
+        assert!(matches!(
+            eval(&[0x13], &Context::default()),
+            Err(EvalError::StackUnderflow)
+        ));
+    }
+}
@@ -0,0 +1,549 @@
+//! Explains common instruction idioms with pseudocode comments, e.g. `rax = rdi + rsi*4`
+//! for `lea rax, [rdi + rsi*4]`, `eax = 0` for `xor eax, eax`, and the conditions of
+//! conditional jumps after a comparison, for readers who don't read assembly fluently.
+//!
+//! Only single instructions (and a comparison right before a conditional jump) are
+//! explained, so the comments say what an instruction does rather than what the code
+//! means.
+
+use super::binary::{Arch, Binary};
+use super::panics::{block_panic, jump_panic, CheckKind};
+use super::{DisasmLine, Disassembly, Flow};
+
+/// Comments the instructions of x86, x86_64, and AArch64 that follow a recognizable idiom
+/// with pseudocode. Instructions that already have a comment are left alone.
+pub fn annotate(binary: &Binary, disassembly: &mut Disassembly) {
+    let arch = binary.arch();
+    if !matches!(arch, Arch::X86 | Arch::X86_64 | Arch::AArch64) {
+        return;
+    }
+    let lines = &mut disassembly.lines;
+    let mut comments: Vec<(usize, String)> = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        if line.comments.is_some() {
+            continue;
+        }
+        let explanation = if line.flow() == Flow::ConditionalJump {
+            explain_branch(arch, lines, idx)
+        } else if arch == Arch::AArch64 {
+            explain_arm64(line, idx.checked_sub(1).map(|prev| &lines[prev]))
+        } else {
+            explain_x86(line, idx.checked_sub(1).map(|prev| &lines[prev]))
+        };
+        if let Some(explanation) = explanation {
+            comments.push((idx, explanation));
+        }
+    }
+
+    for (idx, comment) in comments {
+        lines[idx].comments = Some(comment.into());
+    }
+}
+
+/// An operand of an instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Operand {
+    Register(String),
+    Immediate(String),
+    /// A memory operand and the expression of its address, e.g. `rdi + rsi*4 + 0x10`.
+    Memory(String),
+}
+
+impl Operand {
+    /// Parses an operand in Intel, AT&T, or AArch64 syntax.
+    fn parse(operand: &str) -> Option<Operand> {
+        let operand = operand.trim();
+        if let Some(start) = operand.find('[') {
+            let end = operand.rfind(']')?;
+            let address = bracket_address(&operand[(start + 1)..end])?;
+            // Keep the segment of accesses relative to the thread pointer, e.g. `fs:[0x28]`.
+            return Some(Operand::Memory(match operand[..start].rsplit(' ').next() {
+                Some(segment) if segment.ends_with(':') => format!("{}{}", segment, address),
+                _ => address,
+            }));
+        }
+        if let Some(start) = operand.find('(') {
+            let end = operand.rfind(')')?;
+            return Some(Operand::Memory(att_address(
+                &operand[..start],
+                &operand[(start + 1)..end],
+            )?));
+        }
+        if let Some(imm) = operand
+            .strip_prefix('$')
+            .or_else(|| operand.strip_prefix('#'))
+        {
+            return Some(Operand::Immediate(imm.to_string()));
+        }
+        if operand.starts_with(|c: char| c.is_ascii_digit() || c == '-') {
+            return Some(Operand::Immediate(operand.to_string()));
+        }
+        let register = operand.trim_start_matches('%');
+        if !register.is_empty()
+            && register
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.')
+        {
+            Some(Operand::Register(register.to_string()))
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operand::Register(name) | Operand::Immediate(name) => f.write_str(name),
+            Operand::Memory(address) => write!(f, "*({})", address),
+        }
+    }
+}
+
+/// Builds the address of an Intel (`rdi + rsi*4 + 0x10`) or AArch64 (`x0, x1, lsl #3`)
+/// memory operand.
+fn bracket_address(memory: &str) -> Option<String> {
+    let parts = memory.split(',').map(str::trim).collect::<Vec<_>>();
+    let mut address = parts[0].to_string();
+    match parts.get(1) {
+        None => return Some(address),
+        Some(offset) => match offset.strip_prefix('#') {
+            Some(imm) => match imm.strip_prefix('-') {
+                Some(imm) => address.push_str(&format!(" - {}", imm)),
+                None => address.push_str(&format!(" + {}", imm)),
+            },
+            None => address.push_str(&format!(" + {}", offset)),
+        },
+    }
+    match parts.get(2) {
+        None => {}
+        Some(shift) => {
+            let amount = shift
+                .strip_prefix("lsl #")
+                .and_then(|amount| amount.parse::<u32>().ok())?;
+            address.push_str(&format!("*{}", 1u64 << amount));
+        }
+    }
+    Some(address)
+}
+
+/// Builds the address of an AT&T memory operand like `0x10(%rdi,%rsi,4)`.
+fn att_address(displacement: &str, memory: &str) -> Option<String> {
+    let parts = memory
+        .split(',')
+        .map(|part| part.trim().trim_start_matches('%'))
+        .collect::<Vec<_>>();
+    let mut terms = Vec::new();
+    if !parts[0].is_empty() {
+        terms.push(parts[0].to_string());
+    }
+    match (parts.get(1), parts.get(2)) {
+        (Some(index), Some(&"1")) | (Some(index), None) => terms.push(index.to_string()),
+        (Some(index), Some(scale)) => terms.push(format!("{}*{}", index, scale)),
+        _ => {}
+    }
+    let (segment, displacement) = match displacement.rfind(':') {
+        Some(idx) => (&displacement[..=idx], &displacement[(idx + 1)..]),
+        None => ("", displacement),
+    };
+    let mut address = terms.join(" + ");
+    if let Some(displacement) = displacement.strip_prefix('-') {
+        address.push_str(&format!(" - {}", displacement));
+    } else if !displacement.is_empty() && displacement != "0" {
+        if address.is_empty() {
+            address = displacement.to_string();
+        } else {
+            address.push_str(&format!(" + {}", displacement));
+        }
+    }
+    if address.is_empty() {
+        None
+    } else {
+        Some(format!("{}{}", segment.trim_start_matches('%'), address))
+    }
+}
+
+/// Splits the operands of an instruction at the commas that aren't inside of a memory
+/// operand.
+fn split_operands(operands: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (idx, c) in operands.char_indices() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(operands[start..idx].trim());
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    if !operands[start..].trim().is_empty() {
+        parts.push(operands[start..].trim());
+    }
+    parts
+}
+
+/// Parses the operands of an instruction with the destination first, which means reversing
+/// them in AT&T syntax. The mnemonic loses the operand size suffix of AT&T syntax.
+fn parse_operands(line: &DisasmLine) -> Option<(&str, Vec<Operand>)> {
+    let parts = split_operands(line.operands());
+    let att = line.operands().contains('%');
+    let mut operands = parts
+        .iter()
+        .map(|part| Operand::parse(part))
+        .collect::<Option<Vec<_>>>()?;
+    let mut mnemonic = line.mnemonic();
+    if att {
+        operands.reverse();
+        if !is_known_x86(mnemonic) && mnemonic.ends_with(['b', 'w', 'l', 'q']) {
+            let stripped = &mnemonic[..(mnemonic.len() - 1)];
+            if is_known_x86(stripped) {
+                mnemonic = stripped;
+            }
+        }
+    }
+    Some((mnemonic, operands))
+}
+
+fn is_known_x86(mnemonic: &str) -> bool {
+    matches!(
+        mnemonic,
+        "lea"
+            | "add"
+            | "sub"
+            | "and"
+            | "or"
+            | "xor"
+            | "shl"
+            | "sal"
+            | "shr"
+            | "sar"
+            | "imul"
+            | "inc"
+            | "dec"
+            | "neg"
+            | "not"
+            | "cmp"
+            | "test"
+    )
+}
+
+/// Explains an x86 instruction, or a `setcc` after the comparison before it.
+fn explain_x86(line: &DisasmLine, prev: Option<&DisasmLine>) -> Option<String> {
+    if let Some(cc) = line.mnemonic().strip_prefix("set") {
+        let condition = comparison(Arch::X86_64, prev?)?.condition(cc)?;
+        let dest = Operand::parse(line.operands())?;
+        return Some(format!("{} = {}", dest, condition));
+    }
+    if let Some(explanation) = explain_extension(line) {
+        return Some(explanation);
+    }
+
+    let (mnemonic, operands) = parse_operands(line)?;
+
+    let explanation = match (mnemonic, &operands[..]) {
+        ("lea", [dest, Operand::Memory(address)]) => format!("{} = {}", dest, address),
+        ("xor", [dest, src]) | ("sub", [dest, src]) if dest == src => format!("{} = 0", dest),
+        ("pxor", [dest, src]) | ("xorps", [dest, src]) | ("xorpd", [dest, src]) if dest == src => {
+            format!("{} = 0", dest)
+        }
+        ("vpxor", [dest, a, b])
+        | ("vpxord", [dest, a, b])
+        | ("vpxorq", [dest, a, b])
+        | ("vxorps", [dest, a, b])
+        | ("vxorpd", [dest, a, b])
+            if a == b =>
+        {
+            format!("{} = 0", dest)
+        }
+        ("add", [dest, src]) => format!("{} += {}", dest, src),
+        ("sub", [dest, src]) => format!("{} -= {}", dest, src),
+        ("and", [dest, src]) => format!("{} &= {}", dest, src),
+        ("or", [dest, src]) => format!("{} |= {}", dest, src),
+        ("xor", [dest, src]) => format!("{} ^= {}", dest, src),
+        ("shl", [dest, src]) | ("sal", [dest, src]) => format!("{} <<= {}", dest, src),
+        ("shr", [dest, src]) => format!("{} >>= {}", dest, src),
+        ("sar", [dest, src]) => format!("{} >>= {} (signed)", dest, src),
+        ("imul", [dest, src]) => format!("{} *= {}", dest, src),
+        ("imul", [dest, src, imm]) => format!("{} = {} * {}", dest, src, imm),
+        ("inc", [dest]) => format!("{} += 1", dest),
+        ("dec", [dest]) => format!("{} -= 1", dest),
+        ("neg", [dest]) => format!("{} = -{}", dest, dest),
+        ("not", [dest]) => format!("{} = !{}", dest, dest),
+        _ => return None,
+    };
+    Some(explanation)
+}
+
+/// Explains the zero and sign extending moves of x86, e.g. `movzx eax, byte ptr [rdi]` or
+/// `movzbl (%rdi), %eax`.
+fn explain_extension(line: &DisasmLine) -> Option<String> {
+    let mnemonic = line.mnemonic();
+    let att = line.operands().contains('%');
+    let kind = match mnemonic {
+        "movzx" => "zero-extended",
+        "movsx" | "movsxd" => "sign-extended",
+        _ if att && mnemonic.len() == 6 && mnemonic.starts_with("movz") => "zero-extended",
+        _ if att && mnemonic.len() == 6 && mnemonic.starts_with("movs") => "sign-extended",
+        _ => return None,
+    };
+    let mut operands = split_operands(line.operands())
+        .into_iter()
+        .map(Operand::parse)
+        .collect::<Option<Vec<_>>>()?;
+    if att {
+        operands.reverse();
+    }
+    match &operands[..] {
+        [dest, src] => Some(format!("{} = {} ({})", dest, src, kind)),
+        _ => None,
+    }
+}
+
+/// Explains an AArch64 instruction, or a `cset` after the comparison before it.
+fn explain_arm64(line: &DisasmLine, prev: Option<&DisasmLine>) -> Option<String> {
+    let parts = split_operands(line.operands());
+    // A shifted register operand, e.g. `x2, lsl #2`, is a multiplication by a power of two.
+    let scale = match parts.last().and_then(|last| last.strip_prefix("lsl #")) {
+        Some(amount) => Some(1u64 << amount.parse::<u32>().ok()?),
+        None => None,
+    };
+    let operands = parts
+        .iter()
+        .take(parts.len() - scale.map_or(0, |_| 1))
+        .map(|part| Operand::parse(part))
+        .collect::<Option<Vec<_>>>()?;
+    let scaled = |operand: &Operand| match scale {
+        Some(scale) => format!("{}*{}", operand, scale),
+        None => operand.to_string(),
+    };
+    let is_zero = |operand: &Operand| match operand {
+        Operand::Register(name) => name == "xzr" || name == "wzr",
+        Operand::Immediate(imm) => imm == "0" || imm == "0x0",
+        Operand::Memory(_) => false,
+    };
+
+    let explanation = match (line.mnemonic(), &operands[..]) {
+        ("cset", [dest, Operand::Register(cc)]) => {
+            let condition = comparison(Arch::AArch64, prev?)?.condition(cc)?;
+            format!("{} = {}", dest, condition)
+        }
+        ("mov", [dest, src]) if is_zero(src) && matches!(src, Operand::Register(_)) => {
+            format!("{} = 0", dest)
+        }
+        ("movi", [dest, src]) if is_zero(src) => format!("{} = 0", dest),
+        ("eor", [dest, a, b]) if a == b && scale.is_none() => format!("{} = 0", dest),
+        ("add", [dest, a, b]) => format!("{} = {} + {}", dest, a, scaled(b)),
+        ("sub", [dest, a, b]) => format!("{} = {} - {}", dest, a, scaled(b)),
+        ("and", [dest, a, b]) => format!("{} = {} & {}", dest, a, scaled(b)),
+        ("orr", [dest, a, b]) => format!("{} = {} | {}", dest, a, scaled(b)),
+        ("eor", [dest, a, b]) => format!("{} = {} ^ {}", dest, a, scaled(b)),
+        ("mul", [dest, a, b]) => format!("{} = {} * {}", dest, a, b),
+        ("madd", [dest, a, b, c]) => format!("{} = {} * {} + {}", dest, a, b, c),
+        ("msub", [dest, a, b, c]) => format!("{} = {} - {} * {}", dest, c, a, b),
+        ("udiv", [dest, a, b]) => format!("{} = {} / {}", dest, a, b),
+        ("sdiv", [dest, a, b]) => format!("{} = {} / {} (signed)", dest, a, b),
+        ("lsl", [dest, a, b]) => format!("{} = {} << {}", dest, a, b),
+        ("lsr", [dest, a, b]) => format!("{} = {} >> {}", dest, a, b),
+        ("asr", [dest, a, b]) => format!("{} = {} >> {} (signed)", dest, a, b),
+        ("neg", [dest, src]) => format!("{} = -{}", dest, src),
+        ("mvn", [dest, src]) => format!("{} = !{}", dest, src),
+        _ => return None,
+    };
+    Some(explanation)
+}
+
+/// A comparison that sets the flags that a conditional jump uses.
+enum Comparison {
+    /// `cmp a, b`, which compares `a` with `b`.
+    Compare(Operand, Operand),
+    /// `test a, b` or `tst a, b`, which compares `a & b` with zero.
+    Test(Operand, Operand),
+}
+
+/// The conditions of conditional jumps after a comparison.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Condition {
+    Equal,
+    NotEqual,
+    Below,
+    AboveOrEqual,
+    BelowOrEqual,
+    Above,
+    Less,
+    GreaterOrEqual,
+    LessOrEqual,
+    Greater,
+    Negative,
+    NotNegative,
+}
+
+impl Condition {
+    /// Parses the condition code of an x86 (`ae`) or AArch64 (`hs`) instruction.
+    fn parse(cc: &str) -> Option<Condition> {
+        Some(match cc {
+            "e" | "z" | "eq" => Condition::Equal,
+            "ne" | "nz" => Condition::NotEqual,
+            "b" | "c" | "nae" | "lo" | "cc" => Condition::Below,
+            "ae" | "nb" | "nc" | "hs" | "cs" => Condition::AboveOrEqual,
+            "be" | "na" | "ls" => Condition::BelowOrEqual,
+            "a" | "nbe" | "hi" => Condition::Above,
+            "l" | "nge" | "lt" => Condition::Less,
+            "ge" | "nl" => Condition::GreaterOrEqual,
+            "le" | "ng" => Condition::LessOrEqual,
+            "g" | "nle" | "gt" => Condition::Greater,
+            "s" | "mi" => Condition::Negative,
+            "ns" | "pl" => Condition::NotNegative,
+            _ => return None,
+        })
+    }
+
+    fn negate(self) -> Condition {
+        match self {
+            Condition::Equal => Condition::NotEqual,
+            Condition::NotEqual => Condition::Equal,
+            Condition::Below => Condition::AboveOrEqual,
+            Condition::AboveOrEqual => Condition::Below,
+            Condition::BelowOrEqual => Condition::Above,
+            Condition::Above => Condition::BelowOrEqual,
+            Condition::Less => Condition::GreaterOrEqual,
+            Condition::GreaterOrEqual => Condition::Less,
+            Condition::LessOrEqual => Condition::Greater,
+            Condition::Greater => Condition::LessOrEqual,
+            Condition::Negative => Condition::NotNegative,
+            Condition::NotNegative => Condition::Negative,
+        }
+    }
+}
+
+impl Comparison {
+    fn condition(&self, cc: &str) -> Option<String> {
+        self.describe(Condition::parse(cc)?)
+    }
+
+    fn describe(&self, condition: Condition) -> Option<String> {
+        let description = match self {
+            Comparison::Compare(a, b) => {
+                let (op, suffix) = match condition {
+                    Condition::Equal => ("==", ""),
+                    Condition::NotEqual => ("!=", ""),
+                    Condition::Below => ("<", " (unsigned)"),
+                    Condition::AboveOrEqual => (">=", " (unsigned)"),
+                    Condition::BelowOrEqual => ("<=", " (unsigned)"),
+                    Condition::Above => (">", " (unsigned)"),
+                    Condition::Less => ("<", ""),
+                    Condition::GreaterOrEqual => (">=", ""),
+                    Condition::LessOrEqual => ("<=", ""),
+                    Condition::Greater => (">", ""),
+                    Condition::Negative | Condition::NotNegative => return None,
+                };
+                format!("{} {} {}{}", a, op, b, suffix)
+            }
+            Comparison::Test(a, b) if a == b => {
+                let op = match condition {
+                    Condition::Equal => "==",
+                    Condition::NotEqual => "!=",
+                    Condition::Less | Condition::Negative => "<",
+                    Condition::GreaterOrEqual | Condition::NotNegative => ">=",
+                    Condition::LessOrEqual => "<=",
+                    Condition::Greater => ">",
+                    _ => return None,
+                };
+                format!("{} {} 0", a, op)
+            }
+            Comparison::Test(a, b) => match condition {
+                Condition::Equal => format!("({} & {}) == 0", a, b),
+                Condition::NotEqual => format!("({} & {}) != 0", a, b),
+                _ => return None,
+            },
+        };
+        Some(description)
+    }
+}
+
+/// Returns the comparison that an instruction makes, if it is `cmp`, `test`, or `tst`.
+fn comparison(arch: Arch, line: &DisasmLine) -> Option<Comparison> {
+    let (mnemonic, operands) = if arch == Arch::AArch64 {
+        let operands = split_operands(line.operands())
+            .into_iter()
+            .map(Operand::parse)
+            .collect::<Option<Vec<_>>>()?;
+        (line.mnemonic(), operands)
+    } else {
+        parse_operands(line)?
+    };
+    match (mnemonic, &operands[..]) {
+        ("cmp", [a, b]) => Some(Comparison::Compare(a.clone(), b.clone())),
+        ("test", [a, b]) | ("tst", [a, b]) => Some(Comparison::Test(a.clone(), b.clone())),
+        _ => None,
+    }
+}
+
+/// Explains the condition of a conditional jump. Jumps that go to a panic, or that skip
+/// over one, are checks like bounds checks, which are described as such.
+fn explain_branch(arch: Arch, lines: &[DisasmLine], idx: usize) -> Option<String> {
+    let line = &lines[idx];
+    let (condition, taken) = if arch == Arch::AArch64 {
+        arm64_branch_condition(line, idx.checked_sub(1).map(|prev| &lines[prev]))?
+    } else {
+        let cc = line.mnemonic().strip_prefix('j')?;
+        let comparison = comparison(arch, lines.get(idx.checked_sub(1)?)?)?;
+        let condition = Condition::parse(cc)?;
+        (
+            comparison.describe(condition)?,
+            comparison.describe(condition.negate()),
+        )
+    };
+
+    if let Some(panic) = jump_panic(line, lines) {
+        return Some(format!("{}: panic if {}", check_name(panic), condition));
+    }
+    match (block_panic(lines, idx + 1), taken) {
+        (Some(panic), Some(negated)) => {
+            Some(format!("{}: panic if {}", check_name(panic), negated))
+        }
+        _ => Some(format!("if {}", condition)),
+    }
+}
+
+/// Names the kind of check that panics with a function, e.g. a bounds check for
+/// `core::panicking::panic_bounds_check`.
+fn check_name(panic: &str) -> String {
+    CheckKind::of(panic).map_or_else(|| "check".to_string(), |kind| kind.to_string())
+}
+
+/// Returns the condition of an AArch64 conditional branch and its negation, which are
+/// either from the comparison before a `b.cc` or part of the branch (`cbz`, `tbnz`).
+fn arm64_branch_condition(
+    line: &DisasmLine,
+    prev: Option<&DisasmLine>,
+) -> Option<(String, Option<String>)> {
+    if let Some(cc) = line.mnemonic().strip_prefix("b.") {
+        let comparison = comparison(Arch::AArch64, prev?)?;
+        let condition = Condition::parse(cc)?;
+        return Some((
+            comparison.describe(condition)?,
+            comparison.describe(condition.negate()),
+        ));
+    }
+    let operands = split_operands(line.operands());
+    let register = operands.first()?;
+    let bit = || operands.get(1).map(|bit| bit.trim_start_matches('#'));
+    let (condition, negated) = match line.mnemonic() {
+        "cbz" => (format!("{} == 0", register), format!("{} != 0", register)),
+        "cbnz" => (format!("{} != 0", register), format!("{} == 0", register)),
+        "tbz" => (
+            format!("bit {} of {} is 0", bit()?, register),
+            format!("bit {} of {} is 1", bit()?, register),
+        ),
+        "tbnz" => (
+            format!("bit {} of {} is 1", bit()?, register),
+            format!("bit {} of {} is 0", bit()?, register),
+        ),
+        _ => return None,
+    };
+    Some((condition, Some(negated)))
+}
@@ -0,0 +1,195 @@
+//! Summarizes the stack frames of functions from their call frame information and the
+//! instructions of their prologues.
+
+use super::binary::{Arch, Binary, Bits};
+use super::symbol::Symbol;
+use super::unwind;
+use super::{Disassembly, Flow};
+use std::fmt;
+
+/// How many instructions at the start of a function are searched for its prologue.
+const PROLOGUE_WINDOW: usize = 24;
+
+/// The size of the stack frame of a function and the registers that it saves.
+#[derive(Clone, Debug, Default)]
+pub struct FrameLayout {
+    /// The size of the frame in bytes, including the return address on x86.
+    pub size: u64,
+
+    /// The callee-saved registers that are saved in the frame, in the order that they're
+    /// saved.
+    pub saved_registers: Vec<String>,
+
+    /// The frame pointer register, if the function sets one up.
+    pub frame_pointer: Option<&'static str>,
+}
+
+impl fmt::Display for FrameLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "frame: 0x{:x} bytes", self.size)?;
+        if !self.saved_registers.is_empty() {
+            write!(f, ", saves {}", self.saved_registers.join(" "))?;
+        }
+        if let Some(frame_pointer) = self.frame_pointer {
+            write!(f, ", frame pointer {}", frame_pointer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the layout of the stack frame of a function. The call frame information is
+/// preferred for the saved registers, and the larger of the sizes from the call frame
+/// information and the prologue is used because the call frame information of functions
+/// with a frame pointer doesn't include the space that is allocated after it is set up.
+pub fn analyze(
+    binary: &Binary,
+    symbol: &Symbol,
+    disassembly: &Disassembly,
+) -> anyhow::Result<Option<FrameLayout>> {
+    let prologue = match binary.arch() {
+        Arch::X86 | Arch::X86_64 => x86_prologue(binary, disassembly),
+        Arch::AArch64 => arm64_prologue(disassembly),
+        _ => None,
+    };
+    let info = unwind::frame_info_for(binary, symbol)?;
+
+    let layout = match (prologue, info) {
+        (Some(mut layout), Some(info)) => {
+            layout.size = layout.size.max(info.cfa_offset);
+            if !info.saved_registers.is_empty() {
+                layout.saved_registers = info.saved_registers;
+            }
+            layout
+        }
+        (Some(layout), None) => layout,
+        (None, Some(info)) => FrameLayout {
+            size: info.cfa_offset,
+            saved_registers: info.saved_registers,
+            frame_pointer: None,
+        },
+        (None, None) => return Ok(None),
+    };
+    Ok(Some(layout))
+}
+
+/// Adds up the pushes and stack allocations at the start of an x86 function, e.g.
+/// `push rbx` and `sub rsp, 0x40`, which are in either Intel or AT&T syntax.
+fn x86_prologue(binary: &Binary, disassembly: &Disassembly) -> Option<FrameLayout> {
+    let (word, stack_pointer, frame_pointer) = if binary.bits() == Bits::Bits64 {
+        (8, "rsp", "rbp")
+    } else {
+        (4, "esp", "ebp")
+    };
+
+    let mut layout = FrameLayout {
+        size: word,
+        ..FrameLayout::default()
+    };
+    let mut found = false;
+    for line in disassembly.lines().iter().take(PROLOGUE_WINDOW) {
+        if line.flow() != Flow::Next {
+            break;
+        }
+        let operands = line
+            .operands()
+            .split(',')
+            .map(|operand| operand.trim().trim_start_matches('%'))
+            .collect::<Vec<_>>();
+        // Intel syntax has the destination first and AT&T syntax has it last.
+        let att = line.operands().contains('%');
+        let (dest, src) = match (operands.len(), att) {
+            (2, false) => (operands[0], operands[1]),
+            (2, true) => (operands[1], operands[0]),
+            _ => (operands[0], ""),
+        };
+
+        let mnemonic = line.mnemonic();
+        if mnemonic.starts_with("push") && operands.len() == 1 {
+            layout.size += word;
+            layout.saved_registers.push(dest.to_string());
+            found = true;
+        } else if mnemonic.starts_with("sub") && dest == stack_pointer {
+            // Large frames are allocated by a register after a call to a stack probe.
+            layout.size += parse_immediate(src).unwrap_or(0);
+            found = true;
+        } else if mnemonic.starts_with("mov") && dest == frame_pointer && src == stack_pointer {
+            layout.frame_pointer = Some(frame_pointer);
+        }
+    }
+    if found {
+        Some(layout)
+    } else {
+        None
+    }
+}
+
+/// Adds up the stack allocations at the start of an AArch64 function, which are either
+/// pre-indexed stores of register pairs (`stp x29, x30, [sp, #-0x30]!`) or `sub sp, sp`.
+fn arm64_prologue(disassembly: &Disassembly) -> Option<FrameLayout> {
+    let is_callee_saved = |register: &str| {
+        let number = register
+            .strip_prefix('x')
+            .or_else(|| register.strip_prefix('d'))
+            .and_then(|number| number.parse::<u32>().ok());
+        match (register.chars().next(), number) {
+            (Some('x'), Some(number)) => number >= 19,
+            (Some('d'), Some(number)) => (8..=15).contains(&number),
+            _ => false,
+        }
+    };
+
+    let mut layout = FrameLayout::default();
+    let mut found = false;
+    for line in disassembly.lines().iter().take(PROLOGUE_WINDOW) {
+        if line.flow() != Flow::Next {
+            break;
+        }
+        let operands = line.operands();
+        let mnemonic = line.mnemonic();
+        if (mnemonic == "stp" || mnemonic == "str") && operands.contains("[sp") {
+            let registers = operands[..operands.find('[')?]
+                .split(',')
+                .map(str::trim)
+                .filter(|register| !register.is_empty());
+            for register in registers {
+                if is_callee_saved(register) {
+                    layout.saved_registers.push(register.to_string());
+                }
+            }
+            // Pre-indexed stores allocate the frame, e.g. `[sp, #-0x30]!`.
+            if operands.ends_with('!') {
+                let offset = operands.rsplit('#').next()?.trim_end_matches([']', '!']);
+                layout.size += parse_immediate(offset.trim_start_matches('-')).unwrap_or(0);
+            }
+            found = true;
+        } else if mnemonic == "sub" && operands.starts_with("sp, sp, #") {
+            let mut parts = operands["sp, sp, ".len()..].split(',');
+            let imm = parts.next().and_then(parse_immediate).unwrap_or(0);
+            let shift = if parts.next().map(str::trim) == Some("lsl #12") {
+                12
+            } else {
+                0
+            };
+            layout.size += imm << shift;
+            found = true;
+        } else if (mnemonic == "mov" && operands == "x29, sp")
+            || (mnemonic == "add" && operands.starts_with("x29, sp"))
+        {
+            layout.frame_pointer = Some("x29");
+        }
+    }
+    if found {
+        Some(layout)
+    } else {
+        None
+    }
+}
+
+/// Parses an immediate like `0x40`, `$0x40`, `#0x40`, or `64`.
+fn parse_immediate(operand: &str) -> Option<u64> {
+    let operand = operand.trim().trim_start_matches(['$', '#']);
+    match operand.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => operand.parse().ok(),
+    }
+}
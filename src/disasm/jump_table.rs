@@ -0,0 +1,504 @@
+//! Recovers the jump tables that `match` statements and other switches are compiled to, so
+//! that the targets of indirect jumps through them can be shown.
+
+use super::binary::{Arch, Binary, Endian};
+use super::symbol::Symbol;
+use super::{DisasmLine, Flow, Jump};
+use std::collections::HashMap;
+
+/// How many instructions before an indirect jump are searched for the code that loads its
+/// target from a jump table.
+const SEARCH_WINDOW: usize = 24;
+
+/// The maximum number of entries that are read from a table without a bounds check.
+const MAX_ENTRIES: usize = 512;
+
+/// A table of jump targets that an indirect jump selects from by an index.
+#[derive(Clone, Debug)]
+pub struct JumpTable {
+    jump: usize,
+    address: u64,
+    targets: Vec<u64>,
+}
+
+impl JumpTable {
+    /// The index of the line with the indirect jump.
+    pub fn jump_line(&self) -> usize {
+        self.jump
+    }
+
+    /// The address of the table.
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    /// The target of each case, by the index that selects it.
+    pub fn targets(&self) -> &[u64] {
+        &self.targets
+    }
+
+    /// Returns the distinct targets of the table and the cases that jump to them, in the
+    /// order of their first case.
+    pub fn cases(&self) -> Vec<(Vec<usize>, u64)> {
+        let mut cases: Vec<(Vec<usize>, u64)> = Vec::new();
+        let mut target_idx = HashMap::new();
+        for (case, &target) in self.targets.iter().enumerate() {
+            let idx = *target_idx.entry(target).or_insert_with(|| {
+                cases.push((Vec::new(), target));
+                cases.len() - 1
+            });
+            cases[idx].0.push(case);
+        }
+        cases
+    }
+}
+
+/// How the entries of a jump table are turned into target addresses.
+#[derive(Copy, Clone, Debug)]
+struct TableLayout {
+    address: u64,
+    entry_size: u8,
+    signed: bool,
+    /// The address that entries are added to, or `None` if entries are absolute addresses.
+    base: Option<u64>,
+    shift: u32,
+}
+
+/// Finds the indirect jumps in a function that go through a jump table and reads the
+/// targets of the tables. Only the idioms that compilers commonly use on x86, x86_64, and
+/// AArch64 are recognized.
+pub fn find_jump_tables(binary: &Binary, symbol: &Symbol, lines: &[DisasmLine]) -> Vec<JumpTable> {
+    let mut tables = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        if line.flow() != Flow::Jump || !matches!(line.jump(), Jump::None) {
+            continue;
+        }
+
+        let layout = match binary.arch() {
+            Arch::X86 | Arch::X86_64 => x86_table(lines, idx),
+            Arch::AArch64 => arm64_table(lines, idx),
+            _ => None,
+        };
+        let layout = match layout {
+            Some(layout) => layout,
+            None => continue,
+        };
+
+        let count = bounds_check(lines, idx);
+        let targets = read_targets(binary, symbol, layout, count);
+        if targets.is_empty() {
+            continue;
+        }
+        log::trace!(
+            "found jump table at 0x{:x} with {} entries for the jump at 0x{:x}",
+            layout.address,
+            targets.len(),
+            line.address()
+        );
+        tables.push(JumpTable {
+            jump: idx,
+            address: layout.address,
+            targets,
+        });
+    }
+    tables
+}
+
+/// Reads the targets of a table. Without a known number of entries, entries are read until
+/// one of them points outside of the function.
+fn read_targets(
+    binary: &Binary,
+    symbol: &Symbol,
+    layout: TableLayout,
+    count: Option<usize>,
+) -> Vec<u64> {
+    let size = usize::from(layout.entry_size);
+    let mut targets = Vec::new();
+    for idx in 0..count.unwrap_or(MAX_ENTRIES).min(MAX_ENTRIES) {
+        let address = layout.address + (idx * size) as u64;
+        let bytes = match binary.read_only_data_at(address, size) {
+            Some(bytes) => bytes,
+            None => break,
+        };
+        let mut buf = [0; 8];
+        let entry = match binary.endian() {
+            Endian::Big => {
+                buf[(8 - size)..].copy_from_slice(bytes);
+                u64::from_be_bytes(buf) as i64
+            }
+            _ => {
+                buf[..size].copy_from_slice(bytes);
+                u64::from_le_bytes(buf) as i64
+            }
+        };
+        let entry = if layout.signed {
+            let unused = 64 - size as u32 * 8;
+            (entry << unused) >> unused
+        } else {
+            entry
+        };
+
+        let target = match layout.base {
+            Some(base) => base.wrapping_add((entry << layout.shift) as u64),
+            None => entry as u64,
+        };
+        if !symbol.address_range().contains(&target) {
+            if count.is_some() {
+                return Vec::new();
+            }
+            break;
+        }
+        targets.push(target);
+    }
+    targets
+}
+
+/// Recognizes the jump tables of x86 and x86_64, which are either tables of absolute
+/// addresses, e.g. `jmp qword ptr [rax*8 + 0x4a1230]`, or tables of 32-bit offsets from the
+/// start of the table in position independent code:
+///
+/// ```text
+/// lea     rcx, [rip + 0x1234]
+/// movsxd  rax, dword ptr [rcx + rax*4]
+/// add     rax, rcx
+/// jmp     rax
+/// ```
+fn x86_table(lines: &[DisasmLine], jump: usize) -> Option<TableLayout> {
+    let operands = lines[jump].operands();
+    if operands.contains('[') || operands.contains('(') {
+        let entry_size = if operands.contains("*8") || operands.contains(",8)") {
+            8
+        } else if operands.contains("*4") || operands.contains(",4)") {
+            4
+        } else {
+            return None;
+        };
+        let start = operands.rfind("0x")?;
+        let digits = operands[(start + 2)..]
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .map_or(&operands[(start + 2)..], |end| {
+                &operands[(start + 2)..(start + 2 + end)]
+            });
+        return Some(TableLayout {
+            address: u64::from_str_radix(digits, 16).ok()?,
+            entry_size,
+            signed: false,
+            base: None,
+            shift: 0,
+        });
+    }
+
+    let window = &lines[jump.saturating_sub(SEARCH_WINDOW)..jump];
+    let load = window
+        .iter()
+        .rposition(|line| matches!(line.mnemonic(), "movsxd" | "movslq"))?;
+    let table = window[..load]
+        .iter()
+        .rev()
+        .find(|line| line.mnemonic().starts_with("lea"))?
+        .memory_reference()?;
+    Some(TableLayout {
+        address: table,
+        entry_size: 4,
+        signed: true,
+        base: Some(table),
+        shift: 0,
+    })
+}
+
+/// Recognizes the jump tables of AArch64, whose entries are loaded from a table with
+/// `adrp` and `add`, and then shifted and added to a base address from `adr`:
+///
+/// ```text
+/// adrp    x9, #0x40000
+/// add     x9, x9, #0x120
+/// adr     x10, #0x2a3c
+/// ldrb    w11, [x9, x8]
+/// add     x10, x10, x11, lsl #2
+/// br      x10
+/// ```
+fn arm64_table(lines: &[DisasmLine], jump: usize) -> Option<TableLayout> {
+    let target_reg = arm64_register(lines[jump].operands().trim());
+
+    let mut values: HashMap<String, u64> = HashMap::new();
+    // The registers that table entries were loaded into, and the table and entry size.
+    let mut loads: HashMap<String, (u64, u8, bool)> = HashMap::new();
+    let mut layout = None;
+    for line in &lines[jump.saturating_sub(SEARCH_WINDOW)..jump] {
+        let operands = split_operands(line.operands());
+        let dest = match operands.first() {
+            Some(dest) => arm64_register(dest),
+            None => continue,
+        };
+        let mnemonic = line.mnemonic();
+        match (mnemonic, operands.len()) {
+            ("adr", 2) | ("adrp", 2) => {
+                if let Some(value) = parse_immediate(&operands[1]) {
+                    values.insert(dest, value);
+                }
+                continue;
+            }
+            ("ldrb", _) | ("ldrh", _) | ("ldrsb", _) | ("ldrsh", _) | ("ldrsw", _) => {
+                let table = operands
+                    .get(1)
+                    .and_then(|memory| memory.trim_start_matches('[').split(',').next())
+                    .and_then(|base| values.get(&arm64_register(base)).copied());
+                if let Some(table) = table {
+                    let entry_size = match mnemonic {
+                        "ldrb" | "ldrsb" => 1,
+                        "ldrh" | "ldrsh" => 2,
+                        _ => 4,
+                    };
+                    loads.insert(dest, (table, entry_size, mnemonic.starts_with("ldrs")));
+                    continue;
+                }
+            }
+            ("add", 3) | ("add", 4) => {
+                let source = arm64_register(&operands[1]);
+                if let Some(imm) = parse_immediate(&operands[2]) {
+                    if let Some(&value) = values.get(&source) {
+                        values.insert(dest, value.wrapping_add(imm));
+                        continue;
+                    }
+                } else if let Some(&(table, entry_size, signed)) =
+                    loads.get(&arm64_register(&operands[2]))
+                {
+                    let shift = operands.get(3).and_then(|shift| {
+                        parse_immediate(shift.split_whitespace().last()?).map(|s| s as u32)
+                    });
+                    if let Some(&base) = values.get(&source) {
+                        if dest == target_reg {
+                            layout = Some(TableLayout {
+                                address: table,
+                                entry_size,
+                                signed,
+                                base: Some(base),
+                                shift: shift.unwrap_or(0),
+                            });
+                        }
+                        continue;
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        // Anything else that writes to a register makes what's known about it stale.
+        values.remove(&dest);
+        loads.remove(&dest);
+    }
+    layout
+}
+
+/// Returns the 64-bit name of a general purpose register, so that `w9` and `x9` match.
+fn arm64_register(name: &str) -> String {
+    let name = name.trim();
+    match name.strip_prefix('w') {
+        Some(number) if number.chars().all(|c| c.is_ascii_digit()) => format!("x{}", number),
+        _ => name.to_string(),
+    }
+}
+
+/// Finds the number of entries in a table from the bounds check of the index before the
+/// jump, e.g. `cmp eax, 0x1f` and `ja` for 32 entries.
+fn bounds_check(lines: &[DisasmLine], jump: usize) -> Option<usize> {
+    let start = jump.saturating_sub(SEARCH_WINDOW);
+    for idx in (start..jump).rev() {
+        let line = &lines[idx];
+        if !line.mnemonic().starts_with("cmp") {
+            continue;
+        }
+        let branch = lines.get(idx + 1)?;
+        let inclusive = match branch.mnemonic() {
+            "ja" | "jnbe" | "b.hi" => true,
+            "jae" | "jnb" | "b.hs" | "b.cs" => false,
+            _ => continue,
+        };
+
+        // The immediate is the last operand in Intel syntax and the first one in AT&T.
+        let operands = split_operands(line.operands());
+        let limit = operands
+            .iter()
+            .find_map(|operand| parse_immediate(operand))? as usize;
+        // An unsigned comparison with `-1` doesn't bound the index at all.
+        return if inclusive {
+            limit.checked_add(1)
+        } else {
+            Some(limit)
+        };
+    }
+    None
+}
+
+/// Splits operands at the commas that aren't inside of brackets.
+fn split_operands(operands: &str) -> Vec<String> {
+    let mut split = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in operands.chars() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                split.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if !current.trim().is_empty() {
+        split.push(current.trim().to_string());
+    }
+    split
+}
+
+/// Parses an immediate operand like `0x1f`, `#0x1f`, `$0x1f`, or `31`.
+fn parse_immediate(operand: &str) -> Option<u64> {
+    let operand = operand.trim().trim_start_matches(['#', '$']);
+    if let Some(hex) = operand.strip_prefix("0x") {
+        u64::from_str_radix(hex, 16).ok()
+    } else {
+        operand.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::disasm::binary::BinaryData;
+
+    fn line(mnemonic: &str, operands: &str) -> DisasmLine {
+        DisasmLine {
+            address: 0,
+            mnemonic: mnemonic.into(),
+            operands: operands.into(),
+            comments: None,
+            bytes: Box::new([]),
+            source_lines: None,
+            source_locations: None,
+            inlined_calls: None,
+            jump: Jump::None,
+            flow: Flow::Next,
+            memory_reference: None,
+            simd: None,
+            is_symbolicated_jump: false,
+        }
+    }
+
+    /// Returns the number of entries found for a jump after `lines`.
+    fn bounds(lines: &[(&str, &str)]) -> Option<usize> {
+        let mut lines = lines
+            .iter()
+            .map(|&(mnemonic, operands)| line(mnemonic, operands))
+            .collect::<Vec<_>>();
+        lines.push(line("jmp", "rax"));
+        bounds_check(&lines, lines.len() - 1)
+    }
+
+    /// Loads the bytes of a flat x86_64 binary at 0x1000.
+    fn raw_binary(name: &str, bytes: &[u8]) -> Binary {
+        let path = std::env::temp_dir().join(format!(
+            "cargo-disasm-jump-table-{}-{}",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, bytes).unwrap();
+        let data = BinaryData::from_path(&path).unwrap();
+        let binary = Binary::new_raw(data, Arch::X86_64, 0x1000).unwrap();
+        let _ = std::fs::remove_file(&path);
+        binary
+    }
+
+    #[test]
+    fn bounds_check_intel_and_att() {
+        assert_eq!(bounds(&[("cmp", "eax, 0x1f"), ("ja", "0x1040")]), Some(32));
+        assert_eq!(
+            bounds(&[("cmpl", "$0x1f, %eax"), ("ja", "0x1040")]),
+            Some(32)
+        );
+        assert_eq!(bounds(&[("cmp", "eax, 5"), ("jae", "0x1040")]), Some(5));
+        assert_eq!(
+            bounds(&[
+                ("cmp", "rax, 0xffffffffffffffff"),
+                ("ja", "0x1040"),
+                ("lea", "rcx, [rip + 0x10]"),
+            ]),
+            None
+        );
+    }
+
+    #[test]
+    fn bounds_check_arm64() {
+        assert_eq!(
+            bounds(&[("cmp", "w8, #7"), ("b.hi", "#0x1040"), ("adrp", "x9, #0")]),
+            Some(8)
+        );
+        assert_eq!(
+            bounds(&[("cmp", "x8, #0x10"), ("b.hs", "#0x1040")]),
+            Some(16)
+        );
+    }
+
+    #[test]
+    fn bounds_check_without_a_branch() {
+        assert_eq!(bounds(&[]), None);
+        assert_eq!(bounds(&[("cmp", "eax, 0x1f"), ("mov", "ecx, eax")]), None);
+        assert_eq!(bounds(&[("cmp", "eax, 0x1f"), ("jne", "0x1040")]), None);
+    }
+
+    #[test]
+    fn read_relative_targets() {
+        let mut bytes = vec![0xcc; 0x40];
+        for (idx, entry) in [-0x20i32, 0x08, -0x10, 0x100].iter().enumerate() {
+            bytes[(0x20 + idx * 4)..(0x24 + idx * 4)].copy_from_slice(&entry.to_le_bytes());
+        }
+        let binary = raw_binary("relative", &bytes);
+        let symbol = binary.symbols()[0];
+        let layout = TableLayout {
+            address: 0x1020,
+            entry_size: 4,
+            signed: true,
+            base: Some(0x1020),
+            shift: 0,
+        };
+
+        // Without a bounds check the entries are read until one is outside of the function.
+        assert_eq!(
+            read_targets(&binary, symbol, layout, None),
+            vec![0x1000, 0x1028, 0x1010]
+        );
+        assert_eq!(
+            read_targets(&binary, symbol, layout, Some(2)),
+            vec![0x1000, 0x1028]
+        );
+        // A table with an entry outside of the function isn't a jump table.
+        assert_eq!(
+            read_targets(&binary, symbol, layout, Some(4)),
+            Vec::<u64>::new()
+        );
+    }
+
+    #[test]
+    fn read_absolute_targets() {
+        let mut bytes = vec![0xcc; 0x40];
+        bytes[0x30..0x38].copy_from_slice(&0x1004u64.to_le_bytes());
+        bytes[0x38..0x40].copy_from_slice(&0x1008u64.to_le_bytes());
+        let binary = raw_binary("absolute", &bytes);
+        let symbol = binary.symbols()[0];
+        let layout = TableLayout {
+            address: 0x1030,
+            entry_size: 8,
+            signed: false,
+            base: None,
+            shift: 0,
+        };
+
+        // The table ends with the function.
+        assert_eq!(
+            read_targets(&binary, symbol, layout, None),
+            vec![0x1004, 0x1008]
+        );
+    }
+}
@@ -1,30 +1,131 @@
+pub mod atomics;
 pub mod binary;
+pub mod callgraph;
+pub mod cfg;
+pub mod context;
+pub mod coredump;
+pub mod cost;
+pub mod demangle;
+pub mod diff;
 pub mod display;
+pub mod dwarf_expr;
+pub mod explain;
+pub mod frame;
+pub mod jump_table;
+pub mod panics;
+pub mod section;
+pub mod signature;
 pub mod source;
+pub mod stats;
 pub mod symbol;
+pub mod symbol_table;
+pub mod syscall;
+pub mod tls;
+pub mod unwind;
+pub mod vars;
+pub mod vector;
 
 mod anal;
+mod backend;
+mod debuginfod;
 mod dwarf;
 mod pdb;
 pub mod strmatch;
+mod wasm;
 
-pub use self::anal::Jump;
+pub use self::anal::{Flow, Jump};
+use self::backend::Backend;
+pub use self::backend::Engine;
 use self::binary::Binary;
 use self::symbol::Symbol;
 use anyhow::Context as _;
 use capstone::Capstone;
-use source::SourceLoader;
+use source::{InlinedCall, SourceLoader, SourceLocation};
+use vector::SimdClass;
 
-pub fn disasm(binary: &Binary, symbol: &Symbol, load_source: bool) -> anyhow::Result<Disassembly> {
+/// Options for configuring how symbols are disassembled.
+#[derive(Copy, Clone, Default)]
+pub struct Options {
+    /// Load the source lines associated with each instruction.
+    pub load_source: bool,
+
+    /// Find the calls that were inlined into the disassembled symbol.
+    pub load_inlined_calls: bool,
+
+    /// Comment the instructions that access local variables with the names of the
+    /// variables, from the DWARF debug information.
+    pub load_variables: bool,
+
+    /// The assembly syntax to use for architectures that support more than one.
+    pub syntax: Syntax,
+
+    /// Stop disassembling at the first invalid instruction instead of showing its bytes
+    /// as data and continuing after it.
+    pub no_skip_data: bool,
+
+    /// The disassembler to use for instructions.
+    pub engine: Engine,
+
+    /// Comment the instructions that follow a recognizable idiom with pseudocode, e.g.
+    /// `rax = rdi + rsi*4`.
+    pub explain: bool,
+}
+
+/// Assembly syntax for x86 and x86_64.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Syntax {
+    #[default]
+    Intel,
+    Att,
+}
+
+impl std::str::FromStr for Syntax {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("intel") {
+            Ok(Syntax::Intel)
+        } else if s.eq_ignore_ascii_case("att") || s.eq_ignore_ascii_case("at&t") {
+            Ok(Syntax::Att)
+        } else {
+            Err("invalid assembly syntax")
+        }
+    }
+}
+
+pub fn disasm(binary: &Binary, symbol: &Symbol, options: Options) -> anyhow::Result<Disassembly> {
     let disasm_timer = std::time::Instant::now();
-    let caps = capstone_for_binary(binary)?;
     let mut disassembly = Disassembly::new();
-    let source_loader = if load_source {
-        Some(SourceLoader::new())
+
+    if binary.arch() == binary::Arch::Wasm {
+        wasm::disasm_symbol_lines(binary, symbol, &mut disassembly)?;
+        symbolicate_and_internalize_jumps(binary, symbol, &mut disassembly);
     } else {
-        None
-    };
-    disasm_symbol_lines(&caps, binary, symbol, source_loader, &mut disassembly)?;
+        let mut backend = backend::for_binary(binary, &options)?;
+        let source_loader = if options.load_source {
+            Some(SourceLoader::new(binary.build_id()))
+        } else {
+            None
+        };
+        disasm_symbol_lines(
+            &mut *backend,
+            binary,
+            symbol,
+            source_loader,
+            options.load_inlined_calls,
+            &mut disassembly,
+        )?;
+        disassembly.jump_tables = jump_table::find_jump_tables(binary, symbol, disassembly.lines());
+        tls::annotate(binary, &mut disassembly);
+        syscall::annotate(binary, &mut disassembly);
+        atomics::annotate(binary, &mut disassembly);
+        if options.load_variables {
+            vars::annotate(binary, symbol, &mut disassembly)?;
+        }
+        if options.explain {
+            explain::annotate(binary, &mut disassembly);
+        }
+    }
     log::trace!(
         "disassembled symbol {} in {}",
         symbol.name(),
@@ -34,48 +135,285 @@ pub fn disasm(binary: &Binary, symbol: &Symbol, load_source: bool) -> anyhow::Re
 }
 
 fn disasm_symbol_lines(
-    caps: &Capstone,
+    backend: &mut dyn Backend,
     binary: &Binary,
     symbol: &Symbol,
     mut source_loader: Option<SourceLoader>,
+    load_inlined_calls: bool,
+    disassembly: &mut Disassembly,
+) -> anyhow::Result<()> {
+    for (range, code_mode) in binary.code_regions(symbol) {
+        let start = symbol.offset() + (range.start - symbol.address()) as usize;
+        let code = &binary.data()[start..(start + (range.end - range.start) as usize)];
+
+        // ARM binaries can switch between ARM and Thumb code and have data, such as literal
+        // pools, in between functions.
+        match code_mode {
+            Some(binary::CodeMode::Data) => {
+                push_data_lines(binary, code, range.start, disassembly);
+                continue;
+            }
+            Some(code_mode) => backend.set_code_mode(code_mode)?,
+            None => {}
+        }
+
+        disasm_code_lines(
+            backend,
+            binary,
+            code,
+            range.start,
+            &mut source_loader,
+            load_inlined_calls,
+            disassembly,
+        )?;
+    }
+    symbolicate_and_internalize_jumps(binary, symbol, disassembly);
+    Ok(())
+}
+
+fn disasm_code_lines(
+    backend: &mut dyn Backend,
+    binary: &Binary,
+    code: &[u8],
+    address: u64,
+    source_loader: &mut Option<SourceLoader>,
+    load_inlined_calls: bool,
     disassembly: &mut Disassembly,
 ) -> anyhow::Result<()> {
-    for insn in caps.disasm_iter(
-        &binary.data()[symbol.offset()..symbol.end()],
-        symbol.address(),
-    ) {
-        let insn = insn.context("failed to disassemble instruction")?;
-        let jump = anal::identify_jump_target(insn, caps);
+    backend.decode(code, address, &mut |insn| {
+        if insn.is_data {
+            push_data_lines(binary, insn.bytes, insn.address, disassembly);
+            return Ok(());
+        }
+
+        let mut jump = insn.jump;
+        let flow = insn.flow;
+        let insn_end = insn.address + insn.bytes.len() as u64;
+
+        let mut comments = None;
+        let mut operands = None;
+        if let Some((reloc_addr, reloc)) = binary.relocation_in(insn.address..insn_end) {
+            // The operands of relocated instructions are just placeholders until the
+            // object is linked, so they're replaced with the target of the relocation.
+            let is_branch = matches!(flow, Flow::Call | Flow::Jump | Flow::ConditionalJump);
+            match reloc.resolve(reloc_addr, insn_end) {
+                Some(target) if is_branch => jump = Jump::External(target),
+                target => {
+                    jump = Jump::None;
+                    let symbolic = match reloc.offset(reloc_addr, insn_end) {
+                        0 => reloc.target().to_string(),
+                        offset if offset < 0 => format!("{}-0x{:x}", reloc.target(), -offset),
+                        offset => format!("{}+0x{:x}", reloc.target(), offset),
+                    };
+                    operands = relocated_operands(insn.operands, &symbolic);
+                    comments = match target.and_then(|addr| binary.string_at(addr)) {
+                        Some(string) => Some(format!("{:?}", string).into()),
+                        None if operands.is_none() => Some(symbolic.into()),
+                        None => None,
+                    };
+                }
+            }
+        } else if let Jump::None = jump {
+            if let Some(addr) = insn.memory_reference {
+                if let Some(string) = binary.string_at(addr) {
+                    comments = Some(format!("{:?}", string).into());
+                } else if let Some(reloc) = binary
+                    .data_relocation_at(addr)
+                    .filter(|reloc| !reloc.is_thread_local())
+                {
+                    // Position independent code loads the addresses of symbols from GOT
+                    // entries and other data that is filled in by dynamic relocations.
+                    comments = Some(format!("&{}", relocation_target(binary, reloc)).into());
+                }
+            }
+        }
 
         let mut source_lines = Vec::new();
-        if let Some(ref mut source_loader) = source_loader {
+        let mut source_locations = Vec::new();
+        if let Some(ref mut source_loader) = *source_loader {
+            source_locations.extend(
+                binary
+                    .addr2line(insn.address)?
+                    .into_iter()
+                    .flatten()
+                    .map(|(path, line)| SourceLocation::new(path, line)),
+            );
             source_loader
                 .load_lines(
-                    binary.addr2line(insn.address())?.iter_mut().flatten(),
+                    source_locations.iter().map(|l| (l.path(), l.line())),
                     &mut source_lines,
                 )
                 .context("error while loading sources for line")?;
         }
+        let mut inlined_calls = Vec::new();
+        if load_inlined_calls {
+            inlined_calls.extend(
+                binary
+                    .inlined_calls_at(insn.address)?
+                    .into_iter()
+                    .flatten()
+                    .cloned(),
+            );
+        }
+
         let source_lines = if source_lines.is_empty() {
             None
         } else {
             Some(source_lines.into_boxed_slice())
         };
+        let source_locations = if source_locations.is_empty() {
+            None
+        } else {
+            Some(source_locations.into_boxed_slice())
+        };
+        let inlined_calls = if inlined_calls.is_empty() {
+            None
+        } else {
+            Some(inlined_calls.into_boxed_slice())
+        };
 
         let line = DisasmLine {
-            address: insn.address(),
-            mnemonic: insn.mnemonic().into(),
-            operands: insn.operands().into(),
-            comments: None,
-            bytes: insn.bytes().to_vec().into_boxed_slice(),
+            address: insn.address,
+            mnemonic: insn.mnemonic.into(),
+            operands: operands.map_or_else(|| insn.operands.into(), String::into_boxed_str),
+            comments,
+            bytes: insn.bytes.to_vec().into_boxed_slice(),
             source_lines,
+            source_locations,
+            inlined_calls,
             jump,
+            flow,
+            memory_reference: insn.memory_reference,
+            simd: insn
+                .simd
+                .or_else(|| SimdClass::from_operands(binary.arch(), insn.operands)),
             is_symbolicated_jump: false,
         };
         disassembly.push_line(line);
+        Ok(())
+    })
+}
+
+/// Replaces the placeholder that the relocated field of an instruction is shown as with the
+/// target of the relocation, e.g. `[rip]` becomes `[rip + foo+0x8]` and `mov edi, 0` becomes
+/// `mov edi, foo`. Returns `None` if the placeholder can't be found.
+fn relocated_operands(operands: &str, symbolic: &str) -> Option<String> {
+    let is_number = |token: &str| {
+        let token = token.trim().trim_start_matches('$').trim_start_matches('-');
+        match token.strip_prefix("0x") {
+            Some(hex) => !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()),
+            None => !token.is_empty() && token.chars().all(|c| c.is_ascii_digit()),
+        }
+    };
+
+    // Intel syntax: `[rip + 0x0]`, `[rip]`, or an absolute address like `[0x0]`.
+    if let Some(start) = operands.find('[') {
+        let end = start + operands[start..].find(']')?;
+        let memory = &operands[(start + 1)..end];
+        let replacement = if memory.starts_with("rip") || memory.starts_with("eip") {
+            format!("[{} + {}]", &memory[..3], symbolic)
+        } else if is_number(memory) {
+            format!("[{}]", symbolic)
+        } else {
+            return None;
+        };
+        return Some(format!(
+            "{}{}{}",
+            &operands[..start],
+            replacement,
+            &operands[(end + 1)..]
+        ));
+    }
+
+    // AT&T syntax: `0x0(%rip)` or `(%rip)`.
+    if let Some(paren) = operands.find("(%rip)") {
+        let start = operands[..paren].rfind([' ', ',']).map_or(0, |idx| idx + 1);
+        return Some(format!(
+            "{}{}{}",
+            &operands[..start],
+            symbolic,
+            &operands[paren..]
+        ));
+    }
+
+    // Immediates are the last operand in Intel syntax and the first one in AT&T syntax.
+    let first_end = operands.find(',').unwrap_or(operands.len());
+    let last_start = operands.rfind(',').map_or(0, |idx| idx + 1);
+    for &(start, end) in [(last_start, operands.len()), (0, first_end)].iter() {
+        let token = &operands[start..end];
+        if is_number(token) {
+            let prefix = if token.trim().starts_with('$') {
+                "$"
+            } else {
+                ""
+            };
+            let space = if start > 0 { " " } else { "" };
+            return Some(format!(
+                "{}{}{}{}{}",
+                &operands[..start],
+                space,
+                prefix,
+                symbolic,
+                &operands[end..]
+            ));
+        }
+    }
+    None
+}
+
+/// Returns the symbol that a dynamic relocation points to, with the offset into it.
+fn relocation_target(binary: &Binary, reloc: &binary::Relocation) -> String {
+    if reloc.target().is_empty() {
+        let address = reloc.target_address().unwrap_or(0);
+        return match binary.symbolicate(address) {
+            Some((symbol, 0)) => symbol.name().to_string(),
+            Some((symbol, offset)) => format!("{}+0x{:x}", symbol.name(), offset),
+            None => format!("0x{:x}", address),
+        };
+    }
+    match reloc.addend() {
+        0 => reloc.target().to_string(),
+        addend if addend < 0 => format!("{}-0x{:x}", reloc.target(), -addend),
+        addend => format!("{}+0x{:x}", reloc.target(), addend),
+    }
+}
+
+/// Pushes lines for data in between code, as `.word`s where they're aligned and as
+/// `.byte`s everywhere else.
+fn push_data_lines(binary: &Binary, data: &[u8], address: u64, disassembly: &mut Disassembly) {
+    let mut offset = 0;
+    while offset < data.len() {
+        let line_address = address + offset as u64;
+        let (len, mnemonic, operands) =
+            if line_address.is_multiple_of(4) && data.len() - offset >= 4 {
+                let mut word = [0; 4];
+                word.copy_from_slice(&data[offset..(offset + 4)]);
+                let word = match binary.endian() {
+                    binary::Endian::Big => u32::from_be_bytes(word),
+                    _ => u32::from_le_bytes(word),
+                };
+                (4, ".word", format!("0x{:08x}", word))
+            } else {
+                (1, ".byte", format!("0x{:02x}", data[offset]))
+            };
+
+        disassembly.push_line(DisasmLine {
+            address: line_address,
+            mnemonic: mnemonic.into(),
+            operands: operands.into(),
+            comments: None,
+            bytes: data[offset..(offset + len)].to_vec().into_boxed_slice(),
+            source_lines: None,
+            source_locations: None,
+            inlined_calls: None,
+            jump: Jump::None,
+            flow: Flow::Next,
+            memory_reference: None,
+            simd: None,
+            is_symbolicated_jump: false,
+        });
+        offset += len;
     }
-    symbolicate_and_internalize_jumps(binary, symbol, disassembly);
-    Ok(())
 }
 
 fn symbolicate_and_internalize_jumps(
@@ -84,18 +422,22 @@ fn symbolicate_and_internalize_jumps(
     disassembly: &mut Disassembly,
 ) {
     for idx in 0..disassembly.lines.len() {
-        let jump_addr = if let Jump::External(addr) = disassembly.lines[idx].jump {
-            addr
-        } else {
-            continue;
+        let jump_addr = match disassembly.lines[idx].jump {
+            Jump::External(addr) => addr,
+            Jump::Indirect(slot_addr) => {
+                if let Some(import) = binary.import_at(slot_addr) {
+                    disassembly.lines[idx].comments = Some(import.into());
+                }
+                continue;
+            }
+            _ => continue,
         };
 
         // This is an internal jump, so we can skip the more
         // expensive symbolication step.
         if symbol.address_range().contains(&jump_addr) {
-            disassembly.lines[idx].operands =
-                format!("{}+0x{:x}", symbol.name(), jump_addr - symbol.address()).into();
-            disassembly.lines[idx].comments = Some(format!("0x{:x}", jump_addr).into());
+            disassembly.lines[idx].comments =
+                Some(format!("{}+0x{:x}", symbol.name(), jump_addr - symbol.address()).into());
             disassembly.lines[idx].is_symbolicated_jump = true;
 
             if let Some(index) = disassembly
@@ -105,23 +447,25 @@ fn symbolicate_and_internalize_jumps(
             {
                 disassembly.lines[idx].jump = Jump::Internal(index);
             }
+        } else if let Some(import) = binary.import_at(jump_addr) {
+            disassembly.lines[idx].comments = Some(format!("-> {}", import).into());
+            disassembly.lines[idx].is_symbolicated_jump = true;
         } else if let Some((symbol, offset)) = binary.symbolicate(jump_addr) {
             if offset == 0 {
-                disassembly.lines[idx].operands = symbol.name().into();
+                disassembly.lines[idx].comments = Some(symbol.name().into());
             } else {
-                disassembly.lines[idx].operands =
-                    format!("{}+0x{:x}", symbol.name(), offset).into();
+                disassembly.lines[idx].comments =
+                    Some(format!("{}+0x{:x}", symbol.name(), offset).into());
             }
-            disassembly.lines[idx].comments = Some(format!("0x{:x}", jump_addr).into());
             disassembly.lines[idx].is_symbolicated_jump = true;
         }
     }
 }
 
 /// Creates a Capstone instance for the binary.
-fn capstone_for_binary(binary: &Binary) -> anyhow::Result<Capstone> {
+fn capstone_for_binary(binary: &Binary, syntax: Syntax) -> anyhow::Result<Capstone> {
     use binary::Arch as BinArch;
-    use capstone::{Arch as CapArch, Mode};
+    use capstone::Arch as CapArch;
 
     let capstone_arch = match binary.arch() {
         BinArch::Unknown => {
@@ -129,12 +473,45 @@ fn capstone_for_binary(binary: &Binary) -> anyhow::Result<Capstone> {
                 "unknown or unsupported binary architecture"
             ))
         }
+        BinArch::Wasm => return Err(anyhow::anyhow!("WebAssembly is not supported by Capstone")),
+        // FIXME the bindings of the bundled Capstone don't include RISC-V yet. Binaries with
+        // compressed instructions will need its RISCVC mode as well.
+        BinArch::RiscV32 | BinArch::RiscV64 => {
+            return Err(anyhow::anyhow!(
+                "{} is not supported by the bundled version of Capstone",
+                binary.arch()
+            ))
+        }
         BinArch::X86 => CapArch::X86,
         BinArch::X86_64 => CapArch::X86,
-        BinArch::Arm => CapArch::Arm,
+        BinArch::Arm | BinArch::Thumb => CapArch::Arm,
         BinArch::AArch64 => CapArch::Arm64,
+        BinArch::Mips | BinArch::Mips64 => CapArch::Mips,
+        BinArch::PowerPc | BinArch::PowerPc64 => CapArch::PowerPc,
+        BinArch::Sparc | BinArch::Sparc64 => CapArch::Sparc,
     };
 
+    let mut caps = Capstone::open(capstone_arch, capstone_mode(binary))
+        .context("failed to initialize Capstone")?;
+    caps.set_details_enabled(true)
+        .context("failed to enable Capstone detail mode")?;
+
+    if capstone_arch == CapArch::X86 {
+        caps.set_syntax(match syntax {
+            Syntax::Intel => capstone::Syntax::Intel,
+            Syntax::Att => capstone::Syntax::Att,
+        })
+        .context("failed to set Capstone assembly syntax")?;
+    }
+
+    Ok(caps)
+}
+
+/// Returns the Capstone mode for the binary's architecture and byte order.
+fn capstone_mode(binary: &Binary) -> capstone::Mode {
+    use binary::Arch as BinArch;
+    use capstone::Mode;
+
     let mut mode = Mode::empty();
 
     match binary.endian() {
@@ -146,24 +523,30 @@ fn capstone_for_binary(binary: &Binary) -> anyhow::Result<Capstone> {
         binary::Endian::Unknown => mode |= Mode::BigEndian,
     }
 
-    if binary.arch() == BinArch::X86_64 {
-        mode |= Mode::Bits64;
+    match binary.arch() {
+        BinArch::X86_64 | BinArch::PowerPc64 => mode |= Mode::Bits64,
+        BinArch::Thumb => mode |= Mode::Thumb,
+        BinArch::Mips => mode |= Mode::Mips32,
+        BinArch::Mips64 => mode |= Mode::Mips64,
+        BinArch::PowerPc => mode |= Mode::Bits32,
+        BinArch::Sparc64 => mode |= Mode::V9,
+        _ => {}
     }
 
-    let mut caps = Capstone::open(capstone_arch, mode).context("failed to initialize Capstone")?;
-    caps.set_details_enabled(true)
-        .context("failed to enable Capstone detail mode")?;
-
-    Ok(caps)
+    mode
 }
 
 pub struct Disassembly {
     lines: Vec<DisasmLine>,
+    jump_tables: Vec<jump_table::JumpTable>,
 }
 
 impl Disassembly {
     fn new() -> Disassembly {
-        Disassembly { lines: Vec::new() }
+        Disassembly {
+            lines: Vec::new(),
+            jump_tables: Vec::new(),
+        }
     }
 
     fn push_line(&mut self, line: DisasmLine) {
@@ -173,6 +556,12 @@ impl Disassembly {
     pub fn lines(&self) -> &[DisasmLine] {
         &*self.lines
     }
+
+    /// The jump tables of the indirect jumps in the disassembly, sorted by the index of
+    /// their jump.
+    pub fn jump_tables(&self) -> &[jump_table::JumpTable] {
+        &self.jump_tables
+    }
 }
 
 pub struct DisasmLine {
@@ -182,7 +571,12 @@ pub struct DisasmLine {
     comments: Option<Box<str>>,
     bytes: Box<[u8]>,
     source_lines: Option<Box<[Box<str>]>>,
+    source_locations: Option<Box<[SourceLocation]>>,
+    inlined_calls: Option<Box<[InlinedCall]>>,
     jump: Jump,
+    flow: Flow,
+    memory_reference: Option<u64>,
+    simd: Option<SimdClass>,
     is_symbolicated_jump: bool,
 }
 
@@ -215,10 +609,36 @@ impl DisasmLine {
         self.source_lines.as_deref().unwrap_or(&[])
     }
 
+    /// The files and line numbers that this instruction was generated from.
+    pub fn source_locations(&self) -> &[SourceLocation] {
+        self.source_locations.as_deref().unwrap_or(&[])
+    }
+
+    /// The inlined calls that begin at this instruction.
+    pub fn inlined_calls(&self) -> &[InlinedCall] {
+        self.inlined_calls.as_deref().unwrap_or(&[])
+    }
+
     pub fn jump(&self) -> Jump {
         self.jump
     }
 
+    /// How this instruction affects the flow of execution.
+    pub fn flow(&self) -> Flow {
+        self.flow
+    }
+
+    /// The address of the memory that this instruction references, if it isn't a jump and
+    /// the address doesn't depend on the values of registers.
+    pub fn memory_reference(&self) -> Option<u64> {
+        self.memory_reference
+    }
+
+    /// The SIMD instruction set extension of this instruction, if it is a SIMD instruction.
+    pub fn simd_class(&self) -> Option<SimdClass> {
+        self.simd
+    }
+
     pub fn is_symbolicated_jump(&self) -> bool {
         self.is_symbolicated_jump
     }
@@ -0,0 +1,215 @@
+//! Finds the cold paths of functions that only lead to a panic, such as the failed bounds
+//! checks and `unwrap`s, so that they can be folded away in listings, and the bounds and
+//! overflow checks that branch to them.
+
+use super::cfg::ControlFlowGraph;
+use super::{DisasmLine, Disassembly, Flow, Jump};
+use std::fmt;
+use std::ops::Range;
+
+/// How many instructions of a basic block are searched for a call to a function that
+/// panics.
+const PANIC_WINDOW: usize = 16;
+
+/// The prefixes of the names of the functions that panic, abort, or resume unwinding and
+/// never return.
+const PANIC_FUNCTIONS: &[&str] = &[
+    "core::panicking::",
+    "std::panicking::",
+    "core::panic::",
+    "std::panic::",
+    "core::result::unwrap_failed",
+    "core::option::unwrap_failed",
+    "core::option::expect_failed",
+    "core::slice::index::slice_",
+    "core::str::slice_error_fail",
+    "core::cell::panic_already_",
+    "alloc::alloc::handle_alloc_error",
+    "alloc::raw_vec::capacity_overflow",
+    "alloc::raw_vec::handle_error",
+    "std::process::abort",
+    "std::alloc::rust_oom",
+    "_Unwind_Resume",
+    "__rust_start_panic",
+    "rust_panic",
+];
+
+/// A run of consecutive lines that can only end in a panic.
+#[derive(Clone, Debug)]
+pub struct PanicPath {
+    /// The indices of the lines of the path.
+    pub lines: Range<usize>,
+
+    /// The name of the function that panics, e.g. `core::panicking::panic_bounds_check`.
+    pub panic: Box<str>,
+}
+
+/// Finds the basic blocks that call a function that panics and the blocks that can only go
+/// on to them, and merges the ones next to each other into paths.
+pub fn find_panic_paths(disassembly: &Disassembly) -> Vec<PanicPath> {
+    let lines = disassembly.lines();
+    let cfg = ControlFlowGraph::new(disassembly);
+    let blocks = cfg.blocks();
+
+    let mut panics: Vec<Option<&str>> = blocks
+        .iter()
+        .map(|block| {
+            lines[block.lines()]
+                .iter()
+                .filter(|line| {
+                    matches!(line.flow(), Flow::Call | Flow::Jump)
+                        && matches!(line.jump(), Jump::External(_) | Jump::Indirect(_))
+                })
+                .map(|line| line.comments().trim_start_matches("-> "))
+                .find(|&name| is_panic_function(name))
+        })
+        .collect();
+
+    // Blocks that only go on to panicking blocks panic too, e.g. a block that sets up the
+    // arguments of the panic and jumps to a shared call.
+    loop {
+        let mut changed = false;
+        for (idx, block) in blocks.iter().enumerate() {
+            if panics[idx].is_some() || block.edges().is_empty() {
+                continue;
+            }
+            let last = &lines[block.lines().end - 1];
+            if last.flow() == Flow::Return {
+                continue;
+            }
+            let mut targets = block.edges().iter().map(|edge| panics[edge.target()]);
+            let first = targets.next().flatten();
+            if first.is_some() && targets.all(|panic| panic.is_some()) {
+                panics[idx] = first;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut paths: Vec<PanicPath> = Vec::new();
+    for (block, panic) in blocks.iter().zip(panics) {
+        let panic = match panic {
+            Some(panic) => panic,
+            None => continue,
+        };
+        match paths.last_mut() {
+            Some(path) if path.lines.end == block.lines().start => {
+                path.lines.end = block.lines().end;
+            }
+            _ => paths.push(PanicPath {
+                lines: block.lines(),
+                panic: panic.into(),
+            }),
+        }
+    }
+    paths
+}
+
+/// The kinds of checks that the compiler inserts, which panic when they fail.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CheckKind {
+    /// A check that an index or range is in the bounds of a slice.
+    Bounds,
+
+    /// A check that arithmetic didn't overflow, in debug builds or with
+    /// `overflow-checks = true`.
+    Overflow,
+}
+
+impl CheckKind {
+    /// Returns the kind of check that panics with a function, e.g. a bounds check for
+    /// `core::panicking::panic_bounds_check`.
+    pub fn of(panic: &str) -> Option<CheckKind> {
+        if panic.contains("bounds_check")
+            || panic.contains("index_len_fail")
+            || panic.contains("index_order_fail")
+        {
+            Some(CheckKind::Bounds)
+        } else if panic.contains("overflow") && !panic.contains("capacity_overflow") {
+            Some(CheckKind::Overflow)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for CheckKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckKind::Bounds => f.write_str("bounds check"),
+            CheckKind::Overflow => f.write_str("overflow check"),
+        }
+    }
+}
+
+/// A conditional jump that either goes to a panic or skips over one.
+#[derive(Clone, Debug)]
+pub struct Check {
+    /// The index of the line of the conditional jump.
+    pub line: usize,
+
+    pub kind: CheckKind,
+}
+
+/// Finds the bounds and overflow checks of a function, which are the conditional jumps
+/// where one of the two paths goes straight to a function that panics because of them.
+pub fn find_checks(disassembly: &Disassembly) -> Vec<Check> {
+    let lines = disassembly.lines();
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.flow() == Flow::ConditionalJump)
+        .filter_map(|(idx, line)| {
+            let kind = jump_panic(line, lines)
+                .and_then(CheckKind::of)
+                .or_else(|| block_panic(lines, idx + 1).and_then(CheckKind::of))?;
+            Some(Check { line: idx, kind })
+        })
+        .collect()
+}
+
+/// Returns the function that panics that a jump goes to, either directly or through the
+/// basic block that it jumps to.
+pub(super) fn jump_panic<'d>(line: &'d DisasmLine, lines: &'d [DisasmLine]) -> Option<&'d str> {
+    match line.jump() {
+        Jump::Internal(target) => block_panic(lines, target),
+        Jump::External(_) | Jump::Indirect(_) => {
+            Some(line.comments().trim_start_matches("-> ")).filter(|&name| is_panic_function(name))
+        }
+        Jump::None => None,
+    }
+}
+
+/// Returns the function that the basic block starting at `start` calls if it calls one
+/// that panics.
+pub(super) fn block_panic(lines: &[DisasmLine], start: usize) -> Option<&str> {
+    for line in lines.iter().skip(start).take(PANIC_WINDOW) {
+        if matches!(line.flow(), Flow::Call | Flow::Jump)
+            && matches!(line.jump(), Jump::External(_) | Jump::Indirect(_))
+        {
+            let name = line.comments().trim_start_matches("-> ");
+            if is_panic_function(name) {
+                return Some(name);
+            }
+        }
+        if line.flow().is_terminator() {
+            break;
+        }
+    }
+    None
+}
+
+/// Whether a function never returns because it panics or unwinds. The names of
+/// symbols that are symbolicated with an offset (`foo+0x10`) aren't calls to functions.
+pub(super) fn is_panic_function(name: &str) -> bool {
+    !name.is_empty()
+        && !name.contains('+')
+        && (PANIC_FUNCTIONS
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+            // Mangled names, with `--no-demangle`.
+            || name.contains("panicking"))
+}
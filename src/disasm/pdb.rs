@@ -1,28 +1,43 @@
 use crate::disasm::binary::BinaryData;
 use crate::disasm::symbol::{Symbol, SymbolSource};
+use crate::util;
 use ::pdb::{AddressMap, FallibleIterator as _, ImageSectionHeader, ModuleInfo, SymbolData, PDB};
 use anyhow::Context as _;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 pub struct PDBInfo {
     pdb: PDB<'static, BinaryData>,
+
+    /// The address that the image is expected to be loaded at.
+    image_base: u64,
+
+    /// Lines sorted by their address in ascending order.
+    lines: Vec<Line>,
+    files: Vec<PathBuf>,
+    lines_initialized: bool,
 }
 
 impl PDBInfo {
-    pub fn new(data: BinaryData) -> anyhow::Result<PDBInfo> {
+    pub fn new(data: BinaryData, image_base: u64) -> anyhow::Result<PDBInfo> {
         PDB::open(data)
-            .map(PDBInfo::with_pdb)
+            .map(|pdb| PDBInfo::with_pdb(pdb, image_base))
             .context("error while opening PDB")
     }
 
-    fn with_pdb(pdb: PDB<'static, BinaryData>) -> Self {
-        PDBInfo { pdb }
+    fn with_pdb(pdb: PDB<'static, BinaryData>, image_base: u64) -> Self {
+        PDBInfo {
+            pdb,
+            image_base,
+            lines: Vec::new(),
+            files: Vec::new(),
+            lines_initialized: false,
+        }
     }
 
-    pub fn load_symbols(
-        &mut self,
-        image_base: u64,
-        symbols: &mut Vec<Symbol>,
-    ) -> anyhow::Result<()> {
+    pub fn load_symbols(&mut self, symbols: &mut Vec<Symbol>) -> anyhow::Result<()> {
+        let image_base = self.image_base;
+
         let sections = if let Some(sections) = self
             .pdb
             .sections()
@@ -116,4 +131,130 @@ impl PDBInfo {
         }
         Ok(())
     }
+
+    /// This will load the line programs for all of the modules in the PDB
+    /// if it has not been done already.
+    pub fn ensure_line_information(&mut self) -> anyhow::Result<()> {
+        if self.lines_initialized {
+            return Ok(());
+        }
+        self.lines_initialized = true;
+
+        log::debug!("loading PDB line information");
+        let load_line_info_timer = std::time::Instant::now();
+
+        let address_map = self
+            .pdb
+            .address_map()
+            .context("error while reading PDB address map")?;
+        let string_table = self
+            .pdb
+            .string_table()
+            .context("error while reading PDB string table")?;
+
+        let debug_information = self
+            .pdb
+            .debug_information()
+            .context("error while getting PDB debug information")?;
+        let mut modules_iter = debug_information
+            .modules()
+            .context("error while getting PDB modules")?;
+
+        let mut file_indices: HashMap<String, usize> = HashMap::new();
+
+        while let Some(module) = modules_iter
+            .next()
+            .context("error while reading PDB module")?
+        {
+            let module_info = if let Some(module_info) = self
+                .pdb
+                .module_info(&module)
+                .context("error while getting PDB module info")?
+            {
+                module_info
+            } else {
+                continue;
+            };
+
+            let program = match module_info.line_program() {
+                Ok(program) => program,
+                Err(err) => {
+                    log::debug!("soft error while reading PDB line program: {}", err);
+                    continue;
+                }
+            };
+
+            let mut lines = program.lines();
+            while let Some(line) = lines.next().context("error while reading PDB line")? {
+                let rva = if let Some(rva) = line.offset.to_rva(&address_map) {
+                    rva
+                } else {
+                    continue;
+                };
+
+                let file_info = program
+                    .get_file_info(line.file_index)
+                    .context("error while reading PDB file info")?;
+                let file_name = file_info
+                    .name
+                    .to_string_lossy(&string_table)
+                    .context("error while reading PDB file name")?;
+
+                let files = &mut self.files;
+                let file = *file_indices
+                    .entry(file_name.into_owned())
+                    .or_insert_with_key(|name| {
+                        files.push(PathBuf::from(name));
+                        files.len() - 1
+                    });
+
+                self.lines.push(Line {
+                    addr: rva.0 as u64 + self.image_base,
+                    file,
+                    line: line.line_start,
+                });
+            }
+        }
+
+        self.lines.sort_by_key(|line| line.addr);
+
+        log::trace!(
+            "loaded {} lines and {} files from PDB debug information in {}",
+            self.lines.len(),
+            self.files.len(),
+            util::DurationDisplay(load_line_info_timer.elapsed())
+        );
+
+        Ok(())
+    }
+
+    pub fn addr2line(&self, addr: u64) -> Option<impl '_ + Iterator<Item = (&Path, u32)>> {
+        let idx = self
+            .lines
+            .binary_search_by(|probe| probe.addr.cmp(&addr))
+            .ok()?;
+        let mut range = idx..(idx + 1);
+
+        // Find the first line with the address
+        while range.start > 0 && self.lines[range.start - 1].addr == addr {
+            range.start -= 1;
+        }
+
+        // Find the final line with the address.
+        while range.end < self.lines.len() && self.lines[range.end].addr == addr {
+            range.end += 1;
+        }
+
+        Some(
+            self.lines[range]
+                .iter()
+                .map(move |line| (self.files[line.file].as_path(), line.line)),
+        )
+    }
+}
+
+struct Line {
+    addr: u64,
+    file: usize,
+    line: u32,
 }
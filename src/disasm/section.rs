@@ -0,0 +1,102 @@
+//! Splits a whole section into the symbols in it and the code in between them that isn't
+//! in any symbol, so that all of it can be disassembled.
+
+use super::binary::{Binary, Section};
+use super::symbol::{Symbol, SymbolSource};
+
+/// A part of a section: a symbol, or a range of the section that no symbol covers.
+pub enum SectionPart<'b> {
+    Symbol(&'b Symbol),
+
+    /// A symbol for a range without one, which is named after the offset of the range in
+    /// the section, e.g. `.text+0x1a0`.
+    Unnamed(Symbol),
+}
+
+impl SectionPart<'_> {
+    pub fn symbol(&self) -> &Symbol {
+        match self {
+            SectionPart::Symbol(symbol) => symbol,
+            SectionPart::Unnamed(symbol) => symbol,
+        }
+    }
+}
+
+/// Splits a section into parts in the order of their addresses. Each symbol is
+/// disassembled from its own start, so that instructions stay aligned with the functions
+/// after data or padding. Symbols that start inside of another symbol are left out, and
+/// ranges without a symbol that are only filled with padding (zeroes or `int3`) are
+/// skipped.
+pub fn split_section<'b>(binary: &'b Binary, section: &Section) -> Vec<SectionPart<'b>> {
+    let offset = match section.offset() {
+        Some(offset) => offset,
+        None => return Vec::new(),
+    };
+    // The contents of the section may be cut short by the end of the file.
+    let range = section.address_range();
+    let end = range
+        .end
+        .min(range.start + binary.data().len().saturating_sub(offset) as u64);
+
+    let mut symbols = binary
+        .all_symbols()
+        .into_iter()
+        .filter(|symbol| symbol.size() > 0 && range.contains(&symbol.address()))
+        .collect::<Vec<_>>();
+    symbols.sort_by_key(|symbol| symbol.address());
+
+    let mut parts = Vec::new();
+    let mut cursor = range.start;
+    for symbol in symbols {
+        if symbol.address() < cursor {
+            continue;
+        }
+        if symbol.address() > cursor {
+            parts.extend(unnamed_part(
+                binary,
+                section,
+                offset,
+                cursor..symbol.address(),
+            ));
+        }
+        parts.push(SectionPart::Symbol(symbol));
+        cursor = symbol.end_address();
+    }
+    if cursor < end {
+        parts.extend(unnamed_part(binary, section, offset, cursor..end));
+    }
+    log::debug!(
+        "split `{}` into {} parts, {} of them without a symbol",
+        section.name(),
+        parts.len(),
+        parts
+            .iter()
+            .filter(|part| matches!(part, SectionPart::Unnamed(_)))
+            .count()
+    );
+    parts
+}
+
+/// Creates a symbol for a range of a section that isn't in any symbol, unless the range is
+/// only padding.
+fn unnamed_part<'b>(
+    binary: &Binary,
+    section: &Section,
+    section_offset: usize,
+    range: std::ops::Range<u64>,
+) -> Option<SectionPart<'b>> {
+    let start = section_offset + (range.start - section.address()) as usize;
+    let len = (range.end - range.start) as usize;
+    let bytes = binary.data().get(start..start + len)?;
+    if bytes.iter().all(|&b| b == 0) || bytes.iter().all(|&b| b == 0xcc) {
+        log::trace!("skipping {} bytes of padding at 0x{:x}", len, range.start);
+        return None;
+    }
+    Some(SectionPart::Unnamed(Symbol::new_unmangled(
+        format!("{}+0x{:x}", section.name(), range.start - section.address()),
+        range.start,
+        start,
+        len,
+        SymbolSource::Raw,
+    )))
+}
@@ -0,0 +1,156 @@
+//! Searches the code of binaries for byte patterns with wildcards, such as the signatures
+//! of known code or the bytes of an instruction that should be patched.
+
+use super::binary::Binary;
+use std::fmt;
+use std::str::FromStr;
+
+/// A sequence of bytes where some of the bytes can be anything, e.g. `48 8b ?? ??`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BytePattern {
+    /// The bytes of the pattern, which are `None` for wildcards.
+    bytes: Vec<Option<u8>>,
+}
+
+impl BytePattern {
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Whether the pattern matches the start of `data`.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.len() >= self.bytes.len()
+            && self
+                .bytes
+                .iter()
+                .zip(data)
+                .all(|(pattern, byte)| pattern.is_none_or(|pattern| pattern == *byte))
+    }
+
+    /// Finds the addresses of every match in the sections with code, or in the functions of
+    /// binaries without sections. Matches can overlap.
+    pub fn find_in_code(&self, binary: &Binary) -> Vec<u64> {
+        let mut ranges = binary
+            .code_sections()
+            .iter()
+            .map(|(range, offset)| (range.start, *offset, (range.end - range.start) as usize))
+            .collect::<Vec<_>>();
+        if ranges.is_empty() {
+            log::debug!("searching the functions of a binary without code sections");
+            ranges = binary
+                .symbols()
+                .iter()
+                .filter(|symbol| symbol.is_function())
+                .map(|symbol| (symbol.address(), symbol.offset(), symbol.size()))
+                .collect();
+        }
+
+        let data = binary.data();
+        let mut addresses = Vec::new();
+        for (address, offset, len) in ranges {
+            let end = offset.saturating_add(len).min(data.len());
+            let code = match data.get(offset..end) {
+                Some(code) => code,
+                None => continue,
+            };
+            for start in 0..code.len().saturating_sub(self.len().saturating_sub(1)) {
+                if self.matches(&code[start..]) {
+                    addresses.push(address + start as u64);
+                }
+            }
+        }
+        addresses.sort_unstable();
+        addresses.dedup();
+        addresses
+    }
+}
+
+impl FromStr for BytePattern {
+    type Err = &'static str;
+
+    /// Parses bytes in hexadecimal and `??` (or `?`) wildcards that are separated by spaces,
+    /// e.g. `48 8B ?? ??`. The bytes can also be written without spaces, e.g. `488B????`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = Vec::new();
+        for token in s.split_whitespace() {
+            if token == "?" || token == "??" {
+                bytes.push(None);
+                continue;
+            }
+            let valid = token.chars().all(|c| c.is_ascii_hexdigit() || c == '?');
+            if token.len() % 2 != 0 || !valid {
+                return Err("bytes must be two hexadecimal digits or `??`");
+            }
+            for idx in (0..token.len()).step_by(2) {
+                match &token[idx..(idx + 2)] {
+                    "??" => bytes.push(None),
+                    byte => bytes
+                        .push(Some(u8::from_str_radix(byte, 16).map_err(|_| {
+                            "bytes must be two hexadecimal digits or `??`"
+                        })?)),
+                }
+            }
+        }
+        if bytes.is_empty() {
+            return Err("the byte pattern is empty");
+        }
+        if bytes.iter().all(Option::is_none) {
+            return Err("the byte pattern only has wildcards");
+        }
+        Ok(BytePattern { bytes })
+    }
+}
+
+impl fmt::Display for BytePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, byte) in self.bytes.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(" ")?;
+            }
+            match byte {
+                Some(byte) => write!(f, "{:02x}", byte)?,
+                None => f.write_str("??")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bytes(pattern: &str) -> Result<Vec<Option<u8>>, &'static str> {
+        pattern.parse::<BytePattern>().map(|pattern| pattern.bytes)
+    }
+
+    #[test]
+    fn parse_patterns() {
+        let expected = vec![Some(0x48), Some(0x8b), None, None];
+        assert_eq!(bytes("48 8B ?? ??"), Ok(expected.clone()));
+        assert_eq!(bytes("48 8b ? ?"), Ok(expected.clone()));
+        assert_eq!(bytes("488B????"), Ok(expected.clone()));
+        assert_eq!(bytes("  48\t8b ??\n?? "), Ok(expected));
+        assert_eq!(bytes("?? c3"), Ok(vec![None, Some(0xc3)]));
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        assert!(bytes("").is_err());
+        assert!(bytes("  ").is_err());
+        assert!(bytes("?? ?").is_err());
+        assert!(bytes("4").is_err());
+        assert!(bytes("488").is_err());
+        assert!(bytes("4?").is_err());
+        assert!(bytes("???").is_err());
+        assert!(bytes("zz").is_err());
+        assert!(bytes("0x48").is_err());
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let pattern = "48 8B ?? ?? c3".parse::<BytePattern>().unwrap();
+        assert_eq!(pattern.to_string(), "48 8b ?? ?? c3");
+        assert_eq!(pattern.to_string().parse(), Ok(pattern));
+    }
+}
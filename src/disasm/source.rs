@@ -1,3 +1,4 @@
+use super::debuginfod;
 use anyhow::Context as _;
 use memmap::{Mmap, MmapOptions};
 use std::borrow::Cow;
@@ -5,19 +6,89 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::path::{Path, PathBuf};
 
+/// A file and line number that an instruction was generated from.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    path: Box<Path>,
+    line: u32,
+}
+
+impl SourceLocation {
+    pub fn new(path: &Path, line: u32) -> SourceLocation {
+        SourceLocation {
+            path: path.into(),
+            line,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+}
+
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.path.display(), self.line)
+    }
+}
+
+/// A call to a function that was inlined into the function being disassembled.
+#[derive(Clone, PartialEq, Eq)]
+pub struct InlinedCall {
+    name: Box<str>,
+    call_location: Option<SourceLocation>,
+}
+
+impl InlinedCall {
+    pub fn new(name: Box<str>, call_location: Option<SourceLocation>) -> InlinedCall {
+        InlinedCall {
+            name,
+            call_location,
+        }
+    }
+
+    /// The name of the function that was inlined.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The location of the call that was inlined, if it is known.
+    pub fn call_location(&self) -> Option<&SourceLocation> {
+        self.call_location.as_ref()
+    }
+}
+
+impl std::fmt::Display for InlinedCall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "inlined from {}", self.name)?;
+        if let Some(ref location) = self.call_location {
+            write!(f, " ({})", location)?;
+        }
+        Ok(())
+    }
+}
+
 pub struct SourceLoader {
     // FIXME implement this
     // /// A map of paths that to not exist to their corresponding
     // /// existing paths. (e.g. an absolute Windows path to a path on a Linux OS).
     // path_mapper: HashMap<PathBuf, PathBuf>,
     cache: HashMap<PathBuf, Option<LineCache>>,
+
+    /// The build ID of the binary, used to download missing source files from debuginfod.
+    build_id: Option<Box<str>>,
 }
 
 impl SourceLoader {
-    pub fn new() -> SourceLoader {
+    pub fn new(build_id: Option<&str>) -> SourceLoader {
         SourceLoader {
             // path_mapper: HashMap::new(),
             cache: HashMap::new(),
+            build_id: build_id.map(Into::into),
         }
     }
 
@@ -30,12 +101,21 @@ impl SourceLoader {
             let cache = match self.cache.entry(path.into()) {
                 Entry::Occupied(o) => o.into_mut(),
                 Entry::Vacant(v) => {
-                    if !path.exists() {
-                        v.insert(None)
+                    let found_path = if path.exists() {
+                        Some(Cow::Borrowed(path))
                     } else {
+                        self.build_id
+                            .as_ref()
+                            .and_then(|build_id| debuginfod::fetch_source(build_id, path))
+                            .map(Cow::Owned)
+                    };
+
+                    if let Some(found_path) = found_path {
                         v.insert(Some(
-                            LineCache::new(path).context("error loading line cache")?,
+                            LineCache::new(&found_path).context("error loading line cache")?,
                         ))
+                    } else {
+                        v.insert(None)
                     }
                 }
             };
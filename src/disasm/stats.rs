@@ -0,0 +1,147 @@
+//! Counts the instructions of functions by what they do, e.g. to check quickly whether a
+//! loop was vectorized.
+
+use super::binary::Arch;
+use super::tls::destination;
+use super::{DisasmLine, Disassembly, Flow};
+use std::fmt;
+
+/// The number of instructions of each class in a function. The classes overlap, e.g. a
+/// SIMD load is counted as both a load and a SIMD instruction.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct InstructionStats {
+    pub instructions: usize,
+
+    /// The size of the instructions in bytes.
+    pub bytes: usize,
+
+    /// Instructions that read memory, including x86 instructions with a memory source
+    /// operand like `add rax, [rbx]`.
+    pub loads: usize,
+
+    /// Instructions that write memory.
+    pub stores: usize,
+
+    /// Jumps and returns.
+    pub branches: usize,
+
+    pub calls: usize,
+
+    /// Instructions that operate on vector registers.
+    pub simd: usize,
+}
+
+impl InstructionStats {
+    /// Counts the instructions of a disassembly. Data in between the instructions isn't
+    /// counted.
+    pub fn collect(arch: Arch, disassembly: &Disassembly) -> InstructionStats {
+        let mut stats = InstructionStats::default();
+        for line in disassembly.lines() {
+            if line.mnemonic().starts_with('.') {
+                continue;
+            }
+            stats.instructions += 1;
+            stats.bytes += line.bytes().len();
+            match line.flow() {
+                Flow::Call => stats.calls += 1,
+                Flow::Jump | Flow::ConditionalJump | Flow::Return => stats.branches += 1,
+                Flow::Next | Flow::Halt => {}
+            }
+            match memory_access(arch, line) {
+                Some(Access::Load) => stats.loads += 1,
+                Some(Access::Store) => stats.stores += 1,
+                None => {}
+            }
+            if is_simd(arch, line) {
+                stats.simd += 1;
+            }
+        }
+        stats
+    }
+}
+
+impl fmt::Display for InstructionStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} instructions ({} bytes)",
+            self.instructions, self.bytes
+        )?;
+        let classes = [
+            (self.loads, "loads"),
+            (self.stores, "stores"),
+            (self.branches, "branches"),
+            (self.calls, "calls"),
+            (self.simd, "SIMD"),
+        ];
+        for (idx, &(count, name)) in classes.iter().enumerate() {
+            f.write_str(if idx == 0 { ": " } else { ", " })?;
+            write!(f, "{} {}", count, name)?;
+            if let Some(percent) = (count * 100).checked_div(self.instructions) {
+                write!(f, " ({}%)", percent)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Access {
+    Load,
+    Store,
+}
+
+/// Finds whether an instruction reads or writes memory from its memory operands. Stores
+/// have the memory operand as the destination on x86 and are the `st*` instructions on
+/// AArch64. `lea` and `nop` have memory operands without accessing memory.
+fn memory_access(arch: Arch, line: &DisasmLine) -> Option<Access> {
+    let mnemonic = line.mnemonic();
+    let operands = line.operands();
+    match arch {
+        Arch::X86 | Arch::X86_64 => {
+            if !operands.contains('[') && !operands.contains('(') {
+                return None;
+            }
+            if mnemonic.starts_with("lea") || mnemonic.starts_with("nop") {
+                return None;
+            }
+            let dest = destination(operands);
+            // Instructions with a single memory operand, like `inc qword ptr [rax]`, write
+            // to it.
+            if dest.contains('[') || dest.contains('(') {
+                Some(Access::Store)
+            } else {
+                Some(Access::Load)
+            }
+        }
+        Arch::AArch64 | Arch::Arm | Arch::Thumb => {
+            if mnemonic.starts_with("ld") || mnemonic == "pop" {
+                Some(Access::Load)
+            } else if mnemonic.starts_with("st") || mnemonic == "push" {
+                Some(Access::Store)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Whether an instruction operates on vector registers, i.e. `xmm`, `ymm`, and `zmm` on
+/// x86 and the `v`/`q` registers and their lanes on ARM.
+fn is_simd(arch: Arch, line: &DisasmLine) -> bool {
+    let operands = line.operands();
+    match arch {
+        Arch::X86 | Arch::X86_64 => {
+            operands.contains("xmm") || operands.contains("ymm") || operands.contains("zmm")
+        }
+        Arch::AArch64 | Arch::Arm | Arch::Thumb => {
+            operands.split([',', ' ', '{', '[']).any(|operand| {
+                let mut chars = operand.chars();
+                matches!(chars.next(), Some('v') | Some('q'))
+                    && chars.next().is_some_and(|c| c.is_ascii_digit())
+            })
+        }
+        _ => false,
+    }
+}
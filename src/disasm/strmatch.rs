@@ -113,3 +113,51 @@ where
 
     Some(dist)
 }
+
+/// Scores how similar strings are to a query by the trigrams (runs of three characters)
+/// that they share, ignoring case. Unlike `distance` this still finds names with typos
+/// or with parts of the query missing, so it is used to suggest symbols.
+pub struct TrigramMatcher {
+    trigrams: Vec<[char; 3]>,
+}
+
+impl TrigramMatcher {
+    pub fn new(query: &str) -> TrigramMatcher {
+        TrigramMatcher {
+            trigrams: trigrams(query),
+        }
+    }
+
+    /// Returns the fraction of the trigrams of the query that are also in the candidate,
+    /// from 0 (nothing in common) to 1 (every trigram). The candidate may contain more
+    /// than the query, e.g. the query may be a function name without its module path.
+    pub fn score(&self, candidate: &str) -> f32 {
+        if self.trigrams.is_empty() {
+            return 0.0;
+        }
+
+        let candidate = trigrams(candidate);
+        let shared = self
+            .trigrams
+            .iter()
+            .filter(|trigram| candidate.binary_search(trigram).is_ok())
+            .count();
+        shared as f32 / self.trigrams.len() as f32
+    }
+}
+
+/// Returns the sorted and deduplicated trigrams of a lowercased string. The string is padded
+/// with spaces so that its first and last characters are weighted the same as the rest.
+fn trigrams(s: &str) -> Vec<[char; 3]> {
+    let chars = std::iter::once(' ')
+        .chain(s.chars().flat_map(char::to_lowercase))
+        .chain(std::iter::once(' '))
+        .collect::<Vec<char>>();
+    let mut trigrams = chars
+        .windows(3)
+        .map(|w| [w[0], w[1], w[2]])
+        .collect::<Vec<_>>();
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    trigrams
+}
@@ -1,11 +1,17 @@
+use super::demangle;
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::fmt;
 
-#[derive(Eq, PartialEq)]
+#[derive(Eq, PartialEq, Serialize, Deserialize)]
 pub struct Symbol {
     /// The demangled name of the symbol.
     name: String,
 
+    /// The name of the symbol before it was demangled, if it was changed by demangling.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mangled_name: Option<Box<str>>,
+
     /// The virtual address of the symbol.
     addr: u64,
 
@@ -16,6 +22,11 @@ pub struct Symbol {
     blen: usize,
     /// Where this symbol is from.
     source: SymbolSource,
+    /// The language that the symbol's name was mangled for.
+    lang: SymbolLang,
+    /// Whether the symbol is code or data.
+    #[serde(default)]
+    kind: SymbolType,
 }
 
 impl Symbol {
@@ -28,10 +39,13 @@ impl Symbol {
     ) -> Self {
         Symbol {
             name,
+            mangled_name: None,
             addr,
             bpos,
             blen,
             source,
+            lang: SymbolLang::Unknown,
+            kind: SymbolType::Function,
         }
     }
 
@@ -39,22 +53,23 @@ impl Symbol {
     where
         N: Into<Cow<'a, str>>,
     {
-        use cpp_demangle::Symbol as CppSymbol;
-        use rustc_demangle::try_demangle;
-
-        // FIXME demangle C names (e.g. stdcall and fastcall naming conventions).
         let name = name.into();
-        let demangled_name = try_demangle(&*name)
-            .map(|n| Cow::from(format!("{:#}", n)))
-            .or_else(|_| CppSymbol::new(name.as_bytes()).map(|s| Cow::from(s.to_string())))
-            .unwrap_or(name);
+        let (demangled_name, lang) = demangle::demangle(name.clone());
+        let mangled_name = if demangled_name != name {
+            Some(name.into_owned().into_boxed_str())
+        } else {
+            None
+        };
 
         Symbol {
             name: demangled_name.into_owned(),
+            mangled_name,
             addr,
             bpos,
             blen,
             source,
+            lang,
+            kind: SymbolType::Function,
         }
     }
 
@@ -79,7 +94,6 @@ impl Symbol {
         self.bpos + self.blen
     }
 
-    #[allow(dead_code)]
     pub fn size(&self) -> usize {
         self.blen
     }
@@ -88,35 +102,223 @@ impl Symbol {
         &*self.name
     }
 
+    /// The name of the symbol as it is in the binary, e.g. for comparing it with the
+    /// output of `nm` or a linker map.
+    pub fn mangled_name(&self) -> &str {
+        self.mangled_name.as_deref().unwrap_or(&self.name)
+    }
+
     pub fn source(&self) -> SymbolSource {
         self.source
     }
 
+    pub fn lang(&self) -> SymbolLang {
+        self.lang
+    }
+
+    pub fn symbol_type(&self) -> SymbolType {
+        self.kind
+    }
+
+    pub fn is_function(&self) -> bool {
+        self.kind == SymbolType::Function
+    }
+
+    /// Returns true for the local labels and mapping symbols that assemblers emit inside of
+    /// functions, e.g. `.LBB0_1` in ELF files, `ltmp0` and `LBB0_1` in Mach-O files, or
+    /// ARM's `$a`, `$t`, `$d`, and `$x`.
+    pub fn is_local_label(&self) -> bool {
+        let name = self.name();
+        if name.starts_with(".L") {
+            return true;
+        }
+        if let Some(kind) = name.strip_prefix('$') {
+            let mut chars = kind.chars();
+            return matches!(chars.next(), Some('a') | Some('t') | Some('d') | Some('x'))
+                && matches!(chars.next(), None | Some('.'));
+        }
+        ["ltmp", "Ltmp", "LBB", "LCPI", "lCPI", "L_."]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+    }
+
+    /// Returns true for functions that the compiler generated without a source function,
+    /// such as the functions of LLVM's machine outliner and retpoline thunks.
+    pub fn is_compiler_generated(&self) -> bool {
+        let name = self.name();
+        [
+            "OUTLINED_FUNCTION_",
+            "__x86_indirect_thunk",
+            "__x86_return_thunk",
+            "__llvm_retpoline_",
+            "__llvm_lvi_thunk_",
+        ]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+    }
+
+    /// The crate that a Rust symbol belongs to, which is the first segment of its path.
+    pub fn crate_name(&self) -> Option<&str> {
+        if self.lang == SymbolLang::Rust {
+            rust_crate_name(&self.name)
+        } else {
+            None
+        }
+    }
+
+    /// The mangling scheme of a Rust symbol's name.
+    pub fn rust_mangling(&self) -> Option<RustMangling> {
+        if self.lang != SymbolLang::Rust {
+            return None;
+        }
+        // Mach-O adds another underscore in front of the names.
+        let mangled_name = self.mangled_name().trim_start_matches('_');
+        if mangled_name.starts_with("ZN") {
+            Some(RustMangling::Legacy)
+        } else if mangled_name.starts_with('R') {
+            Some(RustMangling::V0)
+        } else {
+            None
+        }
+    }
+
+    /// The segments of the path of a Rust symbol, e.g. `["alloc", "vec", "Vec<T>", "push"]`
+    /// or `["<u32 as core::fmt::Debug>", "fmt"]`. Separators inside of generic arguments
+    /// and qualified paths don't split segments.
+    pub fn rust_path(&self) -> Vec<&str> {
+        if self.lang != SymbolLang::Rust {
+            return Vec::new();
+        }
+
+        let mut segments = Vec::new();
+        let mut depth = 0usize;
+        let mut start = 0;
+        let bytes = self.name.as_bytes();
+        let mut idx = 0;
+        while idx < bytes.len() {
+            match bytes[idx] {
+                b'<' | b'(' | b'[' => depth += 1,
+                b'>' | b')' | b']' => depth = depth.saturating_sub(1),
+                b':' if depth == 0 && bytes.get(idx + 1) == Some(&b':') => {
+                    segments.push(&self.name[start..idx]);
+                    idx += 2;
+                    start = idx;
+                    continue;
+                }
+                _ => {}
+            }
+            idx += 1;
+        }
+        segments.push(&self.name[start..]);
+        segments
+    }
+
+    /// Demangles the name of a Rust symbol again with the hashes that are left out by
+    /// default.
+    pub(crate) fn use_name_with_hashes(&mut self) {
+        if self.lang != SymbolLang::Rust {
+            return;
+        }
+        if let Some(name) = self
+            .mangled_name
+            .as_deref()
+            .and_then(demangle::demangle_rust_with_hashes)
+        {
+            self.name = name;
+        }
+    }
+
+    /// Strips the decorations of the stdcall, fastcall, and vectorcall calling conventions
+    /// from the name, if it isn't the name of another language.
+    pub(crate) fn strip_c_decorations(&mut self) {
+        if self.lang != SymbolLang::Unknown {
+            return;
+        }
+        if let Some(undecorated) = demangle::strip_c_decorations(&self.name) {
+            let undecorated = undecorated.to_string();
+            let decorated = std::mem::replace(&mut self.name, undecorated);
+            self.mangled_name = Some(decorated.into_boxed_str());
+            self.lang = SymbolLang::C;
+        }
+    }
+
+    /// Replaces the demangled name of the symbol with its mangled name.
+    pub(crate) fn use_mangled_name(&mut self) {
+        if let Some(mangled_name) = self.mangled_name.take() {
+            self.name = mangled_name.into();
+        }
+    }
+
     pub(crate) fn set_address(&mut self, new_address: u64) {
         self.addr = new_address;
     }
 
+    pub(crate) fn set_offset(&mut self, new_offset: usize) {
+        self.bpos = new_offset;
+    }
+
     pub(crate) fn set_size(&mut self, new_size: usize) {
         self.blen = new_size;
     }
+
+    pub(crate) fn set_symbol_type(&mut self, new_type: SymbolType) {
+        self.kind = new_type;
+    }
+}
+
+fn rust_crate_name(name: &str) -> Option<&str> {
+    let path = name.trim_start_matches(['<', '&', '*']);
+    let path = ["mut ", "const ", "dyn "]
+        .iter()
+        .fold(path, |path, prefix| {
+            path.strip_prefix(prefix).unwrap_or(path)
+        });
+    let len = path
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(path.len());
+    // v0 names that were demangled with their hashes have a crate disambiguator, e.g.
+    // `my_crate[1a2b3c4d5e6f7a8b]::foo`.
+    let rest = match path[len..].strip_prefix('[') {
+        Some(rest) => rest.split_once(']').map_or(rest, |(_, rest)| rest),
+        None => &path[len..],
+    };
+    if len > 0 && rest.starts_with("::") {
+        return Some(&path[..len]);
+    }
+
+    // Implementations for types without a path, e.g. `<u32 as core::fmt::Debug>::fmt`, are
+    // attributed to the crate of the trait.
+    let (_, trait_path) = name.split_once(" as ")?;
+    rust_crate_name(trait_path)
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum SymbolSource {
     Elf,
     Mach,
     Pe,
     Archive,
+    Wasm,
     Dwarf,
     Pdb,
+    /// The single symbol of a binary without a container format.
+    Raw,
+    /// A function that was found by heuristics in a binary without symbols.
+    Heuristic,
 }
 
 impl SymbolSource {
     pub fn priority(self) -> u8 {
         match self {
             SymbolSource::Dwarf | SymbolSource::Pdb => 1,
-            SymbolSource::Elf | SymbolSource::Mach | SymbolSource::Pe | SymbolSource::Archive => 2,
+            SymbolSource::Elf
+            | SymbolSource::Mach
+            | SymbolSource::Pe
+            | SymbolSource::Archive
+            | SymbolSource::Wasm
+            | SymbolSource::Raw => 2,
+            SymbolSource::Heuristic => 3,
         }
     }
 }
@@ -133,10 +335,16 @@ impl std::str::FromStr for SymbolSource {
             Ok(SymbolSource::Pe)
         } else if s.eq_ignore_ascii_case("archive") {
             Ok(SymbolSource::Archive)
+        } else if s.eq_ignore_ascii_case("wasm") {
+            Ok(SymbolSource::Wasm)
         } else if s.eq_ignore_ascii_case("dwarf") {
             Ok(SymbolSource::Dwarf)
         } else if s.eq_ignore_ascii_case("pdb") {
             Ok(SymbolSource::Pdb)
+        } else if s.eq_ignore_ascii_case("raw") {
+            Ok(SymbolSource::Raw)
+        } else if s.eq_ignore_ascii_case("heuristic") {
+            Ok(SymbolSource::Heuristic)
         } else {
             Err("invalid symbol source")
         }
@@ -162,8 +370,74 @@ impl fmt::Display for SymbolSource {
             SymbolSource::Mach => "mach",
             SymbolSource::Pe => "pe",
             SymbolSource::Archive => "archive",
+            SymbolSource::Wasm => "wasm",
             SymbolSource::Dwarf => "dwarf",
             SymbolSource::Pdb => "pdb",
+            SymbolSource::Raw => "raw",
+            SymbolSource::Heuristic => "heuristic",
+        };
+        write!(f, "{}", t)
+    }
+}
+
+/// The mangling schemes of Rust symbol names.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RustMangling {
+    /// The scheme based on the Itanium C++ ABI with a hash at the end of the name, e.g.
+    /// `_ZN4core3ptr13drop_in_place17h0123456789abcdefE`.
+    Legacy,
+    /// The v0 scheme (RFC 2603), e.g. `_RNvCs1234_7mycrate3foo`.
+    V0,
+}
+
+impl fmt::Display for RustMangling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let t = match self {
+            RustMangling::Legacy => "legacy",
+            RustMangling::V0 => "v0",
+        };
+        write!(f, "{}", t)
+    }
+}
+
+/// What a symbol refers to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum SymbolType {
+    /// Executable code.
+    #[default]
+    Function,
+    /// Data, such as a `static` or a constant.
+    Static,
+}
+
+impl fmt::Display for SymbolType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let t = match self {
+            SymbolType::Function => "function",
+            SymbolType::Static => "static",
+        };
+        write!(f, "{}", t)
+    }
+}
+
+/// The language that a symbol's name was mangled for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SymbolLang {
+    Rust,
+    C,
+    Cpp,
+    Swift,
+    Unknown,
+}
+
+impl fmt::Display for SymbolLang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let t = match self {
+            SymbolLang::Rust => "rust",
+            SymbolLang::C => "c",
+            SymbolLang::Cpp => "c++",
+            SymbolLang::Swift => "swift",
+            SymbolLang::Unknown => "unknown",
         };
         write!(f, "{}", t)
     }
@@ -0,0 +1,530 @@
+//! Queries for the symbols of a binary.
+
+use super::dwarf::DwarfInfo;
+use super::symbol::{Symbol, SymbolLang, SymbolSource, SymbolType};
+use regex::Regex;
+use std::ops::Range;
+
+/// The symbols of a binary, sorted by address in ascending order.
+#[derive(Copy, Clone)]
+pub struct SymbolTable<'s> {
+    symbols: &'s [Symbol],
+    index: &'s SymbolIndex,
+
+    /// The DWARF debug information that the crates of symbols without a Rust path are
+    /// found in, if its compilation units were loaded.
+    dwarf: Option<&'s DwarfInfo>,
+}
+
+impl<'s> SymbolTable<'s> {
+    /// Creates a table from symbols that are sorted by address and their index.
+    pub(crate) fn new(
+        symbols: &'s [Symbol],
+        index: &'s SymbolIndex,
+        dwarf: Option<&'s DwarfInfo>,
+    ) -> SymbolTable<'s> {
+        let dwarf = dwarf.filter(|dwarf| dwarf.compilation_units_loaded());
+        SymbolTable {
+            symbols,
+            index,
+            dwarf,
+        }
+    }
+
+    /// Returns the crate that a symbol belongs to, which is the first segment of the path
+    /// of Rust symbols or otherwise the crate of the DWARF compilation unit that contains
+    /// it.
+    pub fn crate_of(&self, symbol: &'s Symbol) -> Option<&'s str> {
+        symbol
+            .crate_name()
+            .or_else(|| self.dwarf?.crate_name_at(symbol.address()))
+    }
+
+    /// Returns the symbol that contains an address and the offset of the address in it.
+    /// When symbols overlap, the one that starts closest to the address is returned.
+    /// Symbols without a size can only be found by their exact address.
+    pub fn at_address(&self, addr: u64) -> Option<(&'s Symbol, u64)> {
+        let by_address = &self.index.by_address;
+
+        // Find the last symbol that starts at or before the address.
+        let mut index_idx = match by_address.binary_search_by(|&(a, _)| a.cmp(&addr)) {
+            Ok(idx) => idx,
+            Err(0) => return None,
+            Err(idx) => idx - 1,
+        };
+
+        let sym = &self.symbols[by_address[index_idx].1];
+        let offset = addr - sym.address();
+        if offset == 0 || offset < sym.size() as u64 {
+            return Some((sym, offset));
+        }
+
+        // An earlier symbol can still contain the address if it's large enough, e.g. a
+        // function with a label in it or a section symbol. This stops as soon as none of
+        // the symbols before an entry end after the address.
+        while index_idx > 0 && self.index.max_ends[index_idx - 1] > addr {
+            index_idx -= 1;
+            let sym = &self.symbols[by_address[index_idx].1];
+            if sym.address_range().contains(&addr) {
+                return Some((sym, addr - sym.address()));
+            }
+        }
+        None
+    }
+
+    /// Returns the other names of a symbol, which are the symbols with the same address range
+    /// that were merged by identical code folding or are aliases of it. Names are only
+    /// returned once even if they were found in more than one source.
+    pub fn aliases(&self, symbol: &Symbol) -> Vec<&'s Symbol> {
+        let start = self
+            .symbols
+            .partition_point(|sym| sym.address() < symbol.address());
+        let mut aliases: Vec<&Symbol> = Vec::new();
+        for sym in self.symbols[start..]
+            .iter()
+            .take_while(|sym| sym.address() == symbol.address())
+        {
+            if sym.address_range() == symbol.address_range()
+                && sym.name() != symbol.name()
+                && !aliases.iter().any(|alias| alias.name() == sym.name())
+            {
+                aliases.push(sym);
+            }
+        }
+        aliases
+    }
+
+    /// Returns the symbol that contains a position in the file of the binary and the offset
+    /// of the position in it.
+    pub fn at_offset(&self, offset: usize) -> Option<(&'s Symbol, u64)> {
+        let by_offset = &self.index.by_offset;
+        let index_idx = match by_offset.binary_search_by(|&(o, _)| o.cmp(&offset)) {
+            Ok(idx) => idx,
+            Err(0) => return None,
+            Err(idx) => idx - 1,
+        };
+        let sym = &self.symbols[by_offset[index_idx].1];
+        if (sym.offset()..sym.end()).contains(&offset) {
+            Some((sym, (offset - sym.offset()) as u64))
+        } else {
+            None
+        }
+    }
+
+    /// Returns a query for all of the symbols, which can be narrowed down with filters and
+    /// sorted before it is iterated. Symbols that were found in more than one source are
+    /// only returned once.
+    pub fn iter(&self) -> SymbolQuery<'s> {
+        SymbolQuery {
+            table: *self,
+            sized: false,
+            langs: Vec::new(),
+            sources: Vec::new(),
+            symbol_type: None,
+            crate_name: None,
+            glob: None,
+            regex: None,
+            address_range: None,
+            order: SymbolOrder::Address,
+        }
+    }
+
+    /// Returns all of the symbols with names matching a regular expression in ascending
+    /// order of their addresses. Symbols that were found in more than one source are only
+    /// returned once.
+    pub fn find_regex(&self, regex: &Regex) -> Vec<&'s Symbol> {
+        self.iter().name_regex(regex).to_vec()
+    }
+
+    /// Returns the number of symbols in the table, including symbols that were found in
+    /// more than one source.
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+/// The orders that symbols can be listed in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SymbolOrder {
+    /// Ascending order of their addresses.
+    Address,
+    /// The largest symbols first.
+    Size,
+    /// Alphabetical order of their names.
+    Name,
+}
+
+impl std::str::FromStr for SymbolOrder {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("address") || s.eq_ignore_ascii_case("addr") {
+            Ok(SymbolOrder::Address)
+        } else if s.eq_ignore_ascii_case("size") {
+            Ok(SymbolOrder::Size)
+        } else if s.eq_ignore_ascii_case("name") {
+            Ok(SymbolOrder::Name)
+        } else {
+            Err("invalid symbol order")
+        }
+    }
+}
+
+/// The filters and the order for listing the symbols of a `SymbolTable`.
+#[derive(Clone)]
+pub struct SymbolQuery<'s> {
+    table: SymbolTable<'s>,
+    sized: bool,
+    langs: Vec<SymbolLang>,
+    sources: Vec<SymbolSource>,
+    symbol_type: Option<SymbolType>,
+    crate_name: Option<String>,
+    glob: Option<String>,
+    regex: Option<Regex>,
+    address_range: Option<Range<u64>>,
+    order: SymbolOrder,
+}
+
+impl<'s> SymbolQuery<'s> {
+    /// Only lists symbols with a non-zero size.
+    pub fn sized(mut self) -> Self {
+        self.sized = true;
+        self
+    }
+
+    /// Only lists symbols with names that were mangled for `lang`. This can be used more
+    /// than once to list the symbols of multiple languages.
+    pub fn lang(mut self, lang: SymbolLang) -> Self {
+        self.langs.push(lang);
+        self
+    }
+
+    /// Only lists symbols that were found in `source`. This can be used more than once to
+    /// list the symbols of multiple sources.
+    pub fn source(mut self, source: SymbolSource) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Only lists functions or data symbols.
+    pub fn symbol_type(mut self, symbol_type: SymbolType) -> Self {
+        self.symbol_type = Some(symbol_type);
+        self
+    }
+
+    /// Only lists functions.
+    pub fn functions(self) -> Self {
+        self.symbol_type(SymbolType::Function)
+    }
+
+    /// Only lists symbols from a crate, see `SymbolTable::crate_of`.
+    pub fn crate_name(mut self, crate_name: &str) -> Self {
+        self.crate_name = Some(crate_name.to_string());
+        self
+    }
+
+    /// Only lists symbols with names that match a glob pattern, where `*` matches any
+    /// number of characters and `?` matches a single character, e.g. `my_crate::*::new`.
+    pub fn name_glob(mut self, pattern: &str) -> Self {
+        self.glob = Some(pattern.to_string());
+        self
+    }
+
+    /// Only lists symbols with names that match a regular expression.
+    pub fn name_regex(mut self, regex: &Regex) -> Self {
+        self.regex = Some(regex.clone());
+        self
+    }
+
+    /// Only lists symbols that start in an address range.
+    pub fn address_range(mut self, range: Range<u64>) -> Self {
+        self.address_range = Some(range);
+        self
+    }
+
+    /// Sets the order of the symbols, which is `SymbolOrder::Address` by default.
+    pub fn sort_by(mut self, order: SymbolOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Collects the symbols that pass all of the filters.
+    pub fn to_vec(&self) -> Vec<&'s Symbol> {
+        // The symbols are sorted by address so the ones in the address range are found
+        // without looking at the others.
+        let symbols = self.table.symbols;
+        let symbols = match self.address_range {
+            Some(ref range) => {
+                let start = symbols.partition_point(|sym| sym.address() < range.start);
+                let end = symbols.partition_point(|sym| sym.address() < range.end);
+                &symbols[start..end.max(start)]
+            }
+            None => symbols,
+        };
+
+        let mut matches = unique_symbols(symbols.iter().filter(|sym| self.matches(sym)));
+        match self.order {
+            SymbolOrder::Address => {}
+            SymbolOrder::Size => matches.sort_by(|lhs, rhs| {
+                rhs.size()
+                    .cmp(&lhs.size())
+                    .then_with(|| lhs.address().cmp(&rhs.address()))
+            }),
+            SymbolOrder::Name => matches.sort_by(|lhs, rhs| {
+                lhs.name()
+                    .cmp(rhs.name())
+                    .then_with(|| lhs.address().cmp(&rhs.address()))
+            }),
+        }
+        matches
+    }
+
+    fn matches(&self, sym: &'s Symbol) -> bool {
+        (!self.sized || sym.size() > 0)
+            && (self.langs.is_empty() || self.langs.contains(&sym.lang()))
+            && (self.sources.is_empty() || self.sources.contains(&sym.source()))
+            && self.symbol_type.is_none_or(|ty| sym.symbol_type() == ty)
+            && self
+                .crate_name
+                .as_deref()
+                .is_none_or(|name| self.table.crate_of(sym) == Some(name))
+            && self
+                .glob
+                .as_deref()
+                .is_none_or(|pattern| glob_match(pattern, sym.name()))
+            && self
+                .regex
+                .as_ref()
+                .is_none_or(|regex| regex.is_match(sym.name()))
+    }
+}
+
+impl<'s> IntoIterator for SymbolQuery<'s> {
+    type Item = &'s Symbol;
+    type IntoIter = std::vec::IntoIter<&'s Symbol>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.to_vec().into_iter()
+    }
+}
+
+/// Sorted ranges of the symbols of a binary for looking up the symbol at an address or a
+/// file offset with a binary search.
+#[derive(Default)]
+pub(crate) struct SymbolIndex {
+    /// The unique start addresses of the symbols and the index of the symbol that is
+    /// preferred at each of them, in ascending order.
+    by_address: Vec<(u64, usize)>,
+
+    /// The largest end address of the symbols up to and including each entry of
+    /// `by_address`, which tells when an earlier symbol can't contain an address.
+    max_ends: Vec<u64>,
+
+    /// The file offsets of the sized symbols in `by_address` and their indices, in
+    /// ascending order.
+    by_offset: Vec<(usize, usize)>,
+}
+
+impl SymbolIndex {
+    /// Builds the index of symbols that are sorted by address. When more than one symbol
+    /// starts at the same address, sized symbols are preferred, followed by the symbol
+    /// from the highest priority source.
+    pub(crate) fn new(symbols: &[Symbol]) -> SymbolIndex {
+        let mut by_address: Vec<(u64, usize)> = Vec::new();
+        for (idx, sym) in symbols.iter().enumerate() {
+            if let Some(last) = by_address.last_mut() {
+                if last.0 == sym.address() {
+                    let current = &symbols[last.1];
+                    if (current.size() == 0 && sym.size() > 0)
+                        || ((current.size() == 0) == (sym.size() == 0)
+                            && sym.source() < current.source())
+                    {
+                        last.1 = idx;
+                    }
+                    continue;
+                }
+            }
+            by_address.push((sym.address(), idx));
+        }
+
+        let mut max_end = 0;
+        let max_ends = by_address
+            .iter()
+            .map(|&(_, idx)| {
+                max_end = max_end.max(symbols[idx].end_address());
+                max_end
+            })
+            .collect();
+
+        let mut by_offset = by_address
+            .iter()
+            .filter(|&&(_, idx)| symbols[idx].size() > 0)
+            .map(|&(_, idx)| (symbols[idx].offset(), idx))
+            .collect::<Vec<_>>();
+        by_offset.sort_unstable();
+
+        SymbolIndex {
+            by_address,
+            max_ends,
+            by_offset,
+        }
+    }
+}
+
+/// Collects symbols that are sorted by address, keeping only the highest priority
+/// symbol when there are multiple symbols that cover the same address range.
+pub(crate) fn unique_symbols<'s, I>(symbols: I) -> Vec<&'s Symbol>
+where
+    I: Iterator<Item = &'s Symbol>,
+{
+    let mut unique: Vec<&Symbol> = Vec::new();
+    for sym in symbols {
+        if let Some(last) = unique.last_mut() {
+            if last.address_range() == sym.address_range() {
+                if sym.source() < last.source() {
+                    *last = sym;
+                }
+                continue;
+            }
+        }
+        unique.push(sym);
+    }
+    unique
+}
+
+/// Matches a name against a glob pattern with `*` and `?` wildcards.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    let name = name.chars().collect::<Vec<char>>();
+
+    let (mut p, mut n) = (0, 0);
+    // The positions to backtrack to after the last `*`, which tries to match one more
+    // character each time.
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn symbol(name: &str, addr: u64, size: usize) -> Symbol {
+        Symbol::new_unmangled(
+            name.to_string(),
+            addr,
+            addr as usize,
+            size,
+            SymbolSource::Elf,
+        )
+    }
+
+    /// Looks up an address in symbols that are sorted by address, and returns the name of
+    /// the symbol and the offset in it.
+    fn at_address(symbols: &[Symbol], addr: u64) -> Option<(String, u64)> {
+        let index = SymbolIndex::new(symbols);
+        let table = SymbolTable::new(symbols, &index, None);
+        table
+            .at_address(addr)
+            .map(|(sym, offset)| (sym.name().to_string(), offset))
+    }
+
+    #[test]
+    fn at_address_overlapping() {
+        let symbols = [
+            symbol("outer", 0x1000, 0x100),
+            symbol("label", 0x1010, 0),
+            symbol("inner", 0x1020, 0x10),
+            symbol("next", 0x1100, 0x10),
+        ];
+        assert_eq!(at_address(&symbols, 0x1000), Some(("outer".to_string(), 0)));
+        assert_eq!(at_address(&symbols, 0x1024), Some(("inner".to_string(), 4)));
+        // After the end of `inner`, but still inside of `outer`.
+        assert_eq!(
+            at_address(&symbols, 0x1030),
+            Some(("outer".to_string(), 0x30))
+        );
+        assert_eq!(
+            at_address(&symbols, 0x10ff),
+            Some(("outer".to_string(), 0xff))
+        );
+        assert_eq!(at_address(&symbols, 0x1100), Some(("next".to_string(), 0)));
+        assert_eq!(at_address(&symbols, 0x1110), None);
+    }
+
+    #[test]
+    fn at_address_zero_sized() {
+        let symbols = [
+            symbol("function", 0x1000, 0x40),
+            symbol("label", 0x1010, 0),
+            symbol("alone", 0x2000, 0),
+            symbol("start", 0x3000, 0),
+            symbol("sized", 0x3000, 0x20),
+        ];
+        // Zero-sized symbols are only found at their own address.
+        assert_eq!(at_address(&symbols, 0x1010), Some(("label".to_string(), 0)));
+        assert_eq!(
+            at_address(&symbols, 0x1018),
+            Some(("function".to_string(), 0x18))
+        );
+        assert_eq!(at_address(&symbols, 0x2000), Some(("alone".to_string(), 0)));
+        assert_eq!(at_address(&symbols, 0x2001), None);
+        // Sized symbols are preferred at the same address.
+        assert_eq!(at_address(&symbols, 0x3000), Some(("sized".to_string(), 0)));
+        assert_eq!(
+            at_address(&symbols, 0x3010),
+            Some(("sized".to_string(), 0x10))
+        );
+    }
+
+    #[test]
+    fn at_address_outside_of_symbols() {
+        let symbols = [symbol("first", 0x1000, 0x10), symbol("last", 0x2000, 0x10)];
+        assert_eq!(at_address(&symbols, 0), None);
+        assert_eq!(at_address(&symbols, 0xfff), None);
+        assert_eq!(at_address(&symbols, 0x1010), None);
+        assert_eq!(at_address(&symbols, 0x2010), None);
+        assert_eq!(at_address(&[], 0x1000), None);
+    }
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("my_crate::*::new", "my_crate::foo::new"));
+        assert!(glob_match("my_crate::*::new", "my_crate::foo::bar::new"));
+        assert!(!glob_match("my_crate::*::new", "my_crate::foo::new_in"));
+        assert!(glob_match("foo", "foo"));
+        assert!(!glob_match("foo", "foobar"));
+        assert!(!glob_match("", "foo"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("**", "anything"));
+        assert!(glob_match("a?c", "abc"));
+        assert!(!glob_match("a?c", "ac"));
+        // `*` has to backtrack when the rest of the pattern doesn't match.
+        assert!(glob_match("*ab", "aab"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("a*b*c", "aXbY"));
+    }
+}
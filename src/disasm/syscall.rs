@@ -0,0 +1,611 @@
+//! Names the system calls that are made directly with `syscall`, `svc`, `ecall`, or
+//! `int 0x80` instead of through the C library, which is common in statically linked
+//! binaries and in code that uses raw system calls.
+//!
+//! The number of a system call is in a register that is usually set right before it, so
+//! only numbers that are assigned a constant in the same basic block are found. The numbers
+//! depend on the operating system and the architecture, and on Windows they also change
+//! between releases, so its table only has the numbers of Windows 10 and 11 that are the
+//! same in every release of them.
+
+use super::binary::{Arch, Binary, Os};
+use super::tls::{destination, parse_signed};
+use super::{DisasmLine, Disassembly, Flow, Jump};
+use std::collections::HashSet;
+
+/// How many instructions before a system call are searched for the assignment of its
+/// number.
+const SEARCH_WINDOW: usize = 16;
+
+/// The first number of the system calls that were added to every architecture of Linux at
+/// the same time, which have the same numbers everywhere.
+const LINUX_COMMON_START: u64 = 424;
+
+/// How a binary makes system calls.
+struct Convention {
+    /// The names of the register that holds the number of the system call, which set it
+    /// entirely when they are written to.
+    registers: &'static [&'static str],
+
+    /// The parts of the register that leave the rest of it alone when they are written to.
+    partial_registers: &'static [&'static str],
+
+    /// The names of the system calls, indexed by their numbers. Numbers that aren't used
+    /// have an empty name.
+    table: &'static [&'static str],
+
+    os: Os,
+}
+
+impl Convention {
+    fn for_binary(binary: &Binary) -> Option<Convention> {
+        let (registers, partial_registers, table): (&[&str], &[&str], &[&str]) =
+            match (binary.os(), binary.arch()) {
+                (Os::Linux, Arch::X86_64) => (&["rax", "eax"], &["ax", "al", "ah"], LINUX_X86_64),
+                (Os::Linux, Arch::X86) => (&["eax"], &["ax", "al", "ah"], LINUX_X86),
+                (Os::Linux, Arch::AArch64) => (&["x8", "w8"], &[], LINUX_GENERIC),
+                (Os::Linux, Arch::RiscV32) | (Os::Linux, Arch::RiscV64) => {
+                    (&["a7"], &[], LINUX_GENERIC)
+                }
+                (Os::Windows, Arch::X86_64) => {
+                    (&["rax", "eax"], &["ax", "al", "ah"], WINDOWS_X86_64)
+                }
+                _ => return None,
+            };
+        Some(Convention {
+            registers,
+            partial_registers,
+            table,
+            os: binary.os(),
+        })
+    }
+
+    /// Returns true if an instruction makes a system call.
+    fn is_syscall(&self, arch: Arch, line: &DisasmLine) -> bool {
+        let operands = line.operands().trim_start_matches(['$', '#']);
+        match arch {
+            Arch::X86_64 => line.mnemonic() == "syscall",
+            Arch::X86 => line.mnemonic() == "int" && operands == "0x80",
+            Arch::AArch64 => line.mnemonic() == "svc" && (operands == "0" || operands == "0x0"),
+            Arch::RiscV32 | Arch::RiscV64 => line.mnemonic() == "ecall",
+            _ => false,
+        }
+    }
+
+    /// Describes a system call, e.g. `write(2)` on Linux, after the section of its manual
+    /// page, or `NtWriteFile` on Windows.
+    fn describe(&self, number: u64) -> String {
+        let name = if self.os == Os::Linux && number >= LINUX_COMMON_START {
+            LINUX_COMMON.get((number - LINUX_COMMON_START) as usize)
+        } else {
+            self.table.get(number as usize)
+        };
+        match (name.copied().filter(|name| !name.is_empty()), self.os) {
+            (Some(name), Os::Linux) => format!("{}(2)", name),
+            (Some(name), _) => name.to_string(),
+            (None, _) => format!("system call {}", number),
+        }
+    }
+}
+
+/// Comments the instructions that make system calls with the names of the calls if their
+/// numbers are constant. Instructions that already have a comment are left alone.
+pub fn annotate(binary: &Binary, disassembly: &mut Disassembly) {
+    let convention = match Convention::for_binary(binary) {
+        Some(convention) => convention,
+        None => return,
+    };
+    let lines = &mut disassembly.lines;
+    let jump_targets = lines
+        .iter()
+        .filter_map(|line| match line.jump() {
+            Jump::Internal(idx) => Some(idx),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+
+    let mut comments: Vec<(usize, String)> = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        if !convention.is_syscall(binary.arch(), line) {
+            continue;
+        }
+        match syscall_number(&convention, lines, &jump_targets, idx) {
+            Some(number) => comments.push((idx, convention.describe(number))),
+            None => log::trace!("no constant system call number at 0x{:x}", line.address()),
+        }
+    }
+
+    for (idx, comment) in comments {
+        if lines[idx].comments.is_none() {
+            lines[idx].comments = Some(comment.into());
+        }
+    }
+}
+
+/// Finds the constant that the number of the system call at `idx` is set to by going back
+/// to the last instruction that writes the register. The search stops at the start of the
+/// basic block, because the number could come from somewhere else there.
+fn syscall_number(
+    convention: &Convention,
+    lines: &[DisasmLine],
+    jump_targets: &HashSet<usize>,
+    idx: usize,
+) -> Option<u64> {
+    for assign_idx in (idx.saturating_sub(SEARCH_WINDOW)..idx).rev() {
+        if jump_targets.contains(&(assign_idx + 1)) {
+            return None;
+        }
+        let line = &lines[assign_idx];
+        if line.flow() != Flow::Next {
+            return None;
+        }
+
+        let dest = destination(line.operands()).trim_start_matches('%');
+        if convention.partial_registers.contains(&dest) {
+            return None;
+        }
+        if !convention.registers.contains(&dest) {
+            continue;
+        }
+        return constant_assignment(line);
+    }
+    None
+}
+
+/// Returns the constant that an instruction sets its destination register to, e.g. for
+/// `mov eax, 0x1`, `mov $0x1, %eax`, `xor eax, eax`, `mov x8, #0x40`, or `li a7, 64`.
+fn constant_assignment(line: &DisasmLine) -> Option<u64> {
+    let operands = line
+        .operands()
+        .split(',')
+        .map(|operand| operand.trim().trim_start_matches('%'))
+        .collect::<Vec<_>>();
+    // AT&T syntax has the source first and the other syntaxes have it last.
+    let att = line.operands().contains('%');
+    let source = if att {
+        operands[0]
+    } else {
+        operands[operands.len() - 1]
+    };
+
+    let value = match line.mnemonic() {
+        "mov" | "movl" | "movq" | "movz" | "li" | "c.li" if operands.len() == 2 => {
+            if source == "wzr" || source == "xzr" || source == "zero" {
+                0
+            } else {
+                parse_signed(source.trim_start_matches('#'))?
+            }
+        }
+        "addi" | "addiw"
+            if operands.len() == 3 && (operands[1] == "zero" || operands[1] == "x0") =>
+        {
+            parse_signed(source)?
+        }
+        "xor" | "xorl" | "xorq" if operands.len() == 2 && operands[0] == operands[1] => 0,
+        _ => return None,
+    };
+    if value < 0 {
+        None
+    } else {
+        Some(value as u64)
+    }
+}
+
+/// The system calls of Linux on x86_64.
+#[rustfmt::skip]
+static LINUX_X86_64: &[&str] = &[
+    /* 0 */ "read", "write", "open", "close",
+    /* 4 */ "stat", "fstat", "lstat", "poll",
+    /* 8 */ "lseek", "mmap", "mprotect", "munmap",
+    /* 12 */ "brk", "rt_sigaction", "rt_sigprocmask", "rt_sigreturn",
+    /* 16 */ "ioctl", "pread64", "pwrite64", "readv",
+    /* 20 */ "writev", "access", "pipe", "select",
+    /* 24 */ "sched_yield", "mremap", "msync", "mincore",
+    /* 28 */ "madvise", "shmget", "shmat", "shmctl",
+    /* 32 */ "dup", "dup2", "pause", "nanosleep",
+    /* 36 */ "getitimer", "alarm", "setitimer", "getpid",
+    /* 40 */ "sendfile", "socket", "connect", "accept",
+    /* 44 */ "sendto", "recvfrom", "sendmsg", "recvmsg",
+    /* 48 */ "shutdown", "bind", "listen", "getsockname",
+    /* 52 */ "getpeername", "socketpair", "setsockopt", "getsockopt",
+    /* 56 */ "clone", "fork", "vfork", "execve",
+    /* 60 */ "exit", "wait4", "kill", "uname",
+    /* 64 */ "semget", "semop", "semctl", "shmdt",
+    /* 68 */ "msgget", "msgsnd", "msgrcv", "msgctl",
+    /* 72 */ "fcntl", "flock", "fsync", "fdatasync",
+    /* 76 */ "truncate", "ftruncate", "getdents", "getcwd",
+    /* 80 */ "chdir", "fchdir", "rename", "mkdir",
+    /* 84 */ "rmdir", "creat", "link", "unlink",
+    /* 88 */ "symlink", "readlink", "chmod", "fchmod",
+    /* 92 */ "chown", "fchown", "lchown", "umask",
+    /* 96 */ "gettimeofday", "getrlimit", "getrusage", "sysinfo",
+    /* 100 */ "times", "ptrace", "getuid", "syslog",
+    /* 104 */ "getgid", "setuid", "setgid", "geteuid",
+    /* 108 */ "getegid", "setpgid", "getppid", "getpgrp",
+    /* 112 */ "setsid", "setreuid", "setregid", "getgroups",
+    /* 116 */ "setgroups", "setresuid", "getresuid", "setresgid",
+    /* 120 */ "getresgid", "getpgid", "setfsuid", "setfsgid",
+    /* 124 */ "getsid", "capget", "capset", "rt_sigpending",
+    /* 128 */ "rt_sigtimedwait", "rt_sigqueueinfo", "rt_sigsuspend", "sigaltstack",
+    /* 132 */ "utime", "mknod", "uselib", "personality",
+    /* 136 */ "ustat", "statfs", "fstatfs", "sysfs",
+    /* 140 */ "getpriority", "setpriority", "sched_setparam", "sched_getparam",
+    /* 144 */ "sched_setscheduler", "sched_getscheduler", "sched_get_priority_max", "sched_get_priority_min",
+    /* 148 */ "sched_rr_get_interval", "mlock", "munlock", "mlockall",
+    /* 152 */ "munlockall", "vhangup", "modify_ldt", "pivot_root",
+    /* 156 */ "_sysctl", "prctl", "arch_prctl", "adjtimex",
+    /* 160 */ "setrlimit", "chroot", "sync", "acct",
+    /* 164 */ "settimeofday", "mount", "umount2", "swapon",
+    /* 168 */ "swapoff", "reboot", "sethostname", "setdomainname",
+    /* 172 */ "iopl", "ioperm", "create_module", "init_module",
+    /* 176 */ "delete_module", "get_kernel_syms", "query_module", "quotactl",
+    /* 180 */ "nfsservctl", "getpmsg", "putpmsg", "afs_syscall",
+    /* 184 */ "tuxcall", "security", "gettid", "readahead",
+    /* 188 */ "setxattr", "lsetxattr", "fsetxattr", "getxattr",
+    /* 192 */ "lgetxattr", "fgetxattr", "listxattr", "llistxattr",
+    /* 196 */ "flistxattr", "removexattr", "lremovexattr", "fremovexattr",
+    /* 200 */ "tkill", "time", "futex", "sched_setaffinity",
+    /* 204 */ "sched_getaffinity", "set_thread_area", "io_setup", "io_destroy",
+    /* 208 */ "io_getevents", "io_submit", "io_cancel", "get_thread_area",
+    /* 212 */ "lookup_dcookie", "epoll_create", "epoll_ctl_old", "epoll_wait_old",
+    /* 216 */ "remap_file_pages", "getdents64", "set_tid_address", "restart_syscall",
+    /* 220 */ "semtimedop", "fadvise64", "timer_create", "timer_settime",
+    /* 224 */ "timer_gettime", "timer_getoverrun", "timer_delete", "clock_settime",
+    /* 228 */ "clock_gettime", "clock_getres", "clock_nanosleep", "exit_group",
+    /* 232 */ "epoll_wait", "epoll_ctl", "tgkill", "utimes",
+    /* 236 */ "vserver", "mbind", "set_mempolicy", "get_mempolicy",
+    /* 240 */ "mq_open", "mq_unlink", "mq_timedsend", "mq_timedreceive",
+    /* 244 */ "mq_notify", "mq_getsetattr", "kexec_load", "waitid",
+    /* 248 */ "add_key", "request_key", "keyctl", "ioprio_set",
+    /* 252 */ "ioprio_get", "inotify_init", "inotify_add_watch", "inotify_rm_watch",
+    /* 256 */ "migrate_pages", "openat", "mkdirat", "mknodat",
+    /* 260 */ "fchownat", "futimesat", "newfstatat", "unlinkat",
+    /* 264 */ "renameat", "linkat", "symlinkat", "readlinkat",
+    /* 268 */ "fchmodat", "faccessat", "pselect6", "ppoll",
+    /* 272 */ "unshare", "set_robust_list", "get_robust_list", "splice",
+    /* 276 */ "tee", "sync_file_range", "vmsplice", "move_pages",
+    /* 280 */ "utimensat", "epoll_pwait", "signalfd", "timerfd_create",
+    /* 284 */ "eventfd", "fallocate", "timerfd_settime", "timerfd_gettime",
+    /* 288 */ "accept4", "signalfd4", "eventfd2", "epoll_create1",
+    /* 292 */ "dup3", "pipe2", "inotify_init1", "preadv",
+    /* 296 */ "pwritev", "rt_tgsigqueueinfo", "perf_event_open", "recvmmsg",
+    /* 300 */ "fanotify_init", "fanotify_mark", "prlimit64", "name_to_handle_at",
+    /* 304 */ "open_by_handle_at", "clock_adjtime", "syncfs", "sendmmsg",
+    /* 308 */ "setns", "getcpu", "process_vm_readv", "process_vm_writev",
+    /* 312 */ "kcmp", "finit_module", "sched_setattr", "sched_getattr",
+    /* 316 */ "renameat2", "seccomp", "getrandom", "memfd_create",
+    /* 320 */ "kexec_file_load", "bpf", "execveat", "userfaultfd",
+    /* 324 */ "membarrier", "mlock2", "copy_file_range", "preadv2",
+    /* 328 */ "pwritev2", "pkey_mprotect", "pkey_alloc", "pkey_free",
+    /* 332 */ "statx", "io_pgetevents", "rseq",
+];
+
+/// The system calls of Linux on x86 that existed before the tables of every architecture
+/// were extended at the same time.
+#[rustfmt::skip]
+static LINUX_X86: &[&str] = &[
+    /* 0 */ "restart_syscall", "exit", "fork", "read",
+    /* 4 */ "write", "open", "close", "waitpid",
+    /* 8 */ "creat", "link", "unlink", "execve",
+    /* 12 */ "chdir", "time", "mknod", "chmod",
+    /* 16 */ "lchown", "break", "oldstat", "lseek",
+    /* 20 */ "getpid", "mount", "umount", "setuid",
+    /* 24 */ "getuid", "stime", "ptrace", "alarm",
+    /* 28 */ "oldfstat", "pause", "utime", "stty",
+    /* 32 */ "gtty", "access", "nice", "ftime",
+    /* 36 */ "sync", "kill", "rename", "mkdir",
+    /* 40 */ "rmdir", "dup", "pipe", "times",
+    /* 44 */ "prof", "brk", "setgid", "getgid",
+    /* 48 */ "signal", "geteuid", "getegid", "acct",
+    /* 52 */ "umount2", "lock", "ioctl", "fcntl",
+    /* 56 */ "mpx", "setpgid", "ulimit", "oldolduname",
+    /* 60 */ "umask", "chroot", "ustat", "dup2",
+    /* 64 */ "getppid", "getpgrp", "setsid", "sigaction",
+    /* 68 */ "sgetmask", "ssetmask", "setreuid", "setregid",
+    /* 72 */ "sigsuspend", "sigpending", "sethostname", "setrlimit",
+    /* 76 */ "getrlimit", "getrusage", "gettimeofday", "settimeofday",
+    /* 80 */ "getgroups", "setgroups", "select", "symlink",
+    /* 84 */ "oldlstat", "readlink", "uselib", "swapon",
+    /* 88 */ "reboot", "readdir", "mmap", "munmap",
+    /* 92 */ "truncate", "ftruncate", "fchmod", "fchown",
+    /* 96 */ "getpriority", "setpriority", "profil", "statfs",
+    /* 100 */ "fstatfs", "ioperm", "socketcall", "syslog",
+    /* 104 */ "setitimer", "getitimer", "stat", "lstat",
+    /* 108 */ "fstat", "olduname", "iopl", "vhangup",
+    /* 112 */ "idle", "vm86old", "wait4", "swapoff",
+    /* 116 */ "sysinfo", "ipc", "fsync", "sigreturn",
+    /* 120 */ "clone", "setdomainname", "uname", "modify_ldt",
+    /* 124 */ "adjtimex", "mprotect", "sigprocmask", "create_module",
+    /* 128 */ "init_module", "delete_module", "get_kernel_syms", "quotactl",
+    /* 132 */ "getpgid", "fchdir", "bdflush", "sysfs",
+    /* 136 */ "personality", "afs_syscall", "setfsuid", "setfsgid",
+    /* 140 */ "_llseek", "getdents", "_newselect", "flock",
+    /* 144 */ "msync", "readv", "writev", "getsid",
+    /* 148 */ "fdatasync", "_sysctl", "mlock", "munlock",
+    /* 152 */ "mlockall", "munlockall", "sched_setparam", "sched_getparam",
+    /* 156 */ "sched_setscheduler", "sched_getscheduler", "sched_yield", "sched_get_priority_max",
+    /* 160 */ "sched_get_priority_min", "sched_rr_get_interval", "nanosleep", "mremap",
+    /* 164 */ "setresuid", "getresuid", "vm86", "query_module",
+    /* 168 */ "poll", "nfsservctl", "setresgid", "getresgid",
+    /* 172 */ "prctl", "rt_sigreturn", "rt_sigaction", "rt_sigprocmask",
+    /* 176 */ "rt_sigpending", "rt_sigtimedwait", "rt_sigqueueinfo", "rt_sigsuspend",
+    /* 180 */ "pread64", "pwrite64", "chown", "getcwd",
+    /* 184 */ "capget", "capset", "sigaltstack", "sendfile",
+    /* 188 */ "getpmsg", "putpmsg", "vfork", "ugetrlimit",
+    /* 192 */ "mmap2", "truncate64", "ftruncate64", "stat64",
+    /* 196 */ "lstat64", "fstat64", "lchown32", "getuid32",
+    /* 200 */ "getgid32", "geteuid32", "getegid32", "setreuid32",
+    /* 204 */ "setregid32", "getgroups32", "setgroups32", "fchown32",
+    /* 208 */ "setresuid32", "getresuid32", "setresgid32", "getresgid32",
+    /* 212 */ "chown32", "setuid32", "setgid32", "setfsuid32",
+    /* 216 */ "setfsgid32", "pivot_root", "mincore", "madvise",
+    /* 220 */ "getdents64", "fcntl64", "", "",
+    /* 224 */ "gettid", "readahead", "setxattr", "lsetxattr",
+    /* 228 */ "fsetxattr", "getxattr", "lgetxattr", "fgetxattr",
+    /* 232 */ "listxattr", "llistxattr", "flistxattr", "removexattr",
+    /* 236 */ "lremovexattr", "fremovexattr", "tkill", "sendfile64",
+    /* 240 */ "futex", "sched_setaffinity", "sched_getaffinity", "set_thread_area",
+    /* 244 */ "get_thread_area", "io_setup", "io_destroy", "io_getevents",
+    /* 248 */ "io_submit", "io_cancel", "fadvise64", "",
+    /* 252 */ "exit_group", "lookup_dcookie", "epoll_create", "epoll_ctl",
+    /* 256 */ "epoll_wait", "remap_file_pages", "set_tid_address", "timer_create",
+    /* 260 */ "timer_settime", "timer_gettime", "timer_getoverrun", "timer_delete",
+    /* 264 */ "clock_settime", "clock_gettime", "clock_getres", "clock_nanosleep",
+    /* 268 */ "statfs64", "fstatfs64", "tgkill", "utimes",
+    /* 272 */ "fadvise64_64", "vserver", "mbind", "get_mempolicy",
+    /* 276 */ "set_mempolicy", "mq_open", "mq_unlink", "mq_timedsend",
+    /* 280 */ "mq_timedreceive", "mq_notify", "mq_getsetattr", "kexec_load",
+    /* 284 */ "waitid", "", "add_key", "request_key",
+    /* 288 */ "keyctl", "ioprio_set", "ioprio_get", "inotify_init",
+    /* 292 */ "inotify_add_watch", "inotify_rm_watch", "migrate_pages", "openat",
+    /* 296 */ "mkdirat", "mknodat", "fchownat", "futimesat",
+    /* 300 */ "fstatat64", "unlinkat", "renameat", "linkat",
+    /* 304 */ "symlinkat", "readlinkat", "fchmodat", "faccessat",
+    /* 308 */ "pselect6", "ppoll", "unshare", "set_robust_list",
+    /* 312 */ "get_robust_list", "splice", "sync_file_range", "tee",
+    /* 316 */ "vmsplice", "move_pages", "getcpu", "epoll_pwait",
+    /* 320 */ "utimensat", "signalfd", "timerfd_create", "eventfd",
+    /* 324 */ "fallocate", "timerfd_settime", "timerfd_gettime", "signalfd4",
+    /* 328 */ "eventfd2", "epoll_create1", "dup3", "pipe2",
+    /* 332 */ "inotify_init1", "preadv", "pwritev", "rt_tgsigqueueinfo",
+    /* 336 */ "perf_event_open", "recvmmsg", "fanotify_init", "fanotify_mark",
+    /* 340 */ "prlimit64", "name_to_handle_at", "open_by_handle_at", "clock_adjtime",
+    /* 344 */ "syncfs", "sendmmsg", "setns", "process_vm_readv",
+    /* 348 */ "process_vm_writev", "kcmp", "finit_module", "sched_setattr",
+    /* 352 */ "sched_getattr", "renameat2", "seccomp", "getrandom",
+    /* 356 */ "memfd_create", "bpf", "execveat", "socket",
+    /* 360 */ "socketpair", "bind", "connect", "listen",
+    /* 364 */ "accept4", "getsockopt", "setsockopt", "getsockname",
+    /* 368 */ "getpeername", "sendto", "sendmsg", "recvfrom",
+    /* 372 */ "recvmsg", "shutdown", "userfaultfd", "membarrier",
+    /* 376 */ "mlock2", "copy_file_range", "preadv2", "pwritev2",
+    /* 380 */ "pkey_mprotect", "pkey_alloc", "pkey_free", "statx",
+    /* 384 */ "arch_prctl", "io_pgetevents", "rseq", "",
+    /* 388 */ "", "", "", "",
+    /* 392 */ "", "semget", "semctl", "shmget",
+    /* 396 */ "shmctl", "shmat", "shmdt", "msgget",
+    /* 400 */ "msgsnd", "msgrcv", "msgctl", "clock_gettime64",
+    /* 404 */ "clock_settime64", "clock_adjtime64", "clock_getres_time64", "clock_nanosleep_time64",
+    /* 408 */ "timer_gettime64", "timer_settime64", "timerfd_gettime64", "timerfd_settime64",
+    /* 412 */ "utimensat_time64", "pselect6_time64", "ppoll_time64", "",
+    /* 416 */ "io_pgetevents_time64", "recvmmsg_time64", "mq_timedsend_time64", "mq_timedreceive_time64",
+    /* 420 */ "semtimedop_time64", "rt_sigtimedwait_time64", "futex_time64", "sched_rr_get_interval_time64",
+];
+
+/// The system calls of Linux on the architectures that use the generic table, such as
+/// AArch64 and RISC-V.
+#[rustfmt::skip]
+static LINUX_GENERIC: &[&str] = &[
+    /* 0 */ "io_setup", "io_destroy", "io_submit", "io_cancel",
+    /* 4 */ "io_getevents", "setxattr", "lsetxattr", "fsetxattr",
+    /* 8 */ "getxattr", "lgetxattr", "fgetxattr", "listxattr",
+    /* 12 */ "llistxattr", "flistxattr", "removexattr", "lremovexattr",
+    /* 16 */ "fremovexattr", "getcwd", "lookup_dcookie", "eventfd2",
+    /* 20 */ "epoll_create1", "epoll_ctl", "epoll_pwait", "dup",
+    /* 24 */ "dup3", "fcntl", "inotify_init1", "inotify_add_watch",
+    /* 28 */ "inotify_rm_watch", "ioctl", "ioprio_set", "ioprio_get",
+    /* 32 */ "flock", "mknodat", "mkdirat", "unlinkat",
+    /* 36 */ "symlinkat", "linkat", "renameat", "umount2",
+    /* 40 */ "mount", "pivot_root", "nfsservctl", "statfs",
+    /* 44 */ "fstatfs", "truncate", "ftruncate", "fallocate",
+    /* 48 */ "faccessat", "chdir", "fchdir", "chroot",
+    /* 52 */ "fchmod", "fchmodat", "fchownat", "fchown",
+    /* 56 */ "openat", "close", "vhangup", "pipe2",
+    /* 60 */ "quotactl", "getdents64", "lseek", "read",
+    /* 64 */ "write", "readv", "writev", "pread64",
+    /* 68 */ "pwrite64", "preadv", "pwritev", "sendfile",
+    /* 72 */ "pselect6", "ppoll", "signalfd4", "vmsplice",
+    /* 76 */ "splice", "tee", "readlinkat", "newfstatat",
+    /* 80 */ "fstat", "sync", "fsync", "fdatasync",
+    /* 84 */ "sync_file_range", "timerfd_create", "timerfd_settime", "timerfd_gettime",
+    /* 88 */ "utimensat", "acct", "capget", "capset",
+    /* 92 */ "personality", "exit", "exit_group", "waitid",
+    /* 96 */ "set_tid_address", "unshare", "futex", "set_robust_list",
+    /* 100 */ "get_robust_list", "nanosleep", "getitimer", "setitimer",
+    /* 104 */ "kexec_load", "init_module", "delete_module", "timer_create",
+    /* 108 */ "timer_gettime", "timer_getoverrun", "timer_settime", "timer_delete",
+    /* 112 */ "clock_settime", "clock_gettime", "clock_getres", "clock_nanosleep",
+    /* 116 */ "syslog", "ptrace", "sched_setparam", "sched_setscheduler",
+    /* 120 */ "sched_getscheduler", "sched_getparam", "sched_setaffinity", "sched_getaffinity",
+    /* 124 */ "sched_yield", "sched_get_priority_max", "sched_get_priority_min", "sched_rr_get_interval",
+    /* 128 */ "restart_syscall", "kill", "tkill", "tgkill",
+    /* 132 */ "sigaltstack", "rt_sigsuspend", "rt_sigaction", "rt_sigprocmask",
+    /* 136 */ "rt_sigpending", "rt_sigtimedwait", "rt_sigqueueinfo", "rt_sigreturn",
+    /* 140 */ "setpriority", "getpriority", "reboot", "setregid",
+    /* 144 */ "setgid", "setreuid", "setuid", "setresuid",
+    /* 148 */ "getresuid", "setresgid", "getresgid", "setfsuid",
+    /* 152 */ "setfsgid", "times", "setpgid", "getpgid",
+    /* 156 */ "getsid", "setsid", "getgroups", "setgroups",
+    /* 160 */ "uname", "sethostname", "setdomainname", "getrlimit",
+    /* 164 */ "setrlimit", "getrusage", "umask", "prctl",
+    /* 168 */ "getcpu", "gettimeofday", "settimeofday", "adjtimex",
+    /* 172 */ "getpid", "getppid", "getuid", "geteuid",
+    /* 176 */ "getgid", "getegid", "gettid", "sysinfo",
+    /* 180 */ "mq_open", "mq_unlink", "mq_timedsend", "mq_timedreceive",
+    /* 184 */ "mq_notify", "mq_getsetattr", "msgget", "msgctl",
+    /* 188 */ "msgrcv", "msgsnd", "semget", "semctl",
+    /* 192 */ "semtimedop", "semop", "shmget", "shmctl",
+    /* 196 */ "shmat", "shmdt", "socket", "socketpair",
+    /* 200 */ "bind", "listen", "accept", "connect",
+    /* 204 */ "getsockname", "getpeername", "sendto", "recvfrom",
+    /* 208 */ "setsockopt", "getsockopt", "shutdown", "sendmsg",
+    /* 212 */ "recvmsg", "readahead", "brk", "munmap",
+    /* 216 */ "mremap", "add_key", "request_key", "keyctl",
+    /* 220 */ "clone", "execve", "mmap", "fadvise64",
+    /* 224 */ "swapon", "swapoff", "mprotect", "msync",
+    /* 228 */ "mlock", "munlock", "mlockall", "munlockall",
+    /* 232 */ "mincore", "madvise", "remap_file_pages", "mbind",
+    /* 236 */ "get_mempolicy", "set_mempolicy", "migrate_pages", "move_pages",
+    /* 240 */ "rt_tgsigqueueinfo", "perf_event_open", "accept4", "recvmmsg",
+    /* 244 */ "", "", "", "",
+    /* 248 */ "", "", "", "",
+    /* 252 */ "", "", "", "",
+    /* 256 */ "", "", "", "",
+    /* 260 */ "wait4", "prlimit64", "fanotify_init", "fanotify_mark",
+    /* 264 */ "name_to_handle_at", "open_by_handle_at", "clock_adjtime", "syncfs",
+    /* 268 */ "setns", "sendmmsg", "process_vm_readv", "process_vm_writev",
+    /* 272 */ "kcmp", "finit_module", "sched_setattr", "sched_getattr",
+    /* 276 */ "renameat2", "seccomp", "getrandom", "memfd_create",
+    /* 280 */ "bpf", "execveat", "userfaultfd", "membarrier",
+    /* 284 */ "mlock2", "copy_file_range", "preadv2", "pwritev2",
+    /* 288 */ "pkey_mprotect", "pkey_alloc", "pkey_free", "statx",
+    /* 292 */ "io_pgetevents", "rseq", "kexec_file_load",
+];
+
+/// The system calls of Linux that have the same numbers on every architecture, starting at
+/// `LINUX_COMMON_START`.
+#[rustfmt::skip]
+static LINUX_COMMON: &[&str] = &[
+    /* 424 */ "pidfd_send_signal", "io_uring_setup", "io_uring_enter", "io_uring_register",
+    /* 428 */ "open_tree", "move_mount", "fsopen", "fsconfig",
+    /* 432 */ "fsmount", "fspick", "pidfd_open", "clone3",
+    /* 436 */ "close_range", "openat2", "pidfd_getfd", "faccessat2",
+    /* 440 */ "process_madvise", "epoll_pwait2", "mount_setattr", "quotactl_fd",
+    /* 444 */ "landlock_create_ruleset", "landlock_add_rule", "landlock_restrict_self", "memfd_secret",
+    /* 448 */ "process_mrelease", "futex_waitv", "set_mempolicy_home_node",
+];
+
+/// The system services of Windows 10 and 11 on x86_64 with the lowest numbers, which are the
+/// same in every release of them.
+#[rustfmt::skip]
+static WINDOWS_X86_64: &[&str] = &[
+    /* 0 */ "NtAccessCheck", "NtWorkerFactoryWorkerReady", "NtAcceptConnectPort",
+    /* 3 */ "NtMapUserPhysicalPagesScatter", "NtWaitForSingleObject", "NtCallbackReturn",
+    /* 6 */ "NtReadFile", "NtDeviceIoControlFile", "NtWriteFile",
+    /* 9 */ "NtRemoveIoCompletion", "NtReleaseSemaphore", "NtReplyWaitReceivePort",
+    /* 12 */ "NtReplyPort", "NtSetInformationThread", "NtSetEvent",
+    /* 15 */ "NtClose", "NtQueryObject", "NtQueryInformationFile",
+    /* 18 */ "NtOpenKey", "NtEnumerateValueKey", "NtFindAtom",
+    /* 21 */ "NtQueryDefaultLocale", "NtQueryKey", "NtQueryValueKey",
+    /* 24 */ "NtAllocateVirtualMemory", "NtQueryInformationProcess", "NtWaitForMultipleObjects32",
+    /* 27 */ "NtWriteFileGather", "NtSetInformationProcess", "NtCreateKey",
+    /* 30 */ "NtFreeVirtualMemory", "NtImpersonateClientOfPort", "NtReleaseMutant",
+    /* 33 */ "NtQueryInformationToken", "NtRequestWaitReplyPort", "NtQueryVirtualMemory",
+    /* 36 */ "NtOpenThreadToken", "NtQueryInformationThread", "NtOpenProcess",
+    /* 39 */ "NtSetInformationFile", "NtMapViewOfSection", "NtAccessCheckAndAuditAlarm",
+    /* 42 */ "NtUnmapViewOfSection", "NtReplyWaitReceivePortEx", "NtTerminateProcess",
+    /* 45 */ "NtSetEventBoostPriority", "NtReadFileScatter", "NtOpenThreadTokenEx",
+    /* 48 */ "NtOpenProcessTokenEx", "NtQueryPerformanceCounter", "NtEnumerateKey",
+    /* 51 */ "NtOpenFile", "NtDelayExecution", "NtQueryDirectoryFile",
+    /* 54 */ "NtQuerySystemInformation", "NtOpenSection", "NtQueryTimer",
+    /* 57 */ "NtFsControlFile", "NtWriteVirtualMemory", "NtCloseObjectAuditAlarm",
+    /* 60 */ "NtDuplicateObject", "NtQueryAttributesFile", "NtClearEvent",
+    /* 63 */ "NtReadVirtualMemory", "NtOpenEvent", "NtAdjustPrivilegesToken",
+    /* 66 */ "NtDuplicateToken", "NtContinue", "NtQueryDefaultUILanguage",
+    /* 69 */ "NtQueueApcThread", "NtYieldExecution", "NtAddAtom",
+    /* 72 */ "NtCreateEvent", "NtQueryVolumeInformationFile", "NtCreateSection",
+    /* 75 */ "NtFlushBuffersFile", "NtApphelpCacheControl", "NtCreateProcessEx",
+    /* 78 */ "NtCreateThread", "NtIsProcessInJob", "NtProtectVirtualMemory",
+    /* 81 */ "NtQuerySection", "NtResumeThread", "NtTerminateThread",
+    /* 84 */ "NtReadRequestData", "NtCreateFile",
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn line(mnemonic: &str, operands: &str) -> DisasmLine {
+        DisasmLine {
+            address: 0,
+            mnemonic: mnemonic.into(),
+            operands: operands.into(),
+            comments: None,
+            bytes: Box::new([]),
+            source_lines: None,
+            source_locations: None,
+            inlined_calls: None,
+            jump: Jump::None,
+            flow: Flow::Next,
+            memory_reference: None,
+            simd: None,
+            is_symbolicated_jump: false,
+        }
+    }
+
+    fn convention_for(table: &'static [&'static str], os: Os) -> Convention {
+        Convention {
+            registers: &[],
+            partial_registers: &[],
+            table,
+            os,
+        }
+    }
+
+    #[test]
+    fn constant_assignments() {
+        let constant = |mnemonic, operands| constant_assignment(&line(mnemonic, operands));
+        assert_eq!(constant("mov", "eax, 0x3c"), Some(60));
+        assert_eq!(constant("movl", "$0x3c, %eax"), Some(60));
+        assert_eq!(constant("xor", "eax, eax"), Some(0));
+        assert_eq!(constant("xorl", "%eax, %eax"), Some(0));
+        assert_eq!(constant("mov", "x8, #0x40"), Some(64));
+        assert_eq!(constant("mov", "w8, wzr"), Some(0));
+        assert_eq!(constant("li", "a7, 93"), Some(93));
+        assert_eq!(constant("addi", "a7, zero, 93"), Some(93));
+
+        assert_eq!(constant("mov", "eax, ebx"), None);
+        assert_eq!(constant("mov", "eax, -1"), None);
+        assert_eq!(constant("xor", "eax, ebx"), None);
+        assert_eq!(constant("add", "eax, 1"), None);
+        assert_eq!(constant("addi", "a7, a0, 93"), None);
+    }
+
+    #[test]
+    fn describe_linux_x86() {
+        let convention = convention_for(LINUX_X86, Os::Linux);
+        assert_eq!(convention.describe(4), "write(2)");
+        assert_eq!(convention.describe(258), "set_tid_address(2)");
+        assert_eq!(convention.describe(295), "openat(2)");
+        assert_eq!(convention.describe(355), "getrandom(2)");
+        assert_eq!(convention.describe(383), "statx(2)");
+        assert_eq!(convention.describe(403), "clock_gettime64(2)");
+        assert_eq!(convention.describe(423), "sched_rr_get_interval_time64(2)");
+        assert_eq!(convention.describe(435), "clone3(2)");
+        // Numbers that aren't used.
+        assert_eq!(convention.describe(285), "system call 285");
+        assert_eq!(convention.describe(390), "system call 390");
+        assert_eq!(convention.describe(1000), "system call 1000");
+    }
+
+    #[test]
+    fn describe_linux_x86_64_and_generic() {
+        let convention = convention_for(LINUX_X86_64, Os::Linux);
+        assert_eq!(convention.describe(1), "write(2)");
+        assert_eq!(convention.describe(257), "openat(2)");
+        assert_eq!(convention.describe(334), "rseq(2)");
+        assert_eq!(convention.describe(400), "system call 400");
+        assert_eq!(convention.describe(439), "faccessat2(2)");
+
+        let convention = convention_for(LINUX_GENERIC, Os::Linux);
+        assert_eq!(convention.describe(64), "write(2)");
+        assert_eq!(convention.describe(56), "openat(2)");
+    }
+
+    #[test]
+    fn describe_windows() {
+        let convention = convention_for(WINDOWS_X86_64, Os::Windows);
+        assert_eq!(convention.describe(8), "NtWriteFile");
+        assert_eq!(convention.describe(0x55), "NtCreateFile");
+        assert_eq!(convention.describe(0x1000), "system call 4096");
+    }
+}
@@ -0,0 +1,301 @@
+//! Resolves the code that accesses thread-local variables to the names of the variables.
+//!
+//! Thread-local variables are reached through the thread pointer (`fs` or `gs` on x86 and
+//! `tpidr_el0` on AArch64) at an offset that is only known relative to the TLS block of the
+//! binary, through GOT entries that are filled in by dynamic relocations, through the
+//! descriptors in `__thread_vars` on macOS, or through `_tls_index` on Windows.
+
+use super::binary::{Arch, Binary};
+use super::{DisasmLine, Disassembly};
+use std::ops::Range;
+
+/// How many instructions after the thread pointer is read are searched for the access to
+/// a variable.
+const SEARCH_WINDOW: usize = 8;
+
+/// The thread-local variables of a binary and the layout of its TLS block.
+#[derive(Clone, Debug, Default)]
+pub struct ThreadLocals {
+    /// The size of the TLS block, rounded up to its alignment.
+    pub block_size: u64,
+
+    /// The alignment of the TLS block.
+    pub block_align: u64,
+
+    /// The offsets of the variables in the TLS block and their names, sorted by offset.
+    pub variables: Vec<(Range<u64>, Box<str>)>,
+
+    /// The address of the `.tls` section of PE files, which the offsets that are used with
+    /// `_tls_index` are relative to.
+    pub section_address: Option<u64>,
+
+    /// The address range of the `__thread_vars` section of Mach-O files, which contains
+    /// the descriptors of the variables.
+    pub descriptors: Option<Range<u64>>,
+}
+
+impl ThreadLocals {
+    /// Returns the variable at an offset in the TLS block and the offset into it.
+    pub fn variable_at(&self, offset: u64) -> Option<(&str, u64)> {
+        let idx = match self
+            .variables
+            .binary_search_by(|(range, _)| range.start.cmp(&offset))
+        {
+            Ok(idx) => idx,
+            Err(0) => return None,
+            Err(idx) => idx - 1,
+        };
+        let (ref range, ref name) = self.variables[idx];
+        if range.contains(&offset) || range.start == offset {
+            Some((name, offset - range.start))
+        } else {
+            None
+        }
+    }
+}
+
+/// Comments the instructions that access thread-local variables with the names of the
+/// variables. Instructions that already have a comment are left alone.
+pub fn annotate(binary: &Binary, disassembly: &mut Disassembly) {
+    let tls = binary.thread_locals();
+    let lines = &mut disassembly.lines;
+    let mut comments: Vec<(usize, String)> = Vec::new();
+
+    for (idx, line) in lines.iter().enumerate() {
+        if let Some(addr) = line.memory_reference() {
+            if let Some(reloc) = binary
+                .data_relocation_at(addr)
+                .filter(|reloc| reloc.is_thread_local())
+            {
+                comments.push((idx, format!("thread local {}", reloc.target())));
+                continue;
+            }
+            if tls
+                .descriptors
+                .as_ref()
+                .is_some_and(|range| range.contains(&addr))
+            {
+                if let Some((symbol, 0)) = binary.symbolicate(addr) {
+                    comments.push((idx, format!("thread local {}", symbol.name())));
+                }
+                continue;
+            }
+        }
+
+        match binary.arch() {
+            Arch::X86 | Arch::X86_64 if tls.section_address.is_some() => {
+                if let Some(comment) = windows_access(binary, lines, idx) {
+                    comments.push(comment);
+                }
+            }
+            Arch::X86 | Arch::X86_64 => {
+                if let Some(comment) = x86_local_exec(binary, lines, idx) {
+                    comments.push(comment);
+                }
+            }
+            Arch::AArch64 => {
+                if let Some(comment) = arm64_local_exec(binary, lines, idx) {
+                    comments.push(comment);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (idx, comment) in comments {
+        if lines[idx].comments.is_none() {
+            lines[idx].comments = Some(comment.into());
+        }
+    }
+}
+
+/// Describes the variable at an offset from the start of the TLS block.
+fn describe(binary: &Binary, offset: i64) -> Option<String> {
+    if offset < 0 {
+        return None;
+    }
+    match binary.thread_locals().variable_at(offset as u64)? {
+        (name, 0) => Some(format!("thread local {}", name)),
+        (name, offset) => Some(format!("thread local {}+0x{:x}", name, offset)),
+    }
+}
+
+/// Recognizes the local-exec accesses of x86 and x86_64, which either use an offset from
+/// the thread pointer in the segment register directly (`mov eax, dword ptr fs:[-0x8]`) or
+/// load the thread pointer first (`mov rax, qword ptr fs:[0]`) and then add the offset.
+/// The TLS block is right before the thread pointer.
+fn x86_local_exec(binary: &Binary, lines: &[DisasmLine], idx: usize) -> Option<(usize, String)> {
+    let segment = if binary.arch() == Arch::X86_64 {
+        "fs:"
+    } else {
+        "gs:"
+    };
+    let block_size = binary.thread_locals().block_size as i64;
+
+    let operands = lines[idx].operands();
+    let segment_start = operands.find(segment)?;
+    let displacement = parse_signed(
+        operands[(segment_start + segment.len())..]
+            .trim_start_matches('[')
+            .split([']', ',', '('])
+            .next()?,
+    )?;
+    if displacement != 0 {
+        return Some((idx, describe(binary, displacement + block_size)?));
+    }
+
+    let thread_pointer = destination(operands);
+    lines
+        .iter()
+        .enumerate()
+        .skip(idx + 1)
+        .take(SEARCH_WINDOW)
+        .find_map(|(access_idx, line)| {
+            let offset = memory_displacement(line.operands(), thread_pointer)?;
+            Some((access_idx, describe(binary, offset + block_size)?))
+        })
+}
+
+/// Recognizes the local-exec accesses of AArch64, which add the offset to the thread
+/// pointer in up to two parts:
+///
+/// ```text
+/// mrs     x8, tpidr_el0
+/// add     x8, x8, #0x0, lsl #12
+/// add     x8, x8, #0x10
+/// ```
+///
+/// The TLS block is after the thread control block, which is 16 bytes.
+fn arm64_local_exec(binary: &Binary, lines: &[DisasmLine], idx: usize) -> Option<(usize, String)> {
+    let line = &lines[idx];
+    if line.mnemonic() != "mrs" || !line.operands().to_lowercase().contains("tpidr_el0") {
+        return None;
+    }
+
+    let mut register = destination(line.operands()).to_string();
+    let mut offset = 0;
+    let mut access = None;
+    for (add_idx, line) in lines.iter().enumerate().skip(idx + 1).take(SEARCH_WINDOW) {
+        let operands = line
+            .operands()
+            .split(',')
+            .map(str::trim)
+            .collect::<Vec<_>>();
+        if line.mnemonic() != "add" || operands.len() < 3 || operands[1] != register {
+            continue;
+        }
+        let imm = parse_signed(operands[2].trim_start_matches('#'))?;
+        let shift = if operands.get(3) == Some(&"lsl #12") {
+            12
+        } else {
+            0
+        };
+        offset += imm << shift;
+        register = operands[0].to_string();
+        access = Some(add_idx);
+    }
+
+    let align = binary.thread_locals().block_align.max(1) as i64;
+    let tcb_size = (16 + align - 1) / align * align;
+    Some((access?, describe(binary, offset - tcb_size)?))
+}
+
+/// Recognizes the accesses of Windows, which find the TLS block of the binary in the
+/// thread's TLS array (`gs:[0x58]` on x86_64 and `fs:[0x2c]` on x86) by `_tls_index` and
+/// then use the offset of the variable in the `.tls` section:
+///
+/// ```text
+/// mov     rax, qword ptr gs:[0x58]
+/// mov     ecx, dword ptr [rip + 0x1234]
+/// mov     rax, qword ptr [rax + rcx*8]
+/// mov     eax, dword ptr [rax + 0x10]
+/// ```
+fn windows_access(binary: &Binary, lines: &[DisasmLine], idx: usize) -> Option<(usize, String)> {
+    let tls_array = if binary.arch() == Arch::X86_64 {
+        "gs:[0x58]"
+    } else {
+        "fs:[0x2c]"
+    };
+    if !lines[idx].operands().contains(tls_array) {
+        return None;
+    }
+    let section_address = binary.thread_locals().section_address?;
+
+    // The load of the binary's block from the array, which is indexed by `_tls_index`.
+    let (block_idx, block) = lines
+        .iter()
+        .enumerate()
+        .skip(idx + 1)
+        .take(SEARCH_WINDOW)
+        .find(|(_, line)| line.operands().contains("*8]") || line.operands().contains("*4]"))?;
+    let block_register = destination(block.operands());
+
+    lines
+        .iter()
+        .enumerate()
+        .skip(block_idx + 1)
+        .take(SEARCH_WINDOW)
+        .find_map(|(access_idx, line)| {
+            let offset = memory_displacement(line.operands(), block_register)?;
+            let (symbol, symbol_offset) = binary.symbolicate(section_address + offset as u64)?;
+            let comment = match symbol_offset {
+                0 => format!("thread local {}", symbol.name()),
+                offset => format!("thread local {}+0x{:x}", symbol.name(), offset),
+            };
+            Some((access_idx, comment))
+        })
+}
+
+/// Returns the register that an instruction writes to, which is the first operand in Intel
+/// syntax and the last one in AT&T syntax.
+pub(super) fn destination(operands: &str) -> &str {
+    if operands.contains('%') {
+        operands.rsplit(',').next().unwrap_or("").trim()
+    } else {
+        operands.split(',').next().unwrap_or("").trim()
+    }
+}
+
+/// Returns the displacement of a memory operand with `register` as its base, e.g. `-0x10`
+/// for `[rax - 0x10]`, `-0x10(%rax)`, or `[x29, #-0x10]`.
+pub(super) fn memory_displacement(operands: &str, register: &str) -> Option<i64> {
+    if let Some(register) = register.strip_prefix('%') {
+        let base = format!("(%{}", register);
+        let end = operands.find(&base)?;
+        let start = operands[..end].rfind([' ', ',']).map_or(0, |idx| idx + 1);
+        return match &operands[start..end] {
+            "" => Some(0),
+            displacement => parse_signed(displacement),
+        };
+    }
+
+    let base = format!("[{}", register);
+    let start = operands.find(&base)? + base.len();
+    let memory = &operands[start..(start + operands[start..].find(']')?)];
+    if memory.is_empty() {
+        Some(0)
+    } else if let Some(displacement) = memory.strip_prefix(" + ") {
+        parse_signed(displacement)
+    } else if let Some(displacement) = memory.strip_prefix(" - ") {
+        parse_signed(displacement).map(|displacement| -displacement)
+    } else if let Some(displacement) = memory.strip_prefix(", #") {
+        parse_signed(displacement)
+    } else {
+        None
+    }
+}
+
+/// Parses a number like `0x10`, `-0x10`, or `16`. Hex numbers with the top bit set, such as
+/// `0xfffffffffffffff8`, are negative.
+pub(super) fn parse_signed(number: &str) -> Option<i64> {
+    let number = number.trim().trim_start_matches('$');
+    let (negative, number) = match number.strip_prefix('-') {
+        Some(number) => (true, number),
+        None => (false, number),
+    };
+    let value = match number.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok()? as i64,
+        None => number.parse().ok()?,
+    };
+    Some(if negative { -value } else { value })
+}
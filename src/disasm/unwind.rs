@@ -0,0 +1,739 @@
+//! Unwind information, which is shown as the assembler directives that would have created
+//! it. This is the call frame information in `.eh_frame` and `.debug_frame` and the unwind
+//! information of x86_64 Windows binaries in `.pdata` and `.xdata`.
+
+use super::binary::{Arch, Binary, Bits};
+use super::symbol::Symbol;
+use anyhow::Context as _;
+use gimli::{BaseAddresses, CallFrameInstruction, EndianSlice, RunTimeEndian, UnwindSection as _};
+use std::ops::Range;
+
+/// A section of a binary with unwind information.
+#[derive(Clone, Debug)]
+pub struct UnwindSection {
+    pub address: u64,
+    pub offset: usize,
+    pub size: usize,
+}
+
+/// The sections of a binary with unwind information.
+#[derive(Clone, Debug, Default)]
+pub struct UnwindSections {
+    pub eh_frame: Option<UnwindSection>,
+    pub debug_frame: Option<UnwindSection>,
+
+    /// The function table of PE files.
+    pub pdata: Option<UnwindSection>,
+
+    /// The base address that the addresses in `.pdata` and `.xdata` are relative to.
+    pub image_base: u64,
+
+    /// The address ranges of the sections and their file offsets, sorted by address, which
+    /// are used to find the unwind information and exception tables that are pointed to by
+    /// the unwind sections.
+    pub sections: Vec<(Range<u64>, usize)>,
+}
+
+/// A range of calls whose exceptions unwind to a landing pad, from the call site table
+/// in the language specific data area (LSDA) of a function.
+#[derive(Clone, Debug)]
+pub struct CallSite {
+    pub range: Range<u64>,
+    pub landing_pad: u64,
+
+    /// The index of the first action in the action table plus one, or 0 if the landing
+    /// pad only runs cleanups (e.g. drops) before resuming the unwinding.
+    pub action: u64,
+}
+
+impl CallSite {
+    /// Returns true if the landing pad only runs cleanups and doesn't catch the exception.
+    pub fn is_cleanup(&self) -> bool {
+        self.action == 0
+    }
+}
+
+/// An assembler directive that describes how to unwind the stack from an address on.
+#[derive(Clone, Debug)]
+pub struct UnwindDirective {
+    pub address: u64,
+    pub text: String,
+}
+
+/// Returns the unwind directives of a function sorted by address, or nothing if the binary
+/// doesn't have unwind information for it.
+pub fn directives_for(binary: &Binary, symbol: &Symbol) -> anyhow::Result<Vec<UnwindDirective>> {
+    let sections = binary.unwind_sections();
+    let endian = RunTimeEndian::from(binary.endian());
+
+    let mut directives = Vec::new();
+    if let Some(ref section) = sections.eh_frame {
+        let eh_frame = gimli::EhFrame::new(section_data(binary, section)?, endian);
+        let bases = BaseAddresses::default().set_eh_frame(section.address);
+        cfi_directives(binary.arch(), &eh_frame, &bases, symbol, &mut directives)
+            .context("error while reading .eh_frame")?;
+    }
+    if directives.is_empty() {
+        if let Some(ref section) = sections.debug_frame {
+            let mut debug_frame = gimli::DebugFrame::new(section_data(binary, section)?, endian);
+            if binary.bits() == Bits::Bits32 {
+                debug_frame.set_address_size(4);
+            }
+            cfi_directives(
+                binary.arch(),
+                &debug_frame,
+                &BaseAddresses::default(),
+                symbol,
+                &mut directives,
+            )
+            .context("error while reading .debug_frame")?;
+        }
+    }
+    if directives.is_empty() && binary.arch() == Arch::X86_64 {
+        seh_directives(binary, symbol, &mut directives)
+            .context("error while reading .pdata and .xdata")?;
+    }
+
+    log::trace!(
+        "found {} unwind directives for {}",
+        directives.len(),
+        symbol.name()
+    );
+    Ok(directives)
+}
+
+/// The stack usage of a function according to its call frame information.
+#[derive(Clone, Debug, Default)]
+pub struct FrameInfo {
+    /// The largest offset of the canonical frame address from the stack pointer, which is
+    /// the size of the frame including the return address on x86. This is 0 if the frame
+    /// is only described relative to a frame pointer.
+    pub cfa_offset: u64,
+
+    /// The registers that are saved in the frame, in the order that they're saved.
+    pub saved_registers: Vec<String>,
+}
+
+/// Evaluates the call frame information of a function to find the size of its frame and
+/// the registers that it saves, or returns nothing if it has no call frame information.
+pub fn frame_info_for(binary: &Binary, symbol: &Symbol) -> anyhow::Result<Option<FrameInfo>> {
+    let sections = binary.unwind_sections();
+    let endian = RunTimeEndian::from(binary.endian());
+
+    if let Some(ref section) = sections.eh_frame {
+        let eh_frame = gimli::EhFrame::new(section_data(binary, section)?, endian);
+        let bases = BaseAddresses::default().set_eh_frame(section.address);
+        let info = evaluate_frame(binary.arch(), &eh_frame, &bases, symbol)
+            .context("error while reading .eh_frame")?;
+        if info.is_some() {
+            return Ok(info);
+        }
+    }
+    if let Some(ref section) = sections.debug_frame {
+        let mut debug_frame = gimli::DebugFrame::new(section_data(binary, section)?, endian);
+        if binary.bits() == Bits::Bits32 {
+            debug_frame.set_address_size(4);
+        }
+        return evaluate_frame(
+            binary.arch(),
+            &debug_frame,
+            &BaseAddresses::default(),
+            symbol,
+        )
+        .context("error while reading .debug_frame");
+    }
+    Ok(None)
+}
+
+fn evaluate_frame<'b, S>(
+    arch: Arch,
+    section: &S,
+    bases: &BaseAddresses,
+    symbol: &Symbol,
+) -> anyhow::Result<Option<FrameInfo>>
+where
+    S: gimli::UnwindSection<Slice<'b>>,
+{
+    let fde = match section.fde_for_address(bases, symbol.address(), S::cie_from_offset) {
+        Ok(fde) => fde,
+        Err(gimli::Error::NoUnwindInfoForAddress) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let stack_pointer = match arch {
+        Arch::X86 => gimli::X86::ESP,
+        Arch::X86_64 => gimli::X86_64::RSP,
+        Arch::AArch64 => gimli::Register(31),
+        _ => gimli::Register(13),
+    };
+    // The return address is always pushed by calls on x86, so it isn't listed as saved.
+    let return_address = match arch {
+        Arch::X86 | Arch::X86_64 => Some(fde.cie().return_address_register()),
+        _ => None,
+    };
+
+    let mut info = FrameInfo::default();
+    let mut saved = Vec::new();
+    let mut context = gimli::UninitializedUnwindContext::new();
+    let mut table = fde.rows(section, bases, &mut context)?;
+    while let Some(row) = table.next_row()? {
+        if let gimli::CfaRule::RegisterAndOffset { register, offset } = *row.cfa() {
+            if register == stack_pointer && offset > 0 {
+                info.cfa_offset = info.cfa_offset.max(offset as u64);
+            }
+        }
+        for &(register, ref rule) in row.registers() {
+            if let gimli::RegisterRule::Offset(offset) = *rule {
+                if Some(register) != return_address
+                    && !saved.iter().any(|&(saved, _)| saved == register)
+                {
+                    saved.push((register, offset));
+                }
+            }
+        }
+    }
+
+    // Registers closer to the canonical frame address were saved first.
+    saved.sort_by_key(|&(_, offset)| std::cmp::Reverse(offset));
+    info.saved_registers = saved
+        .into_iter()
+        .map(|(register, _)| register_name(arch, register).to_lowercase())
+        .collect();
+    Ok(Some(info))
+}
+
+/// Returns the call sites of a function that unwind to landing pads, sorted by address.
+/// These are read from the LSDA that the function's entry in `.eh_frame` points to.
+pub fn call_sites_for(binary: &Binary, symbol: &Symbol) -> anyhow::Result<Vec<CallSite>> {
+    let sections = binary.unwind_sections();
+    let section = match sections.eh_frame {
+        Some(ref section) => section,
+        None => return Ok(Vec::new()),
+    };
+    let endian = RunTimeEndian::from(binary.endian());
+    let eh_frame = gimli::EhFrame::new(section_data(binary, section)?, endian);
+    let bases = BaseAddresses::default().set_eh_frame(section.address);
+    let fde =
+        match eh_frame.fde_for_address(&bases, symbol.address(), gimli::EhFrame::cie_from_offset) {
+            Ok(fde) => fde,
+            Err(gimli::Error::NoUnwindInfoForAddress) => return Ok(Vec::new()),
+            Err(err) => return Err(err).context("error while reading .eh_frame"),
+        };
+    let lsda_address = match fde.lsda() {
+        Some(gimli::Pointer::Direct(address)) => address,
+        Some(gimli::Pointer::Indirect(_)) => {
+            log::debug!("skipping indirect LSDA pointer of {}", symbol.name());
+            return Ok(Vec::new());
+        }
+        None => return Ok(Vec::new()),
+    };
+    let lsda = data_at(binary, lsda_address).context("the LSDA is outside of the binary")?;
+    let address_size = if binary.bits() == Bits::Bits32 { 4 } else { 8 };
+
+    let call_sites = read_call_sites(
+        EndianSlice::new(lsda, endian),
+        lsda_address,
+        fde.initial_address(),
+        address_size,
+    )
+    .context("error while reading the LSDA")?;
+    log::trace!(
+        "found {} call sites with landing pads in {}",
+        call_sites.len(),
+        symbol.name()
+    );
+    Ok(call_sites)
+}
+
+/// Reads the call site table of an LSDA in the format of `.gcc_except_table`. Adjacent
+/// call sites with the same landing pad and action are merged.
+fn read_call_sites(
+    lsda: Slice<'_>,
+    lsda_address: u64,
+    function_start: u64,
+    address_size: u8,
+) -> gimli::Result<Vec<CallSite>> {
+    use gimli::Reader as _;
+
+    const DW_EH_PE_OMIT: u8 = gimli::DW_EH_PE_omit.0;
+
+    let mut reader = lsda;
+    let position = |reader: &Slice<'_>| lsda_address + reader.offset_from(lsda) as u64;
+
+    let lp_start_encoding = reader.read_u8()?;
+    let lp_start = if lp_start_encoding == DW_EH_PE_OMIT {
+        function_start
+    } else {
+        let address = position(&reader);
+        read_encoded(&mut reader, lp_start_encoding, address, address_size)?
+    };
+
+    let type_table_encoding = reader.read_u8()?;
+    if type_table_encoding != DW_EH_PE_OMIT {
+        reader.read_uleb128()?;
+    }
+
+    let call_site_encoding = reader.read_u8()?;
+    let table_len = reader.read_uleb128()?;
+    let mut table = reader.split(table_len as usize)?;
+
+    let mut call_sites: Vec<CallSite> = Vec::new();
+    while !table.is_empty() {
+        let address = position(&table);
+        let start = read_encoded(&mut table, call_site_encoding, address, address_size)?;
+        let address = position(&table);
+        let len = read_encoded(&mut table, call_site_encoding, address, address_size)?;
+        let address = position(&table);
+        let landing_pad = read_encoded(&mut table, call_site_encoding, address, address_size)?;
+        let action = table.read_uleb128()?;
+
+        // Exceptions in calls without a landing pad just continue unwinding.
+        if landing_pad == 0 {
+            continue;
+        }
+
+        let range = (function_start + start)..(function_start + start + len);
+        let landing_pad = lp_start + landing_pad;
+        match call_sites.last_mut() {
+            Some(last)
+                if last.range.end == range.start
+                    && last.landing_pad == landing_pad
+                    && last.action == action =>
+            {
+                last.range.end = range.end;
+            }
+            _ => call_sites.push(CallSite {
+                range,
+                landing_pad,
+                action,
+            }),
+        }
+    }
+    call_sites.sort_by_key(|call_site| call_site.range.start);
+    Ok(call_sites)
+}
+
+/// Reads a pointer in one of the `DW_EH_PE_*` encodings. `position` is the address of the
+/// pointer, which pc-relative pointers are relative to.
+fn read_encoded(
+    reader: &mut Slice<'_>,
+    encoding: u8,
+    position: u64,
+    address_size: u8,
+) -> gimli::Result<u64> {
+    use gimli::Reader as _;
+
+    let value = match gimli::DwEhPe(encoding & 0x0f) {
+        gimli::DW_EH_PE_absptr => reader.read_address(address_size)?,
+        gimli::DW_EH_PE_uleb128 => reader.read_uleb128()?,
+        gimli::DW_EH_PE_udata2 => u64::from(reader.read_u16()?),
+        gimli::DW_EH_PE_udata4 => u64::from(reader.read_u32()?),
+        gimli::DW_EH_PE_udata8 | gimli::DW_EH_PE_sdata8 => reader.read_u64()?,
+        gimli::DW_EH_PE_sleb128 => reader.read_sleb128()? as u64,
+        gimli::DW_EH_PE_sdata2 => i64::from(reader.read_i16()?) as u64,
+        gimli::DW_EH_PE_sdata4 => i64::from(reader.read_i32()?) as u64,
+        _ => return Err(gimli::Error::UnknownPointerEncoding),
+    };
+    if encoding & 0x70 == gimli::DW_EH_PE_pcrel.0 {
+        Ok(position.wrapping_add(value))
+    } else {
+        Ok(value)
+    }
+}
+
+/// Returns the data of the binary from an address to the end of the section that
+/// contains it.
+fn data_at(binary: &Binary, address: u64) -> Option<&[u8]> {
+    let (range, offset) = binary
+        .unwind_sections()
+        .sections
+        .iter()
+        .find(|(range, _)| range.contains(&address))?;
+    let start = offset + (address - range.start) as usize;
+    let end = offset + (range.end - range.start) as usize;
+    binary.data().get(start..end)
+}
+
+/// Returns the address ranges of the functions that have call frame information or an
+/// entry in the function table of PE files, which are used to find the functions of
+/// binaries without symbols.
+pub fn function_ranges(binary: &Binary) -> anyhow::Result<Vec<Range<u64>>> {
+    let sections = binary.unwind_sections();
+    let endian = RunTimeEndian::from(binary.endian());
+    let mut ranges = Vec::new();
+
+    if let Some(ref section) = sections.eh_frame {
+        let eh_frame = gimli::EhFrame::new(section_data(binary, section)?, endian);
+        let bases = BaseAddresses::default().set_eh_frame(section.address);
+        fde_ranges(&eh_frame, &bases, &mut ranges).context("error while reading .eh_frame")?;
+    }
+    if let Some(ref section) = sections.debug_frame {
+        let mut debug_frame = gimli::DebugFrame::new(section_data(binary, section)?, endian);
+        if binary.bits() == Bits::Bits32 {
+            debug_frame.set_address_size(4);
+        }
+        fde_ranges(&debug_frame, &BaseAddresses::default(), &mut ranges)
+            .context("error while reading .debug_frame")?;
+    }
+    if let Some(ref pdata) = sections.pdata {
+        for entry in section_data(binary, pdata)?.chunks_exact(12) {
+            let begin = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+            let end = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
+            if begin < end {
+                ranges.push(
+                    (sections.image_base + u64::from(begin))
+                        ..(sections.image_base + u64::from(end)),
+                );
+            }
+        }
+    }
+    Ok(ranges)
+}
+
+/// Adds the address range of every frame description entry in a section.
+fn fde_ranges<'b, S>(
+    section: &S,
+    bases: &BaseAddresses,
+    ranges: &mut Vec<Range<u64>>,
+) -> anyhow::Result<()>
+where
+    S: gimli::UnwindSection<Slice<'b>>,
+{
+    let mut entries = section.entries(bases);
+    while let Some(entry) = entries.next()? {
+        if let gimli::CieOrFde::Fde(partial) = entry {
+            let fde = partial.parse(S::cie_from_offset)?;
+            if fde.len() > 0 {
+                ranges.push(fde.initial_address()..(fde.initial_address() + fde.len()));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn section_data<'b>(binary: &'b Binary, section: &UnwindSection) -> anyhow::Result<&'b [u8]> {
+    binary
+        .data()
+        .get(section.offset..(section.offset + section.size))
+        .context("unwind section is outside of the binary")
+}
+
+type Slice<'b> = EndianSlice<'b, RunTimeEndian>;
+
+/// Finds the frame description entry of a symbol and writes its call frame instructions,
+/// including the initial instructions of its CIE, as `.cfi_*` directives.
+fn cfi_directives<'b, S>(
+    arch: Arch,
+    section: &S,
+    bases: &BaseAddresses,
+    symbol: &Symbol,
+    directives: &mut Vec<UnwindDirective>,
+) -> anyhow::Result<()>
+where
+    S: gimli::UnwindSection<Slice<'b>>,
+{
+    let fde = match section.fde_for_address(bases, symbol.address(), S::cie_from_offset) {
+        Ok(fde) => fde,
+        Err(gimli::Error::NoUnwindInfoForAddress) => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    let cie = fde.cie();
+    let code_alignment = cie.code_alignment_factor();
+    let data_alignment = cie.data_alignment_factor();
+
+    let mut address = fde.initial_address();
+    directives.push(UnwindDirective {
+        address,
+        text: ".cfi_startproc".into(),
+    });
+
+    let mut instructions = cie.instructions(section, bases);
+    while let Some(instruction) = instructions.next()? {
+        if let Some(text) = cfi_directive(arch, &instruction, data_alignment) {
+            directives.push(UnwindDirective { address, text });
+        }
+    }
+
+    let mut instructions = fde.instructions(section, bases);
+    while let Some(instruction) = instructions.next()? {
+        match instruction {
+            CallFrameInstruction::AdvanceLoc { delta } => {
+                address += u64::from(delta) * code_alignment;
+            }
+            CallFrameInstruction::SetLoc {
+                address: new_address,
+            } => address = new_address,
+            ref instruction => {
+                if let Some(text) = cfi_directive(arch, instruction, data_alignment) {
+                    directives.push(UnwindDirective { address, text });
+                }
+            }
+        }
+    }
+
+    directives.push(UnwindDirective {
+        address: fde.initial_address() + fde.len(),
+        text: ".cfi_endproc".into(),
+    });
+    Ok(())
+}
+
+/// Formats a call frame instruction as the directive that creates it.
+fn cfi_directive(
+    arch: Arch,
+    instruction: &CallFrameInstruction<Slice<'_>>,
+    data_alignment: i64,
+) -> Option<String> {
+    use CallFrameInstruction::*;
+
+    let reg = |register: gimli::Register| register_name(arch, register);
+    let text = match *instruction {
+        DefCfa { register, offset } => format!(".cfi_def_cfa {}, {}", reg(register), offset),
+        DefCfaSf {
+            register,
+            factored_offset,
+        } => format!(
+            ".cfi_def_cfa {}, {}",
+            reg(register),
+            factored_offset * data_alignment
+        ),
+        DefCfaRegister { register } => format!(".cfi_def_cfa_register {}", reg(register)),
+        DefCfaOffset { offset } => format!(".cfi_def_cfa_offset {}", offset),
+        DefCfaOffsetSf { factored_offset } => {
+            format!(".cfi_def_cfa_offset {}", factored_offset * data_alignment)
+        }
+        Undefined { register } => format!(".cfi_undefined {}", reg(register)),
+        SameValue { register } => format!(".cfi_same_value {}", reg(register)),
+        Offset {
+            register,
+            factored_offset,
+        } => format!(
+            ".cfi_offset {}, {}",
+            reg(register),
+            factored_offset as i64 * data_alignment
+        ),
+        OffsetExtendedSf {
+            register,
+            factored_offset,
+        } => format!(
+            ".cfi_offset {}, {}",
+            reg(register),
+            factored_offset * data_alignment
+        ),
+        ValOffset {
+            register,
+            factored_offset,
+        } => format!(
+            ".cfi_val_offset {}, {}",
+            reg(register),
+            factored_offset as i64 * data_alignment
+        ),
+        ValOffsetSf {
+            register,
+            factored_offset,
+        } => format!(
+            ".cfi_val_offset {}, {}",
+            reg(register),
+            factored_offset * data_alignment
+        ),
+        Register {
+            dest_register,
+            src_register,
+        } => format!(
+            ".cfi_register {}, {}",
+            reg(dest_register),
+            reg(src_register)
+        ),
+        Restore { register } => format!(".cfi_restore {}", reg(register)),
+        RememberState => ".cfi_remember_state".into(),
+        RestoreState => ".cfi_restore_state".into(),
+        ArgsSize { size } => format!(".cfi_GNU_args_size {}", size),
+        DefCfaExpression { .. } => ".cfi_escape # DW_CFA_def_cfa_expression".into(),
+        Expression { register, .. } => {
+            format!(".cfi_escape # DW_CFA_expression {}", reg(register))
+        }
+        ValExpression { register, .. } => {
+            format!(".cfi_escape # DW_CFA_val_expression {}", reg(register))
+        }
+        _ => return None,
+    };
+    Some(text)
+}
+
+/// Returns the name of a DWARF register number.
+pub(super) fn register_name(arch: Arch, register: gimli::Register) -> String {
+    let name = match arch {
+        Arch::X86 => gimli::X86::register_name(register),
+        Arch::X86_64 => gimli::X86_64::register_name(register),
+        Arch::Arm | Arch::Thumb => gimli::Arm::register_name(register),
+        Arch::AArch64 => return aarch64_register_name(register),
+        _ => None,
+    };
+    match name {
+        Some(name) => name.to_string(),
+        None => register.0.to_string(),
+    }
+}
+
+/// Returns the name of an AArch64 DWARF register number, which gimli doesn't have names for.
+fn aarch64_register_name(register: gimli::Register) -> String {
+    match register.0 {
+        0..=30 => format!("X{}", register.0),
+        31 => "SP".to_string(),
+        64..=95 => format!("V{}", register.0 - 64),
+        _ => register.0.to_string(),
+    }
+}
+
+/// The general purpose registers of x86_64 by their numbers in Windows unwind codes.
+const SEH_REGISTERS: [&str; 16] = [
+    "rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi", "r8", "r9", "r10", "r11", "r12", "r13",
+    "r14", "r15",
+];
+
+/// The flag of `UNWIND_INFO` for functions whose unwind information continues in the entry
+/// of another function.
+const UNW_FLAG_CHAININFO: u8 = 0x4;
+
+/// Finds the `RUNTIME_FUNCTION` entry of a symbol in `.pdata` and writes the unwind codes
+/// of its `UNWIND_INFO` as `.seh_*` directives.
+fn seh_directives(
+    binary: &Binary,
+    symbol: &Symbol,
+    directives: &mut Vec<UnwindDirective>,
+) -> anyhow::Result<()> {
+    let sections = binary.unwind_sections();
+    let pdata = match sections.pdata {
+        Some(ref pdata) => section_data(binary, pdata)?,
+        None => return Ok(()),
+    };
+    let read_u32 = |data: &[u8], offset: usize| -> Option<u32> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(data.get(offset..(offset + 4))?);
+        Some(u32::from_le_bytes(bytes))
+    };
+
+    // The entries are sorted by their start addresses.
+    let rva = symbol.address().wrapping_sub(sections.image_base);
+    let entry_count = pdata.len() / 12;
+    let entry = {
+        let (mut low, mut high) = (0, entry_count);
+        let mut found = None;
+        while low < high {
+            let mid = (low + high) / 2;
+            let begin = u64::from(read_u32(pdata, mid * 12).unwrap_or(0));
+            let end = u64::from(read_u32(pdata, mid * 12 + 4).unwrap_or(0));
+            if rva < begin {
+                high = mid;
+            } else if rva >= end {
+                low = mid + 1;
+            } else {
+                found = Some(mid);
+                break;
+            }
+        }
+        match found {
+            Some(entry) => entry,
+            None => return Ok(()),
+        }
+    };
+
+    let begin = sections.image_base + u64::from(read_u32(pdata, entry * 12).unwrap_or(0));
+    let end = sections.image_base + u64::from(read_u32(pdata, entry * 12 + 4).unwrap_or(0));
+    let info_address =
+        sections.image_base + u64::from(read_u32(pdata, entry * 12 + 8).unwrap_or(0));
+    let info = data_at(binary, info_address).context("unwind info is outside of the binary")?;
+    let header = info
+        .get(..4)
+        .context("unwind info is outside of the binary")?;
+
+    let flags = header[0] >> 3;
+    let prolog_size = u64::from(header[1]);
+    let code_count = usize::from(header[2]);
+    let frame_register = SEH_REGISTERS[usize::from(header[3] & 0xf)];
+    let frame_offset = u32::from(header[3] >> 4) * 16;
+    let codes = info
+        .get(4..(4 + code_count * 2))
+        .context("unwind codes are outside of the binary")?;
+    let slot = |idx: usize| -> u32 {
+        codes
+            .get((idx * 2)..(idx * 2 + 2))
+            .map_or(0, |slot| u32::from(u16::from_le_bytes([slot[0], slot[1]])))
+    };
+    let large_slot = |idx: usize| -> u32 { slot(idx) | (slot(idx + 1) << 16) };
+
+    directives.push(UnwindDirective {
+        address: begin,
+        text: ".seh_proc".into(),
+    });
+
+    // The codes are in the reverse order of the prolog.
+    let mut prolog = Vec::new();
+    let mut idx = 0;
+    while idx < code_count {
+        let offset = u64::from(codes[idx * 2]);
+        let op = codes[idx * 2 + 1] & 0xf;
+        let op_info = usize::from(codes[idx * 2 + 1] >> 4);
+        let (text, slots) = match op {
+            0 => (format!(".seh_pushreg {}", SEH_REGISTERS[op_info]), 1),
+            1 if op_info == 0 => (format!(".seh_stackalloc {}", slot(idx + 1) * 8), 2),
+            1 => (format!(".seh_stackalloc {}", large_slot(idx + 1)), 3),
+            2 => (format!(".seh_stackalloc {}", op_info * 8 + 8), 1),
+            3 => (
+                format!(".seh_setframe {}, {}", frame_register, frame_offset),
+                1,
+            ),
+            4 => (
+                format!(
+                    ".seh_savereg {}, {}",
+                    SEH_REGISTERS[op_info],
+                    slot(idx + 1) * 8
+                ),
+                2,
+            ),
+            5 => (
+                format!(
+                    ".seh_savereg {}, {}",
+                    SEH_REGISTERS[op_info],
+                    large_slot(idx + 1)
+                ),
+                3,
+            ),
+            8 => (
+                format!(".seh_savexmm xmm{}, {}", op_info, slot(idx + 1) * 16),
+                2,
+            ),
+            9 => (
+                format!(".seh_savexmm xmm{}, {}", op_info, large_slot(idx + 1)),
+                3,
+            ),
+            10 if op_info == 1 => (".seh_pushframe @code".to_string(), 1),
+            10 => (".seh_pushframe".to_string(), 1),
+            // Epilog codes of version 2 unwind info and unknown codes.
+            _ => (format!("# unwind code {} ({})", op, op_info), 1),
+        };
+        prolog.push(UnwindDirective {
+            address: begin + offset,
+            text,
+        });
+        idx += slots;
+    }
+    prolog.reverse();
+    directives.extend(prolog);
+
+    directives.push(UnwindDirective {
+        address: begin + prolog_size,
+        text: ".seh_endprologue".into(),
+    });
+    if flags & UNW_FLAG_CHAININFO != 0 {
+        directives.push(UnwindDirective {
+            address: begin + prolog_size,
+            text: "# continued in the unwind info of the parent function".into(),
+        });
+    }
+    directives.push(UnwindDirective {
+        address: end,
+        text: ".seh_endproc".into(),
+    });
+    directives.sort_by_key(|directive| directive.address);
+    Ok(())
+}
@@ -0,0 +1,118 @@
+//! Comments the instructions that access local variables with the names of the variables,
+//! using the locations of the variables from the DWARF debug information.
+
+use super::binary::Binary;
+use super::symbol::Symbol;
+use super::tls::{destination, memory_displacement};
+use super::unwind::register_name;
+use super::{DisasmLine, Disassembly};
+use std::ops::Range;
+
+/// Where a local variable or parameter is stored over a range of addresses.
+#[derive(Clone, Debug)]
+pub struct VariableLocation {
+    /// The name of the variable.
+    pub name: Box<str>,
+
+    /// The addresses of the instructions where the variable is in this location.
+    pub range: Range<u64>,
+
+    /// The register or memory that the variable is stored in.
+    pub slot: VariableSlot,
+}
+
+/// The register or memory that a variable is stored in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VariableSlot {
+    /// The variable is in a register.
+    Register(gimli::Register),
+
+    /// The variable is in memory at an offset from the address in a register, which is
+    /// usually a slot in the stack frame.
+    Memory { base: gimli::Register, offset: i64 },
+}
+
+/// Comments the instructions that store variables to their stack slots (`spill: counter`)
+/// or load them from there (`counter`), and the instructions where a variable starts to be
+/// kept in a register (`counter in rbx`). Instructions that already have a comment are left
+/// alone.
+pub fn annotate(
+    binary: &Binary,
+    symbol: &Symbol,
+    disassembly: &mut Disassembly,
+) -> anyhow::Result<()> {
+    let locations = binary.variable_locations(symbol)?;
+    if locations.is_empty() {
+        return Ok(());
+    }
+    log::trace!(
+        "found {} variable locations for {}",
+        locations.len(),
+        symbol.name()
+    );
+
+    let arch = binary.arch();
+    let lines = &mut disassembly.lines;
+    let mut comments: Vec<(usize, String)> = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let live = locations
+            .iter()
+            .take_while(|location| location.range.start <= line.address())
+            .filter(|location| location.range.contains(&line.address()));
+        for location in live {
+            let comment = match location.slot {
+                VariableSlot::Memory { base, offset } => {
+                    let base = register_name(arch, base).to_lowercase();
+                    match access(line, &base, offset) {
+                        Some(Access::Store) => format!("spill: {}", location.name),
+                        Some(Access::Load) => location.name.to_string(),
+                        None => continue,
+                    }
+                }
+                VariableSlot::Register(register) if location.range.start == line.address() => {
+                    let register = register_name(arch, register).to_lowercase();
+                    format!("{} in {}", location.name, register)
+                }
+                VariableSlot::Register(_) => continue,
+            };
+            comments.push((idx, comment));
+            break;
+        }
+    }
+
+    for (idx, comment) in comments {
+        if lines[idx].comments.is_none() {
+            lines[idx].comments = Some(comment.into());
+        }
+    }
+    Ok(())
+}
+
+/// Whether an instruction reads or writes memory.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Access {
+    Load,
+    Store,
+}
+
+/// Finds whether an instruction accesses the memory at `offset` from the `base` register.
+/// The memory operand is the destination of stores in Intel and AT&T syntax, and AArch64
+/// stores are the `st*` instructions.
+fn access(line: &DisasmLine, base: &str, offset: i64) -> Option<Access> {
+    let operands = line.operands();
+    let base = if operands.contains('%') {
+        format!("%{}", base)
+    } else {
+        base.to_string()
+    };
+    if memory_displacement(operands, &base)? != offset {
+        return None;
+    }
+
+    let dest = destination(operands);
+    if line.mnemonic().starts_with("st") || dest.contains('[') || dest.contains('(') {
+        Some(Access::Store)
+    } else {
+        Some(Access::Load)
+    }
+}
@@ -0,0 +1,180 @@
+//! Finds out whether functions and their loops were vectorized from the SIMD instruction
+//! set extensions of their instructions.
+
+use super::binary::Arch;
+use super::{DisasmLine, Disassembly, Jump};
+use std::fmt;
+use std::ops::Range;
+
+/// The SIMD instruction set extension of an instruction. The x86 classes are ordered from
+/// the oldest to the newest.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SimdClass {
+    Mmx,
+    /// SSE up to SSE4.2, including SSSE3 and SSE4a.
+    Sse,
+    /// AVX and the extensions that came with it, such as FMA and F16C.
+    Avx,
+    Avx2,
+    Avx512,
+    /// AArch64 Advanced SIMD.
+    Neon,
+    /// AArch64 Scalable Vector Extension.
+    Sve,
+}
+
+impl fmt::Display for SimdClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SimdClass::Mmx => "MMX",
+            SimdClass::Sse => "SSE",
+            SimdClass::Avx => "AVX",
+            SimdClass::Avx2 => "AVX2",
+            SimdClass::Avx512 => "AVX-512",
+            SimdClass::Neon => "NEON",
+            SimdClass::Sve => "SVE",
+        })
+    }
+}
+
+impl SimdClass {
+    /// Finds the class of an instruction from the registers in its operands, for the
+    /// disassemblers and architectures that don't say which extension an instruction
+    /// belongs to. AVX and AVX2 can't be told apart this way.
+    pub fn from_operands(arch: Arch, operands: &str) -> Option<SimdClass> {
+        let registers = operands
+            .split(|c: char| !c.is_ascii_alphanumeric() && c != '.')
+            .filter(|token| !token.is_empty());
+        let mut class = None;
+        for register in registers {
+            let mut chars = register.chars();
+            let found = match arch {
+                Arch::X86 | Arch::X86_64 => {
+                    if register.starts_with("zmm") {
+                        Some(SimdClass::Avx512)
+                    } else if register.starts_with("ymm") {
+                        Some(SimdClass::Avx)
+                    } else if register.starts_with("xmm") {
+                        Some(SimdClass::Sse)
+                    } else if register.starts_with("mm") && register.len() == 3 {
+                        Some(SimdClass::Mmx)
+                    } else {
+                        None
+                    }
+                }
+                Arch::AArch64 => match (chars.next(), chars.next()) {
+                    (Some('z'), Some(c)) | (Some('p'), Some(c))
+                        if c.is_ascii_digit() && register.contains('.') =>
+                    {
+                        Some(SimdClass::Sve)
+                    }
+                    (Some('v'), Some(c)) | (Some('q'), Some(c)) if c.is_ascii_digit() => {
+                        Some(SimdClass::Neon)
+                    }
+                    _ => None,
+                },
+                _ => None,
+            };
+            class = class.max(found);
+        }
+        class
+    }
+}
+
+/// How many instructions of a function or loop use SIMD registers.
+#[derive(Clone, Debug, Default)]
+pub struct SimdUsage {
+    pub instructions: usize,
+
+    /// The instructions that operate on several elements at once. Scalar floating-point
+    /// instructions on x86 also use the SIMD registers, but aren't vectorized.
+    pub vector_instructions: usize,
+
+    /// The newest extension that the vector instructions use.
+    pub class: Option<SimdClass>,
+}
+
+impl SimdUsage {
+    fn add(&mut self, arch: Arch, line: &DisasmLine) {
+        if line.mnemonic().starts_with('.') {
+            return;
+        }
+        self.instructions += 1;
+        if let Some(class) = line.simd_class() {
+            if is_vector(arch, line.mnemonic()) {
+                self.vector_instructions += 1;
+                self.class = self.class.max(Some(class));
+            }
+        }
+    }
+
+    pub fn is_vectorized(&self) -> bool {
+        self.vector_instructions > 0
+    }
+}
+
+impl fmt::Display for SimdUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.class {
+            Some(class) => write!(
+                f,
+                "vectorized with {} ({} of {} instructions)",
+                class, self.vector_instructions, self.instructions
+            ),
+            None => write!(f, "not vectorized ({} instructions)", self.instructions),
+        }
+    }
+}
+
+/// A loop of a function, from the target of a backward jump to the jump.
+#[derive(Clone, Debug)]
+pub struct LoopUsage {
+    /// The addresses of the instructions of the loop.
+    pub range: Range<u64>,
+    pub usage: SimdUsage,
+}
+
+/// Whether a function and each of its loops were vectorized.
+#[derive(Clone, Debug)]
+pub struct VectorizationReport {
+    pub function: SimdUsage,
+    pub loops: Vec<LoopUsage>,
+}
+
+/// Counts the vector instructions of a function and of each of its loops.
+pub fn analyze(arch: Arch, disassembly: &Disassembly) -> VectorizationReport {
+    let lines = disassembly.lines();
+    let mut function = SimdUsage::default();
+    for line in lines {
+        function.add(arch, line);
+    }
+
+    let mut loops = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let start = match line.jump() {
+            Jump::Internal(target) if target <= idx => target,
+            _ => continue,
+        };
+        let mut usage = SimdUsage::default();
+        for line in &lines[start..=idx] {
+            usage.add(arch, line);
+        }
+        loops.push(LoopUsage {
+            range: lines[start].address()..(line.address() + line.bytes().len() as u64),
+            usage,
+        });
+    }
+    VectorizationReport { function, loops }
+}
+
+/// Whether a SIMD instruction operates on several elements, which leaves out the scalar
+/// floating-point instructions of SSE and AVX, e.g. `addsd` and `vmulss`.
+fn is_vector(arch: Arch, mnemonic: &str) -> bool {
+    match arch {
+        Arch::X86 | Arch::X86_64 => {
+            let packed_integer = mnemonic.starts_with('p') || mnemonic.starts_with("vp");
+            packed_integer || !(mnemonic.ends_with("ss") || mnemonic.ends_with("sd"))
+        }
+        _ => true,
+    }
+}
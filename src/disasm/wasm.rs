@@ -0,0 +1,687 @@
+//! A disassembler for WebAssembly bytecode, which Capstone does not support.
+
+use super::binary::{Binary, WasmFunction};
+use super::symbol::Symbol;
+use super::{DisasmLine, Disassembly, Flow, Jump};
+use std::borrow::Cow;
+use std::fmt::Write as _;
+
+/// Reads the primitive values that WebAssembly modules are encoded with.
+pub(crate) struct Reader<'d> {
+    data: &'d [u8],
+    pos: usize,
+}
+
+impl<'d> Reader<'d> {
+    pub fn new(data: &'d [u8]) -> Reader<'d> {
+        Reader { data, pos: 0 }
+    }
+
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    pub fn u8(&mut self) -> Option<u8> {
+        let byte = *self.data.get(self.pos)?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Option<&'d [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    pub fn uleb(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                value |= u64::from(byte & 0x7f) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+    }
+
+    pub fn uleb32(&mut self) -> Option<u32> {
+        self.uleb().map(|value| value as u32)
+    }
+
+    pub fn sleb(&mut self) -> Option<i64> {
+        let mut value = 0i64;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                value |= i64::from(byte & 0x7f) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    value |= -1 << shift;
+                }
+                return Some(value);
+            }
+        }
+    }
+
+    /// Reads a length prefixed UTF-8 string.
+    pub fn name(&mut self) -> Option<&'d str> {
+        let len = self.uleb()? as usize;
+        std::str::from_utf8(self.bytes(len)?).ok()
+    }
+}
+
+/// Skips the local variable declarations at the start of a function body.
+pub(crate) fn skip_locals(reader: &mut Reader) -> Option<()> {
+    let count = reader.uleb()?;
+    for _ in 0..count {
+        reader.uleb()?;
+        reader.u8()?;
+    }
+    Some(())
+}
+
+/// What a decoded instruction does to the structured control flow of the function.
+enum Control {
+    None,
+    Block,
+    Loop,
+    If,
+    Else,
+    End,
+    /// A branch to the label with the given relative depth.
+    Branch(u32),
+    Call(u32),
+}
+
+struct Insn {
+    mnemonic: Cow<'static, str>,
+    operands: String,
+    flow: Flow,
+    control: Control,
+}
+
+impl Insn {
+    fn new(mnemonic: &'static str) -> Insn {
+        Insn {
+            mnemonic: Cow::Borrowed(mnemonic),
+            operands: String::new(),
+            flow: Flow::Next,
+            control: Control::None,
+        }
+    }
+
+    fn operands(mut self, operands: String) -> Insn {
+        self.operands = operands;
+        self
+    }
+
+    fn flow(mut self, flow: Flow) -> Insn {
+        self.flow = flow;
+        self
+    }
+
+    fn control(mut self, control: Control) -> Insn {
+        self.control = control;
+        self
+    }
+}
+
+/// A block, loop or if that was opened by an instruction.
+struct Block {
+    is_loop: bool,
+    /// Index of the line that opened the block.
+    start: usize,
+    /// Index of the line for the `else` instruction of an `if`.
+    else_line: Option<usize>,
+    /// Index of the line for the `end` instruction that closes the block.
+    end: Option<usize>,
+}
+
+impl Block {
+    /// The line that is executed next when branching to this block's label.
+    fn branch_target(&self) -> Option<usize> {
+        if self.is_loop {
+            Some(self.start)
+        } else {
+            self.end
+        }
+    }
+}
+
+pub(super) fn disasm_symbol_lines(
+    binary: &Binary,
+    symbol: &Symbol,
+    disassembly: &mut Disassembly,
+) -> anyhow::Result<()> {
+    let code = &binary.data()[symbol.offset()..symbol.end()];
+    let mut reader = Reader::new(code);
+
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut open_blocks: Vec<usize> = Vec::new();
+    // The lines that branch and the index of the block that they branch to.
+    let mut branches: Vec<(usize, usize)> = Vec::new();
+
+    while !reader.is_empty() {
+        let start = reader.pos();
+        let line_idx = disassembly.lines.len();
+        let address = symbol.address() + start as u64;
+
+        let mut insn = match decode(&mut reader) {
+            Some(insn) => insn,
+            None => {
+                // Either the opcode is unknown or the instruction is truncated, so the rest of
+                // the function can't be decoded.
+                let insn = Insn::new("(bad)").flow(Flow::Halt);
+                disassembly.push_line(disasm_line(address, insn, &code[start..]));
+                break;
+            }
+        };
+
+        let mut jump = Jump::None;
+        let mut comments = None;
+        match insn.control {
+            Control::None => {}
+            Control::Block | Control::Loop | Control::If => {
+                open_blocks.push(blocks.len());
+                blocks.push(Block {
+                    is_loop: matches!(insn.control, Control::Loop),
+                    start: line_idx,
+                    else_line: None,
+                    end: None,
+                });
+                if let Control::If = insn.control {
+                    branches.push((line_idx, blocks.len() - 1));
+                }
+            }
+            Control::Else => {
+                if let Some(&block) = open_blocks.last() {
+                    blocks[block].else_line = Some(line_idx);
+                    branches.push((line_idx, block));
+                }
+            }
+            Control::End => match open_blocks.pop() {
+                Some(block) => blocks[block].end = Some(line_idx),
+                // The last `end` closes the function body.
+                None => insn.flow = Flow::Return,
+            },
+            Control::Branch(depth) => {
+                // Branching to the function's label returns from the function.
+                if let Some(idx) = open_blocks.len().checked_sub(depth as usize + 1) {
+                    branches.push((line_idx, open_blocks[idx]));
+                }
+            }
+            Control::Call(function) => match binary.wasm_function(function) {
+                Some(WasmFunction::Defined(addr)) => jump = Jump::External(addr),
+                Some(WasmFunction::Imported(name)) => {
+                    comments = Some(format!("-> {}", name).into())
+                }
+                None => {}
+            },
+        }
+
+        let mut line = disasm_line(address, insn, &code[start..reader.pos()]);
+        line.jump = jump;
+        line.comments = comments;
+        disassembly.push_line(line);
+    }
+
+    for (line_idx, block_idx) in branches {
+        let block = &blocks[block_idx];
+        let target = if line_idx == block.start {
+            // The condition of an `if` being false jumps past its `else`.
+            block.else_line.map(|line| line + 1).or(block.end)
+        } else {
+            block.branch_target()
+        };
+
+        if let Some(target) = target.and_then(|line| disassembly.lines.get(line)) {
+            let target = target.address();
+            disassembly.lines[line_idx].jump = Jump::External(target);
+        }
+    }
+    Ok(())
+}
+
+fn disasm_line(address: u64, insn: Insn, bytes: &[u8]) -> DisasmLine {
+    DisasmLine {
+        address,
+        mnemonic: insn.mnemonic.into(),
+        operands: insn.operands.into(),
+        comments: None,
+        bytes: bytes.to_vec().into_boxed_slice(),
+        source_lines: None,
+        source_locations: None,
+        inlined_calls: None,
+        jump: Jump::None,
+        flow: insn.flow,
+        memory_reference: None,
+        simd: None,
+        is_symbolicated_jump: false,
+    }
+}
+
+fn decode(reader: &mut Reader) -> Option<Insn> {
+    let opcode = reader.u8()?;
+    let insn = match opcode {
+        0x00 => Insn::new("unreachable").flow(Flow::Halt),
+        0x01 => Insn::new("nop"),
+        0x02 => Insn::new("block")
+            .operands(block_type(reader)?)
+            .control(Control::Block),
+        0x03 => Insn::new("loop")
+            .operands(block_type(reader)?)
+            .control(Control::Loop),
+        0x04 => Insn::new("if")
+            .operands(block_type(reader)?)
+            .flow(Flow::ConditionalJump)
+            .control(Control::If),
+        0x05 => Insn::new("else").flow(Flow::Jump).control(Control::Else),
+        0x0b => Insn::new("end").control(Control::End),
+        0x0c => {
+            let depth = reader.uleb32()?;
+            Insn::new("br")
+                .operands(depth.to_string())
+                .flow(Flow::Jump)
+                .control(Control::Branch(depth))
+        }
+        0x0d => {
+            let depth = reader.uleb32()?;
+            Insn::new("br_if")
+                .operands(depth.to_string())
+                .flow(Flow::ConditionalJump)
+                .control(Control::Branch(depth))
+        }
+        0x0e => {
+            let mut operands = String::new();
+            let count = reader.uleb()?;
+            for _ in 0..=count {
+                if !operands.is_empty() {
+                    operands.push(' ');
+                }
+                write!(operands, "{}", reader.uleb32()?).ok()?;
+            }
+            Insn::new("br_table").operands(operands).flow(Flow::Jump)
+        }
+        0x0f => Insn::new("return").flow(Flow::Return),
+        0x10 => {
+            let function = reader.uleb32()?;
+            Insn::new("call")
+                .operands(function.to_string())
+                .flow(Flow::Call)
+                .control(Control::Call(function))
+        }
+        0x11 => {
+            let ty = reader.uleb32()?;
+            let table = reader.uleb32()?;
+            Insn::new("call_indirect")
+                .operands(format!("{} (type {})", table, ty))
+                .flow(Flow::Call)
+        }
+        0x12 => {
+            let function = reader.uleb32()?;
+            Insn::new("return_call")
+                .operands(function.to_string())
+                .flow(Flow::Jump)
+                .control(Control::Call(function))
+        }
+        0x13 => {
+            let ty = reader.uleb32()?;
+            let table = reader.uleb32()?;
+            Insn::new("return_call_indirect")
+                .operands(format!("{} (type {})", table, ty))
+                .flow(Flow::Return)
+        }
+
+        0x1a => Insn::new("drop"),
+        0x1b => Insn::new("select"),
+        0x1c => {
+            let mut operands = String::new();
+            for _ in 0..reader.uleb()? {
+                if !operands.is_empty() {
+                    operands.push(' ');
+                }
+                operands.push_str(value_type(reader.u8()?)?);
+            }
+            Insn::new("select").operands(format!("(result {})", operands))
+        }
+
+        0x20..=0x26 => {
+            let mnemonic = [
+                "local.get",
+                "local.set",
+                "local.tee",
+                "global.get",
+                "global.set",
+                "table.get",
+                "table.set",
+            ][(opcode - 0x20) as usize];
+            Insn::new(mnemonic).operands(reader.uleb32()?.to_string())
+        }
+
+        0x28..=0x3e => {
+            let mnemonic = MEMORY_MNEMONICS[(opcode - 0x28) as usize];
+            Insn::new(mnemonic).operands(memarg(reader)?)
+        }
+        0x3f => {
+            reader.uleb()?;
+            Insn::new("memory.size")
+        }
+        0x40 => {
+            reader.uleb()?;
+            Insn::new("memory.grow")
+        }
+
+        0x41 => Insn::new("i32.const").operands((reader.sleb()? as i32).to_string()),
+        0x42 => Insn::new("i64.const").operands(reader.sleb()?.to_string()),
+        0x43 => {
+            let mut bytes = [0; 4];
+            bytes.copy_from_slice(reader.bytes(4)?);
+            Insn::new("f32.const").operands(f32::from_le_bytes(bytes).to_string())
+        }
+        0x44 => {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(reader.bytes(8)?);
+            Insn::new("f64.const").operands(f64::from_le_bytes(bytes).to_string())
+        }
+
+        0x45..=0xc4 => Insn::new(NUMERIC_MNEMONICS[(opcode - 0x45) as usize]),
+
+        0xd0 => Insn::new("ref.null").operands(value_type(reader.u8()?)?.into()),
+        0xd1 => Insn::new("ref.is_null"),
+        0xd2 => Insn::new("ref.func").operands(reader.uleb32()?.to_string()),
+
+        0xfc => decode_misc(reader)?,
+        0xfd => decode_simd(reader)?,
+        0xfe => decode_atomic(reader)?,
+
+        _ => return None,
+    };
+    Some(insn)
+}
+
+/// Decodes the saturating truncation, bulk memory and table instructions.
+fn decode_misc(reader: &mut Reader) -> Option<Insn> {
+    let insn = match reader.uleb32()? {
+        op @ 0..=7 => Insn::new(
+            [
+                "i32.trunc_sat_f32_s",
+                "i32.trunc_sat_f32_u",
+                "i32.trunc_sat_f64_s",
+                "i32.trunc_sat_f64_u",
+                "i64.trunc_sat_f32_s",
+                "i64.trunc_sat_f32_u",
+                "i64.trunc_sat_f64_s",
+                "i64.trunc_sat_f64_u",
+            ][op as usize],
+        ),
+        8 => {
+            let data = reader.uleb32()?;
+            reader.uleb()?;
+            Insn::new("memory.init").operands(data.to_string())
+        }
+        9 => Insn::new("data.drop").operands(reader.uleb32()?.to_string()),
+        10 => {
+            reader.uleb()?;
+            reader.uleb()?;
+            Insn::new("memory.copy")
+        }
+        11 => {
+            reader.uleb()?;
+            Insn::new("memory.fill")
+        }
+        12 => {
+            let elem = reader.uleb32()?;
+            let table = reader.uleb32()?;
+            Insn::new("table.init").operands(format!("{} {}", table, elem))
+        }
+        13 => Insn::new("elem.drop").operands(reader.uleb32()?.to_string()),
+        14 => {
+            let dst = reader.uleb32()?;
+            let src = reader.uleb32()?;
+            Insn::new("table.copy").operands(format!("{} {}", dst, src))
+        }
+        op @ 15..=17 => Insn::new(["table.grow", "table.size", "table.fill"][(op - 15) as usize])
+            .operands(reader.uleb32()?.to_string()),
+        _ => return None,
+    };
+    Some(insn)
+}
+
+/// Decodes the 128-bit SIMD instructions. There are too many of these to name individually
+/// so only their immediate operands are decoded.
+fn decode_simd(reader: &mut Reader) -> Option<Insn> {
+    let op = reader.uleb32()?;
+    let mut operands = format!("0x{:x}", op);
+    match op {
+        // Loads and stores.
+        0..=11 | 92 | 93 => write!(operands, " {}", memarg(reader)?).ok()?,
+        // v128.const and i8x16.shuffle
+        12 | 13 => {
+            for byte in reader.bytes(16)? {
+                write!(operands, " {}", byte).ok()?;
+            }
+        }
+        // Extracting and replacing lanes.
+        21..=34 => write!(operands, " {}", reader.u8()?).ok()?,
+        // Loading and storing lanes.
+        84..=91 => {
+            let memarg = memarg(reader)?;
+            write!(operands, " {} {}", memarg, reader.u8()?).ok()?
+        }
+        _ => {}
+    }
+    Some(Insn::new("simd").operands(operands))
+}
+
+/// Decodes the threads proposal's atomic memory instructions.
+fn decode_atomic(reader: &mut Reader) -> Option<Insn> {
+    let op = reader.uleb32()?;
+    if op == 3 {
+        reader.u8()?;
+        return Some(Insn::new("atomic.fence"));
+    }
+    Some(Insn::new("atomic").operands(format!("0x{:x} {}", op, memarg(reader)?)))
+}
+
+fn block_type(reader: &mut Reader) -> Option<String> {
+    let ty = reader.sleb()?;
+    Some(match ty {
+        // The empty block type.
+        -0x40 => String::new(),
+        ty if ty < 0 => format!("(result {})", value_type((ty & 0x7f) as u8)?),
+        ty => format!("(type {})", ty),
+    })
+}
+
+fn value_type(ty: u8) -> Option<&'static str> {
+    Some(match ty {
+        0x7f => "i32",
+        0x7e => "i64",
+        0x7d => "f32",
+        0x7c => "f64",
+        0x7b => "v128",
+        0x70 => "funcref",
+        0x6f => "externref",
+        _ => return None,
+    })
+}
+
+fn memarg(reader: &mut Reader) -> Option<String> {
+    let align = reader.uleb32()?;
+    let offset = reader.uleb()?;
+    let align = 1u64.checked_shl(align)?;
+    if offset == 0 {
+        Some(format!("align={}", align))
+    } else {
+        Some(format!("offset={} align={}", offset, align))
+    }
+}
+
+const MEMORY_MNEMONICS: &[&str] = &[
+    "i32.load",
+    "i64.load",
+    "f32.load",
+    "f64.load",
+    "i32.load8_s",
+    "i32.load8_u",
+    "i32.load16_s",
+    "i32.load16_u",
+    "i64.load8_s",
+    "i64.load8_u",
+    "i64.load16_s",
+    "i64.load16_u",
+    "i64.load32_s",
+    "i64.load32_u",
+    "i32.store",
+    "i64.store",
+    "f32.store",
+    "f64.store",
+    "i32.store8",
+    "i32.store16",
+    "i64.store8",
+    "i64.store16",
+    "i64.store32",
+];
+
+/// The mnemonics for the opcodes from 0x45 to 0xc4, which don't have immediate operands.
+const NUMERIC_MNEMONICS: &[&str] = &[
+    "i32.eqz",
+    "i32.eq",
+    "i32.ne",
+    "i32.lt_s",
+    "i32.lt_u",
+    "i32.gt_s",
+    "i32.gt_u",
+    "i32.le_s",
+    "i32.le_u",
+    "i32.ge_s",
+    "i32.ge_u",
+    "i64.eqz",
+    "i64.eq",
+    "i64.ne",
+    "i64.lt_s",
+    "i64.lt_u",
+    "i64.gt_s",
+    "i64.gt_u",
+    "i64.le_s",
+    "i64.le_u",
+    "i64.ge_s",
+    "i64.ge_u",
+    "f32.eq",
+    "f32.ne",
+    "f32.lt",
+    "f32.gt",
+    "f32.le",
+    "f32.ge",
+    "f64.eq",
+    "f64.ne",
+    "f64.lt",
+    "f64.gt",
+    "f64.le",
+    "f64.ge",
+    "i32.clz",
+    "i32.ctz",
+    "i32.popcnt",
+    "i32.add",
+    "i32.sub",
+    "i32.mul",
+    "i32.div_s",
+    "i32.div_u",
+    "i32.rem_s",
+    "i32.rem_u",
+    "i32.and",
+    "i32.or",
+    "i32.xor",
+    "i32.shl",
+    "i32.shr_s",
+    "i32.shr_u",
+    "i32.rotl",
+    "i32.rotr",
+    "i64.clz",
+    "i64.ctz",
+    "i64.popcnt",
+    "i64.add",
+    "i64.sub",
+    "i64.mul",
+    "i64.div_s",
+    "i64.div_u",
+    "i64.rem_s",
+    "i64.rem_u",
+    "i64.and",
+    "i64.or",
+    "i64.xor",
+    "i64.shl",
+    "i64.shr_s",
+    "i64.shr_u",
+    "i64.rotl",
+    "i64.rotr",
+    "f32.abs",
+    "f32.neg",
+    "f32.ceil",
+    "f32.floor",
+    "f32.trunc",
+    "f32.nearest",
+    "f32.sqrt",
+    "f32.add",
+    "f32.sub",
+    "f32.mul",
+    "f32.div",
+    "f32.min",
+    "f32.max",
+    "f32.copysign",
+    "f64.abs",
+    "f64.neg",
+    "f64.ceil",
+    "f64.floor",
+    "f64.trunc",
+    "f64.nearest",
+    "f64.sqrt",
+    "f64.add",
+    "f64.sub",
+    "f64.mul",
+    "f64.div",
+    "f64.min",
+    "f64.max",
+    "f64.copysign",
+    "i32.wrap_i64",
+    "i32.trunc_f32_s",
+    "i32.trunc_f32_u",
+    "i32.trunc_f64_s",
+    "i32.trunc_f64_u",
+    "i64.extend_i32_s",
+    "i64.extend_i32_u",
+    "i64.trunc_f32_s",
+    "i64.trunc_f32_u",
+    "i64.trunc_f64_s",
+    "i64.trunc_f64_u",
+    "f32.convert_i32_s",
+    "f32.convert_i32_u",
+    "f32.convert_i64_s",
+    "f32.convert_i64_u",
+    "f32.demote_f64",
+    "f64.convert_i32_s",
+    "f64.convert_i32_u",
+    "f64.convert_i64_s",
+    "f64.convert_i64_u",
+    "f64.promote_f32",
+    "i32.reinterpret_f32",
+    "i64.reinterpret_f64",
+    "f32.reinterpret_i32",
+    "f64.reinterpret_i64",
+    "i32.extend8_s",
+    "i32.extend16_s",
+    "i64.extend8_s",
+    "i64.extend16_s",
+    "i64.extend32_s",
+];
@@ -0,0 +1,40 @@
+//! Disassembly of the functions in executables, libraries and object files, with the
+//! symbols and source lines from their debug information.
+//!
+//! ```no_run
+//! use cargo_disasm::Binary;
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let binary = Binary::open("target/release/my_binary")?;
+//! let symbol = binary
+//!     .fuzzy_find_symbol("my_binary::main")
+//!     .expect("symbol not found");
+//! for line in binary.disassemble(symbol)?.lines() {
+//!     println!("{:x}  {} {}", line.address(), line.mnemonic(), line.operands());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#[macro_use]
+mod util;
+mod app;
+mod disasm;
+
+pub use disasm::binary::{Arch, Binary, Os};
+pub use disasm::symbol::{Symbol, SymbolLang, SymbolSource, SymbolType};
+pub use disasm::symbol_table::{SymbolOrder, SymbolQuery, SymbolTable};
+pub use disasm::{DisasmLine, Disassembly, Engine, Flow, Jump, Options, Syntax};
+
+/// Runs the `cargo disasm` command and returns whether it succeeded. This is the entry
+/// point of the binary and not a part of the library.
+#[doc(hidden)]
+pub fn run_command() -> bool {
+    log::set_logger(app::logging::AppLogger::init()).expect("failed to set logger");
+    let result = app::run();
+    if let Err(ref err) = result {
+        log::error!("{:?}", err);
+    }
+    log::logger().flush();
+    result.is_ok()
+}
@@ -35,3 +35,34 @@ where
         std::cmp::Ordering::Equal
     }
 }
+
+/// The directory that per-user cached data should be stored in.
+pub fn user_cache_dir() -> Option<std::path::PathBuf> {
+    use std::path::PathBuf;
+
+    if let Some(path) = std::env::var_os("XDG_CACHE_HOME") {
+        return Some(PathBuf::from(path));
+    }
+    if cfg!(windows) {
+        return std::env::var_os("LOCALAPPDATA").map(PathBuf::from);
+    }
+    let home = PathBuf::from(std::env::var_os("HOME")?);
+    if cfg!(target_os = "macos") {
+        Some(home.join("Library").join("Caches"))
+    } else {
+        Some(home.join(".cache"))
+    }
+}
+
+/// The directory that per-user configuration files should be stored in.
+pub fn user_config_dir() -> Option<std::path::PathBuf> {
+    use std::path::PathBuf;
+
+    if let Some(path) = std::env::var_os("XDG_CONFIG_HOME") {
+        return Some(PathBuf::from(path));
+    }
+    if cfg!(windows) {
+        return std::env::var_os("APPDATA").map(PathBuf::from);
+    }
+    Some(PathBuf::from(std::env::var_os("HOME")?).join(".config"))
+}